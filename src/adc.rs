@@ -0,0 +1,265 @@
+//! Decoders of the packed 24-bit sample formats of ECG analog front-ends, e.g. the ADS1292 and
+//! ADS1298.
+//!
+//! These front-ends shift out frames of an optional status word followed by one 24-bit two's
+//! complement sample per channel, most significant byte first. [`PackedLayout`] describes such a
+//! frame and decodes it into a [`PackedFrame`], with the samples sign-extended into `i32`.
+//!
+//! The samples are ADC codes. Configure the detector with [`Config::with_calibration`] to work in
+//! millivolts, or combine multiple channels using the [`leads`] front-ends.
+//!
+//! ```rust
+//! use qrs_detector::adc::PackedLayout;
+//! use qrs_detector::prelude::*;
+//!
+//! // ADS1292: 24-bit status word, 2 channels
+//! let layout = PackedLayout::<2>::new().with_status_bytes(3);
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//!
+//! # let dma_buffer = [0xC0, 0x00, 0x00, 0xFF, 0xFF, 0xFE, 0x00, 0x01, 0x00];
+//! for frame in layout.frames(&dma_buffer) {
+//!     let [_respiration, ecg] = frame.samples;
+//!     detector.update(ecg as f32);
+//! }
+//! # assert_eq!(9, layout.frame_size());
+//! ```
+//!
+//! [`Config::with_calibration`]: crate::config::Config::with_calibration
+//! [`leads`]: crate::leads
+
+use core::slice::ChunksExact;
+
+/// The smallest value of a 24-bit sample. Front-ends output it when the input is below their
+/// range.
+pub const I24_MIN: i32 = -(1 << 23);
+
+/// The largest value of a 24-bit sample. Front-ends output it when the input is above their
+/// range.
+pub const I24_MAX: i32 = (1 << 23) - 1;
+
+/// The order of the bytes of the status word and the samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Most significant byte first, as shifted out by most front-ends.
+    #[default]
+    BigEndian,
+    /// Least significant byte first, e.g. after a 32-bit little-endian DMA transfer was
+    /// repacked.
+    LittleEndian,
+}
+
+/// Decodes a 24-bit two's complement sample.
+pub const fn i24_from_bytes(bytes: [u8; 3], order: ByteOrder) -> i32 {
+    let [a, b, c] = bytes;
+    let raw = match order {
+        ByteOrder::BigEndian => i32::from_be_bytes([a, b, c, 0]),
+        ByteOrder::LittleEndian => i32::from_be_bytes([c, b, a, 0]),
+    };
+    // The arithmetic shift sign-extends the sample
+    raw >> 8
+}
+
+/// Encodes a 24-bit two's complement sample. Values outside [`I24_MIN`]..=[`I24_MAX`] are
+/// saturated.
+pub const fn i24_to_bytes(value: i32, order: ByteOrder) -> [u8; 3] {
+    let value = if value < I24_MIN {
+        I24_MIN
+    } else if value > I24_MAX {
+        I24_MAX
+    } else {
+        value
+    };
+    let [_, a, b, c] = value.to_be_bytes();
+    match order {
+        ByteOrder::BigEndian => [a, b, c],
+        ByteOrder::LittleEndian => [c, b, a],
+    }
+}
+
+/// An error returned when decoding a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameError {
+    /// The length of the data does not match the frame size of the layout.
+    Length {
+        /// The size of a frame, in bytes.
+        expected: usize,
+        /// The length of the data.
+        actual: usize,
+    },
+}
+
+impl core::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Length { expected, actual } => {
+                write!(f, "frame is {actual} bytes long, expected {expected}")
+            }
+        }
+    }
+}
+
+/// A decoded frame of `C` channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedFrame<const C: usize> {
+    /// The status word, 0 if the layout has none. The bytes are combined in the byte order of
+    /// the layout.
+    pub status: u32,
+    /// The sign-extended samples of the channels.
+    pub samples: [i32; C],
+}
+
+impl<const C: usize> PackedFrame<C> {
+    /// Returns the samples as `f32`, e.g. to feed a [`ComplexLead`](crate::leads::ComplexLead).
+    pub fn to_f32(&self) -> [f32; C] {
+        self.samples.map(|sample| sample as f32)
+    }
+
+    /// Returns whether any of the samples is at the limit of the 24-bit range.
+    pub fn is_clipped(&self) -> bool {
+        self.samples
+            .iter()
+            .any(|&sample| sample == I24_MIN || sample == I24_MAX)
+    }
+}
+
+/// The layout of a frame of `C` packed 24-bit channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedLayout<const C: usize> {
+    status_bytes: usize,
+    order: ByteOrder,
+}
+
+impl<const C: usize> Default for PackedLayout<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const C: usize> PackedLayout<C> {
+    /// Creates a big-endian layout without a status word.
+    pub const fn new() -> Self {
+        Self {
+            status_bytes: 0,
+            order: ByteOrder::BigEndian,
+        }
+    }
+
+    /// Sets the length of the status word preceding the samples, e.g. 3 for the ADS129x family.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is larger than 4.
+    pub const fn with_status_bytes(mut self, bytes: usize) -> Self {
+        assert!(bytes <= 4, "The status word must fit in 4 bytes");
+        self.status_bytes = bytes;
+        self
+    }
+
+    /// Sets the byte order of the status word and the samples.
+    pub const fn with_byte_order(mut self, order: ByteOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Returns the size of a frame, in bytes.
+    pub const fn frame_size(&self) -> usize {
+        self.status_bytes + 3 * C
+    }
+
+    /// Decodes a frame. `bytes` must be exactly one frame long.
+    pub fn decode(&self, bytes: &[u8]) -> Result<PackedFrame<C>, FrameError> {
+        if bytes.len() != self.frame_size() {
+            return Err(FrameError::Length {
+                expected: self.frame_size(),
+                actual: bytes.len(),
+            });
+        }
+
+        let (status, samples) = bytes.split_at(self.status_bytes);
+        let status = match self.order {
+            ByteOrder::BigEndian => status
+                .iter()
+                .fold(0, |word, &byte| (word << 8) | byte as u32),
+            ByteOrder::LittleEndian => status
+                .iter()
+                .rev()
+                .fold(0, |word, &byte| (word << 8) | byte as u32),
+        };
+
+        let mut frame = PackedFrame {
+            status,
+            samples: [0; C],
+        };
+        for (sample, bytes) in frame.samples.iter_mut().zip(samples.chunks_exact(3)) {
+            *sample = i24_from_bytes([bytes[0], bytes[1], bytes[2]], self.order);
+        }
+
+        Ok(frame)
+    }
+
+    /// Encodes a frame into `out`, which must be exactly one frame long. Only the low
+    /// `status_bytes` bytes of the status word are encoded. Useful to feed recorded signals into
+    /// firmware under test.
+    pub fn encode(&self, frame: &PackedFrame<C>, out: &mut [u8]) -> Result<(), FrameError> {
+        if out.len() != self.frame_size() {
+            return Err(FrameError::Length {
+                expected: self.frame_size(),
+                actual: out.len(),
+            });
+        }
+
+        let (status, samples) = out.split_at_mut(self.status_bytes);
+        let word = frame.status.to_le_bytes();
+        for (idx, byte) in status.iter_mut().enumerate() {
+            *byte = match self.order {
+                ByteOrder::BigEndian => word[self.status_bytes - 1 - idx],
+                ByteOrder::LittleEndian => word[idx],
+            };
+        }
+        for (&sample, bytes) in frame.samples.iter().zip(samples.chunks_exact_mut(3)) {
+            bytes.copy_from_slice(&i24_to_bytes(sample, self.order));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an iterator decoding the consecutive frames of `bytes`, e.g. a DMA buffer. An
+    /// incomplete frame at the end is not decoded, see [`Frames::remainder`].
+    pub fn frames<'a>(&self, bytes: &'a [u8]) -> Frames<'a, C> {
+        Frames {
+            layout: *self,
+            chunks: bytes.chunks_exact(self.frame_size().max(1)),
+        }
+    }
+}
+
+/// An iterator over the frames of a buffer, see [`PackedLayout::frames`].
+#[derive(Clone, Debug)]
+pub struct Frames<'a, const C: usize> {
+    layout: PackedLayout<C>,
+    chunks: ChunksExact<'a, u8>,
+}
+
+impl<'a, const C: usize> Frames<'a, C> {
+    /// Returns the bytes of the incomplete frame at the end of the buffer, which should be
+    /// prepended to the next buffer.
+    pub fn remainder(&self) -> &'a [u8] {
+        self.chunks.remainder()
+    }
+}
+
+impl<const C: usize> Iterator for Frames<'_, C> {
+    type Item = PackedFrame<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        // The chunks are exactly one frame long
+        self.layout.decode(chunk).ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<const C: usize> ExactSizeIterator for Frames<'_, C> {}
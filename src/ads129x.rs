@@ -0,0 +1,261 @@
+//! Integration of the ADS129x family of ECG analog front-ends.
+//!
+//! The ADS129x devices precede the samples of every frame with a 24-bit status word, which
+//! reports the lead-off state of the electrodes and the GPIO pins. [`Ads129xStatus`] parses it,
+//! and [`Ads129xDetector`] combines decoding, lead-off and saturation handling and detection, so
+//! that the firmware only needs to pass the frames it reads from the device:
+//!
+//! ```rust
+//! use qrs_detector::ads129x::{Ads129xDetector, StatusFormat};
+//! use qrs_detector::prelude::*;
+//!
+//! // ADS1292R: respiration on channel 1, ECG on channel 2
+//! let detector = QrsDetector::new::<150, 25>(500.sps());
+//! let mut frontend = Ads129xDetector::<2, _, _, _, _, _, _, _, _>::new(
+//!     detector,
+//!     StatusFormat::Ads1292,
+//!     1,
+//! );
+//!
+//! # let frames = [[0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10]; 10];
+//! for frame in frames {
+//!     if let Ok(Some(beat)) = frontend.push_frame(&frame) {
+//!         // Handle the detection
+//!     }
+//! }
+//! # assert!(!frontend.is_lead_off());
+//! ```
+
+use crate::{
+    adc::{FrameError, PackedLayout},
+    component::ThresholdComponent,
+    noise::NoiseEstimator,
+    transform::SampleTransform,
+    Detection, QrsDetector, Suppressed,
+};
+
+/// The time after which the detector is reset when the electrodes reconnect, in milliseconds.
+const DEFAULT_RESET_AFTER_MS: f32 = 2000.0;
+
+/// The layout of the status word, which differs between the members of the family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusFormat {
+    /// ADS1291 and ADS1292(R): `1100`, LOFF_STAT\[4:0\], GPIO\[1:0\], followed by zeros.
+    Ads1292,
+    /// ADS1294, ADS1296 and ADS1298(R): `1100`, LOFF_STATP\[7:0\], LOFF_STATN\[7:0\],
+    /// GPIO\[4:7\].
+    Ads1298,
+}
+
+/// The parsed status word of an ADS129x frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ads129xStatus {
+    /// The lead-off state of the positive electrodes. Bit `n` is set if the electrode of
+    /// channel `n` is off.
+    pub lead_off_positive: u8,
+    /// The lead-off state of the negative electrodes. Bit `n` is set if the electrode of
+    /// channel `n` is off.
+    pub lead_off_negative: u8,
+    /// Whether the right leg drive electrode is off. Only reported by [`StatusFormat::Ads1292`].
+    pub rld_off: bool,
+    /// The state of the GPIO pins, GPIO1 in bit 0.
+    pub gpio: u8,
+}
+
+impl Ads129xStatus {
+    /// Parses a status word. Returns `None` if the word does not start with the `1100` sync
+    /// pattern, e.g. because the frame is misaligned.
+    pub const fn parse(word: u32, format: StatusFormat) -> Option<Self> {
+        if (word >> 20) & 0xF != 0b1100 {
+            return None;
+        }
+
+        let status = match format {
+            StatusFormat::Ads1292 => {
+                let loff = (word >> 15) as u8 & 0x1F;
+                Self {
+                    lead_off_positive: (loff & 0b0001) | ((loff >> 1) & 0b0010),
+                    lead_off_negative: ((loff >> 1) & 0b0001) | ((loff >> 2) & 0b0010),
+                    rld_off: loff & 0b1_0000 != 0,
+                    gpio: (word >> 13) as u8 & 0b11,
+                }
+            }
+            StatusFormat::Ads1298 => Self {
+                lead_off_positive: (word >> 12) as u8,
+                lead_off_negative: (word >> 4) as u8,
+                rld_off: false,
+                gpio: word as u8 & 0xF,
+            },
+        };
+
+        Some(status)
+    }
+
+    /// Returns whether any electrode of `channel` (0-based), or the right leg drive electrode,
+    /// is off.
+    pub const fn is_lead_off(&self, channel: usize) -> bool {
+        let mask = 1u8.checked_shl(channel as u32);
+        let mask = match mask {
+            Some(mask) => mask,
+            None => 0,
+        };
+        self.rld_off || (self.lead_off_positive | self.lead_off_negative) & mask != 0
+    }
+}
+
+/// An error returned by [`Ads129xDetector::push_frame`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ads129xError {
+    /// The frame could not be decoded.
+    Frame(FrameError),
+    /// The status word does not start with the sync pattern. The frame is treated as a lost
+    /// sample.
+    Sync(u32),
+}
+
+impl From<FrameError> for Ads129xError {
+    fn from(error: FrameError) -> Self {
+        Self::Frame(error)
+    }
+}
+
+impl core::fmt::Display for Ads129xError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Frame(error) => write!(f, "{error}"),
+            Self::Sync(word) => write!(f, "invalid status word {word:06X}"),
+        }
+    }
+}
+
+/// Feeds the frames of an ADS129x device with `C` channels to a detector.
+///
+/// For every frame, [`Ads129xDetector::push_frame`]:
+///
+/// - decodes the status word and the samples,
+/// - skips the sample while an electrode of the ECG channel is off, notifying the detector of
+///   the gap (see [`QrsDetector::notify_gap`]), and resets the detector if the electrodes were
+///   off for longer than 2 seconds (see [`Ads129xDetector::with_reset_after`]),
+/// - reports amplifier saturation when the ECG sample is at the limit of the 24-bit range (see
+///   [`QrsDetector::set_saturated`], `quality` feature),
+/// - processes the sample and returns the detection.
+///
+/// The samples are ADC codes. Configure the detector with
+/// [`Config::with_calibration`](crate::config::Config::with_calibration) to work in millivolts.
+pub struct Ads129xDetector<const C: usize, FMW, FB, H, T, S, N, FC, RC> {
+    detector: QrsDetector<FMW, FB, H, T, S, N, FC, RC>,
+    layout: PackedLayout<C>,
+    format: StatusFormat,
+    channel: usize,
+    status: Option<Ads129xStatus>,
+    /// The number of consecutive frames with the ECG channel off.
+    lead_off_samples: u32,
+    reset_after: u32,
+}
+
+impl<const C: usize, FMW, FB, H, T, S, N, FC, RC> Ads129xDetector<C, FMW, FB, H, T, S, N, FC, RC>
+where
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    T: SampleTransform,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    N: NoiseEstimator,
+    FC: ThresholdComponent,
+    RC: ThresholdComponent,
+{
+    /// Creates a front-end feeding the `channel` (0-based) of the frames to `detector`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not less than `C`.
+    pub fn new(
+        detector: QrsDetector<FMW, FB, H, T, S, N, FC, RC>,
+        format: StatusFormat,
+        channel: usize,
+    ) -> Self {
+        assert!(channel < C, "The ECG channel must be one of the channels");
+        let reset_after = detector
+            .sampling_frequency()
+            .ms_to_samples(DEFAULT_RESET_AFTER_MS) as u32;
+        Self {
+            detector,
+            layout: PackedLayout::new().with_status_bytes(3),
+            format,
+            channel,
+            status: None,
+            lead_off_samples: 0,
+            reset_after,
+        }
+    }
+
+    /// Sets how long the electrodes may be off before the detector is reset, in milliseconds.
+    /// Shorter episodes are handled as gaps, and the detector keeps its thresholds.
+    pub fn with_reset_after(mut self, ms: f32) -> Self {
+        self.reset_after = self.detector.sampling_frequency().ms_to_samples(ms) as u32;
+        self
+    }
+
+    /// Returns the detector.
+    pub fn detector(&self) -> &QrsDetector<FMW, FB, H, T, S, N, FC, RC> {
+        &self.detector
+    }
+
+    /// Returns the detector, e.g. to apply a configuration.
+    pub fn detector_mut(&mut self) -> &mut QrsDetector<FMW, FB, H, T, S, N, FC, RC> {
+        &mut self.detector
+    }
+
+    /// Returns the detector, consuming the front-end.
+    pub fn into_inner(self) -> QrsDetector<FMW, FB, H, T, S, N, FC, RC> {
+        self.detector
+    }
+
+    /// Returns the status word of the last valid frame.
+    pub fn status(&self) -> Option<Ads129xStatus> {
+        self.status
+    }
+
+    /// Returns whether an electrode of the ECG channel is off, according to the last valid
+    /// frame.
+    pub fn is_lead_off(&self) -> bool {
+        self.lead_off_samples > 0
+    }
+
+    /// Returns the number of consecutive frames for which an electrode of the ECG channel has
+    /// been off.
+    pub fn lead_off_samples(&self) -> u32 {
+        self.lead_off_samples
+    }
+
+    /// Processes a frame read from the device. Returns Some sample index if a QRS complex is
+    /// detected, see [`QrsDetector::update`].
+    ///
+    /// A frame with an invalid status word is treated as a lost sample.
+    pub fn push_frame(&mut self, bytes: &[u8]) -> Result<Option<u32>, Ads129xError> {
+        let frame = self.layout.decode(bytes)?;
+        let Some(status) = Ads129xStatus::parse(frame.status, self.format) else {
+            self.detector.notify_gap(1);
+            return Err(Ads129xError::Sync(frame.status));
+        };
+        self.status = Some(status);
+
+        if status.is_lead_off(self.channel) {
+            self.lead_off_samples = self.lead_off_samples.saturating_add(1);
+            self.detector.notify_gap(1);
+            return Ok(None);
+        }
+
+        if core::mem::take(&mut self.lead_off_samples) > self.reset_after {
+            // The thresholds adapted before the electrodes came off are no longer valid
+            self.detector.clear();
+        }
+
+        #[cfg(feature = "quality")]
+        {
+            let sample = frame.samples[self.channel];
+            let clipped = sample == crate::adc::I24_MIN || sample == crate::adc::I24_MAX;
+            self.detector.set_saturated(clipped);
+        }
+
+        Ok(self.detector.update(frame.samples[self.channel] as f32))
+    }
+}
@@ -0,0 +1,237 @@
+//! Alarm management on top of the rhythm and signal [`Event`]s.
+//!
+//! The detector and the signal quality monitors report events as they happen. [`AlarmManager`]
+//! turns them into alarms that a monitoring device can annunciate:
+//!
+//! - every event has a [`Priority`], which can be overridden per event,
+//! - an alarm stays active for a hold time after its last event, so that intermittent events
+//!   don't toggle it,
+//! - alarms with a high enough priority latch: they stay active until acknowledged, even if their
+//!   events stop,
+//! - the active alarms can be silenced for a while, or acknowledged. Alarms raised later are
+//!   audible again.
+//!
+//! Events that must persist before they raise an alarm can be debounced first, see
+//! [`EventDebouncer`](crate::events::EventDebouncer).
+//!
+//! Time is provided by the caller, in milliseconds since an arbitrary epoch, e.g. using
+//! [`EpochAnchor`](crate::time::EpochAnchor).
+//!
+//! ```rust
+//! use qrs_detector::alarms::{AlarmManager, Priority};
+//! use qrs_detector::events::Event;
+//!
+//! let mut alarms = AlarmManager::new();
+//!
+//! alarms.raise(Event::VentricularFibrillation, 1_000);
+//! assert_eq!(Some(Priority::High), alarms.audible());
+//!
+//! // The alarm is latched, and stays active after its events stop
+//! alarms.update(60_000);
+//! assert_eq!(Some(Priority::High), alarms.audible());
+//!
+//! // The alarm remains visible after it is acknowledged, until its hold time expires
+//! alarms.acknowledge();
+//! assert_eq!(None, alarms.audible());
+//! assert!(alarms.highest().is_some());
+//!
+//! alarms.update(60_001);
+//! assert_eq!(None, alarms.highest());
+//! ```
+
+use core::time::Duration;
+
+use crate::events::Event;
+
+/// The urgency of an alarm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Priority {
+    /// Technical alarms, e.g. signal artifacts.
+    Low,
+    /// Rhythm abnormalities that need attention.
+    Medium,
+    /// Life-threatening rhythms, see [`Event::is_critical`].
+    High,
+}
+
+impl Priority {
+    /// Returns the default priority of `event`.
+    pub fn of(event: Event) -> Self {
+        match event {
+            Event::Asystole | Event::VentricularFibrillation => Priority::High,
+            Event::Pause | Event::Bradycardia | Event::Tachycardia => Priority::Medium,
+            Event::SignalStep | Event::RefractoryTimeout => Priority::Low,
+        }
+    }
+}
+
+/// An active alarm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alarm {
+    /// The event that raised the alarm.
+    pub event: Event,
+    /// The priority of the alarm.
+    pub priority: Priority,
+    /// The time of the first event, in milliseconds.
+    pub raised_ms: u64,
+    /// The time of the last event, in milliseconds.
+    pub last_ms: u64,
+    /// The alarm stays active until acknowledged.
+    pub latched: bool,
+    /// The alarm has been acknowledged, and is not audible.
+    pub acknowledged: bool,
+    /// The alarm is silenced, see [`AlarmManager::silence`].
+    pub silenced: bool,
+}
+
+impl Alarm {
+    /// Returns `true` if the alarm should be annunciated audibly.
+    pub fn is_audible(&self) -> bool {
+        !self.acknowledged && !self.silenced
+    }
+}
+
+/// Tracks the alarms raised by events. See the [module documentation](self).
+#[derive(Clone, Copy, Debug)]
+pub struct AlarmManager {
+    priorities: [Priority; Event::ALL.len()],
+    latching: Option<Priority>,
+    hold: Duration,
+    silence: Duration,
+    silenced_until: Option<u64>,
+    alarms: [Option<Alarm>; Event::ALL.len()],
+}
+
+impl Default for AlarmManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlarmManager {
+    /// Creates a new alarm manager. By default, high priority alarms latch, alarms are held for
+    /// 10 seconds and silenced for 2 minutes.
+    pub fn new() -> Self {
+        Self {
+            priorities: Event::ALL.map(Priority::of),
+            latching: Some(Priority::High),
+            hold: Duration::from_secs(10),
+            silence: Duration::from_secs(120),
+            silenced_until: None,
+            alarms: [None; Event::ALL.len()],
+        }
+    }
+
+    /// Sets the priority of the alarms raised by `event`.
+    pub fn with_priority(mut self, event: Event, priority: Priority) -> Self {
+        self.priorities[event.index()] = priority;
+        self
+    }
+
+    /// Sets the lowest priority of latching alarms. `None` disables latching.
+    pub fn with_latching(mut self, priority: Option<Priority>) -> Self {
+        self.latching = priority;
+        self
+    }
+
+    /// Sets how long an alarm stays active after its last event, unless latched.
+    pub fn with_hold(mut self, hold: Duration) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Sets how long [`AlarmManager::silence`] silences the active alarms.
+    pub fn with_silence(mut self, silence: Duration) -> Self {
+        self.silence = silence;
+        self
+    }
+
+    /// Returns the priority of the alarms raised by `event`.
+    pub fn priority(&self, event: Event) -> Priority {
+        self.priorities[event.index()]
+    }
+
+    /// Raises or refreshes the alarm of `event`, which happened at `now_ms`.
+    ///
+    /// An alarm raised again after it has been cleared is audible, even if the previous one was
+    /// acknowledged or silenced.
+    pub fn raise(&mut self, event: Event, now_ms: u64) {
+        let priority = self.priority(event);
+        let latched = self.latching.is_some_and(|latching| priority >= latching);
+
+        let alarm = self.alarms[event.index()].get_or_insert(Alarm {
+            event,
+            priority,
+            raised_ms: now_ms,
+            last_ms: now_ms,
+            latched,
+            acknowledged: false,
+            silenced: false,
+        });
+        alarm.last_ms = now_ms;
+        alarm.latched |= latched && !alarm.acknowledged;
+    }
+
+    /// Clears the alarms whose hold time expired, and ends the silence period at `now_ms`.
+    pub fn update(&mut self, now_ms: u64) {
+        let hold = self.hold.as_millis() as u64;
+        for slot in &mut self.alarms {
+            if slot.is_some_and(|alarm| !alarm.latched && now_ms > alarm.last_ms + hold) {
+                *slot = None;
+            }
+        }
+
+        if self.silenced_until.is_some_and(|until| now_ms >= until) {
+            self.silenced_until = None;
+            for alarm in self.alarms.iter_mut().flatten() {
+                alarm.silenced = false;
+            }
+        }
+    }
+
+    /// Silences the active alarms for the silence period, starting at `now_ms`. The alarms stay
+    /// active.
+    pub fn silence(&mut self, now_ms: u64) {
+        self.silenced_until = Some(now_ms + self.silence.as_millis() as u64);
+        for alarm in self.alarms.iter_mut().flatten() {
+            alarm.silenced = true;
+        }
+    }
+
+    /// Acknowledges the active alarms. They are no longer audible or latched, and are cleared
+    /// when their hold time expires.
+    pub fn acknowledge(&mut self) {
+        for alarm in self.alarms.iter_mut().flatten() {
+            alarm.acknowledged = true;
+            alarm.latched = false;
+        }
+    }
+
+    /// Clears every alarm, and ends the silence period.
+    pub fn clear(&mut self) {
+        self.alarms = [None; Event::ALL.len()];
+        self.silenced_until = None;
+    }
+
+    /// Returns the active alarms.
+    pub fn alarms(&self) -> impl Iterator<Item = Alarm> + '_ {
+        self.alarms.iter().flatten().copied()
+    }
+
+    /// Returns the active alarm with the highest priority. Of alarms with equal priority, the most
+    /// recently raised one is returned.
+    pub fn highest(&self) -> Option<Alarm> {
+        self.alarms()
+            .max_by_key(|alarm| (alarm.priority, alarm.raised_ms))
+    }
+
+    /// Returns the highest priority of the audible alarms, or `None` if no alarm is audible.
+    pub fn audible(&self) -> Option<Priority> {
+        self.alarms()
+            .filter(Alarm::is_audible)
+            .map(|alarm| alarm.priority)
+            .max()
+    }
+}
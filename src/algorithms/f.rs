@@ -1,12 +1,27 @@
-use crate::{sampling::*, sliding::SlidingWindow};
+use crate::{
+    component::{ComponentInput, ThresholdComponent},
+    config::{Config, Parameters},
+    sampling::*,
+    sliding::{SlidingWindow, WindowMax},
+    BufferUsage, Corruption,
+};
 
 #[derive(Copy, Clone, Debug)]
 enum FState {
     Ignore(usize),
     Init(usize, f32),
-    Integrate(f32),
+    /// Running F value and the Kahan compensation term of the accumulated increments.
+    Integrate(f32, f32),
+    /// Initialization using `f64` accumulation.
+    WideInit(usize, f64),
+    /// Running F value, using `f64` accumulation.
+    WideIntegrate(f64),
 }
 
+/// The integrating threshold.
+///
+/// F follows the maxima of the signal in 50ms windows over the last 300ms, so that high
+/// frequency noise raises the threshold.
 pub struct F<FMW, FB> {
     fs: SamplingFrequency,
 
@@ -18,7 +33,10 @@ pub struct F<FMW, FB> {
     f_max_window: SlidingWindow<f32, FMW>,
 
     /// 50ms window of the signal
-    f_buffer: SlidingWindow<f32, FB>,
+    f_buffer: WindowMax<FB>,
+
+    /// Accumulate using `f64` values.
+    wide: bool,
 }
 
 impl<FMW, FB> F<FMW, FB>
@@ -26,11 +44,13 @@ where
     FMW: AsRef<[f32]> + AsMut<[f32]>,
     FB: AsRef<[f32]> + AsMut<[f32]>,
 {
-    pub fn new(
-        fs: SamplingFrequency,
-        f_max_window: SlidingWindow<f32, FMW>,
-        f_buffer: SlidingWindow<f32, FB>,
-    ) -> Self {
+    /// Creates a new F component for signals sampled with `fs`.
+    ///
+    /// `f_max_window` must hold 300ms worth of samples, `f_buffer` 50ms worth of samples.
+    pub fn new(fs: SamplingFrequency, f_max_window: FMW, f_buffer: FB) -> Self {
+        let f_max_window = SlidingWindow::new(f_max_window);
+        let f_buffer = WindowMax::new(f_buffer);
+
         // sanity check buffer sizes
         debug_assert_eq!(
             f_max_window.capacity(),
@@ -50,24 +70,51 @@ where
             state: FState::Ignore(fs.s_to_samples(2.65)),
             f_max_window,
             f_buffer,
+            wide: false,
         }
     }
 
+    /// Applies the configuration. Takes effect when F is next initialized.
+    pub fn configure(&mut self, config: &Config) {
+        self.wide = config.f64_accumulation();
+    }
+
+    /// Returns the capacity and fill level of the buffer of 50ms maxima over the last 300ms.
+    pub fn max_window_usage(&self) -> BufferUsage {
+        BufferUsage::new(self.f_max_window.capacity(), self.f_max_window.len())
+    }
+
+    /// Returns the capacity and fill level of the 50ms signal buffer.
+    pub fn buffer_usage(&self) -> BufferUsage {
+        BufferUsage::new(self.f_buffer.capacity(), self.f_buffer.len())
+    }
+
+    /// Restarts the initialization, including the initial ignore period.
     pub fn clear(&mut self) {
         self.state = FState::Ignore(self.fs.s_to_samples(2.65));
         self.f_max_window.clear();
         self.f_buffer.clear();
     }
 
-    fn update_f_buffers(&mut self, sample: f32) -> (Option<f32>, f32) {
-        // TODO: there are some special cases where the max search can be skipped
-        self.f_buffer.push(sample);
+    /// Restarts the initialization without waiting for the initial ignore period.
+    pub fn readapt(&mut self) {
+        self.state = self.init_state();
+        self.f_max_window.clear();
+        self.f_buffer.clear();
+    }
 
+    fn init_state(&self) -> FState {
+        let samples = self.f_max_window.capacity() - 1;
+        if self.wide {
+            FState::WideInit(samples, 0.0)
+        } else {
+            FState::Init(samples, 0.0)
+        }
+    }
+
+    fn update_f_buffers(&mut self, sample: f32) -> (Option<f32>, f32) {
         // Calculate maximum value in the latest 50ms window
-        let max: f32 = self
-            .f_buffer
-            .iter_unordered()
-            .fold(0.0, |acc, x| acc.max(x));
+        let max = self.f_buffer.push(sample);
 
         // Keep the 50ms maximum values for each sample in latest 300ms window
         // The oldest sample corresponds to the oldest 50ms in the latest 350ms window
@@ -77,32 +124,121 @@ where
         (old, max)
     }
 
+    /// Processes a sample.
     pub fn update(&mut self, sample: f32) {
         self.state = match self.state {
-            FState::Ignore(1) => FState::Init(self.f_max_window.capacity() - 1, 0.0),
+            FState::Ignore(1) => self.init_state(),
             FState::Ignore(n) => FState::Ignore(n - 1),
             FState::Init(n, favg) => {
                 let favg = favg + sample;
                 self.update_f_buffers(sample);
 
                 if n == 0 {
-                    FState::Integrate(favg.max(0.0) / (self.f_max_window.capacity() as f32))
+                    FState::Integrate(favg.max(0.0) / (self.f_max_window.capacity() as f32), 0.0)
                 } else {
                     FState::Init(n - 1, favg)
                 }
             }
-            FState::Integrate(f) => {
+            FState::Integrate(f, compensation) => {
+                let (oldest_max, max) = self.update_f_buffers(sample);
+
+                // F is updated with tiny increments over hours of signal. Use compensated
+                // summation to keep the rounding error from accumulating.
+                let increment = (max - oldest_max.unwrap_or(0.0)) / 150.0 - compensation;
+                let sum = f + increment;
+                let compensation = (sum - f) - increment;
+
+                if sum > 0.0 {
+                    FState::Integrate(sum, compensation)
+                } else {
+                    FState::Integrate(0.0, 0.0)
+                }
+            }
+            FState::WideInit(n, favg) => {
+                let favg = favg + sample as f64;
+                self.update_f_buffers(sample);
+
+                if n == 0 {
+                    FState::WideIntegrate(favg.max(0.0) / self.f_max_window.capacity() as f64)
+                } else {
+                    FState::WideInit(n - 1, favg)
+                }
+            }
+            FState::WideIntegrate(f) => {
                 let (oldest_max, max) = self.update_f_buffers(sample);
-                FState::Integrate((f + (max - oldest_max.unwrap_or(0.0)) / 150.0).max(0.0))
+
+                let increment = (max as f64 - oldest_max.unwrap_or(0.0) as f64) / 150.0;
+                FState::WideIntegrate((f + increment).max(0.0))
             }
         };
     }
 
+    /// Returns the current value of F. `None` during the initialization.
     pub fn threshold(&self) -> Option<f32> {
-        if let FState::Integrate(f) = self.state {
-            Some(f)
-        } else {
-            None
+        match self.state {
+            FState::Integrate(f, _) => Some(f),
+            FState::WideIntegrate(f) => Some(f as f32),
+            _ => None,
         }
     }
+
+    /// Validates the internal invariants, see [`QrsDetector::verify`](crate::QrsDetector::verify).
+    pub fn verify(&self) -> Result<(), Corruption> {
+        match self.state {
+            FState::Ignore(n) if n == 0 || n > self.fs.s_to_samples(2.65) => {
+                Err(Corruption::InvalidState)
+            }
+            FState::Init(n, _) if n >= self.f_max_window.capacity() => {
+                Err(Corruption::InvalidState)
+            }
+            FState::WideInit(n, _) if n >= self.f_max_window.capacity() => {
+                Err(Corruption::InvalidState)
+            }
+            FState::Init(_, favg) if !favg.is_finite() => Err(Corruption::NonFiniteThreshold),
+            FState::WideInit(_, favg) if !favg.is_finite() => Err(Corruption::NonFiniteThreshold),
+            FState::WideIntegrate(f) if !f.is_finite() => Err(Corruption::NonFiniteThreshold),
+            FState::Integrate(f, compensation) if !(f + compensation).is_finite() => {
+                Err(Corruption::NonFiniteThreshold)
+            }
+            FState::Integrate(_, _) | FState::WideIntegrate(_) if !self.f_max_window.is_full() => {
+                Err(Corruption::BufferNotFull)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<FMW, FB> ThresholdComponent for F<FMW, FB>
+where
+    FMW: AsRef<[f32]> + AsMut<[f32]>,
+    FB: AsRef<[f32]> + AsMut<[f32]>,
+{
+    fn update(&mut self, input: &ComponentInput) {
+        F::update(self, input.sample);
+    }
+
+    fn threshold(&self) -> Option<f32> {
+        F::threshold(self)
+    }
+
+    fn clear(&mut self) {
+        F::clear(self);
+    }
+
+    fn readapt(&mut self) {
+        F::readapt(self);
+    }
+
+    fn configure(&mut self, config: &Config) {
+        F::configure(self, config);
+    }
+
+    fn reconfigure(&mut self, config: &Config, _changed: Parameters) -> Parameters {
+        F::configure(self, config);
+        Parameters::empty()
+    }
+
+    fn verify(&self, _index: u32) -> Result<(), Corruption> {
+        F::verify(self)
+    }
 }
@@ -1,43 +1,289 @@
-use crate::{sampling::*, sliding::SlidingWindow};
+use crate::{
+    component::{ComponentInput, ThresholdComponent},
+    config::{Config, MDecay, Parameter, Parameters, MAX_MM_DEPTH},
+    sampling::*,
+    sliding::SlidingWindow,
+    BufferUsage, Corruption,
+};
+
+/// Hard limit on the duration of the refractory period, including extensions.
+const MAX_DISALLOW_S: f32 = 1.0;
+
+/// A new maximum during the refractory period extends it to at least this fraction of the
+/// refractory period [200 ms of 225 ms by default].
+const REFRACTORY_EXTENSION: f32 = 0.2 / 0.225;
+
+/// The remaining difference to the target value at the end of an exponential decrease is
+/// `e^-EXP_DECAY_RATE`.
+const EXP_DECAY_RATE: f32 = 4.0;
+
+/// The initialization factor of M is interpolated between these values, based on the
+/// signal-to-noise ratio.
+const INIT_FACTOR_CLEAN: (f32, f32) = (20.0, 0.5); // SNR, factor
+const INIT_FACTOR_NOISY: (f32, f32) = (5.0, 0.7);
+
+/// M does not decrease below the noise level multiplied by this value.
+const NOISE_FLOOR_RATIO: f32 = 2.0;
+
+/// Backing storage of the MM buffer, with a depth selected at runtime.
+#[derive(Clone)]
+struct MmBuffer {
+    values: [f32; MAX_MM_DEPTH],
+    depth: usize,
+}
+
+impl AsRef<[f32]> for MmBuffer {
+    fn as_ref(&self) -> &[f32] {
+        &self.values[..self.depth]
+    }
+}
+
+impl AsMut<[f32]> for MmBuffer {
+    fn as_mut(&mut self) -> &mut [f32] {
+        &mut self.values[..self.depth]
+    }
+}
 
 #[derive(Copy, Clone, Debug)]
 enum MState {
     Init(usize, f32),
-    Disallow(usize, f32),
-    Decreasing(usize, f32, f32),
+    Disallow(usize, f32, usize), // samples remaining, max value, samples remaining until forced end
+    Decreasing(usize, f32, f32), // samples remaining, value, decrement or decay factor
     ConstantLow(f32),
 }
 
+/// The steep-slope threshold.
+///
+/// M is refreshed from the peak of every detected QRS complex, and decreases slowly until the
+/// next detection. Detections are not allowed during the refractory period following one.
 pub struct M {
     state: MState,
-    mm: SlidingWindow<f32, [f32; 5]>,
+    mm: SlidingWindow<f32, MmBuffer>,
     fs: SamplingFrequency,
+    decay: MDecay,
+    /// The refractory period, in samples.
+    refractory: usize,
+    /// The minimum remaining refractory period after a new maximum, in samples.
+    refractory_extension: usize,
+    /// The value M decreases to.
+    decay_target: f32,
+    /// The rate M decreases at, per sample.
     pub current_decrement: f32,
+    /// Set when the refractory period was forcibly ended by the watchdog.
+    pub refractory_timeout: bool,
+    /// The estimated noise level, if the thresholds adapt to the signal-to-noise ratio.
+    pub noise_level: Option<f32>,
+    /// Sum the MM values using `f64` accumulation.
+    wide: bool,
+    /// MM values saved before an amplifier saturation episode.
+    #[cfg(feature = "quality")]
+    checkpoint: Option<SlidingWindow<f32, MmBuffer>>,
 }
 
 impl M {
-    pub fn new(fs: SamplingFrequency) -> Self {
+    /// Creates a new M component for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency, config: &Config) -> Self {
         Self {
             fs,
-            mm: SlidingWindow::default(),
+            mm: SlidingWindow::new(MmBuffer {
+                values: [0.0; MAX_MM_DEPTH],
+                depth: config.mm_depth(),
+            }),
+            decay: config.m_decay(),
+            refractory: fs.ms_to_samples(config.refractory_ms()),
+            refractory_extension: fs.ms_to_samples(config.refractory_ms() * REFRACTORY_EXTENSION),
+            decay_target: 0.0,
             // Initially M = 0.6*max(Y) is set for the first 3 s [originally 5s] of the signal
             state: MState::Init(fs.s_to_samples(3.0), 0.0),
             current_decrement: 0.0,
+            refractory_timeout: false,
+            noise_level: None,
+            wide: config.f64_accumulation(),
+            #[cfg(feature = "quality")]
+            checkpoint: None,
         }
     }
 
+    /// Restarts the initial 3 s initialization.
     pub fn clear(&mut self) {
         self.mm.clear();
         self.state = MState::Init(self.fs.s_to_samples(3.0), 0.0);
         self.current_decrement = 0.0;
+        self.refractory_timeout = false;
+        self.noise_level = None;
+        #[cfg(feature = "quality")]
+        {
+            self.checkpoint = None;
+        }
+    }
+
+    /// Restarts the initialization using a shorter, 1 s period.
+    #[cfg(feature = "quality")]
+    pub fn readapt(&mut self) {
+        self.mm.clear();
+        self.state = MState::Init(self.fs.s_to_samples(1.0), 0.0);
+        self.current_decrement = 0.0;
+    }
+
+    /// Saves the MM buffer so that values recorded during a saturation episode can be discarded.
+    #[cfg(feature = "quality")]
+    pub fn checkpoint(&mut self) {
+        self.checkpoint = match self.state {
+            MState::Init(_, _) => None,
+            _ => Some(self.mm.clone()),
+        };
+    }
+
+    /// Restores the MM buffer saved by [`M::checkpoint`].
+    #[cfg(feature = "quality")]
+    pub fn rollback(&mut self) {
+        match self.checkpoint.take() {
+            Some(mm) => {
+                self.mm = mm;
+                self.state = self.decrease(self.m());
+            }
+            // The saturation started during initialization, the collected maximum is invalid.
+            None => self.readapt(),
+        }
+    }
+
+    /// Presets MM using the typical peak value of the signal.
+    pub fn warm_start(&mut self, peak: f32) {
+        let m = 0.6 * peak;
+
+        self.mm.clear();
+        for _ in 0..self.mm.capacity() {
+            self.mm.push(m / self.depth());
+        }
+        self.state = self.decrease(m);
+    }
+
+    /// Returns the MM values, from the oldest to the newest, once M is initialized.
+    pub fn mm_values(&self) -> impl Iterator<Item = f32> + '_ {
+        let initialized = !matches!(self.state, MState::Init(_, _));
+        self.mm.iter().filter(move |_| initialized)
+    }
+
+    /// Restores the MM values returned by [`M::mm_values`]. M is initialized again if the number
+    /// of values does not match the depth of MM.
+    pub fn restore(&mut self, mm: &[f32]) {
+        self.clear();
+        if mm.len() != self.mm.capacity() {
+            return;
+        }
+
+        for &m in mm {
+            self.mm.push(m);
+        }
+        self.state = self.decrease(self.m());
+    }
+
+    /// Applies `config` without discarding the adapted threshold. Returns the changed parameters
+    /// that required resetting M.
+    ///
+    /// A new MM depth resets the MM buffer, which is preset from the previous peak average. A new
+    /// decay restarts the decrease of M from the MM average.
+    pub fn reconfigure(&mut self, config: &Config, changed: Parameters) -> Parameters {
+        if changed.contains(Parameter::MmDepth) {
+            let peak = self.peak_average();
+            *self = M::new(self.fs, config);
+            if let Some(peak) = peak {
+                self.warm_start(peak);
+            }
+            return Parameters::of(&[Parameter::MmDepth]);
+        }
+
+        self.decay = config.m_decay();
+        self.refractory = self.fs.ms_to_samples(config.refractory_ms());
+        self.refractory_extension = self
+            .fs
+            .ms_to_samples(config.refractory_ms() * REFRACTORY_EXTENSION);
+        self.wide = config.f64_accumulation();
+        if let MState::Disallow(samples, m, limit) = self.state {
+            // A shorter refractory period takes effect in the ongoing one
+            self.state = MState::Disallow(samples.min(self.refractory), m, limit);
+        }
+        if changed.contains(Parameter::MDecay) && matches!(self.state, MState::Decreasing(..)) {
+            self.state = self.decrease(self.m());
+        }
+        Parameters::empty()
+    }
+
+    /// Returns the depth and fill level of the MM buffer.
+    pub fn mm_usage(&self) -> BufferUsage {
+        BufferUsage::new(self.mm.capacity(), self.mm.len())
+    }
+
+    /// Returns the length of the refractory period, in samples.
+    pub fn refractory_samples(&self) -> usize {
+        self.refractory
+    }
+
+    /// Returns the length of the interval M decreases in, in samples. A duration shorter than a
+    /// sample decreases M in a single sample.
+    pub fn decay_samples(&self) -> usize {
+        self.fs.ms_to_samples(self.decay.duration_ms()).max(1)
+    }
+
+    /// Returns the average peak value M was refreshed from, once M is initialized.
+    pub fn peak_average(&self) -> Option<f32> {
+        match self.state {
+            MState::Init(_, _) => None,
+            _ => Some(self.m() / 0.6),
+        }
+    }
+
+    fn depth(&self) -> f32 {
+        self.mm.capacity() as f32
     }
 
     fn m(&self) -> f32 {
         // M is calculated as an average value of MM.
-        // Divide by 5 was done while calculating the individual Mx values
-        self.mm.iter_unordered().sum()
+        // Divide by the buffer depth was done while calculating the individual Mx values
+        if self.wide {
+            self.mm.iter_unordered().map(f64::from).sum::<f64>() as f32
+        } else {
+            self.mm.iter_unordered().sum()
+        }
+    }
+
+    fn end_disallow(&mut self, m: f32, sample: f32) -> MState {
+        // In the interval QRS ÷ QRS+200ms a new value of M5 is calculated:
+        // newM 5 = 0.6*max(Yi)
+        let m = 0.6 * m.max(sample) / self.depth(); // divide by depth for averaging
+
+        // The estimated newM 5 value can become quite high, if steep slope premature
+        // ventricular contraction or artifact appeared, and for that reason it is
+        // limited to newM5 = 1.1* M5 if newM 5 > 1.5* M5.
+        let prev_m = self.mm.last().unwrap_or(0.0);
+        if m > prev_m * 1.5 {
+            self.mm.push(1.1 * prev_m);
+        } else {
+            self.mm.push(m);
+        }
+
+        self.decrease(self.m())
     }
 
+    fn decrease(&mut self, m: f32) -> MState {
+        // M is decreased in an interval 225 to 1225 ms [originally 200 to 1200 ms]
+        // following the last QRS detection at a low slope, reaching 60 % of its
+        // refreshed value at 1225 ms [originally 1200 ms].
+        // [The shape, amount and duration of the decrease are configurable.]
+        let n_samples = self.decay_samples();
+        let decrement = m * self.decay.fraction() / n_samples as f32;
+        self.current_decrement = decrement;
+        self.decay_target = m * (1.0 - self.decay.fraction());
+
+        let step = match self.decay {
+            MDecay::Linear { .. } => decrement,
+            MDecay::Exponential { .. } => (1.0 - EXP_DECAY_RATE / n_samples as f32).max(0.0),
+        };
+
+        MState::Decreasing(n_samples, m, step)
+    }
+
+    /// Processes a sample.
     pub fn update(&mut self, sample: f32) {
         self.state = match self.state {
             MState::Init(0, m) => {
@@ -45,92 +291,172 @@ impl M {
                 // A buffer with 5 steep-slope threshold values is preset:
                 // MM = [M1 M2 M3 M4 M5],
                 // where M1 ÷ M5 are equal to M
-                let m = 0.6 * m.max(sample);
+                // [The number of values is configurable, 5 by default.]
+                // [The factor may adapt to the signal-to-noise ratio.]
+                let max = m.max(sample);
+                let m = self.init_factor(max) * max;
 
-                for _ in 0..5 {
-                    self.mm.push(m / 5.0);
+                for _ in 0..self.mm.capacity() {
+                    self.mm.push(m / self.depth());
                 }
 
                 // It is not clear in the article what to do initially:
                 // - wait for a QRS detection and then start the M algorithm with Disallow state
                 // - decrease using "low slope" immediately
                 // This implementation uses the second option
-
-                // M is decreased in an interval 225 to 1225 ms [originally 200 to 1200 ms]
-                // following the last QRS detection at a low slope, reaching 60 % of its
-                // refreshed value at 1225 ms [originally 1200 ms].
-                let n_samples = self.fs.s_to_samples(1.0);
-                let decrement = m * 0.4 / n_samples as f32;
-                self.current_decrement = decrement;
-
-                MState::Decreasing(n_samples, m, decrement)
+                self.decrease(m)
             }
 
             // Initially M = 0.6*max(Y) is set for the first 3 s [originally 5s] of the signal
             // Collect maximum value while in Init state
             MState::Init(samples, m) => MState::Init(samples - 1, m.max(sample)),
 
-            MState::Disallow(0, m) => {
-                // In the interval QRS ÷ QRS+200ms a new value of M5 is calculated:
-                // newM 5 = 0.6*max(Yi)
-                let m = 0.6 * m.max(sample) / 5.0; // divide by 5 for averaging
-
-                // The estimated newM 5 value can become quite high, if steep slope premature
-                // ventricular contraction or artifact appeared, and for that reason it is
-                // limited to newM5 = 1.1* M5 if newM 5 > 1.5* M5.
-                let prev_m = self.mm.last().unwrap_or(0.0);
-                if m > prev_m * 1.5 {
-                    self.mm.push(1.1 * prev_m);
-                } else {
-                    self.mm.push(m);
-                }
-
-                let m = self.m();
-
-                // M is decreased in an interval 225 to 1225 ms [originally 200 to 1200 ms]
-                // following the last QRS detection at a low slope, reaching 60 % of its
-                // refreshed value at 1225 ms [originally 1200 ms].
-                let n_samples = self.fs.s_to_samples(1.0);
-                let decrement = m * 0.4 / n_samples as f32;
-                self.current_decrement = decrement;
-
-                MState::Decreasing(n_samples, m, decrement)
+            MState::Disallow(0, m, _) => self.end_disallow(m, sample),
+            MState::Disallow(_, m, 0) => {
+                // A continuously rising signal would extend the refractory period indefinitely.
+                // Only flag the timeout if the limit cuts an extended refractory period short.
+                self.refractory_timeout = true;
+                self.end_disallow(m, sample)
             }
 
             // In the interval QRS ÷ QRS+200ms a new value of M5 is calculated:
             // newM 5 = 0.6*max(Yi)
             // Collect maximum value while in Disallow state
-            MState::Disallow(samples, m) if sample > m => {
+            MState::Disallow(samples, m, limit) if sample > m => {
                 // if we found a new maximum, extend the disallow period
-                MState::Disallow(samples.max(self.fs.s_to_samples(0.2)), sample)
+                MState::Disallow(samples.max(self.refractory_extension), sample, limit - 1)
             }
-            MState::Disallow(samples, m) => MState::Disallow(samples - 1, m),
+            MState::Disallow(samples, m, limit) => MState::Disallow(samples - 1, m, limit - 1),
 
             // After 1225 ms [originally 1200 ms] M remains unchanged.
-            MState::Decreasing(0, m, _) => MState::ConstantLow(m),
+            MState::Decreasing(0, m, _) => match self.decay {
+                MDecay::Linear { .. } => MState::ConstantLow(m),
+                // The exponential decrease never reaches the target value, snap to it
+                MDecay::Exponential { .. } => MState::ConstantLow(self.decay_target),
+            },
 
             // M is decreased in an interval 225 to 1225 ms [originally 200 to 1200 ms]
             // following the last QRS detection at a low slope, reaching 60 % of its
             // refreshed value at 1225 ms [originally 1200 ms].
-            MState::Decreasing(samples, m, decrease_amount) => {
-                // Linear decrease using precomputed decrement value
-                MState::Decreasing(samples - 1, m - decrease_amount, decrease_amount)
-            }
+            MState::Decreasing(samples, m, step) => match self.decay {
+                MDecay::Linear { .. } => {
+                    // Linear decrease using precomputed decrement value
+                    MState::Decreasing(samples - 1, m - step, step)
+                }
+                MDecay::Exponential { .. } => {
+                    let m = self.decay_target + (m - self.decay_target) * step;
+                    MState::Decreasing(samples - 1, m, step)
+                }
+            },
 
             // After 1225 ms [originally 1200 ms] M remains unchanged.
             MState::ConstantLow(m) => MState::ConstantLow(m),
         };
     }
 
+    /// Returns the factor of the initial M value, relative to the maximum of the signal.
+    fn init_factor(&self, max: f32) -> f32 {
+        let Some(noise) = self.noise_level.filter(|noise| *noise > 0.0) else {
+            return 0.6;
+        };
+
+        let (clean_snr, clean_factor) = INIT_FACTOR_CLEAN;
+        let (noisy_snr, noisy_factor) = INIT_FACTOR_NOISY;
+        let t = ((max / noise - noisy_snr) / (clean_snr - noisy_snr)).clamp(0.0, 1.0);
+
+        noisy_factor + (clean_factor - noisy_factor) * t
+    }
+
+    /// Returns the current value of M. `None` during the initialization and the refractory
+    /// period.
     pub fn threshold(&self) -> Option<f32> {
         match self.state {
-            MState::Init(_, _) | MState::Disallow(_, _) => None,
-            MState::Decreasing(_, m, _) | MState::ConstantLow(m) => Some(m),
+            MState::Init(_, _) | MState::Disallow(_, _, _) => None,
+            MState::Decreasing(_, m, _) | MState::ConstantLow(m) => match self.noise_level {
+                // Not using `max`, so that a non-finite M is not hidden from `verify`
+                Some(noise) if m < NOISE_FLOOR_RATIO * noise => Some(NOISE_FLOOR_RATIO * noise),
+                _ => Some(m),
+            },
+        }
+    }
+
+    /// Validates the internal invariants, see [`QrsDetector::verify`](crate::QrsDetector::verify).
+    pub fn verify(&self) -> Result<(), Corruption> {
+        let (samples, max_samples, values) = match self.state {
+            MState::Init(n, m) => (n, self.fs.s_to_samples(3.0), [m, 0.0]),
+            MState::Disallow(_, _, limit) if limit > self.fs.s_to_samples(MAX_DISALLOW_S) => {
+                return Err(Corruption::InvalidState)
+            }
+            MState::Disallow(n, m, _) => (n, self.refractory, [m, 0.0]),
+            MState::Decreasing(n, m, d) => (n, self.decay_samples(), [m, d]),
+            MState::ConstantLow(m) => (0, 0, [m, 0.0]),
+        };
+
+        if !values.iter().all(|v| v.is_finite())
+            || !self.current_decrement.is_finite()
+            || !self.mm.iter_unordered().all(f32::is_finite)
+        {
+            return Err(Corruption::NonFiniteThreshold);
+        }
+        if samples > max_samples {
+            return Err(Corruption::InvalidState);
+        }
+        // MM is preset when the initialization period ends
+        if !matches!(self.state, MState::Init(_, _)) && !self.mm.is_full() {
+            return Err(Corruption::BufferNotFull);
         }
+
+        Ok(())
+    }
+
+    /// Returns `true` while detections are not allowed after the previous one.
+    pub fn in_refractory(&self) -> bool {
+        matches!(self.state, MState::Disallow(_, _, _))
     }
 
+    /// Starts the refractory period following a detection at `sample`.
     pub fn detection_event(&mut self, sample: f32) {
         // No detection is allowed 225 ms [originally 200 ms] after the current one.
-        self.state = MState::Disallow(self.fs.s_to_samples(0.225), sample);
+        // [The period is configurable.]
+        self.state = MState::Disallow(
+            self.refractory,
+            sample,
+            self.fs.s_to_samples(MAX_DISALLOW_S),
+        );
+    }
+}
+
+impl ThresholdComponent for M {
+    fn update(&mut self, input: &ComponentInput) {
+        M::update(self, input.sample);
+    }
+
+    fn threshold(&self) -> Option<f32> {
+        M::threshold(self)
+    }
+
+    fn detection_event(&mut self, _index: u32, sample: f32) {
+        M::detection_event(self, sample);
+    }
+
+    fn clear(&mut self) {
+        M::clear(self);
+    }
+
+    #[cfg(feature = "quality")]
+    fn readapt(&mut self) {
+        M::readapt(self);
+    }
+
+    fn configure(&mut self, config: &Config) {
+        *self = M::new(self.fs, config);
+    }
+
+    fn reconfigure(&mut self, config: &Config, changed: Parameters) -> Parameters {
+        M::reconfigure(self, config, changed)
+    }
+
+    fn verify(&self, _index: u32) -> Result<(), Corruption> {
+        M::verify(self)
     }
 }
@@ -1,7 +1,55 @@
+//! The threshold components of the detector.
+//!
+//! The detection threshold is the sum of three components, see [`Thresholds`]:
+//!
+//! - [`M`] - the steep-slope threshold, refreshed from the peak of every detected QRS complex.
+//! - [`F`] - the integrating threshold, following the high frequency noise of the signal.
+//! - [`R`] - the beat expectation threshold, decreasing as the next beat is expected.
+//!
+//! The components are exposed by the `internals` feature, to compose them differently or to
+//! test them in isolation. Their interfaces may change between minor versions. The components
+//! implement [`ThresholdComponent`], so that they can be combined with custom components.
+//!
+//! # Example
+//! ```rust
+//! # #[cfg(feature = "internals")] {
+//! use qrs_detector::algorithms::{F, M, R};
+//! use qrs_detector::prelude::*;
+//!
+//! let fs = 500.sps();
+//! let config = Config::new();
+//! let mut m = M::new(fs, &config);
+//! let mut f = F::new(fs, [0.0; 150], [0.0; 25]);
+//! let mut r = R::new(&config);
+//!
+//! # let signal = [0.0; 2000];
+//! for (idx, &sample) in signal.iter().enumerate() {
+//!     m.update(sample);
+//!     f.update(sample);
+//!     r.update(m.current_decrement, m.threshold());
+//!
+//!     let threshold = m.threshold().zip(f.threshold()).map(|(m, f)| m + f + r.threshold());
+//!     if threshold.is_some_and(|threshold| sample > threshold) && !m.in_refractory() {
+//!         m.detection_event(sample);
+//!         r.detection_event(idx as u32);
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! [`Thresholds`]: crate::Thresholds
+//! [`ThresholdComponent`]: crate::component::ThresholdComponent
+
 mod f;
 mod m;
+mod pace;
 mod r;
+mod skip;
+mod timing;
 
 pub use f::F;
 pub use m::M;
+pub(crate) use pace::PaceTracker;
 pub use r::R;
+pub(crate) use skip::SkipTracker;
+pub(crate) use timing::TimingTracker;
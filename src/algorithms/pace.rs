@@ -0,0 +1,45 @@
+/// Counts down the blanking period after a pacing pulse, and the window in which a detected beat
+/// is labeled as paced.
+pub struct PaceTracker {
+    /// The number of samples left to blank.
+    blanking: u32,
+    /// The number of samples left in which a detection is a paced beat.
+    capture: u32,
+}
+
+impl PaceTracker {
+    pub const fn new() -> Self {
+        Self {
+            blanking: 0,
+            capture: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Starts the blanking and capture periods of a pacing pulse, of which `elapsed` samples
+    /// have already passed.
+    pub fn pulse(&mut self, blanking: u32, capture: u32, elapsed: u32) {
+        self.blanking = blanking.saturating_sub(elapsed);
+        self.capture = capture.saturating_sub(elapsed);
+    }
+
+    /// Advances by one sample. Returns `true` if the sample is blanked.
+    pub fn update(&mut self) -> bool {
+        self.capture = self.capture.saturating_sub(1);
+        if self.blanking > 0 {
+            self.blanking -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the detection is the beat evoked by the last pulse. Only the first
+    /// detection after a pulse is labeled.
+    pub fn detection_event(&mut self) -> bool {
+        core::mem::take(&mut self.capture) > 0
+    }
+}
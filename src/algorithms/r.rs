@@ -1,4 +1,28 @@
-use crate::sliding::SlidingWindow;
+use crate::{
+    component::{ComponentInput, ThresholdComponent},
+    config::{Config, Parameter, Parameters, MAX_RR_DEPTH},
+    sliding::SlidingWindow,
+    BufferUsage, Corruption,
+};
+
+/// Backing storage of the RR interval buffer, with a depth selected at runtime.
+#[derive(Clone)]
+struct RrBuffer {
+    values: [u32; MAX_RR_DEPTH],
+    depth: usize,
+}
+
+impl AsRef<[u32]> for RrBuffer {
+    fn as_ref(&self) -> &[u32] {
+        &self.values[..self.depth]
+    }
+}
+
+impl AsMut<[u32]> for RrBuffer {
+    fn as_mut(&mut self) -> &mut [u32] {
+        &mut self.values[..self.depth]
+    }
+}
 
 #[derive(Copy, Clone, Debug)]
 enum RState {
@@ -6,57 +30,199 @@ enum RState {
     InitBuffer,
     NoDecrease(u32, u32),    // samples remaining, average rr interval
     Decrease(u32, f32, f32), // samples remaining, value, decrement
-    Constant(f32),
+    Constant(u32, f32),      // samples remaining until timeout, value
 }
 
+/// The beat expectation threshold.
+///
+/// R decreases the threshold when the next beat is expected, based on the average of the recent
+/// RR intervals. It does not change the threshold until enough RR intervals are collected.
 pub struct R {
     state: RState,
-    rr: SlidingWindow<u32, [u32; 5]>,
+    rr: SlidingWindow<u32, RrBuffer>,
     prev_idx: u32, // no need to make it an Option
+    weight: f32,
+    delay: f32,
+    timeout_factor: f32,
+    floor_factor: f32,
+    /// The lowest allowed value, relative to the current M threshold.
+    floor: f32,
+    /// Number of samples R remains constant for before returning to its initial state.
+    timeout: u32,
 }
 
 impl R {
-    pub fn new() -> Self {
+    /// Creates a new R component.
+    pub fn new(config: &Config) -> Self {
         Self {
             state: RState::Ignore,
-            rr: SlidingWindow::default(),
+            rr: SlidingWindow::new(RrBuffer {
+                values: [0; MAX_RR_DEPTH],
+                depth: config.rr_depth(),
+            }),
             prev_idx: 0,
+            weight: config.r_weight(),
+            delay: config.r_delay(),
+            timeout_factor: config.r_timeout(),
+            floor_factor: config.r_floor(),
+            floor: 0.0,
+            timeout: 0,
         }
     }
 
+    /// Discards the collected RR intervals.
     pub fn clear(&mut self) {
         self.state = RState::Ignore;
         self.rr.clear();
         self.prev_idx = 0;
+        self.floor = 0.0;
     }
 
-    fn enter_no_decrease(&mut self) {
+    /// Returns the average RR interval in samples, once enough intervals have been collected.
+    pub fn rr_average(&self) -> Option<u32> {
+        if self.rr.is_full() {
+            Some(self.average())
+        } else {
+            None
+        }
+    }
+
+    fn average(&self) -> u32 {
         let rr_sum: u32 = self.rr.iter_unordered().sum();
-        let rr_avg = rr_sum / 5;
-        self.state = RState::NoDecrease(rr_avg * 2 / 3, rr_avg);
+        rr_sum / self.rr.capacity() as u32
+    }
+
+    /// Returns the sample index of the last detection.
+    pub fn last_detection(&self) -> u32 {
+        self.prev_idx
+    }
+
+    /// Returns the collected RR intervals, from the oldest to the newest.
+    pub fn rr_intervals(&self) -> impl Iterator<Item = u32> + '_ {
+        self.rr.iter()
+    }
+
+    /// Returns the depth and fill level of the RR interval buffer.
+    pub fn rr_usage(&self) -> BufferUsage {
+        BufferUsage::new(self.rr.capacity(), self.rr.len())
+    }
+
+    /// Restores the RR intervals returned by [`R::rr_intervals`] and the index of the last
+    /// detection. R becomes active after two detections, like after [`R::warm_start`].
+    pub fn restore(&mut self, rr: &[u32], last_detection: u32) {
+        self.clear();
+        let skip = rr.len().saturating_sub(self.rr.capacity());
+        for &rr in &rr[skip..] {
+            self.rr.push(rr);
+        }
+        self.prev_idx = last_detection;
+    }
+
+    /// Applies `config` without discarding the collected RR intervals. Returns the changed
+    /// parameters that required resetting R.
+    ///
+    /// A new RR depth keeps the most recent intervals, and R becomes active after two detections,
+    /// like after [`R::restore`]. The other parameters take effect at the next detection.
+    pub fn reconfigure(&mut self, config: &Config, changed: Parameters) -> Parameters {
+        self.weight = config.r_weight();
+        self.delay = config.r_delay();
+        self.timeout_factor = config.r_timeout();
+        self.floor_factor = config.r_floor();
+        if !changed.contains(Parameter::RrDepth) {
+            return Parameters::empty();
+        }
+
+        let mut rr = [0; MAX_RR_DEPTH];
+        let len = self.rr.len();
+        for (slot, interval) in rr.iter_mut().zip(self.rr.iter()) {
+            *slot = interval;
+        }
+        let last_detection = self.prev_idx;
+
+        *self = R::new(config);
+        self.restore(&rr[..len], last_detection);
+        Parameters::of(&[Parameter::RrDepth])
+    }
+
+    /// Presets the RR interval buffer. R becomes active after two detections.
+    pub fn warm_start(&mut self, rr: u32) {
+        self.clear();
+        for _ in 0..self.rr.capacity() {
+            self.rr.push(rr);
+        }
     }
 
-    pub fn update(&mut self, m_decrement: f32) {
+    fn enter_no_decrease(&mut self) {
+        let rr_avg = self.average();
+        self.timeout = (rr_avg as f32 * (self.timeout_factor - 1.0)) as u32;
+        self.state = RState::NoDecrease((rr_avg as f32 * self.delay) as u32, rr_avg);
+    }
+
+    /// Processes a sample. `m_decrement` is the rate M decreases at, and `m` is the current value
+    /// of M, see [`M`](super::M).
+    pub fn update(&mut self, m_decrement: f32, m: Option<f32>) {
+        if let Some(m) = m {
+            self.floor = -self.floor_factor * m;
+        }
+
         self.state = match self.state {
-            RState::NoDecrease(0, rr_avg) => RState::Decrease(rr_avg / 3, 0.0, m_decrement / 1.4),
+            RState::NoDecrease(0, rr_avg) => RState::Decrease(
+                (rr_avg as f32 * (1.0 - self.delay)) as u32,
+                0.0,
+                m_decrement * self.weight,
+            ),
             RState::NoDecrease(samples, rr_avg) => RState::NoDecrease(samples - 1, rr_avg),
-            RState::Decrease(0, r, _) => RState::Constant(r),
+            RState::Decrease(0, r, _) => RState::Constant(self.timeout, r),
             RState::Decrease(samples, r, decrement) => {
                 RState::Decrease(samples - 1, r - decrement, decrement)
             }
+            RState::Constant(0, _) => {
+                // The rhythm paused, the collected RR intervals are no longer relevant
+                self.rr.clear();
+                RState::Ignore
+            }
+            RState::Constant(samples, r) => RState::Constant(samples - 1, r),
             o => o,
         };
     }
 
+    /// Returns the current value of R. The value is 0 or negative.
     pub fn threshold(&self) -> f32 {
         match self.state {
             RState::Ignore | RState::InitBuffer | RState::NoDecrease(_, _) => 0.0,
-            RState::Constant(r) | RState::Decrease(_, r, _) => r,
+            RState::Constant(_, r) | RState::Decrease(_, r, _) => r.max(self.floor),
         }
     }
 
+    /// Validates the internal invariants, see [`QrsDetector::verify`](crate::QrsDetector::verify).
+    /// `total_samples` is the index of the current sample.
+    pub fn verify(&self, total_samples: u32) -> Result<(), Corruption> {
+        // The sample counter wraps around. A last detection more than half of the counter range
+        // in the past is considered to be in the future.
+        let elapsed = total_samples.wrapping_sub(self.prev_idx);
+        if !matches!(self.state, RState::Ignore) && elapsed > u32::MAX / 2 {
+            return Err(Corruption::InvalidState);
+        }
+
+        match self.state {
+            RState::Decrease(_, r, decrement) if !(r.is_finite() && decrement.is_finite()) => {
+                Err(Corruption::NonFiniteThreshold)
+            }
+            RState::Constant(_, r) if !r.is_finite() => Err(Corruption::NonFiniteThreshold),
+            RState::NoDecrease(_, _) | RState::Decrease(_, _, _) | RState::Constant(_, _)
+                if !self.rr.is_full() =>
+            {
+                Err(Corruption::BufferNotFull)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records a detection at sample index `idx`.
     pub fn detection_event(&mut self, idx: u32) {
         match self.state {
+            // The first detection only marks the start of the first RR interval. Pushing
+            // `idx - prev_idx` here would measure the time since the start of the signal.
             RState::Ignore => self.state = RState::InitBuffer,
             RState::InitBuffer => {
                 self.rr.push(idx.wrapping_sub(self.prev_idx));
@@ -73,3 +239,33 @@ impl R {
         self.prev_idx = idx;
     }
 }
+
+impl ThresholdComponent for R {
+    fn update(&mut self, input: &ComponentInput) {
+        R::update(self, input.m_decrement, input.m);
+    }
+
+    fn threshold(&self) -> Option<f32> {
+        Some(R::threshold(self))
+    }
+
+    fn detection_event(&mut self, index: u32, _sample: f32) {
+        R::detection_event(self, index);
+    }
+
+    fn clear(&mut self) {
+        R::clear(self);
+    }
+
+    fn configure(&mut self, config: &Config) {
+        *self = R::new(config);
+    }
+
+    fn reconfigure(&mut self, config: &Config, changed: Parameters) -> Parameters {
+        R::reconfigure(self, config, changed)
+    }
+
+    fn verify(&self, index: u32) -> Result<(), Corruption> {
+        R::verify(self, index)
+    }
+}
@@ -0,0 +1,78 @@
+use crate::SkippedBeat;
+
+/// An RR interval is considered to skip a beat if it exceeds the running average this many times.
+const SKIP_RATIO: f32 = 1.75;
+
+/// Records the sample closest to the threshold between detections, and reports it when the RR
+/// interval suggests that a beat was missed.
+pub struct SkipTracker {
+    prev_detection: Option<u32>,
+    rr_average: Option<f32>,
+    /// Index, sample value and threshold of the closest approach in the current segment.
+    closest: Option<(u32, f32, f32)>,
+    pub skipped: Option<SkippedBeat>,
+    pub count: u32,
+}
+
+impl SkipTracker {
+    pub const fn new() -> Self {
+        Self {
+            prev_detection: None,
+            rr_average: None,
+            closest: None,
+            skipped: None,
+            count: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Ends the current segment without evaluating it, e.g. because samples were lost.
+    pub fn interrupt(&mut self) {
+        self.prev_detection = None;
+        self.closest = None;
+    }
+
+    /// Processes an undetected sample outside of the refractory period.
+    pub fn update(&mut self, idx: u32, sample: f32, threshold: f32) {
+        if self.prev_detection.is_none() {
+            return;
+        }
+
+        let closer = self.closest.is_none_or(|(_, closest, closest_threshold)| {
+            threshold - sample < closest_threshold - closest
+        });
+        if closer {
+            self.closest = Some((idx, sample, threshold));
+        }
+    }
+
+    pub fn detection_event(&mut self, idx: u32) {
+        if let Some(prev) = self.prev_detection {
+            let rr = idx.wrapping_sub(prev) as f32;
+            match self.rr_average {
+                Some(average) if rr > SKIP_RATIO * average => {
+                    // The long interval is kept out of the average, so that consecutive missed
+                    // beats are reported as well.
+                    if let Some((index, amplitude, threshold)) = self.closest {
+                        self.skipped = Some(SkippedBeat {
+                            start: prev,
+                            end: idx,
+                            index,
+                            amplitude,
+                            threshold,
+                        });
+                        self.count = self.count.wrapping_add(1);
+                    }
+                }
+                Some(average) => self.rr_average = Some(average + (rr - average) / 8.0),
+                None => self.rr_average = Some(rr),
+            }
+        }
+
+        self.prev_detection = Some(idx);
+        self.closest = None;
+    }
+}
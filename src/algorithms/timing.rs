@@ -0,0 +1,65 @@
+use crate::sampling::SamplingFrequency;
+
+/// The longest pulse a QRS complex is expected to cause, in milliseconds.
+const MAX_PULSE_MS: f32 = 200.0;
+
+/// A pulse of the detector signal being measured.
+struct Pulse {
+    threshold: f32,
+    peak: f32,
+    /// The number of samples above the threshold.
+    width: u32,
+}
+
+/// Estimates the dominant frequency of detected QRS complexes, from the width of the pulse they
+/// cause in the detector signal.
+pub struct TimingTracker {
+    max_width: u32,
+    pulse: Option<Pulse>,
+}
+
+impl TimingTracker {
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            max_width: fs.ms_to_samples(MAX_PULSE_MS) as u32,
+            pulse: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pulse = None;
+    }
+
+    /// Starts measuring the pulse that crossed `threshold` with `sample`.
+    pub fn detection_event(&mut self, sample: f32, threshold: f32) {
+        self.pulse = Some(Pulse {
+            threshold,
+            peak: sample,
+            width: 1,
+        });
+    }
+
+    /// Processes a sample after the detection. Returns the dominant frequency of the QRS complex,
+    /// relative to the sampling frequency, once the pulse has fallen below the threshold.
+    pub fn update(&mut self, sample: f32) -> Option<f32> {
+        let pulse = self.pulse.as_mut()?;
+        if sample > pulse.threshold {
+            pulse.peak = pulse.peak.max(sample);
+            pulse.width += 1;
+            if pulse.width > self.max_width {
+                // Not a QRS complex, e.g. a step in the signal
+                self.pulse = None;
+            }
+            return None;
+        }
+
+        let pulse = self.pulse.take()?;
+        // The pulse is modeled as half a period of a sine wave. Above `threshold / peak`, it lasts
+        // for `1 - 2 asin(threshold / peak) / pi` of the half period.
+        let level = (pulse.threshold / pulse.peak).clamp(0.0, 1.0);
+        let above = 1.0 - 2.0 * micromath::F32Ext::asin(level) / core::f32::consts::PI;
+        let frequency = above / (2.0 * pulse.width as f32);
+
+        Some(frequency.min(0.5))
+    }
+}
@@ -0,0 +1,138 @@
+//! Beat classes compatible with the ANSI/AAMI EC57 standard.
+//!
+//! EC57 groups the beat annotations of the MIT-BIH and other WFDB databases into five classes,
+//! see [`BeatClass`]. Paced beats, which EC57 counts as unknown beats, are kept in a class of
+//! their own, so that the detection of paced beats can be assessed separately. The
+//! [`validation`](crate::validation) module breaks the detection results down by these classes,
+//! and [`BeatCsvWriter`](crate::io::BeatCsvWriter) writes them into the beat files.
+//!
+//! The classes convert from and to the WFDB annotation mnemonics, e.g. `'N'` or `'V'`, and the
+//! numeric annotation codes of the WFDB library, e.g. `1` (`NORMAL`) or `5` (`PVC`):
+//!
+//! ```rust
+//! use qrs_detector::annotation::BeatClass;
+//!
+//! let classes = "NLAV/".chars().filter_map(BeatClass::from_wfdb_symbol);
+//! assert_eq!(
+//!     [
+//!         BeatClass::Normal,
+//!         BeatClass::Normal,
+//!         BeatClass::Supraventricular,
+//!         BeatClass::Ventricular,
+//!         BeatClass::Paced,
+//!     ],
+//!     classes.collect::<Vec<_>>()[..]
+//! );
+//!
+//! assert_eq!(Some(BeatClass::Ventricular), BeatClass::from_wfdb_code(5));
+//! assert_eq!('V', BeatClass::Ventricular.wfdb_symbol());
+//! assert_eq!('Q', BeatClass::Paced.ec57_symbol());
+//! ```
+
+use core::fmt;
+
+/// The class of a beat, as defined by ANSI/AAMI EC57.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BeatClass {
+    /// `N` - normal beats, including bundle branch block and escape beats.
+    Normal,
+    /// `S` - supraventricular ectopic beats: atrial, aberrated atrial, nodal and
+    /// supraventricular premature beats.
+    Supraventricular,
+    /// `V` - ventricular ectopic beats: premature ventricular contractions and ventricular escape
+    /// beats.
+    Ventricular,
+    /// `F` - fusions of ventricular and normal beats.
+    Fusion,
+    /// `Q` - unclassifiable beats.
+    Unknown,
+    /// Paced beats and fusions of paced and normal beats. EC57 counts them as unknown beats.
+    Paced,
+}
+
+impl BeatClass {
+    /// The classes, in the order of EC57, followed by paced beats.
+    pub const ALL: [Self; 6] = [
+        Self::Normal,
+        Self::Supraventricular,
+        Self::Ventricular,
+        Self::Fusion,
+        Self::Unknown,
+        Self::Paced,
+    ];
+
+    /// Returns the class of the beat with the given WFDB annotation mnemonic, or `None` if the
+    /// mnemonic does not annotate a beat.
+    pub const fn from_wfdb_symbol(symbol: char) -> Option<Self> {
+        match symbol {
+            'N' | 'L' | 'R' | 'B' | 'e' | 'j' | 'n' => Some(Self::Normal),
+            'A' | 'a' | 'J' | 'S' => Some(Self::Supraventricular),
+            'V' | 'E' | 'r' => Some(Self::Ventricular),
+            'F' => Some(Self::Fusion),
+            'Q' => Some(Self::Unknown),
+            '/' | 'f' => Some(Self::Paced),
+            _ => None,
+        }
+    }
+
+    /// Returns the class of the beat with the given numeric WFDB annotation code, or `None` if
+    /// the code does not annotate a beat.
+    pub const fn from_wfdb_code(code: u8) -> Option<Self> {
+        match code {
+            // NORMAL, LBBB, RBBB, NESC, BBB, AESC, SVESC
+            1 | 2 | 3 | 11 | 25 | 34 | 35 => Some(Self::Normal),
+            // ABERR, NPC, APC, SVPB
+            4 | 7 | 8 | 9 => Some(Self::Supraventricular),
+            // PVC, VESC, RONT
+            5 | 10 | 41 => Some(Self::Ventricular),
+            // FUSION
+            6 => Some(Self::Fusion),
+            // UNKNOWN
+            13 => Some(Self::Unknown),
+            // PACE, PFUS
+            12 | 38 => Some(Self::Paced),
+            _ => None,
+        }
+    }
+
+    /// Returns the WFDB annotation mnemonic of the class. Except for paced beats, annotated as
+    /// `/`, it is also the EC57 symbol of the class.
+    pub const fn wfdb_symbol(self) -> char {
+        match self {
+            Self::Normal => 'N',
+            Self::Supraventricular => 'S',
+            Self::Ventricular => 'V',
+            Self::Fusion => 'F',
+            Self::Unknown => 'Q',
+            Self::Paced => '/',
+        }
+    }
+
+    /// Returns the EC57 symbol of the class. Paced beats are unknown beats, `Q`.
+    pub const fn ec57_symbol(self) -> char {
+        match self {
+            Self::Paced => 'Q',
+            _ => self.wfdb_symbol(),
+        }
+    }
+
+    /// Returns the numeric WFDB annotation code of [`BeatClass::wfdb_symbol`].
+    pub const fn wfdb_code(self) -> u8 {
+        match self {
+            Self::Normal => 1,
+            Self::Supraventricular => 9,
+            Self::Ventricular => 5,
+            Self::Fusion => 6,
+            Self::Unknown => 13,
+            Self::Paced => 12,
+        }
+    }
+}
+
+/// Formats the class as its WFDB annotation mnemonic, e.g. `N`, see [`BeatClass::wfdb_symbol`].
+impl fmt::Display for BeatClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.wfdb_symbol())
+    }
+}
@@ -0,0 +1,171 @@
+//! Compact binary encoding of detected beats, for long-term logging into flash memory.
+//!
+//! Every beat is stored as:
+//!
+//! - the difference between its RR interval and the previous RR interval, in samples, as a
+//!   zigzag-encoded LEB128 variable length integer. The RR interval of the first beat is its
+//!   sample index.
+//! - a byte containing the quantized confidence in the upper 5 bits, and 3 application-defined
+//!   flag bits in the lower bits.
+//!
+//! A beat of a regular rhythm usually takes 2 bytes.
+//!
+//! ```rust
+//! use qrs_detector::beat_log::{BeatLogDecoder, BeatLogEncoder, BeatRecord};
+//! use qrs_detector::Detection;
+//!
+//! let mut log = [0; 64];
+//! let mut len = 0;
+//!
+//! let mut encoder = BeatLogEncoder::new();
+//! for index in [500, 1000, 1510] {
+//!     let detection = Detection { index, amplitude: 2.0, threshold: 1.0, ..Default::default() };
+//!     len += encoder.encode(&detection, 0, &mut log[len..]).unwrap();
+//! }
+//!
+//! let rr = BeatLogDecoder::new(&log[..len]).map(|beat| beat.rr).collect::<Vec<_>>();
+//! assert_eq!(rr, [500, 500, 510]);
+//! ```
+
+use crate::Detection;
+
+/// The largest number of bytes a single beat may be encoded into.
+pub const MAX_RECORD_SIZE: usize = 6;
+
+/// The largest value of the application-defined flags.
+pub const MAX_FLAGS: u8 = 0b111;
+
+const CONFIDENCE_STEPS: f32 = 31.0;
+
+/// The output buffer is too small to hold the encoded beat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferFull;
+
+/// A decoded beat.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BeatRecord {
+    /// The number of samples since the previous beat, or the sample index of the first beat.
+    pub rr: u32,
+    /// The confidence of the detection, quantized to 1/31 steps. See [`Detection::confidence`].
+    pub confidence: f32,
+    /// Application-defined flags.
+    pub flags: u8,
+}
+
+/// Encodes beats into a compact binary format.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BeatLogEncoder {
+    prev_idx: u32,
+    prev_rr: u32,
+}
+
+impl BeatLogEncoder {
+    /// Creates a new encoder, starting a new log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes `detection` into `out` and returns the number of bytes written.
+    ///
+    /// `flags` may be at most [`MAX_FLAGS`]. If `out` is too small, nothing is written and the
+    /// encoder state is not modified.
+    pub fn encode(
+        &mut self,
+        detection: &Detection,
+        flags: u8,
+        out: &mut [u8],
+    ) -> Result<usize, BufferFull> {
+        debug_assert!(flags <= MAX_FLAGS, "Flags must fit into 3 bits");
+
+        let rr = detection.index.wrapping_sub(self.prev_idx);
+        let delta = rr.wrapping_sub(self.prev_rr) as i32;
+
+        let mut record = [0; MAX_RECORD_SIZE];
+        let mut len = write_varint(zigzag(delta), &mut record);
+        let confidence = (detection.confidence() * CONFIDENCE_STEPS + 0.5) as u8;
+        record[len] = (confidence << 3) | (flags & MAX_FLAGS);
+        len += 1;
+
+        out.get_mut(..len)
+            .ok_or(BufferFull)?
+            .copy_from_slice(&record[..len]);
+
+        self.prev_idx = detection.index;
+        self.prev_rr = rr;
+
+        Ok(len)
+    }
+}
+
+/// Decodes beats encoded by [`BeatLogEncoder`].
+///
+/// Iteration ends at the end of the data. An incomplete record at the end of the data is ignored.
+#[derive(Clone, Debug)]
+pub struct BeatLogDecoder<'a> {
+    data: &'a [u8],
+    prev_rr: u32,
+}
+
+impl<'a> BeatLogDecoder<'a> {
+    /// Creates a new decoder reading from the start of a log.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, prev_rr: 0 }
+    }
+
+    /// Returns the data that has not been decoded yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl Iterator for BeatLogDecoder<'_> {
+    type Item = BeatRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (delta, len) = read_varint(self.data)?;
+        let packed = *self.data.get(len)?;
+        self.data = &self.data[len + 1..];
+
+        let rr = self.prev_rr.wrapping_add(unzigzag(delta) as u32);
+        self.prev_rr = rr;
+
+        Some(BeatRecord {
+            rr,
+            confidence: (packed >> 3) as f32 / CONFIDENCE_STEPS,
+            flags: packed & MAX_FLAGS,
+        })
+    }
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+fn unzigzag(value: u32) -> i32 {
+    ((value >> 1) as i32) ^ -((value & 1) as i32)
+}
+
+fn write_varint(mut value: u32, out: &mut [u8]) -> usize {
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out[len] = byte;
+            return len + 1;
+        }
+        out[len] = byte | 0x80;
+        len += 1;
+    }
+}
+
+fn read_varint(data: &[u8]) -> Option<(u32, usize)> {
+    let mut value = 0;
+    for (i, byte) in data.iter().take(MAX_RECORD_SIZE - 1).enumerate() {
+        value |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
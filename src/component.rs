@@ -0,0 +1,99 @@
+//! Replaceable components of the detection threshold.
+//!
+//! The detection threshold is the sum of the steep-slope threshold M, the integrating threshold
+//! F and the beat expectation threshold R. The F and R components may be replaced by
+//! implementations of [`ThresholdComponent`], e.g. to evaluate research variants of the
+//! algorithm, see [`QrsDetector::with_f_component`] and [`QrsDetector::with_r_component`].
+//!
+//! M drives the refractory period of the detector and cannot be replaced.
+//!
+//! # Example
+//!
+//! A beat expectation threshold that is always 0:
+//!
+//! ```rust
+//! use qrs_detector::component::{ComponentInput, ThresholdComponent};
+//! use qrs_detector::prelude::*;
+//!
+//! struct NoExpectation;
+//!
+//! impl ThresholdComponent for NoExpectation {
+//!     fn update(&mut self, _input: &ComponentInput) {}
+//!
+//!     fn threshold(&self) -> Option<f32> {
+//!         Some(0.0)
+//!     }
+//!
+//!     fn clear(&mut self) {}
+//! }
+//!
+//! let detector = QrsDetector::new::<150, 25>(500.sps()).with_r_component(NoExpectation);
+//! ```
+//!
+//! [`QrsDetector::with_f_component`]: crate::QrsDetector::with_f_component
+//! [`QrsDetector::with_r_component`]: crate::QrsDetector::with_r_component
+
+use crate::{
+    config::{Config, Parameters},
+    Corruption,
+};
+
+/// The state of the detector passed to [`ThresholdComponent::update`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComponentInput {
+    /// The processed sample, after the sample transform.
+    pub sample: f32,
+    /// The index of the sample, counted from the last reset of the detector.
+    pub index: u32,
+    /// The steep-slope threshold M after processing the sample. `None` during the
+    /// initialization and the refractory period.
+    pub m: Option<f32>,
+    /// The rate M decreases at, per sample.
+    pub m_decrement: f32,
+    /// `true` during the refractory period following a detection.
+    pub in_refractory: bool,
+}
+
+/// A component of the detection threshold.
+pub trait ThresholdComponent {
+    /// Processes a sample.
+    fn update(&mut self, input: &ComponentInput);
+
+    /// Returns the current value of the component. The detector does not detect QRS complexes
+    /// while the F component returns `None`. `None` is treated as 0 for the R component.
+    fn threshold(&self) -> Option<f32>;
+
+    /// Notifies the component of a detection at sample index `index`, with the sample value
+    /// `sample`.
+    fn detection_event(&mut self, _index: u32, _sample: f32) {}
+
+    /// Resets the internal state.
+    fn clear(&mut self);
+
+    /// Restarts the adaptation after an artifact, e.g. a DC offset step. Resets the internal
+    /// state by default.
+    fn readapt(&mut self) {
+        self.clear();
+    }
+
+    /// Applies `config` to the component.
+    fn configure(&mut self, _config: &Config) {}
+
+    /// Applies `config` to a running component, preserving the adapted state where possible.
+    /// `changed` is the set of parameters that differ from the previous configuration. Returns
+    /// the changed parameters that required resetting the internal state.
+    ///
+    /// Calls [`ThresholdComponent::configure`] and reports every changed parameter by default.
+    fn reconfigure(&mut self, config: &Config, changed: Parameters) -> Parameters {
+        self.configure(config);
+        changed
+    }
+
+    /// Validates the internal invariants, see [`QrsDetector::verify`]. `index` is the index of
+    /// the next sample.
+    ///
+    /// [`QrsDetector::verify`]: crate::QrsDetector::verify
+    fn verify(&self, _index: u32) -> Result<(), Corruption> {
+        Ok(())
+    }
+}
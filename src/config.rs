@@ -0,0 +1,1336 @@
+//! Configuration of the detection algorithm.
+//!
+//! The default configuration follows the parameters of the original article, with the
+//! modifications noted in the source.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//!
+//! let config = Config::new().with_mm_depth(3);
+//! let detector = QrsDetector::new::<150, 25>(500.sps()).with_config(config);
+//! ```
+
+use core::fmt;
+
+use crate::{
+    math::Fnv1a,
+    sampling::SamplingFrequency,
+    transform::{Difference, Rectification, MAX_DIFFERENCE_LAG},
+};
+
+/// The smallest supported depth of the M threshold buffer.
+pub const MIN_MM_DEPTH: usize = 3;
+
+/// The largest supported depth of the M threshold buffer.
+pub const MAX_MM_DEPTH: usize = 8;
+
+/// The smallest supported number of RR intervals R averages.
+pub const MIN_RR_DEPTH: usize = 2;
+
+/// The largest supported number of RR intervals R averages.
+pub const MAX_RR_DEPTH: usize = 8;
+
+/// The version of [`ConfigDescription`]. It is incremented when parameters are added or their
+/// meaning changes.
+///
+/// [`Config::from_description`] reads descriptions of every earlier version. Parameters added
+/// after the version of the description take their default value. The versions added:
+///
+/// - 2: `f64_accumulation`
+/// - 3: `latency_compensation_ms`
+/// - 4: `rr_depth`
+/// - 5: `refractory_ms`
+/// - 6: `dc_blocker`
+/// - 7: `input_mode`
+/// - 8: `difference_lag`
+/// - 9: `pace_blanking_ms`
+/// - 10: `z_score`
+/// - 11: `step_blanking`
+/// - 12: the `preprocessing` feature, which is `false` in earlier versions
+pub const DESCRIPTION_VERSION: u16 = 12;
+
+/// The shape of the M threshold decrease following a detection.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MDecay {
+    /// M decreases linearly, by `fraction` of its refreshed value over `duration_ms`.
+    Linear { fraction: f32, duration_ms: f32 },
+    /// M decreases exponentially towards `1 - fraction` of its refreshed value, and reaches it
+    /// after `duration_ms`. Compared to the linear decrease, most of the decrease happens early.
+    Exponential { fraction: f32, duration_ms: f32 },
+}
+
+impl MDecay {
+    /// The decrease described by the article: 40% over 1 second.
+    pub const PAPER: Self = Self::Linear {
+        fraction: 0.4,
+        duration_ms: 1000.0,
+    };
+
+    /// Returns the fraction of the refreshed value M decreases by.
+    pub fn fraction(&self) -> f32 {
+        match *self {
+            Self::Linear { fraction, .. } | Self::Exponential { fraction, .. } => fraction,
+        }
+    }
+
+    /// Returns the duration of the decrease, in milliseconds.
+    pub fn duration_ms(&self) -> f32 {
+        match *self {
+            Self::Linear { duration_ms, .. } | Self::Exponential { duration_ms, .. } => duration_ms,
+        }
+    }
+}
+
+/// The built-in transform of the input samples, see [`Config::with_input_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputMode {
+    /// The samples are processed unchanged. The input must already emphasize the QRS slopes.
+    #[default]
+    Raw,
+    /// `|x[n] - x[n-2]|`, the preprocessing described by the article. The lag is set by
+    /// [`Config::with_difference_lag`].
+    AbsDiff,
+    /// `(x[n] - x[n-2])²`, which emphasizes the steepest slopes. The lag is set by
+    /// [`Config::with_difference_lag`].
+    SquaredDiff,
+}
+
+impl InputMode {
+    /// Returns the rectification of the differences, or `None` for raw input.
+    pub const fn rectification(&self) -> Option<Rectification> {
+        match self {
+            Self::Raw => None,
+            Self::AbsDiff => Some(Rectification::Abs),
+            Self::SquaredDiff => Some(Rectification::Square),
+        }
+    }
+}
+
+/// The lag of the built-in difference, see [`Config::with_difference_lag`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DifferenceLag {
+    /// A fixed lag, between 1 and [`MAX_DIFFERENCE_LAG`] samples.
+    Samples(u8),
+    /// A lag scaled to the sampling frequency, see [`Difference::lag_for`].
+    Scaled,
+}
+
+impl DifferenceLag {
+    /// Returns the lag in samples, for signals sampled with `fs`.
+    pub fn samples(&self, fs: SamplingFrequency) -> usize {
+        match *self {
+            Self::Samples(lag) => lag as usize,
+            Self::Scaled => Difference::lag_for(fs),
+        }
+    }
+
+    const fn is_valid(&self) -> bool {
+        match *self {
+            Self::Samples(lag) => lag >= 1 && lag as usize <= MAX_DIFFERENCE_LAG,
+            Self::Scaled => true,
+        }
+    }
+}
+
+impl Default for DifferenceLag {
+    fn default() -> Self {
+        Self::Samples(2)
+    }
+}
+
+/// Parameters of the detection algorithm.
+///
+/// With the `serde` feature, the configuration is serialized as its [`ConfigDescription`]. A
+/// deserialized configuration is validated like [`Config::from_description`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "ConfigDescription", into = "ConfigDescription")
+)]
+pub struct Config {
+    mm_depth: usize,
+    m_decay: MDecay,
+    r_weight: f32,
+    r_delay: f32,
+    r_timeout: f32,
+    r_floor: f32,
+    rr_depth: usize,
+    refractory_ms: f32,
+    snr_adaptive: bool,
+    skip_diagnostics: bool,
+    calibration: Option<f32>,
+    f64_accumulation: bool,
+    latency_compensation_ms: f32,
+    dc_blocker: Option<f32>,
+    input_mode: InputMode,
+    difference_lag: DifferenceLag,
+    pace_blanking_ms: f32,
+    z_score: Option<f32>,
+    step_blanking: bool,
+}
+
+impl Config {
+    /// Returns the default configuration.
+    pub const fn new() -> Self {
+        Self {
+            mm_depth: 5,
+            m_decay: MDecay::PAPER,
+            r_weight: 1.0 / 1.4,
+            r_delay: 2.0 / 3.0,
+            r_timeout: 3.0,
+            r_floor: 0.5,
+            rr_depth: 5,
+            refractory_ms: 225.0,
+            snr_adaptive: false,
+            skip_diagnostics: false,
+            calibration: None,
+            f64_accumulation: false,
+            latency_compensation_ms: 0.0,
+            dc_blocker: None,
+            input_mode: InputMode::Raw,
+            difference_lag: DifferenceLag::Samples(2),
+            pace_blanking_ms: 50.0,
+            z_score: None,
+            step_blanking: false,
+        }
+    }
+
+    /// Returns a configuration that adapts the thresholds to the signal-to-noise ratio, see
+    /// [`Config::with_snr_adaptive`].
+    pub const fn adaptive() -> Self {
+        Self::new().with_snr_adaptive(true)
+    }
+
+    /// Returns a configuration for rapidly changing heart rates, e.g. exercise stress tests.
+    ///
+    /// Compared to the default configuration, M is averaged from 3 values and decreases faster,
+    /// following the QRS amplitude that drops as the heart rate increases. R averages the last 3
+    /// RR intervals, and starts decreasing after half of the average RR interval, because the
+    /// average lags behind the shortening RR intervals.
+    ///
+    /// The faster adaptation makes the detector more sensitive to strong broadband noise.
+    pub const fn exercise() -> Self {
+        Self {
+            mm_depth: 3,
+            m_decay: MDecay::Exponential {
+                fraction: 0.3,
+                duration_ms: 400.0,
+            },
+            r_delay: 0.5,
+            rr_depth: 3,
+            ..Self::new()
+        }
+    }
+
+    /// Returns a configuration for low heart rates (35-60 bpm), e.g. nocturnal monitoring.
+    ///
+    /// During long diastoles, M decreases far below the QRS slope before the next beat, and
+    /// large T waves may be detected. Compared to the default configuration, M decreases by 20%
+    /// over 2 seconds. R remains 0 for 80% of the average RR interval, lowers the threshold by
+    /// at most a quarter of M, and tolerates pauses up to 4 average RR intervals.
+    pub const fn sleep() -> Self {
+        Self {
+            m_decay: MDecay::Linear {
+                fraction: 0.2,
+                duration_ms: 2000.0,
+            },
+            r_delay: 0.8,
+            r_timeout: 4.0,
+            r_floor: 0.25,
+            ..Self::new()
+        }
+    }
+
+    /// Returns a configuration for neonatal ECG, with heart rates up to 230 bpm and low QRS
+    /// amplitudes.
+    ///
+    /// Some of the paper's constants assume adult heart rates. At 230 bpm, the RR interval
+    /// (260 ms) is barely longer than the 225 ms refractory period, and M, decreasing over
+    /// 1 second, does not follow the beat-to-beat amplitude changes. Compared to the default
+    /// configuration, the refractory period is 150 ms, M decreases by 30% over 250 ms, and R
+    /// starts decreasing after half of the average RR interval.
+    ///
+    /// The 300 ms and 50 ms buffers of F are sized when the detector is created, and are not
+    /// changed by this preset. The narrower neonatal QRS complexes fit in both windows.
+    ///
+    /// The fast adaptation makes the detector sensitive to strong broadband noise, and the
+    /// short refractory period allows adult T waves to be detected.
+    pub const fn neonatal() -> Self {
+        Self {
+            m_decay: MDecay::Exponential {
+                fraction: 0.3,
+                duration_ms: 250.0,
+            },
+            r_delay: 0.5,
+            refractory_ms: 150.0,
+            ..Self::new()
+        }
+    }
+
+    /// Sets the number of steep-slope threshold values M is averaged from.
+    ///
+    /// A shorter buffer adapts faster to amplitude changes (e.g. on exercise ECG), a longer buffer
+    /// results in a steadier threshold. The default value is 5.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is not between [`MIN_MM_DEPTH`] and [`MAX_MM_DEPTH`].
+    pub const fn with_mm_depth(mut self, depth: usize) -> Self {
+        assert!(
+            depth >= MIN_MM_DEPTH && depth <= MAX_MM_DEPTH,
+            "MM depth must be between 3 and 8"
+        );
+        self.mm_depth = depth;
+        self
+    }
+
+    /// Returns the number of steep-slope threshold values M is averaged from.
+    pub const fn mm_depth(&self) -> usize {
+        self.mm_depth
+    }
+
+    /// Sets the shape of the M threshold decrease. The default is [`MDecay::PAPER`]. A duration
+    /// shorter than the sampling period decreases M in a single sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fraction is not in the `0..1` range, or the duration is not positive.
+    pub fn with_m_decay(mut self, decay: MDecay) -> Self {
+        assert!(
+            (0.0..1.0).contains(&decay.fraction()),
+            "M decay fraction must be between 0 and 1"
+        );
+        assert!(
+            decay.duration_ms() > 0.0,
+            "M decay duration must be positive"
+        );
+        self.m_decay = decay;
+        self
+    }
+
+    /// Returns the shape of the M threshold decrease.
+    pub const fn m_decay(&self) -> MDecay {
+        self.m_decay
+    }
+
+    /// Sets the slope of the R threshold decrease, relative to the average slope of the M
+    /// threshold decrease. The default value is `1 / 1.4`.
+    ///
+    /// The R threshold decreases with a slope of `weight * M * fraction / duration`, where `M` is
+    /// the latest refreshed M value, and `fraction` and `duration` are set by [`MDecay`]. A larger
+    /// weight lowers the total threshold faster when the next beat is expected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is negative.
+    pub const fn with_r_weight(mut self, weight: f32) -> Self {
+        assert!(weight >= 0.0, "R weight must not be negative");
+        self.r_weight = weight;
+        self
+    }
+
+    /// Returns the slope of the R threshold decrease, relative to the slope of M.
+    pub const fn r_weight(&self) -> f32 {
+        self.r_weight
+    }
+
+    /// Sets the fraction of the average RR interval R remains 0 for, after a detection. R
+    /// decreases during the rest of the average RR interval. The default value is `2 / 3`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fraction` is not in the `0..=1` range.
+    pub const fn with_r_delay(mut self, fraction: f32) -> Self {
+        assert!(
+            fraction >= 0.0 && fraction <= 1.0,
+            "R delay must be between 0 and 1"
+        );
+        self.r_delay = fraction;
+        self
+    }
+
+    /// Returns the fraction of the average RR interval R remains 0 for, after a detection.
+    pub const fn r_delay(&self) -> f32 {
+        self.r_delay
+    }
+
+    /// Sets the time without detections, in multiples of the average RR interval, after which R
+    /// returns to 0 and the collected RR intervals are discarded. The default value is 3.
+    ///
+    /// Without a timeout, R would keep lowering the total threshold indefinitely when the rhythm
+    /// pauses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is less than 1.
+    pub fn with_r_timeout(mut self, factor: f32) -> Self {
+        assert!(factor >= 1.0, "R timeout must be at least 1 RR interval");
+        self.r_timeout = factor;
+        self
+    }
+
+    /// Returns the time without detections, in multiples of the average RR interval, after which
+    /// R returns to 0.
+    pub const fn r_timeout(&self) -> f32 {
+        self.r_timeout
+    }
+
+    /// Limits how much R may lower the total threshold, as a fraction of the current M threshold.
+    /// The default value is 0.5.
+    ///
+    /// For a regular rhythm, R reaches about `-0.1 * M` per second of RR interval. Irregular RR
+    /// intervals may result in a large average RR interval and an unreasonably low total threshold.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fraction` is negative.
+    pub fn with_r_floor(mut self, fraction: f32) -> Self {
+        assert!(fraction >= 0.0, "R floor must not be negative");
+        self.r_floor = fraction;
+        self
+    }
+
+    /// Returns how much R may lower the total threshold, as a fraction of the current M threshold.
+    pub const fn r_floor(&self) -> f32 {
+        self.r_floor
+    }
+
+    /// Sets the number of RR intervals R averages to predict the next beat. The default value is
+    /// 5.
+    ///
+    /// A shorter buffer follows heart rate changes faster, a longer buffer is less affected by
+    /// irregular beats.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is not between [`MIN_RR_DEPTH`] and [`MAX_RR_DEPTH`].
+    pub const fn with_rr_depth(mut self, depth: usize) -> Self {
+        assert!(
+            depth >= MIN_RR_DEPTH && depth <= MAX_RR_DEPTH,
+            "RR depth must be between 2 and 8"
+        );
+        self.rr_depth = depth;
+        self
+    }
+
+    /// Returns the number of RR intervals R averages.
+    pub const fn rr_depth(&self) -> usize {
+        self.rr_depth
+    }
+
+    /// Sets the refractory period after a detection, in milliseconds. No beat is detected during
+    /// the refractory period. The default value is 225 ms [originally 200 ms].
+    ///
+    /// A shorter period is required for heart rates above about 200 bpm.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ms` is not between 0 and 1000.
+    pub fn with_refractory_ms(mut self, ms: f32) -> Self {
+        assert!(
+            ms > 0.0 && ms < 1000.0,
+            "Refractory period must be between 0 and 1000 ms"
+        );
+        self.refractory_ms = ms;
+        self
+    }
+
+    /// Returns the refractory period after a detection, in milliseconds.
+    pub const fn refractory_ms(&self) -> f32 {
+        self.refractory_ms
+    }
+
+    /// Sets whether the thresholds adapt to the signal-to-noise ratio. Disabled by default.
+    ///
+    /// When enabled, the initial M threshold is set lower on clean signals and higher on noisy
+    /// ones, and M does not decrease below twice the noise level. This requires a noise estimator,
+    /// see [`QrsDetector::with_noise_estimator`](crate::QrsDetector::with_noise_estimator).
+    pub const fn with_snr_adaptive(mut self, adaptive: bool) -> Self {
+        self.snr_adaptive = adaptive;
+        self
+    }
+
+    /// Returns whether the thresholds adapt to the signal-to-noise ratio.
+    pub const fn snr_adaptive(&self) -> bool {
+        self.snr_adaptive
+    }
+
+    /// Enables the diagnostics of skipped beats. Disabled by default.
+    ///
+    /// When enabled, the detector records the sample closest to the threshold between every two
+    /// detections. If the RR interval is longer than 1.75 times the running average, the record
+    /// is reported, see [`QrsDetector::take_skipped_beat`](crate::QrsDetector::take_skipped_beat).
+    /// This helps to distinguish real pauses from beats that were too small to be detected.
+    pub const fn with_skip_diagnostics(mut self, enabled: bool) -> Self {
+        self.skip_diagnostics = enabled;
+        self
+    }
+
+    /// Returns whether the diagnostics of skipped beats are enabled.
+    pub const fn skip_diagnostics(&self) -> bool {
+        self.skip_diagnostics
+    }
+
+    /// Sets the gain of the processed signal, in units per millivolt. Not set by default.
+    ///
+    /// When set, the detector also reports thresholds and amplitudes in millivolts, e.g. see
+    /// [`QrsDetector::thresholds_mv`](crate::QrsDetector::thresholds_mv). The gain must include
+    /// the gain of any preprocessing applied to the signal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lsb_per_mv` is not a positive, normal number.
+    pub const fn with_calibration(mut self, lsb_per_mv: f32) -> Self {
+        assert!(
+            lsb_per_mv.is_normal() && lsb_per_mv > 0.0,
+            "Calibration must be positive"
+        );
+        self.calibration = Some(lsb_per_mv);
+        self
+    }
+
+    /// Returns the gain of the processed signal, in units per millivolt, if set.
+    pub const fn calibration(&self) -> Option<f32> {
+        self.calibration
+    }
+
+    /// Sets whether the thresholds accumulate using `f64` values internally. Disabled by default.
+    ///
+    /// The F threshold is updated with tiny increments over hours of signal. By default, it uses
+    /// compensated `f32` summation, which is accurate enough for most uses. `f64` accumulation
+    /// improves numerical fidelity further, at the cost of execution time on targets without a
+    /// double precision FPU. The samples remain `f32`.
+    pub const fn with_f64_accumulation(mut self, enabled: bool) -> Self {
+        self.f64_accumulation = enabled;
+        self
+    }
+
+    /// Returns whether the thresholds accumulate using `f64` values internally.
+    pub const fn f64_accumulation(&self) -> bool {
+        self.f64_accumulation
+    }
+
+    /// Sets the latency of the detections relative to the R peaks, in milliseconds. The default
+    /// value is 0.
+    ///
+    /// The detector reports the sample where the threshold is crossed, which may be before
+    /// (negative latency) or after the R peak, depending on the preprocessing. The reported
+    /// detection indices are adjusted by this latency, in addition to the delay of the
+    /// preprocessing, see
+    /// [`QrsDetector::total_group_delay_samples`](crate::QrsDetector::total_group_delay_samples).
+    /// The latency can be measured on annotated records using
+    /// [`validation::LatencyStats`](crate::validation::LatencyStats). With a negative latency,
+    /// detections may be reported at indices after the last processed sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `latency_ms` is not finite.
+    pub fn with_latency_compensation(mut self, latency_ms: f32) -> Self {
+        assert!(latency_ms.is_finite(), "Latency must be finite");
+        self.latency_compensation_ms = latency_ms;
+        self
+    }
+
+    /// Returns the latency of the detections relative to the R peaks, in milliseconds.
+    pub const fn latency_compensation_ms(&self) -> f32 {
+        self.latency_compensation_ms
+    }
+
+    /// Removes the DC offset of the input samples using a one-pole high-pass filter with a cutoff
+    /// frequency of `cutoff` Hz, see [`DcBlocker`](crate::transform::DcBlocker). Not set by
+    /// default.
+    ///
+    /// The filter is applied to the raw samples, before the sample transform of the detector. It
+    /// is the cheapest preprocessing for raw ADC data with a large DC offset, when the filters of
+    /// the `preprocessing` module are not needed. A cutoff frequency of 0.5 Hz also removes most
+    /// of the baseline wander.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cutoff` is not positive and finite.
+    pub fn with_dc_blocker(mut self, cutoff: f32) -> Self {
+        assert!(
+            cutoff > 0.0 && cutoff.is_finite(),
+            "DC blocker cutoff frequency must be positive"
+        );
+        self.dc_blocker = Some(cutoff);
+        self
+    }
+
+    /// Returns the cutoff frequency of the DC blocker, in Hz, if enabled.
+    pub const fn dc_blocker(&self) -> Option<f32> {
+        self.dc_blocker
+    }
+
+    /// Sets the built-in transform of the input samples. The default is [`InputMode::Raw`].
+    ///
+    /// The transform is applied after the sample transform of the detector, so that the raw
+    /// signal can be filtered first, and its delay is included in
+    /// [`QrsDetector::total_group_delay_samples`](crate::QrsDetector::total_group_delay_samples).
+    /// With [`InputMode::AbsDiff`], the canonical usage of the detector requires no external
+    /// preprocessing. Calibration (see [`Config::with_calibration`]) must include the gain of the
+    /// differentiation, which is not linear for [`InputMode::SquaredDiff`].
+    pub const fn with_input_mode(mut self, mode: InputMode) -> Self {
+        self.input_mode = mode;
+        self
+    }
+
+    /// Returns the built-in transform of the input samples.
+    pub const fn input_mode(&self) -> InputMode {
+        self.input_mode
+    }
+
+    /// Sets the lag of the difference calculated by [`InputMode::AbsDiff`] and
+    /// [`InputMode::SquaredDiff`]. The default is 2 samples.
+    ///
+    /// The best lag depends on the sampling frequency and the slope of the QRS complexes, see
+    /// [`Difference`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a fixed lag is not between 1 and [`MAX_DIFFERENCE_LAG`] samples.
+    pub const fn with_difference_lag(mut self, lag: DifferenceLag) -> Self {
+        assert!(lag.is_valid(), "The lag must be between 1 and 4 samples");
+        self.difference_lag = lag;
+        self
+    }
+
+    /// Returns the lag of the built-in difference.
+    pub const fn difference_lag(&self) -> DifferenceLag {
+        self.difference_lag
+    }
+
+    /// Sets how long the signal is blanked after a pacing pulse, in milliseconds. The default
+    /// value is 50ms.
+    ///
+    /// The pulses are reported using
+    /// [`QrsDetector::notify_pace`](crate::QrsDetector::notify_pace). The blanking period is
+    /// extended by the length of the preprocessing filters, which smear the pacing artifact.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `blanking_ms` is not between 0 and 1000ms.
+    pub fn with_pace_blanking(mut self, blanking_ms: f32) -> Self {
+        assert!(
+            (0.0..1000.0).contains(&blanking_ms),
+            "Pace blanking must be between 0 and 1000ms"
+        );
+        self.pace_blanking_ms = blanking_ms;
+        self
+    }
+
+    /// Returns how long the signal is blanked after a pacing pulse, in milliseconds.
+    pub const fn pace_blanking_ms(&self) -> f32 {
+        self.pace_blanking_ms
+    }
+
+    /// Normalizes the input of the thresholds to its z-score over a sliding window of `window_s`
+    /// seconds, see [`ZScore`](crate::transform::ZScore). Not set by default.
+    ///
+    /// The normalization is applied last, after the built-in transform of the input samples (see
+    /// [`Config::with_input_mode`]), so that signals with widely different amplitudes can be
+    /// processed with the same thresholds. The statistics are calculated from blocks of
+    /// `window_s / 16` seconds. The detector does not process samples until the first block is
+    /// complete, so that the initial M threshold is calculated from normalized samples. The
+    /// thresholds and amplitudes are in units of standard deviations, so calibration (see
+    /// [`Config::with_calibration`]) does not apply.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window_s` is less than 1 second or not finite.
+    pub fn with_z_score(mut self, window_s: f32) -> Self {
+        assert!(
+            window_s >= 1.0 && window_s.is_finite(),
+            "The z-score window must be at least 1 second"
+        );
+        self.z_score = Some(window_s);
+        self
+    }
+
+    /// Returns the window of the z-score normalization, in seconds, if enabled.
+    pub const fn z_score(&self) -> Option<f32> {
+        self.z_score
+    }
+
+    /// Enables the blanking of DC offset steps, see
+    /// [`StepDetector`](crate::quality::StepDetector). Disabled by default.
+    ///
+    /// When enabled, samples that jump far above the recent peaks of the signal, e.g. after an
+    /// electrode is reconnected, are not processed, and the thresholds restart their adaptation
+    /// once the artifact has passed. Threshold crossings during the artifact are reported as
+    /// suppressed. Requires the `quality` feature, the setting has no effect without it.
+    pub const fn with_step_blanking(mut self, enabled: bool) -> Self {
+        self.step_blanking = enabled;
+        self
+    }
+
+    /// Returns whether DC offset steps are blanked.
+    pub const fn step_blanking(&self) -> bool {
+        self.step_blanking
+    }
+
+    /// Returns the parameters that differ between `self` and `other`.
+    ///
+    /// ```rust
+    /// use qrs_detector::config::{Parameter, Parameters};
+    /// use qrs_detector::prelude::*;
+    ///
+    /// let changed = Config::new().changes(&Config::new().with_mm_depth(3).with_r_floor(0.25));
+    /// assert_eq!(Parameters::of(&[Parameter::MmDepth, Parameter::RFloor]), changed);
+    /// ```
+    pub fn changes(&self, other: &Self) -> Parameters {
+        let differs = [
+            (Parameter::MmDepth, self.mm_depth != other.mm_depth),
+            (Parameter::MDecay, self.m_decay != other.m_decay),
+            (Parameter::RWeight, self.r_weight != other.r_weight),
+            (Parameter::RDelay, self.r_delay != other.r_delay),
+            (Parameter::RTimeout, self.r_timeout != other.r_timeout),
+            (Parameter::RFloor, self.r_floor != other.r_floor),
+            (Parameter::RrDepth, self.rr_depth != other.rr_depth),
+            (
+                Parameter::Refractory,
+                self.refractory_ms != other.refractory_ms,
+            ),
+            (
+                Parameter::SnrAdaptive,
+                self.snr_adaptive != other.snr_adaptive,
+            ),
+            (
+                Parameter::SkipDiagnostics,
+                self.skip_diagnostics != other.skip_diagnostics,
+            ),
+            (
+                Parameter::Calibration,
+                self.calibration != other.calibration,
+            ),
+            (
+                Parameter::F64Accumulation,
+                self.f64_accumulation != other.f64_accumulation,
+            ),
+            (
+                Parameter::LatencyCompensation,
+                self.latency_compensation_ms != other.latency_compensation_ms,
+            ),
+            (Parameter::DcBlocker, self.dc_blocker != other.dc_blocker),
+            (Parameter::InputMode, self.input_mode != other.input_mode),
+            (
+                Parameter::DifferenceLag,
+                self.difference_lag != other.difference_lag,
+            ),
+            (
+                Parameter::PaceBlanking,
+                self.pace_blanking_ms != other.pace_blanking_ms,
+            ),
+            (Parameter::ZScore, self.z_score != other.z_score),
+            (
+                Parameter::StepBlanking,
+                self.step_blanking != other.step_blanking,
+            ),
+        ];
+
+        differs
+            .into_iter()
+            .filter(|(_, differs)| *differs)
+            .fold(Parameters::empty(), |set, (parameter, _)| {
+                set.with(parameter)
+            })
+    }
+
+    /// Returns a description of every parameter, and the cargo features the crate was built
+    /// with. The description can be stored in device logs, and turned back into the
+    /// configuration using [`Config::from_description`].
+    ///
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    ///
+    /// let config = Config::new().with_mm_depth(3);
+    /// let description = config.describe();
+    ///
+    /// assert_eq!(Ok(config), Config::from_description(&description));
+    /// ```
+    pub fn describe(&self) -> ConfigDescription {
+        ConfigDescription {
+            version: DESCRIPTION_VERSION,
+            mm_depth: self.mm_depth as u32,
+            m_decay: self.m_decay,
+            r_weight: self.r_weight,
+            r_delay: self.r_delay,
+            r_timeout: self.r_timeout,
+            r_floor: self.r_floor,
+            rr_depth: self.rr_depth as u32,
+            refractory_ms: self.refractory_ms,
+            snr_adaptive: self.snr_adaptive,
+            skip_diagnostics: self.skip_diagnostics,
+            calibration: self.calibration,
+            f64_accumulation: self.f64_accumulation,
+            latency_compensation_ms: self.latency_compensation_ms,
+            dc_blocker: self.dc_blocker,
+            input_mode: self.input_mode,
+            difference_lag: self.difference_lag,
+            pace_blanking_ms: self.pace_blanking_ms,
+            z_score: self.z_score,
+            step_blanking: self.step_blanking,
+            features: Features::enabled(),
+        }
+    }
+
+    /// Restores the configuration from its description.
+    ///
+    /// Descriptions of earlier versions are accepted, see [`DESCRIPTION_VERSION`]. Fails if the
+    /// description has an unsupported version, contains invalid parameters, or was created with
+    /// a different set of cargo features that affect detection.
+    pub fn from_description(description: &ConfigDescription) -> Result<Self, DescriptionError> {
+        if !(1..=DESCRIPTION_VERSION).contains(&description.version) {
+            return Err(DescriptionError::UnsupportedVersion(description.version));
+        }
+        let description = &description.upgrade();
+        if description.features.quality != Features::enabled().quality {
+            return Err(DescriptionError::FeatureMismatch);
+        }
+
+        let valid = (MIN_MM_DEPTH..=MAX_MM_DEPTH).contains(&(description.mm_depth as usize))
+            && (0.0..1.0).contains(&description.m_decay.fraction())
+            && description.m_decay.duration_ms() > 0.0
+            && description.r_weight >= 0.0
+            && (0.0..=1.0).contains(&description.r_delay)
+            && description.r_timeout >= 1.0
+            && description.r_floor >= 0.0
+            && (MIN_RR_DEPTH..=MAX_RR_DEPTH).contains(&(description.rr_depth as usize))
+            && description.refractory_ms > 0.0
+            && description.refractory_ms < 1000.0
+            && description
+                .calibration
+                .is_none_or(|lsb_per_mv| lsb_per_mv.is_normal() && lsb_per_mv > 0.0)
+            && description.latency_compensation_ms.is_finite()
+            && description
+                .dc_blocker
+                .is_none_or(|cutoff| cutoff > 0.0 && cutoff.is_finite())
+            && description.difference_lag.is_valid()
+            && (0.0..1000.0).contains(&description.pace_blanking_ms)
+            && description
+                .z_score
+                .is_none_or(|window_s| window_s >= 1.0 && window_s.is_finite());
+        if !valid {
+            return Err(DescriptionError::Invalid);
+        }
+
+        Ok(Self {
+            mm_depth: description.mm_depth as usize,
+            m_decay: description.m_decay,
+            r_weight: description.r_weight,
+            r_delay: description.r_delay,
+            r_timeout: description.r_timeout,
+            r_floor: description.r_floor,
+            rr_depth: description.rr_depth as usize,
+            refractory_ms: description.refractory_ms,
+            snr_adaptive: description.snr_adaptive,
+            skip_diagnostics: description.skip_diagnostics,
+            calibration: description.calibration,
+            f64_accumulation: description.f64_accumulation,
+            latency_compensation_ms: description.latency_compensation_ms,
+            dc_blocker: description.dc_blocker,
+            input_mode: description.input_mode,
+            difference_lag: description.difference_lag,
+            pace_blanking_ms: description.pace_blanking_ms,
+            z_score: description.z_score,
+            step_blanking: description.step_blanking,
+        })
+    }
+}
+
+impl From<Config> for ConfigDescription {
+    fn from(config: Config) -> Self {
+        config.describe()
+    }
+}
+
+impl TryFrom<ConfigDescription> for Config {
+    type Error = DescriptionError;
+
+    fn try_from(description: ConfigDescription) -> Result<Self, Self::Error> {
+        Self::from_description(&description)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The cargo features the crate was built with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Features {
+    /// The `quality` feature, which affects detection.
+    pub quality: bool,
+    /// The `hrv` feature.
+    pub hrv: bool,
+    /// The `alloc` feature.
+    pub alloc: bool,
+    /// The `preprocessing` feature.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub preprocessing: bool,
+}
+
+impl Features {
+    /// Returns the features of this build.
+    pub const fn enabled() -> Self {
+        Self {
+            quality: cfg!(feature = "quality"),
+            hrv: cfg!(feature = "hrv"),
+            alloc: cfg!(feature = "alloc"),
+            preprocessing: cfg!(feature = "preprocessing"),
+        }
+    }
+}
+
+/// A stable, versioned description of a [`Config`], see [`Config::describe`].
+///
+/// The `Display` implementation formats the description as a single line of `key=value` pairs.
+///
+/// When deserialized, parameters missing from descriptions of earlier versions take their default
+/// value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ConfigDescription {
+    /// The version of the description, see [`DESCRIPTION_VERSION`].
+    pub version: u16,
+    /// See [`Config::mm_depth`].
+    pub mm_depth: u32,
+    /// See [`Config::m_decay`].
+    pub m_decay: MDecay,
+    /// See [`Config::r_weight`].
+    pub r_weight: f32,
+    /// See [`Config::r_delay`].
+    pub r_delay: f32,
+    /// See [`Config::r_timeout`].
+    pub r_timeout: f32,
+    /// See [`Config::r_floor`].
+    pub r_floor: f32,
+    /// See [`Config::rr_depth`].
+    pub rr_depth: u32,
+    /// See [`Config::refractory_ms`].
+    pub refractory_ms: f32,
+    /// See [`Config::snr_adaptive`].
+    pub snr_adaptive: bool,
+    /// See [`Config::skip_diagnostics`].
+    pub skip_diagnostics: bool,
+    /// See [`Config::calibration`].
+    pub calibration: Option<f32>,
+    /// See [`Config::f64_accumulation`].
+    pub f64_accumulation: bool,
+    /// See [`Config::latency_compensation_ms`].
+    pub latency_compensation_ms: f32,
+    /// See [`Config::dc_blocker`].
+    pub dc_blocker: Option<f32>,
+    /// See [`Config::input_mode`].
+    pub input_mode: InputMode,
+    /// See [`Config::difference_lag`].
+    pub difference_lag: DifferenceLag,
+    /// See [`Config::pace_blanking_ms`].
+    pub pace_blanking_ms: f32,
+    /// See [`Config::z_score`].
+    pub z_score: Option<f32>,
+    /// See [`Config::step_blanking`].
+    pub step_blanking: bool,
+    /// The cargo features the description was created with.
+    pub features: Features,
+}
+
+/// The description of the default configuration.
+impl Default for ConfigDescription {
+    fn default() -> Self {
+        Config::new().describe()
+    }
+}
+
+impl ConfigDescription {
+    /// Returns the description in the current version. Parameters added after the version of
+    /// the description are set to their default value.
+    fn upgrade(&self) -> Self {
+        let defaults = Self::default();
+        let since = |version: u16| self.version >= version;
+
+        Self {
+            version: DESCRIPTION_VERSION,
+            f64_accumulation: if since(2) {
+                self.f64_accumulation
+            } else {
+                defaults.f64_accumulation
+            },
+            latency_compensation_ms: if since(3) {
+                self.latency_compensation_ms
+            } else {
+                defaults.latency_compensation_ms
+            },
+            rr_depth: if since(4) {
+                self.rr_depth
+            } else {
+                defaults.rr_depth
+            },
+            refractory_ms: if since(5) {
+                self.refractory_ms
+            } else {
+                defaults.refractory_ms
+            },
+            dc_blocker: if since(6) {
+                self.dc_blocker
+            } else {
+                defaults.dc_blocker
+            },
+            input_mode: if since(7) {
+                self.input_mode
+            } else {
+                defaults.input_mode
+            },
+            difference_lag: if since(8) {
+                self.difference_lag
+            } else {
+                defaults.difference_lag
+            },
+            pace_blanking_ms: if since(9) {
+                self.pace_blanking_ms
+            } else {
+                defaults.pace_blanking_ms
+            },
+            z_score: if since(10) {
+                self.z_score
+            } else {
+                defaults.z_score
+            },
+            step_blanking: if since(11) {
+                self.step_blanking
+            } else {
+                defaults.step_blanking
+            },
+            features: Features {
+                preprocessing: since(12) && self.features.preprocessing,
+                ..self.features
+            },
+            ..*self
+        }
+    }
+
+    /// Returns a hash of every parameter and feature in the description.
+    pub fn hash(&self) -> u32 {
+        let (decay, fraction, duration_ms) = match self.m_decay {
+            MDecay::Linear {
+                fraction,
+                duration_ms,
+            } => (0, fraction, duration_ms),
+            MDecay::Exponential {
+                fraction,
+                duration_ms,
+            } => (1, fraction, duration_ms),
+        };
+
+        let mut hash = Fnv1a::new();
+        hash.write(&self.version.to_le_bytes());
+        hash.write(&self.mm_depth.to_le_bytes());
+        hash.write(&self.rr_depth.to_le_bytes());
+        let lag = match self.difference_lag {
+            DifferenceLag::Samples(lag) => lag,
+            DifferenceLag::Scaled => 0,
+        };
+        hash.write(&[decay, self.input_mode as u8, lag]);
+        for value in [
+            fraction,
+            duration_ms,
+            self.r_weight,
+            self.r_delay,
+            self.r_timeout,
+            self.r_floor,
+            self.calibration.unwrap_or(0.0),
+            self.latency_compensation_ms,
+            self.refractory_ms,
+            self.dc_blocker.unwrap_or(0.0),
+            self.pace_blanking_ms,
+            self.z_score.unwrap_or(0.0),
+        ] {
+            hash.write(&value.to_bits().to_le_bytes());
+        }
+        hash.write(&[
+            self.snr_adaptive as u8,
+            self.skip_diagnostics as u8,
+            self.calibration.is_some() as u8,
+            self.f64_accumulation as u8,
+            self.dc_blocker.is_some() as u8,
+            self.z_score.is_some() as u8,
+            self.step_blanking as u8,
+            self.features.quality as u8,
+            self.features.hrv as u8,
+            self.features.alloc as u8,
+            self.features.preprocessing as u8,
+        ]);
+        hash.finish()
+    }
+}
+
+impl fmt::Display for ConfigDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decay = match self.m_decay {
+            MDecay::Linear { .. } => "linear",
+            MDecay::Exponential { .. } => "exponential",
+        };
+        write!(
+            f,
+            "version={} mm_depth={} m_decay={}({},{}) r_weight={} r_delay={} r_timeout={} \
+             r_floor={} snr_adaptive={} skip_diagnostics={} f64_accumulation={} \
+             latency_compensation={} rr_depth={} refractory={}",
+            self.version,
+            self.mm_depth,
+            decay,
+            self.m_decay.fraction(),
+            self.m_decay.duration_ms(),
+            self.r_weight,
+            self.r_delay,
+            self.r_timeout,
+            self.r_floor,
+            self.snr_adaptive,
+            self.skip_diagnostics,
+            self.f64_accumulation,
+            self.latency_compensation_ms,
+            self.rr_depth,
+            self.refractory_ms
+        )?;
+        match self.calibration {
+            Some(lsb_per_mv) => write!(f, " calibration={lsb_per_mv}")?,
+            None => write!(f, " calibration=-")?,
+        }
+        match self.dc_blocker {
+            Some(cutoff) => write!(f, " dc_blocker={cutoff}")?,
+            None => write!(f, " dc_blocker=-")?,
+        }
+        let input = match self.input_mode {
+            InputMode::Raw => "raw",
+            InputMode::AbsDiff => "abs_diff",
+            InputMode::SquaredDiff => "squared_diff",
+        };
+        write!(f, " input={input}")?;
+        match self.difference_lag {
+            DifferenceLag::Samples(lag) => write!(f, " difference_lag={lag}")?,
+            DifferenceLag::Scaled => write!(f, " difference_lag=scaled")?,
+        }
+        write!(f, " pace_blanking={}", self.pace_blanking_ms)?;
+        match self.z_score {
+            Some(window_s) => write!(f, " z_score={window_s}")?,
+            None => write!(f, " z_score=-")?,
+        }
+        write!(f, " step_blanking={}", self.step_blanking)?;
+        write!(
+            f,
+            " quality={} hrv={} alloc={} preprocessing={}",
+            self.features.quality,
+            self.features.hrv,
+            self.features.alloc,
+            self.features.preprocessing
+        )
+    }
+}
+
+/// A parameter of [`Config`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Parameter {
+    /// See [`Config::with_mm_depth`].
+    MmDepth,
+    /// See [`Config::with_m_decay`].
+    MDecay,
+    /// See [`Config::with_r_weight`].
+    RWeight,
+    /// See [`Config::with_r_delay`].
+    RDelay,
+    /// See [`Config::with_r_timeout`].
+    RTimeout,
+    /// See [`Config::with_r_floor`].
+    RFloor,
+    /// See [`Config::with_rr_depth`].
+    RrDepth,
+    /// See [`Config::with_refractory_ms`].
+    Refractory,
+    /// See [`Config::with_snr_adaptive`].
+    SnrAdaptive,
+    /// See [`Config::with_skip_diagnostics`].
+    SkipDiagnostics,
+    /// See [`Config::with_calibration`].
+    Calibration,
+    /// See [`Config::with_f64_accumulation`].
+    F64Accumulation,
+    /// See [`Config::with_latency_compensation`].
+    LatencyCompensation,
+    /// See [`Config::with_dc_blocker`].
+    DcBlocker,
+    /// See [`Config::with_input_mode`].
+    InputMode,
+    /// See [`Config::with_difference_lag`].
+    DifferenceLag,
+    /// See [`Config::with_pace_blanking`].
+    PaceBlanking,
+    /// See [`Config::with_z_score`].
+    ZScore,
+    /// See [`Config::with_step_blanking`].
+    StepBlanking,
+}
+
+impl Parameter {
+    /// Every parameter.
+    pub const ALL: [Self; 19] = [
+        Self::MmDepth,
+        Self::MDecay,
+        Self::RWeight,
+        Self::RDelay,
+        Self::RTimeout,
+        Self::RFloor,
+        Self::RrDepth,
+        Self::Refractory,
+        Self::SnrAdaptive,
+        Self::SkipDiagnostics,
+        Self::Calibration,
+        Self::F64Accumulation,
+        Self::LatencyCompensation,
+        Self::DcBlocker,
+        Self::InputMode,
+        Self::DifferenceLag,
+        Self::PaceBlanking,
+        Self::ZScore,
+        Self::StepBlanking,
+    ];
+
+    /// Returns the name of the parameter, as used by the `Display` implementation of
+    /// [`ConfigDescription`].
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::MmDepth => "mm_depth",
+            Self::MDecay => "m_decay",
+            Self::RWeight => "r_weight",
+            Self::RDelay => "r_delay",
+            Self::RTimeout => "r_timeout",
+            Self::RFloor => "r_floor",
+            Self::RrDepth => "rr_depth",
+            Self::Refractory => "refractory",
+            Self::SnrAdaptive => "snr_adaptive",
+            Self::SkipDiagnostics => "skip_diagnostics",
+            Self::Calibration => "calibration",
+            Self::F64Accumulation => "f64_accumulation",
+            Self::LatencyCompensation => "latency_compensation",
+            Self::DcBlocker => "dc_blocker",
+            Self::InputMode => "input",
+            Self::DifferenceLag => "difference_lag",
+            Self::PaceBlanking => "pace_blanking",
+            Self::ZScore => "z_score",
+            Self::StepBlanking => "step_blanking",
+        }
+    }
+}
+
+/// A set of [`Parameter`]s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parameters(u32);
+
+impl Parameters {
+    /// Returns an empty set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns a set of `parameters`.
+    pub const fn of(parameters: &[Parameter]) -> Self {
+        let mut set = Self::empty();
+        let mut i = 0;
+        while i < parameters.len() {
+            set = set.with(parameters[i]);
+            i += 1;
+        }
+        set
+    }
+
+    /// Returns the set extended with `parameter`.
+    pub const fn with(self, parameter: Parameter) -> Self {
+        Self(self.0 | 1 << parameter as u32)
+    }
+
+    /// Returns the parameters that are in either set.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns the parameters that are in both sets.
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Returns `true` if the set contains `parameter`.
+    pub const fn contains(&self, parameter: Parameter) -> bool {
+        self.0 & 1 << parameter as u32 != 0
+    }
+
+    /// Returns `true` if the set is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the parameters in the set.
+    pub fn iter(&self) -> impl Iterator<Item = Parameter> + '_ {
+        Parameter::ALL
+            .into_iter()
+            .filter(|parameter| self.contains(*parameter))
+    }
+}
+
+/// Formats the set as a comma separated list of parameter names, or `-` if the set is empty.
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        for (i, parameter) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", parameter.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of applying a configuration to a running detector, see
+/// [`QrsDetector::apply_config`](crate::QrsDetector::apply_config).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigUpdate {
+    /// The parameters that changed.
+    pub changed: Parameters,
+    /// The changed parameters that required re-initializing a part of the detector. The rest
+    /// were applied without losing the adapted state.
+    pub reinitialized: Parameters,
+}
+
+/// Errors returned by [`Config::from_description`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescriptionError {
+    /// The description was created using an unsupported version.
+    UnsupportedVersion(u16),
+    /// The description was created with a different `quality` feature setting.
+    FeatureMismatch,
+    /// The description contains invalid parameters.
+    Invalid,
+}
+
+impl fmt::Display for DescriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported description version {version}")
+            }
+            Self::FeatureMismatch => write!(f, "the quality feature setting does not match"),
+            Self::Invalid => write!(f, "invalid parameters"),
+        }
+    }
+}
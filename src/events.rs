@@ -0,0 +1,254 @@
+//! Rhythm and signal events.
+
+use core::time::Duration;
+
+/// An event observed while processing the signal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// No QRS complex was detected for longer than [`EventLimits::pause`].
+    Pause,
+    /// A beat with a heart rate below [`EventLimits::bradycardia_bpm`].
+    Bradycardia,
+    /// A beat with a heart rate above [`EventLimits::tachycardia_bpm`].
+    Tachycardia,
+    /// A DC offset step in the signal.
+    SignalStep,
+    /// A refractory period forcibly ended by the watchdog.
+    RefractoryTimeout,
+    /// The signal is flat, see `quality::ArrestDetector`.
+    Asystole,
+    /// Ventricular fibrillation, see `quality::ArrestDetector`.
+    VentricularFibrillation,
+}
+
+impl Event {
+    /// Every event, in declaration order.
+    pub const ALL: [Event; 7] = [
+        Event::Pause,
+        Event::Bradycardia,
+        Event::Tachycardia,
+        Event::SignalStep,
+        Event::RefractoryTimeout,
+        Event::Asystole,
+        Event::VentricularFibrillation,
+    ];
+
+    /// Returns the position of the event in [`Event::ALL`].
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns `true` for rhythms that require immediate attention.
+    pub fn is_critical(&self) -> bool {
+        matches!(self, Event::Asystole | Event::VentricularFibrillation)
+    }
+}
+
+/// Limits used to classify beats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventLimits {
+    /// RR intervals longer than this are reported as [`Event::Pause`]. Default: 2 seconds.
+    pub pause: Duration,
+    /// Default: 50 bpm.
+    pub bradycardia_bpm: f32,
+    /// Default: 100 bpm.
+    pub tachycardia_bpm: f32,
+}
+
+impl Default for EventLimits {
+    fn default() -> Self {
+        Self {
+            pause: Duration::from_secs(2),
+            bradycardia_bpm: 50.0,
+            tachycardia_bpm: 100.0,
+        }
+    }
+}
+
+impl EventLimits {
+    /// Classifies a beat based on the RR interval preceding it.
+    pub fn classify(&self, rr: Duration) -> Option<Event> {
+        let bpm = 60.0 / rr.as_secs_f32();
+
+        if rr > self.pause {
+            Some(Event::Pause)
+        } else if bpm < self.bradycardia_bpm {
+            Some(Event::Bradycardia)
+        } else if bpm > self.tachycardia_bpm {
+            Some(Event::Tachycardia)
+        } else {
+            None
+        }
+    }
+}
+
+/// The number of events of each kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventSummary {
+    pub pauses: u32,
+    pub bradycardia_beats: u32,
+    pub tachycardia_beats: u32,
+    pub signal_steps: u32,
+    pub refractory_timeouts: u32,
+    pub asystoles: u32,
+    pub ventricular_fibrillations: u32,
+}
+
+impl EventSummary {
+    /// Counts `event`.
+    pub fn record(&mut self, event: Event) {
+        let counter = match event {
+            Event::Pause => &mut self.pauses,
+            Event::Bradycardia => &mut self.bradycardia_beats,
+            Event::Tachycardia => &mut self.tachycardia_beats,
+            Event::SignalStep => &mut self.signal_steps,
+            Event::RefractoryTimeout => &mut self.refractory_timeouts,
+            Event::Asystole => &mut self.asystoles,
+            Event::VentricularFibrillation => &mut self.ventricular_fibrillations,
+        };
+        *counter = counter.saturating_add(1);
+    }
+}
+
+/// The number of consecutive observations required to start and to end a debounced event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Debounce {
+    /// The event starts after this many consecutive observations with the event.
+    pub onset: u8,
+    /// The event ends after this many consecutive observations without the event.
+    pub offset: u8,
+}
+
+impl Default for Debounce {
+    /// Events start and end immediately.
+    fn default() -> Self {
+        Self {
+            onset: 1,
+            offset: 1,
+        }
+    }
+}
+
+/// The state of a debounced event.
+#[derive(Clone, Copy, Debug, Default)]
+struct DebounceState {
+    active: bool,
+    /// The number of consecutive observations that contradict the state.
+    count: u8,
+}
+
+/// Debounces events, so that a condition must persist before it is reported, and must be absent
+/// for a while before it ends.
+///
+/// The debouncer is updated once per observation, usually with the classification of every beat,
+/// see [`EventLimits::classify`]. The observations are counted separately for every event.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::events::{Debounce, Event, EventDebouncer};
+///
+/// // Tachycardia starts after 4 fast beats, and ends after 8 beats below the limit
+/// let mut debouncer = EventDebouncer::new()
+///     .with_debounce(Event::Tachycardia, Debounce { onset: 4, offset: 8 });
+///
+/// let beats = [Some(Event::Tachycardia); 4];
+/// let started = beats.map(|beat| debouncer.update(beat));
+///
+/// assert_eq!([None, None, None, Some(Event::Tachycardia)], started);
+/// assert!(debouncer.is_active(Event::Tachycardia));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct EventDebouncer {
+    debounce: [Debounce; Event::ALL.len()],
+    states: [DebounceState; Event::ALL.len()],
+}
+
+impl Default for EventDebouncer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventDebouncer {
+    /// Creates a new debouncer. By default, events start and end immediately.
+    pub fn new() -> Self {
+        Self {
+            debounce: [Debounce::default(); Event::ALL.len()],
+            states: [DebounceState::default(); Event::ALL.len()],
+        }
+    }
+
+    /// Sets the debouncing of `event`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the onset or the offset is 0.
+    pub fn with_debounce(mut self, event: Event, debounce: Debounce) -> Self {
+        assert!(
+            debounce.onset > 0 && debounce.offset > 0,
+            "Onset and offset must be at least 1"
+        );
+        self.debounce[event.index()] = debounce;
+        self
+    }
+
+    /// Returns the debouncing of `event`.
+    pub fn debounce(&self, event: Event) -> Debounce {
+        self.debounce[event.index()]
+    }
+
+    /// Processes an observation, e.g. the classification of a beat. Returns the event if it has
+    /// just started.
+    pub fn update(&mut self, observed: Option<Event>) -> Option<Event> {
+        let mut started = None;
+
+        for ((state, debounce), event) in self
+            .states
+            .iter_mut()
+            .zip(self.debounce.iter())
+            .zip(Event::ALL)
+        {
+            if (observed == Some(event)) == state.active {
+                state.count = 0;
+                continue;
+            }
+
+            state.count += 1;
+            let limit = if state.active {
+                debounce.offset
+            } else {
+                debounce.onset
+            };
+            if state.count >= limit {
+                state.active = !state.active;
+                state.count = 0;
+                if state.active {
+                    started = Some(event);
+                }
+            }
+        }
+
+        started
+    }
+
+    /// Returns `true` if `event` has started and not yet ended.
+    pub fn is_active(&self, event: Event) -> bool {
+        self.states[event.index()].active
+    }
+
+    /// Returns the active events.
+    pub fn active(&self) -> impl Iterator<Item = Event> + '_ {
+        Event::ALL
+            .into_iter()
+            .filter(|&event| self.is_active(event))
+    }
+
+    /// Ends every event, and clears the observation counts.
+    pub fn clear(&mut self) {
+        self.states = [DebounceState::default(); Event::ALL.len()];
+    }
+}
@@ -0,0 +1,220 @@
+//! Export of the detected beats to the formats health platforms ingest, e.g. Apple HealthKit
+//! heartbeat series or Google Fit heart rate data points.
+//!
+//! [`HealthExporter`] collects the beats of a session into a [`HealthExport`]: the start and end
+//! time of the session, the RR intervals in milliseconds, heart rate samples, and the identifier
+//! of the algorithm that detected the beats. The export implements `serde::Serialize`, and can
+//! be written as JSON or CSV without further dependencies.
+//!
+//! ```rust
+//! use qrs_detector::export::HealthExporter;
+//! use qrs_detector::prelude::*;
+//! use qrs_detector::time::EpochAnchor;
+//!
+//! // The session started at 2024-01-01 00:00:00 UTC
+//! let anchor = EpochAnchor::new(500.sps(), 1_704_067_200_000);
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//! let mut exporter =
+//!     HealthExporter::new(anchor).with_algorithm_version(detector.algorithm_version());
+//!
+//! # let samples = [0.0; 10];
+//! for sample in samples {
+//!     if let Some(index) = detector.update(sample) {
+//!         exporter.push_detection(index);
+//!     }
+//! }
+//!
+//! let mut json = Vec::new();
+//! exporter.export().write_json(&mut json)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use core::time::Duration;
+use std::{
+    io::{self, Write},
+    vec::Vec,
+};
+
+use crate::time::EpochAnchor;
+
+/// RR intervals longer than this are not exported, the beats are treated as separated by a gap.
+const MAX_RR_MS: u32 = 3000;
+
+/// A heart rate sample.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HeartRateSample {
+    /// The time of the last beat of the sample, in milliseconds since the epoch.
+    pub time_ms: u64,
+    /// The average heart rate of the beats in the sample, in beats per minute.
+    pub bpm: f32,
+}
+
+/// The beats of a session, see the [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HealthExport {
+    /// The time of the first beat, in milliseconds since the epoch.
+    pub start_time_ms: u64,
+    /// The time of the last beat, in milliseconds since the epoch.
+    pub end_time_ms: u64,
+    /// The RR intervals between consecutive beats, in milliseconds. Intervals spanning a gap
+    /// are left out.
+    pub rr_ms: Vec<u32>,
+    /// The heart rate samples, in chronological order.
+    pub heart_rate: Vec<HeartRateSample>,
+    /// The identifier of the algorithm that detected the beats, see
+    /// [`HealthExporter::with_algorithm_version`].
+    pub algorithm_version: Option<u32>,
+}
+
+impl HealthExport {
+    /// Writes the export as a JSON object. The field names match the `serde` representation.
+    pub fn write_json<W: Write>(&self, mut out: W) -> io::Result<()> {
+        write!(
+            out,
+            "{{\"start_time_ms\":{},\"end_time_ms\":{},\"rr_ms\":[",
+            self.start_time_ms, self.end_time_ms
+        )?;
+        for (i, rr) in self.rr_ms.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(out, "{rr}")?;
+        }
+        write!(out, "],\"heart_rate\":[")?;
+        for (i, sample) in self.heart_rate.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(
+                out,
+                "{{\"time_ms\":{},\"bpm\":{:.1}}}",
+                sample.time_ms, sample.bpm
+            )?;
+        }
+        write!(out, "],\"algorithm_version\":")?;
+        match self.algorithm_version {
+            Some(version) => write!(out, "{version}}}"),
+            None => write!(out, "null}}"),
+        }
+    }
+
+    /// Writes the heart rate samples as CSV, with a `time_ms,bpm` header.
+    pub fn write_heart_rate_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "time_ms,bpm")?;
+        for sample in &self.heart_rate {
+            writeln!(out, "{},{:.1}", sample.time_ms, sample.bpm)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the RR intervals as CSV, with a `rr_ms` header.
+    pub fn write_rr_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "rr_ms")?;
+        for rr in &self.rr_ms {
+            writeln!(out, "{rr}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects detected beats into a [`HealthExport`].
+///
+/// Heart rate samples average the beats of consecutive periods, 5 seconds long by default.
+#[derive(Clone, Debug)]
+pub struct HealthExporter {
+    anchor: EpochAnchor,
+    interval_ms: u64,
+    export: HealthExport,
+    started: bool,
+    /// The time of the previous beat, unless interrupted.
+    prev_ms: Option<u64>,
+    /// The start of the current heart rate period, the number of its intervals and their sum.
+    period: Option<(u64, u32, u64)>,
+}
+
+impl HealthExporter {
+    /// Creates an exporter that converts sample indices to time using `anchor`.
+    pub fn new(anchor: EpochAnchor) -> Self {
+        Self {
+            anchor,
+            interval_ms: 5000,
+            export: HealthExport::default(),
+            started: false,
+            prev_ms: None,
+            period: None,
+        }
+    }
+
+    /// Sets the length of the period a heart rate sample averages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is shorter than 1 millisecond.
+    pub fn with_heart_rate_interval(mut self, interval: Duration) -> Self {
+        assert!(
+            interval.as_millis() > 0,
+            "The heart rate interval must be at least 1 ms"
+        );
+        self.interval_ms = interval.as_millis() as u64;
+        self
+    }
+
+    /// Sets the identifier of the algorithm that detected the beats, see
+    /// [`QrsDetector::algorithm_version`](crate::QrsDetector::algorithm_version).
+    pub fn with_algorithm_version(mut self, version: u32) -> Self {
+        self.export.algorithm_version = Some(version);
+        self
+    }
+
+    /// Records a detection at sample index `index`. Detections must be recorded in order.
+    pub fn push_detection(&mut self, index: u32) {
+        let time_ms = self.anchor.to_epoch_ms(index);
+        if !self.started {
+            self.export.start_time_ms = time_ms;
+            self.started = true;
+        }
+        self.export.end_time_ms = time_ms;
+
+        let rr_ms = self
+            .prev_ms
+            .map(|prev_ms| time_ms.saturating_sub(prev_ms) as u32)
+            .filter(|rr_ms| (1..=MAX_RR_MS).contains(rr_ms));
+        self.prev_ms = Some(time_ms);
+
+        let Some(rr_ms) = rr_ms else {
+            // The first beat after a gap starts a new heart rate period
+            self.period = Some((time_ms, 0, 0));
+            return;
+        };
+        self.export.rr_ms.push(rr_ms);
+
+        let (start_ms, count, sum) = self.period.unwrap_or((time_ms, 0, 0));
+        let (count, sum) = (count + 1, sum + rr_ms as u64);
+        if time_ms.saturating_sub(start_ms) >= self.interval_ms {
+            self.export.heart_rate.push(HeartRateSample {
+                time_ms,
+                bpm: 60_000.0 * count as f32 / sum as f32,
+            });
+            self.period = Some((time_ms, 0, 0));
+        } else {
+            self.period = Some((start_ms, count, sum));
+        }
+    }
+
+    /// Notifies the exporter of a gap in the signal. The interval spanning the gap is not
+    /// exported, and the beats of the unfinished heart rate period are discarded.
+    pub fn interrupt(&mut self) {
+        self.prev_ms = None;
+        self.period = None;
+    }
+
+    /// Returns the collected beats.
+    pub fn export(&self) -> &HealthExport {
+        &self.export
+    }
+
+    /// Returns the collected beats, consuming the exporter.
+    pub fn into_export(self) -> HealthExport {
+        self.export
+    }
+}
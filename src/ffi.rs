@@ -0,0 +1,86 @@
+//! C interface to the detector.
+//!
+//! The detector is allocated on the heap, and C code handles it through an opaque pointer. The
+//! detector uses the default configuration.
+//!
+//! ```c
+//! typedef struct QrsDetector QrsDetector;
+//!
+//! QrsDetector *qrs_detector_new(float sps);
+//! bool qrs_detector_update(QrsDetector *detector, float sample, uint32_t *index);
+//! void qrs_detector_clear(QrsDetector *detector);
+//! void qrs_detector_free(QrsDetector *detector);
+//!
+//! QrsDetector *detector = qrs_detector_new(500.0f);
+//! uint32_t index;
+//! if (qrs_detector_update(detector, sample, &index)) {
+//!     // A QRS complex was detected at `index`
+//! }
+//! qrs_detector_free(detector);
+//! ```
+
+use alloc::boxed::Box;
+use core::ptr;
+
+use crate::{sampling::SamplingFrequencyExt, BufferSizes, QrsDetector};
+
+/// The detector handled by the C interface.
+pub type FfiDetector = QrsDetector<Box<[f32]>, Box<[f32]>>;
+
+/// Creates a detector for signals sampled with `sps` samples per second.
+///
+/// Returns a null pointer if `sps` is not finite, or too low for the 50ms window to hold a
+/// sample. The detector must be released using [`qrs_detector_free`].
+#[no_mangle]
+pub extern "C" fn qrs_detector_new(sps: f32) -> *mut FfiDetector {
+    let fs = sps.sps();
+    if !sps.is_finite() || BufferSizes::for_fs(fs).samples_50 == 0 {
+        return ptr::null_mut();
+    }
+
+    Box::into_raw(Box::new(QrsDetector::new_alloc(fs)))
+}
+
+/// Processes a sample. Returns `true` and writes the sample index to `index` if a QRS complex is
+/// detected. See [`QrsDetector::update`].
+///
+/// # Safety
+///
+/// `detector` must be a pointer returned by [`qrs_detector_new`], not yet freed. `index` must be
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn qrs_detector_update(
+    detector: *mut FfiDetector,
+    sample: f32,
+    index: *mut u32,
+) -> bool {
+    match (*detector).update(sample) {
+        Some(detection) => {
+            index.write(detection);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Resets the detector. See [`QrsDetector::clear`].
+///
+/// # Safety
+///
+/// `detector` must be a pointer returned by [`qrs_detector_new`], not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn qrs_detector_clear(detector: *mut FfiDetector) {
+    (*detector).clear();
+}
+
+/// Releases a detector. Does nothing if `detector` is null.
+///
+/// # Safety
+///
+/// `detector` must be null, or a pointer returned by [`qrs_detector_new`], not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn qrs_detector_free(detector: *mut FfiDetector) {
+    if !detector.is_null() {
+        drop(Box::from_raw(detector));
+    }
+}
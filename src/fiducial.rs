@@ -0,0 +1,264 @@
+//! Fiducial points of the QRS complex.
+//!
+//! The Q and S points are the local extrema bounding the R peak: the turning points where the
+//! signal stops falling away from the peak, on either side of it. Measurements that need the
+//! onset, the offset or the duration of the QRS complex, or that align beats to each other,
+//! should use the same points, so that they are consistent.
+//!
+//! The points are located on a look-back buffer of the signal, see [`HistoryBuffer`]. The
+//! [`multi_rate`](crate::multi_rate) module reports them for every measured beat.
+//!
+//! Relative to the QRS onset, [`FiducialLocator::p_wave`] provides a coarse check for the
+//! presence of a P wave, which is missing during atrial fibrillation.
+//!
+//! ```rust
+//! use qrs_detector::fiducial::FiducialLocator;
+//! use qrs_detector::history::HistoryBuffer;
+//! use qrs_detector::prelude::*;
+//!
+//! let mut signal = HistoryBuffer::new([0.0; 256]);
+//! for sample in [0.0, 0.0, -0.1, -0.2, 0.5, 1.0, 0.4, -0.3, -0.1, 0.0, 0.0] {
+//!     signal.push(sample);
+//! }
+//!
+//! let locator = FiducialLocator::new(500.sps());
+//! let fiducials = locator.locate(&signal, 5, 1.0, None).unwrap();
+//! assert_eq!(Some(3), fiducials.q_index);
+//! assert_eq!(Some(7), fiducials.s_index);
+//! ```
+
+use crate::{history::HistoryBuffer, sampling::SamplingFrequency};
+
+/// The Q point is searched for in this period before the R peak, at 60 bpm.
+pub const Q_WINDOW_MS: f32 = 60.0;
+/// The S point is searched for in this period after the R peak, at 60 bpm.
+pub const S_WINDOW_MS: f32 = 80.0;
+/// The limits of the scaling of the search windows with the RR interval.
+const MIN_WINDOW_SCALE: f32 = 0.75;
+const MAX_WINDOW_SCALE: f32 = 1.25;
+/// Changes smaller than this fraction of the R amplitude are considered noise.
+const TOLERANCE: f32 = 0.02;
+/// The P wave is searched for between these times before the QRS onset.
+pub const P_WINDOW_START_MS: f32 = 250.0;
+pub const P_WINDOW_END_MS: f32 = 120.0;
+/// The P window is not checked if the T wave of the previous beat leaves less than this of it.
+const P_MIN_WINDOW_MS: f32 = 40.0;
+/// The T wave ends this long after the R peak, at 60 bpm. Scales with the square root of the RR
+/// interval (Bazett).
+const T_END_MS: f32 = 450.0;
+/// The baseline is the isoelectric PR segment, this long before the QRS onset.
+const P_BASELINE_MS: f32 = 40.0;
+/// A P wave is present if the energy of the P window is at least this many times the energy of
+/// the baseline.
+const P_PRESENT_RATIO: f32 = 2.0;
+/// The baseline energy is at least this fraction of the R amplitude, squared, so that low
+/// amplitude fibrillatory waves are not taken for P waves on a quiet baseline.
+const P_ENERGY_FLOOR: f32 = 9e-4;
+
+/// The fiducial points of a beat, as sample indices of the look-back buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fiducials {
+    /// The Q point, the onset of the QRS complex. `None` if the signal before the R peak is not
+    /// buffered.
+    pub q_index: Option<u32>,
+    /// The R peak.
+    pub r_index: u32,
+    /// The S point, the offset of the QRS complex. `None` if the signal after the R peak is not
+    /// buffered.
+    pub s_index: Option<u32>,
+}
+
+impl Fiducials {
+    /// Returns the number of samples between the Q and S points.
+    pub fn qrs_samples(&self) -> Option<u32> {
+        Some(self.s_index?.wrapping_sub(self.q_index?))
+    }
+}
+
+/// The result of the P wave presence check, see [`FiducialLocator::p_wave`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PWave {
+    /// Whether a P wave is present before the QRS complex.
+    pub present: bool,
+    /// The confidence of the decision, between 0 (at the decision threshold) and 1.
+    pub confidence: f32,
+    /// The energy of the P window relative to the energy of the baseline.
+    pub energy_ratio: f32,
+}
+
+/// Locates the Q and S points around R peaks.
+///
+/// Starting from the R peak, the signal is followed away from the peak on both sides, as long as
+/// it keeps falling, or rising for negative R peaks. The point is the extremum of the run, or,
+/// on a flat segment, the first sample within the noise tolerance of it. The search windows
+/// scale with the square root of the RR interval, like the QRS complex narrows at higher heart
+/// rates.
+#[derive(Clone, Copy, Debug)]
+pub struct FiducialLocator {
+    fs: SamplingFrequency,
+    q_window_ms: f32,
+    s_window_ms: f32,
+}
+
+impl FiducialLocator {
+    /// Creates a locator for a signal sampled with `fs`, using the default search windows.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            fs,
+            q_window_ms: Q_WINDOW_MS,
+            s_window_ms: S_WINDOW_MS,
+        }
+    }
+
+    /// Sets the search windows before and after the R peak at 60 bpm, in milliseconds.
+    pub fn with_windows_ms(mut self, q_window_ms: f32, s_window_ms: f32) -> Self {
+        self.q_window_ms = q_window_ms;
+        self.s_window_ms = s_window_ms;
+        self
+    }
+
+    /// Returns the search windows before and after the R peak, in samples, for the RR interval
+    /// `rr_ms`. The windows at 60 bpm are used if the RR interval is not known.
+    pub fn windows(&self, rr_ms: Option<f32>) -> (u32, u32) {
+        let scale = rr_ms.map_or(1.0, |rr_ms| {
+            crate::math::sqrt(rr_ms / 1000.0).clamp(MIN_WINDOW_SCALE, MAX_WINDOW_SCALE)
+        });
+        (
+            self.fs.ms_to_samples(self.q_window_ms * scale) as u32,
+            self.fs.ms_to_samples(self.s_window_ms * scale) as u32,
+        )
+    }
+
+    /// Locates the Q and S points around the R peak at `r_index` of `signal`. The sign of
+    /// `amplitude`, the R amplitude relative to the baseline, selects the polarity of the
+    /// complex, and its magnitude sets the noise tolerance.
+    ///
+    /// Returns `None` if the R peak is not buffered.
+    pub fn locate<C>(
+        &self,
+        signal: &HistoryBuffer<f32, C>,
+        r_index: u32,
+        amplitude: f32,
+        rr_ms: Option<f32>,
+    ) -> Option<Fiducials>
+    where
+        C: AsRef<[f32]> + AsMut<[f32]>,
+    {
+        signal.get(r_index)?;
+
+        let (q_window, s_window) = self.windows(rr_ms);
+        let polarity = if amplitude < 0.0 { -1.0 } else { 1.0 };
+        let tolerance = TOLERANCE * amplitude.abs();
+        let turning_point = |forward: bool, window: u32| {
+            let index = |k: u32| {
+                if forward {
+                    r_index.wrapping_add(k)
+                } else {
+                    r_index.wrapping_sub(k)
+                }
+            };
+            let value = |k: u32| signal.get(index(k)).map(|value| value * polarity);
+
+            // Follow the signal away from the peak until it turns back
+            let mut extremum = value(0)?;
+            let mut extremum_k = 0;
+            for k in 1..=window {
+                let value = value(k)?;
+                if value < extremum {
+                    extremum = value;
+                    extremum_k = k;
+                } else if value > extremum + tolerance {
+                    break;
+                }
+            }
+
+            // On a flat segment, the first sample that reached it
+            let k = (1..=extremum_k)
+                .find(|&k| value(k).is_some_and(|value| value <= extremum + tolerance))
+                .unwrap_or(extremum_k);
+            Some(index(k))
+        };
+
+        Some(Fiducials {
+            q_index: turning_point(false, q_window),
+            r_index,
+            s_index: turning_point(true, s_window),
+        })
+    }
+
+    /// Checks for the presence of a P wave before the QRS complex of `fiducials`, with the R
+    /// amplitude `amplitude` and the RR interval `rr_ms`.
+    ///
+    /// The check is coarse: it compares the energy of the signal 120 to 250 ms before the QRS
+    /// onset, where the P wave is expected, to the energy of the isoelectric segment in the last
+    /// 40 ms before the onset, but at least to 3% of the R amplitude. The energies are measured
+    /// around the mean of each window, so that the baseline level does not matter. During atrial
+    /// fibrillation, the low amplitude fibrillatory waves do not reach the energy of a P wave.
+    ///
+    /// At high heart rates, the P window starts after the estimated end of the T wave of the
+    /// previous beat.
+    ///
+    /// Returns `None` if the Q point is not known, the signal before it is not buffered, or the
+    /// previous T wave covers the P window.
+    pub fn p_wave<C>(
+        &self,
+        signal: &HistoryBuffer<f32, C>,
+        fiducials: &Fiducials,
+        amplitude: f32,
+        rr_ms: Option<f32>,
+    ) -> Option<PWave>
+    where
+        C: AsRef<[f32]> + AsMut<[f32]>,
+    {
+        let onset = fiducials.q_index?;
+        let ms = |ms: f32| self.fs.ms_to_samples(ms) as u32;
+
+        // Samples from the onset back to the start of the P window
+        let mut window_start = ms(P_WINDOW_START_MS);
+        if let Some(rr_ms) = rr_ms {
+            let t_end_ms = T_END_MS * crate::math::sqrt(rr_ms / 1000.0);
+            let previous_t_end = fiducials
+                .r_index
+                .wrapping_sub(ms(rr_ms))
+                .wrapping_add(ms(t_end_ms));
+            let after_t_end = onset.wrapping_sub(previous_t_end) as i32;
+            window_start = window_start.min(after_t_end.max(0) as u32);
+        }
+        if window_start < ms(P_WINDOW_END_MS) + ms(P_MIN_WINDOW_MS) {
+            return None;
+        }
+
+        let energy = |start: u32, end: u32| {
+            let count = end.wrapping_sub(start).max(1) as f32;
+            let mean = signal.range(start, end)?.sum::<f32>() / count;
+            let energy = signal
+                .range(start, end)?
+                .map(|value| (value - mean) * (value - mean))
+                .sum::<f32>()
+                / count;
+            Some(energy)
+        };
+
+        let p_energy = energy(
+            onset.wrapping_sub(window_start),
+            onset.wrapping_sub(ms(P_WINDOW_END_MS)),
+        )?;
+        let baseline_energy = energy(onset.wrapping_sub(ms(P_BASELINE_MS)), onset)?
+            .max(P_ENERGY_FLOOR * amplitude * amplitude);
+        let energy_ratio = p_energy / baseline_energy;
+
+        let present = energy_ratio >= P_PRESENT_RATIO;
+        let confidence = if present {
+            1.0 - P_PRESENT_RATIO / energy_ratio
+        } else {
+            1.0 - energy_ratio / P_PRESENT_RATIO
+        };
+        Some(PWave {
+            present,
+            confidence,
+            energy_ratio,
+        })
+    }
+}
@@ -0,0 +1,188 @@
+//! Streaming histogram of the signal amplitude, for remote threshold tuning.
+//!
+//! The histogram shows how the detection thresholds sit relative to the distribution of the
+//! processed signal, e.g. a threshold below most of the noise floor, or above most QRS peaks.
+//! Record it using [`QrsDetector::with_amplitude_histogram`] and retrieve it using
+//! [`QrsDetector::amplitude_histogram`].
+//!
+//! ```rust
+//! use qrs_detector::histogram::AmplitudeHistogram;
+//! use qrs_detector::prelude::*;
+//!
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps())
+//!     .with_amplitude_histogram(AmplitudeHistogram::new(0.001));
+//!
+//! # let samples = [0.0; 10];
+//! for sample in samples {
+//!     detector.update(sample);
+//! }
+//!
+//! if let (Some(histogram), Some(threshold)) =
+//!     (detector.amplitude_histogram(), detector.thresholds().total())
+//! {
+//!     let above = histogram.fraction_above(threshold);
+//!     // Send `histogram.counts()` and `above` as telemetry
+//! }
+//! ```
+//!
+//! [`QrsDetector::with_amplitude_histogram`]: crate::QrsDetector::with_amplitude_histogram
+//! [`QrsDetector::amplitude_histogram`]: crate::QrsDetector::amplitude_histogram
+
+/// The number of bins of [`AmplitudeHistogram`], including the underflow and overflow bins.
+pub const HISTOGRAM_BINS: usize = 24;
+
+/// The mantissa bits of `sqrt(2)`, which splits an octave into two bins.
+const HALF_OCTAVE_MANTISSA: u32 = 0x0035_04F3;
+
+/// A histogram of the absolute sample values, with half-octave (3 dB) wide bins.
+///
+/// Bin 0 counts the samples below the lowest bin edge, including 0. Bin `k` between 1 and
+/// `HISTOGRAM_BINS - 2` counts the samples in `lowest * 2^((k - 1) / 2)..lowest * 2^(k / 2)`,
+/// covering 11 octaves. The last bin counts the larger samples. NaN samples are not counted.
+///
+/// The counts are 16 bit wide. When a bin is full, every count is halved, so that the histogram
+/// follows the recent signal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmplitudeHistogram {
+    /// The lower edge of bin 1.
+    lowest: f32,
+    counts: [u16; HISTOGRAM_BINS],
+}
+
+impl AmplitudeHistogram {
+    /// Creates an empty histogram whose first bin starts at `lowest`.
+    ///
+    /// `lowest` should be around the noise floor of the processed signal, e.g. a few LSBs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lowest` is not positive and finite.
+    pub fn new(lowest: f32) -> Self {
+        assert!(
+            lowest.is_normal() && lowest > 0.0,
+            "The lowest bin edge must be positive"
+        );
+        Self {
+            lowest,
+            counts: [0; HISTOGRAM_BINS],
+        }
+    }
+
+    /// Returns the lower edge of bin 1.
+    pub fn lowest(&self) -> f32 {
+        self.lowest
+    }
+
+    /// Adds a sample to the histogram.
+    pub fn add(&mut self, sample: f32) {
+        if sample.is_nan() {
+            return;
+        }
+
+        let bin = self.bin(sample);
+        if self.counts[bin] == u16::MAX {
+            for count in self.counts.iter_mut() {
+                *count /= 2;
+            }
+        }
+        self.counts[bin] += 1;
+    }
+
+    /// Returns the bin `sample` belongs to.
+    pub fn bin(&self, sample: f32) -> usize {
+        let ratio = sample.abs() / self.lowest();
+        if ratio.is_nan() || ratio < 1.0 {
+            return 0;
+        }
+        if !ratio.is_finite() {
+            return HISTOGRAM_BINS - 1;
+        }
+
+        // The exponent is the octave, the mantissa selects its half
+        let bits = ratio.to_bits();
+        let octave = (bits >> 23) as usize - 127;
+        let upper_half = (bits & 0x007F_FFFF) >= HALF_OCTAVE_MANTISSA;
+        (1 + 2 * octave + upper_half as usize).min(HISTOGRAM_BINS - 1)
+    }
+
+    /// Returns the lower and upper edge of `bin`. The underflow bin starts at 0, the overflow bin
+    /// ends at infinity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bin` is not less than [`HISTOGRAM_BINS`].
+    pub fn bin_edges(&self, bin: usize) -> (f32, f32) {
+        assert!(bin < HISTOGRAM_BINS, "Bin index out of range");
+
+        let edge = |k: usize| {
+            let octave = (1u32 << (k / 2)) as f32;
+            match k & 1 {
+                0 => self.lowest() * octave,
+                _ => self.lowest() * octave * core::f32::consts::SQRT_2,
+            }
+        };
+
+        match bin {
+            0 => (0.0, self.lowest()),
+            _ if bin == HISTOGRAM_BINS - 1 => (edge(bin - 1), f32::INFINITY),
+            _ => (edge(bin - 1), edge(bin)),
+        }
+    }
+
+    /// Returns the counts of the bins.
+    pub fn counts(&self) -> &[u16; HISTOGRAM_BINS] {
+        &self.counts
+    }
+
+    /// Returns the number of counted samples. Older samples are weighted less after the counts
+    /// are halved.
+    pub fn total(&self) -> u32 {
+        self.counts.iter().map(|&count| count as u32).sum()
+    }
+
+    /// Returns the fraction of the samples in the bins above the bin of `value`, between 0 and 1.
+    /// Returns 0 for an empty histogram.
+    pub fn fraction_above(&self, value: f32) -> f32 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+
+        let above = self.counts[self.bin(value) + 1..]
+            .iter()
+            .map(|&count| count as u32)
+            .sum::<u32>();
+        above as f32 / total as f32
+    }
+
+    /// Returns the upper edge of the bin that contains the `p` quantile of the samples, e.g. 0.5
+    /// for the median. Returns `None` for an empty histogram.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not between 0 and 1.
+    pub fn quantile(&self, p: f32) -> Option<f32> {
+        assert!((0.0..=1.0).contains(&p), "p must be between 0 and 1");
+
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+
+        let target = p * total as f32;
+        let mut cumulative = 0;
+        for (bin, &count) in self.counts.iter().enumerate() {
+            cumulative += count as u32;
+            if count > 0 && cumulative as f32 >= target {
+                return Some(self.bin_edges(bin).1);
+            }
+        }
+        Some(f32::INFINITY)
+    }
+
+    /// Removes every sample from the histogram.
+    pub fn clear(&mut self) {
+        self.counts = [0; HISTOGRAM_BINS];
+    }
+}
@@ -0,0 +1,157 @@
+//! Random-access look-back buffer for the recent signal.
+//!
+//! Beat measurements need to look back at the signal around an earlier detection. A
+//! [`HistoryBuffer`] keeps the most recent samples in caller-provided storage, and addresses them
+//! by their absolute sample index, so that indices reported by the detector can be used directly.
+
+use core::marker::PhantomData;
+
+/// Keeps the most recent values in a ring buffer, addressed by absolute index.
+///
+/// The capacity of the storage must be a power of two. This makes addressing a simple bit mask,
+/// and keeps it correct when the 32 bit index wraps around.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::history::HistoryBuffer;
+///
+/// let mut history = HistoryBuffer::new([0.0; 4]);
+///
+/// for sample in [1.0, 2.0, 3.0, 4.0, 5.0] {
+///     history.push(sample);
+/// }
+///
+/// assert_eq!(None, history.get(0));
+/// assert_eq!(Some(2.0), history.get(1));
+/// assert_eq!(Some(5.0), history.get(4));
+/// assert_eq!(None, history.get(5));
+/// ```
+#[derive(Clone)]
+pub struct HistoryBuffer<T, C> {
+    buffer: C,
+    /// The absolute index of the next value.
+    next: u32,
+    len: u32,
+    _marker: PhantomData<T>,
+}
+
+impl<T, C> HistoryBuffer<T, C>
+where
+    T: Copy,
+    C: AsRef<[T]> + AsMut<[T]>,
+{
+    /// Creates a new history buffer using `buffer` as the storage. The first value pushed has the
+    /// index 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `buffer` is not a power of two.
+    pub fn new(buffer: C) -> Self {
+        Self::starting_at(buffer, 0)
+    }
+
+    /// Creates a new history buffer using `buffer` as the storage. The first value pushed has the
+    /// index `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `buffer` is not a power of two.
+    pub fn starting_at(buffer: C, index: u32) -> Self {
+        assert!(
+            buffer.as_ref().len().is_power_of_two() && buffer.as_ref().len() <= 1 << 31,
+            "The capacity of the history buffer must be a power of two"
+        );
+
+        Self {
+            buffer,
+            next: index,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Discards the stored values. The indices of the new values continue from the current
+    /// index.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the number of values the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.buffer.as_ref().len()
+    }
+
+    /// Returns the number of stored values.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Returns `true` if no values are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the index the next pushed value will have.
+    pub fn next_index(&self) -> u32 {
+        self.next
+    }
+
+    /// Returns the index of the oldest stored value.
+    pub fn oldest_index(&self) -> u32 {
+        self.next.wrapping_sub(self.len)
+    }
+
+    /// Stores `value`, overwriting the oldest value if the buffer is full. Returns the index of
+    /// the value.
+    pub fn push(&mut self, value: T) -> u32 {
+        let index = self.next;
+        let mask = self.mask();
+        self.buffer.as_mut()[index as usize & mask] = value;
+
+        self.next = index.wrapping_add(1);
+        if (self.len as usize) < self.capacity() {
+            self.len += 1;
+        }
+
+        index
+    }
+
+    /// Returns the value at `index`, if it is still stored.
+    pub fn get(&self, index: u32) -> Option<T> {
+        if self.contains(index) {
+            Some(self.buffer.as_ref()[index as usize & self.mask()])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the most recent value.
+    pub fn newest(&self) -> Option<T> {
+        self.get(self.next.wrapping_sub(1))
+    }
+
+    /// Returns `true` if the value at `index` is still stored.
+    pub fn contains(&self, index: u32) -> bool {
+        self.next.wrapping_sub(index).wrapping_sub(1) < self.len
+    }
+
+    /// Iterates over the values with indices in `start..end`. Returns `None` if any of them is
+    /// not stored.
+    pub fn range(&self, start: u32, end: u32) -> Option<impl Iterator<Item = T> + '_> {
+        let count = end.wrapping_sub(start);
+        if count > self.len
+            || (count > 0 && !(self.contains(start) && self.contains(end.wrapping_sub(1))))
+        {
+            return None;
+        }
+
+        Some(
+            (0..count)
+                .map(move |i| self.buffer.as_ref()[start.wrapping_add(i) as usize & self.mask()]),
+        )
+    }
+
+    fn mask(&self) -> usize {
+        self.capacity() - 1
+    }
+}
@@ -0,0 +1,233 @@
+//! Heart rate estimation from detected QRS complexes.
+
+use core::time::Duration;
+
+use crate::{sampling::SamplingFrequency, sliding::SlidingWindow};
+
+/// RR intervals deviating from the robust estimate by this fraction have their weight halved.
+const PLAUSIBLE_DEVIATION: f32 = 0.15;
+
+/// Selects how [`RateTracker::display_heart_rate`] calculates the heart rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RatePolicy {
+    /// The average of the last `N` RR intervals, see [`RateTracker::heart_rate`].
+    #[default]
+    Average,
+    /// The median of the last `intervals` RR intervals, see [`RateTracker::median_heart_rate`].
+    Median {
+        /// The number of RR intervals, at most `N`.
+        intervals: usize,
+    },
+    /// The plausibility-weighted average of the last `N` RR intervals, see
+    /// [`RateTracker::robust_heart_rate`].
+    Robust,
+}
+
+/// Calculates the heart rate from the last `N` RR intervals.
+///
+/// Besides the plain average, the tracker provides robust heart rates, see
+/// [`RateTracker::robust_heart_rate`] and [`RateTracker::median_heart_rate`]. The value to be
+/// displayed can be selected at runtime using [`RatePolicy`].
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::hr::RateTracker;
+/// use qrs_detector::sampling::*;
+///
+/// let mut tracker = RateTracker::<4>::new(500.sps());
+///
+/// // Detections every 500 samples
+/// for idx in [1000, 1500, 2000] {
+///     tracker.push_detection(idx);
+/// }
+///
+/// assert_eq!(Some(60.0), tracker.heart_rate());
+/// ```
+pub struct RateTracker<const N: usize = 8> {
+    fs: SamplingFrequency,
+    rr: SlidingWindow<u32, [u32; N]>,
+    weights: SlidingWindow<f32, [f32; N]>,
+    prev_idx: Option<u32>,
+    policy: RatePolicy,
+}
+
+impl<const N: usize> RateTracker<N> {
+    /// Creates a new heart rate tracker for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            fs,
+            rr: SlidingWindow::default(),
+            weights: SlidingWindow::default(),
+            prev_idx: None,
+            policy: RatePolicy::Average,
+        }
+    }
+
+    /// Sets the policy used by [`RateTracker::display_heart_rate`].
+    pub fn with_policy(mut self, policy: RatePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Changes the policy used by [`RateTracker::display_heart_rate`].
+    pub fn set_policy(&mut self, policy: RatePolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns the policy used by [`RateTracker::display_heart_rate`].
+    pub fn policy(&self) -> RatePolicy {
+        self.policy
+    }
+
+    /// Resets the internal state of the tracker.
+    pub fn clear(&mut self) {
+        self.rr.clear();
+        self.weights.clear();
+        self.prev_idx = None;
+    }
+
+    /// Records a detection at sample index `idx`. Returns the RR interval, in samples, if this is
+    /// not the first detection.
+    pub fn push_detection(&mut self, idx: u32) -> Option<u32> {
+        self.push_detection_with_confidence(idx, 1.0)
+    }
+
+    /// Records a detection at sample index `idx`, with the given confidence between 0 and 1 (see
+    /// [`Detection::confidence`](crate::Detection::confidence)). Returns the RR interval, in
+    /// samples, if this is not the first detection.
+    ///
+    /// The confidence is used to weight the RR interval in the robust heart rate.
+    pub fn push_detection_with_confidence(&mut self, idx: u32, confidence: f32) -> Option<u32> {
+        let rr = self.prev_idx.map(|prev| idx.wrapping_sub(prev));
+        if let Some(rr) = rr {
+            let weight = confidence.clamp(0.0, 1.0) * self.plausibility(rr);
+            self.rr.push(rr);
+            self.weights.push(weight);
+        }
+        self.prev_idx = Some(idx);
+
+        rr
+    }
+
+    /// Returns how well `rr` fits the current robust estimate, between 0 and 1.
+    fn plausibility(&self, rr: u32) -> f32 {
+        match self.robust_rr() {
+            Some(estimate) => {
+                let deviation = (rr as f32 - estimate) / (PLAUSIBLE_DEVIATION * estimate);
+                1.0 / (1.0 + deviation * deviation)
+            }
+            None => 1.0,
+        }
+    }
+
+    /// Returns the weighted average of the RR intervals, in samples.
+    fn robust_rr(&self) -> Option<f32> {
+        let (weighted_sum, weight_sum) = self
+            .rr
+            .iter_unordered()
+            .zip(self.weights.iter_unordered())
+            .fold((0.0, 0.0), |(sum, weights), (rr, weight)| {
+                (sum + rr as f32 * weight, weights + weight)
+            });
+
+        (weight_sum > 0.0).then(|| weighted_sum / weight_sum)
+    }
+
+    /// Returns the last RR interval.
+    pub fn last_rr(&self) -> Option<Duration> {
+        self.rr.last().map(|rr| self.to_duration(rr))
+    }
+
+    /// Returns the heart rate in beats per minute, averaged over the last `N` RR intervals.
+    ///
+    /// Returns `None` until at least one RR interval is available.
+    pub fn heart_rate(&self) -> Option<f32> {
+        if self.rr.is_empty() {
+            return None;
+        }
+
+        let rr_sum: u32 = self.rr.iter_unordered().sum();
+        let rr_avg = self.fs.samples_to_s(rr_sum as usize) / self.rr.len() as f32;
+
+        Some(60.0 / rr_avg)
+    }
+
+    /// Returns the heart rate in beats per minute, calculated from the last `N` RR intervals
+    /// weighted by their plausibility and the confidence of their detection.
+    ///
+    /// RR intervals that deviate from the current estimate, e.g. because a beat was missed or an
+    /// artifact was detected as a beat, have a small weight. The estimate follows a real change
+    /// in the heart rate within `N` beats.
+    ///
+    /// ```rust
+    /// use qrs_detector::hr::RateTracker;
+    /// use qrs_detector::sampling::*;
+    ///
+    /// let mut tracker = RateTracker::<8>::new(500.sps());
+    ///
+    /// // Detections every 500 samples, with a missed beat at 3500
+    /// for idx in [0, 500, 1000, 1500, 2000, 2500, 3000, 4000] {
+    ///     tracker.push_detection(idx);
+    /// }
+    ///
+    /// assert!(tracker.heart_rate().unwrap() < 55.0);
+    /// assert!((tracker.robust_heart_rate().unwrap() - 60.0).abs() < 1.0);
+    /// ```
+    pub fn robust_heart_rate(&self) -> Option<f32> {
+        let rr = self.robust_rr()?;
+        Some(60.0 * self.fs.raw() / rr)
+    }
+
+    /// Returns the heart rate in beats per minute, calculated from the median of the last
+    /// `intervals` RR intervals. `intervals` is limited to `N`.
+    ///
+    /// ```rust
+    /// use qrs_detector::hr::RateTracker;
+    /// use qrs_detector::sampling::*;
+    ///
+    /// let mut tracker = RateTracker::<8>::new(500.sps());
+    ///
+    /// // Detections every 500 samples, with an extra detection at 3250
+    /// for idx in [0, 500, 1000, 1500, 2000, 2500, 3000, 3250, 3500] {
+    ///     tracker.push_detection(idx);
+    /// }
+    ///
+    /// assert_eq!(Some(60.0), tracker.median_heart_rate(5));
+    /// ```
+    pub fn median_heart_rate(&self, intervals: usize) -> Option<f32> {
+        let count = intervals.min(self.rr.len());
+        if count == 0 {
+            return None;
+        }
+
+        let mut rr = [0; N];
+        let rr = &mut rr[..count];
+        for (i, slot) in rr.iter_mut().enumerate() {
+            *slot = self.rr.nth_newest(i)?;
+        }
+        rr.sort_unstable();
+
+        let median = if count % 2 == 1 {
+            rr[count / 2] as f32
+        } else {
+            (rr[count / 2 - 1] as f32 + rr[count / 2] as f32) / 2.0
+        };
+
+        Some(60.0 * self.fs.raw() / median)
+    }
+
+    /// Returns the heart rate in beats per minute, calculated as selected by the
+    /// [`RatePolicy`].
+    pub fn display_heart_rate(&self) -> Option<f32> {
+        match self.policy {
+            RatePolicy::Average => self.heart_rate(),
+            RatePolicy::Median { intervals } => self.median_heart_rate(intervals),
+            RatePolicy::Robust => self.robust_heart_rate(),
+        }
+    }
+
+    fn to_duration(&self, samples: u32) -> Duration {
+        Duration::from_secs_f32(self.fs.samples_to_s(samples as usize))
+    }
+}
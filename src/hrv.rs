@@ -0,0 +1,92 @@
+//! Time-domain heart rate variability metrics.
+
+use crate::math::sqrt;
+
+/// Heart rate variability metrics.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HrvMetrics {
+    /// Standard deviation of the RR intervals, in milliseconds.
+    pub sdnn_ms: f32,
+    /// Root mean square of successive RR interval differences, in milliseconds.
+    pub rmssd_ms: f32,
+}
+
+/// Calculates heart rate variability metrics over every RR interval pushed into it.
+///
+/// The metrics are calculated incrementally, without storing the RR intervals.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::hrv::HrvAnalyzer;
+///
+/// let mut hrv = HrvAnalyzer::new();
+/// for rr_ms in [800.0, 850.0, 800.0, 850.0] {
+///     hrv.push_rr(rr_ms);
+/// }
+///
+/// let metrics = hrv.metrics().unwrap();
+/// assert!((metrics.rmssd_ms - 50.0).abs() < 0.001);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HrvAnalyzer {
+    count: u32,
+    mean: f32,
+    m2: f32,
+    prev_rr: Option<f32>,
+    successive_sq_sum: f32,
+}
+
+impl HrvAnalyzer {
+    /// Creates a new, empty analyzer.
+    pub const fn new() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            prev_rr: None,
+            successive_sq_sum: 0.0,
+        }
+    }
+
+    /// Resets the analyzer.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Records an RR interval, in milliseconds.
+    pub fn push_rr(&mut self, rr_ms: f32) {
+        // Welford's online variance algorithm
+        self.count += 1;
+        let delta = rr_ms - self.mean;
+        self.mean += delta / self.count as f32;
+        self.m2 += delta * (rr_ms - self.mean);
+
+        if let Some(prev) = self.prev_rr.replace(rr_ms) {
+            let diff = rr_ms - prev;
+            self.successive_sq_sum += diff * diff;
+        }
+    }
+
+    /// Returns the number of recorded RR intervals.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the mean RR interval in milliseconds, if an RR interval has been recorded.
+    pub fn mean_rr_ms(&self) -> Option<f32> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Returns the metrics, if at least two RR intervals have been recorded.
+    pub fn metrics(&self) -> Option<HrvMetrics> {
+        if self.count < 2 {
+            return None;
+        }
+
+        let sdnn_ms = sqrt(self.m2 / (self.count - 1) as f32);
+        let rmssd_ms = sqrt(self.successive_sq_sum / (self.count - 1) as f32);
+
+        Some(HrvMetrics { sdnn_ms, rmssd_ms })
+    }
+}
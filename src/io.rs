@@ -0,0 +1,293 @@
+//! Output of the detection results in formats consumed by analysis pipelines.
+//!
+//! [`BeatCsvWriter`] writes one row per beat, with a selectable set of [`Column`]s:
+//!
+//! ```rust
+//! use qrs_detector::io::{BeatCsvWriter, Column};
+//! use qrs_detector::prelude::*;
+//!
+//! let mut detector =
+//!     QrsDetector::new::<150, 25>(500.sps()).with_history([Detection::default(); 1]);
+//! let mut writer = BeatCsvWriter::new(Vec::new(), 500.sps())
+//!     .with_columns(&[Column::Index, Column::Rr, Column::HeartRate, Column::AlgorithmVersion])
+//!     .with_algorithm_version(detector.algorithm_version());
+//!
+//! # let samples = [0.0; 10];
+//! for sample in samples {
+//!     if detector.update(sample).is_some() {
+//!         let detection = detector.recent_detections().last().unwrap();
+//!         writer.write_detection(&detection)?;
+//!     }
+//! }
+//!
+//! let csv = String::from_utf8(writer.into_inner()).unwrap();
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::{
+    io::{self, Write},
+    vec::Vec,
+};
+
+use crate::{annotation::BeatClass, sampling::SamplingFrequency, Detection};
+
+/// A column of [`BeatCsvWriter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    /// The sample index of the detection.
+    Index,
+    /// The time of the detection, in seconds since sample 0.
+    Time,
+    /// The RR interval ending at the beat, in milliseconds. Empty for the first beat.
+    Rr,
+    /// The instantaneous heart rate calculated from the RR interval, in beats per minute. Empty
+    /// for the first beat.
+    HeartRate,
+    /// The confidence of the detection, see [`Detection::confidence`].
+    Confidence,
+    /// The application-defined quality flags of the beat, see [`Beat::with_flags`].
+    QualityFlags,
+    /// The ST level of the beat, see [`Beat::with_st_level`]. Empty if not measured.
+    StLevel,
+    /// `1` for paced beats, `0` otherwise, see [`Detection::paced`].
+    Paced,
+    /// The reasons the detector altered its behavior around the beat, separated by `|`. Empty
+    /// if there are none, see [`Detection::reasons`].
+    Reasons,
+    /// The timing correction of the beat, in milliseconds, see
+    /// [`Detection::timing_correction`].
+    TimingCorrection,
+    /// The EC57 class of the beat as its WFDB annotation mnemonic, e.g. `N`, see
+    /// [`Beat::with_class`]. Empty if not classified.
+    Class,
+    /// The identifier of the algorithm that detected the beat, see
+    /// [`BeatCsvWriter::with_algorithm_version`]. Empty if not set.
+    AlgorithmVersion,
+}
+
+impl Column {
+    /// The columns written by default.
+    pub const DEFAULT: [Self; 5] = [
+        Self::Index,
+        Self::Time,
+        Self::Rr,
+        Self::HeartRate,
+        Self::Confidence,
+    ];
+
+    /// Returns the header of the column.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Index => "index",
+            Self::Time => "time_s",
+            Self::Rr => "rr_ms",
+            Self::HeartRate => "hr_bpm",
+            Self::Confidence => "confidence",
+            Self::QualityFlags => "flags",
+            Self::StLevel => "st_level",
+            Self::Paced => "paced",
+            Self::Reasons => "reasons",
+            Self::TimingCorrection => "timing_correction_ms",
+            Self::Class => "class",
+            Self::AlgorithmVersion => "algorithm_version",
+        }
+    }
+}
+
+/// A beat written by [`BeatCsvWriter`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Beat {
+    /// The detection of the beat.
+    pub detection: Detection,
+    /// Application-defined quality flags.
+    pub flags: u8,
+    /// The ST level of the beat, e.g. measured by
+    /// [`MultiRateDetector`](crate::multi_rate::MultiRateDetector).
+    pub st_level: Option<f32>,
+    /// The class of the beat.
+    pub class: Option<BeatClass>,
+}
+
+impl Beat {
+    /// Creates a beat without flags, ST level and class.
+    pub const fn new(detection: Detection) -> Self {
+        Self {
+            detection,
+            flags: 0,
+            st_level: None,
+            class: None,
+        }
+    }
+
+    /// Sets the quality flags of the beat.
+    pub const fn with_flags(self, flags: u8) -> Self {
+        Self { flags, ..self }
+    }
+
+    /// Sets the ST level of the beat.
+    pub const fn with_st_level(self, st_level: f32) -> Self {
+        Self {
+            st_level: Some(st_level),
+            ..self
+        }
+    }
+
+    /// Sets the class of the beat.
+    pub const fn with_class(self, class: BeatClass) -> Self {
+        Self {
+            class: Some(class),
+            ..self
+        }
+    }
+}
+
+/// Writes detected beats as comma separated values, one row per beat.
+///
+/// The header row is written before the first beat. Values that are not available are left
+/// empty.
+pub struct BeatCsvWriter<W: Write> {
+    out: W,
+    fs: SamplingFrequency,
+    columns: Vec<Column>,
+    algorithm_version: Option<u32>,
+    prev_index: Option<u32>,
+    header_written: bool,
+}
+
+impl<W: Write> BeatCsvWriter<W> {
+    /// Creates a writer of beats detected in a signal sampled with `fs`, writing the
+    /// [`Column::DEFAULT`] columns into `out`.
+    pub fn new(out: W, fs: SamplingFrequency) -> Self {
+        Self {
+            out,
+            fs,
+            columns: Column::DEFAULT.to_vec(),
+            algorithm_version: None,
+            prev_index: None,
+            header_written: false,
+        }
+    }
+
+    /// Selects the columns to write, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `columns` is empty.
+    pub fn with_columns(mut self, columns: &[Column]) -> Self {
+        assert!(!columns.is_empty(), "At least one column must be selected");
+        self.columns = columns.to_vec();
+        self
+    }
+
+    /// Returns the selected columns.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Sets the identifier of the algorithm written in [`Column::AlgorithmVersion`], see
+    /// [`QrsDetector::algorithm_version`](crate::QrsDetector::algorithm_version).
+    pub fn with_algorithm_version(mut self, version: u32) -> Self {
+        self.algorithm_version = Some(version);
+        self
+    }
+
+    /// Writes a row for `detection`, without flags, ST level and class.
+    pub fn write_detection(&mut self, detection: &Detection) -> io::Result<()> {
+        self.write_beat(&Beat::new(*detection))
+    }
+
+    /// Writes a row for `beat`. Beats must be written in the order of their detection.
+    pub fn write_beat(&mut self, beat: &Beat) -> io::Result<()> {
+        if !self.header_written {
+            self.write_header()?;
+        }
+
+        let index = beat.detection.index;
+        let rr_ms = self
+            .prev_index
+            .map(|prev| self.fs.samples_to_s(index.wrapping_sub(prev) as usize) * 1000.0);
+        self.prev_index = Some(index);
+
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                write!(self.out, ",")?;
+            }
+            match column {
+                Column::Index => write!(self.out, "{index}")?,
+                Column::Time => {
+                    let time = index as f64 / self.fs.raw() as f64;
+                    write!(self.out, "{time:.3}")?
+                }
+                Column::Rr => {
+                    if let Some(rr_ms) = rr_ms {
+                        write!(self.out, "{rr_ms:.1}")?
+                    }
+                }
+                Column::HeartRate => {
+                    if let Some(rr_ms) = rr_ms.filter(|rr_ms| *rr_ms > 0.0) {
+                        write!(self.out, "{:.1}", 60_000.0 / rr_ms)?
+                    }
+                }
+                Column::Confidence => write!(self.out, "{:.3}", beat.detection.confidence())?,
+                Column::QualityFlags => write!(self.out, "{}", beat.flags)?,
+                Column::StLevel => {
+                    if let Some(st_level) = beat.st_level {
+                        write!(self.out, "{st_level}")?
+                    }
+                }
+                Column::Paced => write!(self.out, "{}", beat.detection.paced as u8)?,
+                Column::Reasons => {
+                    for (i, reason) in beat.detection.reasons.iter().enumerate() {
+                        if i > 0 {
+                            write!(self.out, "|")?;
+                        }
+                        write!(self.out, "{}", reason.name())?;
+                    }
+                }
+                Column::TimingCorrection => {
+                    let correction_ms = beat.detection.timing_correction * 1000.0 / self.fs.raw();
+                    write!(self.out, "{correction_ms:.2}")?
+                }
+                Column::Class => {
+                    if let Some(class) = beat.class {
+                        write!(self.out, "{class}")?
+                    }
+                }
+                Column::AlgorithmVersion => {
+                    if let Some(version) = self.algorithm_version {
+                        write!(self.out, "{version}")?
+                    }
+                }
+            }
+        }
+        writeln!(self.out)
+    }
+
+    /// Writes the header row. Called automatically before the first beat.
+    fn write_header(&mut self) -> io::Result<()> {
+        let names = self
+            .columns
+            .iter()
+            .map(Column::name)
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(self.out, "{names}")?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    /// Forgets the previous beat, e.g. after a gap in the signal. The next row has no RR interval.
+    pub fn interrupt(&mut self) {
+        self.prev_index = None;
+    }
+
+    /// Flushes the output.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    /// Returns the output.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
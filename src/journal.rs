@@ -0,0 +1,447 @@
+//! Incremental journaling of the detector state, to restore it after a power loss.
+//!
+//! Writing the complete detector state to non-volatile memory every few seconds wears out flash
+//! and takes long on slow EEPROM. Instead, [`JournalWriter`] encodes the adapted state of the
+//! detector into small records that only contain the changes since the previous record. Every
+//! few records, a keyframe containing the complete state is written. After a brown-out,
+//! [`JournalReader`] replays the records and [`QrsDetector::restore_journal`] restores the
+//! detector.
+//!
+//! At most the changes since the last written record are lost. A record that was only partially
+//! written, or was corrupted in storage, is detected by its CRC-32 and ignored, along with the
+//! records following it. Records that pass the checksum are also checked for values the detector
+//! could not have produced, so a damaged journal never restores a wrong state.
+//!
+//! # Compatibility
+//!
+//! Every record starts with its version and its length, see [`JournalWriter`] for the layout.
+//! Readers reject records of an unknown version. Later revisions of a version may only append
+//! new fields between the RR intervals and the checksum, which older readers skip using the
+//! record length. Changing the meaning or the position of an existing field requires a new
+//! version.
+//!
+//! # Example
+//! ```rust
+//! use qrs_detector::journal::{JournalReader, JournalWriter};
+//! use qrs_detector::prelude::*;
+//!
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//! let mut writer = JournalWriter::new();
+//! let mut storage = Vec::new();
+//!
+//! # let signal = [0.0; 5000];
+//! for (i, &sample) in signal.iter().enumerate() {
+//!     detector.update(sample);
+//!
+//!     // Journal the state every 2 seconds
+//!     if i % 1000 == 999 {
+//!         let record = writer.write(&detector.journal_state());
+//!         if record.is_keyframe() {
+//!             // Older records are no longer needed
+//!             storage.clear();
+//!         }
+//!         storage.extend_from_slice(record.as_bytes());
+//!     }
+//! }
+//!
+//! // After the power loss
+//! let mut reader = JournalReader::new();
+//! reader.replay(&storage);
+//!
+//! let mut restored = QrsDetector::new::<150, 25>(500.sps());
+//! if let Some(state) = reader.state() {
+//!     restored.restore_journal(state);
+//! }
+//! ```
+//!
+//! [`QrsDetector::restore_journal`]: crate::QrsDetector::restore_journal
+
+use crate::{
+    config::{MAX_MM_DEPTH, MAX_RR_DEPTH},
+    math::crc32,
+};
+
+/// The version of the journal record encoding.
+pub const JOURNAL_VERSION: u8 = 2;
+
+/// The size of the largest journal record, in bytes.
+pub const MAX_RECORD_SIZE: usize = HEADER_SIZE + 2 + 4 * MAX_MM_DEPTH + 4 * MAX_RR_DEPTH + 4;
+
+/// By default, every 8th record is a keyframe.
+const DEFAULT_KEYFRAME_INTERVAL: u8 = 8;
+
+/// Version, record length, sequence number, sample index and last detection.
+const HEADER_SIZE: usize = 11;
+
+/// A record with empty MM and RR values.
+const MIN_RECORD_SIZE: usize = HEADER_SIZE + 2 + 4;
+
+/// An error returned when replaying a journal record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JournalError {
+    /// The data is shorter than the record.
+    Truncated,
+    /// The data was encoded using an unsupported version, or the storage is erased.
+    UnsupportedVersion(u8),
+    /// The checksum of the record does not match, e.g. because it was only partially written.
+    Checksum,
+    /// The record contains changes to a state that was not replayed, e.g. because a record is
+    /// missing.
+    MissingBase,
+    /// The record contains invalid values.
+    Invalid,
+}
+
+/// The adapted state of a [`QrsDetector`], see [`QrsDetector::journal_state`].
+///
+/// The state does not contain the signal buffers, which are refilled within 300ms after the
+/// detector is restored.
+///
+/// [`QrsDetector`]: crate::QrsDetector
+/// [`QrsDetector::journal_state`]: crate::QrsDetector::journal_state
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JournalState {
+    pub(crate) sample_index: u32,
+    pub(crate) last_detection: u32,
+    pub(crate) mm: Values<f32, MAX_MM_DEPTH>,
+    pub(crate) rr: Values<u32, MAX_RR_DEPTH>,
+}
+
+impl JournalState {
+    /// Returns the index of the next sample to be processed.
+    pub fn sample_index(&self) -> u32 {
+        self.sample_index
+    }
+
+    /// Returns the sample index of the last detection.
+    pub fn last_detection(&self) -> u32 {
+        self.last_detection
+    }
+
+    /// Returns the values of the steep-slope threshold buffer, from the oldest to the newest.
+    /// Empty while the detector is initializing.
+    pub fn mm_values(&self) -> &[f32] {
+        self.mm.as_slice()
+    }
+
+    /// Returns the collected RR intervals in samples, from the oldest to the newest.
+    pub fn rr_intervals(&self) -> &[u32] {
+        self.rr.as_slice()
+    }
+}
+
+/// A short list of values with a fixed capacity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Values<T, const N: usize> {
+    values: [T; N],
+    len: usize,
+}
+
+impl<T: Copy + Default + PartialEq, const N: usize> Values<T, N> {
+    /// Collects the last `N` values of `iter`.
+    pub(crate) fn collect(iter: impl Iterator<Item = T>) -> Self {
+        let mut values = Self {
+            values: [T::default(); N],
+            len: 0,
+        };
+        for value in iter {
+            values.push(value);
+        }
+        values
+    }
+
+    pub(crate) fn as_slice(&self) -> &[T] {
+        &self.values[..self.len]
+    }
+
+    fn push(&mut self, value: T) {
+        if self.len == N {
+            self.values.copy_within(1.., 0);
+            self.len -= 1;
+        }
+        self.values[self.len] = value;
+        self.len += 1;
+    }
+
+    /// Returns the number of values at the end of `prev` that start the current values.
+    fn kept_from(&self, prev: &Self) -> usize {
+        let prev = prev.as_slice();
+        (0..=self.len.min(prev.len()))
+            .rev()
+            .find(|&keep| prev[prev.len() - keep..] == self.values[..keep])
+            .unwrap_or(0)
+    }
+
+    /// Keeps the last `keep` values and appends `new`.
+    fn apply(&self, keep: usize, new: impl Iterator<Item = T>) -> Self {
+        let mut values = Self::collect(self.as_slice()[self.len - keep..].iter().copied());
+        for value in new {
+            values.push(value);
+        }
+        values
+    }
+}
+
+/// An encoded journal record, see [`JournalWriter::write`].
+#[derive(Clone, Copy, Debug)]
+pub struct JournalRecord {
+    bytes: [u8; MAX_RECORD_SIZE],
+    len: usize,
+    keyframe: bool,
+}
+
+impl JournalRecord {
+    /// Returns the encoded record.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// Returns `true` if the record contains the complete state. The records written before a
+    /// keyframe are no longer needed to restore the state.
+    pub fn is_keyframe(&self) -> bool {
+        self.keyframe
+    }
+}
+
+/// Encodes the changes of the detector state into journal records.
+///
+/// Records have the following little-endian layout:
+///
+/// - version: `u8`
+/// - record length: `u8`, including the checksum
+/// - sequence number: `u8`
+/// - sample index: `u32`
+/// - last detection: `u32`
+/// - MM values: `u8`, the number of kept values in the high nibble and the number of new values
+///   in the low nibble, followed by the new values as `f32`
+/// - RR intervals: same as the MM values, with the new values as `u32`
+/// - checksum: `u32` CRC-32 of the preceding bytes
+///
+/// A keyframe keeps no values. See the [module documentation](self#compatibility) for how the
+/// layout may be extended.
+pub struct JournalWriter {
+    prev: Option<JournalState>,
+    sequence: u8,
+    keyframe_interval: u8,
+    since_keyframe: u8,
+}
+
+impl Default for JournalWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JournalWriter {
+    /// Creates a new writer. The first record is a keyframe.
+    pub fn new() -> Self {
+        Self {
+            prev: None,
+            sequence: 0,
+            keyframe_interval: DEFAULT_KEYFRAME_INTERVAL,
+            since_keyframe: 0,
+        }
+    }
+
+    /// Writes a keyframe every `interval` records. The journal needs to keep at most this many
+    /// records to restore the state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is 0.
+    pub fn with_keyframe_interval(mut self, interval: u8) -> Self {
+        assert!(interval > 0, "The keyframe interval must be at least 1");
+        self.keyframe_interval = interval;
+        self
+    }
+
+    /// Forces the next record to be a keyframe, e.g. after the journal storage was erased.
+    pub fn force_keyframe(&mut self) {
+        self.prev = None;
+    }
+
+    /// Encodes the changes since the previous record.
+    pub fn write(&mut self, state: &JournalState) -> JournalRecord {
+        let base = match self.prev {
+            Some(prev) if self.since_keyframe < self.keyframe_interval => Some(prev),
+            _ => None,
+        };
+        let (keep_mm, keep_rr) = match &base {
+            Some(prev) => (state.mm.kept_from(&prev.mm), state.rr.kept_from(&prev.rr)),
+            None => (0, 0),
+        };
+
+        let mut bytes = [0; MAX_RECORD_SIZE];
+        bytes[0] = JOURNAL_VERSION;
+        bytes[2] = self.sequence;
+        bytes[3..7].copy_from_slice(&state.sample_index.to_le_bytes());
+        bytes[7..11].copy_from_slice(&state.last_detection.to_le_bytes());
+
+        let mut len = HEADER_SIZE;
+        let mut write_values = |keep: usize, new: &mut dyn Iterator<Item = [u8; 4]>| {
+            let counts = len;
+            let mut count = 0;
+            len += 1;
+            for value in new {
+                bytes[len..len + 4].copy_from_slice(&value);
+                len += 4;
+                count += 1;
+            }
+            bytes[counts] = ((keep as u8) << 4) | count;
+        };
+        write_values(
+            keep_mm,
+            &mut state.mm.as_slice()[keep_mm..]
+                .iter()
+                .map(|v| v.to_le_bytes()),
+        );
+        write_values(
+            keep_rr,
+            &mut state.rr.as_slice()[keep_rr..]
+                .iter()
+                .map(|v| v.to_le_bytes()),
+        );
+
+        bytes[1] = (len + 4) as u8;
+        let checksum = crc32(&bytes[..len]);
+        bytes[len..len + 4].copy_from_slice(&checksum.to_le_bytes());
+        len += 4;
+
+        let keyframe = base.is_none();
+        self.since_keyframe = if keyframe { 1 } else { self.since_keyframe + 1 };
+        self.sequence = self.sequence.wrapping_add(1);
+        self.prev = Some(*state);
+
+        JournalRecord {
+            bytes,
+            len,
+            keyframe,
+        }
+    }
+}
+
+/// Replays journal records written by [`JournalWriter`] to reconstruct the detector state.
+pub struct JournalReader {
+    state: Option<JournalState>,
+    sequence: u8,
+}
+
+impl Default for JournalReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JournalReader {
+    /// Creates a new reader, without a state.
+    pub fn new() -> Self {
+        Self {
+            state: None,
+            sequence: 0,
+        }
+    }
+
+    /// Returns the state reconstructed from the replayed records.
+    pub fn state(&self) -> Option<&JournalState> {
+        self.state.as_ref()
+    }
+
+    /// Replays the consecutive records stored in `bytes`, until the first invalid record.
+    ///
+    /// Returns the number of bytes replayed. New records may be appended to the storage from
+    /// this position.
+    pub fn replay(&mut self, bytes: &[u8]) -> usize {
+        let mut position = 0;
+        while let Ok(len) = self.apply(&bytes[position..]) {
+            position += len;
+        }
+        position
+    }
+
+    /// Replays the record at the start of `bytes`. Returns the size of the record.
+    ///
+    /// The state is not changed if an error is returned.
+    pub fn apply(&mut self, bytes: &[u8]) -> Result<usize, JournalError> {
+        let version = *bytes.first().ok_or(JournalError::Truncated)?;
+        if version != JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion(version));
+        }
+        let len = *bytes.get(1).ok_or(JournalError::Truncated)? as usize;
+        if len < MIN_RECORD_SIZE {
+            return Err(JournalError::Invalid);
+        }
+        let record = bytes.get(..len).ok_or(JournalError::Truncated)?;
+        let (bytes, checksum) = record.split_at(len - 4);
+        if checksum != crc32(bytes).to_le_bytes() {
+            return Err(JournalError::Checksum);
+        }
+        let sequence = bytes[2];
+
+        let counts = |idx: usize, max: usize| -> Result<(usize, usize), JournalError> {
+            let counts = *bytes.get(idx).ok_or(JournalError::Invalid)?;
+            let (keep, new) = ((counts >> 4) as usize, (counts & 0x0F) as usize);
+            if keep + new > max {
+                return Err(JournalError::Invalid);
+            }
+            Ok((keep, new))
+        };
+        let (keep_mm, new_mm) = counts(HEADER_SIZE, MAX_MM_DEPTH)?;
+        let rr_start = HEADER_SIZE + 1 + 4 * new_mm;
+        let (keep_rr, new_rr) = counts(rr_start, MAX_RR_DEPTH)?;
+        // Any bytes after the RR intervals are fields added by a later revision of the format
+        if rr_start + 1 + 4 * new_rr > bytes.len() {
+            return Err(JournalError::Invalid);
+        }
+
+        let u32_at = |idx: usize| u32::from_le_bytes(bytes[idx..idx + 4].try_into().unwrap());
+        let (sample_index, last_detection) = (u32_at(3), u32_at(7));
+        if sample_index.wrapping_sub(last_detection) > u32::MAX / 2 {
+            // The last detection is after the current sample
+            return Err(JournalError::Invalid);
+        }
+
+        let empty = JournalState {
+            sample_index: 0,
+            last_detection: 0,
+            mm: Values::collect(core::iter::empty()),
+            rr: Values::collect(core::iter::empty()),
+        };
+        let base = if keep_mm == 0 && keep_rr == 0 {
+            // A keyframe, or a record that replaces every value
+            empty
+        } else {
+            match self.state {
+                Some(state) if sequence == self.sequence.wrapping_add(1) => state,
+                _ => return Err(JournalError::MissingBase),
+            }
+        };
+        if keep_mm > base.mm.as_slice().len() || keep_rr > base.rr.as_slice().len() {
+            return Err(JournalError::MissingBase);
+        }
+
+        let f32_at = |idx: usize| f32::from_le_bytes(bytes[idx..idx + 4].try_into().unwrap());
+        let mm = base.mm.apply(
+            keep_mm,
+            (0..new_mm).map(|i| f32_at(HEADER_SIZE + 1 + 4 * i)),
+        );
+        if !mm.as_slice().iter().all(|m| m.is_finite()) {
+            return Err(JournalError::Invalid);
+        }
+        let rr = base
+            .rr
+            .apply(keep_rr, (0..new_rr).map(|i| u32_at(rr_start + 1 + 4 * i)));
+        if rr.as_slice().contains(&0) {
+            return Err(JournalError::Invalid);
+        }
+
+        self.state = Some(JournalState {
+            sample_index,
+            last_detection,
+            mm,
+            rr,
+        });
+        self.sequence = sequence;
+
+        Ok(len)
+    }
+}
@@ -0,0 +1,371 @@
+//! Front-ends that combine multiple electrode or lead signals into the single signal processed by
+//! the detector.
+
+use crate::sampling::SamplingFrequency;
+
+/// The time constant of the electrode offset tracking, in seconds.
+const OFFSET_TIME_CONSTANT_S: f32 = 2.0;
+
+/// Tracks the DC offset of a signal.
+#[derive(Clone, Copy, Debug)]
+struct OffsetTracker {
+    offset: Option<f32>,
+    alpha: f32,
+}
+
+impl OffsetTracker {
+    fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            offset: None,
+            alpha: 1.0 / (fs.raw() * OFFSET_TIME_CONSTANT_S),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.offset = None;
+    }
+
+    /// Returns `sample` with the tracked offset removed.
+    fn update(&mut self, sample: f32) -> f32 {
+        // Start from the first sample instead of slowly converging from 0
+        let offset = self.offset.unwrap_or(sample);
+        let offset = offset + (sample - offset) * self.alpha;
+        self.offset = Some(offset);
+
+        sample - offset
+    }
+}
+
+/// Forms a differential lead from two single-ended electrode signals, e.g. two ADC channels of a
+/// dry-electrode wearable.
+///
+/// The DC offset of each electrode is removed separately, so that a mismatch between the
+/// electrode offsets does not appear in the output. The gains of the channels can be matched
+/// using [`DifferentialInput::with_gains`].
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::leads::DifferentialInput;
+/// use qrs_detector::prelude::*;
+///
+/// let mut input = DifferentialInput::new(500.sps()).with_gains(1.0, 1.05);
+/// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+///
+/// # let channels = [(0.0, 0.0); 10];
+/// for (a, b) in channels {
+///     detector.update(input.combine(a, b));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DifferentialInput {
+    gains: (f32, f32),
+    inverted: bool,
+    offsets: (OffsetTracker, OffsetTracker),
+}
+
+impl DifferentialInput {
+    /// Creates a new differential front-end for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            gains: (1.0, 1.0),
+            inverted: false,
+            offsets: (OffsetTracker::new(fs), OffsetTracker::new(fs)),
+        }
+    }
+
+    /// Sets the gains applied to the channels, to compensate for a gain mismatch.
+    pub fn with_gains(mut self, a: f32, b: f32) -> Self {
+        assert!(
+            a.is_finite() && b.is_finite(),
+            "The channel gains must be finite"
+        );
+        self.gains = (a, b);
+        self
+    }
+
+    /// Sets whether the output is inverted, i.e. calculated as `b - a` instead of `a - b`.
+    pub fn with_inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Resets the tracked electrode offsets.
+    pub fn clear(&mut self) {
+        self.offsets.0.clear();
+        self.offsets.1.clear();
+    }
+
+    /// Returns the differential signal of the electrode samples `a` and `b`.
+    pub fn combine(&mut self, a: f32, b: f32) -> f32 {
+        let a = self.offsets.0.update(a) * self.gains.0;
+        let b = self.offsets.1.update(b) * self.gains.1;
+
+        if self.inverted {
+            b - a
+        } else {
+            a - b
+        }
+    }
+}
+
+/// Forms the complex lead of the article from `L` simultaneously sampled leads.
+///
+/// The complex lead is the average of the absolute derivatives of the leads:
+/// `Y(n) = 1/L * sum(|X_j(n) - X_j(n-2)|)`. The detector's published performance was measured
+/// using this input. The leads should be filtered before they are combined.
+///
+/// The output is 0 until two samples of each lead have been received.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::leads::ComplexLead;
+/// use qrs_detector::prelude::*;
+///
+/// let mut complex_lead = ComplexLead::<3>::new();
+/// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+///
+/// # let samples = [[0.0; 3]; 10];
+/// for leads in samples {
+///     detector.update(complex_lead.update(&leads));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ComplexLead<const L: usize> {
+    prev: [[f32; L]; 2],
+    received: u8,
+}
+
+impl<const L: usize> Default for ComplexLead<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const L: usize> ComplexLead<L> {
+    /// Creates a new complex lead front-end.
+    pub fn new() -> Self {
+        assert!(L > 0, "At least one lead is required");
+        Self {
+            prev: [[0.0; L]; 2],
+            received: 0,
+        }
+    }
+
+    /// Resets the internal state.
+    pub fn clear(&mut self) {
+        self.received = 0;
+    }
+
+    /// Processes a sample of every lead and returns the complex lead sample.
+    pub fn update(&mut self, leads: &[f32; L]) -> f32 {
+        let [prev2, prev1] = self.prev;
+        self.prev = [prev1, *leads];
+
+        if self.received < 2 {
+            self.received += 1;
+            return 0.0;
+        }
+
+        let sum: f32 = leads
+            .iter()
+            .zip(prev2.iter())
+            .map(|(x, x2)| (x - x2).abs())
+            .sum();
+
+        sum / L as f32
+    }
+}
+
+/// A limb lead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Lead {
+    I,
+    II,
+    III,
+    AVR,
+    AVL,
+    AVF,
+}
+
+impl Lead {
+    /// Every limb lead, in the order of [`LimbLeads::to_array`].
+    pub const ALL: [Lead; 6] = [
+        Lead::I,
+        Lead::II,
+        Lead::III,
+        Lead::AVR,
+        Lead::AVL,
+        Lead::AVF,
+    ];
+}
+
+/// The six limb leads, derived from leads I and II.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::leads::{ComplexLead, Lead, LimbLeads};
+///
+/// let leads = LimbLeads::from_i_ii(1.0, 2.0);
+/// assert_eq!(1.0, leads.get(Lead::III));
+///
+/// // Form the complex lead of every limb lead
+/// let mut complex_lead = ComplexLead::<6>::new();
+/// complex_lead.update(&leads.to_array());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LimbLeads {
+    pub i: f32,
+    pub ii: f32,
+    pub iii: f32,
+    pub avr: f32,
+    pub avl: f32,
+    pub avf: f32,
+}
+
+impl LimbLeads {
+    /// Derives the limb leads from a sample of leads I and II, using Einthoven's law and the
+    /// definition of the Goldberger augmented leads.
+    pub fn from_i_ii(i: f32, ii: f32) -> Self {
+        Self {
+            i,
+            ii,
+            iii: ii - i,
+            avr: -(i + ii) / 2.0,
+            avl: i - ii / 2.0,
+            avf: ii - i / 2.0,
+        }
+    }
+
+    /// Returns the sample of `lead`.
+    pub fn get(&self, lead: Lead) -> f32 {
+        match lead {
+            Lead::I => self.i,
+            Lead::II => self.ii,
+            Lead::III => self.iii,
+            Lead::AVR => self.avr,
+            Lead::AVL => self.avl,
+            Lead::AVF => self.avf,
+        }
+    }
+
+    /// Returns the leads in the order of [`Lead::ALL`].
+    pub fn to_array(&self) -> [f32; 6] {
+        [self.i, self.ii, self.iii, self.avr, self.avl, self.avf]
+    }
+}
+
+/// The R peak is searched for in this period after a detection.
+const PEAK_WINDOW_MS: f32 = 200.0;
+/// The weight of a new beat in the averaged amplitudes.
+const AMPLITUDE_WEIGHT: f32 = 1.0 / 8.0;
+
+/// Estimates the relative gain of multiple leads from their R peak amplitudes.
+///
+/// The relative gains may be used to normalize the detection thresholds when switching between
+/// leads, or to diagnose electrode contact problems.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::leads::GainEstimator;
+/// use qrs_detector::prelude::*;
+///
+/// let mut estimator = GainEstimator::<2>::new(500.sps());
+/// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+///
+/// # let samples = [[0.0; 2]; 10];
+/// for leads in samples {
+///     // Detect on the first lead
+///     let detected = detector.update(leads[0]).is_some();
+///     estimator.update(&leads, detected);
+/// }
+///
+/// if let Some(gains) = estimator.relative_gains(0) {
+///     // gains[1] is the amplitude of the second lead relative to the first
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct GainEstimator<const L: usize> {
+    baselines: [OffsetTracker; L],
+    window: usize,
+    remaining: usize,
+    peaks: [f32; L],
+    amplitudes: Option<[f32; L]>,
+}
+
+impl<const L: usize> GainEstimator<L> {
+    /// Creates a new estimator for leads sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            baselines: [OffsetTracker::new(fs); L],
+            window: fs.ms_to_samples(PEAK_WINDOW_MS),
+            remaining: 0,
+            peaks: [0.0; L],
+            amplitudes: None,
+        }
+    }
+
+    /// Resets the internal state.
+    pub fn clear(&mut self) {
+        for baseline in &mut self.baselines {
+            baseline.clear();
+        }
+        self.remaining = 0;
+        self.amplitudes = None;
+    }
+
+    /// Processes a sample of every lead. `detected` should be set when a QRS complex is detected
+    /// at this sample.
+    pub fn update(&mut self, leads: &[f32; L], detected: bool) {
+        if detected && self.remaining == 0 {
+            self.remaining = self.window;
+            self.peaks = [0.0; L];
+        }
+
+        for ((baseline, peak), sample) in self.baselines.iter_mut().zip(&mut self.peaks).zip(leads)
+        {
+            let value = baseline.update(*sample).abs();
+            if self.remaining > 0 {
+                *peak = peak.max(value);
+            }
+        }
+
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                self.record_beat();
+            }
+        }
+    }
+
+    fn record_beat(&mut self) {
+        match &mut self.amplitudes {
+            Some(amplitudes) => {
+                for (amplitude, peak) in amplitudes.iter_mut().zip(self.peaks) {
+                    *amplitude += (peak - *amplitude) * AMPLITUDE_WEIGHT;
+                }
+            }
+            None => self.amplitudes = Some(self.peaks),
+        }
+    }
+
+    /// Returns the averaged R peak amplitude of every lead, once a beat has been measured.
+    pub fn amplitudes(&self) -> Option<[f32; L]> {
+        self.amplitudes
+    }
+
+    /// Returns the amplitude of every lead relative to the `reference` lead.
+    ///
+    /// Returns `None` until a beat has been measured, if the reference lead has no signal, or if
+    /// `reference` is not a lead index.
+    pub fn relative_gains(&self, reference: usize) -> Option<[f32; L]> {
+        let amplitudes = self.amplitudes?;
+        let reference = *amplitudes.get(reference)?;
+        if reference <= 0.0 {
+            return None;
+        }
+
+        Some(amplitudes.map(|amplitude| amplitude / reference))
+    }
+}
@@ -1,20 +1,146 @@
 //! This crate provides a realtime ECG QRS detector.
 //!
 //! The implementation is based on [this article](https://biomedical-engineering-online.biomedcentral.com/articles/10.1186/1475-925X-3-28).
-#![cfg_attr(not(test), no_std)]
+//!
+//! # Features
+//!
+//! The core detector is always available. Optional components are gated behind cargo features,
+//! so that embedded users only pay for what they use:
+//!
+//! - `alloc` - enables [`QrsDetector::new_alloc`] to allocate the internal buffers on the heap.
+//! - `quality` (default) - signal quality monitoring, see the [`quality`] module. The detector
+//!   uses it to recover from DC offset steps and amplifier saturation.
+//! - `hrv` (default) - heart rate variability metrics, see the [`hrv`] module, and the
+//!   [`session::SessionAnalyzer`] that combines every component. Also enables the modules built on
+//!   the heart rate: [`hr`], [`summary`], [`alarms`] and [`telemetry`].
+//! - `preprocessing` (default) - filters to preprocess the signal, see the [`preprocessing`]
+//!   module.
+//! - `internals` - exposes the threshold components of the detector, see the [`algorithms`]
+//!   module. Their interfaces may change between minor versions.
+//! - `sensors` - decoders of ECG front-end data frames, see the [`adc`], [`ads129x`] and
+//!   [`max30003`] modules, and the alignment of auxiliary sensor streams, see [`sync`].
+//! - `std` - parameter tuning against annotated records, see the [`tuning`] module, a clock
+//!   based on `std::time`, see [`time`], and, with `hrv`, a decoder of telemetry frames. Implies
+//!   `alloc`.
+//! - `io` - file formats: a CSV writer of the detected beats, see [`io`], and test vectors to
+//!   compare the detector with other implementations, see [`vectors`]. Implies `std`.
+//! - `serde` - implements `Serialize` and `Deserialize` for the report types. Together with
+//!   `io`, enables exporting sessions to health platforms, see [`export`].
+//! - `ufmt` - implements `uDisplay` and `uDebug` for the report types, for targets where
+//!   `core::fmt` is too large.
+//! - `async` - drives the detector from an asynchronous sample source, see the [`stream`] module.
+//! - `ffi` - a C interface to the detector, see the [`ffi`] module. Implies `alloc`.
+//! - `wasm` - JavaScript bindings generated by `wasm-bindgen`, see the [`wasm`] module. Implies
+//!   `alloc`.
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "sensors")]
+pub mod adc;
+#[cfg(feature = "sensors")]
+pub mod ads129x;
+#[cfg(feature = "hrv")]
+pub mod alarms;
+#[cfg(feature = "internals")]
+pub mod algorithms;
+#[cfg(not(feature = "internals"))]
 mod algorithms;
+pub mod annotation;
+pub mod beat_log;
+pub mod component;
+pub mod config;
+pub mod events;
+#[cfg(all(feature = "io", feature = "serde"))]
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fiducial;
+pub mod histogram;
+pub mod history;
+#[cfg(feature = "hrv")]
+pub mod hr;
+#[cfg(feature = "hrv")]
+pub mod hrv;
+#[cfg(feature = "io")]
+pub mod io;
+pub mod journal;
+pub mod leads;
+mod math;
+#[cfg(feature = "sensors")]
+pub mod max30003;
+pub mod multi_rate;
+pub mod noise;
+pub mod prelude;
+#[cfg(feature = "preprocessing")]
+pub mod preprocessing;
+pub mod profile;
+#[cfg(feature = "quality")]
+pub mod quality;
 pub mod sampling;
+#[cfg(feature = "hrv")]
+pub mod session;
 mod sliding;
+pub mod spectrum;
+#[cfg(feature = "async")]
+pub mod stream;
+#[cfg(feature = "hrv")]
+pub mod summary;
+#[cfg(feature = "sensors")]
+pub mod sync;
+pub mod synth;
+#[cfg(feature = "hrv")]
+pub mod telemetry;
+pub mod testing;
+pub mod time;
+pub mod timeline;
+pub mod transform;
+#[cfg(feature = "std")]
+pub mod tuning;
+#[cfg(feature = "ufmt")]
+mod ufmt_impl;
+pub mod validation;
+#[cfg(feature = "io")]
+pub mod vectors;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod wcet;
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Div, Mul},
+    time::Duration,
+};
 
-use algorithms::{F, M, R};
+use algorithms::{PaceTracker, SkipTracker, TimingTracker, F, M, R};
+use component::{ComponentInput, ThresholdComponent};
+use config::{Config, ConfigUpdate, Parameter, Parameters};
+use histogram::AmplitudeHistogram;
+use journal::{JournalState, Values};
+use noise::{NoEstimator, NoiseEstimator};
+use profile::PatientProfile;
+#[cfg(feature = "quality")]
+use quality::StepDetector;
 use sampling::SamplingFrequency;
+use testing::StateDump;
+use timeline::Timeline;
+use transform::{DcBlocker, Difference, Identity, SampleTransform, ZScore};
 
 use crate::sliding::SlidingWindow;
 
+/// The revision of the detection algorithm. It is incremented when a change affects the
+/// detections, see [`QrsDetector::algorithm_version`].
+pub const ALGORITHM_REVISION: u32 = 1;
+
+/// A beat detected this long after the pace blanking period is labeled as paced.
+const PACED_BEAT_WINDOW_MS: f32 = 250.0;
+
+/// Threshold crossings are only detected if they exceed the estimated noise level this many times.
+#[cfg(feature = "quality")]
+const NOISE_GATE_RATIO: f32 = 2.0;
+
 /// Finds QRS complexes in real-time sampled ECG signal.
 ///
 /// # Type parameters:
@@ -24,11 +150,68 @@ use crate::sliding::SlidingWindow;
 ///
 /// These type parameters are checked at runtime and, if incorrect, the error message will contain
 /// the correct sizes.
-pub struct QrsDetector<FMW, FB> {
-    total_samples: u32,
+///
+/// - `H` - a buffer type to record the most recent detections in. By default, no history is
+///   recorded. See [`QrsDetector::with_history`].
+/// - `T` - the preprocessing applied to every sample. By default, samples are processed
+///   unchanged. See [`QrsDetector::with_transform`].
+/// - `S` - a buffer type to record the most recent suppressed threshold crossings in. By default,
+///   they are only counted. See [`QrsDetector::with_suppressed_log`].
+/// - `N` - the noise level estimator. By default, the noise level is not estimated. See
+///   [`QrsDetector::with_noise_estimator`].
+/// - `FC`, `RC` - the F and R threshold components. By default, the components of the original
+///   algorithm are used. See [`QrsDetector::with_f_component`] and
+///   [`QrsDetector::with_r_component`].
+pub struct QrsDetector<
+    FMW,
+    FB,
+    H = [Detection; 0],
+    T = Identity,
+    S = [Suppressed; 0],
+    N = NoEstimator,
+    FC = F<FMW, FB>,
+    RC = R,
+> {
+    fs: SamplingFrequency,
+    config: Config,
+    /// Maps the relative time of the thresholds to absolute sample indices.
+    timeline: Timeline,
     m: M,
-    f: F<FMW, FB>,
-    r: R,
+    f: FC,
+    r: RC,
+    /// The sample index of the last detection, relative to the start of the epoch.
+    last_detection: u32,
+    history: SlidingWindow<Detection, H>,
+    transform: T,
+    /// The DC blocker enabled by the configuration, applied before the transform.
+    dc_blocker: Option<DcBlocker>,
+    /// The input transform selected by the configuration, applied after the transform.
+    input_transform: Option<Difference>,
+    /// The z-score normalization enabled by the configuration, applied after the input transform.
+    z_score: Option<ZScore>,
+    /// The configuration to apply at the next safe point, see [`QrsDetector::schedule_config`].
+    pending_config: Option<Config>,
+    config_update: Option<ConfigUpdate>,
+    histogram: Option<AmplitudeHistogram>,
+    suppressed: SlidingWindow<Suppressed, S>,
+    suppressed_counts: SuppressedCounts,
+    /// The last known detection threshold, used to detect suppressed crossings.
+    last_threshold: Option<f32>,
+    above_threshold: bool,
+    noise: N,
+    skip: SkipTracker,
+    pace: PaceTracker,
+    timing: TimingTracker,
+    /// The reasons collected since the last detection.
+    reasons: Reasons,
+    /// The number of detections to mark with [`Reason::LeadSwitch`].
+    lead_switch_beats: u8,
+    #[cfg(feature = "quality")]
+    step: StepDetector,
+    #[cfg(feature = "quality")]
+    saturated: bool,
+    /// The buffer types of the default F component.
+    _marker: PhantomData<(FMW, FB)>,
 }
 
 impl QrsDetector<(), ()> {
@@ -37,12 +220,11 @@ impl QrsDetector<(), ()> {
     ///
     /// # Arguments
     /// * `fs` - The sampling frequency of the processed signal. For more information see
-    /// [`sampling::SamplingFrequencyExt`].
+    ///   [`sampling::SamplingFrequencyExt`].
     ///
     /// # Example
     /// ```rust
-    /// use qrs_detector::sampling::*;
-    /// use qrs_detector::QrsDetector;
+    /// use qrs_detector::prelude::*;
     ///
     /// // Assuming 500 samples per second
     /// // Type parameters must be 300ms and 50ms in number of samples
@@ -51,19 +233,14 @@ impl QrsDetector<(), ()> {
     pub fn new<const SAMPLES_300: usize, const SAMPLES_50: usize>(
         fs: SamplingFrequency,
     ) -> QrsDetector<[f32; SAMPLES_300], [f32; SAMPLES_50]> {
-        QrsDetector {
-            total_samples: 0,
-            m: M::new(fs),
-            f: F::new(fs, SlidingWindow::default(), SlidingWindow::default()),
-            r: R::new(),
-        }
+        QrsDetector::from_f(fs, F::new(fs, [0.0; SAMPLES_300], [0.0; SAMPLES_50]))
     }
 
     /// Creates a new QRS detector for signals sampled with `fs`, using the provided buffers.
     ///
     /// # Arguments
     /// * `fs` - The sampling frequency of the processed signal. For more information see
-    /// [`sampling::SamplingFrequencyExt`].
+    ///   [`sampling::SamplingFrequencyExt`].
     /// * `f_buffer_300` - A buffer containing 300ms worth of samples.
     /// * `f_buffer_50` - A buffer containing 50ms worth of samples.
     ///
@@ -72,8 +249,7 @@ impl QrsDetector<(), ()> {
     /// The backing buffers may be slices:
     ///
     /// ```rust
-    /// use qrs_detector::sampling::*;
-    /// use qrs_detector::QrsDetector;
+    /// use qrs_detector::prelude::*;
     ///
     /// // Assuming 500 samples per second
     /// // Buffers must hold 300ms and 50ms worth of samples.
@@ -85,8 +261,7 @@ impl QrsDetector<(), ()> {
     /// The backing buffers may be arrays:
     ///
     /// ```rust
-    /// use qrs_detector::sampling::*;
-    /// use qrs_detector::QrsDetector;
+    /// use qrs_detector::prelude::*;
     ///
     /// // Assuming 500 samples per second
     /// // Buffers must hold 300ms and 50ms worth of samples.
@@ -103,23 +278,51 @@ impl QrsDetector<(), ()> {
         FMW: AsRef<[f32]> + AsMut<[f32]>,
         FB: AsRef<[f32]> + AsMut<[f32]>,
     {
-        QrsDetector {
-            total_samples: 0,
-            m: M::new(fs),
-            f: F::new(
-                fs,
-                SlidingWindow::new(f_buffer_300),
-                SlidingWindow::new(f_buffer_50),
-            ),
-            r: R::new(),
-        }
+        QrsDetector::from_f(fs, F::new(fs, f_buffer_300, f_buffer_50))
+    }
+
+    /// Creates a new QRS detector for signals sampled with `fs`, using the provided buffers.
+    ///
+    /// Unlike [`QrsDetector::new_from`], which only checks the buffer sizes in debug builds, this
+    /// function returns an error if a buffer does not hold exactly 300ms or 50ms worth of samples.
+    /// An incorrectly sized buffer changes the time constants of the F threshold, which quietly
+    /// degrades the detection accuracy. Use this function when the buffer sizes are not known at
+    /// compile time, e.g. when they are read from a configuration file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    /// use qrs_detector::BufferSizeError;
+    ///
+    /// let mut f_buffer_300 = [0.0; 150];
+    /// let mut f_buffer_50 = [0.0; 25];
+    /// assert!(QrsDetector::try_new_from(500.sps(), &mut f_buffer_300, &mut f_buffer_50).is_ok());
+    ///
+    /// // The buffers were sized for 500 samples per second
+    /// let result = QrsDetector::try_new_from(360.sps(), &mut f_buffer_300, &mut f_buffer_50);
+    /// assert_eq!(
+    ///     Some(BufferSizeError::Samples300 { expected: 108, actual: 150 }),
+    ///     result.err()
+    /// );
+    /// ```
+    pub fn try_new_from<FMW, FB>(
+        fs: SamplingFrequency,
+        f_buffer_300: FMW,
+        f_buffer_50: FB,
+    ) -> Result<QrsDetector<FMW, FB>, BufferSizeError>
+    where
+        FMW: AsRef<[f32]> + AsMut<[f32]>,
+        FB: AsRef<[f32]> + AsMut<[f32]>,
+    {
+        BufferSizes::for_fs(fs).check(f_buffer_300.as_ref().len(), f_buffer_50.as_ref().len())?;
+        Ok(QrsDetector::new_from(fs, f_buffer_300, f_buffer_50))
     }
 
     /// Creates a new QRS detector for signals sampled with `fs`, using the provided buffers.
     ///
     /// # Arguments
     /// * `fs` - The sampling frequency of the processed signal. For more information see
-    /// [`sampling::SamplingFrequencyExt`].
+    ///   [`sampling::SamplingFrequencyExt`].
     /// * `f_buffer_300` - A buffer containing 300ms worth of samples.
     /// * `f_buffer_50` - A buffer containing 50ms worth of samples.
     ///
@@ -128,8 +331,7 @@ impl QrsDetector<(), ()> {
     /// The backing buffers may be slices:
     ///
     /// ```rust
-    /// use qrs_detector::sampling::*;
-    /// use qrs_detector::QrsDetector;
+    /// use qrs_detector::prelude::*;
     ///
     /// // Assuming 500 samples per second
     /// // Buffers must hold 300ms and 50ms worth of samples.
@@ -141,8 +343,7 @@ impl QrsDetector<(), ()> {
     /// The backing buffers may be arrays:
     ///
     /// ```rust
-    /// use qrs_detector::sampling::*;
-    /// use qrs_detector::QrsDetector;
+    /// use qrs_detector::prelude::*;
     ///
     /// // Assuming 500 samples per second
     /// // Buffers must hold 300ms and 50ms worth of samples.
@@ -155,52 +356,1118 @@ impl QrsDetector<(), ()> {
         fs: SamplingFrequency,
     ) -> QrsDetector<alloc::boxed::Box<[f32]>, alloc::boxed::Box<[f32]>> {
         use alloc::vec;
-        QrsDetector {
-            total_samples: 0,
-            m: M::new(fs),
-            f: F::new(
+        QrsDetector::from_f(
+            fs,
+            F::new(
                 fs,
-                SlidingWindow::new(vec![0.0; fs.ms_to_samples(300.0)].into_boxed_slice()),
-                SlidingWindow::new(vec![0.0; fs.ms_to_samples(50.0)].into_boxed_slice()),
+                vec![0.0; fs.ms_to_samples(300.0)].into_boxed_slice(),
+                vec![0.0; fs.ms_to_samples(50.0)].into_boxed_slice(),
             ),
-            r: R::new(),
+        )
+    }
+}
+
+impl<FMW, FB> QrsDetector<FMW, FB> {
+    fn from_f(fs: SamplingFrequency, f: F<FMW, FB>) -> Self {
+        let config = Config::new();
+        QrsDetector {
+            fs,
+            config,
+            timeline: Timeline::new(),
+            m: M::new(fs, &config),
+            f,
+            r: R::new(&config),
+            last_detection: 0,
+            history: SlidingWindow::new([]),
+            transform: Identity,
+            dc_blocker: None,
+            input_transform: None,
+            z_score: None,
+            pending_config: None,
+            config_update: None,
+            histogram: None,
+            suppressed: SlidingWindow::new([]),
+            suppressed_counts: SuppressedCounts::default(),
+            last_threshold: None,
+            above_threshold: false,
+            noise: NoEstimator,
+            skip: SkipTracker::new(),
+            pace: PaceTracker::new(),
+            timing: TimingTracker::new(fs),
+            reasons: Reasons::empty(),
+            lead_switch_beats: 0,
+            #[cfg(feature = "quality")]
+            step: StepDetector::new(fs),
+            #[cfg(feature = "quality")]
+            saturated: false,
+            _marker: PhantomData,
         }
     }
 }
 
-impl<FMW, FB> QrsDetector<FMW, FB>
+impl<FMW, FB, H, T, S, N, FC, RC> QrsDetector<FMW, FB, H, T, S, N, FC, RC> {
+    /// Records the most recent detections in `buffer`, see [`QrsDetector::recent_detections`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    ///
+    /// // Keep the last 8 detections
+    /// let detector = QrsDetector::new::<150, 25>(500.sps())
+    ///     .with_history([Detection::default(); 8]);
+    /// ```
+    pub fn with_history<H2>(self, buffer: H2) -> QrsDetector<FMW, FB, H2, T, S, N, FC, RC>
+    where
+        H2: AsRef<[Detection]> + AsMut<[Detection]>,
+    {
+        QrsDetector {
+            fs: self.fs,
+            config: self.config,
+            timeline: self.timeline,
+            m: self.m,
+            f: self.f,
+            r: self.r,
+            last_detection: self.last_detection,
+            history: SlidingWindow::new(buffer),
+            transform: self.transform,
+            dc_blocker: self.dc_blocker,
+            input_transform: self.input_transform,
+            z_score: self.z_score,
+            pending_config: self.pending_config,
+            config_update: self.config_update,
+            histogram: self.histogram,
+            suppressed: self.suppressed,
+            suppressed_counts: self.suppressed_counts,
+            last_threshold: self.last_threshold,
+            above_threshold: self.above_threshold,
+            noise: self.noise,
+            skip: self.skip,
+            pace: self.pace,
+            timing: self.timing,
+            reasons: self.reasons,
+            lead_switch_beats: self.lead_switch_beats,
+            #[cfg(feature = "quality")]
+            step: self.step,
+            #[cfg(feature = "quality")]
+            saturated: self.saturated,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Applies `transform` to every sample before it is processed by the detector.
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    ///
+    /// // Process the absolute value of the signal
+    /// let detector = QrsDetector::new::<150, 25>(500.sps())
+    ///     .with_transform(|sample: f32| sample.abs());
+    /// ```
+    pub fn with_transform<T2>(self, transform: T2) -> QrsDetector<FMW, FB, H, T2, S, N, FC, RC>
+    where
+        T2: SampleTransform,
+    {
+        QrsDetector {
+            fs: self.fs,
+            config: self.config,
+            timeline: self.timeline,
+            m: self.m,
+            f: self.f,
+            r: self.r,
+            last_detection: self.last_detection,
+            history: self.history,
+            transform,
+            dc_blocker: self.dc_blocker,
+            input_transform: self.input_transform,
+            z_score: self.z_score,
+            pending_config: self.pending_config,
+            config_update: self.config_update,
+            histogram: self.histogram,
+            suppressed: self.suppressed,
+            suppressed_counts: self.suppressed_counts,
+            last_threshold: self.last_threshold,
+            above_threshold: self.above_threshold,
+            noise: self.noise,
+            skip: self.skip,
+            pace: self.pace,
+            timing: self.timing,
+            reasons: self.reasons,
+            lead_switch_beats: self.lead_switch_beats,
+            #[cfg(feature = "quality")]
+            step: self.step,
+            #[cfg(feature = "quality")]
+            saturated: self.saturated,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Records the most recent suppressed threshold crossings in `buffer`, see
+    /// [`QrsDetector::recent_suppressed`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    /// use qrs_detector::Suppressed;
+    ///
+    /// // Keep the last 4 suppressed crossings
+    /// let detector = QrsDetector::new::<150, 25>(500.sps())
+    ///     .with_suppressed_log([Suppressed::default(); 4]);
+    /// ```
+    pub fn with_suppressed_log<S2>(self, buffer: S2) -> QrsDetector<FMW, FB, H, T, S2, N, FC, RC>
+    where
+        S2: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    {
+        QrsDetector {
+            fs: self.fs,
+            config: self.config,
+            timeline: self.timeline,
+            m: self.m,
+            f: self.f,
+            r: self.r,
+            last_detection: self.last_detection,
+            history: self.history,
+            transform: self.transform,
+            dc_blocker: self.dc_blocker,
+            input_transform: self.input_transform,
+            z_score: self.z_score,
+            pending_config: self.pending_config,
+            config_update: self.config_update,
+            histogram: self.histogram,
+            suppressed: SlidingWindow::new(buffer),
+            suppressed_counts: self.suppressed_counts,
+            last_threshold: self.last_threshold,
+            above_threshold: self.above_threshold,
+            noise: self.noise,
+            skip: self.skip,
+            pace: self.pace,
+            timing: self.timing,
+            reasons: self.reasons,
+            lead_switch_beats: self.lead_switch_beats,
+            #[cfg(feature = "quality")]
+            step: self.step,
+            #[cfg(feature = "quality")]
+            saturated: self.saturated,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Estimates the noise level of the processed signal using `estimator`.
+    ///
+    /// The noise level is available using [`QrsDetector::noise_level`]. With the `quality`
+    /// feature enabled, threshold crossings that do not exceed the noise level by a large margin
+    /// are not detected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrs_detector::noise::RmsNoise;
+    /// use qrs_detector::prelude::*;
+    ///
+    /// let detector = QrsDetector::new::<150, 25>(500.sps())
+    ///     .with_noise_estimator(RmsNoise::new(500.sps()));
+    /// ```
+    pub fn with_noise_estimator<N2>(
+        self,
+        estimator: N2,
+    ) -> QrsDetector<FMW, FB, H, T, S, N2, FC, RC>
+    where
+        N2: NoiseEstimator,
+    {
+        QrsDetector {
+            fs: self.fs,
+            config: self.config,
+            timeline: self.timeline,
+            m: self.m,
+            f: self.f,
+            r: self.r,
+            last_detection: self.last_detection,
+            history: self.history,
+            transform: self.transform,
+            dc_blocker: self.dc_blocker,
+            input_transform: self.input_transform,
+            z_score: self.z_score,
+            pending_config: self.pending_config,
+            config_update: self.config_update,
+            histogram: self.histogram,
+            suppressed: self.suppressed,
+            suppressed_counts: self.suppressed_counts,
+            last_threshold: self.last_threshold,
+            above_threshold: self.above_threshold,
+            noise: estimator,
+            skip: self.skip,
+            pace: self.pace,
+            timing: self.timing,
+            reasons: self.reasons,
+            lead_switch_beats: self.lead_switch_beats,
+            #[cfg(feature = "quality")]
+            step: self.step,
+            #[cfg(feature = "quality")]
+            saturated: self.saturated,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replaces the integrating threshold F with `component`.
+    ///
+    /// The component is configured with the active configuration. The buffers of the default F
+    /// component are no longer used.
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrs_detector::component::{ComponentInput, ThresholdComponent};
+    /// use qrs_detector::prelude::*;
+    ///
+    /// /// A constant threshold.
+    /// struct Constant(f32);
+    ///
+    /// impl ThresholdComponent for Constant {
+    ///     fn update(&mut self, _input: &ComponentInput) {}
+    ///
+    ///     fn threshold(&self) -> Option<f32> {
+    ///         Some(self.0)
+    ///     }
+    ///
+    ///     fn clear(&mut self) {}
+    /// }
+    ///
+    /// let detector = QrsDetector::new::<150, 25>(500.sps()).with_f_component(Constant(0.1));
+    /// ```
+    pub fn with_f_component<FC2>(
+        self,
+        mut component: FC2,
+    ) -> QrsDetector<FMW, FB, H, T, S, N, FC2, RC>
+    where
+        FC2: ThresholdComponent,
+    {
+        component.configure(&self.config);
+
+        QrsDetector {
+            fs: self.fs,
+            config: self.config,
+            timeline: self.timeline,
+            m: self.m,
+            f: component,
+            r: self.r,
+            last_detection: self.last_detection,
+            history: self.history,
+            transform: self.transform,
+            dc_blocker: self.dc_blocker,
+            input_transform: self.input_transform,
+            z_score: self.z_score,
+            pending_config: self.pending_config,
+            config_update: self.config_update,
+            histogram: self.histogram,
+            suppressed: self.suppressed,
+            suppressed_counts: self.suppressed_counts,
+            last_threshold: self.last_threshold,
+            above_threshold: self.above_threshold,
+            noise: self.noise,
+            skip: self.skip,
+            pace: self.pace,
+            timing: self.timing,
+            reasons: self.reasons,
+            lead_switch_beats: self.lead_switch_beats,
+            #[cfg(feature = "quality")]
+            step: self.step,
+            #[cfg(feature = "quality")]
+            saturated: self.saturated,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replaces the beat expectation threshold R with `component`, see
+    /// [`QrsDetector::with_f_component`].
+    ///
+    /// The component is configured with the active configuration. The RR interval based features,
+    /// e.g. [`QrsDetector::rr_average`] and [`QrsDetector::warm_start`], require the default R
+    /// component.
+    pub fn with_r_component<RC2>(
+        self,
+        mut component: RC2,
+    ) -> QrsDetector<FMW, FB, H, T, S, N, FC, RC2>
+    where
+        RC2: ThresholdComponent,
+    {
+        component.configure(&self.config);
+
+        QrsDetector {
+            fs: self.fs,
+            config: self.config,
+            timeline: self.timeline,
+            m: self.m,
+            f: self.f,
+            r: component,
+            last_detection: self.last_detection,
+            history: self.history,
+            transform: self.transform,
+            dc_blocker: self.dc_blocker,
+            input_transform: self.input_transform,
+            z_score: self.z_score,
+            pending_config: self.pending_config,
+            config_update: self.config_update,
+            histogram: self.histogram,
+            suppressed: self.suppressed,
+            suppressed_counts: self.suppressed_counts,
+            last_threshold: self.last_threshold,
+            above_threshold: self.above_threshold,
+            noise: self.noise,
+            skip: self.skip,
+            pace: self.pace,
+            timing: self.timing,
+            reasons: self.reasons,
+            lead_switch_beats: self.lead_switch_beats,
+            #[cfg(feature = "quality")]
+            step: self.step,
+            #[cfg(feature = "quality")]
+            saturated: self.saturated,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Records the amplitude distribution of the processed samples in `histogram`, see the
+    /// [`histogram`] module.
+    ///
+    /// The samples are recorded after the sample transform and the input transform of the
+    /// configuration, i.e. in the same units as the thresholds.
+    pub fn with_amplitude_histogram(mut self, histogram: AmplitudeHistogram) -> Self {
+        self.histogram = Some(histogram);
+        self
+    }
+
+    /// Returns the amplitude histogram set by [`QrsDetector::with_amplitude_histogram`].
+    pub fn amplitude_histogram(&self) -> Option<&AmplitudeHistogram> {
+        self.histogram.as_ref()
+    }
+
+    /// Returns the F threshold component.
+    pub fn f_component(&self) -> &FC {
+        &self.f
+    }
+
+    /// Returns the R threshold component.
+    pub fn r_component(&self) -> &RC {
+        &self.r
+    }
+}
+
+impl<FMW, FB, H, T, S, N, FC, RC> QrsDetector<FMW, FB, H, T, S, N, FC, RC>
 where
-    FMW: AsRef<[f32]> + AsMut<[f32]>,
-    FB: AsRef<[f32]> + AsMut<[f32]>,
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    T: SampleTransform,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    N: NoiseEstimator,
+    FC: ThresholdComponent,
+    RC: ThresholdComponent,
 {
+    /// Applies `config` to the detector. Resets the internal state of the detector.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.reset_config(config);
+        self
+    }
+
+    fn reset_config(&mut self, config: Config) {
+        self.config = config;
+        self.m = M::new(self.fs, &config);
+        self.f.configure(&config);
+        self.r.configure(&config);
+        self.dc_blocker = config
+            .dc_blocker()
+            .map(|cutoff| DcBlocker::new(self.fs, cutoff));
+        let lag = config.difference_lag().samples(self.fs);
+        self.input_transform = config
+            .input_mode()
+            .rectification()
+            .map(|rectification| Difference::new(rectification).with_lag(lag));
+        self.z_score = config
+            .z_score()
+            .map(|window_s| ZScore::new(self.fs, window_s));
+        self.clear();
+    }
+
+    /// Returns the active configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Applies `config` to the running detector immediately, preserving the adapted state where
+    /// possible. Returns the changed parameters, and the ones that required re-initializing a
+    /// part of the detector.
+    ///
+    /// Unlike [`QrsDetector::with_config`], the thresholds, RR intervals and detection history
+    /// are kept, unless the changed parameter invalidates them:
+    /// * A new MM depth presets M from the previous peak average.
+    /// * A new RR depth keeps the most recent RR intervals, R becomes active after two detections.
+    /// * Enabling the skipped beat diagnostics starts tracking from the next detection.
+    /// * A new DC blocker cutoff restarts the DC blocker.
+    /// * A new input mode, difference lag or z-score normalization changes the scale of the
+    ///   processed signal, which resets the whole detector.
+    ///
+    /// Applying a configuration during a QRS complex may cause a missed or a double detection. Use
+    /// [`QrsDetector::schedule_config`] to apply it between beats.
+    pub fn apply_config(&mut self, config: Config) -> ConfigUpdate {
+        let changed = self.config.changes(&config);
+        self.config = config;
+
+        let rescaled = Parameters::of(&[
+            Parameter::InputMode,
+            Parameter::DifferenceLag,
+            Parameter::ZScore,
+        ]);
+        if !changed.intersection(rescaled).is_empty() {
+            self.reset_config(config);
+            return ConfigUpdate {
+                changed,
+                reinitialized: changed,
+            };
+        }
+
+        let mut reinitialized = self.m.reconfigure(&config, changed);
+        reinitialized = reinitialized.union(self.f.reconfigure(&config, changed));
+        reinitialized = reinitialized.union(self.r.reconfigure(&config, changed));
+        if changed.contains(Parameter::DcBlocker) {
+            self.dc_blocker = config
+                .dc_blocker()
+                .map(|cutoff| DcBlocker::new(self.fs, cutoff));
+            reinitialized = reinitialized.with(Parameter::DcBlocker);
+        }
+        if changed.contains(Parameter::SkipDiagnostics) {
+            self.skip.clear();
+            reinitialized = reinitialized.with(Parameter::SkipDiagnostics);
+        }
+        #[cfg(feature = "quality")]
+        if changed.contains(Parameter::StepBlanking) {
+            self.step.clear();
+            reinitialized = reinitialized.with(Parameter::StepBlanking);
+        }
+        if !reinitialized.is_empty() {
+            self.reasons = self.reasons.with(Reason::Reinitialized);
+        }
+
+        ConfigUpdate {
+            changed,
+            reinitialized,
+        }
+    }
+
+    /// Applies `config` at the next safe point between beats, when the detector is neither in a
+    /// refractory period nor above the detection threshold. Replaces a configuration that is
+    /// still pending.
+    ///
+    /// The result is returned by [`QrsDetector::take_config_update`].
+    ///
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    ///
+    /// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+    ///
+    /// // Received over the air
+    /// let tuned = Config::new().with_r_floor(0.25);
+    /// detector.schedule_config(tuned);
+    ///
+    /// # let samples = [0.0; 10];
+    /// for sample in samples {
+    ///     detector.update(sample);
+    ///     if let Some(update) = detector.take_config_update() {
+    ///         // Report `update.reinitialized` to the tuning server
+    ///     }
+    /// }
+    /// assert_eq!(&tuned, detector.config());
+    /// ```
+    pub fn schedule_config(&mut self, config: Config) {
+        self.pending_config = Some(config);
+    }
+
+    /// Returns the configuration scheduled by [`QrsDetector::schedule_config`] that has not been
+    /// applied yet.
+    pub fn pending_config(&self) -> Option<&Config> {
+        self.pending_config.as_ref()
+    }
+
+    /// Returns the result of applying the scheduled configurations since the last call.
+    pub fn take_config_update(&mut self) -> Option<ConfigUpdate> {
+        self.config_update.take()
+    }
+
+    /// Returns an identifier of the algorithm and its parameters.
+    ///
+    /// The identifier is a hash of the crate version, [`ALGORITHM_REVISION`] and the
+    /// configuration description (see [`config::Config::describe`]). Including it in exported
+    /// data allows distinguishing data processed by different crate versions or configurations.
+    /// The sample transform and the noise estimator are not included.
+    pub fn algorithm_version(&self) -> u32 {
+        let mut hash = math::Fnv1a::new();
+        hash.write(env!("CARGO_PKG_VERSION").as_bytes());
+        hash.write(&ALGORITHM_REVISION.to_le_bytes());
+        hash.write(&self.config.describe().hash().to_le_bytes());
+        hash.finish()
+    }
+
     /// Resets the internal state of the detector.
+    ///
+    /// Starts a new epoch of the [`timeline`](QrsDetector::timeline), detection indices continue
+    /// from the current position.
     pub fn clear(&mut self) {
+        // Clearing a fresh detector, e.g. while applying the initial configuration, is not a reset
+        self.reasons = if self.timeline.elapsed() > 0 {
+            Reasons::of(&[Reason::Reinitialized])
+        } else {
+            Reasons::empty()
+        };
+        self.lead_switch_beats = 0;
         self.m.clear();
         self.f.clear();
         self.r.clear();
+        self.last_detection = 0;
+        self.timeline.reset();
+        self.history.clear();
+        self.transform.clear();
+        if let Some(dc_blocker) = &mut self.dc_blocker {
+            dc_blocker.clear();
+        }
+        if let Some(input_transform) = &mut self.input_transform {
+            input_transform.clear();
+        }
+        if let Some(z_score) = &mut self.z_score {
+            z_score.clear();
+        }
+        if let Some(histogram) = &mut self.histogram {
+            histogram.clear();
+        }
+        self.suppressed.clear();
+        self.suppressed_counts = SuppressedCounts::default();
+        self.last_threshold = None;
+        self.above_threshold = false;
+        self.noise.clear();
+        self.skip.clear();
+        self.pace.clear();
+        self.timing.clear();
+        #[cfg(feature = "quality")]
+        {
+            self.step.clear();
+            self.saturated = false;
+        }
     }
 
     /// Processes a sample. Returns Some sample index if a QRS complex is detected.
+    ///
+    /// # Execution time
+    ///
+    /// The work done per sample is bounded. Apart from the 50ms buffer, no loop iterates over
+    /// more than [`config::MAX_MM_DEPTH`] elements. The 50ms buffer is scanned once per 50ms, or
+    /// on every sample if it is shorter than 16 samples. The worst case execution time can be
+    /// measured using [`wcet::WcetMeter`].
     pub fn update(&mut self, sample: f32) -> Option<u32> {
+        if self.pending_config.is_some() && !self.m.in_refractory() && !self.above_threshold {
+            self.apply_pending_config();
+        }
+
+        let sample = match &mut self.dc_blocker {
+            Some(dc_blocker) => dc_blocker.transform(sample),
+            None => sample,
+        };
+        let sample = self.transform.transform(sample);
+        let sample = match &mut self.input_transform {
+            Some(input_transform) => input_transform.transform(sample),
+            None => sample,
+        };
+        // The pacing artifact must not reach the thresholds
+        let blanked = self.pace.update();
+        let sample = match &mut self.z_score {
+            // Blanked samples are replaced by the mean, so that the artifact does not distort the
+            // statistics
+            Some(z_score) if blanked => z_score.transform(z_score.mean().unwrap_or(0.0)),
+            Some(z_score) => z_score.transform(sample),
+            None if blanked => 0.0,
+            None => sample,
+        };
+        if self
+            .z_score
+            .as_ref()
+            .is_some_and(|z_score| !z_score.is_ready())
+        {
+            // M is initialized from the first normalized samples
+            self.timeline.advance();
+            return None;
+        }
+        if let Some(histogram) = &mut self.histogram {
+            histogram.add(sample);
+        }
+
+        #[cfg(feature = "quality")]
+        if self.config.step_blanking() && self.step.update(sample) {
+            self.reasons = self.reasons.with(Reason::QualityHold);
+            self.check_suppressed(sample, SuppressionReason::SignalStep);
+
+            // A DC offset step invalidates the adapted thresholds. Instead of waiting for them to
+            // slowly decay, restart the adaptation once the artifact has passed.
+            self.m.readapt();
+            self.f.readapt();
+            self.r.update(&self.component_input(sample));
+
+            self.timeline.advance();
+            return None;
+        }
+
+        if self.config.snr_adaptive() {
+            self.m.noise_level = self.noise.noise_level();
+        }
         self.m.update(sample);
-        self.f.update(sample);
-        self.r.update(self.m.current_decrement);
+        let input = self.component_input(sample);
+        self.f.update(&input);
+        self.r.update(&input);
+        self.noise.update(sample, self.m.in_refractory());
+
+        if let Some(frequency) = self.timing.update(sample) {
+            let correction = self.timing_correction(frequency);
+            if let Some(last) = self.history.last_mut() {
+                last.timing_correction = correction;
+            }
+        }
+        if self.m.in_refractory() {
+            // Track the peak of the last detected QRS complex
+            if let Some(last) = self.history.last_mut() {
+                last.amplitude = last.amplitude.max(sample);
+            }
+            self.check_suppressed(sample, SuppressionReason::Refractory);
+        }
 
         let thresholds = self.thresholds();
+        let was_above = self.above_threshold;
+        if let Some(mfr) = thresholds.total() {
+            self.last_threshold = Some(mfr);
+            self.above_threshold = sample > mfr;
+        }
 
         let result = match thresholds.total() {
-            Some(mfr) if sample > mfr => {
+            Some(mfr) if sample > mfr && self.passes_noise_gate(sample, mfr, was_above) => {
                 self.m.detection_event(sample);
-                self.r.detection_event(self.total_samples);
-                Some(self.total_samples)
+                self.f.detection_event(self.timeline.elapsed(), sample);
+                self.r.detection_event(self.timeline.elapsed(), sample);
+                self.last_detection = self.timeline.elapsed();
+                let index = self.input_index();
+                if self.config.skip_diagnostics() {
+                    self.skip.detection_event(index);
+                }
+                let paced = self.pace.detection_event();
+                let reasons = self.take_reasons();
+                if self.history.capacity() > 0 {
+                    self.timing.detection_event(sample, mfr);
+                    self.history.push(Detection {
+                        index,
+                        amplitude: sample,
+                        threshold: mfr,
+                        paced,
+                        reasons,
+                        timing_correction: 0.0,
+                    });
+                }
+                Some(index)
+            }
+            Some(mfr) => {
+                if self.config.skip_diagnostics() && !self.m.in_refractory() {
+                    self.skip.update(self.input_index(), sample, mfr);
+                }
+                None
             }
-            _ => None,
+            None => None,
         };
 
-        self.total_samples += 1;
+        self.timeline.advance();
+        result
+    }
+
+    fn apply_pending_config(&mut self) {
+        if let Some(config) = self.pending_config.take() {
+            let update = self.apply_config(config);
+            self.config_update = Some(match self.config_update {
+                Some(previous) => ConfigUpdate {
+                    changed: previous.changed.union(update.changed),
+                    reinitialized: previous.reinitialized.union(update.reinitialized),
+                },
+                None => update,
+            });
+        }
+    }
+
+    /// Returns the reasons to mark the current detection with, and starts collecting them for the
+    /// next one.
+    fn take_reasons(&mut self) -> Reasons {
+        let mut reasons = core::mem::take(&mut self.reasons);
+        #[cfg(feature = "quality")]
+        if self.saturated {
+            reasons = reasons.with(Reason::QualityHold);
+        }
+        if self.lead_switch_beats > 0 {
+            self.lead_switch_beats -= 1;
+            reasons = reasons.with(Reason::LeadSwitch);
+        }
+        reasons
+    }
+
+    /// Returns the state of the detector passed to the F and R components.
+    fn component_input(&self, sample: f32) -> ComponentInput {
+        ComponentInput {
+            sample,
+            index: self.timeline.elapsed(),
+            m: self.m.threshold(),
+            m_decrement: self.m.current_decrement,
+            in_refractory: self.m.in_refractory(),
+        }
+    }
+
+    /// Processes a chunk of samples, e.g. the payload of a received packet. Returns the
+    /// detections, along with their offset within the chunk.
+    ///
+    /// The offset can be used to timestamp the detection relative to the chunk, without
+    /// converting from the absolute sample index.
+    ///
+    /// The samples are processed lazily, as the returned iterator is advanced. The iterator must
+    /// be fully consumed, otherwise the rest of the chunk is not processed and the detector falls
+    /// behind the input.
+    ///
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    ///
+    /// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+    ///
+    /// # let packets = [[0.0; 20]; 3];
+    /// for packet in packets {
+    ///     for detection in detector.update_chunk(&packet) {
+    ///         // Offset the arrival time of the packet by `detection.offset` samples
+    ///     }
+    /// }
+    /// ```
+    #[must_use = "the samples are only processed as the iterator is consumed"]
+    pub fn update_chunk<'a>(
+        &'a mut self,
+        samples: &'a [f32],
+    ) -> impl Iterator<Item = ChunkDetection> + 'a {
+        samples
+            .iter()
+            .enumerate()
+            .filter_map(move |(offset, &sample)| {
+                self.update(sample)
+                    .map(|index| ChunkDetection { index, offset })
+            })
+    }
+
+    /// Returns the total delay of the processing pipeline, in samples.
+    ///
+    /// The delay is the sum of the group delays of the preprocessing, see
+    /// [`SampleTransform::group_delay`] and [`Config::with_input_mode`], and the latency of the
+    /// detector, see [`Config::with_latency_compensation`]. Reported detection indices are
+    /// already adjusted by the rounded delay, so that they refer to the position of the beat in
+    /// the input signal.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "preprocessing")] {
+    /// use qrs_detector::prelude::*;
+    /// use qrs_detector::preprocessing::{moving_average, Fir};
+    ///
+    /// let detector = QrsDetector::new::<150, 25>(500.sps())
+    ///     .with_transform(Fir::new(moving_average::<5>()));
+    ///
+    /// assert_eq!(2.0, detector.total_group_delay_samples());
+    /// # }
+    /// ```
+    pub fn total_group_delay_samples(&self) -> f32 {
+        let latency = self.config.latency_compensation_ms() * self.fs.raw() / 1000.0;
+        let input_transform = self
+            .input_transform
+            .as_ref()
+            .map_or(0.0, Difference::group_delay);
+        self.transform.group_delay() + input_transform + latency
+    }
+
+    /// Returns the delay reported detection indices are adjusted by, in samples.
+    fn compensated_delay(&self) -> i32 {
+        let delay = self.total_group_delay_samples();
+        if delay < 0.0 {
+            (delay - 0.5) as i32
+        } else {
+            (delay + 0.5) as i32
+        }
+    }
+
+    /// Returns the index of the current sample in the timeline of the input signal.
+    fn input_index(&self) -> u32 {
+        self.timeline
+            .position()
+            .wrapping_sub(self.compensated_delay() as u32)
+    }
+
+    /// Returns the difference of the delay of the pipeline at `frequency`, relative to the
+    /// sampling frequency, and the delay compensated in the detection indices.
+    fn timing_correction(&self, frequency: f32) -> f32 {
+        let latency = self.config.latency_compensation_ms() * self.fs.raw() / 1000.0;
+        let input_transform = self
+            .input_transform
+            .as_ref()
+            .map_or(0.0, |transform| transform.group_delay_at(frequency));
+        let delay = self.transform.group_delay_at(frequency) + input_transform + latency;
+        delay - self.compensated_delay() as f32
+    }
+
+    /// Notifies the detector that `samples` samples were lost during acquisition.
+    ///
+    /// The sample counter is advanced, so that detection indices and RR intervals stay aligned
+    /// with the acquisition time. Long gaps should be handled by calling [`QrsDetector::clear`]
+    /// as well, because the thresholds adapted before the gap are probably no longer valid.
+    pub fn notify_gap(&mut self, samples: u32) {
+        self.reasons = self.reasons.with(Reason::Gap);
+        self.timeline.skip(samples);
+        self.skip.interrupt();
+    }
+
+    /// Notifies the detector that the last `samples` samples passed to
+    /// [`QrsDetector::update`] were duplicates of earlier samples, e.g. retransmitted by the
+    /// acquisition link.
+    ///
+    /// The sample counter is moved back, so that detection indices and RR intervals stay aligned
+    /// with the acquisition time. The counter is not moved before the last detection.
+    pub fn notify_duplicate(&mut self, samples: u32) {
+        self.reasons = self.reasons.with(Reason::Gap);
+        self.timeline
+            .rewind(samples, self.last_detection.wrapping_add(1));
+    }
+
+    /// Notifies the detector of a pacing pulse, `samples_ago` samples before the next sample
+    /// passed to [`QrsDetector::update`], e.g. reported by the pace detector of the analog front
+    /// end.
+    ///
+    /// The processed signal is blanked after the pulse, so that the pacing artifact neither
+    /// triggers a detection nor distorts the thresholds, see [`Config::with_pace_blanking`]. The
+    /// first beat detected within 250ms after the blanking period is labeled as paced, see
+    /// [`Detection::paced`].
+    ///
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    ///
+    /// let mut detector = QrsDetector::new::<150, 25>(500.sps())
+    ///     .with_history([Detection::default(); 8]);
+    ///
+    /// # let samples = [(0.0, false); 10];
+    /// for (sample, pace) in samples {
+    ///     if pace {
+    ///         detector.notify_pace(0);
+    ///     }
+    ///     if detector.update(sample).is_some() {
+    ///         let paced = detector.recent_detections().last().unwrap().paced;
+    ///     }
+    /// }
+    /// ```
+    pub fn notify_pace(&mut self, samples_ago: u32) {
+        // The artifact spreads over the length of the filters, twice their group delay
+        let delay = self.transform.group_delay()
+            + self
+                .input_transform
+                .as_ref()
+                .map_or(0.0, Difference::group_delay);
+        let smear = micromath::F32Ext::ceil(2.0 * delay.max(0.0)) as u32;
+        let blanking = self.fs.ms_to_samples(self.config.pace_blanking_ms()) as u32 + smear;
+        let capture = blanking + self.fs.ms_to_samples(PACED_BEAT_WINDOW_MS) as u32;
+        self.pace.pulse(blanking, capture, samples_ago);
+    }
+
+    /// Notifies the detector that the application switched the lead it passes to
+    /// [`QrsDetector::update`], e.g. because an electrode fell off.
+    ///
+    /// The thresholds keep adapting to the new lead. Until the steep-slope threshold is refreshed
+    /// from the new lead, detections are marked with [`Reason::LeadSwitch`].
+    pub fn notify_lead_switch(&mut self) {
+        self.lead_switch_beats = self.config.mm_depth() as u8;
+    }
+
+    /// Returns the sample counter of the detector, see the [`timeline`] module.
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Records a suppressed detection if `sample` crosses the last known threshold.
+    fn check_suppressed(&mut self, sample: f32, reason: SuppressionReason) {
+        let above = self
+            .last_threshold
+            .is_some_and(|threshold| sample > threshold);
+
+        if above && !self.above_threshold {
+            self.record_suppressed(sample, self.last_threshold.unwrap_or(0.0), reason);
+        }
+
+        self.above_threshold = above;
+    }
+
+    fn record_suppressed(&mut self, sample: f32, threshold: f32, reason: SuppressionReason) {
+        match reason {
+            SuppressionReason::Refractory => self.suppressed_counts.refractory += 1,
+            SuppressionReason::SignalStep => self.suppressed_counts.signal_step += 1,
+            SuppressionReason::Noise => self.suppressed_counts.noise += 1,
+        }
+        if reason != SuppressionReason::Refractory {
+            self.reasons = self.reasons.with(Reason::Suppression);
+        }
+        if self.suppressed.capacity() > 0 {
+            self.suppressed.push(Suppressed {
+                index: self.input_index(),
+                amplitude: sample,
+                threshold,
+                reason,
+            });
+        }
+    }
+
+    /// Returns `false` if a threshold crossing does not exceed the estimated noise level by a
+    /// large margin.
+    #[cfg(feature = "quality")]
+    fn passes_noise_gate(&mut self, sample: f32, threshold: f32, was_above: bool) -> bool {
+        match self.noise.noise_level() {
+            Some(noise) if sample <= NOISE_GATE_RATIO * noise => {
+                if !was_above {
+                    self.record_suppressed(sample, threshold, SuppressionReason::Noise);
+                }
+                false
+            }
+            _ => true,
+        }
+    }
+
+    #[cfg(not(feature = "quality"))]
+    fn passes_noise_gate(&mut self, _sample: f32, _threshold: f32, _was_above: bool) -> bool {
+        true
+    }
+
+    /// Validates the internal invariants of the detector.
+    ///
+    /// This is a cheap check that may be called periodically to detect silent state corruption,
+    /// e.g. caused by non-finite input samples or memory errors.
+    pub fn verify(&self) -> Result<(), Corruption> {
+        self.m.verify()?;
+        self.f.verify(self.timeline.elapsed())?;
+        self.r.verify(self.timeline.elapsed())
+    }
+
+    /// Validates the internal invariants of the detector and resets the detector if they are
+    /// violated.
+    ///
+    /// The sample counter is preserved, so detection indices remain monotonic after recovery.
+    /// Returns the detected corruption, if any.
+    pub fn verify_or_recover(&mut self) -> Result<(), Corruption> {
+        let result = self.verify();
+        if result.is_err() {
+            self.clear();
+        }
         result
     }
 
+    /// Notifies the detector about amplifier saturation.
+    ///
+    /// While the amplifier is saturated, the detector may adapt its thresholds to the rail values.
+    /// When the saturation episode ends, threshold values learned during the episode are
+    /// discarded, so that the detector recovers in a single beat. The saturation state may be
+    /// determined using [`quality::SaturationDetector`].
+    #[cfg(feature = "quality")]
+    pub fn set_saturated(&mut self, saturated: bool) {
+        match (self.saturated, saturated) {
+            (false, true) => self.m.checkpoint(),
+            (true, false) => {
+                self.m.rollback();
+                self.reasons = self.reasons.with(Reason::QualityHold);
+            }
+            _ => {}
+        }
+        self.saturated = saturated;
+    }
+
+    /// Returns `true` if a refractory period has been forcibly ended since the last call.
+    ///
+    /// The refractory period following a detection is extended as long as the signal keeps
+    /// rising. To prevent a continuously rising input from blocking detection indefinitely, the
+    /// refractory period is limited to 1 second.
+    pub fn take_refractory_timeout(&mut self) -> bool {
+        core::mem::take(&mut self.m.refractory_timeout)
+    }
+
+    /// Returns `true` if a DC offset step has been detected in the signal since the last call.
+    /// Steps are only detected if enabled using [`Config::with_step_blanking`].
+    #[cfg(feature = "quality")]
+    pub fn take_signal_step(&mut self) -> bool {
+        self.step.take_event()
+    }
+
+    /// Returns the last skipped beat if one has been found since the last call.
+    ///
+    /// Requires the skipped beat diagnostics to be enabled, see
+    /// [`config::Config::with_skip_diagnostics`].
+    pub fn take_skipped_beat(&mut self) -> Option<SkippedBeat> {
+        self.skip.skipped.take()
+    }
+
+    /// Returns the number of RR intervals that were long enough to suggest a skipped beat.
+    ///
+    /// Requires the skipped beat diagnostics to be enabled, see
+    /// [`config::Config::with_skip_diagnostics`].
+    pub fn skipped_beat_count(&self) -> u32 {
+        self.skip.count
+    }
+
+    /// Returns the typical peak value of the detected QRS complexes, once the detector is
+    /// initialized.
+    pub fn peak_average(&self) -> Option<f32> {
+        self.m.peak_average()
+    }
+
+    /// Returns the typical peak value of the detected QRS complexes in millivolts, if the
+    /// calibration is set, see [`config::Config::with_calibration`].
+    pub fn peak_average_mv(&self) -> Option<f32> {
+        self.to_mv(self.peak_average()?)
+    }
+
+    /// Converts `value` to millivolts, if the calibration is set, see
+    /// [`config::Config::with_calibration`].
+    pub fn to_mv(&self, value: f32) -> Option<f32> {
+        self.config
+            .calibration()
+            .map(|lsb_per_mv| value / lsb_per_mv)
+    }
+
+    /// Returns the noise level estimated by the noise estimator, see
+    /// [`QrsDetector::with_noise_estimator`].
+    pub fn noise_level(&self) -> Option<f32> {
+        self.noise.noise_level()
+    }
+
+    /// Returns the sampling frequency of the processed signal.
+    pub fn sampling_frequency(&self) -> SamplingFrequency {
+        self.fs
+    }
+
+    /// Returns the most recent detections, from the oldest to the newest.
+    ///
+    /// The number of detections kept is determined by the buffer passed to
+    /// [`QrsDetector::with_history`].
+    pub fn recent_detections(&self) -> impl Iterator<Item = Detection> + '_ {
+        self.history.iter()
+    }
+
+    /// Returns the most recent detections in millivolts, if the calibration is set, see
+    /// [`QrsDetector::recent_detections`] and [`config::Config::with_calibration`].
+    pub fn recent_detections_mv(&self) -> Option<impl Iterator<Item = Detection> + '_> {
+        let lsb_per_mv = self.config.calibration()?;
+        Some(self.history.iter().map(move |d| d.to_mv(lsb_per_mv)))
+    }
+
+    /// Returns the number of threshold crossings that did not result in a detection.
+    pub fn suppressed_counts(&self) -> SuppressedCounts {
+        self.suppressed_counts
+    }
+
+    /// Returns the most recent suppressed threshold crossings, from the oldest to the newest.
+    ///
+    /// The number of crossings kept is determined by the buffer passed to
+    /// [`QrsDetector::with_suppressed_log`].
+    pub fn recent_suppressed(&self) -> impl Iterator<Item = Suppressed> + '_ {
+        self.suppressed.iter()
+    }
+
     /// Returns the current threshold value.
     /// This value is used to determine if a sample is a QRS complex.
     /// The final threshold is calculated as `M + F + R`.
@@ -208,15 +1475,666 @@ where
         Thresholds {
             m: self.m.threshold(),
             f: self.f.threshold(),
-            r: self.r.threshold(),
+            r: self.r.threshold().unwrap_or(0.0),
+        }
+    }
+
+    /// Returns the current threshold values in millivolts, if the calibration is set, see
+    /// [`config::Config::with_calibration`].
+    ///
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    ///
+    /// // 1000 units per millivolt
+    /// let config = Config::new().with_calibration(1000.0);
+    /// let detector = QrsDetector::new::<150, 25>(500.sps()).with_config(config);
+    ///
+    /// let thresholds_mv = detector.thresholds_mv().unwrap();
+    /// ```
+    pub fn thresholds_mv(&self) -> Option<Thresholds> {
+        let lsb_per_mv = self.config.calibration()?;
+        Some(self.thresholds() / lsb_per_mv)
+    }
+}
+
+impl<FMW, FB, H, T, S, N, FC> QrsDetector<FMW, FB, H, T, S, N, FC, R>
+where
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    T: SampleTransform,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    N: NoiseEstimator,
+    FC: ThresholdComponent,
+{
+    /// Resets the detector and presets its thresholds using `profile`.
+    ///
+    /// The detector starts detecting after about 300ms instead of the initial 3 seconds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    /// use qrs_detector::profile::PatientProfile;
+    ///
+    /// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+    ///
+    /// # let stored = PatientProfile {
+    /// #     r_amplitude: 1.5,
+    /// #     rr_mean_ms: 800.0,
+    /// #     rr_std_ms: 40.0,
+    /// #     polarity: Default::default(),
+    /// #     preferred_lead: None,
+    /// # }.to_bytes();
+    /// if let Ok(profile) = PatientProfile::from_bytes(&stored) {
+    ///     detector.warm_start(&profile);
+    /// }
+    /// ```
+    pub fn warm_start(&mut self, profile: &PatientProfile) {
+        self.clear();
+        if !profile.is_valid() {
+            return;
+        }
+
+        self.m.warm_start(profile.r_amplitude);
+        self.f.readapt();
+        self.r
+            .warm_start(self.fs.ms_to_samples(profile.rr_mean_ms) as u32);
+    }
+
+    /// Returns the adapted state of the detector, to be journaled using
+    /// [`journal::JournalWriter`].
+    pub fn journal_state(&self) -> JournalState {
+        JournalState {
+            sample_index: self.timeline.position(),
+            last_detection: self.timeline.to_absolute(self.last_detection),
+            mm: Values::collect(self.m.mm_values()),
+            rr: Values::collect(self.r.rr_intervals()),
+        }
+    }
+
+    /// Returns a deterministic dump of the internal state, e.g. to compare the detector against a
+    /// reference implementation in hardware-in-the-loop tests. See [`testing::Simulator`].
+    pub fn state_dump(&self) -> StateDump {
+        StateDump {
+            timeline: self.timeline,
+            last_detection: self.timeline.to_absolute(self.last_detection),
+            thresholds: self.thresholds(),
+            in_refractory: self.m.in_refractory(),
+            mm: Values::collect(self.m.mm_values()),
+            rr: Values::collect(self.r.rr_intervals()),
+            suppressed: self.suppressed_counts,
+            skipped_beats: self.skip.count,
+        }
+    }
+
+    /// Resets the detector and restores a state reconstructed by [`journal::JournalReader`],
+    /// e.g. after a power loss.
+    ///
+    /// The thresholds are restored immediately and the beat expectation threshold becomes active
+    /// after two detections, like after [`QrsDetector::warm_start`]. The sample counter continues
+    /// from the journaled value, samples lost since then should be reported using
+    /// [`QrsDetector::notify_gap`]. If the state was journaled with a different configuration,
+    /// the detector initializes its thresholds from the signal.
+    pub fn restore_journal(&mut self, state: &JournalState) {
+        self.clear();
+        // The new epoch starts at the last detection
+        self.timeline
+            .restart_at(state.last_detection, state.sample_index);
+        self.m.restore(state.mm_values());
+        if !state.mm_values().is_empty() {
+            self.f.readapt();
+        }
+        self.r.restore(state.rr_intervals(), 0);
+        self.last_detection = 0;
+    }
+
+    /// Returns the average of the last RR intervals, as many as the configured RR depth (see
+    /// [`config::Config::with_rr_depth`], 5 by default).
+    ///
+    /// Returns `None` until enough consecutive QRS complexes have been detected, or after the
+    /// rhythm has paused (see [`config::Config::with_r_timeout`]).
+    pub fn rr_average(&self) -> Option<Duration> {
+        self.r
+            .rr_average()
+            .map(|samples| Duration::from_secs_f32(self.fs.samples_to_s(samples as usize)))
+    }
+}
+
+impl<FMW, FB, H, T, S, N> QrsDetector<FMW, FB, H, T, S, N, F<FMW, FB>, R>
+where
+    FMW: AsRef<[f32]> + AsMut<[f32]>,
+    FB: AsRef<[f32]> + AsMut<[f32]>,
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+{
+    /// Returns the buffer capacities, fill levels and window lengths of the detector, for runtime
+    /// diagnostics.
+    ///
+    /// This is mostly useful when the buffers are provided by the caller, e.g. with sizes read
+    /// from a configuration file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrs_detector::prelude::*;
+    ///
+    /// let detector = QrsDetector::new_from(500.sps(), [0.0; 150], [0.0; 25])
+    ///     .with_history([Detection::default(); 8]);
+    ///
+    /// let introspection = detector.introspect();
+    /// assert!(introspection.f_buffers_match());
+    /// assert_eq!(8, introspection.history.capacity);
+    /// assert_eq!(0, introspection.history.len);
+    /// ```
+    pub fn introspect(&self) -> Introspection {
+        let fs = self.fs;
+        Introspection {
+            fs,
+            required: BufferSizes::for_fs(fs),
+            f_max_window: self.f.max_window_usage(),
+            f_buffer: self.f.buffer_usage(),
+            mm: self.m.mm_usage(),
+            rr: self.r.rr_usage(),
+            history: BufferUsage::new(self.history.capacity(), self.history.len()),
+            suppressed: BufferUsage::new(self.suppressed.capacity(), self.suppressed.len()),
+            refractory_samples: self.m.refractory_samples(),
+            m_decay_samples: self.m.decay_samples(),
+        }
+    }
+}
+
+/// Formats a value with 3 decimals, or the precision of the format string. Large values are
+/// formatted in scientific notation to keep the output short.
+fn fmt_value(f: &mut fmt::Formatter<'_>, value: f32) -> fmt::Result {
+    let precision = f.precision().unwrap_or(3);
+    if value.abs() < 1e6 {
+        write!(f, "{value:.precision$}")
+    } else {
+        write!(f, "{value:.precision$e}")
+    }
+}
+
+/// Sizes of the buffers required by [`QrsDetector`], in samples.
+///
+/// This is useful to calculate the type parameters of [`QrsDetector::new`] at compile time.
+///
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::BufferSizes;
+///
+/// const SIZES: BufferSizes = BufferSizes::for_sps(500);
+///
+/// let detector = QrsDetector::new::<{ SIZES.samples_300 }, { SIZES.samples_50 }>(500.sps());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferSizes {
+    /// The number of samples in 300ms.
+    pub samples_300: usize,
+    /// The number of samples in 50ms.
+    pub samples_50: usize,
+}
+
+impl BufferSizes {
+    /// Returns the buffer sizes for a signal sampled with `sps` samples per second.
+    pub const fn for_sps(sps: usize) -> Self {
+        Self {
+            samples_300: sps * 300 / 1000,
+            samples_50: sps * 50 / 1000,
+        }
+    }
+
+    /// Returns the buffer sizes for a signal sampled with `fs`.
+    pub fn for_fs(fs: SamplingFrequency) -> Self {
+        Self {
+            samples_300: fs.ms_to_samples(300.0),
+            samples_50: fs.ms_to_samples(50.0),
+        }
+    }
+
+    fn check(&self, samples_300: usize, samples_50: usize) -> Result<(), BufferSizeError> {
+        if samples_300 != self.samples_300 {
+            return Err(BufferSizeError::Samples300 {
+                expected: self.samples_300,
+                actual: samples_300,
+            });
+        }
+        if samples_50 != self.samples_50 {
+            return Err(BufferSizeError::Samples50 {
+                expected: self.samples_50,
+                actual: samples_50,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// An error returned by [`QrsDetector::try_new_from`] when a buffer does not match the sampling
+/// frequency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferSizeError {
+    /// The buffer containing 300ms worth of samples (`FMW`) has an incorrect size.
+    Samples300 {
+        /// The required size, in samples.
+        expected: usize,
+        /// The size of the provided buffer.
+        actual: usize,
+    },
+    /// The buffer containing 50ms worth of samples (`FB`) has an incorrect size.
+    Samples50 {
+        /// The required size, in samples.
+        expected: usize,
+        /// The size of the provided buffer.
+        actual: usize,
+    },
+}
+
+/// Formats the error as `incorrect 300ms buffer: <actual> samples, must be <expected> samples`.
+impl fmt::Display for BufferSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (window, expected, actual) = match *self {
+            Self::Samples300 { expected, actual } => (300, expected, actual),
+            Self::Samples50 { expected, actual } => (50, expected, actual),
+        };
+        write!(
+            f,
+            "incorrect {window}ms buffer: {actual} samples, must be {expected} samples"
+        )
+    }
+}
+
+/// The capacity and fill level of a buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufferUsage {
+    /// The number of values the buffer can hold.
+    pub capacity: usize,
+    /// The number of values currently in the buffer.
+    pub len: usize,
+}
+
+impl BufferUsage {
+    /// Creates a new `BufferUsage`.
+    pub const fn new(capacity: usize, len: usize) -> Self {
+        Self { capacity, len }
+    }
+
+    /// Returns `true` if the buffer is filled to its capacity.
+    pub const fn is_full(&self) -> bool {
+        self.len >= self.capacity
+    }
+
+    /// Returns the fill level of the buffer between 0 and 1. Buffers without capacity are
+    /// reported as 0.
+    pub fn utilization(&self) -> f32 {
+        if self.capacity == 0 {
+            0.0
+        } else {
+            self.len as f32 / self.capacity as f32
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Formats the usage as `<len>/<capacity>`.
+impl fmt::Display for BufferUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.len, self.capacity)
+    }
+}
+
+/// Buffer capacities, fill levels and window lengths of a detector, see
+/// [`QrsDetector::introspect`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Introspection {
+    /// The sampling frequency the detector was created for.
+    pub fs: SamplingFrequency,
+    /// The F buffer sizes required by the sampling frequency.
+    pub required: BufferSizes,
+    /// The buffer of 50ms maxima over the last 300ms (`FMW`).
+    pub f_max_window: BufferUsage,
+    /// The 50ms signal buffer (`FB`).
+    pub f_buffer: BufferUsage,
+    /// The MM buffer of the steep-slope threshold.
+    pub mm: BufferUsage,
+    /// The RR interval buffer of the beat expectation threshold.
+    pub rr: BufferUsage,
+    /// The detection history, see [`QrsDetector::with_history`].
+    pub history: BufferUsage,
+    /// The suppressed crossing log, see [`QrsDetector::with_suppressed_log`].
+    pub suppressed: BufferUsage,
+    /// The length of the refractory period, in samples.
+    pub refractory_samples: usize,
+    /// The length of the interval M decreases in after the refractory period, in samples.
+    pub m_decay_samples: usize,
+}
+
+impl Introspection {
+    /// Returns `true` if the F buffers have the sizes required by the sampling frequency.
+    pub fn f_buffers_match(&self) -> bool {
+        self.f_max_window.capacity == self.required.samples_300
+            && self.f_buffer.capacity == self.required.samples_50
+    }
+}
+
+/// Formats the buffer usage as `FMW=<len>/<capacity> FB=... MM=... RR=... H=... S=...`.
+impl fmt::Display for Introspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FMW={} FB={} MM={} RR={} H={} S={}",
+            self.f_max_window, self.f_buffer, self.mm, self.rr, self.history, self.suppressed
+        )
+    }
+}
+
+/// Internal state corruption reported by [`QrsDetector::verify`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corruption {
+    /// A threshold component holds a non-finite value.
+    NonFiniteThreshold,
+    /// A buffer is not filled even though the algorithm state requires it to be.
+    BufferNotFull,
+    /// A state counter is out of its valid range.
+    InvalidState,
+}
+
+/// A detected QRS complex.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Detection {
+    /// The sample index where the signal crossed the threshold, in the timeline of the input
+    /// signal.
+    pub index: u32,
+    /// The peak value of the QRS complex.
+    pub amplitude: f32,
+    /// The threshold value at the time of the detection.
+    pub threshold: f32,
+    /// Whether the beat followed a pacing pulse, see [`QrsDetector::notify_pace`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub paced: bool,
+    /// The conditions that altered the behavior of the detector around the beat.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reasons: Reasons,
+    /// The timing error of `index`, in samples, caused by the nonlinear phase of the
+    /// preprocessing.
+    ///
+    /// `index` is adjusted by the rounded [total group
+    /// delay](QrsDetector::total_group_delay_samples), but IIR filters delay the frequencies of
+    /// the QRS complex differently. Once the QRS complex has passed the threshold, its dominant
+    /// frequency is estimated from the width of the pulse, and this field is set to the
+    /// difference of the group delay at that frequency and the compensated delay, see
+    /// [`SampleTransform::group_delay_at`]. The beat occurred at `index - timing_correction`,
+    /// see [`Detection::corrected_interval`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timing_correction: f32,
+}
+
+impl Detection {
+    /// Returns how far the QRS complex exceeded the threshold, relative to its amplitude.
+    ///
+    /// The value is between 0 (barely detected) and 1.
+    pub fn confidence(&self) -> f32 {
+        if self.amplitude > 0.0 {
+            (1.0 - self.threshold / self.amplitude).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the number of samples since `previous`, e.g. the RR interval, corrected by the
+    /// [timing corrections](Detection::timing_correction) of both beats.
+    pub fn corrected_interval(&self, previous: &Detection) -> f32 {
+        self.index.wrapping_sub(previous.index) as f32
+            - (self.timing_correction - previous.timing_correction)
+    }
+
+    /// Returns the detection with its amplitude and threshold converted to millivolts, using
+    /// the gain of the processed signal in units per millivolt.
+    pub fn to_mv(&self, lsb_per_mv: f32) -> Self {
+        Self {
+            index: self.index,
+            amplitude: self.amplitude / lsb_per_mv,
+            threshold: self.threshold / lsb_per_mv,
+            paced: self.paced,
+            reasons: self.reasons,
+            timing_correction: self.timing_correction,
+        }
+    }
+}
+
+/// Formats the detection as `#<index> amp=<amplitude> thr=<threshold>`, followed by ` paced` for
+/// paced beats.
+///
+/// Values are formatted with 3 decimals by default, which can be overridden using the precision
+/// of the format string. With the default precision, the output fits into 128 bytes.
+impl fmt::Display for Detection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} amp=", self.index)?;
+        fmt_value(f, self.amplitude)?;
+        write!(f, " thr=")?;
+        fmt_value(f, self.threshold)?;
+        if self.paced {
+            write!(f, " paced")?;
+        }
+        Ok(())
+    }
+}
+
+/// A condition that altered the behavior of the detector around a beat, see
+/// [`Detection::reasons`].
+///
+/// Downstream analytics may exclude the marked beats, e.g. from heart rate variability metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Reason {
+    /// The lead was switched recently, and the thresholds are still adapting to it. See
+    /// [`QrsDetector::notify_lead_switch`].
+    LeadSwitch,
+    /// The amplifier was saturated, or the detector recovered from saturation or a DC offset
+    /// step since the previous beat. See [`QrsDetector::set_saturated`].
+    QualityHold,
+    /// Threshold crossings were suppressed as noise or signal steps since the previous beat, a
+    /// beat may have been missed.
+    Suppression,
+    /// Samples were lost or duplicated since the previous beat, see
+    /// [`QrsDetector::notify_gap`] and [`QrsDetector::notify_duplicate`]. The RR interval is not
+    /// reliable.
+    Gap,
+    /// The detector was reset or reinitialized by a configuration change since the previous beat.
+    Reinitialized,
+}
+
+impl Reason {
+    /// Every reason.
+    pub const ALL: [Self; 5] = [
+        Self::LeadSwitch,
+        Self::QualityHold,
+        Self::Suppression,
+        Self::Gap,
+        Self::Reinitialized,
+    ];
+
+    /// Returns the name of the reason.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::LeadSwitch => "lead_switch",
+            Self::QualityHold => "quality_hold",
+            Self::Suppression => "suppression",
+            Self::Gap => "gap",
+            Self::Reinitialized => "reinitialized",
+        }
+    }
+}
+
+/// A set of [`Reason`]s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reasons(u8);
+
+impl Reasons {
+    /// Returns an empty set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns a set of `reasons`.
+    pub const fn of(reasons: &[Reason]) -> Self {
+        let mut set = Self::empty();
+        let mut i = 0;
+        while i < reasons.len() {
+            set = set.with(reasons[i]);
+            i += 1;
+        }
+        set
+    }
+
+    /// Returns the set extended with `reason`.
+    pub const fn with(self, reason: Reason) -> Self {
+        Self(self.0 | 1 << reason as u8)
+    }
+
+    /// Returns `true` if the set contains `reason`.
+    pub const fn contains(&self, reason: Reason) -> bool {
+        self.0 & 1 << reason as u8 != 0
+    }
+
+    /// Returns `true` if the set is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the reasons in the set.
+    pub fn iter(&self) -> impl Iterator<Item = Reason> + '_ {
+        Reason::ALL
+            .into_iter()
+            .filter(|reason| self.contains(*reason))
+    }
+}
+
+/// Formats the set as a comma separated list of reason names, or `-` if the set is empty.
+impl fmt::Display for Reasons {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        for (i, reason) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", reason.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// A detection reported by [`QrsDetector::update_chunk`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkDetection {
+    /// The sample index where the signal crossed the threshold, in the timeline of the input
+    /// signal. See [`QrsDetector::total_group_delay_samples`].
+    pub index: u32,
+    /// The position of the sample within the chunk that completed the detection.
+    pub offset: usize,
+}
+
+/// The reason a threshold crossing did not result in a detection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SuppressionReason {
+    /// The crossing happened during the refractory period following a detection.
+    #[default]
+    Refractory,
+    /// The crossing happened while the signal was recovering from a DC offset step.
+    SignalStep,
+    /// The crossing did not exceed the estimated noise level by a large margin.
+    Noise,
+}
+
+/// A threshold crossing that did not result in a detection.
+///
+/// These are useful to tune the detector, as they are the candidates that were closest to being
+/// detected.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Suppressed {
+    /// The sample index where the signal crossed the threshold, in the timeline of the input
+    /// signal.
+    pub index: u32,
+    /// The sample value at the crossing.
+    pub amplitude: f32,
+    /// The last detection threshold before the crossing.
+    pub threshold: f32,
+    /// The reason the crossing was not detected.
+    pub reason: SuppressionReason,
+}
+
+/// The number of suppressed threshold crossings, by reason.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuppressedCounts {
+    /// Crossings during the refractory period.
+    pub refractory: u32,
+    /// Crossings while recovering from a DC offset step.
+    pub signal_step: u32,
+    /// Crossings within the noise level.
+    pub noise: u32,
+}
+
+/// Formats the counts as `refractory=<n> step=<n> noise=<n>`.
+impl fmt::Display for SuppressedCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "refractory={} step={} noise={}",
+            self.refractory, self.signal_step, self.noise
+        )
+    }
+}
+
+/// An RR interval that is long enough to suggest that a beat was not detected.
+///
+/// The sample closest to the threshold in the interval helps to distinguish a real pause from a
+/// beat that was too small to be detected. See [`config::Config::with_skip_diagnostics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkippedBeat {
+    /// The sample index of the detection starting the interval.
+    pub start: u32,
+    /// The sample index of the detection ending the interval.
+    pub end: u32,
+    /// The sample index closest to the threshold, outside of the refractory periods.
+    pub index: u32,
+    /// The sample value closest to the threshold.
+    pub amplitude: f32,
+    /// The threshold value at `index`.
+    pub threshold: f32,
+}
+
+impl SkippedBeat {
+    /// Returns how far the closest sample stayed below the threshold.
+    ///
+    /// The value is negative if the sample crossed the threshold but was rejected as noise.
+    pub fn margin(&self) -> f32 {
+        self.threshold - self.amplitude
+    }
+
+    /// Returns the margin relative to the threshold. A small value suggests that a beat was
+    /// missed, while a value close to 1 suggests a real pause.
+    pub fn relative_margin(&self) -> f32 {
+        if self.threshold > 0.0 {
+            self.margin() / self.threshold
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The components of the detection threshold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Thresholds {
+    /// Steep-slope threshold. `None` until the detector is initialized.
     pub m: Option<f32>,
+    /// Integrating threshold. `None` until the detector is initialized.
     pub f: Option<f32>,
+    /// Beat expectation threshold.
     pub r: f32,
 }
 
@@ -228,4 +2146,84 @@ impl Thresholds {
             None
         }
     }
+
+    /// Returns `true` if the corresponding components differ by at most `tolerance`.
+    ///
+    /// ```rust
+    /// # use qrs_detector::Thresholds;
+    /// #
+    /// let a = Thresholds { m: Some(1.0), f: Some(0.5), r: 0.0 };
+    /// let b = Thresholds { m: Some(1.001), f: Some(0.5), r: 0.0 };
+    ///
+    /// assert!(a.approx_eq(&b, 0.01));
+    /// assert!(!a.approx_eq(&b, 0.0001));
+    /// ```
+    pub fn approx_eq(&self, other: &Self, tolerance: f32) -> bool {
+        let close = |a: f32, b: f32| (a - b).abs() <= tolerance;
+        let close_opt = |a: Option<f32>, b: Option<f32>| match (a, b) {
+            (Some(a), Some(b)) => close(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+
+        close_opt(self.m, other.m) && close_opt(self.f, other.f) && close(self.r, other.r)
+    }
+}
+
+/// Scales every component of the thresholds, e.g. to normalize them for display.
+///
+/// ```rust
+/// # use qrs_detector::Thresholds;
+/// #
+/// let thresholds = Thresholds { m: Some(1.0), f: None, r: 0.5 } * 2.0;
+///
+/// assert_eq!(thresholds, Thresholds { m: Some(2.0), f: None, r: 1.0 });
+/// ```
+impl Mul<f32> for Thresholds {
+    type Output = Thresholds;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Thresholds {
+            m: self.m.map(|m| m * rhs),
+            f: self.f.map(|f| f * rhs),
+            r: self.r * rhs,
+        }
+    }
+}
+
+impl Div<f32> for Thresholds {
+    type Output = Thresholds;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        self * (1.0 / rhs)
+    }
+}
+
+/// Formats the thresholds as `M=<m> F=<f> R=<r>`, using `-` for missing components.
+///
+/// Values are formatted with 3 decimals by default, which can be overridden using the precision
+/// of the format string. With the default precision, the output fits into 128 bytes.
+///
+/// ```rust
+/// use qrs_detector::Thresholds;
+///
+/// let thresholds = Thresholds { m: Some(0.5), f: None, r: -0.125 };
+///
+/// assert_eq!("M=0.500 F=- R=-0.125", format!("{thresholds}"));
+/// assert_eq!("M=0.5 F=- R=-0.1", format!("{thresholds:.1}"));
+/// ```
+impl fmt::Display for Thresholds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let component = |f: &mut fmt::Formatter<'_>, name: &str, value: Option<f32>| {
+            write!(f, "{name}=")?;
+            match value {
+                Some(value) => fmt_value(f, value),
+                None => write!(f, "-"),
+            }
+        };
+
+        component(f, "M", self.m)?;
+        component(f, " F", self.f)?;
+        component(f, " R", Some(self.r))
+    }
 }
@@ -0,0 +1,134 @@
+//! Floating point helpers for `no_std` targets.
+
+/// Square root with full `f32` precision.
+///
+/// `micromath` only provides an approximation, which is refined using Newton's method.
+pub(crate) fn sqrt(x: f32) -> f32 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    let mut y = micromath::F32Ext::sqrt(x);
+    for _ in 0..2 {
+        y = 0.5 * (y + x / y);
+    }
+    y
+}
+
+/// 32 bit FNV-1a hash, used to identify configurations.
+pub(crate) struct Fnv1a(u32);
+
+impl Fnv1a {
+    pub(crate) const fn new() -> Self {
+        Self(0x811c_9dc5)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u32).wrapping_mul(0x0100_0193);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u32 {
+        self.0
+    }
+}
+
+/// CRC-16/CCITT-FALSE (polynomial 0x1021, initial value 0xFFFF), used to protect data sent over
+/// lossy links.
+#[cfg(feature = "hrv")]
+pub(crate) fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFF_u16;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-32 (ISO-HDLC, as used by zlib and Ethernet), used to protect data stored in non-volatile
+/// memory.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Sine of an `f64` value, usable in const contexts.
+///
+/// The argument is reduced to the `-π..=π` range, and the Taylor series is evaluated.
+const fn sin_f64(x: f64) -> f64 {
+    const PI: f64 = core::f64::consts::PI;
+
+    let turns = x / (2.0 * PI);
+    // Round to the nearest integer
+    let turns = if turns < 0.0 {
+        (turns - 0.5) as i64
+    } else {
+        (turns + 0.5) as i64
+    };
+    let x = x - turns as f64 * 2.0 * PI;
+
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 1;
+    while n < 12 {
+        term *= -x * x / ((2 * n) * (2 * n + 1)) as f64;
+        sum += term;
+        n += 1;
+    }
+    sum
+}
+
+/// Sine, usable in const contexts.
+#[cfg(feature = "preprocessing")]
+pub(crate) const fn const_sin(x: f32) -> f32 {
+    sin_f64(x as f64) as f32
+}
+
+/// Cosine, usable in const contexts.
+pub(crate) const fn const_cos(x: f32) -> f32 {
+    sin_f64(x as f64 + core::f64::consts::FRAC_PI_2) as f32
+}
+
+/// Returns the group delay of the polynomial `p[0] + p[1] z^-1 + p[2] z^-2` at `frequency`,
+/// relative to the sampling frequency, in samples.
+///
+/// The delay is the real part of `sum(k p[k] z^-k) / sum(p[k] z^-k)` on the unit circle. Returns
+/// `None` close to a zero of the polynomial, where the delay is not defined. The polynomial is
+/// evaluated in `f64`, because it nearly cancels out around the zeros of high-pass filters.
+pub(crate) fn polynomial_group_delay(p: [f32; 3], frequency: f32) -> Option<f32> {
+    let [p0, p1, p2] = p.map(f64::from);
+    let w = 2.0 * core::f64::consts::PI * frequency as f64;
+    let (sin, cos) = (sin_f64(w), sin_f64(w + core::f64::consts::FRAC_PI_2));
+    // z^-1 and z^-2
+    let (re1, im1) = (cos, -sin);
+    let (re2, im2) = (cos * cos - sin * sin, -2.0 * sin * cos);
+
+    let re = p0 + p1 * re1 + p2 * re2;
+    let im = p1 * im1 + p2 * im2;
+    let weighted_re = p1 * re1 + 2.0 * p2 * re2;
+    let weighted_im = p1 * im1 + 2.0 * p2 * im2;
+
+    let magnitude = re * re + im * im;
+    let scale = p0.abs() + p1.abs() + p2.abs();
+    if magnitude < 1e-14 * scale * scale {
+        return None;
+    }
+    Some(((weighted_re * re + weighted_im * im) / magnitude) as f32)
+}
@@ -0,0 +1,252 @@
+//! Integration of the MAX30003 biopotential analog front-end.
+//!
+//! The MAX30003 stores the ECG samples in a FIFO. Every word read from the `ECG_FIFO` register
+//! holds an 18-bit two's complement sample and an ECG tag (ETAG), which marks samples taken
+//! during fast recovery, the last sample in the FIFO, an empty FIFO and an overflow.
+//! [`EcgFifoWord`] decodes the words, and [`Max30003Detector`] maps the tags onto the gap
+//! handling and signal quality APIs of the detector:
+//!
+//! ```rust
+//! use qrs_detector::max30003::{Max30003Detector, Max30003Error};
+//! use qrs_detector::prelude::*;
+//!
+//! let mut frontend = Max30003Detector::new(QrsDetector::new::<38, 6>(128.sps()));
+//!
+//! # let mut fifo = [0x00_0040, 0x00_0010].into_iter();
+//! # let mut read_ecg_fifo = || fifo.next().unwrap_or(0x00_0030);
+//! // On the EINT interrupt, read the FIFO until it is empty
+//! loop {
+//!     let word = read_ecg_fifo();
+//!     match frontend.push_word(word) {
+//!         Ok(Some(beat)) => { /* Handle the detection */ }
+//!         Ok(None) => {}
+//!         Err(Max30003Error::Overflow) => { /* Send FIFO_RST */ break; }
+//!         Err(_) => break,
+//!     }
+//!     if frontend.is_fifo_drained() {
+//!         break;
+//!     }
+//! }
+//! ```
+
+use crate::{
+    component::ThresholdComponent, noise::NoiseEstimator, transform::SampleTransform, Detection,
+    QrsDetector, Suppressed,
+};
+
+/// The depth of the ECG FIFO, in words.
+pub const FIFO_DEPTH: u32 = 32;
+
+/// The ECG tag of a FIFO word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Etag {
+    /// A valid sample.
+    Valid,
+    /// A sample taken while the channel was in fast recovery mode.
+    FastMode,
+    /// A valid sample, and the last one in the FIFO.
+    LastValid,
+    /// A sample taken in fast recovery mode, and the last one in the FIFO.
+    LastFastMode,
+    /// The FIFO is empty. The word does not contain a sample.
+    Empty,
+    /// The FIFO overflowed, samples were lost. The word does not contain a sample, and the FIFO
+    /// must be reset using the `FIFO_RST` command.
+    Overflow,
+    /// A reserved tag value, e.g. because of a corrupted transfer.
+    Reserved(u8),
+}
+
+impl Etag {
+    /// Decodes a 3-bit tag.
+    pub const fn from_bits(bits: u8) -> Self {
+        match bits & 0b111 {
+            0b000 => Self::Valid,
+            0b001 => Self::FastMode,
+            0b010 => Self::LastValid,
+            0b011 => Self::LastFastMode,
+            0b110 => Self::Empty,
+            0b111 => Self::Overflow,
+            bits => Self::Reserved(bits),
+        }
+    }
+
+    /// Returns whether the word contains a sample.
+    pub const fn has_sample(self) -> bool {
+        matches!(
+            self,
+            Self::Valid | Self::FastMode | Self::LastValid | Self::LastFastMode
+        )
+    }
+
+    /// Returns whether the sample was taken in fast recovery mode.
+    pub const fn is_fast_mode(self) -> bool {
+        matches!(self, Self::FastMode | Self::LastFastMode)
+    }
+
+    /// Returns whether the FIFO contains no more samples after this word.
+    pub const fn is_last(self) -> bool {
+        matches!(
+            self,
+            Self::LastValid | Self::LastFastMode | Self::Empty | Self::Overflow
+        )
+    }
+}
+
+/// A decoded word of the ECG FIFO.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EcgFifoWord {
+    /// The sign-extended 18-bit sample. 0 if the tag indicates no sample.
+    pub sample: i32,
+    /// The ECG tag.
+    pub etag: Etag,
+}
+
+impl EcgFifoWord {
+    /// Decodes the 24-bit value of the `ECG_FIFO` register. Bits above 23 are ignored.
+    pub const fn decode(word: u32) -> Self {
+        let etag = Etag::from_bits((word >> 3) as u8);
+        // Move the sample to the top of the word, then sign-extend it by an arithmetic shift
+        let sample = if etag.has_sample() {
+            ((word << 8) as i32) >> 14
+        } else {
+            0
+        };
+
+        Self { sample, etag }
+    }
+
+    /// Decodes the 3 data bytes of a register read, most significant byte first.
+    pub const fn from_be_bytes(bytes: [u8; 3]) -> Self {
+        let [a, b, c] = bytes;
+        Self::decode(u32::from_be_bytes([0, a, b, c]))
+    }
+}
+
+/// An error returned by [`Max30003Detector::push_word`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Max30003Error {
+    /// The FIFO overflowed. The detector has been notified of the lost samples, and the FIFO
+    /// must be reset using the `FIFO_RST` command.
+    Overflow,
+    /// The word has a reserved tag. The word is treated as a lost sample.
+    InvalidTag(u8),
+}
+
+impl core::fmt::Display for Max30003Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "ECG FIFO overflow"),
+            Self::InvalidTag(tag) => write!(f, "invalid ECG tag {tag:03b}"),
+        }
+    }
+}
+
+/// Feeds the ECG FIFO words of a MAX30003 to a detector.
+///
+/// For every word, [`Max30003Detector::push_word`]:
+///
+/// - processes valid samples, and returns the detection,
+/// - reports samples taken in fast recovery mode as amplifier saturation, so that the thresholds
+///   learned during recovery are discarded (see [`QrsDetector::set_saturated`], `quality`
+///   feature),
+/// - notifies the detector of the samples lost to a FIFO overflow, by default the depth of the
+///   FIFO (see [`QrsDetector::notify_gap`] and [`Max30003Detector::with_overflow_gap`]),
+/// - ignores the words of an empty FIFO.
+///
+/// The samples are ADC codes. Configure the detector with
+/// [`Config::with_calibration`](crate::config::Config::with_calibration) to work in millivolts.
+pub struct Max30003Detector<FMW, FB, H, T, S, N, FC, RC> {
+    detector: QrsDetector<FMW, FB, H, T, S, N, FC, RC>,
+    overflow_gap: u32,
+    last_etag: Option<Etag>,
+    overflows: u32,
+}
+
+impl<FMW, FB, H, T, S, N, FC, RC> Max30003Detector<FMW, FB, H, T, S, N, FC, RC>
+where
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    T: SampleTransform,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    N: NoiseEstimator,
+    FC: ThresholdComponent,
+    RC: ThresholdComponent,
+{
+    /// Creates a front-end feeding `detector`.
+    pub fn new(detector: QrsDetector<FMW, FB, H, T, S, N, FC, RC>) -> Self {
+        Self {
+            detector,
+            overflow_gap: FIFO_DEPTH,
+            last_etag: None,
+            overflows: 0,
+        }
+    }
+
+    /// Sets the number of samples assumed to be lost on a FIFO overflow. Defaults to
+    /// [`FIFO_DEPTH`], the contents of the FIFO discarded by the reset. Applications that know
+    /// the time between FIFO reads can provide a better estimate.
+    pub fn with_overflow_gap(mut self, samples: u32) -> Self {
+        self.overflow_gap = samples;
+        self
+    }
+
+    /// Returns the detector.
+    pub fn detector(&self) -> &QrsDetector<FMW, FB, H, T, S, N, FC, RC> {
+        &self.detector
+    }
+
+    /// Returns the detector, e.g. to apply a configuration.
+    pub fn detector_mut(&mut self) -> &mut QrsDetector<FMW, FB, H, T, S, N, FC, RC> {
+        &mut self.detector
+    }
+
+    /// Returns the detector, consuming the front-end.
+    pub fn into_inner(self) -> QrsDetector<FMW, FB, H, T, S, N, FC, RC> {
+        self.detector
+    }
+
+    /// Returns the tag of the last word.
+    pub fn last_etag(&self) -> Option<Etag> {
+        self.last_etag
+    }
+
+    /// Returns whether the last word emptied the FIFO, i.e. the application should stop reading
+    /// until the next interrupt.
+    pub fn is_fifo_drained(&self) -> bool {
+        self.last_etag.is_some_and(Etag::is_last)
+    }
+
+    /// Returns the number of FIFO overflows since the front-end was created.
+    pub fn overflows(&self) -> u32 {
+        self.overflows
+    }
+
+    /// Processes the 24-bit value of the `ECG_FIFO` register. Returns Some sample index if a QRS
+    /// complex is detected, see [`QrsDetector::update`].
+    pub fn push_word(&mut self, word: u32) -> Result<Option<u32>, Max30003Error> {
+        self.push(EcgFifoWord::decode(word))
+    }
+
+    /// Processes a decoded FIFO word, see [`Max30003Detector::push_word`].
+    pub fn push(&mut self, word: EcgFifoWord) -> Result<Option<u32>, Max30003Error> {
+        self.last_etag = Some(word.etag);
+        match word.etag {
+            Etag::Empty => Ok(None),
+            Etag::Overflow => {
+                self.overflows = self.overflows.wrapping_add(1);
+                self.detector.notify_gap(self.overflow_gap);
+                Err(Max30003Error::Overflow)
+            }
+            Etag::Reserved(tag) => {
+                self.detector.notify_gap(1);
+                Err(Max30003Error::InvalidTag(tag))
+            }
+            _ => {
+                #[cfg(feature = "quality")]
+                self.detector.set_saturated(word.etag.is_fast_mode());
+
+                Ok(self.detector.update(word.sample as f32))
+            }
+        }
+    }
+}
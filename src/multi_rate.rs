@@ -0,0 +1,379 @@
+//! Split-rate processing: QRS detection on a decimated signal, beat measurements on the full rate
+//! signal.
+//!
+//! Detection does not require a high sampling frequency, but measurements of the beat morphology
+//! do. [`MultiRateDetector`] runs the detector on a decimated signal to save processing time, and
+//! measures the detected beats on the buffered full rate signal.
+
+use crate::{
+    fiducial::{FiducialLocator, Fiducials, PWave},
+    history::HistoryBuffer,
+    noise::{NoEstimator, NoiseEstimator},
+    sampling::{SamplingFrequency, SamplingFrequencyExt},
+    transform::{Identity, SampleTransform},
+    Detection, QrsDetector, Suppressed,
+};
+
+/// The full rate buffer must hold at least this much signal.
+pub const BUFFER_MS: f32 = 350.0;
+/// The full rate buffer must hold at least this much signal to check for P waves, see
+/// [`MultiRateDetector::with_p_wave_check`].
+pub const P_WAVE_BUFFER_MS: f32 = 600.0;
+
+/// The baseline is averaged over this period, starting 350 ms before the measurement.
+const BASELINE_MS: f32 = 40.0;
+/// The peak is searched for in this period before and after the detection.
+const PEAK_BEFORE_MS: f32 = 50.0;
+const PEAK_AFTER_MS: f32 = 100.0;
+/// The ST level is measured this long after the peak.
+const ST_OFFSET_MS: f32 = 100.0;
+/// RR intervals longer than this are not used to scale the fiducial search windows.
+const MAX_RR_MS: f32 = 2000.0;
+/// The samples following the detection that are needed to measure the beat.
+const POST_DETECTION_MS: f32 = PEAK_AFTER_MS + ST_OFFSET_MS;
+/// A peak is clipped if at least this many consecutive samples are at its value.
+const CLIPPED_SAMPLES: u32 = 3;
+/// The number of samples of each flank the amplitude of a clipped peak is extrapolated from.
+const FLANK_SAMPLES: u32 = 3;
+/// The extrapolated amplitude is limited to this multiple of the clipped amplitude.
+const MAX_EXTRAPOLATION: f32 = 4.0;
+
+/// Measurements of a detected beat, on the full rate signal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BeatMeasurement {
+    /// The full rate sample index of the R peak.
+    pub peak_index: u32,
+    /// The amplitude of the R peak, relative to the baseline. Estimated if the peak is
+    /// [clipped](BeatMeasurement::clipped).
+    pub amplitude: f32,
+    /// The width of the QRS complex, measured where the signal is above half of the amplitude.
+    pub qrs_width_ms: f32,
+    /// The signal level 100 ms after the R peak, relative to the baseline.
+    pub st_level: f32,
+    /// Whether the R peak is clipped, i.e. has a flat top at the limit of the amplifier range.
+    /// The amplitude of a clipped peak is extrapolated from its flanks, assuming a parabolic
+    /// shape, and the peak index is the middle of the flat top.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub clipped: bool,
+    /// The Q and S points bounding the R peak, see [`FiducialLocator`]. The search windows
+    /// scale with the interval since the previous measured beat.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fiducials: Fiducials,
+    /// The result of the P wave presence check, if enabled using
+    /// [`MultiRateDetector::with_p_wave_check`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub p_wave: Option<PWave>,
+}
+
+/// Detects QRS complexes on a decimated signal and measures them on the full rate signal.
+///
+/// The full rate signal is decimated by averaging blocks of `factor` samples. The detector
+/// should preprocess the decimated signal using a [`SampleTransform`], see
+/// [`QrsDetector::with_transform`].
+///
+/// Beats are measured 200 ms after their detection, when enough of the signal has been
+/// buffered.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::multi_rate::MultiRateDetector;
+/// use qrs_detector::prelude::*;
+///
+/// // Detect at 250 sps, measure at 1000 sps
+/// let detector = QrsDetector::new::<75, 12>(250.sps()).with_transform(|sample: f32| sample);
+/// let mut multi_rate = MultiRateDetector::new(detector, 4, [0.0; 512]);
+///
+/// # let samples = [0.0; 10];
+/// for sample in samples {
+///     if let Some(beat) = multi_rate.push(sample) {
+///         // Process the measured beat
+///     }
+/// }
+/// ```
+pub struct MultiRateDetector<
+    B,
+    FMW,
+    FB,
+    H = [Detection; 0],
+    T = Identity,
+    S = [Suppressed; 0],
+    N = NoEstimator,
+> {
+    detector: QrsDetector<FMW, FB, H, T, S, N>,
+    fs: SamplingFrequency,
+    factor: u32,
+    buffer: HistoryBuffer<f32, B>,
+    block_sum: f32,
+    block_samples: u32,
+    pending: Option<u32>,
+    fiducials: FiducialLocator,
+    p_wave_check: bool,
+    /// The R peak of the previous measured beat.
+    last_peak: Option<u32>,
+}
+
+impl<B, FMW, FB, H, T, S, N> MultiRateDetector<B, FMW, FB, H, T, S, N>
+where
+    B: AsRef<[f32]> + AsMut<[f32]>,
+    FMW: AsRef<[f32]> + AsMut<[f32]>,
+    FB: AsRef<[f32]> + AsMut<[f32]>,
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    T: SampleTransform,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    N: NoiseEstimator,
+{
+    /// Creates a new multi-rate detector.
+    ///
+    /// # Arguments
+    /// * `detector` - The detector running on the decimated signal.
+    /// * `factor` - The ratio of the full and decimated sampling frequencies.
+    /// * `buffer` - A buffer for at least 350ms worth of full rate samples. The length must be a
+    ///   power of two, see [`HistoryBuffer`].
+    pub fn new(detector: QrsDetector<FMW, FB, H, T, S, N>, factor: u32, buffer: B) -> Self {
+        assert!(factor > 0, "The decimation factor must be at least 1");
+
+        let fs = (detector.sampling_frequency().raw() * factor as f32).sps();
+        let buffer = HistoryBuffer::new(buffer);
+
+        // sanity check buffer size
+        assert!(
+            buffer.capacity() >= fs.ms_to_samples(BUFFER_MS),
+            "Buffer too small. Buffer must be at least {} samples long.",
+            fs.ms_to_samples(BUFFER_MS)
+        );
+
+        Self {
+            detector,
+            fs,
+            factor,
+            buffer,
+            block_sum: 0.0,
+            block_samples: 0,
+            pending: None,
+            fiducials: FiducialLocator::new(fs),
+            p_wave_check: false,
+            last_peak: None,
+        }
+    }
+
+    /// Enables the P wave presence check of the measured beats, see
+    /// [`FiducialLocator::p_wave`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer holds less than 600 ms of the full rate signal.
+    pub fn with_p_wave_check(mut self) -> Self {
+        assert!(
+            self.buffer.capacity() >= self.fs.ms_to_samples(P_WAVE_BUFFER_MS),
+            "Buffer too small. Buffer must be at least {} samples long.",
+            self.fs.ms_to_samples(P_WAVE_BUFFER_MS)
+        );
+        self.p_wave_check = true;
+        self
+    }
+
+    /// Returns the underlying detector.
+    pub fn detector(&self) -> &QrsDetector<FMW, FB, H, T, S, N> {
+        &self.detector
+    }
+
+    /// Returns the full sampling frequency.
+    pub fn sampling_frequency(&self) -> SamplingFrequency {
+        self.fs
+    }
+
+    /// Resets the internal state, including the detector.
+    pub fn clear(&mut self) {
+        self.detector.clear();
+        self.buffer.clear();
+        self.pending = None;
+        self.last_peak = None;
+        // The current block is kept, so that detection indices remain aligned with the full rate
+        // sample index.
+    }
+
+    /// Processes a full rate sample. Returns the measurements of a previously detected beat, once
+    /// they are available.
+    pub fn push(&mut self, sample: f32) -> Option<BeatMeasurement> {
+        let newest = self.buffer.push(sample);
+
+        self.block_sum += sample;
+        self.block_samples += 1;
+        if self.block_samples == self.factor {
+            let decimated = self.block_sum / self.factor as f32;
+            self.block_sum = 0.0;
+            self.block_samples = 0;
+
+            if let Some(idx) = self.detector.update(decimated) {
+                // The detector is not fast enough to detect a beat while the previous one is
+                // being measured, but a reset may cause it to.
+                self.pending = Some(idx.wrapping_mul(self.factor));
+            }
+        }
+
+        let detection = self.pending?;
+        let post = self.fs.ms_to_samples(POST_DETECTION_MS) as u32;
+        if newest.wrapping_sub(detection) < post {
+            return None;
+        }
+
+        self.pending = None;
+        let beat = self.measure(detection);
+        if let Some(beat) = beat {
+            self.last_peak = Some(beat.peak_index);
+        }
+        beat
+    }
+
+    fn measure(&self, detection: u32) -> Option<BeatMeasurement> {
+        let ms = |ms: f32| self.fs.ms_to_samples(ms) as u32;
+
+        let start = detection.wrapping_sub(ms(PEAK_BEFORE_MS));
+        let end = detection.wrapping_add(ms(PEAK_AFTER_MS));
+
+        // Average the signal before the QRS complex to find the baseline
+        let baseline_start = self.buffer.next_index().wrapping_sub(ms(BUFFER_MS));
+        let baseline_samples = ms(BASELINE_MS).max(1);
+        let baseline = self
+            .buffer
+            .range(
+                baseline_start,
+                baseline_start.wrapping_add(baseline_samples),
+            )?
+            .sum::<f32>()
+            / baseline_samples as f32;
+
+        // Find the R peak
+        let mut peak_index = start;
+        let mut amplitude = 0.0f32;
+        let mut i = start;
+        while i != end {
+            let value = self.buffer.get(i)? - baseline;
+            if value.abs() > amplitude.abs() {
+                peak_index = i;
+                amplitude = value;
+            }
+            i = i.wrapping_add(1);
+        }
+
+        // A flat top at the peak value means the amplifier clipped the R peak
+        let mut clipped = false;
+        let mut flat_end = peak_index;
+        while amplitude != 0.0
+            && flat_end != end
+            && self.buffer.get(flat_end.wrapping_add(1))? - baseline == amplitude
+        {
+            flat_end = flat_end.wrapping_add(1);
+        }
+        if flat_end.wrapping_sub(peak_index) + 1 >= CLIPPED_SAMPLES {
+            clipped = true;
+            let flat_start = peak_index;
+            peak_index = flat_start.wrapping_add(flat_end.wrapping_sub(flat_start) / 2);
+            if let Some(estimate) = self.extrapolate_peak(flat_start, flat_end, baseline, amplitude)
+            {
+                amplitude = estimate;
+            }
+        }
+
+        // Measure the width where the signal is above half of the amplitude
+        let above_half = |index: u32| {
+            self.buffer.get(index).is_some_and(|value| {
+                (value - baseline) * amplitude.signum() > amplitude.abs() / 2.0
+            })
+        };
+        let mut first = peak_index;
+        while first != start && above_half(first.wrapping_sub(1)) {
+            first = first.wrapping_sub(1);
+        }
+        let mut last = peak_index;
+        while last != end && above_half(last.wrapping_add(1)) {
+            last = last.wrapping_add(1);
+        }
+        let width = last.wrapping_sub(first) as usize + 1;
+
+        let st_level = self.buffer.get(peak_index.wrapping_add(ms(ST_OFFSET_MS)))? - baseline;
+
+        let rr_ms = self
+            .last_peak
+            .map(|last| {
+                self.fs
+                    .samples_to_ms(peak_index.wrapping_sub(last) as usize)
+            })
+            .filter(|&rr_ms| rr_ms <= MAX_RR_MS);
+        let fiducials = self
+            .fiducials
+            .locate(&self.buffer, peak_index, amplitude, rr_ms)?;
+        let p_wave = if self.p_wave_check {
+            self.fiducials
+                .p_wave(&self.buffer, &fiducials, amplitude, rr_ms)
+        } else {
+            None
+        };
+
+        Some(BeatMeasurement {
+            peak_index,
+            amplitude,
+            qrs_width_ms: self.fs.samples_to_ms(width),
+            st_level,
+            clipped,
+            fiducials,
+            p_wave,
+        })
+    }
+
+    /// Estimates the amplitude of a peak clipped at `clipped` between `flat_start` and
+    /// `flat_end`, by fitting a parabola to the samples of its flanks.
+    fn extrapolate_peak(
+        &self,
+        flat_start: u32,
+        flat_end: u32,
+        baseline: f32,
+        clipped: f32,
+    ) -> Option<f32> {
+        // Centering the time axis on the flat top keeps the fit well conditioned
+        let center = flat_end.wrapping_sub(flat_start) as f32 / 2.0;
+        let left = (1..=FLANK_SAMPLES).map(|i| flat_start.wrapping_sub(i));
+        let right = (1..=FLANK_SAMPLES).map(|i| flat_end.wrapping_add(i));
+
+        // Normal equations of the least squares fit of `a * t^2 + b * t + c`
+        let mut t_sums = [0.0f32; 5];
+        let mut ty_sums = [0.0f32; 3];
+        for index in left.chain(right) {
+            let t = index.wrapping_sub(flat_start) as i32 as f32 - center;
+            let y = (self.buffer.get(index)? - baseline) * clipped.signum();
+            let mut t_n = 1.0;
+            for (power, sum) in t_sums.iter_mut().enumerate() {
+                *sum += t_n;
+                if power < 3 {
+                    ty_sums[power] += t_n * y;
+                }
+                t_n *= t;
+            }
+        }
+
+        let [s0, s1, s2, s3, s4] = t_sums;
+        let [y0, y1, y2] = ty_sums;
+        let det3 = |m: [[f32; 3]; 3]| {
+            m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+                - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+                + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+        };
+        let det = det3([[s4, s3, s2], [s3, s2, s1], [s2, s1, s0]]);
+        if det == 0.0 {
+            return None;
+        }
+        let a = det3([[y2, s3, s2], [y1, s2, s1], [y0, s1, s0]]) / det;
+        let b = det3([[s4, y2, s2], [s3, y1, s1], [s2, y0, s0]]) / det;
+        let c = det3([[s4, s3, y2], [s3, s2, y1], [s2, s1, y0]]) / det;
+        if a >= 0.0 {
+            // The flanks do not curve towards a peak
+            return None;
+        }
+
+        let vertex = c - b * b / (4.0 * a);
+        let magnitude = clipped.abs();
+        let estimate = vertex.clamp(magnitude, MAX_EXTRAPOLATION * magnitude);
+        Some(estimate * clipped.signum())
+    }
+}
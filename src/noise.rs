@@ -0,0 +1,159 @@
+//! Noise level estimation.
+//!
+//! The detector feeds its input to a [`NoiseEstimator`], see
+//! [`QrsDetector::with_noise_estimator`](crate::QrsDetector::with_noise_estimator). The estimated
+//! noise level is available for telemetry using
+//! [`QrsDetector::noise_level`](crate::QrsDetector::noise_level).
+
+use crate::{math::sqrt, sampling::SamplingFrequency};
+
+/// The scale factor of the median absolute deviation to estimate the standard deviation of
+/// normally distributed noise.
+const MAD_SCALE: f32 = 1.4826;
+
+/// Estimates the noise level of a signal.
+pub trait NoiseEstimator {
+    /// Processes a sample. `in_qrs` is set for samples that are part of a detected QRS complex.
+    fn update(&mut self, sample: f32, in_qrs: bool);
+
+    /// Returns the estimated noise level, in the units of the signal.
+    fn noise_level(&self) -> Option<f32>;
+
+    /// Resets the internal state of the estimator.
+    fn clear(&mut self) {}
+}
+
+/// Does not estimate the noise level. This is the default estimator of the detector.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoEstimator;
+
+impl NoiseEstimator for NoEstimator {
+    #[inline(always)]
+    fn update(&mut self, _sample: f32, _in_qrs: bool) {}
+
+    #[inline(always)]
+    fn noise_level(&self) -> Option<f32> {
+        None
+    }
+}
+
+/// Estimates the noise level as the RMS value of the signal outside of the QRS complexes.
+///
+/// The estimate is updated every second.
+#[derive(Clone, Copy, Debug)]
+pub struct RmsNoise {
+    window: u32,
+    remaining: u32,
+    sum: f32,
+    count: u32,
+    level: Option<f32>,
+}
+
+impl RmsNoise {
+    /// Creates a new estimator for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        let window = fs.s_to_samples(1.0) as u32;
+        Self {
+            window,
+            remaining: window,
+            sum: 0.0,
+            count: 0,
+            level: None,
+        }
+    }
+}
+
+impl NoiseEstimator for RmsNoise {
+    fn update(&mut self, sample: f32, in_qrs: bool) {
+        if !in_qrs {
+            self.sum += sample * sample;
+            self.count += 1;
+        }
+
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            if self.count > 0 {
+                self.level = Some(sqrt(self.sum / self.count as f32));
+            }
+            self.remaining = self.window;
+            self.sum = 0.0;
+            self.count = 0;
+        }
+    }
+
+    fn noise_level(&self) -> Option<f32> {
+        self.level
+    }
+
+    fn clear(&mut self) {
+        self.remaining = self.window;
+        self.sum = 0.0;
+        self.count = 0;
+        self.level = None;
+    }
+}
+
+/// Estimates the noise level as the scaled median absolute deviation of the last `N` samples.
+///
+/// The median absolute deviation is insensitive to the QRS complexes as long as they make up a
+/// small part of the window. The estimate is updated every `N` samples.
+#[derive(Clone, Copy, Debug)]
+pub struct MadNoise<const N: usize> {
+    samples: [f32; N],
+    count: usize,
+    level: Option<f32>,
+}
+
+impl<const N: usize> Default for MadNoise<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> MadNoise<N> {
+    /// Creates a new estimator.
+    pub fn new() -> Self {
+        assert!(N > 0, "The window must not be empty");
+        Self {
+            samples: [0.0; N],
+            count: 0,
+            level: None,
+        }
+    }
+
+    fn median(values: &mut [f32; N]) -> f32 {
+        values.sort_unstable_by(f32::total_cmp);
+        if N.is_multiple_of(2) {
+            (values[N / 2 - 1] + values[N / 2]) / 2.0
+        } else {
+            values[N / 2]
+        }
+    }
+}
+
+impl<const N: usize> NoiseEstimator for MadNoise<N> {
+    fn update(&mut self, sample: f32, _in_qrs: bool) {
+        self.samples[self.count] = sample;
+        self.count += 1;
+
+        if self.count == N {
+            self.count = 0;
+
+            let mut values = self.samples;
+            let median = Self::median(&mut values);
+            for value in &mut values {
+                *value = (*value - median).abs();
+            }
+            self.level = Some(MAD_SCALE * Self::median(&mut values));
+        }
+    }
+
+    fn noise_level(&self) -> Option<f32> {
+        self.level
+    }
+
+    fn clear(&mut self) {
+        self.count = 0;
+        self.level = None;
+    }
+}
@@ -0,0 +1,11 @@
+//! Commonly used types and traits.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//!
+//! let detector = QrsDetector::new::<150, 25>(500.sps());
+//! ```
+
+pub use crate::config::Config;
+pub use crate::sampling::{SamplingFrequency, SamplingFrequencyExt};
+pub use crate::{Detection, QrsDetector, Thresholds};
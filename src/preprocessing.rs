@@ -0,0 +1,1064 @@
+//! Filters to preprocess the ECG signal before QRS detection.
+//!
+//! The filters implement [`SampleTransform`], so they can be chained and passed to
+//! [`QrsDetector::with_transform`](crate::QrsDetector::with_transform).
+//!
+//! Filter coefficients are calculated by `const fn`s. When the sampling frequency is known at
+//! compile time, the coefficients are evaluated by the compiler, so no trigonometric functions
+//! are needed at runtime and the coefficients can be placed in flash.
+//!
+//! ```rust
+//! use qrs_detector::preprocessing::{Biquad, BiquadCoefficients};
+//! use qrs_detector::sampling::SamplingFrequency;
+//! use qrs_detector::transform::SampleTransform;
+//!
+//! const FS: SamplingFrequency = SamplingFrequency::from_sps(500.0);
+//! const NOTCH: BiquadCoefficients = BiquadCoefficients::notch(FS, 50.0, 30.0);
+//!
+//! let mut notch = Biquad::new(NOTCH);
+//! let filtered = notch.transform(0.5);
+//! ```
+
+use crate::{
+    math::{const_cos, const_sin, polynomial_group_delay},
+    sampling::SamplingFrequency,
+    spectrum::Goertzel,
+    transform::SampleTransform,
+};
+
+/// Coefficients of a second order IIR filter, normalized so that `a0 = 1`.
+///
+/// The constructors implement the formulas of the Audio EQ Cookbook by Robert Bristow-Johnson.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BiquadCoefficients {
+    /// Feedforward coefficient of the current sample.
+    pub b0: f32,
+    /// Feedforward coefficient of the previous sample.
+    pub b1: f32,
+    /// Feedforward coefficient of the sample before the previous one.
+    pub b2: f32,
+    /// Feedback coefficient of the previous output.
+    pub a1: f32,
+    /// Feedback coefficient of the output before the previous one.
+    pub a2: f32,
+}
+
+impl BiquadCoefficients {
+    /// Returns the coefficients of a low-pass filter with the given cutoff frequency, in Hz, and
+    /// quality factor. A quality factor of `1 / sqrt(2)` results in a Butterworth response.
+    pub const fn lowpass(fs: SamplingFrequency, cutoff: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::prewarp(fs, cutoff, q);
+        Self::normalize(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            cos,
+            alpha,
+        )
+    }
+
+    /// Returns the coefficients of a high-pass filter with the given cutoff frequency, in Hz, and
+    /// quality factor. A quality factor of `1 / sqrt(2)` results in a Butterworth response.
+    pub const fn highpass(fs: SamplingFrequency, cutoff: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::prewarp(fs, cutoff, q);
+        Self::normalize(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            cos,
+            alpha,
+        )
+    }
+
+    /// Returns the coefficients of a notch filter at the given frequency, in Hz. A higher quality
+    /// factor results in a narrower notch.
+    pub const fn notch(fs: SamplingFrequency, frequency: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::prewarp(fs, frequency, q);
+        Self::normalize([1.0, -2.0 * cos, 1.0], cos, alpha)
+    }
+
+    /// Returns the group delay of the filter at low frequencies, in samples. Filters that block
+    /// DC, e.g. high-pass filters, are considered to have no delay.
+    pub const fn group_delay(&self) -> f32 {
+        let b_sum = self.b0 + self.b1 + self.b2;
+        let b_magnitude = abs(self.b0) + abs(self.b1) + abs(self.b2);
+        if abs(b_sum) < 1e-4 * b_magnitude {
+            return 0.0;
+        }
+
+        // The delay of the zeros minus the delay of the poles, at DC
+        let zeros = (self.b1 + 2.0 * self.b2) / b_sum;
+        let poles = (self.a1 + 2.0 * self.a2) / (1.0 + self.a1 + self.a2);
+        zeros - poles
+    }
+
+    /// Returns the group delay of the filter at `frequency`, relative to the sampling frequency,
+    /// in samples. Falls back to [`BiquadCoefficients::group_delay`] at the frequency of a zero,
+    /// e.g. at DC for high-pass filters.
+    pub fn group_delay_at(&self, frequency: f32) -> f32 {
+        let zeros = polynomial_group_delay([self.b0, self.b1, self.b2], frequency);
+        let poles = polynomial_group_delay([1.0, self.a1, self.a2], frequency);
+        match zeros.zip(poles) {
+            Some((zeros, poles)) => zeros - poles,
+            None => self.group_delay(),
+        }
+    }
+
+    const fn prewarp(fs: SamplingFrequency, frequency: f32, q: f32) -> (f32, f32) {
+        let w0 = 2.0 * core::f32::consts::PI * frequency / fs.raw();
+        (const_cos(w0), const_sin(w0) / (2.0 * q))
+    }
+
+    const fn normalize(b: [f32; 3], cos: f32, alpha: f32) -> Self {
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: b[0] / a0,
+            b1: b[1] / a0,
+            b2: b[2] / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+}
+
+/// A second order IIR filter, in transposed direct form II.
+#[derive(Clone, Copy, Debug)]
+pub struct Biquad {
+    coefficients: BiquadCoefficients,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    /// Creates a new filter using `coefficients`.
+    pub const fn new(coefficients: BiquadCoefficients) -> Self {
+        Self {
+            coefficients,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    /// Returns the coefficients of the filter.
+    pub const fn coefficients(&self) -> BiquadCoefficients {
+        self.coefficients
+    }
+}
+
+impl SampleTransform for Biquad {
+    fn transform(&mut self, sample: f32) -> f32 {
+        let c = &self.coefficients;
+        let output = c.b0 * sample + self.z1;
+        self.z1 = c.b1 * sample - c.a1 * output + self.z2;
+        self.z2 = c.b2 * sample - c.a2 * output;
+        output
+    }
+
+    fn clear(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+
+    fn group_delay(&self) -> f32 {
+        self.coefficients.group_delay()
+    }
+
+    fn group_delay_at(&self, frequency: f32) -> f32 {
+        self.coefficients.group_delay_at(frequency)
+    }
+}
+
+/// Returns the coefficients of an `N` tap moving average filter.
+pub const fn moving_average<const N: usize>() -> [f32; N] {
+    [1.0 / N as f32; N]
+}
+
+/// Returns the coefficients of an `N` tap low-pass filter with the given cutoff frequency, in
+/// Hz, designed using a Hamming windowed sinc function. The DC gain is 1.
+///
+/// # Panics
+///
+/// Panics if `N` is even, as the filter would not be symmetric around a single sample.
+pub const fn windowed_sinc<const N: usize>(fs: SamplingFrequency, cutoff: f32) -> [f32; N] {
+    assert!(N % 2 == 1, "The number of taps must be odd");
+
+    let fc = cutoff / fs.raw();
+    let center = (N / 2) as f32;
+    let pi = core::f32::consts::PI;
+
+    let mut coefficients = [0.0; N];
+    let mut sum = 0.0;
+    let mut i = 0;
+    while i < N {
+        let x = i as f32 - center;
+        let sinc = if i == N / 2 {
+            2.0 * fc
+        } else {
+            const_sin(2.0 * pi * fc * x) / (pi * x)
+        };
+        let window = if N == 1 {
+            1.0
+        } else {
+            0.54 - 0.46 * const_cos(2.0 * pi * i as f32 / (N - 1) as f32)
+        };
+
+        coefficients[i] = sinc * window;
+        sum += coefficients[i];
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < N {
+        coefficients[i] /= sum;
+        i += 1;
+    }
+    coefficients
+}
+
+/// A finite impulse response filter with `N` taps.
+#[derive(Clone, Copy, Debug)]
+pub struct Fir<const N: usize> {
+    coefficients: [f32; N],
+    history: [f32; N],
+    idx: usize,
+}
+
+impl<const N: usize> Fir<N> {
+    /// Creates a new filter using `coefficients`. The first coefficient is applied to the newest
+    /// sample.
+    pub const fn new(coefficients: [f32; N]) -> Self {
+        Self {
+            coefficients,
+            history: [0.0; N],
+            idx: 0,
+        }
+    }
+
+    /// Returns the coefficients of the filter.
+    pub const fn coefficients(&self) -> &[f32; N] {
+        &self.coefficients
+    }
+}
+
+impl<const N: usize> SampleTransform for Fir<N> {
+    fn transform(&mut self, sample: f32) -> f32 {
+        if N == 0 {
+            return 0.0;
+        }
+
+        self.idx = if self.idx == 0 { N - 1 } else { self.idx - 1 };
+        self.history[self.idx] = sample;
+
+        // The history is filled backwards, from the newest sample to the oldest
+        let (oldest, newest) = self.history.split_at(self.idx);
+        newest
+            .iter()
+            .chain(oldest)
+            .zip(self.coefficients.iter())
+            .map(|(sample, coefficient)| sample * coefficient)
+            .sum()
+    }
+
+    fn clear(&mut self) {
+        self.history = [0.0; N];
+        self.idx = 0;
+    }
+
+    /// Returns the center of the coefficients. This is exact for linear phase filters, i.e.
+    /// symmetric or antisymmetric coefficients.
+    fn group_delay(&self) -> f32 {
+        let (weighted, total) = self.coefficients.iter().enumerate().fold(
+            (0.0, 0.0),
+            |(weighted, total), (i, coefficient)| {
+                (
+                    weighted + i as f32 * abs(*coefficient),
+                    total + abs(*coefficient),
+                )
+            },
+        );
+
+        if total > 0.0 {
+            weighted / total
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A median filter over the last `N` samples, to suppress bursty muscle (EMG) noise.
+///
+/// Unlike linear low-pass filters, the median filter removes short spikes without smearing them,
+/// and preserves the steep edges of the QRS complexes. The window should be short compared to the
+/// QRS complex, e.g. 10-20 ms. `N` must be odd, so that the filter has an integer delay of
+/// `(N - 1) / 2` samples.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::preprocessing::Median;
+/// use qrs_detector::transform::SampleTransform;
+///
+/// let mut median = Median::<3>::new();
+/// let output = [1.0, 5.0, 1.0, 1.0].map(|sample| median.transform(sample));
+///
+/// // The spike is removed
+/// assert_eq!([0.0, 1.0, 1.0, 1.0], output);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Median<const N: usize> {
+    history: [f32; N],
+    idx: usize,
+}
+
+impl<const N: usize> Default for Median<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Median<N> {
+    /// Creates a new median filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is even.
+    pub const fn new() -> Self {
+        assert!(N % 2 == 1, "The window length must be odd");
+        Self {
+            history: [0.0; N],
+            idx: 0,
+        }
+    }
+}
+
+impl<const N: usize> SampleTransform for Median<N> {
+    fn transform(&mut self, sample: f32) -> f32 {
+        self.history[self.idx] = sample;
+        self.idx = (self.idx + 1) % N;
+
+        let mut sorted = self.history;
+        sorted.sort_unstable_by(f32::total_cmp);
+        sorted[N / 2]
+    }
+
+    fn clear(&mut self) {
+        self.history = [0.0; N];
+        self.idx = 0;
+    }
+
+    fn group_delay(&self) -> f32 {
+        (N / 2) as f32
+    }
+}
+
+/// Adds triangular (TPDF) dither to the samples of a coarse ADC.
+///
+/// With only a few codes across the QRS complex, the quantization error follows the signal, and
+/// the flat runs of equal codes break up the slopes the detector looks for. Dither of one LSB
+/// decorrelates the error from the signal, so that the filters following this stage smooth it out.
+/// With noise shaping enabled, the dither is high-pass shaped and mostly removed by a subsequent
+/// low-pass filter, e.g. [`Preprocessor::with_lowpass`].
+///
+/// The detector needs about 16 codes across the QRS complex, e.g. 9 bits for a ±5 mV input
+/// range and a 0.5 mV QRS complex. With a 40 Hz low-pass filter before differentiating the
+/// signal, about 8 codes are enough. Dither without noise shaping raises the noise floor and
+/// should only be used with more codes than this.
+///
+/// The dither is pseudo-random, but deterministic for a given seed.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::preprocessing::{Dither, Preprocessor};
+/// use qrs_detector::transform::SampleTransform;
+///
+/// // An 8 bit ADC with a ±5 mV input range, samples in mV
+/// let lsb = 10.0 / 256.0;
+///
+/// let detector = QrsDetector::new::<150, 25>(500.sps()).with_transform(
+///     Dither::new(lsb, 1)
+///         .with_noise_shaping()
+///         .chain(Preprocessor::new(500.sps()).with_lowpass(40.0)),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Dither {
+    lsb: f32,
+    seed: u32,
+    state: u32,
+    noise_shaping: bool,
+    /// The previous dither value, used by noise shaping.
+    prev: f32,
+}
+
+impl Dither {
+    /// Creates a new dither stage for samples quantized with a step of `lsb`.
+    pub fn new(lsb: f32, seed: u32) -> Self {
+        // xorshift does not work with a zero state
+        let seed = seed.max(1);
+        Self {
+            lsb,
+            seed,
+            state: seed,
+            noise_shaping: false,
+            prev: 0.0,
+        }
+    }
+
+    /// Shapes the spectrum of the dither using a first order difference, moving its power to
+    /// high frequencies.
+    pub fn with_noise_shaping(mut self) -> Self {
+        self.noise_shaping = true;
+        self
+    }
+
+    /// Returns a pseudo-random number between 0 and 1.
+    fn random(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+impl SampleTransform for Dither {
+    fn transform(&mut self, sample: f32) -> f32 {
+        // The sum of two uniform values has a triangular distribution between -1 and 1 LSB
+        let dither = (self.random() + self.random() - 1.0) * self.lsb;
+
+        let noise = if self.noise_shaping {
+            let shaped = (dither - self.prev) * 0.5;
+            self.prev = dither;
+            shaped
+        } else {
+            dither
+        };
+
+        sample + noise
+    }
+
+    fn clear(&mut self) {
+        self.state = self.seed;
+        self.prev = 0.0;
+    }
+}
+
+/// The quality factor of the notch filters of [`Preprocessor`].
+const NOTCH_Q: f32 = 30.0;
+/// The duration of the mains frequency measurement.
+const MAINS_DETECTION_S: f32 = 2.0;
+/// The power at the detected mains frequency must be this many times the power at the other one.
+const MAINS_POWER_RATIO: f32 = 4.0;
+/// The power at the detected mains frequency must be at least this fraction of the signal power.
+/// The ECG itself has about 0.01% of its power at these frequencies.
+const MAINS_MIN_FRACTION: f32 = 0.001;
+
+/// The frequency of the power grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MainsFrequency {
+    /// 50 Hz, e.g. in Europe, Africa and most of Asia.
+    Hz50,
+    /// 60 Hz, e.g. in the Americas.
+    Hz60,
+}
+
+impl MainsFrequency {
+    /// Returns the frequency in Hz.
+    pub const fn hz(self) -> f32 {
+        match self {
+            Self::Hz50 => 50.0,
+            Self::Hz60 => 60.0,
+        }
+    }
+
+    /// Detects the mains frequency by comparing the interference at 50 and 60 Hz in the first
+    /// two seconds of `samples`.
+    ///
+    /// Returns `None` if `samples` is shorter than two seconds, or if neither frequency is
+    /// clearly dominant, e.g. because the device is battery powered and picks up no
+    /// interference.
+    pub fn detect(fs: SamplingFrequency, samples: impl IntoIterator<Item = f32>) -> Option<Self> {
+        let mut detector = MainsDetector::new(fs);
+        samples
+            .into_iter()
+            .find(|&sample| detector.update(sample))
+            .and_then(|_| detector.result())
+    }
+}
+
+/// Detects the mains frequency in a stream of samples, see [`MainsFrequency::detect`].
+#[derive(Clone, Copy, Debug)]
+pub struct MainsDetector {
+    window: u32,
+    hz50: Goertzel,
+    hz60: Goertzel,
+    /// The first sample, subtracted from the samples to preserve precision.
+    offset: Option<f32>,
+    sum: f32,
+    sum_sq: f32,
+}
+
+impl MainsDetector {
+    /// Creates a new detector for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            window: fs.s_to_samples(MAINS_DETECTION_S) as u32,
+            hz50: Goertzel::new(fs, 50.0),
+            hz60: Goertzel::new(fs, 60.0),
+            offset: None,
+            sum: 0.0,
+            sum_sq: 0.0,
+        }
+    }
+
+    /// Processes a sample. Returns `true` once the measurement is complete.
+    pub fn update(&mut self, sample: f32) -> bool {
+        if !self.is_complete() {
+            let sample = sample - *self.offset.get_or_insert(sample);
+            self.hz50.update(sample);
+            self.hz60.update(sample);
+            self.sum += sample;
+            self.sum_sq += sample * sample;
+        }
+        self.is_complete()
+    }
+
+    /// Returns `true` if the measurement is complete.
+    pub fn is_complete(&self) -> bool {
+        self.hz50.samples() >= self.window
+    }
+
+    /// Returns the detected mains frequency, once the measurement is complete.
+    pub fn result(&self) -> Option<MainsFrequency> {
+        if !self.is_complete() {
+            return None;
+        }
+
+        let n = self.hz50.samples() as f32;
+        let mean = self.sum / n;
+        let min_power = MAINS_MIN_FRACTION * (self.sum_sq / n - mean * mean);
+
+        let (p50, p60) = (self.hz50.power(), self.hz60.power());
+        if p50.max(p60) < min_power {
+            None
+        } else if p50 > MAINS_POWER_RATIO * p60 {
+            Some(MainsFrequency::Hz50)
+        } else if p60 > MAINS_POWER_RATIO * p50 {
+            Some(MainsFrequency::Hz60)
+        } else {
+            None
+        }
+    }
+
+    /// Restarts the measurement.
+    pub fn clear(&mut self) {
+        self.hz50.clear();
+        self.hz60.clear();
+        self.offset = None;
+        self.sum = 0.0;
+        self.sum_sq = 0.0;
+    }
+}
+
+/// The quality factor of [`AdaptiveNotch`]. It is lower than that of the fixed notch filters,
+/// to follow the interference while the frequency is adapting.
+const ADAPTIVE_NOTCH_Q: f32 = 15.0;
+/// The largest deviation of the adaptive notch from the nominal mains frequency, in Hz.
+const ADAPTIVE_NOTCH_RANGE: f32 = 5.0;
+/// The normalized step size of the adaptation.
+const ADAPTIVE_NOTCH_STEP: f32 = 0.01;
+/// The smoothing factor of the gradient power estimate, which normalizes the step size.
+const ADAPTIVE_NOTCH_SMOOTHING: f32 = 0.01;
+
+/// A notch filter that follows a drifting mains frequency, e.g. of generators or vehicle power
+/// supplies.
+///
+/// The filter has the structure of [`BiquadCoefficients::notch`]. The coefficient that sets
+/// the notch frequency is adapted by a normalized LMS algorithm, which minimizes the output
+/// power. The coefficient is bounded to frequencies within 5 Hz of the nominal mains frequency,
+/// so the filter stays stable and does not wander off when there is no interference. The gain
+/// at DC is 1 regardless of the adapted frequency.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::preprocessing::{AdaptiveNotch, MainsFrequency};
+/// use qrs_detector::transform::SampleTransform;
+///
+/// let mut notch = AdaptiveNotch::new(500.sps(), MainsFrequency::Hz50);
+/// let filtered = notch.transform(0.5);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct AdaptiveNotch {
+    fs: SamplingFrequency,
+    /// `b0` and `b2`, constant.
+    gain: f32,
+    /// `a2`, constant.
+    a2: f32,
+    /// `b1` and `a1`, adapted.
+    k: f32,
+    nominal: f32,
+    k_min: f32,
+    k_max: f32,
+    inputs: [f32; 2],
+    outputs: [f32; 2],
+    /// The previous values of the gradient of the output with respect to `k`.
+    gradients: [f32; 2],
+    gradient_power: f32,
+}
+
+impl AdaptiveNotch {
+    /// Creates a new filter starting at the `mains` frequency.
+    pub fn new(fs: SamplingFrequency, mains: MainsFrequency) -> Self {
+        let coefficients = BiquadCoefficients::notch(fs, mains.hz(), ADAPTIVE_NOTCH_Q);
+        let k = |frequency: f32| {
+            let w = 2.0 * core::f32::consts::PI * frequency / fs.raw();
+            -2.0 * const_cos(w) * coefficients.b0
+        };
+
+        Self {
+            fs,
+            gain: coefficients.b0,
+            a2: coefficients.a2,
+            k: coefficients.a1,
+            nominal: coefficients.a1,
+            // `k` increases with the frequency
+            k_min: k(mains.hz() - ADAPTIVE_NOTCH_RANGE),
+            k_max: k(mains.hz() + ADAPTIVE_NOTCH_RANGE),
+            inputs: [0.0; 2],
+            outputs: [0.0; 2],
+            gradients: [0.0; 2],
+            gradient_power: 0.0,
+        }
+    }
+
+    /// Returns the current notch frequency, in Hz.
+    pub fn frequency(&self) -> f32 {
+        let cos = (-self.k / (2.0 * self.gain)).clamp(-1.0, 1.0);
+
+        // Refine the approximation of `micromath` using Newton's method
+        let mut w = micromath::F32Ext::acos(cos);
+        for _ in 0..2 {
+            let sin = const_sin(w);
+            if sin > 0.0 {
+                w += (const_cos(w) - cos) / sin;
+            }
+        }
+        w * self.fs.raw() / (2.0 * core::f32::consts::PI)
+    }
+
+    /// Returns the coefficients of the filter at the current notch frequency.
+    pub fn coefficients(&self) -> BiquadCoefficients {
+        BiquadCoefficients {
+            b0: self.gain,
+            b1: self.k,
+            b2: self.gain,
+            a1: self.k,
+            a2: self.a2,
+        }
+    }
+}
+
+impl SampleTransform for AdaptiveNotch {
+    fn transform(&mut self, sample: f32) -> f32 {
+        let [x1, x2] = self.inputs;
+        let [y1, y2] = self.outputs;
+        let [g1, g2] = self.gradients;
+
+        let output = self.gain * (sample + x2) + self.k * (x1 - y1) - self.a2 * y2;
+
+        // The derivative of the output with respect to `k`, including the recursive terms
+        let gradient = x1 - y1 - self.k * g1 - self.a2 * g2;
+        self.gradient_power +=
+            (gradient * gradient - self.gradient_power) * ADAPTIVE_NOTCH_SMOOTHING;
+        if self.gradient_power > 0.0 {
+            let step = ADAPTIVE_NOTCH_STEP * output * gradient / self.gradient_power;
+            self.k = (self.k - step).clamp(self.k_min, self.k_max);
+        }
+
+        self.inputs = [sample, x1];
+        self.outputs = [output, y1];
+        self.gradients = [gradient, g1];
+        output
+    }
+
+    /// Resets the filter to the nominal mains frequency.
+    fn clear(&mut self) {
+        self.k = self.nominal;
+        self.inputs = [0.0; 2];
+        self.outputs = [0.0; 2];
+        self.gradients = [0.0; 2];
+        self.gradient_power = 0.0;
+    }
+
+    /// The delay changes as the notch frequency adapts.
+    fn group_delay(&self) -> f32 {
+        self.coefficients().group_delay()
+    }
+
+    fn group_delay_at(&self, frequency: f32) -> f32 {
+        self.coefficients().group_delay_at(frequency)
+    }
+}
+
+/// The mains interference filter of [`Preprocessor`].
+#[derive(Clone, Copy, Debug)]
+enum Notch {
+    None,
+    Detecting(MainsDetector),
+    Active(MainsFrequency, Biquad),
+    Adaptive(MainsFrequency, AdaptiveNotch),
+}
+
+/// A configurable filter chain: baseline wander removal, mains interference removal and
+/// low-pass filtering, applied in this order. Every stage is optional.
+///
+/// The mains frequency may be detected automatically, see [`Preprocessor::with_auto_notch`].
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::preprocessing::Preprocessor;
+/// use qrs_detector::transform::SampleTransform;
+///
+/// let preprocessor = Preprocessor::new(500.sps())
+///     .with_highpass(0.5)
+///     .with_auto_notch()
+///     .with_lowpass(40.0);
+///
+/// let detector = QrsDetector::new::<150, 25>(500.sps())
+///     .with_transform(preprocessor.chain(|sample: f32| sample.abs()));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Preprocessor {
+    fs: SamplingFrequency,
+    highpass: Option<Biquad>,
+    notch: Notch,
+    lowpass: Option<Biquad>,
+}
+
+impl Preprocessor {
+    /// Creates a new filter chain for signals sampled with `fs`, that passes samples through
+    /// unchanged.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            fs,
+            highpass: None,
+            notch: Notch::None,
+            lowpass: None,
+        }
+    }
+
+    /// Removes baseline wander below `cutoff` Hz, using a Butterworth high-pass filter.
+    pub fn with_highpass(mut self, cutoff: f32) -> Self {
+        self.highpass = Some(Biquad::new(BiquadCoefficients::highpass(
+            self.fs,
+            cutoff,
+            core::f32::consts::FRAC_1_SQRT_2,
+        )));
+        self
+    }
+
+    /// Removes interference at the `mains` frequency, using a notch filter.
+    pub fn with_notch(mut self, mains: MainsFrequency) -> Self {
+        self.notch = Self::notch(self.fs, mains);
+        self
+    }
+
+    /// Removes interference around the `mains` frequency, using a notch filter that follows the
+    /// frequency as it drifts. See [`AdaptiveNotch`].
+    pub fn with_adaptive_notch(mut self, mains: MainsFrequency) -> Self {
+        self.notch = Notch::Adaptive(mains, AdaptiveNotch::new(self.fs, mains));
+        self
+    }
+
+    /// Detects the mains frequency during the first two seconds, then removes the interference
+    /// using a notch filter. No notch filter is applied if the interference is not detected.
+    pub fn with_auto_notch(mut self) -> Self {
+        self.notch = Notch::Detecting(MainsDetector::new(self.fs));
+        self
+    }
+
+    /// Removes noise above `cutoff` Hz, using a Butterworth low-pass filter.
+    pub fn with_lowpass(mut self, cutoff: f32) -> Self {
+        self.lowpass = Some(Biquad::new(BiquadCoefficients::lowpass(
+            self.fs,
+            cutoff,
+            core::f32::consts::FRAC_1_SQRT_2,
+        )));
+        self
+    }
+
+    /// Returns the nominal frequency of the active notch filter.
+    pub fn mains_frequency(&self) -> Option<MainsFrequency> {
+        match self.notch {
+            Notch::Active(mains, _) | Notch::Adaptive(mains, _) => Some(mains),
+            Notch::None | Notch::Detecting(_) => None,
+        }
+    }
+
+    fn notch(fs: SamplingFrequency, mains: MainsFrequency) -> Notch {
+        Notch::Active(
+            mains,
+            Biquad::new(BiquadCoefficients::notch(fs, mains.hz(), NOTCH_Q)),
+        )
+    }
+}
+
+impl SampleTransform for Preprocessor {
+    fn transform(&mut self, sample: f32) -> f32 {
+        let mut sample = sample;
+        if let Some(highpass) = &mut self.highpass {
+            sample = highpass.transform(sample);
+        }
+
+        match &mut self.notch {
+            Notch::None => {}
+            Notch::Detecting(detector) => {
+                if detector.update(sample) {
+                    self.notch = match detector.result() {
+                        Some(mains) => Self::notch(self.fs, mains),
+                        None => Notch::None,
+                    };
+                }
+            }
+            Notch::Active(_, notch) => sample = notch.transform(sample),
+            Notch::Adaptive(_, notch) => sample = notch.transform(sample),
+        }
+
+        if let Some(lowpass) = &mut self.lowpass {
+            sample = lowpass.transform(sample);
+        }
+        sample
+    }
+
+    /// Resets the filters. A detected mains frequency is kept.
+    fn clear(&mut self) {
+        if let Some(highpass) = &mut self.highpass {
+            highpass.clear();
+        }
+        match &mut self.notch {
+            Notch::Active(_, notch) => notch.clear(),
+            Notch::Adaptive(_, notch) => notch.clear(),
+            Notch::None | Notch::Detecting(_) => {}
+        }
+        if let Some(lowpass) = &mut self.lowpass {
+            lowpass.clear();
+        }
+    }
+
+    fn group_delay(&self) -> f32 {
+        let notch = match &self.notch {
+            Notch::Active(_, notch) => notch.group_delay(),
+            Notch::Adaptive(_, notch) => notch.group_delay(),
+            Notch::None | Notch::Detecting(_) => 0.0,
+        };
+
+        [self.highpass, self.lowpass]
+            .iter()
+            .flatten()
+            .map(Biquad::group_delay)
+            .sum::<f32>()
+            + notch
+    }
+
+    fn group_delay_at(&self, frequency: f32) -> f32 {
+        let notch = match &self.notch {
+            Notch::Active(_, notch) => notch.group_delay_at(frequency),
+            Notch::Adaptive(_, notch) => notch.group_delay_at(frequency),
+            Notch::None | Notch::Detecting(_) => 0.0,
+        };
+
+        [self.highpass, self.lowpass]
+            .iter()
+            .flatten()
+            .map(|filter| filter.group_delay_at(frequency))
+            .sum::<f32>()
+            + notch
+    }
+}
+
+const fn abs(value: f32) -> f32 {
+    if value < 0.0 {
+        -value
+    } else {
+        value
+    }
+}
+
+/// The number of fractional bits of fixed-point filter coefficients. Coefficients are in the
+/// `-2.0..2.0` range.
+pub const FIXED_FRACTION_BITS: u32 = 30;
+
+/// The number of extra fractional bits [`FixedBiquad`] keeps of its previous outputs. Without
+/// them, the rounding error of high-Q filters is amplified near their resonant frequency.
+pub const FIXED_STATE_BITS: u32 = 6;
+
+/// Converts `value` to a fixed-point coefficient, see [`FIXED_FRACTION_BITS`]. Values out of
+/// range are saturated.
+pub const fn to_fixed(value: f32) -> i32 {
+    let scaled = value as f64 * (1u64 << FIXED_FRACTION_BITS) as f64;
+    let rounded = if scaled < 0.0 {
+        scaled - 0.5
+    } else {
+        scaled + 0.5
+    };
+
+    if rounded >= i32::MAX as f64 {
+        i32::MAX
+    } else if rounded <= i32::MIN as f64 {
+        i32::MIN
+    } else {
+        rounded as i32
+    }
+}
+
+/// Converts FIR filter coefficients to fixed-point coefficients, see [`to_fixed`].
+pub const fn to_fixed_fir<const N: usize>(coefficients: &[f32; N]) -> [i32; N] {
+    let mut fixed = [0; N];
+    let mut i = 0;
+    while i < N {
+        fixed[i] = to_fixed(coefficients[i]);
+        i += 1;
+    }
+    fixed
+}
+
+/// Fixed-point coefficients of a second order IIR filter, see [`FIXED_FRACTION_BITS`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FixedBiquadCoefficients {
+    /// Feedforward coefficient of the current sample.
+    pub b0: i32,
+    /// Feedforward coefficient of the previous sample.
+    pub b1: i32,
+    /// Feedforward coefficient of the sample before the previous one.
+    pub b2: i32,
+    /// Feedback coefficient of the previous output.
+    pub a1: i32,
+    /// Feedback coefficient of the output before the previous one.
+    pub a2: i32,
+}
+
+impl BiquadCoefficients {
+    /// Converts the coefficients to fixed-point, see [`FIXED_FRACTION_BITS`].
+    pub const fn to_fixed(&self) -> FixedBiquadCoefficients {
+        FixedBiquadCoefficients {
+            b0: to_fixed(self.b0),
+            b1: to_fixed(self.b1),
+            b2: to_fixed(self.b2),
+            a1: to_fixed(self.a1),
+            a2: to_fixed(self.a2),
+        }
+    }
+}
+
+/// The integer-only variant of [`Biquad`], for targets without an FPU.
+///
+/// Samples must be in the 24 bit range. The filter is implemented in direct form I. The outputs
+/// are fed back with [`FIXED_STATE_BITS`] extra fractional bits, and the remaining rounding error
+/// is carried over to the next sample, so that narrow notch and low frequency filters (e.g.
+/// baseline wander removal) remain accurate.
+///
+/// ```rust
+/// use qrs_detector::preprocessing::{BiquadCoefficients, FixedBiquad, FixedBiquadCoefficients};
+/// use qrs_detector::sampling::SamplingFrequency;
+///
+/// const FS: SamplingFrequency = SamplingFrequency::from_sps(500.0);
+/// const NOTCH: FixedBiquadCoefficients = BiquadCoefficients::notch(FS, 50.0, 30.0).to_fixed();
+///
+/// let mut notch = FixedBiquad::new(NOTCH);
+/// let filtered = notch.transform(1000);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FixedBiquad {
+    coefficients: FixedBiquadCoefficients,
+    inputs: [i32; 2],
+    /// Previous outputs, with `FIXED_STATE_BITS` fractional bits.
+    outputs: [i64; 2],
+    error: i64,
+}
+
+impl FixedBiquad {
+    /// Creates a new filter using `coefficients`.
+    pub const fn new(coefficients: FixedBiquadCoefficients) -> Self {
+        Self {
+            coefficients,
+            inputs: [0; 2],
+            outputs: [0; 2],
+            error: 0,
+        }
+    }
+
+    /// Filters `sample`.
+    pub fn transform(&mut self, sample: i32) -> i32 {
+        let c = &self.coefficients;
+        let [x1, x2] = self.inputs.map(i64::from);
+        let [y1, y2] = self.outputs;
+
+        let feedforward = c.b0 as i64 * sample as i64 + c.b1 as i64 * x1 + c.b2 as i64 * x2;
+        let acc =
+            (feedforward << FIXED_STATE_BITS) - c.a1 as i64 * y1 - c.a2 as i64 * y2 + self.error;
+        let state = acc >> FIXED_FRACTION_BITS;
+        self.error = acc - (state << FIXED_FRACTION_BITS);
+
+        self.inputs = [sample, self.inputs[0]];
+        self.outputs = [state, y1];
+
+        // Round to nearest
+        ((state + (1 << (FIXED_STATE_BITS - 1))) >> FIXED_STATE_BITS) as i32
+    }
+
+    /// Resets the internal state of the filter.
+    pub fn clear(&mut self) {
+        self.inputs = [0; 2];
+        self.outputs = [0; 2];
+        self.error = 0;
+    }
+}
+
+/// The integer-only variant of [`Fir`], for targets without an FPU. Samples must be in the 24
+/// bit range.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedFir<const N: usize> {
+    coefficients: [i32; N],
+    history: [i32; N],
+    idx: usize,
+}
+
+impl<const N: usize> FixedFir<N> {
+    /// Creates a new filter using fixed-point `coefficients`, see [`to_fixed_fir`]. The first
+    /// coefficient is applied to the newest sample.
+    pub const fn new(coefficients: [i32; N]) -> Self {
+        Self {
+            coefficients,
+            history: [0; N],
+            idx: 0,
+        }
+    }
+
+    /// Filters `sample`.
+    pub fn transform(&mut self, sample: i32) -> i32 {
+        if N == 0 {
+            return 0;
+        }
+
+        self.idx = if self.idx == 0 { N - 1 } else { self.idx - 1 };
+        self.history[self.idx] = sample;
+
+        // The history is filled backwards, from the newest sample to the oldest
+        let (oldest, newest) = self.history.split_at(self.idx);
+        let acc: i64 = newest
+            .iter()
+            .chain(oldest)
+            .zip(self.coefficients.iter())
+            .map(|(&sample, &coefficient)| sample as i64 * coefficient as i64)
+            .sum();
+
+        // Round to nearest
+        ((acc + (1 << (FIXED_FRACTION_BITS - 1))) >> FIXED_FRACTION_BITS) as i32
+    }
+
+    /// Resets the internal state of the filter.
+    pub fn clear(&mut self) {
+        self.history = [0; N];
+        self.idx = 0;
+    }
+}
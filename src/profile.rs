@@ -0,0 +1,139 @@
+//! Learned, per-patient parameters to warm-start the detector.
+
+use crate::leads::Lead;
+
+/// The version of the binary encoding of [`PatientProfile`].
+pub const PROFILE_VERSION: u8 = 1;
+
+/// The size of the binary encoding of [`PatientProfile`], in bytes.
+pub const ENCODED_SIZE: usize = 16;
+
+/// The polarity of the QRS complexes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Polarity {
+    #[default]
+    Unknown,
+    Positive,
+    Negative,
+}
+
+/// An error returned when decoding a [`PatientProfile`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileError {
+    /// The data is shorter than [`ENCODED_SIZE`].
+    Truncated,
+    /// The data was encoded using an unsupported version.
+    UnsupportedVersion(u8),
+    /// The data contains invalid values.
+    Invalid,
+}
+
+/// Parameters learned during a session, used to warm-start the detector in the next one.
+///
+/// See [`SessionAnalyzer::profile`](crate::session::SessionAnalyzer::profile) and
+/// [`QrsDetector::warm_start`](crate::QrsDetector::warm_start).
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::profile::{PatientProfile, Polarity};
+///
+/// let profile = PatientProfile {
+///     r_amplitude: 1.5,
+///     rr_mean_ms: 800.0,
+///     rr_std_ms: 40.0,
+///     polarity: Polarity::Positive,
+///     preferred_lead: None,
+/// };
+///
+/// // Store the profile in non-volatile memory
+/// let bytes = profile.to_bytes();
+///
+/// assert_eq!(Ok(profile), PatientProfile::from_bytes(&bytes));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatientProfile {
+    /// The typical peak value of the QRS complexes, in the units of the detector input.
+    pub r_amplitude: f32,
+    /// The mean RR interval, in milliseconds.
+    pub rr_mean_ms: f32,
+    /// The standard deviation of the RR intervals, in milliseconds.
+    pub rr_std_ms: f32,
+    /// The polarity of the QRS complexes.
+    pub polarity: Polarity,
+    /// The lead with the best signal.
+    pub preferred_lead: Option<Lead>,
+}
+
+impl PatientProfile {
+    /// Encodes the profile into a versioned, little-endian binary format.
+    pub fn to_bytes(&self) -> [u8; ENCODED_SIZE] {
+        let mut bytes = [0; ENCODED_SIZE];
+
+        bytes[0] = PROFILE_VERSION;
+        bytes[1] = match self.polarity {
+            Polarity::Unknown => 0,
+            Polarity::Positive => 1,
+            Polarity::Negative => 2,
+        };
+        bytes[2] = match self.preferred_lead {
+            Some(lead) => Lead::ALL.iter().position(|l| *l == lead).unwrap() as u8,
+            None => u8::MAX,
+        };
+        // bytes[3] is reserved
+        bytes[4..8].copy_from_slice(&self.r_amplitude.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.rr_mean_ms.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.rr_std_ms.to_le_bytes());
+
+        bytes
+    }
+
+    /// Decodes a profile encoded by [`PatientProfile::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProfileError> {
+        let bytes: &[u8; ENCODED_SIZE] = bytes
+            .get(..ENCODED_SIZE)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ProfileError::Truncated)?;
+
+        if bytes[0] != PROFILE_VERSION {
+            return Err(ProfileError::UnsupportedVersion(bytes[0]));
+        }
+
+        let polarity = match bytes[1] {
+            0 => Polarity::Unknown,
+            1 => Polarity::Positive,
+            2 => Polarity::Negative,
+            _ => return Err(ProfileError::Invalid),
+        };
+        let preferred_lead = match bytes[2] {
+            u8::MAX => None,
+            idx => Some(*Lead::ALL.get(idx as usize).ok_or(ProfileError::Invalid)?),
+        };
+        let f32_at = |idx: usize| f32::from_le_bytes(bytes[idx..idx + 4].try_into().unwrap());
+
+        let profile = Self {
+            r_amplitude: f32_at(4),
+            rr_mean_ms: f32_at(8),
+            rr_std_ms: f32_at(12),
+            polarity,
+            preferred_lead,
+        };
+
+        if !profile.is_valid() {
+            return Err(ProfileError::Invalid);
+        }
+
+        Ok(profile)
+    }
+
+    /// Returns `true` if the values of the profile are usable.
+    pub fn is_valid(&self) -> bool {
+        self.r_amplitude.is_finite()
+            && self.r_amplitude > 0.0
+            && self.rr_mean_ms.is_finite()
+            && self.rr_mean_ms > 0.0
+            && self.rr_std_ms.is_finite()
+            && self.rr_std_ms >= 0.0
+    }
+}
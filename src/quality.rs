@@ -0,0 +1,754 @@
+//! Signal quality monitoring.
+
+use crate::{events::Event, math::sqrt, sampling::SamplingFrequency, spectrum::Goertzel};
+
+/// A sample is considered a step artifact if it exceeds the signal envelope this many times.
+const STEP_RATIO: f32 = 4.0;
+
+/// Mains interference is reported if its power exceeds this fraction of the signal power.
+const MAINS_FRACTION: f32 = 0.05;
+/// Muscle noise is reported if its power exceeds this fraction of the signal power. The ECG itself
+/// has about 0.2% of its power in the muscle noise band.
+const MUSCLE_FRACTION: f32 = 0.01;
+
+/// Center frequencies of the muscle noise band. Each frequency represents a 5 Hz wide part of the
+/// 70-110 Hz band, where the ECG has little power.
+const MUSCLE_FREQUENCIES: [f32; 8] = [72.5, 77.5, 82.5, 87.5, 92.5, 97.5, 102.5, 107.5];
+const MUSCLE_BIN_WIDTH: f32 = 5.0;
+
+/// The filters of the flutter detector are `FLUTTER_RESOLUTION_HZ` apart, starting at
+/// `FLUTTER_LOW_HZ`. The resolution is the inverse of the measurement window.
+const FLUTTER_LOW_HZ: f32 = 3.625;
+const FLUTTER_RESOLUTION_HZ: f32 = 0.125;
+const FLUTTER_WINDOW_S: f32 = 8.0;
+const FLUTTER_FILTERS: usize = 19;
+/// The filters of the 240-330 bpm band flutter waves are searched in.
+const FLUTTER_BAND: core::ops::Range<usize> = 3..16;
+/// A window is suspicious if its strongest flutter band component exceeds the filters 2-3 bins
+/// away this many times...
+const FLUTTER_PEAK_RATIO: f32 = 8.0;
+/// ... and its amplitude exceeds this fraction of the QRS amplitude...
+const FLUTTER_AMPLITUDE: f32 = 0.06;
+/// ... unless it is a harmonic of a regular ventricular rhythm, whose successive RR intervals
+/// differ by less than this fraction of the mean RR interval on average.
+const REGULAR_RR_DIFFERENCE: f32 = 0.08;
+/// Flutter is reported after this many consecutive suspicious windows at the same frequency.
+const FLUTTER_WINDOWS: u8 = 2;
+/// Samples exceeding this fraction of the QRS amplitude start a blanking period.
+const QRS_FRACTION: f32 = 0.5;
+
+/// The arrest detector suppresses the noise above this frequency.
+const ARREST_LOW_PASS_HZ: f32 = 25.0;
+const ARREST_WINDOW_S: f32 = 2.0;
+/// Windows without waves steeper than a sine wave of this frequency, with the same peak to peak
+/// amplitude, contain no QRS complexes. Fibrillation waves correspond to 4-7 Hz, QRS complexes
+/// to 13 Hz or more.
+const QRS_STEEPNESS_HZ: f32 = 11.0;
+/// An arrest rhythm is reported after this many consecutive windows.
+const ARREST_WINDOWS: u8 = 2;
+
+/// Detects step changes in the DC offset of the signal (e.g. caused by electrode repositioning).
+///
+/// In the differentiated input of the detector, a DC offset step appears as a single, very large
+/// spike compared to the QRS complexes. This detector tracks the envelope of the signal and
+/// reports samples that exceed it by a large margin, as well as a short blanking period after
+/// them.
+pub struct StepDetector {
+    fs: SamplingFrequency,
+    envelope: f32,
+    decay: f32,
+    warmup: usize,
+    blanking: usize,
+    event: bool,
+}
+
+impl StepDetector {
+    /// Creates a new step detector for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            fs,
+            envelope: 0.0,
+            // The envelope halves in about 2 seconds without new peaks
+            decay: 1.0 - 0.35 / fs.raw(),
+            warmup: fs.s_to_samples(1.0),
+            blanking: 0,
+            event: false,
+        }
+    }
+
+    /// Resets the internal state of the step detector.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.fs);
+    }
+
+    /// Processes a sample. Returns `true` if the sample is part of a step artifact.
+    pub fn update(&mut self, sample: f32) -> bool {
+        if self.warmup > 0 {
+            self.warmup -= 1;
+            self.envelope = self.envelope.max(sample);
+            return false;
+        }
+
+        if sample > STEP_RATIO * self.envelope {
+            if self.blanking == 0 {
+                self.event = true;
+            }
+            self.blanking = self.fs.ms_to_samples(100.0);
+            return true;
+        }
+
+        if self.blanking > 0 {
+            self.blanking -= 1;
+            return true;
+        }
+
+        self.envelope = (self.envelope * self.decay).max(sample);
+        false
+    }
+
+    /// Returns `true` if a new step artifact has started since the last call.
+    pub fn take_event(&mut self) -> bool {
+        core::mem::take(&mut self.event)
+    }
+}
+
+/// Detects amplifier saturation in the raw, unprocessed signal.
+///
+/// A sample is considered saturated if it reaches either of the rails. Since the detector processes
+/// a derived signal, the saturated state is held for a short period after the raw signal has left
+/// the rail.
+pub struct SaturationDetector {
+    low: f32,
+    high: f32,
+    hold: usize,
+    remaining: usize,
+}
+
+impl SaturationDetector {
+    /// Creates a new saturation detector for signals sampled with `fs`.
+    ///
+    /// # Arguments
+    /// * `low` - The lower rail of the amplifier.
+    /// * `high` - The upper rail of the amplifier.
+    pub fn new(fs: SamplingFrequency, low: f32, high: f32) -> Self {
+        Self {
+            low,
+            high,
+            hold: fs.ms_to_samples(100.0),
+            remaining: 0,
+        }
+    }
+
+    /// Resets the internal state of the saturation detector.
+    pub fn clear(&mut self) {
+        self.remaining = 0;
+    }
+
+    /// Processes a raw sample. Returns `true` if the amplifier is considered saturated.
+    pub fn update(&mut self, raw: f32) -> bool {
+        if raw <= self.low || raw >= self.high {
+            self.remaining = self.hold;
+            true
+        } else if self.remaining > 0 {
+            self.remaining -= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The dominant noise source of the signal, see [`BandPower::noise_source`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NoiseSource {
+    /// 50 Hz mains interference.
+    Mains50,
+    /// 60 Hz mains interference.
+    Mains60,
+    /// Muscle (EMG) noise.
+    Muscle,
+}
+
+/// The power of the signal in the frequency bands of common noise sources, measured over one
+/// second. The values are in the squared units of the signal.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BandPower {
+    /// The power at 50 Hz.
+    pub mains_50: f32,
+    /// The power at 60 Hz.
+    pub mains_60: f32,
+    /// The power in the 70-110 Hz band, or the part of it below the Nyquist frequency.
+    pub muscle: f32,
+    /// The total power (variance) of the signal.
+    pub total: f32,
+}
+
+impl BandPower {
+    /// Returns the power of the mains interference, at either frequency.
+    pub fn mains(&self) -> f32 {
+        self.mains_50.max(self.mains_60)
+    }
+
+    /// Returns the strongest noise source, if it makes up a significant part of the signal power.
+    pub fn noise_source(&self) -> Option<NoiseSource> {
+        [
+            (NoiseSource::Mains50, self.mains_50, MAINS_FRACTION),
+            (NoiseSource::Mains60, self.mains_60, MAINS_FRACTION),
+            (NoiseSource::Muscle, self.muscle, MUSCLE_FRACTION),
+        ]
+        .into_iter()
+        .filter(|(_, power, fraction)| *power > fraction * self.total)
+        .max_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+        .map(|(source, _, _)| source)
+    }
+}
+
+/// Tracks the power of the raw signal in the frequency bands of mains interference and muscle
+/// noise.
+///
+/// The measurement is lightweight: ten Goertzel filters, updated with one multiplication each
+/// per sample. The results are available every second, for telemetry or to explain why the
+/// detections degraded.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::quality::BandPowerTracker;
+///
+/// let mut bands = BandPowerTracker::new(500.sps());
+///
+/// # let samples = [0.0; 10];
+/// for sample in samples {
+///     if let Some(power) = bands.update(sample) {
+///         if let Some(source) = power.noise_source() {
+///             // Report the noise source
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct BandPowerTracker {
+    window: u32,
+    mains_50: Goertzel,
+    mains_60: Goertzel,
+    muscle: [Goertzel; MUSCLE_FREQUENCIES.len()],
+    /// The number of muscle band filters below the Nyquist frequency.
+    muscle_bins: usize,
+    /// The first sample of the window, subtracted from the samples to preserve precision.
+    offset: Option<f32>,
+    sum: f32,
+    sum_sq: f32,
+    last: Option<BandPower>,
+}
+
+impl BandPowerTracker {
+    /// Creates a new tracker for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            window: fs.s_to_samples(1.0) as u32,
+            mains_50: Goertzel::new(fs, 50.0),
+            mains_60: Goertzel::new(fs, 60.0),
+            muscle: MUSCLE_FREQUENCIES.map(|frequency| Goertzel::new(fs, frequency)),
+            muscle_bins: MUSCLE_FREQUENCIES
+                .iter()
+                .filter(|&&frequency| frequency + MUSCLE_BIN_WIDTH / 2.0 <= fs.raw() / 2.0)
+                .count(),
+            offset: None,
+            sum: 0.0,
+            sum_sq: 0.0,
+            last: None,
+        }
+    }
+
+    /// Resets the internal state of the tracker.
+    pub fn clear(&mut self) {
+        self.restart();
+        self.last = None;
+    }
+
+    fn restart(&mut self) {
+        self.mains_50.clear();
+        self.mains_60.clear();
+        self.muscle.iter_mut().for_each(Goertzel::clear);
+        self.offset = None;
+        self.sum = 0.0;
+        self.sum_sq = 0.0;
+    }
+
+    /// Processes a raw sample. Returns the band powers at the end of every second.
+    pub fn update(&mut self, raw: f32) -> Option<BandPower> {
+        let sample = raw - *self.offset.get_or_insert(raw);
+
+        self.mains_50.update(sample);
+        self.mains_60.update(sample);
+        for filter in &mut self.muscle[..self.muscle_bins] {
+            filter.update(sample);
+        }
+        self.sum += sample;
+        self.sum_sq += sample * sample;
+
+        let n = self.mains_50.samples();
+        if n < self.window {
+            return None;
+        }
+
+        let mean = self.sum / n as f32;
+        // Each filter measures the power in a 1 Hz wide bin, scale it to the width of its band
+        let muscle = self.muscle[..self.muscle_bins]
+            .iter()
+            .map(|filter| filter.power() * MUSCLE_BIN_WIDTH)
+            .sum();
+
+        let power = BandPower {
+            mains_50: self.mains_50.power(),
+            mains_60: self.mains_60.power(),
+            muscle,
+            total: (self.sum_sq / n as f32 - mean * mean).max(0.0),
+        };
+
+        self.restart();
+        self.last = Some(power);
+        Some(power)
+    }
+
+    /// Returns the band powers measured in the last complete second.
+    pub fn last(&self) -> Option<BandPower> {
+        self.last
+    }
+}
+
+/// The strongest component of the flutter band in a measurement window, see
+/// [`FlutterDetector`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlutterEstimate {
+    /// The frequency of the component, as an atrial rate, in beats per minute.
+    pub atrial_rate_bpm: f32,
+    /// The amplitude of the component, relative to the QRS amplitude.
+    pub relative_amplitude: f32,
+    /// The power of the component, relative to the frequencies around it.
+    pub peak_ratio: f32,
+    /// Whether the component is a harmonic of a regular ventricular rhythm, and may be part of
+    /// its P and T waves.
+    pub ventricular_harmonic: bool,
+}
+
+impl FlutterEstimate {
+    /// Returns `true` if the component is strong and narrow enough to be flutter waves.
+    pub fn is_suspicious(&self) -> bool {
+        !self.ventricular_harmonic
+            && self.peak_ratio > FLUTTER_PEAK_RATIO
+            && self.relative_amplitude > FLUTTER_AMPLITUDE
+    }
+}
+
+/// Detects the regular, about 300 bpm baseline oscillation of atrial flutter (flutter waves).
+///
+/// During atrial flutter, the ventricles follow some of the flutter waves, and the heart rate
+/// derived from the QRS complexes does not reflect the atrial activity. This detector raises a
+/// flag in such cases.
+///
+/// The QRS complexes are blanked using an amplitude envelope, and Goertzel filters measure the
+/// spectrum of the remaining signal between 3.6 and 5.9 Hz, over 8 second windows. The flutter
+/// circuit is very regular, so its waves appear as a narrow peak in the 240-330 bpm band. A
+/// window is suspicious if such a peak stands out from the frequencies around it. Flutter is
+/// reported after 2 consecutive suspicious windows at the same frequency, and cleared by the
+/// first window without the peak.
+///
+/// The P and T waves of a regular rhythm also produce narrow peaks, at the harmonics of the
+/// ventricular rate. The detections of the QRS detector must be reported using
+/// [`FlutterDetector::detection_event`], and peaks at these harmonics are ignored. Because of
+/// this, flutter with a fixed conduction ratio (e.g. 2:1) is not reported. This is a coarse
+/// check, not a diagnosis.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::quality::FlutterDetector;
+///
+/// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+/// let mut flutter = FlutterDetector::new(500.sps());
+///
+/// # let samples = [0.0; 10];
+/// for sample in samples {
+///     flutter.update(sample);
+///     if detector.update(sample).is_some() {
+///         flutter.detection_event();
+///     }
+///     if flutter.is_flutter_suspected() {
+///         // The heart rate derived from the QRS complexes may be misleading
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FlutterDetector {
+    fs: SamplingFrequency,
+    window: u32,
+    filters: [Goertzel; FLUTTER_FILTERS],
+    baseline: Option<f32>,
+    baseline_alpha: f32,
+    envelope: f32,
+    decay: f32,
+    warmup: usize,
+    blanking: usize,
+    beats: Beats,
+    last: Option<FlutterEstimate>,
+    /// The number of consecutive suspicious windows, and the filter of the last one.
+    streak: (u8, usize),
+}
+
+/// The QRS complexes found in a measurement window.
+#[derive(Clone, Copy, Debug, Default)]
+struct Beats {
+    count: u32,
+    first: u32,
+    last: u32,
+    last_rr: Option<u32>,
+    /// The sum of the absolute differences of successive RR intervals.
+    rr_differences: u32,
+}
+
+impl Beats {
+    fn push(&mut self, index: u32) {
+        if self.count == 0 {
+            self.first = index;
+        } else {
+            let rr = index - self.last;
+            if let Some(last_rr) = self.last_rr {
+                self.rr_differences += rr.abs_diff(last_rr);
+            }
+            self.last_rr = Some(rr);
+        }
+        self.last = index;
+        self.count += 1;
+    }
+
+    /// Returns the mean RR interval, in samples, if the RR intervals are regular.
+    fn regular_rr(&self) -> Option<f32> {
+        if self.count < 3 {
+            return None;
+        }
+
+        let mean = (self.last - self.first) as f32 / (self.count - 1) as f32;
+        let difference = self.rr_differences as f32 / (self.count - 2) as f32;
+        (difference <= REGULAR_RR_DIFFERENCE * mean).then_some(mean)
+    }
+}
+
+impl FlutterDetector {
+    /// Creates a new flutter detector for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            fs,
+            window: fs.s_to_samples(FLUTTER_WINDOW_S) as u32,
+            filters: core::array::from_fn(|i| Goertzel::new(fs, Self::frequency(i))),
+            baseline: None,
+            // Removes the baseline below about 1 Hz
+            baseline_alpha: 2.0 * core::f32::consts::PI / fs.raw(),
+            envelope: 0.0,
+            // The envelope halves in about 4 seconds without new peaks
+            decay: 1.0 - 0.17 / fs.raw(),
+            warmup: fs.s_to_samples(1.0),
+            blanking: 0,
+            beats: Beats::default(),
+            last: None,
+            streak: (0, 0),
+        }
+    }
+
+    /// Resets the internal state of the detector.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.fs);
+    }
+
+    /// Returns the center frequency of filter `i`.
+    fn frequency(i: usize) -> f32 {
+        FLUTTER_LOW_HZ + i as f32 * FLUTTER_RESOLUTION_HZ
+    }
+
+    /// Processes a raw sample. Returns the strongest flutter band component at the end of every
+    /// 8 second window.
+    pub fn update(&mut self, raw: f32) -> Option<FlutterEstimate> {
+        let baseline = self.baseline.get_or_insert(raw);
+        *baseline += (raw - *baseline) * self.baseline_alpha;
+        let sample = raw - *baseline;
+
+        self.envelope = (self.envelope * self.decay).max(sample.abs());
+        if self.warmup > 0 {
+            self.warmup -= 1;
+            return None;
+        }
+
+        // Remove the QRS complexes, keep the timing of the rest of the signal
+        if sample.abs() > QRS_FRACTION * self.envelope {
+            self.blanking = self.fs.ms_to_samples(120.0);
+        }
+        let sample = if self.blanking > 0 {
+            self.blanking -= 1;
+            0.0
+        } else {
+            sample
+        };
+
+        for filter in &mut self.filters {
+            filter.update(sample);
+        }
+
+        if self.filters[0].samples() < self.window {
+            return None;
+        }
+
+        let powers = self.filters.map(|filter| filter.power());
+        self.filters.iter_mut().for_each(Goertzel::clear);
+        let beats = core::mem::take(&mut self.beats);
+
+        let peak = FLUTTER_BAND
+            .max_by(|&a, &b| powers[a].total_cmp(&powers[b]))
+            .unwrap();
+        let surroundings = [peak - 3, peak - 2, peak + 2, peak + 3]
+            .map(|i| powers[i])
+            .into_iter()
+            .fold(0.0, f32::max);
+        let frequency = Self::frequency(peak);
+        let ventricular_harmonic = beats.regular_rr().is_some_and(|rr| {
+            let ventricular = self.fs.raw() / rr;
+            let harmonic = micromath::F32Ext::round(frequency / ventricular) * ventricular;
+            (harmonic - frequency).abs() <= FLUTTER_RESOLUTION_HZ
+        });
+
+        let estimate = FlutterEstimate {
+            atrial_rate_bpm: frequency * 60.0,
+            // The power of a sine wave is half of its squared amplitude
+            relative_amplitude: sqrt(2.0 * powers[peak]) / self.envelope,
+            peak_ratio: powers[peak] / surroundings,
+            ventricular_harmonic,
+        };
+
+        self.streak = if !estimate.is_suspicious() {
+            (0, peak)
+        } else if self.streak.0 > 0 && self.streak.1.abs_diff(peak) <= 1 {
+            (self.streak.0.saturating_add(1), peak)
+        } else {
+            (1, peak)
+        };
+        self.last = Some(estimate);
+        Some(estimate)
+    }
+
+    /// Notifies the flutter detector about a QRS complex, e.g. one found by
+    /// [`QrsDetector`](crate::QrsDetector). The detections are used to tell flutter waves from the
+    /// P and T waves of a regular rhythm, and must be reported as soon as they are available.
+    pub fn detection_event(&mut self) {
+        self.beats.push(self.filters[0].samples());
+    }
+
+    /// Returns the estimate of the last complete window.
+    pub fn last(&self) -> Option<FlutterEstimate> {
+        self.last
+    }
+
+    /// Returns `true` if flutter waves were found in the last few windows. The heart rate derived
+    /// from the QRS complexes may not reflect the atrial rate in this case.
+    pub fn is_flutter_suspected(&self) -> bool {
+        self.streak.0 >= FLUTTER_WINDOWS
+    }
+}
+
+/// A life-threatening rhythm without QRS complexes, see [`ArrestDetector`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArrestRhythm {
+    /// No electrical activity, the signal is flat.
+    Asystole,
+    /// Ventricular fibrillation: irregular waves without QRS complexes.
+    VentricularFibrillation,
+}
+
+impl ArrestRhythm {
+    /// Returns the event reported when the rhythm starts.
+    pub fn event(self) -> Event {
+        match self {
+            ArrestRhythm::Asystole => Event::Asystole,
+            ArrestRhythm::VentricularFibrillation => Event::VentricularFibrillation,
+        }
+    }
+}
+
+/// The measurements of a window, see [`ArrestDetector`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArrestEstimate {
+    /// The peak to peak amplitude of the filtered signal, in the units of the signal.
+    pub peak_to_peak: f32,
+    /// The frequency of the sine wave with the same peak to peak amplitude and maximum slope as
+    /// the signal, in Hz. QRS complexes are much steeper than the rest of the signal.
+    pub steepness_hz: f32,
+    /// The rhythm of the window, if it contains no QRS complexes.
+    pub rhythm: Option<ArrestRhythm>,
+}
+
+/// Tells asystole and ventricular fibrillation apart when the signal contains no QRS complexes.
+///
+/// The raw signal is band-pass filtered between 1 and 25 Hz, and measured over 2 second windows.
+/// A window whose peak to peak amplitude is below the asystole threshold is flat. Otherwise, the
+/// steepest wave of the window is compared to its amplitude: QRS complexes are narrow, with
+/// steep edges, while fibrillation waves are slow, sine-like oscillations. A window with energy,
+/// but without steep waves, is ventricular fibrillation. A rhythm is reported after 2
+/// consecutive windows, and cleared by the first window with QRS complexes.
+///
+/// The QRS detector keeps triggering on the fibrillation waves, so its detections are not used.
+/// Fine fibrillation below the asystole threshold is reported as asystole. This is a coarse
+/// check, not a shock advisory.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::quality::ArrestDetector;
+///
+/// // The signal is in millivolts
+/// let mut arrest = ArrestDetector::new(500.sps(), 0.1);
+///
+/// # let samples = [0.0; 10];
+/// for sample in samples {
+///     arrest.update(sample);
+///     if let Some(event) = arrest.take_event() {
+///         // Raise an alarm
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ArrestDetector {
+    fs: SamplingFrequency,
+    window: u32,
+    asystole_amplitude: f32,
+    baseline: Option<f32>,
+    baseline_alpha: f32,
+    low_pass: [f32; 2],
+    low_pass_alpha: f32,
+    warmup: usize,
+    previous: f32,
+    samples: u32,
+    min: f32,
+    max: f32,
+    max_slope: f32,
+    last: Option<ArrestEstimate>,
+    /// The number of consecutive windows with the same rhythm, and the rhythm.
+    streak: (u8, Option<ArrestRhythm>),
+    event: Option<Event>,
+}
+
+impl ArrestDetector {
+    /// Creates a new arrest detector for signals sampled with `fs`. Signals with a peak to peak
+    /// amplitude below `asystole_amplitude` are considered flat, e.g. 0.1 mV in the units of
+    /// the signal.
+    pub fn new(fs: SamplingFrequency, asystole_amplitude: f32) -> Self {
+        Self {
+            fs,
+            window: fs.s_to_samples(ARREST_WINDOW_S) as u32,
+            asystole_amplitude,
+            baseline: None,
+            // Removes the baseline below about 1 Hz
+            baseline_alpha: 2.0 * core::f32::consts::PI / fs.raw(),
+            low_pass: [0.0; 2],
+            low_pass_alpha: 1.0
+                - micromath::F32Ext::exp(
+                    -2.0 * core::f32::consts::PI * ARREST_LOW_PASS_HZ / fs.raw(),
+                ),
+            warmup: fs.s_to_samples(1.0),
+            previous: 0.0,
+            samples: 0,
+            min: f32::MAX,
+            max: f32::MIN,
+            max_slope: 0.0,
+            last: None,
+            streak: (0, None),
+            event: None,
+        }
+    }
+
+    /// Resets the internal state of the detector.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.fs, self.asystole_amplitude);
+    }
+
+    /// Processes a raw sample. Returns the measurements at the end of every 2 second window.
+    pub fn update(&mut self, raw: f32) -> Option<ArrestEstimate> {
+        let [first, second] = &mut self.low_pass;
+        *first += (raw - *first) * self.low_pass_alpha;
+        *second += (*first - *second) * self.low_pass_alpha;
+
+        let baseline = self.baseline.get_or_insert(*second);
+        *baseline += (*second - *baseline) * self.baseline_alpha;
+        let sample = *second - *baseline;
+
+        let slope = (sample - self.previous).abs();
+        self.previous = sample;
+        if self.warmup > 0 {
+            self.warmup -= 1;
+            return None;
+        }
+
+        self.min = self.min.min(sample);
+        self.max = self.max.max(sample);
+        self.max_slope = self.max_slope.max(slope);
+        self.samples += 1;
+        if self.samples < self.window {
+            return None;
+        }
+
+        let peak_to_peak = self.max - self.min;
+        let steepness_hz = if peak_to_peak > 0.0 {
+            // The maximum slope of a sine wave is pi * f * peak to peak amplitude
+            self.max_slope * self.fs.raw() / (core::f32::consts::PI * peak_to_peak)
+        } else {
+            0.0
+        };
+        let rhythm = if peak_to_peak < self.asystole_amplitude {
+            Some(ArrestRhythm::Asystole)
+        } else if steepness_hz < QRS_STEEPNESS_HZ {
+            Some(ArrestRhythm::VentricularFibrillation)
+        } else {
+            None
+        };
+
+        self.samples = 0;
+        self.min = f32::MAX;
+        self.max = f32::MIN;
+        self.max_slope = 0.0;
+
+        let estimate = ArrestEstimate {
+            peak_to_peak,
+            steepness_hz,
+            rhythm,
+        };
+
+        let count = if rhythm.is_some() && rhythm == self.streak.1 {
+            self.streak.0.saturating_add(1)
+        } else {
+            1
+        };
+        if count == ARREST_WINDOWS {
+            self.event = rhythm.map(ArrestRhythm::event);
+        }
+        self.streak = (count, rhythm);
+        self.last = Some(estimate);
+        Some(estimate)
+    }
+
+    /// Returns the measurements of the last complete window.
+    pub fn last(&self) -> Option<ArrestEstimate> {
+        self.last
+    }
+
+    /// Returns the arrest rhythm found in the last few windows.
+    pub fn rhythm(&self) -> Option<ArrestRhythm> {
+        self.streak.1.filter(|_| self.streak.0 >= ARREST_WINDOWS)
+    }
+
+    /// Returns the critical event reported when an arrest rhythm starts, then clears it.
+    pub fn take_event(&mut self) -> Option<Event> {
+        self.event.take()
+    }
+}
@@ -51,8 +51,21 @@ impl SamplingFrequencyExt for f64 {
 }
 
 impl SamplingFrequency {
+    /// Creates a sampling frequency of `sps` samples per second. Unlike
+    /// [`SamplingFrequencyExt::sps`], this can be used in const contexts.
+    ///
+    /// ```rust
+    /// # use qrs_detector::sampling::*;
+    /// #
+    /// const FS: SamplingFrequency = SamplingFrequency::from_sps(500.0);
+    /// assert_eq!(FS, 500.sps());
+    /// ```
+    pub const fn from_sps(sps: f32) -> Self {
+        Self(sps)
+    }
+
     /// Returns the sampling frequency in units of samples per second.
-    pub fn raw(self) -> f32 {
+    pub const fn raw(self) -> f32 {
         self.0
     }
 
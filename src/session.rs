@@ -0,0 +1,240 @@
+//! A batteries-included analyzer built on top of the QRS detector.
+
+use core::time::Duration;
+
+use crate::{
+    config::Config,
+    events::{Event, EventLimits, EventSummary},
+    hr::RateTracker,
+    hrv::{HrvAnalyzer, HrvMetrics},
+    noise::{NoEstimator, NoiseEstimator},
+    profile::PatientProfile,
+    sampling::SamplingFrequency,
+    transform::{Identity, SampleTransform},
+    Detection, QrsDetector, Suppressed,
+};
+
+/// A summary of the analyzed session.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionReport {
+    /// The number of processed samples.
+    pub samples: u32,
+    /// The number of detected beats.
+    pub beats: u32,
+    /// The current heart rate, in beats per minute.
+    pub heart_rate: Option<f32>,
+    /// The last RR interval.
+    pub last_rr: Option<Duration>,
+    /// Heart rate variability over the whole session.
+    pub hrv: Option<HrvMetrics>,
+    /// The events observed during the session.
+    pub events: EventSummary,
+    /// The identifier of the algorithm, see [`QrsDetector::algorithm_version`].
+    pub algorithm_version: u32,
+}
+
+/// Analyzes an ECG signal: detects QRS complexes, and tracks heart rate, heart rate variability
+/// and rhythm events.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::session::SessionAnalyzer;
+///
+/// let mut analyzer = SessionAnalyzer::new(QrsDetector::new::<150, 25>(500.sps()));
+///
+/// # let samples = [0.0; 10];
+/// for sample in samples {
+///     analyzer.push(sample);
+/// }
+///
+/// let report = analyzer.report();
+/// ```
+pub struct SessionAnalyzer<
+    FMW,
+    FB,
+    H = [Detection; 0],
+    T = Identity,
+    S = [Suppressed; 0],
+    N = NoEstimator,
+> {
+    detector: QrsDetector<FMW, FB, H, T, S, N>,
+    rate: RateTracker,
+    hrv: HrvAnalyzer,
+    limits: EventLimits,
+    events: EventSummary,
+    samples: u32,
+    beats: u32,
+}
+
+impl<FMW, FB, H, T, S, N> SessionAnalyzer<FMW, FB, H, T, S, N>
+where
+    FMW: AsRef<[f32]> + AsMut<[f32]>,
+    FB: AsRef<[f32]> + AsMut<[f32]>,
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    T: SampleTransform,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    N: NoiseEstimator,
+{
+    /// Creates a new analyzer using `detector`.
+    pub fn new(detector: QrsDetector<FMW, FB, H, T, S, N>) -> Self {
+        let fs = detector.sampling_frequency();
+        Self {
+            detector,
+            rate: RateTracker::new(fs),
+            hrv: HrvAnalyzer::new(),
+            limits: EventLimits::default(),
+            events: EventSummary::default(),
+            samples: 0,
+            beats: 0,
+        }
+    }
+
+    /// Sets the limits used to classify beats.
+    pub fn with_event_limits(mut self, limits: EventLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the underlying detector.
+    pub fn detector(&self) -> &QrsDetector<FMW, FB, H, T, S, N> {
+        &self.detector
+    }
+
+    /// Resets the analyzer and the underlying detector.
+    pub fn clear(&mut self) {
+        self.detector.clear();
+        self.rate.clear();
+        self.hrv.clear();
+        self.events = EventSummary::default();
+        self.samples = 0;
+        self.beats = 0;
+    }
+
+    /// Processes a sample. Returns Some sample index if a QRS complex is detected.
+    pub fn push(&mut self, sample: f32) -> Option<u32> {
+        let detection = self.detector.update(sample);
+        self.samples = self.samples.wrapping_add(1);
+
+        if self.detector.take_refractory_timeout() {
+            self.events.record(Event::RefractoryTimeout);
+        }
+        #[cfg(feature = "quality")]
+        if self.detector.take_signal_step() {
+            self.events.record(Event::SignalStep);
+        }
+
+        if let Some(idx) = detection {
+            self.beats = self.beats.wrapping_add(1);
+
+            if self.rate.push_detection(idx).is_some() {
+                let rr = self.rate.last_rr().unwrap();
+                self.hrv.push_rr(rr.as_secs_f32() * 1000.0);
+
+                if let Some(event) = self.limits.classify(rr) {
+                    self.events.record(event);
+                }
+            }
+        }
+
+        detection
+    }
+
+    /// Returns the parameters learned during the session, once the detector is initialized and
+    /// at least two RR intervals have been recorded.
+    ///
+    /// The polarity and the preferred lead are not known to the analyzer, and should be set by
+    /// the caller if available.
+    pub fn profile(&self) -> Option<PatientProfile> {
+        let metrics = self.hrv.metrics()?;
+
+        Some(PatientProfile {
+            r_amplitude: self.detector.peak_average()?,
+            rr_mean_ms: self.hrv.mean_rr_ms()?,
+            rr_std_ms: metrics.sdnn_ms,
+            polarity: Default::default(),
+            preferred_lead: None,
+        })
+    }
+
+    /// Returns a summary of the session.
+    pub fn report(&self) -> SessionReport {
+        SessionReport {
+            samples: self.samples,
+            beats: self.beats,
+            heart_rate: self.rate.heart_rate(),
+            last_rr: self.rate.last_rr(),
+            hrv: self.hrv.metrics(),
+            events: self.events,
+            algorithm_version: self.detector.algorithm_version(),
+        }
+    }
+}
+
+/// Builds a [`SessionAnalyzer`] with statically allocated buffers.
+///
+/// The memory required by the analyzer can be calculated at compile time, using
+/// [`SessionBuilder::memory_size`].
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::session::SessionBuilder;
+/// use qrs_detector::BufferSizes;
+///
+/// const SIZES: BufferSizes = BufferSizes::for_sps(500);
+/// const MEMORY: usize = SessionBuilder::memory_size::<{ SIZES.samples_300 }, { SIZES.samples_50 }>();
+///
+/// // Fail the build if the analyzer does not fit into the memory budget
+/// const _: () = assert!(MEMORY < 4096);
+///
+/// let analyzer = SessionBuilder::new(500.sps())
+///     .with_config(Config::new().with_mm_depth(3))
+///     .build::<{ SIZES.samples_300 }, { SIZES.samples_50 }>();
+/// ```
+pub struct SessionBuilder {
+    fs: SamplingFrequency,
+    config: Config,
+    limits: EventLimits,
+}
+
+impl SessionBuilder {
+    /// Creates a new builder for signals sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            fs,
+            config: Config::new(),
+            limits: EventLimits::default(),
+        }
+    }
+
+    /// Sets the configuration of the detector.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the limits used to classify beats.
+    pub fn with_event_limits(mut self, limits: EventLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Builds the analyzer. The type parameters must be 300ms and 50ms in number of samples, see
+    /// [`crate::BufferSizes`].
+    pub fn build<const SAMPLES_300: usize, const SAMPLES_50: usize>(
+        self,
+    ) -> SessionAnalyzer<[f32; SAMPLES_300], [f32; SAMPLES_50]> {
+        let detector =
+            QrsDetector::new::<SAMPLES_300, SAMPLES_50>(self.fs).with_config(self.config);
+
+        SessionAnalyzer::new(detector).with_event_limits(self.limits)
+    }
+
+    /// Returns the number of bytes the analyzer built by
+    /// [`build::<SAMPLES_300, SAMPLES_50>`](SessionBuilder::build) occupies.
+    pub const fn memory_size<const SAMPLES_300: usize, const SAMPLES_50: usize>() -> usize {
+        core::mem::size_of::<SessionAnalyzer<[f32; SAMPLES_300], [f32; SAMPLES_50]>>()
+    }
+}
@@ -2,6 +2,7 @@
 
 use core::marker::PhantomData;
 
+#[derive(Clone)]
 pub struct SlidingWindow<T, C> {
     buffer: C,
     idx: usize,
@@ -60,6 +61,19 @@ where
         self.buffer.as_ref().get(idx - 1).copied()
     }
 
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        let idx = if self.idx == 0 {
+            if self.full {
+                self.capacity()
+            } else {
+                return None;
+            }
+        } else {
+            self.idx
+        };
+        self.buffer.as_mut().get_mut(idx - 1)
+    }
+
     #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -82,7 +96,211 @@ where
         old
     }
 
+    /// Returns the elements in storage order.
+    ///
+    /// Once the window is full, the whole buffer is returned. For array-backed windows, the
+    /// length of the slice is then a compile-time constant, which lets the compiler unroll and
+    /// vectorize loops over it.
+    pub fn as_unordered_slice(&self) -> &[T] {
+        let buffer = self.buffer.as_ref();
+        if self.full {
+            buffer
+        } else {
+            &buffer[..self.idx]
+        }
+    }
+
     pub fn iter_unordered(&self) -> impl Iterator<Item = T> + Clone + '_ {
-        (0..self.len()).map(|i| self.buffer.as_ref()[i])
+        self.as_unordered_slice().iter().copied()
+    }
+
+    /// Returns the `n`-th newest element. The newest element is returned for `n = 0`.
+    #[allow(dead_code)]
+    pub fn nth_newest(&self, n: usize) -> Option<T> {
+        if n >= self.len() {
+            return None;
+        }
+        let capacity = self.capacity();
+        Some(self.buffer.as_ref()[(self.idx + capacity - 1 - n) % capacity])
+    }
+
+    /// Iterates over the elements from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = T> + Clone + '_ {
+        let start = if self.full { self.idx } else { 0 };
+        (0..self.len()).map(move |i| self.buffer.as_ref()[(start + i) % self.capacity()])
+    }
+}
+
+/// Windows shorter than this are scanned for their maximum, longer windows are split into
+/// blocks, see [`WindowMax`].
+const BLOCK_MAX_MIN_LEN: usize = 16;
+
+/// Returns the maximum of `values`, or 0 if every value is negative. NaN values are ignored.
+///
+/// Unlike `f32::max`, the comparison maps to a single instruction on most targets, and the loop
+/// can be unrolled when the length of `values` is a compile-time constant.
+fn slice_max(values: &[f32]) -> f32 {
+    values
+        .iter()
+        .fold(0.0, |max, &x| if x > max { x } else { max })
+}
+
+/// Tracks the maximum of the last `N` samples, or 0 if every sample is negative, where `N` is
+/// the capacity of the backing buffer. NaN samples are ignored.
+///
+/// Short windows are scanned on every sample. Longer windows are split into blocks of `N`
+/// samples: the maximum is combined from the running maximum of the current block and the
+/// suffix maxima of the previous block. The suffix maxima are calculated once per block, in the
+/// same buffer, so a sample costs constant time on average and no more than a scan of the window
+/// in the worst case.
+pub struct WindowMax<C> {
+    buffer: C,
+    /// The position in the ring buffer, or in the current block.
+    idx: usize,
+    full: bool,
+    blocks: bool,
+    /// The maximum of the current block.
+    block_max: f32,
+}
+
+impl<C> WindowMax<C>
+where
+    C: AsRef<[f32]> + AsMut<[f32]>,
+{
+    pub fn new(buffer: C) -> Self {
+        let blocks = buffer.as_ref().len() >= BLOCK_MAX_MIN_LEN;
+        Self {
+            buffer,
+            idx: 0,
+            full: false,
+            blocks,
+            block_max: 0.0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.idx = 0;
+        self.full = false;
+        self.block_max = 0.0;
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buffer.as_ref().len()
+    }
+
+    pub fn len(&self) -> usize {
+        if self.full {
+            self.capacity()
+        } else {
+            self.idx
+        }
+    }
+
+    /// Adds a sample to the window and returns the maximum of the window.
+    pub fn push(&mut self, sample: f32) -> f32 {
+        let buffer = self.buffer.as_mut();
+        let len = buffer.len();
+        let idx = self.idx;
+
+        let max = if self.blocks {
+            // Negative and NaN samples do not change the result. Replacing them keeps NaN out
+            // of the suffix maxima.
+            let sample = if sample > 0.0 { sample } else { 0.0 };
+
+            // The suffix maximum at `idx` is no longer part of the window
+            buffer[idx] = sample;
+            if sample > self.block_max {
+                self.block_max = sample;
+            }
+
+            match buffer.get(idx + 1) {
+                Some(&suffix) if self.full && suffix > self.block_max => suffix,
+                _ => self.block_max,
+            }
+        } else {
+            buffer[idx] = sample;
+            let window = if self.full { &*buffer } else { &buffer[..=idx] };
+            slice_max(window)
+        };
+
+        self.idx += 1;
+        if self.idx == len {
+            self.idx = 0;
+            self.full = true;
+
+            if self.blocks {
+                // Replace the samples of the completed block with its suffix maxima
+                for i in (0..len - 1).rev() {
+                    if buffer[i + 1] > buffer[i] {
+                        buffer[i] = buffer[i + 1];
+                    }
+                }
+                self.block_max = 0.0;
+            }
+        }
+
+        max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Xorshift noise, with negative values and NaN samples mixed in.
+    fn noise(seed: &mut u32) -> f32 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 17;
+        *seed ^= *seed << 5;
+        match *seed % 50 {
+            0 => f32::NAN,
+            _ => (*seed % 100_000) as f32 / 100_000.0 - 0.2,
+        }
+    }
+
+    fn check<const N: usize>() {
+        let mut seed = N as u32 + 1;
+        let mut window_max = WindowMax::new([0.0; N]);
+        let mut samples = Vec::new();
+
+        // Several wraparounds, ending in the middle of a block
+        for _ in 0..10 * N + N / 2 + 1 {
+            let sample = noise(&mut seed);
+            samples.push(sample);
+
+            let window = &samples[samples.len().saturating_sub(N)..];
+            let expected = window
+                .iter()
+                .copied()
+                .filter(|sample| *sample > 0.0)
+                .fold(0.0, f32::max);
+            assert_eq!(expected, window_max.push(sample), "N={N}");
+            assert_eq!(window.len(), window_max.len());
+        }
+    }
+
+    #[test]
+    fn window_max_matches_naive_max() {
+        // Scanned windows
+        check::<1>();
+        check::<4>();
+        check::<15>();
+        // Block maxima, with window lengths that are and are not powers of two
+        check::<16>();
+        check::<17>();
+        check::<25>();
+        check::<64>();
+        check::<150>();
+    }
+
+    #[test]
+    fn window_max_clear() {
+        let mut window_max = WindowMax::new([0.0; 20]);
+        for i in 0..30 {
+            window_max.push(i as f32);
+        }
+        window_max.clear();
+        assert_eq!(0, window_max.len());
+        assert_eq!(1.0, window_max.push(1.0));
     }
 }
@@ -0,0 +1,81 @@
+//! Single frequency power measurement.
+//!
+//! A [`Goertzel`] filter measures the power of a signal at a single frequency, using a single
+//! multiplication per sample. It is used to diagnose noise sources, e.g. mains interference,
+//! without computing a full spectrum.
+
+use crate::{math::const_cos, sampling::SamplingFrequency};
+
+/// Measures the power of a signal at a single frequency, using the Goertzel algorithm.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::sampling::SamplingFrequency;
+/// use qrs_detector::spectrum::Goertzel;
+///
+/// const FS: SamplingFrequency = SamplingFrequency::from_sps(500.0);
+///
+/// let mut mains = Goertzel::new(FS, 50.0);
+/// for i in 0..500 {
+///     mains.update((2.0 * core::f32::consts::PI * 50.0 * i as f32 / 500.0).sin());
+/// }
+///
+/// // The power of a sine wave is half of its squared amplitude
+/// assert!((mains.power() - 0.5).abs() < 0.01);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Goertzel {
+    coefficient: f32,
+    s1: f32,
+    s2: f32,
+    count: u32,
+}
+
+impl Goertzel {
+    /// Creates a new filter measuring the power at `frequency` Hz in signals sampled with `fs`.
+    pub const fn new(fs: SamplingFrequency, frequency: f32) -> Self {
+        let w = 2.0 * core::f32::consts::PI * frequency / fs.raw();
+        Self {
+            coefficient: 2.0 * const_cos(w),
+            s1: 0.0,
+            s2: 0.0,
+            count: 0,
+        }
+    }
+
+    /// Processes a sample.
+    pub fn update(&mut self, sample: f32) {
+        let s0 = sample + self.coefficient * self.s1 - self.s2;
+        self.s2 = self.s1;
+        self.s1 = s0;
+        self.count += 1;
+    }
+
+    /// Returns the number of samples processed since the filter was cleared.
+    pub fn samples(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the power of the signal at the frequency of the filter, i.e. the mean square value
+    /// of the frequency component, since the filter was cleared.
+    ///
+    /// The frequency resolution is the inverse of the measurement duration. The measurement
+    /// should cover an integer number of periods to avoid leakage from nearby frequencies.
+    pub fn power(&self) -> f32 {
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        let magnitude =
+            self.s1 * self.s1 + self.s2 * self.s2 - self.coefficient * self.s1 * self.s2;
+        let n = self.count as f32;
+        2.0 * magnitude / (n * n)
+    }
+
+    /// Resets the filter to start a new measurement.
+    pub fn clear(&mut self) {
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+        self.count = 0;
+    }
+}
@@ -0,0 +1,69 @@
+//! Asynchronous sample sources.
+//!
+//! [`SampleSource`] is implemented by drivers that wait for samples without blocking the
+//! executor, e.g. for a DMA transfer or a data ready interrupt of the ADC.
+//! [`QrsDetector::next_detection`] feeds the samples of a source to the detector until a QRS
+//! complex is detected.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//! use qrs_detector::stream::SampleSource;
+//!
+//! async fn run(mut source: impl SampleSource) {
+//!     let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//!
+//!     while let Some(index) = detector.next_detection(&mut source).await {
+//!         // Report the detection at `index`
+//!     }
+//! }
+//! ```
+
+use core::future::Future;
+
+use crate::{
+    component::ThresholdComponent, noise::NoiseEstimator, transform::SampleTransform, Detection,
+    QrsDetector, Suppressed,
+};
+
+/// A source of samples that are waited for asynchronously.
+pub trait SampleSource {
+    /// Waits for the next sample. Returns `None` if the source has no more samples.
+    fn next_sample(&mut self) -> impl Future<Output = Option<f32>>;
+}
+
+impl<T> SampleSource for &mut T
+where
+    T: SampleSource,
+{
+    fn next_sample(&mut self) -> impl Future<Output = Option<f32>> {
+        T::next_sample(self)
+    }
+}
+
+impl<FMW, FB, H, T, S, N, FC, RC> QrsDetector<FMW, FB, H, T, S, N, FC, RC>
+where
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    T: SampleTransform,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    N: NoiseEstimator,
+    FC: ThresholdComponent,
+    RC: ThresholdComponent,
+{
+    /// Processes the samples of `source` until a QRS complex is detected. Returns the sample
+    /// index of the detection, or `None` if the source has no more samples.
+    ///
+    /// Dropping the returned future loses no samples, as long as dropping the future of
+    /// [`SampleSource::next_sample`] loses none.
+    pub async fn next_detection<I>(&mut self, source: &mut I) -> Option<u32>
+    where
+        I: SampleSource,
+    {
+        while let Some(sample) = source.next_sample().await {
+            if let Some(index) = self.update(sample) {
+                return Some(index);
+            }
+        }
+
+        None
+    }
+}
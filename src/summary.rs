@@ -0,0 +1,304 @@
+//! Periodic, Holter-style summaries.
+
+use core::{fmt, time::Duration};
+
+use crate::{
+    events::EventLimits,
+    sampling::SamplingFrequency,
+    time::{ClockSource, SampleClock},
+};
+
+/// Successive RR intervals differing by more than this fraction are counted as irregular.
+const IRREGULAR_RR_FRACTION: f32 = 0.15;
+
+/// A compact summary of a period of the signal, suitable for logging into flash memory.
+///
+/// Heart rate fields are 0 if no RR interval ended in the period.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct PeriodSummary {
+    /// The number of detected beats.
+    pub beats: u32,
+    /// The lowest beat-to-beat heart rate, in beats per minute.
+    pub min_bpm: u16,
+    /// The average heart rate, in beats per minute.
+    pub avg_bpm: u16,
+    /// The highest beat-to-beat heart rate, in beats per minute.
+    pub max_bpm: u16,
+    /// The number of pauses, see [`EventLimits::pause`].
+    pub pauses: u16,
+    /// The percentage of irregular RR intervals, of the intervals following another one. This is
+    /// a rough estimate of the atrial fibrillation burden. Intervals ending in beats with a P
+    /// wave are not counted, if reported using [`SummaryAggregator::notify_p_wave`].
+    pub af_burden_percent: u8,
+    /// The percentage of samples marked as noisy.
+    pub noise_percent: u8,
+}
+
+/// Formats the summary as `beats=<n> hr=<min>/<avg>/<max> pauses=<n> af=<n>% noise=<n>%`.
+///
+/// The output fits into 128 bytes.
+impl fmt::Display for PeriodSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "beats={} hr={}/{}/{} pauses={} af={}% noise={}%",
+            self.beats,
+            self.min_bpm,
+            self.avg_bpm,
+            self.max_bpm,
+            self.pauses,
+            self.af_burden_percent,
+            self.noise_percent
+        )
+    }
+}
+
+/// Aggregates detections into [`PeriodSummary`] records.
+///
+/// By default, the periods are measured by counting samples. A different [`ClockSource`] may be
+/// set using [`SummaryAggregator::with_clock`], e.g. to keep the periods aligned to wall time
+/// when samples are lost.
+///
+/// # Example
+/// ```rust
+/// use core::time::Duration;
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::summary::SummaryAggregator;
+///
+/// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+/// let mut aggregator = SummaryAggregator::new(500.sps(), Duration::from_secs(3600));
+///
+/// # let samples = [0.0; 10];
+/// for sample in samples {
+///     let detection = detector.update(sample);
+///     if let Some(summary) = aggregator.update(detection, false) {
+///         // Store the hourly summary
+///     }
+/// }
+/// ```
+pub struct SummaryAggregator<C = SampleClock> {
+    fs: SamplingFrequency,
+    clock: C,
+    period: Duration,
+    /// The length of the period, in clock ticks.
+    period_ticks: u64,
+    period_start: u64,
+    limits: EventLimits,
+    prev_idx: Option<u32>,
+    prev_rr: Option<u32>,
+    samples: u32,
+    noisy_samples: u32,
+    beats: u32,
+    rr_count: u32,
+    rr_sum: u32,
+    rr_min: u32,
+    rr_max: u32,
+    /// The number of RR intervals compared to the previous one.
+    compared: u32,
+    irregular: u32,
+    /// Whether the last RR interval was counted as irregular.
+    last_irregular: bool,
+    pauses: u16,
+}
+
+impl SummaryAggregator {
+    /// Creates a new aggregator for signals sampled with `fs`, producing a summary every `period`.
+    pub fn new(fs: SamplingFrequency, period: Duration) -> Self {
+        let clock = SampleClock::new(fs);
+        let period_ticks = clock.duration_to_ticks(period);
+        assert!(
+            period_ticks > 0,
+            "The summary period must be at least one sample long"
+        );
+
+        Self {
+            fs,
+            clock,
+            period,
+            period_ticks,
+            period_start: 0,
+            limits: EventLimits::default(),
+            prev_idx: None,
+            prev_rr: None,
+            samples: 0,
+            noisy_samples: 0,
+            beats: 0,
+            rr_count: 0,
+            rr_sum: 0,
+            rr_min: u32::MAX,
+            rr_max: 0,
+            compared: 0,
+            irregular: 0,
+            last_irregular: false,
+            pauses: 0,
+        }
+    }
+
+    /// Measures the periods with `clock`. The current period restarts at the current time of
+    /// the clock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period is shorter than a tick of `clock`.
+    pub fn with_clock<C2: ClockSource>(self, clock: C2) -> SummaryAggregator<C2> {
+        let period_ticks = clock.duration_to_ticks(self.period);
+        assert!(
+            period_ticks > 0,
+            "The summary period must be at least one clock tick long"
+        );
+
+        SummaryAggregator {
+            fs: self.fs,
+            period_start: clock.now(),
+            clock,
+            period: self.period,
+            period_ticks,
+            limits: self.limits,
+            prev_idx: self.prev_idx,
+            prev_rr: self.prev_rr,
+            samples: self.samples,
+            noisy_samples: self.noisy_samples,
+            beats: self.beats,
+            rr_count: self.rr_count,
+            rr_sum: self.rr_sum,
+            rr_min: self.rr_min,
+            rr_max: self.rr_max,
+            compared: self.compared,
+            irregular: self.irregular,
+            last_irregular: self.last_irregular,
+            pauses: self.pauses,
+        }
+    }
+}
+
+impl<C: ClockSource> SummaryAggregator<C> {
+    /// Sets the limits used to detect pauses.
+    pub fn with_event_limits(mut self, limits: EventLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the clock measuring the periods.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Resets the aggregator and discards the current period.
+    pub fn clear(&mut self) {
+        self.period_start = self.clock.now();
+        self.prev_idx = None;
+        self.prev_rr = None;
+        self.reset_period();
+    }
+
+    fn reset_period(&mut self) {
+        self.samples = 0;
+        self.noisy_samples = 0;
+        self.beats = 0;
+        self.rr_count = 0;
+        self.rr_sum = 0;
+        self.rr_min = u32::MAX;
+        self.rr_max = 0;
+        self.compared = 0;
+        self.irregular = 0;
+        self.last_irregular = false;
+        self.pauses = 0;
+    }
+
+    /// Processes the result of a detector update. `noisy` marks the sample as unreliable, e.g.
+    /// because the amplifier is saturated.
+    ///
+    /// Returns the summary of the period when it ends.
+    pub fn update(&mut self, detection: Option<u32>, noisy: bool) -> Option<PeriodSummary> {
+        self.clock.on_sample();
+        self.samples += 1;
+        if noisy {
+            self.noisy_samples += 1;
+        }
+
+        if let Some(idx) = detection {
+            self.beats += 1;
+            self.last_irregular = false;
+            if let Some(prev) = self.prev_idx.replace(idx) {
+                self.push_rr(idx.wrapping_sub(prev));
+            }
+        }
+
+        let now = self.clock.now();
+        if now - self.period_start < self.period_ticks {
+            return None;
+        }
+
+        // Keep the periods aligned, unless the clock skipped more than a period
+        self.period_start += self.period_ticks;
+        if now - self.period_start >= self.period_ticks {
+            self.period_start = now;
+        }
+
+        let summary = self.summary();
+        self.reset_period();
+        Some(summary)
+    }
+
+    /// Reports whether the last detected beat has a P wave, e.g. from
+    /// [`BeatMeasurement::p_wave`](crate::multi_rate::BeatMeasurement::p_wave). Atrial
+    /// fibrillation typically lacks P waves, so an irregular RR interval ending in a beat with a
+    /// P wave, e.g. after an ectopic beat, is not counted towards the atrial fibrillation burden.
+    ///
+    /// Must be called before the next detection is processed.
+    pub fn notify_p_wave(&mut self, present: bool) {
+        if present && self.last_irregular {
+            self.irregular -= 1;
+            self.last_irregular = false;
+        }
+    }
+
+    fn push_rr(&mut self, rr: u32) {
+        self.rr_count += 1;
+        self.rr_sum = self.rr_sum.saturating_add(rr);
+        self.rr_min = self.rr_min.min(rr);
+        self.rr_max = self.rr_max.max(rr);
+
+        if self.fs.samples_to_s(rr as usize) > self.limits.pause.as_secs_f32() {
+            self.pauses = self.pauses.saturating_add(1);
+        }
+
+        if let Some(prev_rr) = self.prev_rr.replace(rr) {
+            self.compared += 1;
+            if rr.abs_diff(prev_rr) as f32 > prev_rr as f32 * IRREGULAR_RR_FRACTION {
+                self.irregular += 1;
+                self.last_irregular = true;
+            }
+        }
+    }
+
+    fn bpm(&self, rr: f32) -> u16 {
+        (60.0 * self.fs.raw() / rr + 0.5) as u16
+    }
+
+    fn summary(&self) -> PeriodSummary {
+        let percent = |n: u32, total: u32| (n as u64 * 100 / total.max(1) as u64) as u8;
+
+        let (min_bpm, avg_bpm, max_bpm) = if self.rr_count > 0 {
+            (
+                self.bpm(self.rr_max as f32),
+                self.bpm(self.rr_sum as f32 / self.rr_count as f32),
+                self.bpm(self.rr_min as f32),
+            )
+        } else {
+            (0, 0, 0)
+        };
+
+        PeriodSummary {
+            beats: self.beats,
+            min_bpm,
+            avg_bpm,
+            max_bpm,
+            pauses: self.pauses,
+            af_burden_percent: percent(self.irregular, self.compared),
+            noise_percent: percent(self.noisy_samples, self.samples),
+        }
+    }
+}
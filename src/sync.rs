@@ -0,0 +1,164 @@
+//! Alignment of auxiliary sensor streams to the ECG timeline.
+//!
+//! Wearables often sample other sensors, e.g. an accelerometer, at a different rate than the
+//! ECG. [`StreamAligner`] buffers the latest samples of such a stream, and returns its value at
+//! any ECG sample index, e.g. at a [`Detection`](crate::Detection), using nearest-sample or
+//! linear interpolation.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//! use qrs_detector::sync::{Interpolation, StreamAligner};
+//!
+//! // 3-axis accelerometer at 50 sps, keeping the last second
+//! let mut accelerometer =
+//!     StreamAligner::<3, 50>::new(500.sps(), 50.sps(), Interpolation::Linear);
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//!
+//! # let ecg = [0.0; 10];
+//! # let mut read_accelerometer = |_| Some([0.0, 0.0, 1.0]);
+//! for (idx, sample) in ecg.into_iter().enumerate() {
+//!     if let Some(xyz) = read_accelerometer(idx) {
+//!         accelerometer.push(xyz);
+//!     }
+//!     if let Some(beat) = detector.update(sample) {
+//!         let motion = accelerometer.sample_at(beat);
+//!     }
+//! }
+//! ```
+
+use crate::sampling::SamplingFrequency;
+
+/// The interpolation between the samples of the auxiliary stream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// The sample closest in time.
+    #[default]
+    Nearest,
+    /// Linear interpolation between the two samples around the requested time.
+    Linear,
+}
+
+/// Aligns a stream of `A`-channel samples to the ECG timeline, keeping the last `N` samples.
+///
+/// Sample `k` of the auxiliary stream is taken at `k / aux_fs + offset`, ECG sample `n` at
+/// `n / ecg_fs`. Both streams start at index 0, the offset can be set using
+/// [`StreamAligner::with_offset_ms`].
+///
+/// The value at a time after the last auxiliary sample holds the last sample. For linear
+/// interpolation around the latest samples, request values with a delay of at least one
+/// auxiliary sample period, e.g. the [delay of the detection](crate::QrsDetector::update) is
+/// usually sufficient.
+#[derive(Clone, Copy, Debug)]
+pub struct StreamAligner<const A: usize, const N: usize> {
+    aux_fs: f64,
+    /// Auxiliary samples per ECG sample.
+    ratio: f64,
+    /// The time of the first auxiliary sample, in auxiliary samples.
+    offset: f64,
+    interpolation: Interpolation,
+    buffer: [[f32; A]; N],
+    /// The number of auxiliary samples received or skipped, the index of the next sample.
+    count: u64,
+    /// The number of valid samples in the buffer.
+    valid: usize,
+}
+
+impl<const A: usize, const N: usize> StreamAligner<A, N> {
+    /// Creates an aligner for a stream sampled with `aux_fs`, to the ECG sampled with `ecg_fs`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is less than 2.
+    pub fn new(
+        ecg_fs: SamplingFrequency,
+        aux_fs: SamplingFrequency,
+        interpolation: Interpolation,
+    ) -> Self {
+        assert!(N >= 2, "The buffer must hold at least 2 samples");
+        Self {
+            aux_fs: aux_fs.raw() as f64,
+            ratio: aux_fs.raw() as f64 / ecg_fs.raw() as f64,
+            offset: 0.0,
+            interpolation,
+            buffer: [[0.0; A]; N],
+            count: 0,
+            valid: 0,
+        }
+    }
+
+    /// Sets the time of the first auxiliary sample relative to the first ECG sample, in
+    /// milliseconds. Positive if the auxiliary stream started later.
+    pub fn with_offset_ms(mut self, ms: f32) -> Self {
+        self.offset = ms as f64 / 1000.0 * self.aux_fs;
+        self
+    }
+
+    /// Resets the internal state. The next sample is the first sample of the stream.
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.valid = 0;
+    }
+
+    /// Returns the number of samples received or skipped.
+    pub fn len(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns whether no sample has been received.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Adds the next sample of the auxiliary stream.
+    pub fn push(&mut self, sample: [f32; A]) {
+        self.buffer[(self.count % N as u64) as usize] = sample;
+        self.count += 1;
+        self.valid = (self.valid + 1).min(N);
+    }
+
+    /// Notifies the aligner that `samples` samples of the auxiliary stream were lost. Values
+    /// are not interpolated across the gap.
+    pub fn notify_gap(&mut self, samples: u32) {
+        self.count += samples as u64;
+        self.valid = 0;
+    }
+
+    /// Returns the auxiliary sample with index `idx`, if it is buffered.
+    fn get(&self, idx: u64) -> Option<[f32; A]> {
+        let oldest = self.count - self.valid as u64;
+        (oldest..self.count)
+            .contains(&idx)
+            .then(|| self.buffer[(idx % N as u64) as usize])
+    }
+
+    /// Returns the value of the auxiliary stream at the time of ECG sample `ecg_index`.
+    ///
+    /// Returns `None` if the time precedes the buffered samples, or no sample has been received
+    /// since the last gap.
+    pub fn sample_at(&self, ecg_index: u32) -> Option<[f32; A]> {
+        let latest = self.count.checked_sub(1)?;
+        let position = ecg_index as f64 * self.ratio - self.offset;
+        if position < 0.0 {
+            return None;
+        }
+        if position >= latest as f64 {
+            return self.get(latest);
+        }
+
+        match self.interpolation {
+            Interpolation::Nearest => self.get((position + 0.5) as u64),
+            Interpolation::Linear => {
+                let before = position as u64;
+                let t = (position - before as f64) as f32;
+                let a = self.get(before)?;
+                let b = self.get(before + 1)?;
+
+                let mut value = a;
+                for (value, b) in value.iter_mut().zip(b) {
+                    *value += (b - *value) * t;
+                }
+                Some(value)
+            }
+        }
+    }
+}
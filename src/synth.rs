@@ -0,0 +1,432 @@
+//! Synthetic ECG signals, to test and tune the detector without clinical data.
+//!
+//! The generator is deterministic: the same parameters and seed always produce the same signal,
+//! so datasets can be regenerated locally instead of being distributed.
+//!
+//! Every beat is the sum of Gaussian P, Q, R, S and T waves. The QT interval follows the RR
+//! interval, so T waves move closer to the QRS complexes at high heart rates.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//! use qrs_detector::synth::{SynthParams, Synthesizer};
+//!
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//! let synthesizer = Synthesizer::new(500.sps(), SynthParams::REST, 1);
+//!
+//! for sample in synthesizer.take(5000) {
+//!     if sample.r_peak {
+//!         // The sample is the peak of an R wave
+//!     }
+//!     detector.update(sample.value);
+//! }
+//! ```
+
+use core::f32::consts::PI;
+
+use crate::sampling::SamplingFrequency;
+
+/// The frequency of the power line interference, in Hz.
+const MAINS_HZ: f32 = 50.0;
+
+/// The frequency of the baseline wander, e.g. caused by breathing, in Hz.
+const WANDER_HZ: f32 = 0.3;
+
+/// The frequency of the fibrillatory waves replacing the P waves during atrial fibrillation, in
+/// Hz.
+const FIBRILLATION_HZ: f32 = 6.0;
+
+/// The amplitude of the fibrillatory waves, relative to the R wave.
+const FIBRILLATION_AMPLITUDE: f32 = 0.05;
+
+/// The frequency of the flutter waves replacing the P waves during atrial flutter (300 bpm), in
+/// Hz.
+const FLUTTER_HZ: f32 = 5.0;
+
+/// The peak amplitude of the sawtooth shaped flutter waves, relative to the R wave.
+const FLUTTER_AMPLITUDE: f32 = 0.15;
+
+/// The mean frequency of the ventricular fibrillation waves, in Hz. The frequency of each wave
+/// deviates from it randomly by up to `VENTRICULAR_FIBRILLATION_HZ_VARIABILITY`.
+const VENTRICULAR_FIBRILLATION_HZ: f32 = 5.0;
+const VENTRICULAR_FIBRILLATION_HZ_VARIABILITY: f32 = 0.3;
+
+/// The maximum amplitude of the ventricular fibrillation waves, relative to the R wave. The
+/// amplitude of each wave is random, between half of this and this value.
+const VENTRICULAR_FIBRILLATION_AMPLITUDE: f32 = 0.5;
+
+/// Waves further than this from their peak are not evaluated, in seconds.
+const WAVE_CUTOFF_S: f32 = 0.5;
+
+/// A Gaussian wave of a beat.
+struct Wave {
+    /// The position of the peak relative to the R peak, in seconds, at 60 bpm.
+    offset_s: f32,
+    /// The amplitude, relative to the R wave.
+    amplitude: f32,
+    /// The standard deviation, in seconds.
+    width_s: f32,
+    /// Whether the position scales with the square root of the RR interval (Bazett).
+    rate_dependent: bool,
+}
+
+const P_WAVE: Wave = Wave {
+    offset_s: -0.16,
+    amplitude: 0.15,
+    width_s: 0.025,
+    rate_dependent: false,
+};
+
+const QRS_WAVES: [Wave; 3] = [
+    Wave {
+        offset_s: -0.025,
+        amplitude: -0.1,
+        width_s: 0.008,
+        rate_dependent: false,
+    },
+    Wave {
+        offset_s: 0.0,
+        amplitude: 1.0,
+        width_s: 0.01,
+        rate_dependent: false,
+    },
+    Wave {
+        offset_s: 0.03,
+        amplitude: -0.25,
+        width_s: 0.01,
+        rate_dependent: false,
+    },
+];
+
+/// The amplitude is set by [`SynthParams::t_wave_ratio`].
+const T_WAVE: Wave = Wave {
+    offset_s: 0.3,
+    amplitude: 1.0,
+    width_s: 0.05,
+    rate_dependent: true,
+};
+
+impl Wave {
+    /// Returns the value of the wave at `t` seconds from the R peak of a beat with the given RR
+    /// interval.
+    fn value(&self, t: f32, rr_s: f32) -> f32 {
+        let offset = if self.rate_dependent {
+            self.offset_s * crate::math::sqrt(rr_s)
+        } else {
+            self.offset_s
+        };
+
+        let dt = t - offset;
+        if dt.abs() > WAVE_CUTOFF_S {
+            return 0.0;
+        }
+        self.amplitude * micromath::F32Ext::exp(-dt * dt / (2.0 * self.width_s * self.width_s))
+    }
+}
+
+/// Parameters of the synthetic signal.
+///
+/// The presets may be adjusted using the struct update syntax:
+///
+/// ```rust
+/// use qrs_detector::synth::SynthParams;
+///
+/// let params = SynthParams {
+///     noise_mv: 0.2,
+///     ..SynthParams::REST
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SynthParams {
+    /// The heart rate at the start of the signal, in beats per minute.
+    pub heart_rate_bpm: f32,
+    /// The heart rate at the end of the ramp, in beats per minute.
+    pub final_heart_rate_bpm: f32,
+    /// The duration of the linear heart rate and amplitude change, in seconds.
+    pub ramp_s: f32,
+    /// The maximum random deviation of the RR intervals, as a fraction of the current RR
+    /// interval.
+    pub rr_variability: f32,
+    /// Replaces the P waves with fibrillatory waves.
+    pub atrial_fibrillation: bool,
+    /// Replaces the P waves with flutter waves at 300 bpm.
+    pub atrial_flutter: bool,
+    /// Replaces the beats with ventricular fibrillation: irregular waves at about 5 Hz, without
+    /// QRS complexes. No R peaks are annotated.
+    pub ventricular_fibrillation: bool,
+    /// The amplitude of the R waves at the start of the signal, in millivolts.
+    pub r_amplitude_mv: f32,
+    /// The amplitude of the R waves at the end of the ramp, in millivolts.
+    pub final_r_amplitude_mv: f32,
+    /// The amplitude of the T waves, relative to the R waves.
+    pub t_wave_ratio: f32,
+    /// The peak amplitude of the uniformly distributed broadband noise, in millivolts.
+    pub noise_mv: f32,
+    /// The amplitude of the baseline wander, in millivolts.
+    pub baseline_wander_mv: f32,
+    /// The amplitude of the 50 Hz power line interference, in millivolts.
+    pub mains_mv: f32,
+}
+
+impl SynthParams {
+    /// A resting sinus rhythm at 60 bpm, with a small amount of noise.
+    pub const REST: Self = Self {
+        heart_rate_bpm: 60.0,
+        final_heart_rate_bpm: 60.0,
+        ramp_s: 0.0,
+        rr_variability: 0.03,
+        atrial_fibrillation: false,
+        atrial_flutter: false,
+        ventricular_fibrillation: false,
+        r_amplitude_mv: 1.0,
+        final_r_amplitude_mv: 1.0,
+        t_wave_ratio: 0.3,
+        noise_mv: 0.01,
+        baseline_wander_mv: 0.05,
+        mains_mv: 0.0,
+    };
+
+    /// A sinus rhythm accelerating from 90 to 170 bpm over 2 minutes, with decreasing R wave
+    /// amplitude and motion artifacts.
+    pub const EXERCISE: Self = Self {
+        heart_rate_bpm: 90.0,
+        final_heart_rate_bpm: 170.0,
+        ramp_s: 120.0,
+        rr_variability: 0.02,
+        atrial_fibrillation: false,
+        atrial_flutter: false,
+        ventricular_fibrillation: false,
+        r_amplitude_mv: 1.0,
+        final_r_amplitude_mv: 0.6,
+        t_wave_ratio: 0.3,
+        noise_mv: 0.03,
+        baseline_wander_mv: 0.2,
+        mains_mv: 0.0,
+    };
+
+    /// Atrial fibrillation at about 110 bpm, with irregular RR intervals.
+    pub const ATRIAL_FIBRILLATION: Self = Self {
+        heart_rate_bpm: 110.0,
+        final_heart_rate_bpm: 110.0,
+        ramp_s: 0.0,
+        rr_variability: 0.3,
+        atrial_fibrillation: true,
+        atrial_flutter: false,
+        ventricular_fibrillation: false,
+        r_amplitude_mv: 1.0,
+        final_r_amplitude_mv: 1.0,
+        t_wave_ratio: 0.3,
+        noise_mv: 0.01,
+        baseline_wander_mv: 0.05,
+        mains_mv: 0.0,
+    };
+
+    /// Atrial flutter at 300 bpm with 2:1 conduction, i.e. a regular ventricular rate of
+    /// 150 bpm.
+    pub const ATRIAL_FLUTTER: Self = Self {
+        heart_rate_bpm: 150.0,
+        final_heart_rate_bpm: 150.0,
+        rr_variability: 0.01,
+        atrial_flutter: true,
+        ..Self::REST
+    };
+
+    /// Coarse ventricular fibrillation, up to 1 mV peak to peak.
+    pub const VENTRICULAR_FIBRILLATION: Self = Self {
+        ventricular_fibrillation: true,
+        ..Self::REST
+    };
+
+    /// A resting sinus rhythm, with strong noise, baseline wander and power line interference.
+    pub const NOISY: Self = Self {
+        noise_mv: 0.15,
+        baseline_wander_mv: 0.3,
+        mains_mv: 0.1,
+        ..Self::REST
+    };
+
+    /// Returns the progress of the ramp at `t` seconds, between 0 and 1.
+    fn progress(&self, t: f32) -> f32 {
+        if self.ramp_s > 0.0 {
+            (t / self.ramp_s).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns the heart rate at `t` seconds, without the random variability.
+    fn heart_rate_at(&self, t: f32) -> f32 {
+        let progress = self.progress(t);
+        self.heart_rate_bpm + (self.final_heart_rate_bpm - self.heart_rate_bpm) * progress
+    }
+
+    /// Returns the amplitude of the R waves at `t` seconds.
+    fn r_amplitude_at(&self, t: f32) -> f32 {
+        let progress = self.progress(t);
+        self.r_amplitude_mv + (self.final_r_amplitude_mv - self.r_amplitude_mv) * progress
+    }
+}
+
+/// A sample generated by [`Synthesizer`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SynthSample {
+    /// The signal value, in millivolts.
+    pub value: f32,
+    /// Whether the sample is the peak of an R wave. These are the reference annotations of the
+    /// signal.
+    pub r_peak: bool,
+}
+
+/// A deterministic generator of synthetic ECG signals.
+///
+/// The generator is an infinite iterator of [`SynthSample`]s.
+#[derive(Clone, Debug)]
+pub struct Synthesizer {
+    fs: SamplingFrequency,
+    params: SynthParams,
+    state: u32,
+    index: u32,
+    /// The index and RR interval, in seconds, of the previous beat.
+    prev_beat: Option<(u32, f32)>,
+    /// The index and RR interval, in seconds, of the next beat.
+    next_beat: (u32, f32),
+    wander_phase: f32,
+    mains_phase: f32,
+    fibrillation_phase: f32,
+    flutter_phase: f32,
+    /// The phase, frequency and amplitude of the current ventricular fibrillation wave.
+    ventricular_fibrillation: (f32, f32, f32),
+}
+
+impl Synthesizer {
+    /// Creates a new generator for a signal sampled with `fs`. Signals generated with the same
+    /// parameters and `seed` are identical.
+    pub fn new(fs: SamplingFrequency, params: SynthParams, seed: u32) -> Self {
+        let mut this = Self {
+            fs,
+            params,
+            // xorshift does not work with a zero state
+            state: seed.max(1),
+            index: 0,
+            prev_beat: None,
+            next_beat: (0, 0.0),
+            wander_phase: 0.0,
+            mains_phase: 0.0,
+            fibrillation_phase: 0.0,
+            flutter_phase: 0.0,
+            ventricular_fibrillation: (
+                0.0,
+                VENTRICULAR_FIBRILLATION_HZ,
+                VENTRICULAR_FIBRILLATION_AMPLITUDE,
+            ),
+        };
+
+        // Start in the middle of an RR interval
+        let rr = this.next_rr(0.0);
+        this.next_beat = (fs.s_to_samples(rr / 2.0) as u32, rr);
+        this
+    }
+
+    /// Returns the parameters of the signal.
+    pub fn params(&self) -> &SynthParams {
+        &self.params
+    }
+
+    /// Returns a pseudo-random number between -1 and 1.
+    fn random(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1 << 23) as f32 - 1.0
+    }
+
+    /// Returns a random RR interval, in seconds, for a beat at `t` seconds.
+    fn next_rr(&mut self, t: f32) -> f32 {
+        let rr = 60.0 / self.params.heart_rate_at(t);
+        rr * (1.0 + self.params.rr_variability * self.random())
+    }
+
+    fn beat(&self, t: f32, rr_s: f32, amplitude: f32) -> f32 {
+        let mut value = QRS_WAVES
+            .iter()
+            .map(|wave| wave.value(t, rr_s))
+            .sum::<f32>()
+            + T_WAVE.value(t, rr_s) * self.params.t_wave_ratio;
+        if !(self.params.atrial_fibrillation || self.params.atrial_flutter) {
+            value += P_WAVE.value(t, rr_s);
+        }
+        value * amplitude
+    }
+
+    /// Returns the next sample of the ventricular fibrillation waves, relative to the R wave.
+    fn ventricular_fibrillation(&mut self) -> f32 {
+        let (phase, frequency, amplitude) = &mut self.ventricular_fibrillation;
+        let started = *phase;
+        let value = *amplitude * Self::advance(phase, *frequency, self.fs);
+
+        // Pick a new frequency and amplitude at the end of every wave, where the value is 0
+        if started > 0.0 && self.ventricular_fibrillation.0 < 0.0 {
+            self.ventricular_fibrillation.1 = VENTRICULAR_FIBRILLATION_HZ
+                * (1.0 + VENTRICULAR_FIBRILLATION_HZ_VARIABILITY * self.random());
+            self.ventricular_fibrillation.2 =
+                VENTRICULAR_FIBRILLATION_AMPLITUDE * (0.75 + 0.25 * self.random());
+        }
+
+        value
+    }
+
+    fn advance(phase: &mut f32, frequency: f32, fs: SamplingFrequency) -> f32 {
+        let value = micromath::F32Ext::sin(*phase);
+        *phase += 2.0 * PI * frequency / fs.raw();
+        if *phase > PI {
+            *phase -= 2.0 * PI;
+        }
+        value
+    }
+}
+
+impl Iterator for Synthesizer {
+    type Item = SynthSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.fs.samples_to_s(self.index as usize);
+        let amplitude = self.params.r_amplitude_at(t);
+
+        let mut r_peak = self.index == self.next_beat.0;
+        if r_peak {
+            let rr = self.next_rr(t);
+            self.prev_beat = Some(self.next_beat);
+            self.next_beat = (self.index + self.fs.s_to_samples(rr) as u32, rr);
+        }
+
+        let mut value = if self.params.ventricular_fibrillation {
+            r_peak = false;
+            self.ventricular_fibrillation() * amplitude
+        } else {
+            let to_s = |beat: u32| (self.index as i64 - beat as i64) as f32 / self.fs.raw();
+            let mut value = self.beat(to_s(self.next_beat.0), self.next_beat.1, amplitude);
+            if let Some((index, rr)) = self.prev_beat {
+                value += self.beat(to_s(index), rr, amplitude);
+            }
+            value
+        };
+
+        let fs = self.fs;
+        value +=
+            self.params.baseline_wander_mv * Self::advance(&mut self.wander_phase, WANDER_HZ, fs);
+        value += self.params.mains_mv * Self::advance(&mut self.mains_phase, MAINS_HZ, fs);
+        let fibrillation = Self::advance(&mut self.fibrillation_phase, FIBRILLATION_HZ, fs);
+        if self.params.atrial_fibrillation {
+            value += FIBRILLATION_AMPLITUDE * amplitude * fibrillation;
+        }
+        // Slowly descending, then rapidly rising sawtooth
+        Self::advance(&mut self.flutter_phase, FLUTTER_HZ, fs);
+        if self.params.atrial_flutter {
+            value -= FLUTTER_AMPLITUDE * amplitude * self.flutter_phase / PI;
+        }
+        value += self.params.noise_mv * self.random();
+
+        self.index += 1;
+        Some(SynthSample { value, r_peak })
+    }
+}
@@ -0,0 +1,416 @@
+//! Compact binary telemetry frames, for publishing the detection results over MQTT, LoRa or
+//! similar links from gateway firmware.
+//!
+//! [`TelemetryEncoder`] packs beats, heart rate, signal quality and alarms into frames with a
+//! sequence number and a CRC. The identifier of the algorithm that detected the beats, see
+//! [`QrsDetector::algorithm_version`](crate::QrsDetector::algorithm_version), can be sent along,
+//! e.g. in the first frame of a session. The backend decodes them using `TelemetryDecoder`
+//! (`std` feature), which also reports frames lost on the link. Frames are versioned by
+//! [`TELEMETRY_VERSION`].
+//!
+//! ```rust
+//! use qrs_detector::telemetry::{TelemetryEncoder, TelemetryItem};
+//!
+//! let mut encoder = TelemetryEncoder::new().with_max_size(51);
+//!
+//! encoder.start(120_000);
+//! encoder
+//!     .push(&TelemetryItem::Beat { offset_ms: 250, rr_ms: Some(810), confidence: 0.8 })
+//!     .unwrap();
+//! encoder.push(&TelemetryItem::HeartRate(Some(74.0))).unwrap();
+//!
+//! let frame = encoder.finish();
+//! // Publish `frame.as_bytes()`
+//! # assert_eq!(19, frame.as_bytes().len());
+//! ```
+//!
+//! # Wire format
+//!
+//! Frames have the following little-endian layout:
+//!
+//! - version: `u8`
+//! - sequence number: `u16`
+//! - time of the frame: `u32`, milliseconds of the device clock
+//! - number of items: `u8`
+//! - items, each starting with a tag byte:
+//!   - `1`, beat: offset from the time of the frame in milliseconds as `u16`, RR interval in
+//!     milliseconds as `u16` (0 if unknown), confidence as `u8` in 1/255 steps
+//!   - `2`, heart rate: 0.1 bpm steps as `u16` (`0xFFFF` if unknown)
+//!   - `3`, signal quality: application-defined flags as `u8`
+//!   - `4`, alarm: event as `u8` (position in [`Event::ALL`]), priority as `u8`, audible as `u8`
+//!   - `5`, algorithm version: `u32`
+//! - checksum: CRC-16/CCITT-FALSE of the preceding bytes, as `u16`
+
+use crate::{
+    alarms::{Alarm, Priority},
+    events::Event,
+    math::crc16,
+};
+
+/// The version of the telemetry frame encoding.
+pub const TELEMETRY_VERSION: u8 = 1;
+
+/// The size of the largest frame, in bytes.
+pub const MAX_FRAME_SIZE: usize = 128;
+
+/// Version, sequence number, time and item count.
+const HEADER_SIZE: usize = 8;
+const CRC_SIZE: usize = 2;
+
+const TAG_BEAT: u8 = 1;
+const TAG_HEART_RATE: u8 = 2;
+const TAG_QUALITY: u8 = 3;
+const TAG_ALARM: u8 = 4;
+const TAG_ALGORITHM_VERSION: u8 = 5;
+
+/// An item of a telemetry frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TelemetryItem {
+    /// A detected beat.
+    Beat {
+        /// The time of the beat, in milliseconds after the time of the frame.
+        offset_ms: u16,
+        /// The RR interval ending at the beat, in milliseconds.
+        rr_ms: Option<u16>,
+        /// The confidence of the detection, between 0 and 1, quantized to 1/255 steps.
+        confidence: f32,
+    },
+    /// The heart rate in beats per minute, quantized to 0.1 bpm steps.
+    HeartRate(Option<f32>),
+    /// Application-defined signal quality flags, e.g. lead-off or saturation.
+    Quality {
+        /// The flags.
+        flags: u8,
+    },
+    /// An active alarm.
+    Alarm {
+        /// The event that raised the alarm.
+        event: Event,
+        /// The priority of the alarm.
+        priority: Priority,
+        /// `true` if the alarm is audible, see [`Alarm::is_audible`].
+        audible: bool,
+    },
+    /// The identifier of the algorithm that detected the beats, see
+    /// [`QrsDetector::algorithm_version`](crate::QrsDetector::algorithm_version).
+    AlgorithmVersion(u32),
+}
+
+impl TelemetryItem {
+    /// Creates an item reporting `alarm`.
+    pub fn alarm(alarm: &Alarm) -> Self {
+        Self::Alarm {
+            event: alarm.event,
+            priority: alarm.priority,
+            audible: alarm.is_audible(),
+        }
+    }
+
+    /// Returns the number of bytes the item is encoded into.
+    pub const fn encoded_size(&self) -> usize {
+        match self {
+            Self::Beat { .. } => 6,
+            Self::HeartRate(_) => 3,
+            Self::Quality { .. } => 2,
+            Self::Alarm { .. } => 4,
+            Self::AlgorithmVersion(_) => 5,
+        }
+    }
+
+    fn encode(&self, out: &mut [u8]) {
+        match *self {
+            Self::Beat {
+                offset_ms,
+                rr_ms,
+                confidence,
+            } => {
+                out[0] = TAG_BEAT;
+                out[1..3].copy_from_slice(&offset_ms.to_le_bytes());
+                out[3..5].copy_from_slice(&rr_ms.unwrap_or(0).to_le_bytes());
+                out[5] = (confidence.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+            }
+            Self::HeartRate(bpm) => {
+                let bpm = bpm.map_or(u16::MAX, |bpm| {
+                    (bpm * 10.0 + 0.5).clamp(0.0, (u16::MAX - 1) as f32) as u16
+                });
+                out[0] = TAG_HEART_RATE;
+                out[1..3].copy_from_slice(&bpm.to_le_bytes());
+            }
+            Self::Quality { flags } => {
+                out[0] = TAG_QUALITY;
+                out[1] = flags;
+            }
+            Self::Alarm {
+                event,
+                priority,
+                audible,
+            } => {
+                out[0] = TAG_ALARM;
+                out[1] = event as u8;
+                out[2] = priority as u8;
+                out[3] = audible as u8;
+            }
+            Self::AlgorithmVersion(version) => {
+                out[0] = TAG_ALGORITHM_VERSION;
+                out[1..5].copy_from_slice(&version.to_le_bytes());
+            }
+        }
+    }
+}
+
+/// The frame is too small to hold the item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameFull;
+
+/// An encoded telemetry frame, see [`TelemetryEncoder::finish`].
+#[derive(Clone, Copy, Debug)]
+pub struct TelemetryFrame {
+    bytes: [u8; MAX_FRAME_SIZE],
+    len: usize,
+}
+
+impl TelemetryFrame {
+    /// Returns the encoded frame.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Encodes telemetry frames. See the [module documentation](self).
+#[derive(Clone, Copy, Debug)]
+pub struct TelemetryEncoder {
+    max_size: usize,
+    sequence: u16,
+    bytes: [u8; MAX_FRAME_SIZE],
+    len: usize,
+}
+
+impl Default for TelemetryEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TelemetryEncoder {
+    /// Creates a new encoder, producing frames of at most [`MAX_FRAME_SIZE`] bytes. The first
+    /// frame is started at time 0.
+    pub fn new() -> Self {
+        let mut encoder = Self {
+            max_size: MAX_FRAME_SIZE,
+            sequence: 0,
+            bytes: [0; MAX_FRAME_SIZE],
+            len: 0,
+        };
+        encoder.start(0);
+        encoder
+    }
+
+    /// Limits the size of the frames, e.g. to the largest payload of the link.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` cannot hold the largest item, or is larger than [`MAX_FRAME_SIZE`].
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        assert!(
+            (HEADER_SIZE + 6 + CRC_SIZE..=MAX_FRAME_SIZE).contains(&max_size),
+            "The frame size must be between 16 and 128 bytes"
+        );
+        self.max_size = max_size;
+        self
+    }
+
+    /// Returns the sequence number of the frame being encoded.
+    pub fn sequence(&self) -> u16 {
+        self.sequence
+    }
+
+    /// Starts a new frame at `time_ms` of the device clock, discarding the items of an unfinished
+    /// frame.
+    pub fn start(&mut self, time_ms: u32) {
+        self.bytes[0] = TELEMETRY_VERSION;
+        self.bytes[1..3].copy_from_slice(&self.sequence.to_le_bytes());
+        self.bytes[3..7].copy_from_slice(&time_ms.to_le_bytes());
+        self.bytes[7] = 0;
+        self.len = HEADER_SIZE;
+    }
+
+    /// Returns `true` if the frame has no items.
+    pub fn is_empty(&self) -> bool {
+        self.bytes[7] == 0
+    }
+
+    /// Adds an item to the frame. If the frame is full, nothing is added and the frame should be
+    /// finished.
+    pub fn push(&mut self, item: &TelemetryItem) -> Result<(), FrameFull> {
+        let size = item.encoded_size();
+        if self.len + size + CRC_SIZE > self.max_size || self.bytes[7] == u8::MAX {
+            return Err(FrameFull);
+        }
+
+        item.encode(&mut self.bytes[self.len..self.len + size]);
+        self.len += size;
+        self.bytes[7] += 1;
+        Ok(())
+    }
+
+    /// Finishes the frame, and starts the next one at the same time.
+    pub fn finish(&mut self) -> TelemetryFrame {
+        let crc = crc16(&self.bytes[..self.len]);
+        self.bytes[self.len..self.len + CRC_SIZE].copy_from_slice(&crc.to_le_bytes());
+
+        let frame = TelemetryFrame {
+            bytes: self.bytes,
+            len: self.len + CRC_SIZE,
+        };
+
+        let time_ms = u32::from_le_bytes(self.bytes[3..7].try_into().unwrap());
+        self.sequence = self.sequence.wrapping_add(1);
+        self.start(time_ms);
+        frame
+    }
+}
+
+/// An error returned when decoding a telemetry frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TelemetryError {
+    /// The data is shorter than the frame.
+    Truncated,
+    /// The frame was encoded using an unsupported version.
+    UnsupportedVersion(u8),
+    /// The checksum of the frame does not match. A corrupted frame may also be reported as
+    /// truncated or invalid, if the corruption changed its structure.
+    Checksum,
+    /// The frame contains an unknown item or an invalid value.
+    Invalid,
+}
+
+impl core::fmt::Display for TelemetryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "truncated frame"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported version {version}"),
+            Self::Checksum => write!(f, "checksum mismatch"),
+            Self::Invalid => write!(f, "invalid item"),
+        }
+    }
+}
+
+/// A decoded telemetry frame.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedFrame {
+    /// The sequence number of the frame.
+    pub sequence: u16,
+    /// The time of the frame, in milliseconds of the device clock.
+    pub time_ms: u32,
+    /// The items of the frame.
+    pub items: std::vec::Vec<TelemetryItem>,
+}
+
+/// Decodes telemetry frames encoded by [`TelemetryEncoder`], and counts the frames lost on the
+/// link.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct TelemetryDecoder {
+    next_sequence: Option<u16>,
+    lost: u32,
+}
+
+#[cfg(feature = "std")]
+impl TelemetryDecoder {
+    /// Creates a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of frames missing from the sequence of the decoded frames.
+    pub fn lost_frames(&self) -> u32 {
+        self.lost
+    }
+
+    /// Decodes a frame.
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<DecodedFrame, TelemetryError> {
+        let frame = decode(bytes)?;
+        if let Some(expected) = self.next_sequence {
+            // Duplicated or reordered frames are not counted
+            let missing = frame.sequence.wrapping_sub(expected);
+            if missing < u16::MAX / 2 {
+                self.lost += missing as u32;
+            }
+        }
+        self.next_sequence = Some(frame.sequence.wrapping_add(1));
+        Ok(frame)
+    }
+}
+
+#[cfg(feature = "std")]
+fn decode(bytes: &[u8]) -> Result<DecodedFrame, TelemetryError> {
+    let version = *bytes.first().ok_or(TelemetryError::Truncated)?;
+    if version != TELEMETRY_VERSION {
+        return Err(TelemetryError::UnsupportedVersion(version));
+    }
+    let header = bytes.get(..HEADER_SIZE).ok_or(TelemetryError::Truncated)?;
+    let u16_at = |bytes: &[u8], idx: usize| u16::from_le_bytes([bytes[idx], bytes[idx + 1]]);
+
+    let mut items = std::vec::Vec::with_capacity(header[7] as usize);
+    let mut position = HEADER_SIZE;
+    for _ in 0..header[7] {
+        let tag = *bytes.get(position).ok_or(TelemetryError::Truncated)?;
+        let size = match tag {
+            TAG_BEAT => 6,
+            TAG_HEART_RATE => 3,
+            TAG_QUALITY => 2,
+            TAG_ALARM => 4,
+            TAG_ALGORITHM_VERSION => 5,
+            _ => return Err(TelemetryError::Invalid),
+        };
+        let item = bytes
+            .get(position..position + size)
+            .ok_or(TelemetryError::Truncated)?;
+        position += size;
+
+        items.push(match tag {
+            TAG_BEAT => TelemetryItem::Beat {
+                offset_ms: u16_at(item, 1),
+                rr_ms: Some(u16_at(item, 3)).filter(|&rr| rr != 0),
+                confidence: item[5] as f32 / 255.0,
+            },
+            TAG_HEART_RATE => TelemetryItem::HeartRate(
+                Some(u16_at(item, 1))
+                    .filter(|&bpm| bpm != u16::MAX)
+                    .map(|bpm| bpm as f32 / 10.0),
+            ),
+            TAG_QUALITY => TelemetryItem::Quality { flags: item[1] },
+            TAG_ALGORITHM_VERSION => {
+                TelemetryItem::AlgorithmVersion(u32::from_le_bytes(item[1..5].try_into().unwrap()))
+            }
+            _ => TelemetryItem::Alarm {
+                event: *Event::ALL
+                    .get(item[1] as usize)
+                    .ok_or(TelemetryError::Invalid)?,
+                priority: match item[2] {
+                    0 => Priority::Low,
+                    1 => Priority::Medium,
+                    2 => Priority::High,
+                    _ => return Err(TelemetryError::Invalid),
+                },
+                audible: item[3] != 0,
+            },
+        });
+    }
+
+    let checksum = bytes
+        .get(position..position + CRC_SIZE)
+        .ok_or(TelemetryError::Truncated)?;
+    if u16_at(checksum, 0) != crc16(&bytes[..position]) {
+        return Err(TelemetryError::Checksum);
+    }
+
+    Ok(DecodedFrame {
+        sequence: u16_at(header, 1),
+        time_ms: u32::from_le_bytes(header[3..7].try_into().unwrap()),
+        items,
+    })
+}
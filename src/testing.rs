@@ -0,0 +1,426 @@
+//! Utilities to test the detector: perturbation of the acquisition with [`Jitter`], and
+//! hardware-in-the-loop test rigs stepping the detector in virtual time with [`Simulator`].
+
+use core::{fmt, time::Duration};
+
+use crate::{
+    algorithms::R,
+    component::ThresholdComponent,
+    config::{ConfigUpdate, MAX_MM_DEPTH, MAX_RR_DEPTH},
+    journal::Values,
+    math::Fnv1a,
+    noise::NoiseEstimator,
+    timeline::Timeline,
+    transform::SampleTransform,
+    Detection, QrsDetector, SkippedBeat, Suppressed, SuppressedCounts, Thresholds,
+};
+
+/// A sample delivered by [`Jitter`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Perturbation {
+    /// A sample delivered normally.
+    Sample(f32),
+    /// A repeated delivery of the previous sample.
+    Duplicate(f32),
+    /// A sample lost during acquisition.
+    Dropped,
+}
+
+/// Simulates an unreliable acquisition link, e.g. Bluetooth Low Energy, that drops and duplicates
+/// samples.
+///
+/// The perturbations are pseudo-random, but deterministic for a given seed.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::testing::{Jitter, Perturbation};
+///
+/// let mut detector = QrsDetector::new::<150, 25>(500.sps());
+///
+/// # let samples = [0.0; 10];
+/// let jitter = Jitter::new(samples.into_iter(), 1)
+///     .with_drop_rate(0.01)
+///     .with_duplicate_rate(0.01);
+///
+/// for perturbation in jitter {
+///     match perturbation {
+///         Perturbation::Sample(sample) => {
+///             detector.update(sample);
+///         }
+///         Perturbation::Duplicate(sample) => {
+///             detector.update(sample);
+///             detector.notify_duplicate(1);
+///         }
+///         Perturbation::Dropped => detector.notify_gap(1),
+///     }
+/// }
+/// ```
+pub struct Jitter<I> {
+    samples: I,
+    state: u32,
+    drop_rate: f32,
+    duplicate_rate: f32,
+    duplicate: Option<f32>,
+}
+
+impl<I> Jitter<I>
+where
+    I: Iterator<Item = f32>,
+{
+    /// Creates a new perturbation source for `samples`. By default, no samples are perturbed.
+    pub fn new(samples: I, seed: u32) -> Self {
+        Self {
+            samples,
+            // xorshift does not work with a zero state
+            state: seed.max(1),
+            drop_rate: 0.0,
+            duplicate_rate: 0.0,
+            duplicate: None,
+        }
+    }
+
+    /// Sets the probability of dropping a sample.
+    pub fn with_drop_rate(mut self, rate: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "The drop rate must be between 0 and 1"
+        );
+        self.drop_rate = rate;
+        self
+    }
+
+    /// Sets the probability of duplicating a sample.
+    pub fn with_duplicate_rate(mut self, rate: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "The duplicate rate must be between 0 and 1"
+        );
+        self.duplicate_rate = rate;
+        self
+    }
+
+    /// Returns a pseudo-random number between 0 and 1.
+    fn random(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+impl<I> Iterator for Jitter<I>
+where
+    I: Iterator<Item = f32>,
+{
+    type Item = Perturbation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(sample) = self.duplicate.take() {
+            return Some(Perturbation::Duplicate(sample));
+        }
+
+        let sample = self.samples.next()?;
+        if self.random() < self.drop_rate {
+            return Some(Perturbation::Dropped);
+        }
+        if self.random() < self.duplicate_rate {
+            self.duplicate = Some(sample);
+        }
+
+        Some(Perturbation::Sample(sample))
+    }
+}
+
+/// The number of detections a [`StepReport`] holds.
+pub const MAX_STEP_DETECTIONS: usize = 16;
+
+/// The outputs of the detector during a step of a [`Simulator`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StepReport {
+    detections: [u32; MAX_STEP_DETECTIONS],
+    len: usize,
+    /// The number of detections that did not fit into the report.
+    pub dropped_detections: u32,
+    /// The number of samples processed in the step.
+    pub samples: u32,
+    /// Whether a DC offset step was detected, see [`QrsDetector::take_signal_step`].
+    pub signal_step: bool,
+    /// Whether a refractory period was forcibly ended, see
+    /// [`QrsDetector::take_refractory_timeout`].
+    pub refractory_timeout: bool,
+    /// The last skipped beat found, see [`QrsDetector::take_skipped_beat`].
+    pub skipped_beat: Option<SkippedBeat>,
+    /// The result of the scheduled configurations applied, see
+    /// [`QrsDetector::take_config_update`].
+    pub config_update: Option<ConfigUpdate>,
+}
+
+impl StepReport {
+    const fn new() -> Self {
+        Self {
+            detections: [0; MAX_STEP_DETECTIONS],
+            len: 0,
+            dropped_detections: 0,
+            samples: 0,
+            signal_step: false,
+            refractory_timeout: false,
+            skipped_beat: None,
+            config_update: None,
+        }
+    }
+
+    /// Returns the indices of the detections, in order. At most [`MAX_STEP_DETECTIONS`] are
+    /// kept, later ones are counted in `dropped_detections`.
+    pub fn detections(&self) -> &[u32] {
+        &self.detections[..self.len]
+    }
+
+    fn push(&mut self, index: u32) {
+        if self.len < MAX_STEP_DETECTIONS {
+            self.detections[self.len] = index;
+            self.len += 1;
+        } else {
+            self.dropped_detections += 1;
+        }
+    }
+}
+
+/// Steps a detector in virtual time, for hardware-in-the-loop test rigs driven by a host.
+///
+/// Every step injects samples and collects every output of the detector into a [`StepReport`].
+/// Between steps, the state can be inspected using [`Simulator::dump`], and compared against a
+/// reference implementation.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::testing::Simulator;
+///
+/// let mut simulator = Simulator::new(QrsDetector::new::<150, 25>(500.sps()));
+///
+/// // 2 seconds of silence, then a lost packet
+/// let report = simulator.step_constant(0.0, 1000);
+/// assert!(report.detections().is_empty());
+/// simulator.skip(20);
+///
+/// assert_eq!(1020, simulator.dump().timeline().position());
+/// assert_eq!(2040, simulator.time().as_millis());
+/// ```
+pub struct Simulator<FMW, FB, H, T, S, N, FC> {
+    detector: QrsDetector<FMW, FB, H, T, S, N, FC, R>,
+    /// The number of samples injected or skipped.
+    virtual_samples: u64,
+}
+
+impl<FMW, FB, H, T, S, N, FC> Simulator<FMW, FB, H, T, S, N, FC>
+where
+    H: AsRef<[Detection]> + AsMut<[Detection]>,
+    T: SampleTransform,
+    S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+    N: NoiseEstimator,
+    FC: ThresholdComponent,
+{
+    /// Creates a simulator stepping `detector`. The virtual time starts at 0.
+    pub fn new(detector: QrsDetector<FMW, FB, H, T, S, N, FC, R>) -> Self {
+        Self {
+            detector,
+            virtual_samples: 0,
+        }
+    }
+
+    /// Returns the detector.
+    pub fn detector(&self) -> &QrsDetector<FMW, FB, H, T, S, N, FC, R> {
+        &self.detector
+    }
+
+    /// Returns the detector, e.g. to schedule a configuration between steps.
+    pub fn detector_mut(&mut self) -> &mut QrsDetector<FMW, FB, H, T, S, N, FC, R> {
+        &mut self.detector
+    }
+
+    /// Returns the detector, consuming the simulator.
+    pub fn into_inner(self) -> QrsDetector<FMW, FB, H, T, S, N, FC, R> {
+        self.detector
+    }
+
+    /// Returns the virtual time elapsed since the simulator was created, including skipped
+    /// samples.
+    pub fn time(&self) -> Duration {
+        let fs = self.detector.sampling_frequency().raw() as f64;
+        Duration::from_secs_f64(self.virtual_samples as f64 / fs)
+    }
+
+    /// Processes `samples` and returns the outputs of the detector.
+    pub fn step(&mut self, samples: &[f32]) -> StepReport {
+        self.step_with(samples.iter().copied())
+    }
+
+    /// Processes `count` samples of the constant `value`, and returns the outputs of the
+    /// detector.
+    pub fn step_constant(&mut self, value: f32, count: u32) -> StepReport {
+        self.step_with((0..count).map(|_| value))
+    }
+
+    fn step_with(&mut self, samples: impl Iterator<Item = f32>) -> StepReport {
+        let mut report = StepReport::new();
+        for sample in samples {
+            if let Some(index) = self.detector.update(sample) {
+                report.push(index);
+            }
+            report.samples += 1;
+        }
+        self.virtual_samples += report.samples as u64;
+
+        #[cfg(feature = "quality")]
+        {
+            report.signal_step = self.detector.take_signal_step();
+        }
+        report.refractory_timeout = self.detector.take_refractory_timeout();
+        report.skipped_beat = self.detector.take_skipped_beat();
+        report.config_update = self.detector.take_config_update();
+        report
+    }
+
+    /// Advances the virtual time by `samples` lost samples, see [`QrsDetector::notify_gap`].
+    pub fn skip(&mut self, samples: u32) {
+        self.detector.notify_gap(samples);
+        self.virtual_samples += samples as u64;
+    }
+
+    /// Returns a dump of the internal state of the detector, see [`QrsDetector::state_dump`].
+    pub fn dump(&self) -> StateDump {
+        self.detector.state_dump()
+    }
+}
+
+/// A deterministic dump of the internal state of a [`QrsDetector`], see
+/// [`QrsDetector::state_dump`].
+///
+/// The `Display` implementation formats the state as a single line of `key=value` pairs, with
+/// floating point values printed exactly, e.g.
+/// `position=1000 elapsed=1000 epoch=0 last_detection=0 m=- f=- r=0 refractory=false mm=- rr=-
+/// suppressed=0/0/0 skipped=0`. Two dumps are equal if their text is equal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StateDump {
+    pub(crate) timeline: Timeline,
+    pub(crate) last_detection: u32,
+    pub(crate) thresholds: Thresholds,
+    pub(crate) in_refractory: bool,
+    pub(crate) mm: Values<f32, MAX_MM_DEPTH>,
+    pub(crate) rr: Values<u32, MAX_RR_DEPTH>,
+    pub(crate) suppressed: SuppressedCounts,
+    pub(crate) skipped_beats: u32,
+}
+
+impl StateDump {
+    /// Returns the sample counter.
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Returns the absolute sample index of the last detection.
+    pub fn last_detection(&self) -> u32 {
+        self.last_detection
+    }
+
+    /// Returns the threshold components.
+    pub fn thresholds(&self) -> Thresholds {
+        self.thresholds
+    }
+
+    /// Returns whether the detector is in the refractory period following a detection.
+    pub fn in_refractory(&self) -> bool {
+        self.in_refractory
+    }
+
+    /// Returns the values of the steep-slope threshold buffer, from the oldest to the newest.
+    pub fn mm_values(&self) -> &[f32] {
+        self.mm.as_slice()
+    }
+
+    /// Returns the collected RR intervals in samples, from the oldest to the newest.
+    pub fn rr_intervals(&self) -> &[u32] {
+        self.rr.as_slice()
+    }
+
+    /// Returns the number of suppressed threshold crossings.
+    pub fn suppressed_counts(&self) -> SuppressedCounts {
+        self.suppressed
+    }
+
+    /// Returns the number of skipped beats found.
+    pub fn skipped_beat_count(&self) -> u32 {
+        self.skipped_beats
+    }
+
+    /// Returns the 32 bit FNV-1a hash of the text of the dump, to compare long runs cheaply.
+    pub fn hash(&self) -> u32 {
+        struct Hasher(Fnv1a);
+
+        impl fmt::Write for Hasher {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0.write(s.as_bytes());
+                Ok(())
+            }
+        }
+
+        let mut hasher = Hasher(Fnv1a::new());
+        // Writing into the hasher never fails
+        let _ = fmt::write(&mut hasher, format_args!("{self}"));
+        hasher.0.finish()
+    }
+}
+
+/// Formats the dump as `key=value` pairs, see [`StateDump`].
+impl fmt::Display for StateDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn option(f: &mut fmt::Formatter<'_>, value: Option<f32>) -> fmt::Result {
+            match value {
+                Some(value) => write!(f, "{value}"),
+                None => write!(f, "-"),
+            }
+        }
+
+        fn list<V: fmt::Display>(f: &mut fmt::Formatter<'_>, values: &[V]) -> fmt::Result {
+            if values.is_empty() {
+                return write!(f, "-");
+            }
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{value}")?;
+            }
+            Ok(())
+        }
+
+        write!(
+            f,
+            "position={} elapsed={} epoch={} last_detection={} m=",
+            self.timeline.position(),
+            self.timeline.elapsed(),
+            self.timeline.epoch(),
+            self.last_detection
+        )?;
+        option(f, self.thresholds.m)?;
+        write!(f, " f=")?;
+        option(f, self.thresholds.f)?;
+        write!(
+            f,
+            " r={} refractory={} mm=",
+            self.thresholds.r, self.in_refractory
+        )?;
+        list(f, self.mm.as_slice())?;
+        write!(f, " rr=")?;
+        list(f, self.rr.as_slice())?;
+        write!(
+            f,
+            " suppressed={}/{}/{} skipped={}",
+            self.suppressed.refractory,
+            self.suppressed.signal_step,
+            self.suppressed.noise,
+            self.skipped_beats
+        )
+    }
+}
@@ -0,0 +1,188 @@
+//! Conversion of sample indices to absolute time, and monotonic clock sources.
+//!
+//! Features that follow wall time, like periodic summaries, read the time from a
+//! [`ClockSource`]. By default, time is measured by counting samples with [`SampleClock`], which
+//! needs no hardware timer. Devices with a timer, or hosts with `std`, may provide a clock that
+//! keeps running across gaps in the acquisition.
+
+use core::time::Duration;
+
+use crate::sampling::SamplingFrequency;
+
+/// A monotonic tick counter.
+///
+/// # Example
+///
+/// A hardware timer running at 32768 Hz:
+///
+/// ```rust
+/// use qrs_detector::time::ClockSource;
+///
+/// struct RtcClock;
+///
+/// impl ClockSource for RtcClock {
+///     fn now(&self) -> u64 {
+///         # let rtc_counter = || 0;
+///         rtc_counter()
+///     }
+///
+///     fn ticks_per_second(&self) -> f32 {
+///         32768.0
+///     }
+/// }
+/// ```
+pub trait ClockSource {
+    /// Returns the current value of the tick counter. The value must never decrease.
+    fn now(&self) -> u64;
+
+    /// Returns the number of ticks per second.
+    fn ticks_per_second(&self) -> f32;
+
+    /// Notifies the clock that a sample has been processed. Clocks that count samples advance
+    /// here, other clocks ignore it.
+    fn on_sample(&mut self) {}
+
+    /// Converts a number of ticks to milliseconds, rounded to the nearest millisecond.
+    fn ticks_to_ms(&self, ticks: u64) -> u64 {
+        (ticks as f64 * 1000.0 / self.ticks_per_second() as f64 + 0.5) as u64
+    }
+
+    /// Converts `duration` to a number of ticks, rounded down.
+    fn duration_to_ticks(&self, duration: Duration) -> u64 {
+        (duration.as_secs_f64() * self.ticks_per_second() as f64) as u64
+    }
+}
+
+/// A clock that counts the processed samples. Time only advances while samples are processed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleClock {
+    fs: SamplingFrequency,
+    samples: u64,
+}
+
+impl SampleClock {
+    /// Creates a new clock for signals sampled with `fs`, starting at 0.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self { fs, samples: 0 }
+    }
+}
+
+impl ClockSource for SampleClock {
+    fn now(&self) -> u64 {
+        self.samples
+    }
+
+    fn ticks_per_second(&self) -> f32 {
+        self.fs.raw()
+    }
+
+    fn on_sample(&mut self) {
+        self.samples += 1;
+    }
+}
+
+/// A clock based on [`std::time::Instant`], counting microseconds since its creation.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    /// Creates a new clock, starting at 0.
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl ClockSource for SystemClock {
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+
+    fn ticks_per_second(&self) -> f32 {
+        1_000_000.0
+    }
+}
+
+/// Maps sample indices to absolute time, in milliseconds since a user-defined epoch (e.g. the
+/// Unix epoch).
+///
+/// The anchor handles the wraparound of the `u32` sample index, as long as the converted index is
+/// less than 2<sup>32</sup> samples after the anchor point.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::sampling::*;
+/// use qrs_detector::time::EpochAnchor;
+///
+/// // Sample 0 was acquired at 2024-01-01 00:00:00 UTC
+/// let mut anchor = EpochAnchor::new(500.sps(), 1_704_067_200_000);
+/// assert_eq!(1_704_067_201_000, anchor.to_epoch_ms(500));
+///
+/// // The device synchronized its clock, sample 1000 was acquired 2.1 seconds after the epoch
+/// anchor.reset_at(1000, 1_704_067_202_100);
+/// assert_eq!(1_704_067_203_100, anchor.to_epoch_ms(1500));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EpochAnchor {
+    fs: SamplingFrequency,
+    index: u32,
+    epoch_ms: u64,
+}
+
+impl EpochAnchor {
+    /// Creates an anchor that maps sample index 0 of a signal sampled with `fs` to `epoch_ms`.
+    pub fn new(fs: SamplingFrequency, epoch_ms: u64) -> Self {
+        Self {
+            fs,
+            index: 0,
+            epoch_ms,
+        }
+    }
+
+    /// Maps sample index `index` to `epoch_ms`. Later indices are converted relative to this
+    /// point.
+    ///
+    /// This should be called when the relation between sample indices and time changes, e.g. when
+    /// the device clock is synchronized or acquisition restarts.
+    pub fn reset_at(&mut self, index: u32, epoch_ms: u64) {
+        self.index = index;
+        self.epoch_ms = epoch_ms;
+    }
+
+    /// Maps sample index `index` to the current time of `clock`. `clock_epoch_ms` is the time of
+    /// tick 0 of the clock, in milliseconds since the epoch.
+    ///
+    /// This is useful when the sample index is read together with a timestamp of a free-running
+    /// clock, e.g. at the start of every acquisition.
+    pub fn reset_at_clock<C: ClockSource>(&mut self, index: u32, clock: &C, clock_epoch_ms: u64) {
+        self.reset_at(index, clock_epoch_ms + clock.ticks_to_ms(clock.now()));
+    }
+
+    /// Returns the sample index and the time the anchor is set to.
+    pub fn anchor(&self) -> (u32, u64) {
+        (self.index, self.epoch_ms)
+    }
+
+    /// Converts sample index `index` to milliseconds since the epoch.
+    ///
+    /// `index` must not precede the anchor point.
+    pub fn to_epoch_ms(&self, index: u32) -> u64 {
+        let samples = index.wrapping_sub(self.index);
+        let ms = samples as f64 * 1000.0 / self.fs.raw() as f64;
+
+        self.epoch_ms + (ms + 0.5) as u64
+    }
+}
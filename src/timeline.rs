@@ -0,0 +1,129 @@
+//! Mapping of the relative time of the detection algorithm to absolute sample indices.
+//!
+//! The thresholds of the detector only depend on the time elapsed since the detector was last
+//! reset. [`Timeline`] keeps the absolute index of the input signal: it starts a new epoch at
+//! every reset, and accounts for samples lost or duplicated by the acquisition. Detection indices
+//! reported by [`QrsDetector`] are absolute, and remain monotonic across resets.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//!
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//! # let samples = [0.0; 10];
+//! for sample in samples {
+//!     detector.update(sample);
+//! }
+//! detector.notify_gap(100);
+//! detector.clear();
+//!
+//! let timeline = detector.timeline();
+//! assert_eq!(110, timeline.position());
+//! assert_eq!(0, timeline.elapsed());
+//! assert_eq!(1, timeline.epoch());
+//! assert_eq!(100, timeline.gap_samples());
+//! ```
+//!
+//! [`QrsDetector`]: crate::QrsDetector
+
+/// The sample counter of the detector.
+///
+/// Relative time is counted in samples since the start of the current epoch. An epoch starts when
+/// the timeline is created, and when the detector is reset. The counters wrap around.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timeline {
+    /// The absolute index of the first sample of the epoch.
+    origin: u32,
+    /// The number of samples since the start of the epoch.
+    elapsed: u32,
+    epoch: u32,
+    gap_samples: u32,
+}
+
+impl Timeline {
+    /// Creates a timeline starting at sample index 0.
+    pub const fn new() -> Self {
+        Self {
+            origin: 0,
+            elapsed: 0,
+            epoch: 0,
+            gap_samples: 0,
+        }
+    }
+
+    /// Returns the absolute index of the next sample.
+    pub const fn position(&self) -> u32 {
+        self.origin.wrapping_add(self.elapsed)
+    }
+
+    /// Returns the number of samples since the start of the current epoch, i.e. the relative
+    /// index of the next sample.
+    pub const fn elapsed(&self) -> u32 {
+        self.elapsed
+    }
+
+    /// Returns the absolute index of the first sample of the current epoch.
+    pub const fn origin(&self) -> u32 {
+        self.origin
+    }
+
+    /// Returns the number of epochs started after the first one.
+    pub const fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// Returns the number of samples reported lost, see [`Timeline::skip`].
+    pub const fn gap_samples(&self) -> u32 {
+        self.gap_samples
+    }
+
+    /// Converts a relative index of the current epoch to an absolute index.
+    pub const fn to_absolute(&self, relative: u32) -> u32 {
+        self.origin.wrapping_add(relative)
+    }
+
+    /// Converts an absolute index to a relative index of the current epoch. Returns `None` if the
+    /// index is outside of the current epoch.
+    pub const fn to_relative(&self, absolute: u32) -> Option<u32> {
+        let relative = absolute.wrapping_sub(self.origin);
+        if relative <= self.elapsed {
+            Some(relative)
+        } else {
+            None
+        }
+    }
+
+    /// Advances the timeline by one processed sample.
+    pub fn advance(&mut self) {
+        self.elapsed = self.elapsed.wrapping_add(1);
+    }
+
+    /// Advances the timeline by `samples` samples lost during acquisition.
+    pub fn skip(&mut self, samples: u32) {
+        self.elapsed = self.elapsed.wrapping_add(samples);
+        self.gap_samples = self.gap_samples.wrapping_add(samples);
+    }
+
+    /// Moves the timeline back by `samples` duplicated samples, but not before the relative index
+    /// `earliest`.
+    pub fn rewind(&mut self, samples: u32, earliest: u32) {
+        self.elapsed = self.elapsed.saturating_sub(samples).max(earliest);
+    }
+
+    /// Starts a new epoch at the current position. Does nothing if no sample has been processed
+    /// since the start of the current epoch.
+    pub fn reset(&mut self) {
+        if self.elapsed != 0 {
+            self.origin = self.position();
+            self.elapsed = 0;
+            self.epoch = self.epoch.wrapping_add(1);
+        }
+    }
+
+    /// Moves the start of the current epoch to absolute index `origin`, continuing from absolute
+    /// index `position`, e.g. when restoring a saved state.
+    pub fn restart_at(&mut self, origin: u32, position: u32) {
+        self.origin = origin;
+        self.elapsed = position.wrapping_sub(origin);
+    }
+}
@@ -0,0 +1,430 @@
+//! Preprocessing applied to the samples before QRS detection.
+//!
+//! The detector expects a signal that emphasizes the steep slopes of the QRS complexes, e.g. a
+//! filtered and differentiated ECG signal. A [`SampleTransform`] moves this preprocessing into
+//! the detector, see [`QrsDetector::with_transform`](crate::QrsDetector::with_transform).
+
+use crate::{math::polynomial_group_delay, sampling::SamplingFrequency};
+
+/// Transforms a sample before it is processed by the detector.
+///
+/// Closures of type `FnMut(f32) -> f32` implement this trait.
+pub trait SampleTransform {
+    /// Transforms `sample`.
+    fn transform(&mut self, sample: f32) -> f32;
+
+    /// Resets the internal state of the transform. Called when the detector is cleared.
+    fn clear(&mut self) {}
+
+    /// Returns the delay of the transform at the frequencies of the QRS complex, in samples.
+    ///
+    /// The detector reports detections earlier by this amount, so that they are aligned with the
+    /// input signal. The default implementation reports no delay, which is also used for
+    /// closures.
+    fn group_delay(&self) -> f32 {
+        0.0
+    }
+
+    /// Returns the delay of the transform at `frequency`, relative to the sampling frequency
+    /// (between 0 and 0.5), in samples.
+    ///
+    /// Transforms with a nonlinear phase response, e.g. IIR filters, delay the frequencies of a
+    /// QRS complex by different amounts. The detector uses this delay at the dominant frequency
+    /// of every beat to correct its timing, see
+    /// [`Detection::timing_correction`](crate::Detection::timing_correction). The default
+    /// implementation returns [`SampleTransform::group_delay`], which is exact for linear phase
+    /// transforms.
+    fn group_delay_at(&self, frequency: f32) -> f32 {
+        let _ = frequency;
+        self.group_delay()
+    }
+
+    /// Applies `next` to the output of this transform.
+    ///
+    /// ```rust
+    /// use qrs_detector::transform::SampleTransform;
+    ///
+    /// let mut transform = (|sample: f32| sample - 1.0).chain(|sample: f32| sample.abs());
+    /// assert_eq!(1.0, transform.transform(0.0));
+    /// ```
+    fn chain<U>(self, next: U) -> Chain<Self, U>
+    where
+        Self: Sized,
+        U: SampleTransform,
+    {
+        Chain {
+            first: self,
+            second: next,
+        }
+    }
+}
+
+/// Passes samples through unchanged. This is the default transform of the detector.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Identity;
+
+impl SampleTransform for Identity {
+    #[inline(always)]
+    fn transform(&mut self, sample: f32) -> f32 {
+        sample
+    }
+}
+
+/// Two transforms applied after each other, see [`SampleTransform::chain`]. The delays of the
+/// transforms add up.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> SampleTransform for Chain<A, B>
+where
+    A: SampleTransform,
+    B: SampleTransform,
+{
+    #[inline(always)]
+    fn transform(&mut self, sample: f32) -> f32 {
+        self.second.transform(self.first.transform(sample))
+    }
+
+    fn clear(&mut self) {
+        self.first.clear();
+        self.second.clear();
+    }
+
+    fn group_delay(&self) -> f32 {
+        self.first.group_delay() + self.second.group_delay()
+    }
+
+    fn group_delay_at(&self, frequency: f32) -> f32 {
+        self.first.group_delay_at(frequency) + self.second.group_delay_at(frequency)
+    }
+}
+
+impl<F> SampleTransform for F
+where
+    F: FnMut(f32) -> f32,
+{
+    #[inline(always)]
+    fn transform(&mut self, sample: f32) -> f32 {
+        self(sample)
+    }
+}
+
+/// A one-pole DC blocking filter: `y[n] = x[n] - x[n-1] + a * y[n-1]`.
+///
+/// This is the cheapest way to remove a large DC offset, e.g. from raw ADC data. The filter
+/// starts from the first sample, so the offset does not cause a step at the start of the signal.
+/// The detector can apply it to the raw samples, see
+/// [`Config::with_dc_blocker`](crate::config::Config::with_dc_blocker).
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::transform::{DcBlocker, SampleTransform};
+///
+/// let mut dc_blocker = DcBlocker::new(500.sps(), 0.5);
+///
+/// assert_eq!(0.0, dc_blocker.transform(2048.0));
+/// assert_eq!(0.0, dc_blocker.transform(2048.0));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DcBlocker {
+    a: f32,
+    /// The previous input and output sample.
+    prev: Option<(f32, f32)>,
+}
+
+impl DcBlocker {
+    /// Creates a new DC blocker for signals sampled with `fs`, with a -3 dB cutoff frequency of
+    /// about `cutoff` Hz.
+    pub fn new(fs: SamplingFrequency, cutoff: f32) -> Self {
+        let a = 1.0 - 2.0 * core::f32::consts::PI * cutoff / fs.raw();
+        Self {
+            a: a.clamp(0.0, 1.0),
+            prev: None,
+        }
+    }
+}
+
+impl SampleTransform for DcBlocker {
+    fn transform(&mut self, sample: f32) -> f32 {
+        let output = match self.prev {
+            Some((input, output)) => sample - input + self.a * output,
+            None => 0.0,
+        };
+        self.prev = Some((sample, output));
+        output
+    }
+
+    fn clear(&mut self) {
+        self.prev = None;
+    }
+
+    fn group_delay_at(&self, frequency: f32) -> f32 {
+        let zeros = polynomial_group_delay([1.0, -1.0, 0.0], frequency);
+        let poles = polynomial_group_delay([1.0, -self.a, 0.0], frequency);
+        match zeros.zip(poles) {
+            Some((zeros, poles)) => zeros - poles,
+            None => 0.0,
+        }
+    }
+}
+
+/// How [`Difference`] rectifies the differences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rectification {
+    /// The absolute value of the difference.
+    Abs,
+    /// The square of the difference. Emphasizes the steepest slopes, the output is in squared
+    /// units.
+    Square,
+}
+
+/// The largest supported lag of [`Difference`], in samples.
+pub const MAX_DIFFERENCE_LAG: usize = 4;
+
+/// The lag selected by [`Difference::lag_for`], in milliseconds.
+const DIFFERENCE_LAG_MS: f32 = 4.0;
+
+/// Calculates the rectified difference of samples `lag` samples apart, e.g. `|x[n] - x[n-2]|`.
+///
+/// The differentiated signal emphasizes the steep slopes of the QRS complexes. The detector can
+/// apply it after the sample transform, see
+/// [`Config::with_input_mode`](crate::config::Config::with_input_mode). The output is 0 until
+/// `lag` samples have been received.
+///
+/// The lag sets the frequency response: the difference has its largest gain at `fs / (2 * lag)`.
+/// A longer lag follows wider QRS complexes and suppresses high frequency noise, a shorter lag
+/// keeps the response on narrow QRS complexes at low sampling frequencies. The default lag of 2
+/// samples is a good fit at about 500 sps, [`Difference::lag_for`] scales it to other sampling
+/// frequencies.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::transform::{Difference, Rectification, SampleTransform};
+///
+/// let mut difference = Difference::new(Rectification::Abs);
+///
+/// let output = [1.0, 2.0, 4.0, 3.0].map(|sample| difference.transform(sample));
+/// assert_eq!([0.0, 0.0, 3.0, 1.0], output);
+///
+/// // A lag of 4 samples at 1000 sps
+/// let difference = Difference::new(Rectification::Abs).with_lag(Difference::lag_for(1000.sps()));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Difference {
+    rectification: Rectification,
+    lag: usize,
+    /// The last `lag` samples, `history[idx]` is the oldest one.
+    history: [f32; MAX_DIFFERENCE_LAG],
+    idx: usize,
+    /// The number of samples received, up to `lag`.
+    len: usize,
+}
+
+impl Difference {
+    /// Creates a new differentiator with a lag of 2 samples.
+    pub const fn new(rectification: Rectification) -> Self {
+        Self {
+            rectification,
+            lag: 2,
+            history: [0.0; MAX_DIFFERENCE_LAG],
+            idx: 0,
+            len: 0,
+        }
+    }
+
+    /// Sets the lag of the difference, in samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lag` is not between 1 and [`MAX_DIFFERENCE_LAG`].
+    pub const fn with_lag(mut self, lag: usize) -> Self {
+        assert!(
+            lag >= 1 && lag <= MAX_DIFFERENCE_LAG,
+            "The lag must be between 1 and 4 samples"
+        );
+        self.lag = lag;
+        self.clear_history();
+        self
+    }
+
+    /// Returns the lag of the difference, in samples.
+    pub const fn lag(&self) -> usize {
+        self.lag
+    }
+
+    /// Returns the lag for signals sampled with `fs`: 4 ms, at least 1 and at most
+    /// [`MAX_DIFFERENCE_LAG`] samples. This is 1 sample at 250 and 360 sps, 2 samples at 500 and
+    /// 720 sps, and 4 samples at 1000 sps.
+    pub fn lag_for(fs: SamplingFrequency) -> usize {
+        fs.ms_to_samples(DIFFERENCE_LAG_MS)
+            .clamp(1, MAX_DIFFERENCE_LAG)
+    }
+
+    const fn clear_history(&mut self) {
+        self.idx = 0;
+        self.len = 0;
+    }
+}
+
+impl SampleTransform for Difference {
+    fn transform(&mut self, sample: f32) -> f32 {
+        let oldest = self.history[self.idx];
+        self.history[self.idx] = sample;
+        self.idx = (self.idx + 1) % self.lag;
+
+        if self.len < self.lag {
+            self.len += 1;
+            return 0.0;
+        }
+
+        let difference = sample - oldest;
+        match self.rectification {
+            Rectification::Abs => difference.abs(),
+            Rectification::Square => difference * difference,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.clear_history();
+    }
+
+    /// The difference is centered between the two samples.
+    fn group_delay(&self) -> f32 {
+        self.lag as f32 / 2.0
+    }
+}
+
+/// The number of blocks [`ZScore`] splits its window into.
+const Z_SCORE_BLOCKS: usize = 16;
+
+/// Normalizes the samples to their z-score, `(x - mean) / std`, with the mean and standard
+/// deviation calculated over a sliding window.
+///
+/// Normalized signals of patients with widely different QRS amplitudes can be processed with the
+/// same thresholds. The detector can apply it to the preprocessed signal, see
+/// [`Config::with_z_score`](crate::config::Config::with_z_score).
+///
+/// The window is split into 16 blocks, and the statistics are updated when a block is complete,
+/// so that the memory use does not depend on the length of the window. The output is 0 until
+/// the first block is complete, see [`ZScore::is_ready`].
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::transform::{SampleTransform, ZScore};
+///
+/// // A window of 16 seconds, updated every second
+/// let mut z_score = ZScore::new(500.sps(), 16.0);
+///
+/// for i in 0..500 {
+///     z_score.transform(if i % 2 == 0 { 1000.0 } else { 3000.0 });
+/// }
+/// assert!(z_score.is_ready());
+/// assert_eq!(1.0, z_score.transform(3000.0));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ZScore {
+    block_len: u32,
+    /// The number of samples and the sums of the samples and their squares, in the current block.
+    current: (u32, f64, f64),
+    /// The sums of the samples and their squares, in the completed blocks.
+    blocks: [(f64, f64); Z_SCORE_BLOCKS],
+    completed: usize,
+    mean: f32,
+    /// The reciprocal of the standard deviation, 0 for a constant signal.
+    inv_std: f32,
+}
+
+impl ZScore {
+    /// Creates a new normalizer for signals sampled with `fs`, over a window of `window_s`
+    /// seconds.
+    pub fn new(fs: SamplingFrequency, window_s: f32) -> Self {
+        let block_len = fs.s_to_samples(window_s / Z_SCORE_BLOCKS as f32).max(1);
+        Self {
+            block_len: block_len as u32,
+            current: (0, 0.0, 0.0),
+            blocks: [(0.0, 0.0); Z_SCORE_BLOCKS],
+            completed: 0,
+            mean: 0.0,
+            inv_std: 0.0,
+        }
+    }
+
+    /// Returns whether the statistics have been calculated from at least one block of samples.
+    pub fn is_ready(&self) -> bool {
+        self.completed > 0
+    }
+
+    /// Returns the mean of the window, if ready.
+    pub fn mean(&self) -> Option<f32> {
+        self.is_ready().then_some(self.mean)
+    }
+
+    /// Returns the standard deviation of the window, if ready.
+    pub fn std(&self) -> Option<f32> {
+        self.is_ready().then(|| {
+            if self.inv_std > 0.0 {
+                1.0 / self.inv_std
+            } else {
+                0.0
+            }
+        })
+    }
+
+    fn complete_block(&mut self) {
+        let (_, sum, sum_sq) = core::mem::replace(&mut self.current, (0, 0.0, 0.0));
+        self.blocks[self.completed % Z_SCORE_BLOCKS] = (sum, sum_sq);
+        self.completed += 1;
+
+        let blocks = self.completed.min(Z_SCORE_BLOCKS);
+        let (sum, sum_sq) = self.blocks[..blocks]
+            .iter()
+            .fold((0.0, 0.0), |(sum, sum_sq), (s, s2)| (sum + s, sum_sq + s2));
+        let n = (blocks as u32 * self.block_len) as f64;
+        let mean = sum / n;
+        let variance = (sum_sq / n - mean * mean).max(0.0) as f32;
+        let std = crate::math::sqrt(variance);
+
+        self.mean = mean as f32;
+        // Rounding errors of a constant signal are not amplified
+        self.inv_std = if std > 0.0 && std > 1e-6 * self.mean.abs() {
+            1.0 / std
+        } else {
+            0.0
+        };
+    }
+}
+
+impl SampleTransform for ZScore {
+    fn transform(&mut self, sample: f32) -> f32 {
+        let output = (sample - self.mean) * self.inv_std;
+
+        let x = sample as f64;
+        self.current.0 += 1;
+        self.current.1 += x;
+        self.current.2 += x * x;
+        if self.current.0 == self.block_len {
+            self.complete_block();
+        }
+
+        output
+    }
+
+    fn clear(&mut self) {
+        let block_len = self.block_len;
+        *self = Self {
+            block_len,
+            current: (0, 0.0, 0.0),
+            blocks: [(0.0, 0.0); Z_SCORE_BLOCKS],
+            completed: 0,
+            mean: 0.0,
+            inv_std: 0.0,
+        };
+    }
+}
@@ -0,0 +1,280 @@
+//! Tuning of the detector parameters against annotated records.
+//!
+//! [`Grid`] lists the values to try for each parameter, and [`Tuner`] evaluates every
+//! combination on a set of [`Record`]s using the [`validation`](crate::validation) metrics. The
+//! best configuration is the one with the fewest false and missed detections.
+//!
+//! The candidates are evaluated in parallel, on every available core.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//! use qrs_detector::tuning::{Grid, Record, Tuner};
+//!
+//! # let samples = [0.0; 10];
+//! # let reference: [u32; 0] = [];
+//! let records = [Record::new(500.sps(), &samples, &reference)];
+//!
+//! let grid = Grid::new(Config::new())
+//!     .with_mm_depths(&[3, 5, 8])
+//!     .with_r_floors(&[0.3, 0.5, 0.7]);
+//!
+//! let best = Tuner::new(&records).tune(&grid);
+//! println!("{}", best.config.describe());
+//! ```
+
+use std::{num::NonZeroUsize, thread, vec::Vec};
+
+use crate::{
+    config::{Config, MDecay},
+    sampling::SamplingFrequency,
+    validation::{self, Metrics},
+    QrsDetector,
+};
+
+/// An annotated record.
+#[derive(Clone, Copy, Debug)]
+pub struct Record<'a> {
+    /// The sampling frequency of the record.
+    pub fs: SamplingFrequency,
+    /// The samples, as fed to the detector. Preprocessing, if any, must already be applied.
+    pub samples: &'a [f32],
+    /// The sample indices of the reference beats, in increasing order.
+    pub reference: &'a [u32],
+}
+
+impl<'a> Record<'a> {
+    /// Creates a new record.
+    pub const fn new(fs: SamplingFrequency, samples: &'a [f32], reference: &'a [u32]) -> Self {
+        Self {
+            fs,
+            samples,
+            reference,
+        }
+    }
+}
+
+/// The values to try for each parameter.
+///
+/// Parameters without values keep the value of the base configuration. The values are checked
+/// when the configurations are generated, see [`Grid::configs`].
+#[derive(Clone, Debug)]
+pub struct Grid {
+    base: Config,
+    mm_depths: Vec<usize>,
+    m_decays: Vec<MDecay>,
+    r_weights: Vec<f32>,
+    r_delays: Vec<f32>,
+    r_timeouts: Vec<f32>,
+    r_floors: Vec<f32>,
+    rr_depths: Vec<usize>,
+}
+
+impl Grid {
+    /// Creates a grid that only contains `base`.
+    pub fn new(base: Config) -> Self {
+        Self {
+            base,
+            mm_depths: Vec::new(),
+            m_decays: Vec::new(),
+            r_weights: Vec::new(),
+            r_delays: Vec::new(),
+            r_timeouts: Vec::new(),
+            r_floors: Vec::new(),
+            rr_depths: Vec::new(),
+        }
+    }
+
+    /// Sets the values to try for [`Config::with_mm_depth`].
+    pub fn with_mm_depths(mut self, values: &[usize]) -> Self {
+        self.mm_depths = values.to_vec();
+        self
+    }
+
+    /// Sets the values to try for [`Config::with_m_decay`].
+    pub fn with_m_decays(mut self, values: &[MDecay]) -> Self {
+        self.m_decays = values.to_vec();
+        self
+    }
+
+    /// Sets the values to try for [`Config::with_r_weight`].
+    pub fn with_r_weights(mut self, values: &[f32]) -> Self {
+        self.r_weights = values.to_vec();
+        self
+    }
+
+    /// Sets the values to try for [`Config::with_r_delay`].
+    pub fn with_r_delays(mut self, values: &[f32]) -> Self {
+        self.r_delays = values.to_vec();
+        self
+    }
+
+    /// Sets the values to try for [`Config::with_r_timeout`].
+    pub fn with_r_timeouts(mut self, values: &[f32]) -> Self {
+        self.r_timeouts = values.to_vec();
+        self
+    }
+
+    /// Sets the values to try for [`Config::with_r_floor`].
+    pub fn with_r_floors(mut self, values: &[f32]) -> Self {
+        self.r_floors = values.to_vec();
+        self
+    }
+
+    /// Sets the values to try for [`Config::with_rr_depth`].
+    pub fn with_rr_depths(mut self, values: &[usize]) -> Self {
+        self.rr_depths = values.to_vec();
+        self
+    }
+
+    /// Returns the number of configurations in the grid.
+    pub fn size(&self) -> usize {
+        [
+            self.mm_depths.len(),
+            self.m_decays.len(),
+            self.r_weights.len(),
+            self.r_delays.len(),
+            self.r_timeouts.len(),
+            self.r_floors.len(),
+            self.rr_depths.len(),
+        ]
+        .iter()
+        .map(|&len| len.max(1))
+        .product()
+    }
+
+    /// Returns every configuration in the grid. The last parameter varies fastest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value is rejected by the corresponding [`Config`] method.
+    pub fn configs(&self) -> impl Iterator<Item = Config> + '_ {
+        (0..self.size()).map(|mut index| {
+            // Decompose the index into a mixed radix number, one digit per parameter
+            fn pick<T: Copy>(values: &[T], index: &mut usize) -> Option<T> {
+                let len = values.len().max(1);
+                let digit = *index % len;
+                *index /= len;
+                values.get(digit).copied()
+            }
+
+            let mut config = self.base;
+            if let Some(value) = pick(&self.rr_depths, &mut index) {
+                config = config.with_rr_depth(value);
+            }
+            if let Some(value) = pick(&self.r_floors, &mut index) {
+                config = config.with_r_floor(value);
+            }
+            if let Some(value) = pick(&self.r_timeouts, &mut index) {
+                config = config.with_r_timeout(value);
+            }
+            if let Some(value) = pick(&self.r_delays, &mut index) {
+                config = config.with_r_delay(value);
+            }
+            if let Some(value) = pick(&self.r_weights, &mut index) {
+                config = config.with_r_weight(value);
+            }
+            if let Some(value) = pick(&self.m_decays, &mut index) {
+                config = config.with_m_decay(value);
+            }
+            if let Some(value) = pick(&self.mm_depths, &mut index) {
+                config = config.with_mm_depth(value);
+            }
+            config
+        })
+    }
+}
+
+/// An evaluated configuration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candidate {
+    /// The configuration.
+    pub config: Config,
+    /// The metrics, summed over every record.
+    pub metrics: Metrics,
+}
+
+impl Candidate {
+    /// Returns the number of false and missed detections.
+    pub fn errors(&self) -> u32 {
+        self.metrics.false_positives + self.metrics.false_negatives
+    }
+}
+
+/// Evaluates configurations on a set of annotated records.
+pub struct Tuner<'a> {
+    records: &'a [Record<'a>],
+    threads: usize,
+}
+
+impl<'a> Tuner<'a> {
+    /// Creates a new tuner, using every available core.
+    pub fn new(records: &'a [Record<'a>]) -> Self {
+        Self {
+            records,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+
+    /// Sets the number of threads used to evaluate the candidates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is 0.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "At least one thread is required");
+        self.threads = threads;
+        self
+    }
+
+    /// Runs the detector with `config` on every record, and returns the summed metrics.
+    pub fn evaluate(&self, config: Config) -> Metrics {
+        self.records
+            .iter()
+            .map(|record| {
+                let mut detector = QrsDetector::new_alloc(record.fs).with_config(config);
+                let detections = record
+                    .samples
+                    .iter()
+                    .filter_map(|&sample| detector.update(sample));
+                validation::evaluate(record.fs, record.reference, detections)
+            })
+            .fold(Metrics::default(), |sum, metrics| sum + metrics)
+    }
+
+    /// Evaluates every configuration of `grid`, in the order of [`Grid::configs`].
+    pub fn sweep(&self, grid: &Grid) -> Vec<Candidate> {
+        let configs = grid.configs().collect::<Vec<_>>();
+        let chunk_size = configs.len().div_ceil(self.threads);
+
+        thread::scope(|scope| {
+            let workers = configs
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|&config| Candidate {
+                                config,
+                                metrics: self.evaluate(config),
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        })
+    }
+
+    /// Returns the configuration of `grid` with the fewest errors. Of equally good
+    /// configurations, the first one is returned.
+    pub fn tune(&self, grid: &Grid) -> Candidate {
+        self.sweep(grid)
+            .into_iter()
+            .min_by_key(Candidate::errors)
+            .unwrap()
+    }
+}
@@ -0,0 +1,216 @@
+//! `ufmt` support, for targets where `core::fmt` is too large.
+//!
+//! The output of [`uDisplay`] matches the [`core::fmt::Display`] implementations with the default
+//! precision, except that the last digit of values above one million may be rounded differently.
+
+use ufmt::{uDebug, uDisplay, uWrite, uwrite, Formatter};
+
+#[cfg(feature = "hrv")]
+use crate::summary::PeriodSummary;
+use crate::{Detection, SuppressedCounts, Thresholds};
+
+/// Formats an `f32` with 3 decimals, as `ufmt` does not support floating point numbers. Large
+/// values are formatted in scientific notation.
+struct Float(f32);
+
+impl Float {
+    /// Returns `value * 1000`, rounded half to even like `core::fmt`. `value` must be a
+    /// non-negative finite number below one million.
+    ///
+    /// The result is computed exactly from the mantissa and the exponent of `value`, using only
+    /// `u32` arithmetic.
+    fn thousandths(value: f32) -> u32 {
+        let bits = value.to_bits();
+        let biased_exponent = (bits >> 23) as i32;
+        let (mantissa, exponent) = if biased_exponent == 0 {
+            // Subnormal numbers
+            (bits & 0x7F_FFFF, -149)
+        } else {
+            ((bits & 0x7F_FFFF) | 0x80_0000, biased_exponent - 150)
+        };
+
+        // value * 1000 = mantissa * 125 * 2^(exponent + 3), and mantissa * 125 < 2^31
+        let scaled = mantissa * 125;
+        let shift = exponent + 3;
+        if shift >= 0 {
+            return scaled << shift;
+        }
+        if shift <= -32 {
+            // Less than half
+            return 0;
+        }
+
+        let shift = -shift as u32;
+        let truncated = scaled >> shift;
+        let remainder = scaled - (truncated << shift);
+        let half = 1 << (shift - 1);
+
+        if remainder > half || (remainder == half && truncated % 2 == 1) {
+            truncated + 1
+        } else {
+            truncated
+        }
+    }
+
+    /// Writes `thousandths` as a decimal number with 3 decimals.
+    fn write_fixed<W: uWrite + ?Sized>(
+        f: &mut Formatter<'_, W>,
+        thousandths: u32,
+    ) -> Result<(), W::Error> {
+        let fraction = thousandths % 1000;
+        uwrite!(f, "{}.", thousandths / 1000)?;
+        if fraction < 100 {
+            f.write_char('0')?;
+        }
+        if fraction < 10 {
+            f.write_char('0')?;
+        }
+        uwrite!(f, "{}", fraction)
+    }
+}
+
+impl uDisplay for Float {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        let value = self.0;
+        if value.is_nan() {
+            return f.write_str("NaN");
+        }
+        if value.is_sign_negative() {
+            f.write_char('-')?;
+        }
+
+        let value = value.abs();
+        if value.is_infinite() {
+            f.write_str("inf")
+        } else if value < 1e6 {
+            Self::write_fixed(f, Self::thousandths(value))
+        } else {
+            let mut exponent = 0;
+            let mut power = 1.0;
+            while value >= power * 10.0 {
+                power *= 10.0;
+                exponent += 1;
+            }
+
+            let mut thousandths = Self::thousandths(value / power);
+            if thousandths >= 10_000 {
+                // Rounding carried into the next digit
+                thousandths /= 10;
+                exponent += 1;
+            }
+
+            Self::write_fixed(f, thousandths)?;
+            uwrite!(f, "e{}", exponent)
+        }
+    }
+}
+
+impl uDebug for Float {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        uDisplay::fmt(self, f)
+    }
+}
+
+impl uDisplay for Detection {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        uwrite!(
+            f,
+            "#{} amp={} thr={}",
+            self.index,
+            Float(self.amplitude),
+            Float(self.threshold)
+        )?;
+        if self.paced {
+            uwrite!(f, " paced")?;
+        }
+        Ok(())
+    }
+}
+
+impl uDebug for Detection {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        f.debug_struct("Detection")?
+            .field("index", &self.index)?
+            .field("amplitude", &Float(self.amplitude))?
+            .field("threshold", &Float(self.threshold))?
+            .field("paced", &self.paced)?
+            .finish()
+    }
+}
+
+impl uDisplay for SuppressedCounts {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        uwrite!(
+            f,
+            "refractory={} step={} noise={}",
+            self.refractory,
+            self.signal_step,
+            self.noise
+        )
+    }
+}
+
+impl uDebug for SuppressedCounts {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        f.debug_struct("SuppressedCounts")?
+            .field("refractory", &self.refractory)?
+            .field("signal_step", &self.signal_step)?
+            .field("noise", &self.noise)?
+            .finish()
+    }
+}
+
+impl uDisplay for Thresholds {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        for (name, value) in [("M=", self.m), (" F=", self.f), (" R=", Some(self.r))] {
+            f.write_str(name)?;
+            match value {
+                Some(value) => uwrite!(f, "{}", Float(value))?,
+                None => f.write_char('-')?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl uDebug for Thresholds {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        f.debug_struct("Thresholds")?
+            .field("m", &self.m.map(Float))?
+            .field("f", &self.f.map(Float))?
+            .field("r", &Float(self.r))?
+            .finish()
+    }
+}
+
+#[cfg(feature = "hrv")]
+impl uDisplay for PeriodSummary {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        uwrite!(
+            f,
+            "beats={} hr={}/{}/{} pauses={} af={}% noise={}%",
+            self.beats,
+            self.min_bpm,
+            self.avg_bpm,
+            self.max_bpm,
+            self.pauses,
+            self.af_burden_percent,
+            self.noise_percent
+        )
+    }
+}
+
+#[cfg(feature = "hrv")]
+impl uDebug for PeriodSummary {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        f.debug_struct("PeriodSummary")?
+            .field("beats", &self.beats)?
+            .field("min_bpm", &self.min_bpm)?
+            .field("avg_bpm", &self.avg_bpm)?
+            .field("max_bpm", &self.max_bpm)?
+            .field("pauses", &self.pauses)?
+            .field("af_burden_percent", &self.af_burden_percent)?
+            .field("noise_percent", &self.noise_percent)?
+            .finish()
+    }
+}
@@ -0,0 +1,736 @@
+//! Evaluation of the detector against annotated records.
+//!
+//! Detections are compared to reference beat annotations following the ANSI/AAMI EC57
+//! methodology: a detection matches a reference beat if it is within 150 ms of it. Every
+//! reference beat can be matched once, so multiple detections of the same beat count as false
+//! positives.
+//!
+//! The results may be broken down by the EC57 class of the reference beats, see [`BeatClass`] and
+//! [`evaluate_by_class`].
+//!
+//! [`NoiseStress`] reproduces the noise stress test of the MIT-BIH Noise Stress Test Database:
+//! a noise record is mixed into a clean record at calibrated signal-to-noise ratios, and the
+//! detection metrics are reported for each of them.
+
+use core::ops::{Add, AddAssign};
+
+use crate::{annotation::BeatClass, sampling::SamplingFrequency};
+
+/// Detections within this period of a reference beat match it.
+pub const MATCH_WINDOW_MS: f32 = 150.0;
+
+/// The peak-to-peak amplitude of a QRS complex is measured within this period of its annotation.
+const QRS_WINDOW_MS: f32 = 50.0;
+
+/// Beat-by-beat comparison results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    /// Detections that match a reference beat.
+    pub true_positives: u32,
+    /// Detections that do not match a reference beat.
+    pub false_positives: u32,
+    /// Reference beats without a matching detection.
+    pub false_negatives: u32,
+}
+
+impl Metrics {
+    /// Returns the fraction of the reference beats that were detected, or 1 if there are no
+    /// reference beats.
+    pub fn sensitivity(&self) -> f32 {
+        Self::ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+
+    /// Returns the fraction of the detections that match a reference beat, or 1 if there are no
+    /// detections.
+    pub fn positive_predictivity(&self) -> f32 {
+        Self::ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    fn ratio(count: u32, total: u32) -> f32 {
+        if total == 0 {
+            1.0
+        } else {
+            count as f32 / total as f32
+        }
+    }
+}
+
+impl Add for Metrics {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            true_positives: self.true_positives + rhs.true_positives,
+            false_positives: self.false_positives + rhs.false_positives,
+            false_negatives: self.false_negatives + rhs.false_negatives,
+        }
+    }
+}
+
+impl AddAssign for Metrics {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// The number of detected beats of a class, see [`ClassBreakdown`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BeatCounts {
+    /// The number of detected reference beats.
+    pub detected: u32,
+    /// The number of reference beats.
+    pub total: u32,
+}
+
+impl BeatCounts {
+    /// Returns the fraction of the reference beats that were detected, or 1 if there are no
+    /// reference beats.
+    pub fn sensitivity(&self) -> f32 {
+        Metrics::ratio(self.detected, self.total)
+    }
+
+    fn add(&mut self, detected: bool) {
+        self.total += 1;
+        if detected {
+            self.detected += 1;
+        }
+    }
+}
+
+impl Add for BeatCounts {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            detected: self.detected + rhs.detected,
+            total: self.total + rhs.total,
+        }
+    }
+}
+
+/// Detected beats by EC57 beat class, to assess whether changes affect some classes of beats more
+/// than others. Paced beats are counted separately, not as unknown beats.
+///
+/// False positives can not be attributed to a beat class, so only the sensitivity is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassBreakdown {
+    /// Normal beats.
+    pub normal: BeatCounts,
+    /// Supraventricular ectopic beats.
+    pub supraventricular: BeatCounts,
+    /// Ventricular ectopic beats.
+    pub ventricular: BeatCounts,
+    /// Fusion beats.
+    pub fusion: BeatCounts,
+    /// Unknown beats.
+    pub unknown: BeatCounts,
+    /// Paced beats.
+    pub paced: BeatCounts,
+}
+
+impl ClassBreakdown {
+    /// Returns the counts of the beats of `class`.
+    pub fn get(&self, class: BeatClass) -> BeatCounts {
+        match class {
+            BeatClass::Normal => self.normal,
+            BeatClass::Supraventricular => self.supraventricular,
+            BeatClass::Ventricular => self.ventricular,
+            BeatClass::Fusion => self.fusion,
+            BeatClass::Unknown => self.unknown,
+            BeatClass::Paced => self.paced,
+        }
+    }
+
+    fn get_mut(&mut self, class: BeatClass) -> &mut BeatCounts {
+        match class {
+            BeatClass::Normal => &mut self.normal,
+            BeatClass::Supraventricular => &mut self.supraventricular,
+            BeatClass::Ventricular => &mut self.ventricular,
+            BeatClass::Fusion => &mut self.fusion,
+            BeatClass::Unknown => &mut self.unknown,
+            BeatClass::Paced => &mut self.paced,
+        }
+    }
+}
+
+impl Add for ClassBreakdown {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            normal: self.normal + rhs.normal,
+            supraventricular: self.supraventricular + rhs.supraventricular,
+            ventricular: self.ventricular + rhs.ventricular,
+            fusion: self.fusion + rhs.fusion,
+            unknown: self.unknown + rhs.unknown,
+            paced: self.paced + rhs.paced,
+        }
+    }
+}
+
+impl AddAssign for ClassBreakdown {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// Agreement of the detected RR intervals with the reference RR intervals, using Bland-Altman
+/// statistics.
+///
+/// An RR interval is compared if both of its beats are detected, and there is no false
+/// detection between them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RrAgreement {
+    ms_per_sample: f64,
+    count: u32,
+    /// The sum of the differences, in samples.
+    sum: f64,
+    /// The sum of the squared differences, in samples².
+    sum_sq: f64,
+}
+
+impl RrAgreement {
+    /// Creates an empty set of statistics for records sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            ms_per_sample: 1000.0 / fs.raw() as f64,
+            count: 0,
+            sum: 0.0,
+            sum_sq: 0.0,
+        }
+    }
+
+    /// Adds an RR interval pair, in samples.
+    pub fn push(&mut self, detected: u32, reference: u32) {
+        let difference = detected as f64 - reference as f64;
+        self.count += 1;
+        self.sum += difference;
+        self.sum_sq += difference * difference;
+    }
+
+    /// Returns the number of compared RR intervals.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the mean difference of the detected and the reference RR intervals, in
+    /// milliseconds.
+    pub fn bias_ms(&self) -> f32 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.samples_to_ms(self.sum / self.count as f64)
+    }
+
+    /// Returns the standard deviation of the differences, in milliseconds.
+    pub fn standard_deviation_ms(&self) -> f32 {
+        if self.count < 2 {
+            return 0.0;
+        }
+
+        let n = self.count as f64;
+        let variance = (self.sum_sq - self.sum * self.sum / n) / (n - 1.0);
+        self.samples_to_ms(crate::math::sqrt(variance.max(0.0) as f32) as f64)
+    }
+
+    /// Returns the 95% limits of agreement, `bias ± 1.96 SD`, in milliseconds.
+    pub fn limits_of_agreement_ms(&self) -> (f32, f32) {
+        let bias = self.bias_ms();
+        let spread = 1.96 * self.standard_deviation_ms();
+        (bias - spread, bias + spread)
+    }
+
+    fn samples_to_ms(&self, samples: f64) -> f32 {
+        (samples * self.ms_per_sample) as f32
+    }
+}
+
+impl Add for RrAgreement {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            ms_per_sample: self.ms_per_sample,
+            count: self.count + rhs.count,
+            sum: self.sum + rhs.sum,
+            sum_sq: self.sum_sq + rhs.sum_sq,
+        }
+    }
+}
+
+/// The number of 1 ms bins of [`LatencyStats`], covering the match window.
+const LATENCY_BINS: usize = 2 * MATCH_WINDOW_MS as usize + 1;
+
+/// Distribution of the detection latency, the position of the detections relative to the
+/// matched reference beats.
+///
+/// Negative latencies mean that the beat is detected before its annotation. Latencies are
+/// recorded in a histogram with 1 ms resolution, so percentiles are rounded to whole
+/// milliseconds. The mean latency can be used as
+/// [`Config::with_latency_compensation`](crate::config::Config::with_latency_compensation).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatencyStats {
+    ms_per_sample: f64,
+    count: u32,
+    /// The sum of the latencies, in samples.
+    sum: f64,
+    histogram: [u32; LATENCY_BINS],
+}
+
+impl LatencyStats {
+    /// Creates an empty set of statistics for records sampled with `fs`.
+    pub fn new(fs: SamplingFrequency) -> Self {
+        Self {
+            ms_per_sample: 1000.0 / fs.raw() as f64,
+            count: 0,
+            sum: 0.0,
+            histogram: [0; LATENCY_BINS],
+        }
+    }
+
+    /// Adds the latency of a detection matching a reference beat, both in samples.
+    pub fn push(&mut self, detected: u32, reference: u32) {
+        let latency = detected as f64 - reference as f64;
+        self.count += 1;
+        self.sum += latency;
+
+        let ms = latency * self.ms_per_sample + MATCH_WINDOW_MS as f64;
+        let bin = (ms + 0.5).clamp(0.0, (LATENCY_BINS - 1) as f64) as usize;
+        self.histogram[bin] += 1;
+    }
+
+    /// Returns the number of matched detections.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the mean latency, in milliseconds.
+    pub fn mean_ms(&self) -> f32 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        (self.sum / self.count as f64 * self.ms_per_sample) as f32
+    }
+
+    /// Returns the latency, in milliseconds, that `fraction` of the detections do not exceed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fraction` is not in the `0..=1` range.
+    pub fn percentile_ms(&self, fraction: f32) -> f32 {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "The fraction must be between 0 and 1"
+        );
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        // Nearest-rank method
+        let rank = (micromath::F32Ext::ceil(fraction * self.count as f32) as u32).max(1);
+        let mut seen = 0;
+        for (bin, &n) in self.histogram.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return bin as f32 - MATCH_WINDOW_MS;
+            }
+        }
+        MATCH_WINDOW_MS
+    }
+
+    /// Returns the 95th percentile of the latency, in milliseconds.
+    pub fn p95_ms(&self) -> f32 {
+        self.percentile_ms(0.95)
+    }
+}
+
+impl Add for LatencyStats {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self.count += rhs.count;
+        self.sum += rhs.sum;
+        for (bin, n) in self.histogram.iter_mut().zip(rhs.histogram) {
+            *bin += n;
+        }
+        self
+    }
+}
+
+/// The result of matching a detection, see [`BeatMatcher::push`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Match {
+    /// The detection matches the reference beat at the given sample index.
+    TruePositive {
+        /// The sample index of the reference beat.
+        reference: u32,
+    },
+    /// The detection does not match a reference beat.
+    FalsePositive,
+}
+
+/// Matches a stream of detections to reference beat annotations.
+///
+/// Both the detections and the reference annotations must be in increasing order.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::validation::BeatMatcher;
+///
+/// let reference = [100, 500, 900];
+/// let mut matcher = BeatMatcher::new(500.sps(), &reference);
+///
+/// for detection in [110, 490, 1500] {
+///     matcher.push(detection);
+/// }
+///
+/// let metrics = matcher.finish();
+/// assert_eq!(2, metrics.true_positives);
+/// assert_eq!(1, metrics.false_positives);
+/// assert_eq!(1, metrics.false_negatives);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BeatMatcher<'a> {
+    reference: &'a [u32],
+    classes: &'a [BeatClass],
+    next: usize,
+    tolerance: u32,
+    metrics: Metrics,
+    breakdown: ClassBreakdown,
+    /// The last matched detection and reference beat, and the position of the reference beat.
+    last_match: Option<(u32, u32, usize)>,
+    rr: RrAgreement,
+    latency: LatencyStats,
+}
+
+impl<'a> BeatMatcher<'a> {
+    /// Creates a new matcher for `reference` beats, annotated in a record sampled with `fs`.
+    pub fn new(fs: SamplingFrequency, reference: &'a [u32]) -> Self {
+        Self {
+            reference,
+            classes: &[],
+            next: 0,
+            tolerance: fs.ms_to_samples(MATCH_WINDOW_MS) as u32,
+            metrics: Metrics::default(),
+            breakdown: ClassBreakdown::default(),
+            last_match: None,
+            rr: RrAgreement::new(fs),
+            latency: LatencyStats::new(fs),
+        }
+    }
+
+    /// Sets the classes of the reference beats, to break the results down by beat class. See
+    /// [`BeatMatcher::breakdown`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of classes does not match the number of reference beats.
+    pub fn with_classes(mut self, classes: &'a [BeatClass]) -> Self {
+        assert_eq!(
+            self.reference.len(),
+            classes.len(),
+            "Every reference beat must have a class"
+        );
+        self.classes = classes;
+        self
+    }
+
+    fn count_reference(&mut self, detected: bool) {
+        if let Some(&class) = self.classes.get(self.next) {
+            self.breakdown.get_mut(class).add(detected);
+        }
+        self.next += 1;
+    }
+
+    /// Matches `detection` to the reference beats.
+    pub fn push(&mut self, detection: u32) -> Match {
+        // Reference beats too early to match this or any later detection are missed
+        while let Some(&reference) = self.reference.get(self.next) {
+            if reference.saturating_add(self.tolerance) >= detection {
+                break;
+            }
+            self.metrics.false_negatives += 1;
+            self.count_reference(false);
+        }
+
+        match self.reference.get(self.next) {
+            Some(&reference) if reference.abs_diff(detection) <= self.tolerance => {
+                if let Some((last_detection, last_reference, position)) = self.last_match {
+                    if position + 1 == self.next {
+                        self.rr
+                            .push(detection - last_detection, reference - last_reference);
+                    }
+                }
+                self.last_match = Some((detection, reference, self.next));
+                self.latency.push(detection, reference);
+
+                self.metrics.true_positives += 1;
+                self.count_reference(true);
+                Match::TruePositive { reference }
+            }
+            _ => {
+                self.last_match = None;
+                self.metrics.false_positives += 1;
+                Match::FalsePositive
+            }
+        }
+    }
+
+    /// Returns the metrics of the detections processed so far. Reference beats that may still
+    /// be matched by a later detection are not counted.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Returns the detected beats by beat class, processed so far. Empty unless the classes of
+    /// the reference beats are set using [`BeatMatcher::with_classes`].
+    pub fn breakdown(&self) -> ClassBreakdown {
+        self.breakdown
+    }
+
+    /// Returns the agreement of the detected and the reference RR intervals, processed so far.
+    pub fn rr_agreement(&self) -> RrAgreement {
+        self.rr
+    }
+
+    /// Returns the latency of the detections matched so far.
+    pub fn latency(&self) -> &LatencyStats {
+        &self.latency
+    }
+
+    /// Returns the final metrics. Unmatched reference beats are counted as missed.
+    pub fn finish(self) -> Metrics {
+        self.finish_by_class().0
+    }
+
+    /// Returns the final metrics, and the detected beats by beat class. Unmatched reference
+    /// beats are counted as missed.
+    pub fn finish_by_class(mut self) -> (Metrics, ClassBreakdown) {
+        while self.next < self.reference.len() {
+            self.metrics.false_negatives += 1;
+            self.count_reference(false);
+        }
+        (self.metrics, self.breakdown)
+    }
+}
+
+/// Compares `detections` to `reference` beats, annotated in a record sampled with `fs`.
+pub fn evaluate(
+    fs: SamplingFrequency,
+    reference: &[u32],
+    detections: impl IntoIterator<Item = u32>,
+) -> Metrics {
+    let mut matcher = BeatMatcher::new(fs, reference);
+    for detection in detections {
+        matcher.push(detection);
+    }
+    matcher.finish()
+}
+
+/// Compares `detections` to `reference` beats of the given `classes`, annotated in a record
+/// sampled with `fs`. See [`ClassBreakdown`].
+pub fn evaluate_by_class(
+    fs: SamplingFrequency,
+    reference: &[u32],
+    classes: &[BeatClass],
+    detections: impl IntoIterator<Item = u32>,
+) -> (Metrics, ClassBreakdown) {
+    let mut matcher = BeatMatcher::new(fs, reference).with_classes(classes);
+    for detection in detections {
+        matcher.push(detection);
+    }
+    matcher.finish_by_class()
+}
+
+/// The detection metrics at a signal-to-noise ratio, see [`NoiseStress::curve`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StressPoint {
+    /// The signal-to-noise ratio, in dB.
+    pub snr_db: f32,
+    /// The detection metrics.
+    pub metrics: Metrics,
+}
+
+/// Evaluates the detector on a clean record with noise added at calibrated signal-to-noise
+/// ratios.
+///
+/// The signal-to-noise ratio is calculated like in the MIT-BIH Noise Stress Test Database. The
+/// signal power is the square of the median peak-to-peak amplitude of the QRS complexes, divided
+/// by 8. The noise power is the variance of the noise record. The noise record is repeated if it
+/// is shorter than the clean record, e.g. a recording of electrode motion or muscle noise.
+///
+/// # Example
+/// ```rust
+/// use qrs_detector::prelude::*;
+/// use qrs_detector::validation::NoiseStress;
+///
+/// # let signal = [0.0; 1000];
+/// # let reference = [];
+/// # let noise = [0.0, 0.1, -0.1];
+/// let stress = NoiseStress::new(500.sps(), &signal, &reference, &noise);
+///
+/// for point in stress.curve(&[24.0, 12.0, 6.0, 0.0], || {
+///     let mut detector = QrsDetector::new::<150, 25>(500.sps());
+///     move |sample| detector.update(sample)
+/// }) {
+///     println!("{} dB: Se={}", point.snr_db, point.metrics.sensitivity());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NoiseStress<'a> {
+    fs: SamplingFrequency,
+    signal: &'a [f32],
+    reference: &'a [u32],
+    noise: &'a [f32],
+    signal_power: f32,
+    noise_mean: f32,
+    noise_power: f32,
+}
+
+impl<'a> NoiseStress<'a> {
+    /// Creates a new noise stress test.
+    ///
+    /// # Arguments
+    /// * `fs` - The sampling frequency of the records.
+    /// * `signal` - The clean record.
+    /// * `reference` - The reference beat annotations of the clean record.
+    /// * `noise` - The noise record.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the noise record is empty.
+    pub fn new(
+        fs: SamplingFrequency,
+        signal: &'a [f32],
+        reference: &'a [u32],
+        noise: &'a [f32],
+    ) -> Self {
+        assert!(!noise.is_empty(), "The noise record must not be empty");
+
+        let noise_mean = noise.iter().sum::<f32>() / noise.len() as f32;
+        let noise_power = noise
+            .iter()
+            .map(|sample| (sample - noise_mean) * (sample - noise_mean))
+            .sum::<f32>()
+            / noise.len() as f32;
+
+        Self {
+            fs,
+            signal,
+            reference,
+            noise,
+            signal_power: qrs_power(fs, signal, reference),
+            noise_mean,
+            noise_power,
+        }
+    }
+
+    /// Returns the power of the QRS complexes of the clean record.
+    pub fn signal_power(&self) -> f32 {
+        self.signal_power
+    }
+
+    /// Returns the power of the noise record.
+    pub fn noise_power(&self) -> f32 {
+        self.noise_power
+    }
+
+    /// Returns the clean record with the noise added at `snr_db` signal-to-noise ratio.
+    pub fn noisy_signal(&self, snr_db: f32) -> impl Iterator<Item = f32> + 'a {
+        let scale = if self.noise_power > 0.0 {
+            crate::math::sqrt(
+                self.signal_power
+                    / (self.noise_power * micromath::F32Ext::powf(10.0, snr_db / 10.0)),
+            )
+        } else {
+            0.0
+        };
+        let mean = self.noise_mean;
+
+        self.signal
+            .iter()
+            .zip(self.noise.iter().cycle())
+            .map(move |(signal, noise)| signal + scale * (noise - mean))
+    }
+
+    /// Runs `detect` on the noisy record and compares the detections to the reference beats.
+    /// `detect` processes a sample and returns the index of the detection, if any, like
+    /// [`QrsDetector::update`](crate::QrsDetector::update).
+    pub fn evaluate(&self, snr_db: f32, mut detect: impl FnMut(f32) -> Option<u32>) -> Metrics {
+        evaluate(
+            self.fs,
+            self.reference,
+            self.noisy_signal(snr_db).filter_map(&mut detect),
+        )
+    }
+
+    /// Evaluates a new detector, created by `detector`, at each of the `snrs`.
+    pub fn curve<'s, D>(
+        &'s self,
+        snrs: &'s [f32],
+        mut detector: impl FnMut() -> D + 's,
+    ) -> impl Iterator<Item = StressPoint> + 's
+    where
+        D: FnMut(f32) -> Option<u32>,
+    {
+        snrs.iter().map(move |&snr_db| StressPoint {
+            snr_db,
+            metrics: self.evaluate(snr_db, detector()),
+        })
+    }
+}
+
+/// Returns the power of the QRS complexes: the square of the median peak-to-peak amplitude,
+/// divided by 8.
+fn qrs_power(fs: SamplingFrequency, signal: &[f32], reference: &[u32]) -> f32 {
+    let window = fs.ms_to_samples(QRS_WINDOW_MS);
+    let amplitude = |index: u32| {
+        let start = (index as usize).saturating_sub(window);
+        let end = (index as usize + window + 1).min(signal.len());
+        signal
+            .get(start..end)
+            .unwrap_or(&[])
+            .iter()
+            .fold(None, |range: Option<(f32, f32)>, &sample| {
+                Some(match range {
+                    Some((min, max)) => (min.min(sample), max.max(sample)),
+                    None => (sample, sample),
+                })
+            })
+            .map_or(0.0, |(min, max)| max - min)
+    };
+
+    if reference.is_empty() {
+        return 0.0;
+    }
+
+    // Find the median by bisection, to avoid storing the amplitudes
+    let (mut low, mut high) = (
+        0.0,
+        reference.iter().map(|&i| amplitude(i)).fold(0.0, f32::max),
+    );
+    for _ in 0..32 {
+        let mid = (low + high) / 2.0;
+        let below = reference.iter().filter(|&&i| amplitude(i) < mid).count();
+        if below * 2 < reference.len() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    let median = (low + high) / 2.0;
+    median * median / 8.0
+}
@@ -0,0 +1,396 @@
+//! Machine-readable test vectors, to verify the detector against other implementations of the
+//! algorithm, e.g. a MATLAB or Python reference.
+//!
+//! A test vector is a CSV file with the input samples and the expected threshold components and
+//! detections of every sample:
+//!
+//! ```text
+//! # qrs_detector test vector 1
+//! # fs=720
+//! sample,m,f,r,detection
+//! 0.0125,,,0,
+//! 0.5,0.25,0.0625,0,
+//! 1.25,0.25,0.0625,0,1
+//! ```
+//!
+//! - The first line identifies the format and its version, [`VECTOR_VERSION`]. Other lines
+//!   starting with `#` are `key=value` metadata, of which `fs`, the sampling frequency in Hz, is
+//!   required. Unknown keys are ignored.
+//! - `sample` is the input of the detector, after any preprocessing.
+//! - `m`, `f` and `r` are the threshold components after processing the sample, see
+//!   [`Thresholds`]. `m` and `f` are empty while the detector initializes.
+//! - `detection` is the reported sample index if the sample completed a detection, and empty
+//!   otherwise.
+//!
+//! [`VectorWriter`] records vectors from a detector, and [`TestVector::verify`] replays a vector
+//! and compares the detector against it, within a [`Tolerance`]. Values are written with the
+//! shortest representation that parses back to the same `f32`.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//! use qrs_detector::vectors::{TestVector, Tolerance, VectorWriter};
+//!
+//! # let samples = [0.0; 10];
+//! let mut writer = VectorWriter::new(Vec::new(), 500.sps())?;
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//! for sample in samples {
+//!     writer.record(&mut detector, sample)?;
+//! }
+//! let csv = String::from_utf8(writer.into_inner()).unwrap();
+//!
+//! // E.g. produced by a reference implementation
+//! let vector = TestVector::parse(&csv).unwrap();
+//! let mut detector = QrsDetector::new::<150, 25>(vector.fs);
+//! assert_eq!(Ok(()), vector.verify(&mut detector, Tolerance::EXACT));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use core::fmt;
+use std::{
+    io::{self, Write},
+    vec::Vec,
+};
+
+use crate::{
+    component::ThresholdComponent,
+    noise::NoiseEstimator,
+    sampling::{SamplingFrequency, SamplingFrequencyExt},
+    transform::SampleTransform,
+    Detection, QrsDetector, Suppressed, Thresholds,
+};
+
+/// The version of the test vector format.
+pub const VECTOR_VERSION: u32 = 1;
+
+const MAGIC: &str = "# qrs_detector test vector ";
+const COLUMNS: &str = "sample,m,f,r,detection";
+
+/// A sample of a test vector, with the expected outputs of the detector.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VectorRow {
+    /// The input sample.
+    pub sample: f32,
+    /// The threshold components after processing the sample.
+    pub thresholds: Thresholds,
+    /// The reported sample index, if the sample completed a detection.
+    pub detection: Option<u32>,
+}
+
+/// An error returned when parsing a test vector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VectorError {
+    /// The first line does not identify a test vector.
+    MissingHeader,
+    /// The vector was written using an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The sampling frequency is missing or invalid.
+    MissingSamplingFrequency,
+    /// The line with the given 1-based number could not be parsed.
+    InvalidLine(usize),
+}
+
+impl fmt::Display for VectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "not a test vector"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported test vector version {version}")
+            }
+            Self::MissingSamplingFrequency => write!(f, "missing sampling frequency"),
+            Self::InvalidLine(line) => write!(f, "invalid line {line}"),
+        }
+    }
+}
+
+/// The allowed difference between the detector and a test vector.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// The allowed difference of the threshold components, relative to the expected value.
+    pub relative: f32,
+    /// The allowed absolute difference of the threshold components, for values close to 0.
+    pub absolute: f32,
+    /// The allowed difference of the detection indices, in samples. The detections must happen
+    /// at the same samples.
+    pub detection_samples: u32,
+}
+
+impl Tolerance {
+    /// Requires bit-exact thresholds and identical detections.
+    pub const EXACT: Self = Self {
+        relative: 0.0,
+        absolute: 0.0,
+        detection_samples: 0,
+    };
+
+    /// A tolerance for implementations using a different floating point precision or summation
+    /// order, e.g. double precision MATLAB or NumPy code.
+    pub const REFERENCE: Self = Self {
+        relative: 1e-4,
+        absolute: 1e-7,
+        detection_samples: 0,
+    };
+
+    fn matches(&self, expected: f32, actual: f32) -> bool {
+        let difference = (expected - actual).abs();
+        expected.to_bits() == actual.to_bits()
+            || difference <= self.absolute
+            || difference <= self.relative * expected.abs()
+    }
+}
+
+/// The output of the detector that differs from a test vector.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difference {
+    /// The steep-slope threshold.
+    M,
+    /// The integrating threshold.
+    F,
+    /// The beat expectation threshold.
+    R,
+    /// The detection.
+    Detection,
+}
+
+/// The first difference between the detector and a test vector, see [`TestVector::verify`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mismatch {
+    /// The 0-based index of the row in the vector.
+    pub row: usize,
+    /// The differing output.
+    pub difference: Difference,
+    /// The row of the vector.
+    pub expected: VectorRow,
+    /// The output of the detector, for the same sample.
+    pub actual: VectorRow,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "row {}: {:?} differs, expected {:?}, got {:?}",
+            self.row, self.difference, self.expected, self.actual
+        )
+    }
+}
+
+/// A parsed test vector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestVector {
+    /// The sampling frequency of the vector.
+    pub fs: SamplingFrequency,
+    /// The samples, with the expected outputs.
+    pub rows: Vec<VectorRow>,
+}
+
+impl TestVector {
+    /// Parses a test vector from its CSV text.
+    pub fn parse(text: &str) -> Result<Self, VectorError> {
+        let mut lines = text.lines().enumerate();
+
+        let version = lines
+            .next()
+            .and_then(|(_, line)| line.strip_prefix(MAGIC))
+            .ok_or(VectorError::MissingHeader)?;
+        let version = version
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| VectorError::MissingHeader)?;
+        if version != VECTOR_VERSION {
+            return Err(VectorError::UnsupportedVersion(version));
+        }
+
+        let mut fs = None;
+        let mut rows = Vec::new();
+        for (idx, line) in lines {
+            let invalid = VectorError::InvalidLine(idx + 1);
+            if let Some(metadata) = line.strip_prefix('#') {
+                if let Some(("fs", value)) = metadata.trim().split_once('=') {
+                    let hz = value.trim().parse::<f32>().map_err(|_| invalid)?;
+                    if !(hz > 0.0 && hz.is_finite()) {
+                        return Err(VectorError::MissingSamplingFrequency);
+                    }
+                    fs = Some(hz.sps());
+                }
+                continue;
+            }
+            if line.trim().is_empty() || line == COLUMNS {
+                continue;
+            }
+            rows.push(parse_row(line).ok_or(invalid)?);
+        }
+
+        Ok(Self {
+            fs: fs.ok_or(VectorError::MissingSamplingFrequency)?,
+            rows,
+        })
+    }
+
+    /// Processes the samples of the vector with `detector`, and compares its outputs with the
+    /// expected ones. Returns the first mismatch.
+    ///
+    /// The detector must be configured like the implementation that produced the vector.
+    pub fn verify<FMW, FB, H, T, S, N, FC, RC>(
+        &self,
+        detector: &mut QrsDetector<FMW, FB, H, T, S, N, FC, RC>,
+        tolerance: Tolerance,
+    ) -> Result<(), Mismatch>
+    where
+        H: AsRef<[Detection]> + AsMut<[Detection]>,
+        T: SampleTransform,
+        S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+        N: NoiseEstimator,
+        FC: ThresholdComponent,
+        RC: ThresholdComponent,
+    {
+        for (row, expected) in self.rows.iter().enumerate() {
+            let detection = detector.update(expected.sample);
+            let actual = VectorRow {
+                sample: expected.sample,
+                thresholds: detector.thresholds(),
+                detection,
+            };
+
+            let optional = |expected: Option<f32>, actual: Option<f32>| match (expected, actual) {
+                (Some(expected), Some(actual)) => tolerance.matches(expected, actual),
+                (expected, actual) => expected.is_none() && actual.is_none(),
+            };
+            let difference = if !optional(expected.thresholds.m, actual.thresholds.m) {
+                Some(Difference::M)
+            } else if !optional(expected.thresholds.f, actual.thresholds.f) {
+                Some(Difference::F)
+            } else if !tolerance.matches(expected.thresholds.r, actual.thresholds.r) {
+                Some(Difference::R)
+            } else {
+                match (expected.detection, actual.detection) {
+                    (Some(expected), Some(actual))
+                        if expected.abs_diff(actual) <= tolerance.detection_samples =>
+                    {
+                        None
+                    }
+                    (None, None) => None,
+                    _ => Some(Difference::Detection),
+                }
+            };
+
+            if let Some(difference) = difference {
+                return Err(Mismatch {
+                    row,
+                    difference,
+                    expected: *expected,
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_row(line: &str) -> Option<VectorRow> {
+    let mut fields = line.split(',').map(str::trim);
+    let mut next = || fields.next();
+    let optional = |field: &str| {
+        if field.is_empty() {
+            Some(None)
+        } else {
+            field.parse::<f32>().ok().map(Some)
+        }
+    };
+
+    let sample = next()?.parse::<f32>().ok()?;
+    let m = optional(next()?)?;
+    let f = optional(next()?)?;
+    let r = next()?.parse::<f32>().ok()?;
+    let detection = match next()? {
+        "" => None,
+        index => Some(index.parse::<u32>().ok()?),
+    };
+    if next().is_some() {
+        return None;
+    }
+
+    Some(VectorRow {
+        sample,
+        thresholds: Thresholds { m, f, r },
+        detection,
+    })
+}
+
+/// Writes a test vector, see the [module documentation](self).
+pub struct VectorWriter<W: Write> {
+    out: W,
+    columns_written: bool,
+}
+
+impl<W: Write> VectorWriter<W> {
+    /// Creates a writer and writes the header of a vector sampled with `fs` into `out`.
+    pub fn new(mut out: W, fs: SamplingFrequency) -> io::Result<Self> {
+        writeln!(out, "{MAGIC}{VECTOR_VERSION}")?;
+        writeln!(out, "# fs={}", fs.raw())?;
+        Ok(Self {
+            out,
+            columns_written: false,
+        })
+    }
+
+    /// Writes a `key=value` metadata line, e.g. the configuration description of the detector.
+    ///
+    /// Metadata written after the first row is placed among the rows.
+    pub fn write_metadata(&mut self, key: &str, value: impl fmt::Display) -> io::Result<()> {
+        writeln!(self.out, "# {key}={value}")
+    }
+
+    /// Writes a row.
+    pub fn write_row(&mut self, row: &VectorRow) -> io::Result<()> {
+        if !self.columns_written {
+            writeln!(self.out, "{COLUMNS}")?;
+            self.columns_written = true;
+        }
+        write!(self.out, "{},", row.sample)?;
+        for value in [row.thresholds.m, row.thresholds.f] {
+            if let Some(value) = value {
+                write!(self.out, "{value}")?;
+            }
+            write!(self.out, ",")?;
+        }
+        write!(self.out, "{},", row.thresholds.r)?;
+        if let Some(index) = row.detection {
+            write!(self.out, "{index}")?;
+        }
+        writeln!(self.out)
+    }
+
+    /// Processes `sample` with `detector`, and writes the row of its outputs. Returns the
+    /// detection, like [`QrsDetector::update`].
+    pub fn record<FMW, FB, H, T, S, N, FC, RC>(
+        &mut self,
+        detector: &mut QrsDetector<FMW, FB, H, T, S, N, FC, RC>,
+        sample: f32,
+    ) -> io::Result<Option<u32>>
+    where
+        H: AsRef<[Detection]> + AsMut<[Detection]>,
+        T: SampleTransform,
+        S: AsRef<[Suppressed]> + AsMut<[Suppressed]>,
+        N: NoiseEstimator,
+        FC: ThresholdComponent,
+        RC: ThresholdComponent,
+    {
+        let detection = detector.update(sample);
+        self.write_row(&VectorRow {
+            sample,
+            thresholds: detector.thresholds(),
+            detection,
+        })?;
+        Ok(detection)
+    }
+
+    /// Flushes the output.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    /// Returns the output, consuming the writer.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
@@ -0,0 +1,61 @@
+//! JavaScript bindings, generated by `wasm-bindgen`.
+//!
+//! The detector uses the default configuration, and is exported to JavaScript as `QrsDetector`:
+//!
+//! ```js
+//! const detector = new QrsDetector(500);
+//!
+//! for (const index of detector.updateChunk(samples)) {
+//!     // A QRS complex was detected at `index`
+//! }
+//! ```
+
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{sampling::SamplingFrequencyExt, BufferSizes, QrsDetector};
+
+/// A detector, allocated on the heap.
+#[wasm_bindgen(js_name = QrsDetector)]
+pub struct WasmDetector {
+    detector: QrsDetector<Box<[f32]>, Box<[f32]>>,
+}
+
+#[wasm_bindgen(js_class = QrsDetector)]
+impl WasmDetector {
+    /// Creates a detector for signals sampled with `sps` samples per second.
+    ///
+    /// Returns an error if `sps` is not finite, or too low for the 50ms window to hold a sample.
+    #[wasm_bindgen(constructor)]
+    pub fn new(sps: f32) -> Result<WasmDetector, String> {
+        let fs = sps.sps();
+        if !sps.is_finite() || BufferSizes::for_fs(fs).samples_50 == 0 {
+            return Err(format!("invalid sampling frequency: {sps}"));
+        }
+
+        Ok(Self {
+            detector: QrsDetector::new_alloc(fs),
+        })
+    }
+
+    /// Processes a sample. Returns the sample index if a QRS complex is detected, `undefined`
+    /// otherwise. See [`QrsDetector::update`].
+    pub fn update(&mut self, sample: f32) -> Option<u32> {
+        self.detector.update(sample)
+    }
+
+    /// Processes a chunk of samples. Returns the sample indices of the detected QRS complexes.
+    #[wasm_bindgen(js_name = updateChunk)]
+    pub fn update_chunk(&mut self, samples: &[f32]) -> Vec<u32> {
+        self.detector
+            .update_chunk(samples)
+            .map(|detection| detection.index)
+            .collect()
+    }
+
+    /// Resets the detector. See [`QrsDetector::clear`].
+    pub fn clear(&mut self) {
+        self.detector.clear();
+    }
+}
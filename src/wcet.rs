@@ -0,0 +1,100 @@
+//! Execution time measurement.
+//!
+//! The work done by [`QrsDetector::update`](crate::QrsDetector::update) is bounded for every
+//! sample, but the worst case path depends on the target. [`WcetMeter`] records the worst case
+//! execution time using a target-specific [`CycleCounter`], e.g. the DWT cycle counter of a
+//! Cortex-M microcontroller.
+//!
+//! ```rust
+//! use qrs_detector::prelude::*;
+//! use qrs_detector::wcet::WcetMeter;
+//!
+//! # let mut cycles = 0;
+//! # let read_cycle_counter = move || { cycles += 10; cycles };
+//! let mut detector = QrsDetector::new::<150, 25>(500.sps());
+//! let mut meter = WcetMeter::new(read_cycle_counter);
+//!
+//! # let samples = [0.0; 10];
+//! for sample in samples {
+//!     meter.measure(|| detector.update(sample));
+//! }
+//!
+//! let worst_case_cycles = meter.worst_case();
+//! ```
+
+/// A free-running counter, e.g. a CPU cycle counter.
+///
+/// Closures of type `FnMut() -> u32` implement this trait.
+pub trait CycleCounter {
+    /// Returns the current value of the counter. The counter may wrap around.
+    fn cycles(&mut self) -> u32;
+}
+
+impl<F> CycleCounter for F
+where
+    F: FnMut() -> u32,
+{
+    fn cycles(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// Records the worst case execution time of the measured code.
+pub struct WcetMeter<C> {
+    counter: C,
+    measurements: u32,
+    worst_case: u32,
+    worst_case_index: u32,
+}
+
+impl<C> WcetMeter<C>
+where
+    C: CycleCounter,
+{
+    /// Creates a new meter using `counter`.
+    pub fn new(counter: C) -> Self {
+        Self {
+            counter,
+            measurements: 0,
+            worst_case: 0,
+            worst_case_index: 0,
+        }
+    }
+
+    /// Resets the recorded measurements.
+    pub fn clear(&mut self) {
+        self.measurements = 0;
+        self.worst_case = 0;
+        self.worst_case_index = 0;
+    }
+
+    /// Runs `f` and records the number of elapsed cycles.
+    pub fn measure<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let start = self.counter.cycles();
+        let result = f();
+        let elapsed = self.counter.cycles().wrapping_sub(start);
+
+        if elapsed > self.worst_case {
+            self.worst_case = elapsed;
+            self.worst_case_index = self.measurements;
+        }
+        self.measurements = self.measurements.wrapping_add(1);
+
+        result
+    }
+
+    /// Returns the number of measurements.
+    pub fn measurements(&self) -> u32 {
+        self.measurements
+    }
+
+    /// Returns the largest number of cycles measured.
+    pub fn worst_case(&self) -> u32 {
+        self.worst_case
+    }
+
+    /// Returns the index of the measurement that took the largest number of cycles.
+    pub fn worst_case_index(&self) -> u32 {
+        self.worst_case_index
+    }
+}
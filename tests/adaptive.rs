@@ -0,0 +1,74 @@
+mod common;
+
+use common::noise;
+use qrs_detector::noise::RmsNoise;
+use qrs_detector::prelude::*;
+
+/// Runs the detector on the reference record with added noise, using the canonical
+/// preprocessing.
+fn detect(config: Config, noise_amplitude: f32) -> Vec<u32> {
+    let mut seed = 1;
+    let samples = common::samples()
+        .into_iter()
+        .map(|sample| sample + noise_amplitude * (noise(&mut seed) - 0.5))
+        .collect::<Vec<_>>();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps())
+        .with_noise_estimator(RmsNoise::new(720.sps()))
+        .with_config(config);
+
+    let averaged = samples
+        .windows(4)
+        .map(|window| window.iter().sum::<f32>() / 4.0)
+        .collect::<Vec<_>>();
+
+    averaged
+        .windows(3)
+        .filter_map(|window| detector.update((window[0] - window[2]).abs()))
+        .collect()
+}
+
+/// Counts the detections that are within 50 ms of a reference detection.
+fn true_positives(reference: &[u32], detections: &[u32]) -> usize {
+    detections
+        .iter()
+        .filter(|idx| reference.iter().any(|r| r.abs_diff(**idx) < 36))
+        .count()
+}
+
+#[test]
+fn test_adaptive_profile_on_clean_signal() {
+    let reference = detect(Config::new(), 0.0);
+    let adaptive = detect(Config::adaptive(), 0.0);
+
+    assert_eq!(reference.len(), adaptive.len());
+    assert_eq!(reference.len(), true_positives(&reference, &adaptive));
+}
+
+#[test]
+fn test_adaptive_profile_on_noisy_signal() {
+    let reference = detect(Config::new(), 0.0);
+
+    for amplitude in [0.2, 0.4] {
+        let default = detect(Config::new(), amplitude);
+        let adaptive = detect(Config::adaptive(), amplitude);
+
+        let default_tp = true_positives(&reference, &default);
+        let adaptive_tp = true_positives(&reference, &adaptive);
+
+        // Adapting to the noise level does not lose sensitivity, and results in fewer false
+        // detections
+        assert!(
+            adaptive_tp >= default_tp,
+            "{} < {}",
+            adaptive_tp,
+            default_tp
+        );
+        assert!(
+            adaptive.len() - adaptive_tp < default.len() - default_tp,
+            "{} false detections, {} without adaptation",
+            adaptive.len() - adaptive_tp,
+            default.len() - default_tp
+        );
+    }
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "preprocessing")]
+
+mod common;
+
+use std::f32::consts::PI;
+
+use common::samples;
+use qrs_detector::prelude::*;
+use qrs_detector::preprocessing::{AdaptiveNotch, MainsFrequency, Preprocessor};
+use qrs_detector::transform::SampleTransform;
+
+/// Returns the RMS difference between the filtered, interfered signal and the clean signal,
+/// after the first five seconds.
+fn residual(mut filter: impl SampleTransform, frequency: impl Fn(usize) -> f32) -> f32 {
+    let mut phase = 0.0;
+    let (sum, count) = samples()
+        .into_iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            phase += 2.0 * PI * frequency(i) / 720.0;
+            filter.transform(sample + 0.05 * phase.sin()) - sample
+        })
+        .skip(5 * 720)
+        .fold((0.0, 0), |(sum, count), error| {
+            (sum + error * error, count + 1)
+        });
+
+    (sum / count as f32).sqrt()
+}
+
+#[test]
+fn test_adaptive_notch_follows_drifting_frequency() {
+    // A generator drifting between 48 and 52 Hz
+    let drifting = |i: usize| 50.0 + 2.0 * (2.0 * PI * i as f32 / (20.0 * 720.0)).sin();
+
+    let fixed = residual(
+        Preprocessor::new(720.sps()).with_notch(MainsFrequency::Hz50),
+        drifting,
+    );
+    let adaptive = residual(
+        Preprocessor::new(720.sps()).with_adaptive_notch(MainsFrequency::Hz50),
+        drifting,
+    );
+
+    assert!(adaptive < fixed / 3.0, "{adaptive} >= {fixed} / 3");
+}
+
+#[test]
+fn test_adaptive_notch_converges() {
+    let mut notch = AdaptiveNotch::new(720.sps(), MainsFrequency::Hz60);
+    assert!((notch.frequency() - 60.0).abs() < 0.01);
+
+    for (i, sample) in samples().into_iter().enumerate() {
+        notch.transform(sample + 0.05 * (2.0 * PI * 62.5 * i as f32 / 720.0).sin());
+    }
+    assert!(
+        (notch.frequency() - 62.5).abs() < 0.3,
+        "{}",
+        notch.frequency()
+    );
+
+    notch.clear();
+    assert!((notch.frequency() - 60.0).abs() < 0.01);
+}
+
+#[test]
+fn test_adaptive_notch_is_bounded() {
+    let mut notch = AdaptiveNotch::new(720.sps(), MainsFrequency::Hz50);
+
+    // Out of range interference
+    for (i, sample) in samples().into_iter().enumerate() {
+        let output = notch.transform(sample + 0.05 * (2.0 * PI * 60.0 * i as f32 / 720.0).sin());
+        assert!(output.is_finite());
+        assert!((45.0 - 0.01..=55.0 + 0.01).contains(&notch.frequency()));
+    }
+
+    // No interference
+    notch.clear();
+    for sample in samples() {
+        assert!(notch.transform(sample).is_finite());
+        assert!((45.0 - 0.01..=55.0 + 0.01).contains(&notch.frequency()));
+    }
+
+    // The DC gain is 1 at any frequency
+    let output = (0..720).fold(0.0, |_, _| notch.transform(1.0));
+    assert!((output - 1.0).abs() < 1e-4, "{output}");
+}
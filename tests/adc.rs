@@ -0,0 +1,165 @@
+#![cfg(feature = "sensors")]
+
+mod common;
+
+use common::codes;
+use qrs_detector::adc::{
+    i24_from_bytes, i24_to_bytes, ByteOrder, FrameError, PackedFrame, PackedLayout, I24_MAX,
+    I24_MIN,
+};
+use qrs_detector::leads::ComplexLead;
+use qrs_detector::prelude::*;
+
+/// Runs the detector on `samples`, using the canonical preprocessing.
+fn detect(samples: impl Iterator<Item = f32>) -> Vec<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+
+    let samples = samples.collect::<Vec<_>>();
+    let averaged = samples
+        .windows(4)
+        .map(|window| window.iter().sum::<f32>() / 4.0)
+        .collect::<Vec<_>>();
+
+    averaged
+        .windows(3)
+        .filter_map(|window| detector.update((window[0] - window[2]).abs()))
+        .collect()
+}
+
+#[test]
+fn test_sign_extension() {
+    for order in [ByteOrder::BigEndian, ByteOrder::LittleEndian] {
+        for value in [0, 1, -1, 256, -256, 0x12_3456, -0x12_3456, I24_MIN, I24_MAX] {
+            assert_eq!(value, i24_from_bytes(i24_to_bytes(value, order), order));
+        }
+    }
+
+    assert_eq!(-1, i24_from_bytes([0xFF, 0xFF, 0xFF], ByteOrder::BigEndian));
+    assert_eq!(
+        I24_MIN,
+        i24_from_bytes([0x80, 0x00, 0x00], ByteOrder::BigEndian)
+    );
+    assert_eq!(
+        I24_MAX,
+        i24_from_bytes([0x7F, 0xFF, 0xFF], ByteOrder::BigEndian)
+    );
+    assert_eq!(
+        0x12_3456,
+        i24_from_bytes([0x12, 0x34, 0x56], ByteOrder::BigEndian)
+    );
+    assert_eq!(
+        0x12_3456,
+        i24_from_bytes([0x56, 0x34, 0x12], ByteOrder::LittleEndian)
+    );
+    assert_eq!(
+        -2,
+        i24_from_bytes([0xFE, 0xFF, 0xFF], ByteOrder::LittleEndian)
+    );
+
+    // Out of range values saturate
+    assert_eq!(
+        [0x7F, 0xFF, 0xFF],
+        i24_to_bytes(1 << 24, ByteOrder::BigEndian)
+    );
+    assert_eq!(
+        [0x80, 0x00, 0x00],
+        i24_to_bytes(i32::MIN, ByteOrder::BigEndian)
+    );
+}
+
+#[test]
+fn test_decode_frame() {
+    // ADS1298: 24-bit status word, 8 channels
+    let layout = PackedLayout::<8>::new().with_status_bytes(3);
+    assert_eq!(27, layout.frame_size());
+
+    let mut bytes = [0; 27];
+    bytes[..3].copy_from_slice(&[0xC0, 0x01, 0x02]);
+    bytes[3..6].copy_from_slice(&[0xFF, 0xFF, 0xFE]);
+    bytes[24..].copy_from_slice(&[0x7F, 0xFF, 0xFF]);
+
+    let frame = layout.decode(&bytes).unwrap();
+    assert_eq!(0xC0_0102, frame.status);
+    assert_eq!([-2, 0, 0, 0, 0, 0, 0, I24_MAX], frame.samples);
+    assert!(frame.is_clipped());
+
+    let mut encoded = [0; 27];
+    layout.encode(&frame, &mut encoded).unwrap();
+    assert_eq!(bytes, encoded);
+
+    // The same frame, with the bytes reversed
+    let layout = layout.with_byte_order(ByteOrder::LittleEndian);
+    let mut reversed = [0; 27];
+    layout.encode(&frame, &mut reversed).unwrap();
+    assert_eq!([0x02, 0x01, 0xC0, 0xFE, 0xFF, 0xFF], reversed[..6]);
+    assert_eq!(frame, layout.decode(&reversed).unwrap());
+
+    assert_eq!(
+        Err(FrameError::Length {
+            expected: 27,
+            actual: 26
+        }),
+        layout.decode(&bytes[..26])
+    );
+    assert_eq!(
+        Err(FrameError::Length {
+            expected: 27,
+            actual: 28
+        }),
+        layout.encode(&frame, &mut [0; 28])
+    );
+}
+
+#[test]
+fn test_frames_remainder() {
+    let layout = PackedLayout::<2>::new().with_status_bytes(3);
+    let frame = PackedFrame {
+        status: 0xC0_0000,
+        samples: [-5, 5],
+    };
+
+    let mut buffer = [0; 9 * 3 + 4];
+    for chunk in buffer.chunks_exact_mut(9) {
+        layout.encode(&frame, chunk).unwrap();
+    }
+
+    let mut frames = layout.frames(&buffer);
+    assert_eq!(3, frames.len());
+    assert!(frames.by_ref().all(|decoded| decoded == frame));
+    assert_eq!(4, frames.remainder().len());
+}
+
+#[test]
+fn test_packed_frames_feed_the_detector() {
+    let codes = codes();
+    let reference = detect(codes.iter().map(|&code| code as f32));
+    assert!(reference.len() > 20);
+
+    // A 3 channel recording with a status word, the ECG on the second channel
+    let layout = PackedLayout::<3>::new().with_status_bytes(3);
+    let mut buffer = vec![0; codes.len() * layout.frame_size()];
+    for (&code, chunk) in codes
+        .iter()
+        .zip(buffer.chunks_exact_mut(layout.frame_size()))
+    {
+        let frame = PackedFrame {
+            status: 0xC0_0000,
+            samples: [I24_MAX, code, -code],
+        };
+        layout.encode(&frame, chunk).unwrap();
+    }
+
+    let decoded = detect(layout.frames(&buffer).map(|frame| frame.samples[1] as f32));
+    assert_eq!(reference, decoded);
+
+    // The frames can be fed directly to the multi-channel front-end
+    let mut lead = ComplexLead::<3>::new();
+    let combined = layout
+        .frames(&buffer)
+        .map(|frame| {
+            let [_, a, b] = frame.to_f32();
+            lead.update(&[0.0, a, b])
+        })
+        .count();
+    assert_eq!(codes.len(), combined);
+}
@@ -0,0 +1,220 @@
+#![cfg(feature = "sensors")]
+
+mod common;
+
+use common::codes;
+use qrs_detector::adc::{PackedFrame, PackedLayout, I24_MAX};
+use qrs_detector::ads129x::{Ads129xDetector, Ads129xError, Ads129xStatus, StatusFormat};
+use qrs_detector::config::InputMode;
+use qrs_detector::prelude::*;
+
+/// An ADS1292 status word with no electrode off.
+const STATUS_OK: u32 = 0xC0_0000;
+/// An ADS1292 status word with IN2N off.
+const STATUS_IN2N_OFF: u32 = 0xC0_0000 | (0b01000 << 15);
+
+fn detector() -> QrsDetector<[f32; 216], [f32; 36]> {
+    QrsDetector::new::<216, 36>(720.sps())
+        .with_config(Config::new().with_input_mode(InputMode::AbsDiff))
+}
+
+/// Encodes an ADS1292 frame with the ECG on channel 2.
+fn frame(status: u32, ecg: i32) -> [u8; 9] {
+    let mut bytes = [0; 9];
+    PackedLayout::<2>::new()
+        .with_status_bytes(3)
+        .encode(
+            &PackedFrame {
+                status,
+                samples: [0, ecg],
+            },
+            &mut bytes,
+        )
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn test_parse_status() {
+    assert_eq!(None, Ads129xStatus::parse(0x00_0000, StatusFormat::Ads1292));
+    assert_eq!(None, Ads129xStatus::parse(0xF0_0000, StatusFormat::Ads1298));
+
+    let status = Ads129xStatus::parse(STATUS_OK | (0b10 << 13), StatusFormat::Ads1292).unwrap();
+    assert_eq!(
+        Ads129xStatus {
+            gpio: 0b10,
+            ..Default::default()
+        },
+        status
+    );
+    assert!(!status.is_lead_off(0));
+    assert!(!status.is_lead_off(1));
+
+    let status = Ads129xStatus::parse(STATUS_IN2N_OFF, StatusFormat::Ads1292).unwrap();
+    assert_eq!(0, status.lead_off_positive);
+    assert_eq!(0b10, status.lead_off_negative);
+    assert!(!status.is_lead_off(0));
+    assert!(status.is_lead_off(1));
+
+    // IN1P off, RLD off
+    let status = Ads129xStatus::parse(STATUS_OK | (0b10001 << 15), StatusFormat::Ads1292).unwrap();
+    assert_eq!(0b01, status.lead_off_positive);
+    assert!(status.rld_off);
+    assert!(status.is_lead_off(1));
+
+    // ADS1298: IN8P and IN3N off, GPIO4 set
+    let word = 0xC0_0000 | (0x80 << 12) | (0x04 << 4) | 0b0001;
+    let status = Ads129xStatus::parse(word, StatusFormat::Ads1298).unwrap();
+    assert_eq!(
+        Ads129xStatus {
+            lead_off_positive: 0x80,
+            lead_off_negative: 0x04,
+            rld_off: false,
+            gpio: 0b0001,
+        },
+        status
+    );
+    assert!(status.is_lead_off(7));
+    assert!(status.is_lead_off(2));
+    assert!(!status.is_lead_off(0));
+    assert!(!status.is_lead_off(8));
+}
+
+#[test]
+fn test_frames_match_direct_processing() {
+    let codes = codes();
+
+    let mut reference = detector();
+    let expected = codes
+        .iter()
+        .filter_map(|&code| reference.update(code as f32))
+        .collect::<Vec<_>>();
+    assert!(expected.len() > 20);
+
+    let mut frontend =
+        Ads129xDetector::<2, _, _, _, _, _, _, _, _>::new(detector(), StatusFormat::Ads1292, 1);
+    let detections = codes
+        .iter()
+        .filter_map(|&code| frontend.push_frame(&frame(STATUS_OK, code)).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(expected, detections);
+    assert!(!frontend.is_lead_off());
+}
+
+#[test]
+fn test_lead_off_is_a_gap() {
+    let codes = codes();
+
+    let mut reference = detector();
+    let expected = codes
+        .iter()
+        .filter_map(|&code| reference.update(code as f32))
+        .collect::<Vec<_>>();
+
+    // The negative electrode is off for 500ms, the samples are garbage
+    let lead_off = 14_400..14_760;
+    let mut frontend =
+        Ads129xDetector::<2, _, _, _, _, _, _, _, _>::new(detector(), StatusFormat::Ads1292, 1);
+    let mut detections = Vec::new();
+    for (idx, &code) in codes.iter().enumerate() {
+        let bytes = if lead_off.contains(&idx) {
+            frame(
+                STATUS_IN2N_OFF,
+                if idx % 2 == 0 { I24_MAX } else { -I24_MAX },
+            )
+        } else {
+            frame(STATUS_OK, code)
+        };
+        if let Some(detection) = frontend.push_frame(&bytes).unwrap() {
+            detections.push(detection);
+        }
+        if idx == lead_off.end - 1 {
+            assert!(frontend.is_lead_off());
+            assert_eq!(360, frontend.lead_off_samples());
+            assert_eq!(0b10, frontend.status().unwrap().lead_off_negative);
+        }
+    }
+
+    // Not reset, and the sample indices stay aligned with the acquisition
+    assert!(!frontend.is_lead_off());
+    assert_eq!(0, frontend.detector().timeline().epoch());
+    assert_eq!(
+        codes.len() as u32,
+        frontend.detector().timeline().position()
+    );
+    assert!(!detections
+        .iter()
+        .any(|&idx| idx >= lead_off.start as u32 && idx < lead_off.end as u32));
+
+    let after = |detections: &[u32]| {
+        detections
+            .iter()
+            .copied()
+            .filter(|&idx| idx > lead_off.end as u32 + 2 * 720)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(after(&expected), after(&detections));
+}
+
+#[test]
+fn test_long_lead_off_resets_the_detector() {
+    let codes = codes();
+    let lead_off = 14_400..14_400 + 3 * 720;
+
+    let mut frontend =
+        Ads129xDetector::<2, _, _, _, _, _, _, _, _>::new(detector(), StatusFormat::Ads1292, 1);
+    let mut detections = Vec::new();
+    for (idx, &code) in codes.iter().enumerate() {
+        let status = if lead_off.contains(&idx) {
+            STATUS_IN2N_OFF
+        } else {
+            STATUS_OK
+        };
+        if let Some(detection) = frontend.push_frame(&frame(status, code)).unwrap() {
+            detections.push(detection);
+        }
+    }
+
+    // A new epoch started when the electrode reconnected
+    let timeline = frontend.detector().timeline();
+    assert_eq!(1, timeline.epoch());
+    assert_eq!(lead_off.end as u32, timeline.origin());
+    assert_eq!(codes.len() as u32, timeline.position());
+    assert!(detections.iter().any(|&idx| idx > lead_off.end as u32));
+
+    // A longer limit keeps the detector running
+    let mut frontend =
+        Ads129xDetector::<2, _, _, _, _, _, _, _, _>::new(detector(), StatusFormat::Ads1292, 1)
+            .with_reset_after(5000.0);
+    for (idx, &code) in codes.iter().enumerate() {
+        let status = if lead_off.contains(&idx) {
+            STATUS_IN2N_OFF
+        } else {
+            STATUS_OK
+        };
+        frontend.push_frame(&frame(status, code)).unwrap();
+    }
+    assert_eq!(0, frontend.detector().timeline().epoch());
+}
+
+#[test]
+fn test_invalid_frames() {
+    let mut frontend =
+        Ads129xDetector::<2, _, _, _, _, _, _, _, _>::new(detector(), StatusFormat::Ads1292, 1);
+
+    assert_eq!(Ok(None), frontend.push_frame(&frame(STATUS_OK, 0)));
+    assert!(matches!(
+        frontend.push_frame(&[0; 8]),
+        Err(Ads129xError::Frame(_))
+    ));
+    assert_eq!(1, frontend.detector().timeline().position());
+
+    // A misaligned frame is a lost sample
+    assert_eq!(
+        Err(Ads129xError::Sync(0x00_0100)),
+        frontend.push_frame(&[0x00, 0x01, 0x00, 0, 0, 0, 0, 0, 0])
+    );
+    assert_eq!(2, frontend.detector().timeline().position());
+    assert_eq!(1, frontend.detector().timeline().gap_samples());
+}
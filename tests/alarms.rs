@@ -0,0 +1,103 @@
+#![cfg(feature = "hrv")]
+
+use core::time::Duration;
+
+use qrs_detector::alarms::{AlarmManager, Priority};
+use qrs_detector::events::Event;
+
+#[test]
+fn test_hold_time() {
+    let mut alarms = AlarmManager::new();
+
+    // Intermittent events keep the alarm active
+    for now_ms in [0, 4_000, 8_000, 12_000] {
+        alarms.raise(Event::Tachycardia, now_ms);
+        alarms.update(now_ms);
+    }
+    let alarm = alarms.highest().unwrap();
+    assert_eq!(Event::Tachycardia, alarm.event);
+    assert_eq!(Priority::Medium, alarm.priority);
+    assert_eq!((0, 12_000), (alarm.raised_ms, alarm.last_ms));
+    assert!(!alarm.latched);
+
+    alarms.update(22_000);
+    assert!(alarms.highest().is_some());
+    alarms.update(22_001);
+    assert_eq!(None, alarms.highest());
+}
+
+#[test]
+fn test_priority_and_latching() {
+    let mut alarms = AlarmManager::new();
+
+    alarms.raise(Event::SignalStep, 0);
+    alarms.raise(Event::Asystole, 1_000);
+    alarms.raise(Event::Bradycardia, 2_000);
+    assert_eq!(3, alarms.alarms().count());
+    assert_eq!(Event::Asystole, alarms.highest().unwrap().event);
+    assert_eq!(Some(Priority::High), alarms.audible());
+
+    // Only the critical alarm is latched
+    alarms.update(60_000);
+    assert_eq!(1, alarms.alarms().count());
+    assert!(alarms.highest().unwrap().latched);
+
+    // Latching can be disabled, and priorities overridden
+    let mut alarms = AlarmManager::new()
+        .with_latching(None)
+        .with_priority(Event::SignalStep, Priority::High)
+        .with_hold(Duration::from_secs(1));
+    alarms.raise(Event::Asystole, 0);
+    alarms.raise(Event::SignalStep, 500);
+    assert_eq!(Event::SignalStep, alarms.highest().unwrap().event);
+    alarms.update(1_600);
+    assert_eq!(None, alarms.highest());
+}
+
+#[test]
+fn test_silence() {
+    let mut alarms = AlarmManager::new().with_silence(Duration::from_secs(60));
+
+    alarms.raise(Event::Pause, 0);
+    alarms.silence(1_000);
+    assert_eq!(None, alarms.audible());
+    assert!(alarms.highest().unwrap().silenced);
+
+    // New alarms are audible during the silence period
+    alarms.raise(Event::VentricularFibrillation, 2_000);
+    assert_eq!(Some(Priority::High), alarms.audible());
+
+    // Refreshing a silenced alarm keeps it silenced, until the silence period ends
+    alarms.acknowledge();
+    alarms.raise(Event::Pause, 5_000);
+    alarms.silence(5_000);
+    alarms.raise(Event::Pause, 30_000);
+    alarms.update(30_000);
+    assert!(alarms.alarms().all(|alarm| alarm.silenced));
+
+    alarms.update(65_000);
+    assert!(alarms.alarms().all(|alarm| !alarm.silenced));
+}
+
+#[test]
+fn test_acknowledge() {
+    let mut alarms = AlarmManager::new();
+
+    alarms.raise(Event::VentricularFibrillation, 0);
+    alarms.acknowledge();
+    assert_eq!(None, alarms.audible());
+
+    // The acknowledged alarm is not latched again while its events continue
+    alarms.raise(Event::VentricularFibrillation, 5_000);
+    assert_eq!(None, alarms.audible());
+    alarms.update(16_000);
+    assert_eq!(None, alarms.highest());
+
+    // Once cleared, the alarm is raised again
+    alarms.raise(Event::VentricularFibrillation, 20_000);
+    assert_eq!(Some(Priority::High), alarms.audible());
+    assert!(alarms.highest().unwrap().latched);
+
+    alarms.clear();
+    assert_eq!(None, alarms.highest());
+}
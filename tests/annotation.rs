@@ -0,0 +1,60 @@
+use qrs_detector::annotation::BeatClass;
+
+#[test]
+fn test_wfdb_symbols() {
+    // The MIT-BIH beat annotations, grouped as in ANSI/AAMI EC57
+    let groups = [
+        (BeatClass::Normal, "NLRej"),
+        (BeatClass::Supraventricular, "AaJS"),
+        (BeatClass::Ventricular, "VE"),
+        (BeatClass::Fusion, "F"),
+        (BeatClass::Unknown, "Q"),
+    ];
+    for (class, symbols) in groups {
+        for symbol in symbols.chars() {
+            assert_eq!(Some(class), BeatClass::from_wfdb_symbol(symbol), "{symbol}");
+        }
+        assert_eq!(class.wfdb_symbol(), class.ec57_symbol());
+    }
+
+    // Paced beats are kept apart, but EC57 counts them as unknown beats
+    for symbol in "/f".chars() {
+        assert_eq!(
+            Some(BeatClass::Paced),
+            BeatClass::from_wfdb_symbol(symbol),
+            "{symbol}"
+        );
+    }
+    assert_eq!('Q', BeatClass::Paced.ec57_symbol());
+
+    // Non-beat annotations
+    for symbol in "+~|x\"![]".chars() {
+        assert_eq!(None, BeatClass::from_wfdb_symbol(symbol), "{symbol}");
+    }
+}
+
+#[test]
+fn test_wfdb_codes() {
+    for class in BeatClass::ALL {
+        assert_eq!(Some(class), BeatClass::from_wfdb_code(class.wfdb_code()));
+        assert_eq!(
+            Some(class),
+            BeatClass::from_wfdb_symbol(class.wfdb_symbol())
+        );
+        assert_eq!(class.wfdb_symbol().to_string(), class.to_string());
+    }
+
+    // NOTQRS, NOISE and RHYTHM do not annotate beats
+    for code in [0, 14, 28] {
+        assert_eq!(None, BeatClass::from_wfdb_code(code));
+    }
+    // LBBB, APC, VESC, PACE and PFUS
+    assert_eq!(Some(BeatClass::Normal), BeatClass::from_wfdb_code(2));
+    assert_eq!(
+        Some(BeatClass::Supraventricular),
+        BeatClass::from_wfdb_code(8)
+    );
+    assert_eq!(Some(BeatClass::Ventricular), BeatClass::from_wfdb_code(10));
+    assert_eq!(Some(BeatClass::Paced), BeatClass::from_wfdb_code(12));
+    assert_eq!(Some(BeatClass::Paced), BeatClass::from_wfdb_code(38));
+}
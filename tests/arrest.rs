@@ -0,0 +1,121 @@
+#![cfg(feature = "quality")]
+
+use qrs_detector::events::Event;
+use qrs_detector::prelude::*;
+use qrs_detector::quality::{ArrestDetector, ArrestRhythm};
+use qrs_detector::synth::{SynthParams, Synthesizer};
+
+/// Runs the arrest detector on 20 seconds of `before`, followed by 40 seconds of `after`.
+/// Returns the events with the time they were reported at, in seconds.
+fn run(fs: f32, before: SynthParams, after: SynthParams, seed: u32) -> Vec<(f32, Event)> {
+    let fs = fs.sps();
+    let mut detector = ArrestDetector::new(fs, 0.1);
+
+    Synthesizer::new(fs, before, seed)
+        .take(fs.s_to_samples(20.0))
+        .chain(Synthesizer::new(fs, after, seed).take(fs.s_to_samples(40.0)))
+        .enumerate()
+        .filter_map(|(i, sample)| {
+            detector.update(sample.value);
+            detector
+                .take_event()
+                .map(|event| (fs.samples_to_s(i), event))
+        })
+        .collect()
+}
+
+const ASYSTOLE: SynthParams = SynthParams {
+    r_amplitude_mv: 0.0,
+    final_r_amplitude_mv: 0.0,
+    ..SynthParams::REST
+};
+
+#[test]
+fn test_ventricular_fibrillation_is_reported() {
+    let noisy = SynthParams {
+        noise_mv: 0.15,
+        baseline_wander_mv: 0.3,
+        mains_mv: 0.1,
+        ..SynthParams::VENTRICULAR_FIBRILLATION
+    };
+
+    for fs in [250.0, 500.0] {
+        for seed in 1..=3 {
+            for params in [SynthParams::VENTRICULAR_FIBRILLATION, noisy] {
+                let events = run(fs, SynthParams::REST, params, seed);
+
+                assert_eq!(1, events.len(), "{fs} sps, seed {seed}: {events:?}");
+                let (time, event) = events[0];
+                assert_eq!(Event::VentricularFibrillation, event);
+                assert!((20.0..27.0).contains(&time), "{time}");
+                assert!(event.is_critical());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_asystole_is_reported() {
+    for fs in [250.0, 500.0] {
+        let events = run(fs, SynthParams::REST, ASYSTOLE, 1);
+
+        assert_eq!(1, events.len(), "{fs} sps: {events:?}");
+        let (time, event) = events[0];
+        assert_eq!(Event::Asystole, event);
+        assert!((20.0..27.0).contains(&time), "{time}");
+    }
+}
+
+#[test]
+fn test_no_arrest_with_qrs_complexes() {
+    let tachycardia = SynthParams {
+        heart_rate_bpm: 180.0,
+        final_heart_rate_bpm: 180.0,
+        r_amplitude_mv: 0.3,
+        final_r_amplitude_mv: 0.3,
+        t_wave_ratio: 0.8,
+        noise_mv: 0.05,
+        ..SynthParams::REST
+    };
+
+    for fs in [250.0, 500.0] {
+        for seed in 1..=3 {
+            for params in [
+                SynthParams::REST,
+                SynthParams::EXERCISE,
+                SynthParams::ATRIAL_FIBRILLATION,
+                SynthParams::ATRIAL_FLUTTER,
+                SynthParams::NOISY,
+                tachycardia,
+            ] {
+                let events = run(fs, params, params, seed);
+                assert!(events.is_empty(), "{fs} sps, seed {seed}: {params:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rhythm_is_cleared_by_qrs_complexes() {
+    let fs = 250.sps();
+    let mut detector = ArrestDetector::new(fs, 0.1);
+
+    for sample in Synthesizer::new(fs, SynthParams::VENTRICULAR_FIBRILLATION, 1).take(10 * 250) {
+        detector.update(sample.value);
+    }
+    assert_eq!(
+        Some(ArrestRhythm::VentricularFibrillation),
+        detector.rhythm()
+    );
+    assert_eq!(Some(Event::VentricularFibrillation), detector.take_event());
+    assert_eq!(None, detector.take_event());
+
+    for sample in Synthesizer::new(fs, SynthParams::REST, 1).take(5 * 250) {
+        detector.update(sample.value);
+    }
+    assert_eq!(None, detector.rhythm());
+    assert_eq!(None, detector.last().unwrap().rhythm);
+
+    detector.clear();
+    assert_eq!(None, detector.last());
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "quality")]
+
+mod common;
+
+use std::f32::consts::PI;
+
+use common::{noise, samples};
+use qrs_detector::prelude::*;
+use qrs_detector::quality::{BandPower, BandPowerTracker, NoiseSource};
+
+fn band_powers(samples: impl Iterator<Item = f32>) -> Vec<BandPower> {
+    let mut tracker = BandPowerTracker::new(720.sps());
+    samples
+        .filter_map(|sample| tracker.update(sample))
+        .collect()
+}
+
+fn mains(frequency: f32, amplitude: f32) -> impl Fn(usize) -> f32 {
+    move |i| amplitude * (2.0 * PI * frequency * i as f32 / 720.0).sin()
+}
+
+#[test]
+fn test_clean_signal_has_no_noise_source() {
+    let powers = band_powers(samples().into_iter());
+
+    assert_eq!(samples().len() / 720, powers.len());
+    for power in powers {
+        assert_eq!(None, power.noise_source(), "{power:?}");
+    }
+}
+
+#[test]
+fn test_mains_interference_is_detected() {
+    for (frequency, source) in [(50.0, NoiseSource::Mains50), (60.0, NoiseSource::Mains60)] {
+        let interference = mains(frequency, 0.05);
+        let powers = band_powers(
+            samples()
+                .into_iter()
+                .enumerate()
+                .map(|(i, sample)| sample + interference(i)),
+        );
+
+        for power in powers {
+            // The power of a sine wave is half of its squared amplitude. The ECG has some power
+            // at the mains frequency, too.
+            let expected = 0.05 * 0.05 / 2.0;
+            assert!(
+                (power.mains() - expected).abs() < 0.15 * expected,
+                "{power:?}"
+            );
+            assert_eq!(Some(source), power.noise_source(), "{power:?}");
+        }
+    }
+}
+
+#[test]
+fn test_muscle_noise_is_detected() {
+    let mut seed = 12345;
+    let powers = band_powers(
+        samples()
+            .into_iter()
+            .map(|sample| sample + 0.4 * (noise(&mut seed) - 0.5)),
+    );
+
+    for power in &powers {
+        assert_eq!(Some(NoiseSource::Muscle), power.noise_source(), "{power:?}");
+    }
+
+    // White noise of variance `s` has `s * 2 * 40 Hz / 720 Hz` power in the 70-110 Hz band
+    let expected = 0.4 * 0.4 / 12.0 * 80.0 / 720.0;
+    let average = powers.iter().map(|power| power.muscle).sum::<f32>() / powers.len() as f32;
+    assert!(
+        (average - expected).abs() < 0.1 * expected,
+        "{average} != {expected}"
+    );
+}
+
+#[test]
+fn test_last_power_is_kept_until_cleared() {
+    let mut tracker = BandPowerTracker::new(720.sps());
+    assert_eq!(None, tracker.last());
+
+    let interference = mains(50.0, 0.05);
+    let power = (0..720).find_map(|i| tracker.update(interference(i)));
+    assert!(power.is_some());
+    assert_eq!(power, tracker.last());
+
+    tracker.clear();
+    assert_eq!(None, tracker.last());
+}
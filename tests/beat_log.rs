@@ -0,0 +1,97 @@
+mod common;
+
+use qrs_detector::beat_log::{BeatLogDecoder, BeatLogEncoder, BufferFull, MAX_RECORD_SIZE};
+use qrs_detector::prelude::*;
+
+#[test]
+fn test_roundtrip_reference_record() {
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_history([Detection::default(); 1]);
+
+    let samples = common::samples();
+
+    let mut encoder = BeatLogEncoder::new();
+    let mut log = Vec::new();
+    let mut detections = Vec::new();
+
+    // The amplitude of a detection is final once the refractory period ends, encode the
+    // previous detection when a new one is found.
+    let mut pending: Option<Detection> = None;
+    for window in samples.windows(3) {
+        if detector.update((window[0] - window[2]).abs()).is_some() {
+            let detection = detector.recent_detections().last().unwrap();
+            if let Some(prev) = pending.replace(detection) {
+                let flags = detections.len() as u8 % 8;
+                let mut record = [0; MAX_RECORD_SIZE];
+                let len = encoder.encode(&prev, flags, &mut record).unwrap();
+                log.extend_from_slice(&record[..len]);
+                detections.push((prev, flags));
+            }
+        }
+    }
+
+    assert!(detections.len() > 50);
+    // Regular rhythm compresses well
+    assert!(log.len() < detections.len() * 3, "{} bytes", log.len());
+
+    let decoded = BeatLogDecoder::new(&log).collect::<Vec<_>>();
+    assert_eq!(detections.len(), decoded.len());
+
+    let mut prev_idx = 0;
+    for ((detection, flags), beat) in detections.iter().zip(decoded) {
+        assert_eq!(detection.index - prev_idx, beat.rr);
+        assert_eq!(*flags, beat.flags);
+        assert!((detection.confidence() - beat.confidence).abs() <= 0.5 / 31.0);
+        prev_idx = detection.index;
+    }
+}
+
+#[test]
+fn test_large_rr_changes() {
+    let mut encoder = BeatLogEncoder::new();
+    let mut log = Vec::new();
+
+    let indices = [u32::MAX / 2, u32::MAX / 2 + 10, u32::MAX - 5, 100];
+    for index in indices {
+        let detection = Detection {
+            index,
+            amplitude: 1.0,
+            threshold: 0.0,
+            ..Default::default()
+        };
+        let mut record = [0; MAX_RECORD_SIZE];
+        let len = encoder.encode(&detection, 7, &mut record).unwrap();
+        log.extend_from_slice(&record[..len]);
+    }
+
+    let mut idx = 0u32;
+    for (expected, beat) in indices.iter().zip(BeatLogDecoder::new(&log)) {
+        idx = idx.wrapping_add(beat.rr);
+        assert_eq!(*expected, idx);
+        assert_eq!(1.0, beat.confidence);
+        assert_eq!(7, beat.flags);
+    }
+}
+
+#[test]
+fn test_buffer_full() {
+    let mut encoder = BeatLogEncoder::new();
+    let detection = Detection {
+        index: 1000,
+        amplitude: 1.0,
+        threshold: 0.5,
+        ..Default::default()
+    };
+
+    let mut small = [0; 2];
+    assert_eq!(Err(BufferFull), encoder.encode(&detection, 0, &mut small));
+
+    // The failed attempt did not change the encoder state
+    let mut record = [0; MAX_RECORD_SIZE];
+    let len = encoder.encode(&detection, 0, &mut record).unwrap();
+    let beat = BeatLogDecoder::new(&record[..len]).next().unwrap();
+    assert_eq!(1000, beat.rr);
+
+    // Truncated records are not decoded
+    assert_eq!(None, BeatLogDecoder::new(&record[..len - 1]).next());
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+
+#[test]
+fn test_chunk_offsets() {
+    let signal = preprocessed();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let expected = signal
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect::<Vec<_>>();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut detections = Vec::new();
+    for (chunk_idx, chunk) in signal.chunks(100).enumerate() {
+        for detection in detector.update_chunk(chunk) {
+            assert!(detection.offset < chunk.len());
+            assert_eq!(detection.index as usize, chunk_idx * 100 + detection.offset);
+            detections.push(detection.index);
+        }
+    }
+
+    assert_eq!(38, detections.len());
+    assert_eq!(expected, detections);
+}
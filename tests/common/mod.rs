@@ -0,0 +1,90 @@
+//! The records shared by the integration tests.
+#![allow(dead_code)]
+
+use qrs_detector::prelude::*;
+use qrs_detector::synth::{SynthParams, Synthesizer};
+use qrs_detector::validation::{evaluate, Metrics};
+
+/// The sampling frequency of the synthetic records.
+pub const SYNTH_FS: f32 = 250.0;
+
+/// The reference record, sampled at 720 sps.
+pub fn samples() -> Vec<f32> {
+    let data = include_str!("../data/aami3a.txt");
+
+    data.split_terminator('\n')
+        .map(|str| str.trim().parse::<f32>().unwrap())
+        .collect()
+}
+
+/// The reference record as ADC codes, with ~100 nV resolution.
+pub fn codes() -> Vec<i32> {
+    samples()
+        .into_iter()
+        .map(|sample| (sample * 10_000.0).round() as i32)
+        .collect()
+}
+
+/// Applies the canonical preprocessing: a 4 sample moving average, followed by the absolute
+/// difference of every other averaged sample.
+pub fn preprocess(samples: &[f32]) -> Vec<f32> {
+    let averaged = samples
+        .windows(4)
+        .map(|window| window.iter().sum::<f32>() / 4.0)
+        .collect::<Vec<_>>();
+
+    averaged
+        .windows(3)
+        .map(|window| (window[0] - window[2]).abs())
+        .collect()
+}
+
+/// The reference record, preprocessed using the canonical filter.
+pub fn preprocessed() -> Vec<f32> {
+    preprocess(&samples())
+}
+
+/// Xorshift noise, uniformly distributed in the 0..1 range.
+pub fn noise(seed: &mut u32) -> f32 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 17;
+    *seed ^= *seed << 5;
+    (*seed % 100_000) as f32 / 100_000.0
+}
+
+/// Feeds the moving average and slope of `seconds` of a synthetic signal to the detector. Beats
+/// of the learning period are not evaluated.
+pub fn run_synth(config: Config, params: SynthParams, seed: u32, seconds: usize) -> Metrics {
+    let learning = 3 * SYNTH_FS as u32;
+    let mut detector = QrsDetector::new::<75, 12>(SYNTH_FS.sps()).with_config(config);
+    let mut window = [0.0; 4];
+    let mut averages = [0.0; 2];
+    let mut peaks = Vec::new();
+    let mut detections = Vec::new();
+
+    for (i, sample) in Synthesizer::new(SYNTH_FS.sps(), params, seed)
+        .take(seconds * SYNTH_FS as usize)
+        .enumerate()
+    {
+        if sample.r_peak && i as u32 > learning {
+            peaks.push(i as u32);
+        }
+
+        window = [window[1], window[2], window[3], sample.value];
+        let average = window.iter().sum::<f32>() / 4.0;
+        let slope = (averages[0] - average).abs();
+        averages = [averages[1], average];
+        if let Some(idx) = detector.update(slope).filter(|&idx| idx > learning) {
+            detections.push(idx);
+        }
+    }
+
+    evaluate(SYNTH_FS.sps(), &peaks, detections)
+}
+
+/// Sums the metrics of `seconds` long synthetic records with three different seeds.
+pub fn total_synth(config: Config, params: SynthParams, seconds: usize) -> Metrics {
+    (1..=3)
+        .map(|seed| run_synth(config, params, seed, seconds))
+        .fold(Metrics::default(), |sum, metrics| sum + metrics)
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::component::{ComponentInput, ThresholdComponent};
+use qrs_detector::prelude::*;
+
+/// A beat expectation threshold that records the calls it receives.
+#[derive(Default)]
+struct Recording {
+    detections: Vec<u32>,
+    clears: usize,
+    refractory_samples: usize,
+    configured: bool,
+}
+
+impl ThresholdComponent for Recording {
+    fn update(&mut self, input: &ComponentInput) {
+        if input.in_refractory {
+            assert_eq!(None, input.m);
+            self.refractory_samples += 1;
+        }
+    }
+
+    fn threshold(&self) -> Option<f32> {
+        None
+    }
+
+    fn detection_event(&mut self, index: u32, _sample: f32) {
+        self.detections.push(index);
+    }
+
+    fn clear(&mut self) {
+        self.clears += 1;
+    }
+
+    fn configure(&mut self, _config: &Config) {
+        self.configured = true;
+    }
+}
+
+#[test]
+fn test_custom_r_component() {
+    let signal = preprocessed();
+
+    let mut stock = QrsDetector::new::<216, 36>(720.sps());
+    let stock_detections = signal
+        .iter()
+        .filter_map(|&sample| stock.update(sample))
+        .collect::<Vec<_>>();
+
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_r_component(Recording::default());
+    let mut detections = Vec::new();
+    for &sample in signal.iter() {
+        detections.extend(detector.update(sample));
+        assert_eq!(0.0, detector.thresholds().r);
+    }
+    assert_eq!(Ok(()), detector.verify());
+
+    // Without the beat expectation threshold, the same beats are found on a clean record
+    assert!(
+        detections.len().abs_diff(stock_detections.len()) <= 2,
+        "{} != {}",
+        detections.len(),
+        stock_detections.len()
+    );
+
+    let component = detector.r_component();
+    assert!(component.configured);
+    assert_eq!(detections, component.detections);
+    assert!(component.refractory_samples > detections.len() * 100);
+
+    detector.clear();
+    assert_eq!(1, detector.r_component().clears);
+}
+
+#[test]
+fn test_custom_f_component() {
+    let signal = preprocessed();
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_f_component(Recording::default());
+
+    // F is never initialized, so nothing is detected
+    assert!(signal
+        .iter()
+        .all(|&sample| detector.update(sample).is_none()));
+    assert_eq!(None, detector.thresholds().total());
+    assert!(detector.f_component().detections.is_empty());
+}
@@ -0,0 +1,462 @@
+mod common;
+
+use qrs_detector::config::{
+    ConfigDescription, DescriptionError, DifferenceLag, Features, InputMode, MDecay,
+    DESCRIPTION_VERSION,
+};
+use qrs_detector::prelude::*;
+
+fn count_detections(config: Config) -> usize {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps()).with_config(config);
+
+    let samples = common::samples();
+
+    samples
+        .windows(3)
+        .filter(|window| detector.update((window[0] - window[2]).abs()).is_some())
+        .count()
+}
+
+/// Returns the refreshed M value after each of 1 Hz pulses, which triple in amplitude at 10s.
+fn m_after_amplitude_change(config: Config) -> Vec<f32> {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps()).with_config(config);
+    let mut values = Vec::new();
+    let mut prev_m = None;
+
+    for i in 0..20 * 250 {
+        let amplitude = if i < 10 * 250 { 1.0 } else { 3.0 };
+        detector.update(if i % 250 < 5 { amplitude } else { 0.0 });
+
+        let m = detector.thresholds().m;
+        if let (None, Some(m)) = (prev_m, m) {
+            values.push(m);
+        }
+        prev_m = m;
+    }
+
+    values
+}
+
+/// Returns the M threshold values following the initialization on a single pulse of amplitude 1.
+fn m_decay_curve(config: Config) -> Vec<f32> {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps()).with_config(config);
+
+    (0..6 * 250)
+        .filter_map(|i| {
+            detector.update(if i == 100 { 1.0 } else { 0.0 });
+            detector.thresholds().m
+        })
+        .collect()
+}
+
+#[test]
+fn test_mm_depth_extremes() {
+    let reference = count_detections(Config::new());
+
+    for depth in [3, 8] {
+        let detections = count_detections(Config::new().with_mm_depth(depth));
+        assert!(
+            detections.abs_diff(reference) <= 2,
+            "depth {}: {} detections, expected {}",
+            depth,
+            detections,
+            reference
+        );
+    }
+}
+
+#[test]
+fn test_shorter_mm_adapts_faster() {
+    let short = m_after_amplitude_change(Config::new().with_mm_depth(3));
+    let long = m_after_amplitude_change(Config::new().with_mm_depth(8));
+
+    // Before the change, the thresholds are equal
+    assert!((short[5] - long[5]).abs() < 1e-6);
+
+    // After a few beats, the shorter buffer is closer to the new amplitude
+    let idx = short.len() - 5;
+    assert!(short[idx] > long[idx], "{} <= {}", short[idx], long[idx]);
+}
+
+#[test]
+#[should_panic]
+fn test_mm_depth_too_small() {
+    Config::new().with_mm_depth(2);
+}
+
+#[test]
+#[should_panic]
+fn test_mm_depth_too_large() {
+    Config::new().with_mm_depth(9);
+}
+
+#[test]
+fn test_rr_depth_extremes() {
+    let reference = count_detections(Config::new());
+
+    for depth in [2, 8] {
+        let detections = count_detections(Config::new().with_rr_depth(depth));
+        assert!(
+            detections.abs_diff(reference) <= 2,
+            "depth {}: {} detections, expected {}",
+            depth,
+            detections,
+            reference
+        );
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_rr_depth_too_small() {
+    Config::new().with_rr_depth(1);
+}
+
+#[test]
+#[should_panic]
+fn test_rr_depth_too_large() {
+    Config::new().with_rr_depth(9);
+}
+
+#[test]
+#[should_panic]
+fn test_refractory_too_short() {
+    Config::new().with_refractory_ms(0.0);
+}
+
+#[test]
+#[should_panic]
+fn test_refractory_too_long() {
+    Config::new().with_refractory_ms(1000.0);
+}
+
+#[test]
+fn test_m_decay_shapes() {
+    for decay in [
+        MDecay::PAPER,
+        MDecay::Linear {
+            fraction: 0.5,
+            duration_ms: 500.0,
+        },
+        MDecay::Exponential {
+            fraction: 0.4,
+            duration_ms: 1000.0,
+        },
+    ] {
+        let curve = m_decay_curve(Config::new().with_m_decay(decay));
+
+        // The refreshed M value is 0.6 * max
+        assert!((curve[0] - 0.6).abs() < 1e-6);
+
+        // The decrease is monotonic
+        assert!(curve.windows(2).all(|w| w[1] <= w[0]), "{:?}", decay);
+
+        // The end value is reached after the configured duration, and remains constant
+        let end = 0.6 * (1.0 - decay.fraction());
+        let end_idx = 250 * decay.duration_ms() as usize / 1000 + 1;
+        assert!(
+            curve[end_idx..].iter().all(|m| (m - end).abs() < 1e-4),
+            "{:?}",
+            decay
+        );
+        assert!(curve[end_idx - 2] > end + 1e-4, "{:?}", decay);
+    }
+}
+
+#[test]
+fn test_m_decay_shorter_than_a_sample() {
+    for decay in [
+        MDecay::Linear {
+            fraction: 0.4,
+            duration_ms: 1.0,
+        },
+        MDecay::Exponential {
+            fraction: 0.4,
+            duration_ms: 1.0,
+        },
+    ] {
+        let curve = m_decay_curve(Config::new().with_m_decay(decay));
+
+        // M drops to the end value in a single sample
+        assert!(curve.iter().all(|m| m.is_finite()), "{:?}", decay);
+        assert!((curve[1] - 0.36).abs() < 1e-6, "{:?}", decay);
+    }
+}
+
+#[test]
+fn test_exponential_decay_is_faster_early() {
+    let linear = m_decay_curve(Config::new());
+    let exponential = m_decay_curve(Config::new().with_m_decay(MDecay::Exponential {
+        fraction: 0.4,
+        duration_ms: 1000.0,
+    }));
+
+    assert!(exponential[50] < linear[50]);
+}
+
+#[test]
+#[should_panic]
+fn test_m_decay_invalid_fraction() {
+    Config::new().with_m_decay(MDecay::Linear {
+        fraction: 1.5,
+        duration_ms: 1000.0,
+    });
+}
+
+/// Returns the R threshold values in the last second of 1 Hz pulses.
+fn r_curve(config: Config) -> Vec<f32> {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps()).with_config(config);
+
+    (0..15 * 250)
+        .map(|i| {
+            detector.update(if i % 250 < 5 { 1.0 } else { 0.0 });
+            detector.thresholds().r
+        })
+        .skip(14 * 250)
+        .collect()
+}
+
+#[test]
+fn test_r_parameters() {
+    let paper = r_curve(Config::new());
+
+    // R does not decrease in the first 2/3 of the RR interval, then decreases
+    let first_negative = paper.iter().position(|&r| r < 0.0).unwrap();
+    assert!((165..=170).contains(&first_negative), "{}", first_negative);
+
+    // A later start results in a smaller decrease
+    let delayed = r_curve(Config::new().with_r_delay(0.8));
+    let first_negative = delayed.iter().position(|&r| r < 0.0).unwrap();
+    assert!((198..=203).contains(&first_negative), "{}", first_negative);
+    assert!(delayed[249] > paper[249]);
+
+    // The slope of R is proportional to the weight
+    let double = r_curve(Config::new().with_r_weight(2.0 / 1.4));
+    for (r, r2) in paper.iter().zip(double.iter()) {
+        assert!((2.0 * r - r2).abs() < 1e-6, "{} {}", r, r2);
+    }
+
+    // R is tied to the decrease of M
+    let slow_m = r_curve(Config::new().with_m_decay(MDecay::Linear {
+        fraction: 0.2,
+        duration_ms: 1000.0,
+    }));
+    assert!((paper[249] - 2.0 * slow_m[249]).abs() < 1e-6);
+
+    // Zero weight disables R
+    let disabled = r_curve(Config::new().with_r_weight(0.0));
+    assert!(disabled.iter().all(|&r| r == 0.0));
+}
+
+#[test]
+fn test_calibration() {
+    let config = Config::new().with_calibration(200.0);
+    let mut detector = QrsDetector::new::<75, 12>(250.sps())
+        .with_config(config)
+        .with_history([Detection::default(); 4]);
+
+    for i in 0..10 * 250 {
+        detector.update(if i % 250 < 5 { 100.0 } else { 0.0 });
+    }
+
+    let thresholds = detector.thresholds();
+    let thresholds_mv = detector.thresholds_mv().unwrap();
+    assert!(thresholds_mv.approx_eq(&(thresholds / 200.0), 1e-6));
+    assert_eq!(
+        Some(0.5),
+        detector
+            .recent_detections_mv()
+            .unwrap()
+            .last()
+            .map(|d| d.amplitude)
+    );
+    assert_eq!(Some(0.5), detector.to_mv(100.0));
+
+    let uncalibrated = QrsDetector::new::<75, 12>(250.sps());
+    assert_eq!(None, uncalibrated.thresholds_mv());
+    assert!(uncalibrated.recent_detections_mv().is_none());
+}
+
+#[test]
+#[should_panic]
+fn test_calibration_not_positive() {
+    Config::new().with_calibration(-200.0);
+}
+
+#[test]
+#[should_panic]
+fn test_calibration_zero() {
+    Config::new().with_calibration(0.0);
+}
+
+#[test]
+fn test_description_round_trip() {
+    let config = Config::adaptive()
+        .with_mm_depth(3)
+        .with_m_decay(MDecay::Exponential {
+            fraction: 0.5,
+            duration_ms: 800.0,
+        })
+        .with_r_floor(0.25)
+        .with_skip_diagnostics(true)
+        .with_calibration(1000.0)
+        .with_dc_blocker(0.5)
+        .with_input_mode(InputMode::SquaredDiff)
+        .with_difference_lag(DifferenceLag::Scaled);
+
+    let description = config.describe();
+    assert_eq!(DESCRIPTION_VERSION, description.version);
+    assert_eq!(Ok(config), Config::from_description(&description));
+
+    let text = description.to_string();
+    assert!(
+        text.starts_with(&format!(
+            "version={DESCRIPTION_VERSION} mm_depth=3 m_decay=exponential(0.5,800) "
+        )),
+        "{text}"
+    );
+    assert!(text.contains(" calibration=1000 "), "{text}");
+    assert!(text.contains(" dc_blocker=0.5 "), "{text}");
+    assert!(
+        text.contains(" input=squared_diff difference_lag=scaled "),
+        "{text}"
+    );
+}
+
+#[test]
+fn test_description_of_earlier_version() {
+    let config = Config::new()
+        .with_mm_depth(3)
+        .with_pace_blanking(100.0)
+        .with_z_score(5.0)
+        .with_step_blanking(true);
+    let description = config.describe();
+
+    // The z-score normalization and DC step blanking were added in versions 10 and 11
+    let restored = Config::from_description(&ConfigDescription {
+        version: 9,
+        ..description
+    });
+    assert_eq!(
+        Ok(Config::new().with_mm_depth(3).with_pace_blanking(100.0)),
+        restored
+    );
+
+    // Every parameter added since the first version takes its default value
+    let restored = Config::from_description(&ConfigDescription {
+        version: 1,
+        refractory_ms: f32::NAN,
+        ..description
+    });
+    assert_eq!(Ok(Config::new().with_mm_depth(3)), restored);
+}
+
+#[test]
+fn test_invalid_description() {
+    let description = Config::new().describe();
+
+    for version in [0, DESCRIPTION_VERSION + 1] {
+        assert_eq!(
+            Err(DescriptionError::UnsupportedVersion(version)),
+            Config::from_description(&ConfigDescription {
+                version,
+                ..description
+            })
+        );
+    }
+    assert_eq!(
+        Err(DescriptionError::Invalid),
+        Config::from_description(&ConfigDescription {
+            mm_depth: 9,
+            ..description
+        })
+    );
+    assert_eq!(
+        Err(DescriptionError::Invalid),
+        Config::from_description(&ConfigDescription {
+            r_delay: f32::NAN,
+            ..description
+        })
+    );
+    assert_eq!(
+        Err(DescriptionError::Invalid),
+        Config::from_description(&ConfigDescription {
+            calibration: Some(-200.0),
+            ..description
+        })
+    );
+    assert_eq!(
+        Err(DescriptionError::Invalid),
+        Config::from_description(&ConfigDescription {
+            rr_depth: 1,
+            ..description
+        })
+    );
+    assert_eq!(
+        Err(DescriptionError::Invalid),
+        Config::from_description(&ConfigDescription {
+            refractory_ms: 0.0,
+            ..description
+        })
+    );
+    assert_eq!(
+        Err(DescriptionError::Invalid),
+        Config::from_description(&ConfigDescription {
+            dc_blocker: Some(-0.5),
+            ..description
+        })
+    );
+    assert_eq!(
+        Err(DescriptionError::Invalid),
+        Config::from_description(&ConfigDescription {
+            difference_lag: DifferenceLag::Samples(0),
+            ..description
+        })
+    );
+    assert_eq!(
+        Err(DescriptionError::FeatureMismatch),
+        Config::from_description(&ConfigDescription {
+            features: Features {
+                quality: !description.features.quality,
+                ..description.features
+            },
+            ..description
+        })
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_validates_config() {
+    let config = Config::new().with_mm_depth(3).with_rr_depth(8);
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(config, serde_json::from_str::<Config>(&json).unwrap());
+
+    for (depth, value) in [("mm_depth", 9), ("rr_depth", 9), ("rr_depth", 0)] {
+        let mut description = serde_json::to_value(config).unwrap();
+        description[depth] = value.into();
+
+        let error = serde_json::from_value::<Config>(description).unwrap_err();
+        assert_eq!("invalid parameters", error.to_string());
+    }
+}
+
+#[test]
+fn test_algorithm_version() {
+    let version = |config: Config| {
+        QrsDetector::new::<75, 12>(250.sps())
+            .with_config(config)
+            .algorithm_version()
+    };
+
+    assert_eq!(version(Config::new()), version(Config::default()));
+    assert_ne!(
+        version(Config::new()),
+        version(Config::new().with_mm_depth(4))
+    );
+    assert_ne!(version(Config::new()), version(Config::adaptive()));
+    assert_ne!(
+        version(Config::new()),
+        version(Config::new().with_calibration(1000.0))
+    );
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "quality")]
+
+mod common;
+
+use qrs_detector::config::Config;
+use qrs_detector::sampling::*;
+use qrs_detector::QrsDetector;
+
+/// Runs the detector with step blanking on the reference record with a DC offset step added at
+/// `step_at`.
+fn detect_with_step(step_at: usize, step: f32) -> Vec<u32> {
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_config(Config::new().with_step_blanking(true));
+
+    let mut samples = common::samples();
+
+    for sample in &mut samples[step_at..] {
+        *sample += step;
+    }
+
+    samples
+        .windows(3)
+        .filter_map(|window| detector.update((window[0] - window[2]).abs()))
+        .collect()
+}
+
+#[test]
+fn test_step_during_initialization() {
+    let reference = detect_with_step(0, 0.0);
+    let detections = detect_with_step(1500, 5.0);
+
+    // Without recovery, the initial M threshold would be set by the step and no QRS complex
+    // would ever be detected.
+    assert!(
+        detections.len() + 2 >= reference.len(),
+        "{} detections, expected {}",
+        detections.len(),
+        reference.len()
+    );
+}
+
+#[test]
+fn test_step_after_initialization() {
+    let reference = detect_with_step(0, 0.0);
+
+    for step in [1.0, 5.0, 50.0] {
+        let detections = detect_with_step(15000, step);
+
+        // The step itself is not detected
+        assert!(!detections.iter().any(|&idx| (14995..15100).contains(&idx)));
+
+        // Detection resumes quickly
+        let first_after = detections.iter().find(|&&idx| idx > 15000).unwrap();
+        assert!(*first_after < 15000 + 3 * 720, "{}", first_after);
+
+        let after = |d: &[u32]| d.iter().filter(|&&idx| idx > 17000).count();
+        assert_eq!(after(&reference), after(&detections));
+    }
+}
+
+#[test]
+fn test_step_blanking_is_opt_in() {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps());
+    assert!(!detector.config().step_blanking());
+
+    for i in 0..5000 {
+        let step = if i >= 4100 { 50.0 } else { 0.0 };
+        detector.update(if i % 250 < 5 { 1.0 } else { 0.0 } + step);
+    }
+    assert!(!detector.take_signal_step());
+}
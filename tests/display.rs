@@ -0,0 +1,124 @@
+use core::fmt::Write;
+
+#[cfg(feature = "hrv")]
+use qrs_detector::summary::PeriodSummary;
+use qrs_detector::{Detection, SuppressedCounts, Thresholds};
+
+/// A fixed size buffer, as used on a target without allocation.
+struct Buffer {
+    data: [u8; 128],
+    len: usize,
+}
+
+impl Buffer {
+    fn format(value: impl core::fmt::Display) -> Self {
+        let mut buffer = Self {
+            data: [0; 128],
+            len: 0,
+        };
+        write!(buffer, "{value}").expect("Output does not fit into 128 bytes");
+        buffer
+    }
+
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.data[..self.len]).unwrap()
+    }
+}
+
+impl Write for Buffer {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        self.data
+            .get_mut(self.len..end)
+            .ok_or(core::fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_display() {
+    let detection = Detection {
+        index: 1234,
+        amplitude: 0.75,
+        threshold: 0.3,
+        ..Default::default()
+    };
+    assert_eq!(
+        "#1234 amp=0.750 thr=0.300",
+        Buffer::format(detection).as_str()
+    );
+    assert_eq!(
+        "#1234 amp=0.750 thr=0.300 paced",
+        Buffer::format(Detection {
+            paced: true,
+            ..detection
+        })
+        .as_str()
+    );
+
+    let counts = SuppressedCounts {
+        refractory: 3,
+        signal_step: 1,
+        noise: 0,
+    };
+    assert_eq!(
+        "refractory=3 step=1 noise=0",
+        Buffer::format(counts).as_str()
+    );
+}
+
+#[test]
+fn test_display_extreme_values_fit() {
+    let thresholds = Thresholds {
+        m: Some(f32::MAX),
+        f: Some(-f32::MAX),
+        r: f32::MIN_POSITIVE,
+    };
+    assert_eq!(
+        "M=3.403e38 F=-3.403e38 R=0.000",
+        Buffer::format(thresholds).as_str()
+    );
+
+    Buffer::format(Detection {
+        index: u32::MAX,
+        amplitude: f32::NAN,
+        threshold: f32::NEG_INFINITY,
+        paced: true,
+        ..Default::default()
+    });
+    Buffer::format(SuppressedCounts {
+        refractory: u32::MAX,
+        signal_step: u32::MAX,
+        noise: u32::MAX,
+    });
+}
+
+#[cfg(feature = "hrv")]
+#[test]
+fn test_display_summary() {
+    let summary = PeriodSummary {
+        beats: 3600,
+        min_bpm: 52,
+        avg_bpm: 60,
+        max_bpm: 75,
+        pauses: 1,
+        af_burden_percent: 2,
+        noise_percent: 5,
+    };
+    assert_eq!(
+        "beats=3600 hr=52/60/75 pauses=1 af=2% noise=5%",
+        Buffer::format(summary).as_str()
+    );
+
+    Buffer::format(PeriodSummary {
+        beats: u32::MAX,
+        min_bpm: u16::MAX,
+        avg_bpm: u16::MAX,
+        max_bpm: u16::MAX,
+        pauses: u16::MAX,
+        af_burden_percent: u8::MAX,
+        noise_percent: u8::MAX,
+    });
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "preprocessing")]
+
+mod common;
+
+use common::{noise, samples};
+use qrs_detector::prelude::*;
+use qrs_detector::preprocessing::{moving_average, Fir, Median};
+use qrs_detector::transform::SampleTransform;
+use qrs_detector::validation::{Metrics, NoiseStress};
+
+/// The record with bursts of muscle noise: 1.5 seconds of broadband noise every 6 seconds.
+fn with_emg_bursts(amplitude: f32) -> Vec<f32> {
+    let mut seed = 12345;
+    samples()
+        .into_iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            let noise = noise(&mut seed) - 0.5;
+            if i % (6 * 720) < 1080 {
+                sample + amplitude * noise
+            } else {
+                sample
+            }
+        })
+        .collect()
+}
+
+/// Moving average and slope, see the other tests.
+fn pipeline() -> impl SampleTransform {
+    let mut prev = [0.0; 2];
+    Fir::new(moving_average::<4>()).chain(move |sample: f32| {
+        let slope = (prev[0] - sample).abs();
+        prev = [prev[1], sample];
+        slope
+    })
+}
+
+fn detect(samples: &[f32], transform: impl SampleTransform) -> Vec<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps()).with_transform(transform);
+    samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect()
+}
+
+/// Returns the number of missed and false detections, compared to `reference`.
+fn errors(reference: &[u32], detections: &[u32]) -> usize {
+    let tolerance = 72;
+    let matches = |a: &[u32], b: &[u32]| {
+        a.iter()
+            .filter(|&&x| b.iter().any(|&y| x.abs_diff(y) <= tolerance))
+            .count()
+    };
+    (reference.len() - matches(reference, detections))
+        + (detections.len() - matches(detections, reference))
+}
+
+#[test]
+fn test_median_filter_suppresses_emg_bursts() {
+    let clean = samples();
+    let reference = detect(&clean, pipeline());
+    assert_eq!(38, reference.len());
+
+    // The median filter does not change the detections in the clean signal
+    let filtered = detect(&clean, Median::<9>::new().chain(pipeline()));
+    assert_eq!(0, errors(&reference, &filtered));
+
+    for amplitude in [0.2, 0.3] {
+        let noisy = with_emg_bursts(amplitude);
+
+        let unfiltered = errors(&reference, &detect(&noisy, pipeline()));
+        let filtered = errors(
+            &reference,
+            &detect(&noisy, Median::<9>::new().chain(pipeline())),
+        );
+
+        assert!(
+            filtered * 3 <= unfiltered,
+            "amplitude {amplitude}: {filtered} errors, {unfiltered} without filtering"
+        );
+    }
+}
+
+/// A muscle noise record: 1.5 seconds of broadband noise every 6 seconds. The `ma` record of the
+/// MIT-BIH Noise Stress Test Database is not included with the tests, this record has the same
+/// bursty character.
+fn muscle_noise_record() -> Vec<f32> {
+    let mut seed = 54321;
+    (0..6 * 720)
+        .map(|i| {
+            let noise = noise(&mut seed) - 0.5;
+            if i < 1080 {
+                noise
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_median_filter_noise_stress() {
+    let clean = samples();
+    let reference = detect(&clean, pipeline());
+    let noise = muscle_noise_record();
+
+    let stress = NoiseStress::new(720.sps(), &clean, &reference, &noise);
+    let snrs = [24.0, 18.0, 12.0, 6.0, 0.0];
+    let unfiltered = stress
+        .curve(&snrs, || {
+            let mut detector = QrsDetector::new::<216, 36>(720.sps()).with_transform(pipeline());
+            move |sample| detector.update(sample)
+        })
+        .collect::<Vec<_>>();
+    let filtered = stress
+        .curve(&snrs, || {
+            let mut detector = QrsDetector::new::<216, 36>(720.sps())
+                .with_transform(Median::<15>::new().chain(pipeline()));
+            move |sample| detector.update(sample)
+        })
+        .collect::<Vec<_>>();
+
+    let errors = |metrics: Metrics| metrics.false_positives + metrics.false_negatives;
+    assert_eq!(0, errors(filtered[0].metrics));
+    for (filtered, unfiltered) in filtered.iter().zip(unfiltered.iter()) {
+        assert!(
+            errors(filtered.metrics) <= errors(unfiltered.metrics),
+            "{} dB: {:?} {:?}",
+            filtered.snr_db,
+            filtered.metrics,
+            unfiltered.metrics
+        );
+    }
+
+    // The filter makes the most difference at moderate noise levels
+    assert!(errors(filtered[2].metrics) * 3 <= errors(unfiltered[2].metrics));
+    assert!(filtered[3].metrics.sensitivity() > 0.85);
+    assert!(unfiltered[3].metrics.sensitivity() < 0.5);
+}
+
+#[test]
+fn test_median_filter() {
+    let mut median = Median::<5>::new();
+    assert_eq!(2.0, median.group_delay());
+
+    let output = [1.0, 2.0, 9.0, 3.0, 4.0, -9.0, 5.0].map(|sample| median.transform(sample));
+    assert_eq!([0.0, 0.0, 1.0, 2.0, 3.0, 3.0, 4.0], output);
+
+    median.clear();
+    assert_eq!(0.0, median.transform(1.0));
+}
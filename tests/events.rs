@@ -0,0 +1,85 @@
+use qrs_detector::events::{Debounce, Event, EventDebouncer};
+
+#[test]
+fn test_debounce_onset_and_offset() {
+    let mut debouncer = EventDebouncer::new().with_debounce(
+        Event::Tachycardia,
+        Debounce {
+            onset: 3,
+            offset: 2,
+        },
+    );
+
+    let fast = Some(Event::Tachycardia);
+    let beats = [
+        fast, fast, None, // Interrupted before the onset
+        fast, fast, fast, // Started
+        None, fast, None, None, // Ended after 2 normal beats in a row
+    ];
+    let states = beats
+        .iter()
+        .map(|&beat| {
+            (
+                debouncer.update(beat),
+                debouncer.is_active(Event::Tachycardia),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        [
+            (None, false),
+            (None, false),
+            (None, false),
+            (None, false),
+            (None, false),
+            (fast, true),
+            (None, true),
+            (None, true),
+            (None, true),
+            (None, false),
+        ],
+        states[..]
+    );
+}
+
+#[test]
+fn test_events_are_debounced_separately() {
+    let mut debouncer = EventDebouncer::new().with_debounce(
+        Event::Bradycardia,
+        Debounce {
+            onset: 2,
+            offset: 4,
+        },
+    );
+
+    // Without debouncing, events start and end immediately
+    assert_eq!(Some(Event::Pause), debouncer.update(Some(Event::Pause)));
+    assert_eq!(None, debouncer.update(Some(Event::Bradycardia)));
+    assert!(!debouncer.is_active(Event::Pause));
+
+    assert_eq!(
+        Some(Event::Bradycardia),
+        debouncer.update(Some(Event::Bradycardia))
+    );
+    assert_eq!(Some(Event::Pause), debouncer.update(Some(Event::Pause)));
+    assert_eq!(
+        vec![Event::Pause, Event::Bradycardia],
+        debouncer.active().collect::<Vec<_>>()
+    );
+
+    debouncer.clear();
+    assert_eq!(0, debouncer.active().count());
+}
+
+#[test]
+#[should_panic]
+fn test_zero_onset() {
+    EventDebouncer::new().with_debounce(
+        Event::Pause,
+        Debounce {
+            onset: 0,
+            offset: 1,
+        },
+    );
+}
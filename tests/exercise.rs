@@ -0,0 +1,47 @@
+mod common;
+
+use common::total_synth;
+use qrs_detector::prelude::*;
+use qrs_detector::synth::SynthParams;
+
+/// The length of the synthetic records, in seconds.
+const SECONDS: usize = 120;
+
+fn errors(config: Config, params: SynthParams) -> u32 {
+    let metrics = total_synth(config, params, SECONDS);
+    metrics.false_positives + metrics.false_negatives
+}
+
+/// The heart rate increases from 70 to 190 bpm in 10 seconds, while the QRS amplitude drops.
+const RAMP: SynthParams = SynthParams {
+    heart_rate_bpm: 70.0,
+    final_heart_rate_bpm: 190.0,
+    ramp_s: 10.0,
+    final_r_amplitude_mv: 0.4,
+    noise_mv: 0.1,
+    ..SynthParams::EXERCISE
+};
+
+#[test]
+fn test_exercise_preset_follows_heart_rate_ramp() {
+    let default = errors(Config::new(), RAMP);
+    let exercise = errors(Config::exercise(), RAMP);
+
+    assert!(default > 10, "{default}");
+    assert!(exercise <= 1, "{exercise}");
+}
+
+#[test]
+fn test_exercise_preset_on_other_rhythms() {
+    for params in [
+        SynthParams::REST,
+        SynthParams::EXERCISE,
+        SynthParams::ATRIAL_FIBRILLATION,
+    ] {
+        assert_eq!(
+            errors(Config::new(), params),
+            errors(Config::exercise(), params),
+            "{params:?}"
+        );
+    }
+}
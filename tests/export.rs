@@ -0,0 +1,104 @@
+#![cfg(all(feature = "io", feature = "serde", feature = "hrv"))]
+
+mod common;
+
+use core::time::Duration;
+
+use common::preprocessed;
+use qrs_detector::export::{HealthExporter, HeartRateSample};
+use qrs_detector::prelude::*;
+use qrs_detector::session::SessionAnalyzer;
+use qrs_detector::time::EpochAnchor;
+
+#[test]
+fn test_export_formats() {
+    let anchor = EpochAnchor::new(500.sps(), 1_000_000);
+    let mut exporter = HealthExporter::new(anchor).with_heart_rate_interval(Duration::from_secs(2));
+
+    for index in [500, 1000, 1500, 2000, 2250] {
+        exporter.push_detection(index);
+    }
+    // Beats separated by a gap
+    exporter.interrupt();
+    exporter.push_detection(10_000);
+    exporter.push_detection(10_400);
+
+    let export = exporter.into_export();
+    assert_eq!(1_001_000, export.start_time_ms);
+    assert_eq!(1_020_800, export.end_time_ms);
+    assert_eq!(vec![1000, 1000, 1000, 500, 800], export.rr_ms);
+    assert_eq!(
+        vec![HeartRateSample {
+            time_ms: 1_003_000,
+            bpm: 60.0
+        }],
+        export.heart_rate
+    );
+
+    let mut json = Vec::new();
+    export.write_json(&mut json).unwrap();
+    assert_eq!(
+        "{\"start_time_ms\":1001000,\"end_time_ms\":1020800,\"rr_ms\":[1000,1000,1000,500,800],\
+         \"heart_rate\":[{\"time_ms\":1003000,\"bpm\":60.0}],\"algorithm_version\":null}",
+        String::from_utf8(json).unwrap()
+    );
+
+    let mut csv = Vec::new();
+    export.write_heart_rate_csv(&mut csv).unwrap();
+    assert_eq!(
+        "time_ms,bpm\n1003000,60.0\n",
+        String::from_utf8(csv).unwrap()
+    );
+
+    let mut csv = Vec::new();
+    export.write_rr_csv(&mut csv).unwrap();
+    assert_eq!(
+        "rr_ms\n1000\n1000\n1000\n500\n800\n",
+        String::from_utf8(csv).unwrap()
+    );
+}
+
+#[test]
+fn test_export_session() {
+    let signal = preprocessed();
+    let mut analyzer = SessionAnalyzer::new(QrsDetector::new::<216, 36>(720.sps()));
+    let mut exporter = HealthExporter::new(EpochAnchor::new(720.sps(), 0))
+        .with_algorithm_version(analyzer.detector().algorithm_version());
+
+    let mut detections = Vec::new();
+    for &sample in signal.iter() {
+        if let Some(index) = analyzer.push(sample) {
+            detections.push(index);
+            exporter.push_detection(index);
+        }
+    }
+
+    let export = exporter.export();
+    assert_eq!(detections.len() - 1, export.rr_ms.len());
+    assert_eq!(
+        (detections[0] as f32 / 0.72).round() as u64,
+        export.start_time_ms
+    );
+
+    // The heart rate samples agree with the heart rate of the session
+    let heart_rate = analyzer.report().heart_rate.unwrap();
+    let last = export.heart_rate.last().unwrap();
+    assert!(export.heart_rate.len() >= 4);
+    assert!(
+        (last.bpm - heart_rate).abs() < 10.0,
+        "{last:?} {heart_rate}"
+    );
+
+    assert_eq!(
+        Some(analyzer.report().algorithm_version),
+        export.algorithm_version
+    );
+
+    let mut json = Vec::new();
+    export.write_json(&mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.ends_with(&format!(
+        ",\"algorithm_version\":{}}}",
+        analyzer.report().algorithm_version
+    )));
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::noise;
+use qrs_detector::prelude::*;
+
+/// Returns the F threshold before and after six hours of noise, with the same signal in the F
+/// windows.
+fn f_before_and_after_noise(config: Config) -> (f32, f32) {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps()).with_config(config);
+
+    let period = 250;
+    let periodic = |i: usize| {
+        let phase = i % period;
+        if phase < 10 {
+            1.0 + phase as f32 * 0.137
+        } else {
+            0.5
+        }
+    };
+
+    let mut seed = 12345;
+    let mut i = 0;
+
+    for _ in 0..20 * period {
+        detector.update(periodic(i));
+        i += 1;
+    }
+    let reference = detector.thresholds().f.unwrap();
+
+    // Six hours of noise in the 0.5..3.5 range, large enough to keep F away from its lower bound
+    for _ in 0..6 * 3600 * 250 {
+        detector.update(0.5 + 3.0 * noise(&mut seed));
+        i += 1;
+    }
+    while i % period != 0 {
+        detector.update(0.5 + 3.0 * noise(&mut seed));
+        i += 1;
+    }
+
+    // Once the F windows contain the same samples again, F must return to the reference value.
+    for _ in 0..20 * period {
+        detector.update(periodic(i));
+        i += 1;
+    }
+    let f = detector.thresholds().f.unwrap();
+
+    (reference, f)
+}
+
+#[test]
+fn test_f_threshold_does_not_drift() {
+    let (reference, f) = f_before_and_after_noise(Config::new());
+
+    assert!(
+        ((f - reference) / reference).abs() < 1e-5,
+        "F drifted from {} to {}",
+        reference,
+        f
+    );
+}
+
+#[test]
+fn test_f_threshold_does_not_drift_with_f64_accumulation() {
+    let (reference, f) = f_before_and_after_noise(Config::new().with_f64_accumulation(true));
+
+    assert!(
+        ((f - reference) / reference).abs() < 1e-6,
+        "F drifted from {} to {}",
+        reference,
+        f
+    );
+
+    // Both accumulation modes follow the same signal
+    let (reference_f32, _) = f_before_and_after_noise(Config::new());
+    assert!(((reference - reference_f32) / reference).abs() < 1e-5);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "ffi")]
+
+mod common;
+
+use std::ptr;
+
+use common::preprocessed;
+use qrs_detector::ffi::{
+    qrs_detector_clear, qrs_detector_free, qrs_detector_new, qrs_detector_update,
+};
+use qrs_detector::prelude::*;
+
+#[test]
+fn test_ffi_matches_detector() {
+    let samples = preprocessed();
+
+    let mut detector = QrsDetector::new_alloc(720.sps());
+    let reference = |detector: &mut QrsDetector<_, _>| {
+        samples
+            .iter()
+            .filter_map(|&sample| detector.update(sample))
+            .collect::<Vec<_>>()
+    };
+    let expected = reference(&mut detector);
+    detector.clear();
+    let expected_after_clear = reference(&mut detector);
+
+    let handle = qrs_detector_new(720.0);
+    assert!(!handle.is_null());
+
+    let run = || {
+        let mut detections = Vec::new();
+        for &sample in &samples {
+            let mut index = 0;
+            if unsafe { qrs_detector_update(handle, sample, &mut index) } {
+                detections.push(index);
+            }
+        }
+        detections
+    };
+
+    assert!(!expected.is_empty());
+    assert_eq!(expected, run());
+
+    unsafe { qrs_detector_clear(handle) };
+    assert_eq!(expected_after_clear, run());
+
+    unsafe { qrs_detector_free(handle) };
+}
+
+#[test]
+fn test_ffi_rejects_invalid_sampling_frequency() {
+    assert!(qrs_detector_new(f32::NAN).is_null());
+    assert!(qrs_detector_new(f32::INFINITY).is_null());
+    assert!(qrs_detector_new(0.0).is_null());
+    assert!(qrs_detector_new(-500.0).is_null());
+    assert!(qrs_detector_new(10.0).is_null());
+
+    unsafe { qrs_detector_free(ptr::null_mut()) };
+}
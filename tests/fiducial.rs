@@ -0,0 +1,159 @@
+use qrs_detector::fiducial::{FiducialLocator, Fiducials, PWave};
+use qrs_detector::history::HistoryBuffer;
+use qrs_detector::multi_rate::MultiRateDetector;
+use qrs_detector::prelude::*;
+use qrs_detector::synth::{SynthParams, Synthesizer};
+
+/// A QRS complex at 1000 sps with the R peak at sample 500: a q wave at 480, an s wave at 530,
+/// on a noisy baseline.
+fn qrs(i: u32) -> f32 {
+    let t = i as f32 - 500.0;
+    let wave = |center: f32, width: f32, amplitude: f32| {
+        amplitude * (-(t - center) * (t - center) / (2.0 * width * width)).exp()
+    };
+    let noise = 0.005 * ((i * 7919) % 13) as f32 / 13.0;
+    wave(-20.0, 5.0, -0.15) + wave(0.0, 8.0, 1.0) + wave(30.0, 6.0, -0.3) + noise
+}
+
+fn buffer(signal: impl Fn(u32) -> f32) -> HistoryBuffer<f32, [f32; 1024]> {
+    let mut buffer = HistoryBuffer::new([0.0; 1024]);
+    for i in 0..1000 {
+        buffer.push(signal(i));
+    }
+    buffer
+}
+
+#[test]
+fn test_q_and_s_points() {
+    let locator = FiducialLocator::new(1000.sps());
+    let fiducials = locator.locate(&buffer(qrs), 500, 1.0, None).unwrap();
+
+    assert_eq!(500, fiducials.r_index);
+    let q = fiducials.q_index.unwrap();
+    let s = fiducials.s_index.unwrap();
+    assert!(q.abs_diff(480) <= 2, "{fiducials:?}");
+    assert!(s.abs_diff(530) <= 2, "{fiducials:?}");
+    assert_eq!(Some(s - q), fiducials.qrs_samples());
+
+    // Negative complexes are bounded by maxima
+    let inverted = buffer(|i| -qrs(i));
+    assert_eq!(Some(fiducials), locator.locate(&inverted, 500, -1.0, None));
+}
+
+#[test]
+fn test_onset_on_flat_baseline() {
+    // A triangle without Q and S waves: the points are where the complex meets the baseline
+    let triangle = buffer(|i| (1.0 - (i as f32 - 500.0).abs() / 40.0).max(0.0));
+    let fiducials = FiducialLocator::new(1000.sps())
+        .locate(&triangle, 500, 1.0, None)
+        .unwrap();
+
+    assert_eq!(
+        Fiducials {
+            q_index: Some(460),
+            r_index: 500,
+            s_index: Some(540),
+        },
+        fiducials
+    );
+}
+
+#[test]
+fn test_rate_adaptive_windows() {
+    let locator = FiducialLocator::new(1000.sps());
+    assert_eq!((60, 80), locator.windows(None));
+    assert_eq!((60, 80), locator.windows(Some(1000.0)));
+    assert_eq!((45, 60), locator.windows(Some(400.0)));
+    assert_eq!((75, 100), locator.windows(Some(3000.0)));
+
+    // At high rates, the S wave is outside of the narrower window
+    let narrow = FiducialLocator::new(1000.sps()).with_windows_ms(20.0, 30.0);
+    let fiducials = narrow.locate(&buffer(qrs), 500, 1.0, Some(500.0)).unwrap();
+    assert!(fiducials.s_index.unwrap() < 525, "{fiducials:?}");
+}
+
+#[test]
+fn test_unbuffered_signal() {
+    let locator = FiducialLocator::new(1000.sps());
+    let signal = buffer(qrs);
+
+    assert_eq!(None, locator.locate(&signal, 1000, 1.0, None));
+
+    // The complex is still falling at the end of the buffer
+    let mut truncated = HistoryBuffer::new([0.0; 1024]);
+    for i in 0..505 {
+        truncated.push(qrs(i));
+    }
+    let fiducials = locator.locate(&truncated, 500, 1.0, None).unwrap();
+    assert!(fiducials.q_index.is_some());
+    assert_eq!(None, fiducials.s_index);
+    assert_eq!(None, fiducials.qrs_samples());
+}
+
+/// Returns the P wave checks of the beats detected in a minute of the synthetic `params` signal.
+fn p_waves(params: SynthParams) -> Vec<PWave> {
+    let mut prev = [0.0; 2];
+    let detector = QrsDetector::new::<75, 12>(250.sps()).with_transform(move |sample: f32| {
+        let slope = (sample - prev[0]).abs();
+        prev = [prev[1], sample];
+        slope
+    });
+    let mut multi_rate = MultiRateDetector::new(detector, 4, [0.0; 1024]).with_p_wave_check();
+
+    Synthesizer::new(1000.sps(), params, 1)
+        .take(60_000)
+        .filter_map(|sample| multi_rate.push(sample.value))
+        .filter_map(|beat| beat.p_wave)
+        .collect()
+}
+
+#[test]
+fn test_p_wave_presence() {
+    let sinus = p_waves(SynthParams::REST);
+    assert!(sinus.len() > 50, "{} beats", sinus.len());
+    let present = sinus.iter().filter(|p_wave| p_wave.present).count();
+    assert!(
+        present * 10 >= sinus.len() * 9,
+        "{present} of {}",
+        sinus.len()
+    );
+
+    // Fibrillatory waves replace the P waves
+    let af = p_waves(SynthParams::ATRIAL_FIBRILLATION);
+    assert!(af.len() > 30, "{} beats", af.len());
+    let present = af.iter().filter(|p_wave| p_wave.present).count();
+    assert!(present * 10 <= af.len(), "{present} of {}", af.len());
+
+    for p_wave in sinus.iter().chain(af.iter()) {
+        assert!((0.0..=1.0).contains(&p_wave.confidence), "{p_wave:?}");
+    }
+}
+
+#[test]
+fn test_p_wave_needs_the_onset() {
+    let locator = FiducialLocator::new(1000.sps());
+    let signal = buffer(qrs);
+    let fiducials = locator.locate(&signal, 500, 1.0, None).unwrap();
+
+    // No P wave before the synthetic complex
+    let p_wave = locator.p_wave(&signal, &fiducials, 1.0, None).unwrap();
+    assert!(!p_wave.present, "{p_wave:?}");
+
+    // The P window is not buffered, or the onset is not known
+    let late = Fiducials {
+        q_index: Some(200),
+        ..fiducials
+    };
+    assert_eq!(None, locator.p_wave(&signal, &late, 1.0, None));
+    let unknown = Fiducials {
+        q_index: None,
+        ..fiducials
+    };
+    assert_eq!(None, locator.p_wave(&signal, &unknown, 1.0, None));
+
+    // At 200 bpm, the T wave of the previous beat covers the P window
+    assert_eq!(None, locator.p_wave(&signal, &fiducials, 1.0, Some(300.0)));
+    assert!(locator
+        .p_wave(&signal, &fiducials, 1.0, Some(1000.0))
+        .is_some());
+}
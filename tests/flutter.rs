@@ -0,0 +1,135 @@
+#![cfg(feature = "quality")]
+
+use qrs_detector::prelude::*;
+use qrs_detector::quality::{FlutterDetector, FlutterEstimate};
+use qrs_detector::synth::{SynthParams, Synthesizer};
+
+/// Runs the flutter detector on 2 minutes of a synthetic signal, with the detections of the QRS
+/// detector. Returns the estimates, and the number of windows flutter was suspected after.
+fn run(fs: f32, params: SynthParams, seed: u32) -> (Vec<FlutterEstimate>, usize) {
+    if fs == 250.0 {
+        run_with(QrsDetector::new::<75, 12>(fs.sps()), params, seed)
+    } else {
+        run_with(QrsDetector::new::<150, 25>(fs.sps()), params, seed)
+    }
+}
+
+fn run_with<const F: usize, const B: usize>(
+    mut detector: QrsDetector<[f32; F], [f32; B]>,
+    params: SynthParams,
+    seed: u32,
+) -> (Vec<FlutterEstimate>, usize) {
+    let fs = detector.sampling_frequency();
+    let samples = fs.s_to_samples(120.0);
+    let mut flutter = FlutterDetector::new(fs);
+    let mut window = [0.0; 4];
+    let mut averages = [0.0; 2];
+    let mut estimates = Vec::new();
+    let mut suspected = 0;
+
+    for sample in Synthesizer::new(fs, params, seed).take(samples) {
+        window = [window[1], window[2], window[3], sample.value];
+        let average = window.iter().sum::<f32>() / 4.0;
+        let slope = (averages[0] - average).abs();
+        averages = [averages[1], average];
+        if detector.update(slope).is_some() {
+            flutter.detection_event();
+        }
+
+        if let Some(estimate) = flutter.update(sample.value) {
+            estimates.push(estimate);
+            suspected += flutter.is_flutter_suspected() as usize;
+        }
+    }
+
+    (estimates, suspected)
+}
+
+#[test]
+fn test_flutter_with_variable_conduction_is_reported() {
+    let params = SynthParams {
+        heart_rate_bpm: 100.0,
+        final_heart_rate_bpm: 100.0,
+        rr_variability: 0.2,
+        ..SynthParams::ATRIAL_FLUTTER
+    };
+
+    for fs in [250.0, 500.0] {
+        for seed in 1..=3 {
+            let (estimates, suspected) = run(fs, params, seed);
+            assert_eq!(14, estimates.len());
+            assert!(
+                estimates.iter().all(|e| e.atrial_rate_bpm == 300.0),
+                "{fs} sps, seed {seed}: {estimates:?}"
+            );
+            assert!(
+                suspected >= estimates.len() / 2,
+                "{fs} sps, seed {seed}: {suspected} {estimates:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_flutter_not_reported_without_flutter_waves() {
+    let regular = |bpm| SynthParams {
+        heart_rate_bpm: bpm,
+        final_heart_rate_bpm: bpm,
+        rr_variability: 0.01,
+        ..SynthParams::REST
+    };
+
+    for params in [
+        SynthParams::REST,
+        SynthParams::EXERCISE,
+        SynthParams::ATRIAL_FIBRILLATION,
+        SynthParams::NOISY,
+        // The harmonics of the P and T waves fall into the flutter band
+        regular(75.0),
+        regular(100.0),
+        regular(150.0),
+    ] {
+        for fs in [250.0, 500.0] {
+            for seed in 1..=3 {
+                let (estimates, suspected) = run(fs, params, seed);
+                assert_eq!(0, suspected, "{fs} sps, seed {seed}: {estimates:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ventricular_harmonics_are_recognized() {
+    let params = SynthParams {
+        heart_rate_bpm: 100.0,
+        final_heart_rate_bpm: 100.0,
+        ..SynthParams::REST
+    };
+
+    let (estimates, _) = run(250.0, params, 1);
+    for estimate in estimates {
+        // The third harmonic of the ventricular rate is a strong, narrow peak
+        assert_eq!(300.0, estimate.atrial_rate_bpm);
+        assert!(estimate.ventricular_harmonic, "{estimate:?}");
+        assert!(!estimate.is_suspicious());
+    }
+}
+
+#[test]
+fn test_clear() {
+    let params = SynthParams {
+        rr_variability: 0.2,
+        heart_rate_bpm: 100.0,
+        final_heart_rate_bpm: 100.0,
+        ..SynthParams::ATRIAL_FLUTTER
+    };
+    let mut flutter = FlutterDetector::new(250.sps());
+    for sample in Synthesizer::new(250.sps(), params, 1).take(30 * 250) {
+        flutter.update(sample.value);
+    }
+    assert!(flutter.last().is_some());
+
+    flutter.clear();
+    assert_eq!(None, flutter.last());
+    assert!(!flutter.is_flutter_suspected());
+}
@@ -0,0 +1,265 @@
+#![cfg(feature = "preprocessing")]
+
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+use qrs_detector::preprocessing::{
+    moving_average, windowed_sinc, AdaptiveNotch, Biquad, BiquadCoefficients, Fir, MainsFrequency,
+    Preprocessor,
+};
+use qrs_detector::sampling::SamplingFrequency;
+use qrs_detector::transform::{DcBlocker, Identity, SampleTransform};
+
+const FS: SamplingFrequency = SamplingFrequency::from_sps(720.0);
+
+fn detect(samples: &[f32], transform: impl SampleTransform) -> Vec<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps()).with_transform(transform);
+    samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect()
+}
+
+#[test]
+fn test_group_delay_of_filters() {
+    assert_eq!(0.0, Identity.group_delay());
+    assert_eq!(0.0, (|sample: f32| sample).group_delay());
+    assert_eq!(2.0, Fir::new(moving_average::<5>()).group_delay());
+    assert_eq!(15.0, Fir::new(windowed_sinc::<31>(FS, 40.0)).group_delay());
+
+    // A second order Butterworth low-pass delays by sqrt(2) / w0 at low frequencies
+    let lowpass = BiquadCoefficients::lowpass(FS, 20.0, core::f32::consts::FRAC_1_SQRT_2);
+    let expected = core::f32::consts::SQRT_2 / (2.0 * core::f32::consts::PI * 20.0 / 720.0);
+    assert!((lowpass.group_delay() - expected).abs() < 0.2 * expected);
+
+    // High-pass filters block DC
+    let highpass = BiquadCoefficients::highpass(FS, 0.5, core::f32::consts::FRAC_1_SQRT_2);
+    assert_eq!(0.0, Biquad::new(highpass).group_delay());
+
+    // Delays of chained transforms add up
+    let chain = Fir::new(moving_average::<5>()).chain(Biquad::new(lowpass));
+    assert_eq!(2.0 + lowpass.group_delay(), chain.group_delay());
+}
+
+#[test]
+fn test_detections_are_reported_in_the_input_timeline() {
+    let signal = preprocessed();
+    let reference = detect(&signal, Identity);
+    assert_eq!(38, reference.len());
+
+    // Filtering outside of the detector delays the detections
+    let mut filter = Fir::new(moving_average::<9>());
+    let filtered = signal
+        .iter()
+        .map(|&sample| filter.transform(sample))
+        .collect::<Vec<_>>();
+    let external = detect(&filtered, Identity);
+
+    // The same filter inside the detector is compensated
+    let compensated = detect(&signal, Fir::new(moving_average::<9>()));
+    assert_eq!(
+        external.iter().map(|index| index - 4).collect::<Vec<_>>(),
+        compensated
+    );
+
+    // Compensated detections are closer to the detections in the unfiltered signal
+    let error = |detections: &[u32]| -> i32 {
+        detections
+            .iter()
+            .zip(reference.iter())
+            .map(|(&detection, &reference)| detection as i32 - reference as i32)
+            .sum()
+    };
+    assert_eq!(reference.len(), compensated.len());
+    assert!(
+        error(&compensated).abs() < error(&external).abs(),
+        "{} >= {}",
+        error(&compensated),
+        error(&external)
+    );
+}
+
+#[test]
+fn test_total_group_delay() {
+    let lowpass = BiquadCoefficients::lowpass(FS, 20.0, core::f32::consts::FRAC_1_SQRT_2);
+    let detector = QrsDetector::new::<216, 36>(720.sps())
+        .with_transform(Fir::new(moving_average::<9>()).chain(Biquad::new(lowpass)));
+
+    assert_eq!(
+        4.0 + lowpass.group_delay(),
+        detector.total_group_delay_samples()
+    );
+}
+
+/// Returns the group delay of `coefficients` at `frequency`, relative to the sampling frequency,
+/// as the numeric derivative of the phase response.
+fn numeric_group_delay(coefficients: [f64; 5], frequency: f64) -> f64 {
+    let [b0, b1, b2, a1, a2] = coefficients;
+    let phase = |w: f64| {
+        let response = |p: [f64; 3]| {
+            let re = p[0] + p[1] * w.cos() + p[2] * (2.0 * w).cos();
+            let im = -p[1] * w.sin() - p[2] * (2.0 * w).sin();
+            im.atan2(re)
+        };
+        response([b0, b1, b2]) - response([1.0, a1, a2])
+    };
+    let w = 2.0 * std::f64::consts::PI * frequency;
+    let dw = 1e-4;
+    -(phase(w + dw) - phase(w - dw)) / (2.0 * dw)
+}
+
+/// A 1 Hz signal of half sine wave pulses, with the dominant frequency of `hz(beat)`.
+fn pulses(hz: impl Fn(u32) -> f32) -> impl Iterator<Item = f32> {
+    (0..30 * 720).map(move |i| {
+        let half_period = 720.0 / (2.0 * hz(i / 720));
+        let t = (i % 720) as f32 - 360.0;
+        if t.abs() < half_period / 2.0 {
+            (core::f32::consts::PI * t / half_period).cos()
+        } else {
+            0.0
+        }
+    })
+}
+
+fn detections(
+    samples: impl Iterator<Item = f32>,
+    transform: impl SampleTransform,
+) -> Vec<Detection> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps())
+        .with_transform(transform)
+        .with_history([Detection::default(); 32]);
+    for sample in samples {
+        detector.update(sample);
+    }
+    detector.recent_detections().collect()
+}
+
+#[test]
+fn test_group_delay_at_frequency() {
+    let filters = [
+        BiquadCoefficients::lowpass(FS, 20.0, core::f32::consts::FRAC_1_SQRT_2),
+        BiquadCoefficients::lowpass(FS, 25.0, 1.5),
+        BiquadCoefficients::highpass(FS, 0.5, core::f32::consts::FRAC_1_SQRT_2),
+        BiquadCoefficients::notch(FS, 50.0, 30.0),
+    ];
+    for filter in filters {
+        let coefficients = [filter.b0, filter.b1, filter.b2, filter.a1, filter.a2].map(f64::from);
+        for hz in [1.0, 5.0, 10.0, 20.0, 40.0] {
+            let frequency = hz / FS.raw();
+            let expected = numeric_group_delay(coefficients, frequency as f64) as f32;
+            let delay = Biquad::new(filter).group_delay_at(frequency);
+            assert!(
+                (delay - expected).abs() < 0.05,
+                "{filter:?} {hz}: {delay} {expected}"
+            );
+        }
+    }
+
+    // The delay at DC is the low frequency delay. High-pass filters fall back to it.
+    let lowpass = filters[0];
+    assert!((lowpass.group_delay_at(0.0) - lowpass.group_delay()).abs() < 1e-3);
+    assert_eq!(0.0, filters[2].group_delay_at(0.0));
+
+    // The one-pole DC blocker
+    let blocker = DcBlocker::new(FS, 0.5);
+    let a = 1.0 - 2.0 * std::f64::consts::PI * 0.5 / 720.0;
+    let expected = numeric_group_delay([1.0, -1.0, 0.0, -a, 0.0], 0.01);
+    assert!((blocker.group_delay_at(0.01) as f64 - expected).abs() < 0.05);
+
+    // Linear phase filters delay every frequency equally, and delays of chains add up
+    let fir = Fir::new(moving_average::<5>());
+    assert_eq!(2.0, fir.group_delay_at(0.02));
+    let chain = fir.chain(Biquad::new(lowpass));
+    assert_eq!(
+        2.0 + lowpass.group_delay_at(0.02),
+        chain.group_delay_at(0.02)
+    );
+}
+
+#[test]
+fn test_group_delay_of_adaptive_notch() {
+    // The delay follows the adapted notch frequency
+    let mut notch = AdaptiveNotch::new(FS, MainsFrequency::Hz50);
+    for i in 0..10 * 720 {
+        notch.transform(0.05 * (2.0 * core::f32::consts::PI * 53.0 * i as f32 / 720.0).sin());
+    }
+    assert!(
+        (notch.frequency() - 53.0).abs() < 0.3,
+        "{}",
+        notch.frequency()
+    );
+
+    let filter = notch.coefficients();
+    let coefficients = [filter.b0, filter.b1, filter.b2, filter.a1, filter.a2].map(f64::from);
+    for hz in [1.0, 5.0, 10.0, 20.0, 40.0] {
+        let frequency = hz / FS.raw();
+        let expected = numeric_group_delay(coefficients, frequency as f64) as f32;
+        let delay = notch.group_delay_at(frequency);
+        assert!((delay - expected).abs() < 0.05, "{hz}: {delay} {expected}");
+    }
+    assert_eq!(filter.group_delay(), notch.group_delay());
+
+    // The preprocessor includes the delay of the adaptive notch
+    let nominal = AdaptiveNotch::new(FS, MainsFrequency::Hz50);
+    let lowpass = BiquadCoefficients::lowpass(FS, 40.0, core::f32::consts::FRAC_1_SQRT_2);
+    let preprocessor = Preprocessor::new(FS)
+        .with_adaptive_notch(MainsFrequency::Hz50)
+        .with_lowpass(40.0);
+    assert_eq!(
+        nominal.group_delay() + lowpass.group_delay(),
+        preprocessor.group_delay()
+    );
+    assert_eq!(
+        nominal.group_delay_at(0.02) + lowpass.group_delay_at(0.02),
+        preprocessor.group_delay_at(0.02)
+    );
+}
+
+#[test]
+fn test_timing_correction() {
+    // Linear phase filters need no correction
+    let fir = detections(pulses(|_| 10.0), Fir::new(moving_average::<9>()));
+    assert!(fir.len() > 20);
+    assert!(fir
+        .iter()
+        .all(|detection| detection.timing_correction == 0.0));
+
+    // The correction is the group delay at the estimated frequency of the pulses, less the delay
+    // compensated in the indices
+    let resonant = BiquadCoefficients::lowpass(FS, 25.0, 1.5);
+    let compensated = resonant.group_delay().round();
+    for hz in [3.0, 15.0] {
+        let expected = resonant.group_delay_at(hz / FS.raw()) - compensated;
+        let detections = detections(pulses(|_| hz), Biquad::new(resonant));
+        for detection in &detections[2..] {
+            let correction = detection.timing_correction;
+            assert!(
+                (correction - expected).abs() < 1.0,
+                "{hz}: {correction} {expected}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_timing_correction_improves_rr_intervals() {
+    // Alternating wide and narrow QRS complexes are delayed differently by a resonant filter
+    let hz = |beat: u32| if beat.is_multiple_of(2) { 3.0 } else { 10.0 };
+    let reference = detections(pulses(hz), Identity);
+    let filter = BiquadCoefficients::lowpass(FS, 30.0, 2.0);
+    let filtered = detections(pulses(hz), Biquad::new(filter));
+    assert_eq!(reference.len(), filtered.len());
+
+    let mut raw_error = 0.0;
+    let mut corrected_error = 0.0;
+    for (reference, filtered) in reference.windows(2).zip(filtered.windows(2)).skip(2) {
+        let rr = reference[1].corrected_interval(&reference[0]);
+        raw_error += (filtered[1].index.abs_diff(filtered[0].index) as f32 - rr).abs();
+        corrected_error += (filtered[1].corrected_interval(&filtered[0]) - rr).abs();
+    }
+    assert!(
+        corrected_error < 0.5 * raw_error,
+        "{corrected_error} {raw_error}"
+    );
+}
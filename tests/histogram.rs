@@ -0,0 +1,83 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::histogram::{AmplitudeHistogram, HISTOGRAM_BINS};
+use qrs_detector::prelude::*;
+
+#[test]
+fn test_half_octave_bins() {
+    let histogram = AmplitudeHistogram::new(1.0);
+
+    assert_eq!(0, histogram.bin(0.0));
+    assert_eq!(0, histogram.bin(0.99));
+    assert_eq!(1, histogram.bin(1.0));
+    assert_eq!(1, histogram.bin(-1.4));
+    assert_eq!(2, histogram.bin(1.5));
+    assert_eq!(3, histogram.bin(2.0));
+    assert_eq!(4, histogram.bin(2.9));
+    assert_eq!(HISTOGRAM_BINS - 2, histogram.bin(2000.0));
+    assert_eq!(HISTOGRAM_BINS - 1, histogram.bin(3000.0));
+    assert_eq!(HISTOGRAM_BINS - 1, histogram.bin(f32::INFINITY));
+
+    assert_eq!((0.0, 1.0), histogram.bin_edges(0));
+    assert_eq!(
+        (2.0, 2.0 * std::f32::consts::SQRT_2),
+        histogram.bin_edges(3)
+    );
+    assert_eq!(
+        (2048.0, f32::INFINITY),
+        histogram.bin_edges(HISTOGRAM_BINS - 1)
+    );
+    for bin in 1..HISTOGRAM_BINS - 1 {
+        let (low, high) = histogram.bin_edges(bin);
+        assert_eq!(bin, histogram.bin(low));
+        assert_eq!(bin, histogram.bin(high * 0.999));
+    }
+}
+
+#[test]
+fn test_counts_are_halved_when_full() {
+    let mut histogram = AmplitudeHistogram::new(1.0);
+    assert_eq!(None, histogram.quantile(0.5));
+    assert_eq!(0.0, histogram.fraction_above(0.0));
+
+    for _ in 0..100 {
+        histogram.add(10.0);
+    }
+    for _ in 0..u16::MAX {
+        histogram.add(0.5);
+    }
+    histogram.add(f32::NAN);
+    assert_eq!(u32::from(u16::MAX) + 100, histogram.total());
+
+    histogram.add(0.5);
+    assert_eq!(u16::MAX / 2 + 1, histogram.counts()[0]);
+    assert_eq!(50, histogram.counts()[histogram.bin(10.0)]);
+
+    histogram.clear();
+    assert_eq!(0, histogram.total());
+}
+
+#[test]
+fn test_threshold_relative_to_signal() {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps())
+        .with_amplitude_histogram(AmplitudeHistogram::new(0.001));
+
+    let signal = preprocessed();
+    for &sample in signal.iter() {
+        detector.update(sample);
+    }
+
+    let histogram = detector.amplitude_histogram().unwrap();
+    assert_eq!(signal.len() as u32, histogram.total());
+
+    // The threshold is above the bulk of the signal, and below the QRS slopes
+    let threshold = detector.thresholds().total().unwrap();
+    let median = histogram.quantile(0.5).unwrap();
+    assert!(median < threshold / 4.0, "{median} {threshold}");
+    let above = histogram.fraction_above(threshold);
+    assert!(above > 0.001 && above < 0.02, "{above}");
+
+    detector.clear();
+    assert_eq!(0, detector.amplitude_histogram().unwrap().total());
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use qrs_detector::prelude::*;
+
+#[test]
+fn test_recent_detections() {
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_history([Detection::default(); 4]);
+
+    let samples = common::samples();
+
+    let mut detections = Vec::new();
+    for window in samples.windows(3) {
+        if let Some(idx) = detector.update((window[0] - window[2]).abs()) {
+            detections.push(idx);
+        }
+
+        let recent = detector.recent_detections().collect::<Vec<_>>();
+        let expected = &detections[detections.len().saturating_sub(4)..];
+
+        assert_eq!(
+            expected,
+            recent
+                .iter()
+                .map(|d| d.index)
+                .collect::<Vec<_>>()
+                .as_slice()
+        );
+        for detection in recent {
+            assert!(detection.amplitude > detection.threshold);
+            assert!(detection.confidence() > 0.0 && detection.confidence() <= 1.0);
+        }
+    }
+
+    detector.clear();
+    assert_eq!(0, detector.recent_detections().count());
+}
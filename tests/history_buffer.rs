@@ -0,0 +1,66 @@
+use qrs_detector::history::HistoryBuffer;
+
+#[test]
+fn test_absolute_addressing() {
+    let mut history = HistoryBuffer::new([0u32; 8]);
+    assert!(history.is_empty());
+    assert_eq!(None, history.newest());
+
+    for i in 0..20 {
+        assert_eq!(i, history.push(i * 10));
+    }
+
+    assert_eq!(8, history.len());
+    assert_eq!(12, history.oldest_index());
+    assert_eq!(20, history.next_index());
+    assert_eq!(Some(190), history.newest());
+    assert_eq!(None, history.get(11));
+    assert_eq!(Some(120), history.get(12));
+    assert_eq!(None, history.get(20));
+
+    assert_eq!(
+        vec![140, 150, 160],
+        history.range(14, 17).unwrap().collect::<Vec<_>>()
+    );
+    assert!(history.range(11, 14).is_none());
+    assert!(history.range(18, 21).is_none());
+    assert_eq!(0, history.range(15, 15).unwrap().count());
+}
+
+#[test]
+fn test_index_wraps_around() {
+    let mut history = HistoryBuffer::starting_at([0u32; 4], u32::MAX - 1);
+
+    for i in 0..5 {
+        history.push(i);
+    }
+
+    assert_eq!(u32::MAX, history.oldest_index());
+    assert_eq!(Some(2), history.get(0));
+    assert_eq!(Some(4), history.get(2));
+    assert_eq!(None, history.get(3));
+    assert_eq!(
+        vec![1, 2, 3, 4],
+        history.range(u32::MAX, 3).unwrap().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_clear_keeps_index() {
+    let mut history = HistoryBuffer::new([0.0; 4]);
+    history.push(1.0);
+    history.push(2.0);
+
+    history.clear();
+
+    assert!(history.is_empty());
+    assert_eq!(None, history.get(1));
+    assert_eq!(2, history.push(3.0));
+    assert_eq!(Some(3.0), history.get(2));
+}
+
+#[test]
+#[should_panic]
+fn test_capacity_must_be_power_of_two() {
+    HistoryBuffer::new([0.0; 350]);
+}
@@ -1,3 +1,5 @@
+mod common;
+
 use qrs_detector::sampling::*;
 use qrs_detector::QrsDetector;
 
@@ -9,12 +11,7 @@ fn test_simulated_signal() {
     let mut prev: Option<f32> = None;
     let mut prev2: Option<f32> = None;
 
-    let data = include_str!("./data/aami3a.txt");
-
-    let samples = data
-        .split_terminator('\n')
-        .map(|str| str.trim().parse::<f32>().unwrap())
-        .collect::<Vec<_>>();
+    let samples = common::samples();
 
     for window in samples.windows(4) {
         // A slight moving average filtering
@@ -22,7 +19,7 @@ fn test_simulated_signal() {
         let avg = sum / 4.0;
         if let Some(p) = prev.replace(avg) {
             if let Some(p2) = prev2.replace(p) {
-                if let Some(_) = detector.update((p2 - avg).abs()) {
+                if detector.update((p2 - avg).abs()).is_some() {
                     detections += 1;
                 }
             }
@@ -41,12 +38,7 @@ fn test_simulated_signal_alloc() {
     let mut prev: Option<f32> = None;
     let mut prev2: Option<f32> = None;
 
-    let data = include_str!("./data/aami3a.txt");
-
-    let samples = data
-        .split_terminator('\n')
-        .map(|str| str.trim().parse::<f32>().unwrap())
-        .collect::<Vec<_>>();
+    let samples = common::samples();
 
     for window in samples.windows(4) {
         // A slight moving average filtering
@@ -54,7 +46,7 @@ fn test_simulated_signal_alloc() {
         let avg = sum / 4.0;
         if let Some(p) = prev.replace(avg) {
             if let Some(p2) = prev2.replace(p) {
-                if let Some(_) = detector.update((p2 - avg).abs()) {
+                if detector.update((p2 - avg).abs()).is_some() {
                     detections += 1;
                 }
             }
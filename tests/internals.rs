@@ -0,0 +1,111 @@
+#![cfg(feature = "internals")]
+
+use qrs_detector::algorithms::{F, M, R};
+use qrs_detector::prelude::*;
+
+#[test]
+fn test_m_initialization_and_refractory() {
+    let fs = 500.sps();
+    let mut m = M::new(fs, &Config::new());
+
+    // M is set to 60% of the maximum of the first 3 seconds
+    for i in 0..1500 {
+        assert_eq!(None, m.threshold());
+        m.update(if i == 100 { 10.0 } else { 0.0 });
+    }
+    m.update(0.0);
+    let initial = m.threshold().unwrap();
+    assert!((initial - 6.0).abs() < 0.1, "{initial}");
+    assert_eq!(Some(10.0), m.peak_average());
+
+    // No threshold during the refractory period
+    m.detection_event(20.0);
+    assert!(m.in_refractory());
+    assert_eq!(None, m.threshold());
+    for _ in 0..fs.ms_to_samples(225.0) + 1 {
+        m.update(0.0);
+    }
+    assert!(!m.in_refractory());
+
+    // The peak of the detection is averaged into M
+    assert!(m.threshold().unwrap() > initial);
+    assert_eq!(Ok(()), m.verify());
+}
+
+#[test]
+fn test_m_refractory_ending_at_the_limit_is_not_a_timeout() {
+    let fs = 500.sps();
+    let mut m = M::new(fs, &Config::new());
+    for _ in 0..=fs.s_to_samples(3.0) {
+        m.update(1.0);
+    }
+
+    // A rising signal keeps extending the refractory period. The extension runs out on the
+    // same sample the 1 second limit is reached.
+    m.detection_event(2.0);
+    let rising = fs.s_to_samples(1.0) - m.refractory_samples();
+    for i in 0..rising {
+        m.update(3.0 + i as f32);
+    }
+    for _ in 0..=m.refractory_samples() {
+        m.update(0.0);
+    }
+    assert!(!m.in_refractory());
+    assert!(!m.refractory_timeout);
+
+    // A signal that is still rising at the limit is cut short
+    m.detection_event(2.0);
+    for i in 0..=fs.s_to_samples(1.0) {
+        m.update(3.0 + i as f32);
+    }
+    assert!(!m.in_refractory());
+    assert!(m.refractory_timeout);
+}
+
+#[test]
+fn test_f_follows_noise() {
+    let fs = 500.sps();
+    let mut f = F::new(fs, [0.0; 150], [0.0; 25]);
+
+    for _ in 0..fs.s_to_samples(3.0) {
+        f.update(0.0);
+    }
+    assert_eq!(Some(0.0), f.threshold());
+
+    for i in 0..fs.s_to_samples(1.0) {
+        f.update(if i % 2 == 0 { 1.0 } else { 0.0 });
+    }
+    assert!(f.threshold().unwrap() > 0.5, "{:?}", f.threshold());
+    assert_eq!(Ok(()), f.verify());
+}
+
+#[test]
+fn test_r_expects_beats() {
+    let config = Config::new();
+    let mut r = R::new(&config);
+
+    // Regular beats every 400 samples
+    let mut idx = 0;
+    for _ in 0..=config.rr_depth() {
+        assert_eq!(None, r.rr_average());
+        r.detection_event(idx);
+        for _ in 0..400 {
+            r.update(0.001, Some(1.0));
+            idx += 1;
+        }
+    }
+    assert_eq!(Some(400), r.rr_average());
+    assert_eq!(Some(400), r.rr_intervals().last());
+
+    // R only decreases the threshold when the next beat is expected
+    r.detection_event(idx);
+    for _ in 0..200 {
+        r.update(0.001, Some(1.0));
+    }
+    assert_eq!(0.0, r.threshold());
+    for _ in 0..250 {
+        r.update(0.001, Some(1.0));
+    }
+    assert!(r.threshold() < 0.0);
+    assert_eq!(Ok(()), r.verify(idx + 450));
+}
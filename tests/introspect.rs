@@ -0,0 +1,100 @@
+use qrs_detector::prelude::*;
+use qrs_detector::{BufferSizes, BufferUsage};
+
+#[test]
+fn test_introspection_reports_fill_levels() {
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_history([Detection::default(); 4]);
+
+    let empty = detector.introspect();
+    assert_eq!(
+        BufferSizes {
+            samples_300: 216,
+            samples_50: 36
+        },
+        empty.required
+    );
+    assert!(empty.f_buffers_match());
+    assert_eq!(BufferUsage::new(216, 0), empty.f_max_window);
+    assert_eq!(BufferUsage::new(36, 0), empty.f_buffer);
+    assert_eq!(BufferUsage::new(5, 0), empty.mm);
+    assert_eq!(BufferUsage::new(4, 0), empty.history);
+    assert_eq!(BufferUsage::new(0, 0), empty.suppressed);
+    assert_eq!(0.0, empty.suppressed.utilization());
+    assert_eq!(720.sps().ms_to_samples(225.0), empty.refractory_samples);
+    assert_eq!(
+        "FMW=0/216 FB=0/36 MM=0/5 RR=0/5 H=0/4 S=0/0",
+        format!("{empty}")
+    );
+
+    // F ignores the first 2.65 seconds
+    for _ in 0..720.sps().s_to_samples(2.65) + 100 {
+        detector.update(0.0);
+    }
+    let partial = detector.introspect();
+    assert!(partial.f_buffer.is_full());
+    assert!(!partial.f_max_window.is_full());
+    assert!(partial.f_max_window.len > 0);
+    assert!(partial.f_max_window.utilization() < 0.5);
+
+    for _ in 0..720 {
+        detector.update(0.0);
+    }
+    let full = detector.introspect();
+    assert!(full.f_max_window.is_full());
+    assert_eq!(1.0, full.f_buffer.utilization());
+}
+
+#[test]
+fn test_introspection_detects_mismatched_buffers() {
+    let mut f_buffer_300 = [0.0; 150];
+    let mut f_buffer_50 = [0.0; 25];
+    let detector = QrsDetector::new_from(500.sps(), &mut f_buffer_300[..], &mut f_buffer_50[..]);
+    assert!(detector.introspect().f_buffers_match());
+
+    // Sizes from a configuration file that was written for a different sampling frequency
+    let introspection = qrs_detector::Introspection {
+        f_max_window: BufferUsage::new(216, 0),
+        ..detector.introspect()
+    };
+    assert!(!introspection.f_buffers_match());
+}
+
+#[test]
+fn test_strict_constructor_rejects_mismatched_buffers() {
+    use qrs_detector::BufferSizeError;
+
+    // Sizes read from a configuration file written for 360 samples per second
+    let mut f_buffer_300 = [0.0; 108];
+    let mut f_buffer_50 = [0.0; 18];
+
+    let error = QrsDetector::try_new_from(500.sps(), &mut f_buffer_300[..], &mut f_buffer_50[..])
+        .err()
+        .unwrap();
+    assert_eq!(
+        BufferSizeError::Samples300 {
+            expected: 150,
+            actual: 108
+        },
+        error
+    );
+    assert_eq!(
+        "incorrect 300ms buffer: 108 samples, must be 150 samples",
+        error.to_string()
+    );
+
+    let error = QrsDetector::try_new_from(500.sps(), [0.0; 150], &mut f_buffer_50[..])
+        .err()
+        .unwrap();
+    assert_eq!(
+        BufferSizeError::Samples50 {
+            expected: 25,
+            actual: 18
+        },
+        error
+    );
+
+    let detector =
+        QrsDetector::try_new_from(360.sps(), &mut f_buffer_300[..], &mut f_buffer_50[..]).unwrap();
+    assert!(detector.introspect().f_buffers_match());
+}
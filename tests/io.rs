@@ -0,0 +1,128 @@
+#![cfg(feature = "io")]
+
+mod common;
+
+use common::preprocessed;
+use qrs_detector::annotation::BeatClass;
+use qrs_detector::io::{Beat, BeatCsvWriter, Column};
+use qrs_detector::prelude::*;
+use qrs_detector::{Reason, Reasons};
+
+#[test]
+fn test_csv_rows() {
+    let mut writer = BeatCsvWriter::new(Vec::new(), 500.sps()).with_columns(&[
+        Column::Index,
+        Column::Time,
+        Column::Rr,
+        Column::HeartRate,
+        Column::QualityFlags,
+        Column::StLevel,
+        Column::Paced,
+        Column::Reasons,
+        Column::TimingCorrection,
+        Column::Class,
+    ]);
+
+    let beat = |index| {
+        Beat::new(Detection {
+            index,
+            amplitude: 2.0,
+            threshold: 1.0,
+            ..Default::default()
+        })
+    };
+    writer.write_beat(&beat(500)).unwrap();
+    writer
+        .write_beat(
+            &beat(900)
+                .with_flags(3)
+                .with_st_level(-0.25)
+                .with_class(BeatClass::Ventricular),
+        )
+        .unwrap();
+    writer.interrupt();
+    let mut after_gap = beat(2000);
+    after_gap.detection.paced = true;
+    after_gap.detection.reasons = Reasons::of(&[Reason::Gap, Reason::Suppression]);
+    after_gap.detection.timing_correction = -1.5;
+    writer.write_beat(&after_gap).unwrap();
+
+    let csv = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(
+        "index,time_s,rr_ms,hr_bpm,flags,st_level,paced,reasons,timing_correction_ms,class\n\
+         500,1.000,,,0,,0,,0.00,\n\
+         900,1.800,800.0,75.0,3,-0.25,0,,0.00,V\n\
+         2000,4.000,,,0,,1,suppression|gap,-3.00,\n",
+        csv
+    );
+}
+
+#[test]
+fn test_csv_of_detections() {
+    let signal = preprocessed();
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_history([Detection::default(); 1]);
+    let mut writer = BeatCsvWriter::new(Vec::new(), 720.sps());
+    assert_eq!(&Column::DEFAULT, writer.columns());
+
+    let mut detections = Vec::new();
+    for &sample in signal.iter() {
+        if let Some(index) = detector.update(sample) {
+            detections.push(index);
+            let detection = detector.recent_detections().last().unwrap();
+            writer.write_detection(&detection).unwrap();
+        }
+    }
+
+    let csv = String::from_utf8(writer.into_inner()).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(Some("index,time_s,rr_ms,hr_bpm,confidence"), lines.next());
+
+    let rows = lines
+        .map(|line| line.split(',').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(detections.len(), rows.len());
+    for (row, index) in rows.iter().zip(detections.iter()) {
+        assert_eq!(5, row.len());
+        assert_eq!(index.to_string(), row[0]);
+        let confidence = row[4].parse::<f32>().unwrap();
+        assert!((0.0..=1.0).contains(&confidence));
+    }
+
+    // Every beat after the first one has a plausible heart rate
+    assert_eq!("", rows[0][3]);
+    for row in &rows[1..] {
+        let hr = row[3].parse::<f32>().unwrap();
+        assert!((30.0..200.0).contains(&hr), "{hr}");
+    }
+}
+
+#[test]
+fn test_csv_algorithm_version() {
+    let detector = QrsDetector::new::<216, 36>(720.sps());
+    let detection = Detection {
+        index: 720,
+        ..Default::default()
+    };
+    let columns = [Column::Index, Column::AlgorithmVersion];
+
+    let mut writer = BeatCsvWriter::new(Vec::new(), 720.sps())
+        .with_columns(&columns)
+        .with_algorithm_version(detector.algorithm_version());
+    writer.write_detection(&detection).unwrap();
+    assert_eq!(
+        format!(
+            "index,algorithm_version\n720,{}\n",
+            detector.algorithm_version()
+        ),
+        String::from_utf8(writer.into_inner()).unwrap()
+    );
+
+    // Without a version, the column is empty
+    let mut writer = BeatCsvWriter::new(Vec::new(), 720.sps()).with_columns(&columns);
+    writer.write_detection(&detection).unwrap();
+    assert_eq!(
+        "index,algorithm_version\n720,\n",
+        String::from_utf8(writer.into_inner()).unwrap()
+    );
+}
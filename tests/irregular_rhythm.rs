@@ -0,0 +1,56 @@
+use qrs_detector::prelude::*;
+
+/// RR intervals of an irregular rhythm, in samples at 250 sps.
+const RR: [usize; 12] = [250, 600, 150, 700, 175, 650, 200, 725, 150, 625, 250, 700];
+
+/// Returns the number of detections and the (M, R) threshold pairs.
+fn run(config: Config) -> (usize, Vec<(f32, f32)>) {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps()).with_config(config);
+
+    // Regular pulses during initialization, then the irregular rhythm, repeated
+    let mut beats = (0..4).map(|i| i * 250).collect::<Vec<_>>();
+    for rr in RR.iter().cycle().take(36) {
+        beats.push(beats.last().unwrap() + rr);
+    }
+
+    let mut detections = 0;
+    let mut thresholds = Vec::new();
+    for i in 0..beats.last().unwrap() + 250 {
+        let pulse = beats.iter().any(|&beat| (beat..beat + 5).contains(&i));
+        if detector.update(if pulse { 1.0 } else { 0.0 }).is_some() {
+            detections += 1;
+        }
+
+        let t = detector.thresholds();
+        if let Some(m) = t.m {
+            thresholds.push((m, t.r));
+        }
+    }
+
+    (detections, thresholds)
+}
+
+#[test]
+fn test_r_floor_default() {
+    let (detections, thresholds) = run(Config::new());
+
+    assert_eq!(37, detections); // every beat after the initialization period
+    assert!(thresholds.iter().all(|&(m, r)| r >= -0.5 * m));
+    assert!(thresholds.iter().any(|&(m, r)| r < -0.1 * m));
+}
+
+#[test]
+fn test_r_floor_limits_r() {
+    let (detections, thresholds) = run(Config::new().with_r_floor(0.1));
+
+    assert_eq!(37, detections); // every beat after the initialization period
+    assert!(thresholds.iter().all(|&(m, r)| r >= -0.1 * m - 1e-6));
+    assert!(thresholds.iter().any(|&(m, r)| (r + 0.1 * m).abs() < 1e-6));
+}
+
+#[test]
+fn test_r_floor_zero_disables_r() {
+    let (_, thresholds) = run(Config::new().with_r_floor(0.0));
+
+    assert!(thresholds.iter().all(|&(_, r)| r == 0.0));
+}
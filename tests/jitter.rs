@@ -0,0 +1,99 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+use qrs_detector::testing::{Jitter, Perturbation};
+
+/// Runs the detector on the perturbed signal. If `notify` is set, the detector is notified about
+/// the acquisition errors.
+fn detect(samples: &[f32], drop_rate: f32, duplicate_rate: f32, notify: bool) -> Vec<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let jitter = Jitter::new(samples.iter().copied(), 0x1234_5678)
+        .with_drop_rate(drop_rate)
+        .with_duplicate_rate(duplicate_rate);
+
+    let mut detections = Vec::new();
+    for perturbation in jitter {
+        let detection = match perturbation {
+            Perturbation::Sample(sample) => detector.update(sample),
+            Perturbation::Duplicate(sample) => {
+                let detection = detector.update(sample);
+                if notify {
+                    detector.notify_duplicate(1);
+                }
+                detection
+            }
+            Perturbation::Dropped => {
+                if notify {
+                    detector.notify_gap(1);
+                }
+                None
+            }
+        };
+        detections.extend(detection);
+    }
+
+    detections
+}
+
+#[test]
+fn test_jitter_is_deterministic() {
+    let samples = preprocessed();
+
+    let count = |perturbation: fn(&Perturbation) -> bool| {
+        Jitter::new(samples.iter().copied(), 42)
+            .with_drop_rate(0.01)
+            .with_duplicate_rate(0.02)
+            .filter(perturbation)
+            .count()
+    };
+
+    let dropped = count(|p| matches!(p, Perturbation::Dropped));
+    let duplicated = count(|p| matches!(p, Perturbation::Duplicate(_)));
+
+    assert_eq!(dropped, count(|p| matches!(p, Perturbation::Dropped)));
+    let expected = samples.len() as f32;
+    assert!(
+        (0.008..0.012).contains(&(dropped as f32 / expected)),
+        "{}",
+        dropped
+    );
+    assert!(
+        (0.016..0.024).contains(&(duplicated as f32 / expected)),
+        "{}",
+        duplicated
+    );
+}
+
+#[test]
+fn test_notified_jitter_keeps_timing() {
+    let samples = preprocessed();
+    let reference = detect(&samples, 0.0, 0.0, true);
+
+    for (drop_rate, duplicate_rate) in [(0.01, 0.0), (0.0, 0.01), (0.02, 0.02)] {
+        let detections = detect(&samples, drop_rate, duplicate_rate, true);
+
+        // Every beat is detected within 10 ms of the reference
+        let tolerance = 720.sps().ms_to_samples(10.0) as u32;
+        assert_eq!(reference.len(), detections.len());
+        for (expected, idx) in reference.iter().zip(&detections) {
+            assert!(
+                expected.abs_diff(*idx) <= tolerance,
+                "{} != {}",
+                expected,
+                idx
+            );
+        }
+    }
+}
+
+#[test]
+fn test_unnotified_jitter_drifts() {
+    let samples = preprocessed();
+    let reference = detect(&samples, 0.0, 0.0, false);
+    let detections = detect(&samples, 0.02, 0.0, false);
+
+    // The beats are still detected, but the indices drift as samples are lost
+    assert_eq!(reference.len(), detections.len());
+    assert!(reference.last().unwrap() - detections.last().unwrap() > 100);
+}
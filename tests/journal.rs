@@ -0,0 +1,290 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::journal::{
+    JournalError, JournalReader, JournalState, JournalWriter, JOURNAL_VERSION, MAX_RECORD_SIZE,
+};
+use qrs_detector::prelude::*;
+
+/// CRC-32 as computed by zlib, independent from the implementation of the crate.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// Replaces the length and checksum of an edited record.
+fn seal(mut record: Vec<u8>) -> Vec<u8> {
+    record.truncate(record.len() - 4);
+    record[1] = record.len() as u8 + 4;
+    let checksum = crc32(&record);
+    record.extend_from_slice(&checksum.to_le_bytes());
+    record
+}
+
+/// A xorshift generator, to keep the fuzz test reproducible.
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.next() as usize % n
+    }
+}
+
+fn journal_records(seconds: usize) -> (Vec<Vec<u8>>, Vec<JournalState>) {
+    let signal = preprocessed();
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut writer = JournalWriter::new();
+
+    let mut records = Vec::new();
+    let mut states = Vec::new();
+    for (i, &sample) in signal.iter().take(seconds * 720).enumerate() {
+        detector.update(sample);
+        if i % 1440 == 1439 {
+            let state = detector.journal_state();
+            records.push(writer.write(&state).as_bytes().to_vec());
+            states.push(state);
+        }
+    }
+    (records, states)
+}
+
+#[test]
+fn test_records_are_small() {
+    let signal = preprocessed();
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut writer = JournalWriter::new();
+
+    let mut sizes = Vec::new();
+    for (i, &sample) in signal.iter().enumerate() {
+        detector.update(sample);
+        // Every 2 seconds
+        if i % 1440 == 1439 {
+            let record = writer.write(&detector.journal_state());
+            assert_eq!(record.is_keyframe(), sizes.len() % 8 == 0);
+            sizes.push(record.as_bytes().len());
+        }
+    }
+
+    let deltas = sizes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % 8 != 0)
+        .map(|(_, size)| *size)
+        .collect::<Vec<_>>();
+    assert!(deltas.iter().all(|size| *size <= 40), "{:?}", sizes);
+    assert!(sizes.iter().all(|size| *size <= MAX_RECORD_SIZE));
+}
+
+#[test]
+fn test_restore_after_power_loss() {
+    let signal = preprocessed();
+    let (before, after) = signal.split_at(signal.len() / 2);
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut writer = JournalWriter::new().with_keyframe_interval(4);
+    let mut storage = Vec::new();
+    for (i, &sample) in before.iter().enumerate() {
+        detector.update(sample);
+        if i % 1440 == 1439 {
+            let record = writer.write(&detector.journal_state());
+            if record.is_keyframe() {
+                storage.clear();
+            }
+            storage.extend_from_slice(record.as_bytes());
+        }
+    }
+    let journaled = detector.journal_state();
+
+    // The last record was torn by the power loss
+    let mut torn = writer.write(&journaled).as_bytes().to_vec();
+    torn.truncate(torn.len() - 3);
+    let complete = storage.len();
+    storage.extend_from_slice(&torn);
+
+    let mut reader = JournalReader::new();
+    assert_eq!(complete, reader.replay(&storage));
+    let state = *reader.state().unwrap();
+    assert!(state.sample_index() <= journaled.sample_index());
+    assert!(journaled.sample_index() - state.sample_index() < 1440);
+    assert!(!state.mm_values().is_empty());
+
+    let mut restored = QrsDetector::new::<216, 36>(720.sps());
+    restored.restore_journal(&state);
+    restored.notify_gap(before.len() as u32 - state.sample_index());
+    assert!(restored.thresholds().m.is_some());
+
+    let mut cold = QrsDetector::new::<216, 36>(720.sps());
+    cold.notify_gap(before.len() as u32);
+
+    let mut continued = Vec::new();
+    let mut restored_detections = Vec::new();
+    let mut cold_detections = Vec::new();
+    for &sample in after {
+        continued.extend(detector.update(sample));
+        restored_detections.extend(restored.update(sample));
+        cold_detections.extend(cold.update(sample));
+    }
+
+    // The restored detector finds the beats the cold one misses while initializing
+    assert!(restored_detections[0] < before.len() as u32 + 720);
+    assert!(cold_detections[0] > before.len() as u32 + 3 * 720);
+    assert!(restored_detections.len() > cold_detections.len());
+
+    // The detection indices continue where the uninterrupted detector is
+    for (continued, restored) in continued
+        .iter()
+        .rev()
+        .zip(restored_detections.iter().rev())
+        .take(10)
+    {
+        assert!(
+            continued.abs_diff(*restored) <= 7,
+            "{continued} != {restored}"
+        );
+    }
+}
+
+#[test]
+fn test_replay_errors() {
+    let signal = preprocessed();
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut writer = JournalWriter::new();
+
+    let mut records = Vec::new();
+    for (i, &sample) in signal.iter().take(20 * 720).enumerate() {
+        detector.update(sample);
+        if i % 1440 == 1439 {
+            records.push(writer.write(&detector.journal_state()).as_bytes().to_vec());
+        }
+    }
+
+    // Erased storage
+    let mut reader = JournalReader::new();
+    assert_eq!(
+        Err(JournalError::UnsupportedVersion(0xFF)),
+        reader.apply(&[0xFF; MAX_RECORD_SIZE])
+    );
+    assert_eq!(0, reader.replay(&[0xFF; MAX_RECORD_SIZE]));
+
+    // Corrupted record
+    let mut corrupted = records[0].clone();
+    corrupted[3] ^= 1;
+    assert_eq!(Err(JournalError::Checksum), reader.apply(&corrupted));
+    assert_eq!(
+        Err(JournalError::Truncated),
+        reader.apply(&records[0][..records[0].len() - 1])
+    );
+    assert_eq!(None, reader.state());
+
+    // A record is missing
+    assert_eq!(Ok(records[0].len()), reader.apply(&records[0]));
+    let delta = records
+        .iter()
+        .skip(2)
+        .find(|record| record[11] >> 4 != 0)
+        .unwrap();
+    assert_eq!(Err(JournalError::MissingBase), reader.apply(delta));
+
+    // Replaying every record reconstructs the state
+    let mut reader = JournalReader::new();
+    let bytes = records.concat();
+    assert_eq!(bytes.len(), reader.replay(&bytes));
+    assert_eq!(Some(&detector.journal_state()), reader.state());
+}
+
+#[test]
+fn test_record_layout() {
+    let (records, states) = journal_records(20);
+
+    for record in &records {
+        assert_eq!(JOURNAL_VERSION, record[0]);
+        assert_eq!(record.len(), record[1] as usize);
+        let (data, checksum) = record.split_at(record.len() - 4);
+        assert_eq!(crc32(data).to_le_bytes(), checksum);
+    }
+
+    // Fields appended by a later revision of the format are skipped
+    let mut extended = records[0].clone();
+    extended.splice(extended.len() - 4..extended.len() - 4, [0xA5; 6]);
+    let extended = seal(extended);
+
+    let mut reader = JournalReader::new();
+    let mut bytes = extended.clone();
+    bytes.extend(records[1..].concat());
+    assert_eq!(bytes.len(), reader.replay(&bytes));
+    assert_eq!(states.last(), reader.state());
+}
+
+#[test]
+fn test_fuzz_corrupted_records() {
+    let (records, states) = journal_records(40);
+    let mut rng = Rng(0x2545_F491);
+
+    for _ in 0..20_000 {
+        let target = rng.below(records.len());
+        let mut record = records[target].clone();
+        match rng.below(5) {
+            0 => {
+                let bit = rng.below(8 * record.len());
+                record[bit / 8] ^= 1 << (bit % 8);
+            }
+            1 => {
+                let idx = rng.below(record.len());
+                record[idx] = rng.next() as u8;
+            }
+            2 => record.truncate(rng.below(record.len())),
+            3 => {
+                let len = rng.below(MAX_RECORD_SIZE + 8);
+                record = (0..len).map(|_| rng.next() as u8).collect();
+                if !record.is_empty() && rng.below(2) == 0 {
+                    record[0] = JOURNAL_VERSION;
+                }
+            }
+            _ => {
+                // Damage that the checksum can not detect
+                let idx = 3 + rng.below(record.len() - 7);
+                record[idx] = rng.next() as u8;
+                record = seal(record);
+            }
+        }
+
+        let mut reader = JournalReader::new();
+        reader.replay(&records[..target].concat());
+        let base = reader.state().copied();
+
+        match reader.apply(&record) {
+            Ok(len) => {
+                assert!(len <= record.len());
+                let state = reader.state().unwrap();
+                if record == records[target] {
+                    assert_eq!(&states[target], state);
+                } else {
+                    assert!(state.mm_values().iter().all(|m| m.is_finite()));
+                    assert!(state.rr_intervals().iter().all(|rr| *rr > 0));
+                    assert!(
+                        state.sample_index().wrapping_sub(state.last_detection()) <= u32::MAX / 2
+                    );
+                }
+            }
+            Err(_) => assert_eq!(base.as_ref(), reader.state()),
+        }
+
+        // A corrupted record never makes the reader skip past it
+        let mut storage = record.clone();
+        storage.extend_from_slice(&[0xFF; 16]);
+        assert!(JournalReader::new().replay(&storage) <= record.len());
+    }
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::samples;
+use qrs_detector::leads::{ComplexLead, DifferentialInput, GainEstimator, Lead, LimbLeads};
+use qrs_detector::prelude::*;
+
+/// Runs the detector on `samples`, using the canonical preprocessing.
+fn detect(samples: &[f32]) -> Vec<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+
+    let averaged = samples
+        .windows(4)
+        .map(|window| window.iter().sum::<f32>() / 4.0)
+        .collect::<Vec<_>>();
+
+    averaged
+        .windows(3)
+        .filter_map(|window| detector.update((window[0] - window[2]).abs()))
+        .collect()
+}
+
+#[test]
+fn test_differential_input_removes_offset_mismatch() {
+    let samples = samples();
+    let reference = detect(&samples);
+
+    // Each electrode sees half of the signal, with different offsets and gains
+    let mut input = DifferentialInput::new(720.sps()).with_gains(1.0, 1.0 / 1.2);
+    let combined = samples
+        .iter()
+        .map(|x| input.combine(0.5 * x + 100.0, -0.6 * x - 50.0))
+        .collect::<Vec<_>>();
+
+    // The output has no DC offset
+    let tail = &combined[combined.len() - 7200..];
+    let mean = tail.iter().sum::<f32>() / tail.len() as f32;
+    assert!(mean.abs() < 0.05, "{}", mean);
+
+    let detections = detect(&combined);
+    assert_eq!(reference.len(), detections.len());
+    for (expected, idx) in reference.iter().zip(&detections) {
+        assert!(expected.abs_diff(*idx) <= 2, "{} != {}", expected, idx);
+    }
+}
+
+#[test]
+fn test_differential_input_polarity() {
+    let mut input = DifferentialInput::new(100.sps());
+    let mut inverted = DifferentialInput::new(100.sps()).with_inverted(true);
+
+    for i in 0..1000 {
+        let a = (i as f32 * 0.1).sin() + 3.0;
+        let b = -2.0;
+        assert_eq!(input.combine(a, b), -inverted.combine(a, b));
+    }
+}
+
+#[test]
+fn test_complex_lead_of_scaled_leads() {
+    let samples = samples();
+    let averaged = samples
+        .windows(4)
+        .map(|window| window.iter().sum::<f32>() / 4.0)
+        .collect::<Vec<_>>();
+
+    let reference = detect(&samples);
+
+    // The complex lead of scaled copies is a scaled derivative of the single lead
+    let mut complex_lead = ComplexLead::<3>::new();
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let detections = averaged
+        .iter()
+        .filter_map(|&x| detector.update(complex_lead.update(&[x, -x, 2.0 * x])))
+        .collect::<Vec<_>>();
+
+    // The complex lead outputs 2 samples before the first derivative
+    let shifted = detections.iter().map(|idx| idx - 2).collect::<Vec<_>>();
+    assert_eq!(reference, shifted);
+}
+
+#[test]
+fn test_complex_lead_combines_leads() {
+    let mut complex_lead = ComplexLead::<2>::new();
+
+    assert_eq!(0.0, complex_lead.update(&[0.0, 0.0]));
+    assert_eq!(0.0, complex_lead.update(&[1.0, 1.0]));
+    // |2 - 0| and |-2 - 0|
+    assert_eq!(2.0, complex_lead.update(&[2.0, -2.0]));
+    // |1 - 1| and |5 - 1|
+    assert_eq!(2.0, complex_lead.update(&[1.0, 5.0]));
+
+    complex_lead.clear();
+    assert_eq!(0.0, complex_lead.update(&[10.0, 10.0]));
+}
+
+#[test]
+fn test_limb_lead_derivation() {
+    let i = 0.8;
+    let ii = 1.4;
+    let leads = LimbLeads::from_i_ii(i, ii);
+
+    // Einthoven's law
+    assert_eq!(leads.ii, leads.i + leads.iii);
+    // The augmented leads sum to zero
+    assert!((leads.avr + leads.avl + leads.avf).abs() < 1e-6);
+
+    // Compare with the electrode potentials
+    let (ra, la, ll) = (0.1, 0.9, 1.5);
+    let from_electrodes = LimbLeads::from_i_ii(la - ra, ll - ra);
+    assert!((from_electrodes.iii - (ll - la)).abs() < 1e-6);
+    assert!((from_electrodes.avr - (ra - (la + ll) / 2.0)).abs() < 1e-6);
+    assert!((from_electrodes.avl - (la - (ra + ll) / 2.0)).abs() < 1e-6);
+    assert!((from_electrodes.avf - (ll - (ra + la) / 2.0)).abs() < 1e-6);
+
+    for (lead, value) in Lead::ALL.iter().zip(leads.to_array()) {
+        assert_eq!(value, leads.get(*lead));
+    }
+}
+
+#[test]
+fn test_gain_imbalance_estimation() {
+    let samples = samples();
+    let averaged = samples
+        .windows(4)
+        .map(|window| window.iter().sum::<f32>() / 4.0)
+        .collect::<Vec<_>>();
+
+    let mut estimator = GainEstimator::<3>::new(720.sps());
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+
+    assert_eq!(None, estimator.relative_gains(0));
+
+    for window in averaged.windows(3) {
+        let x = window[2];
+        // The leads have different gains and offsets
+        let leads = [x, 0.5 * x + 2.0, -1.5 * x - 1.0];
+        let detected = detector.update((window[0] - window[2]).abs()).is_some();
+        estimator.update(&leads, detected);
+    }
+
+    let gains = estimator.relative_gains(0).unwrap();
+    assert_eq!(1.0, gains[0]);
+    assert!((gains[1] - 0.5).abs() < 0.05, "{:?}", gains);
+    assert!((gains[2] - 1.5).abs() < 0.05, "{:?}", gains);
+    assert_eq!(None, estimator.relative_gains(3));
+
+    estimator.clear();
+    assert_eq!(None, estimator.amplitudes());
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "preprocessing")]
+
+mod common;
+
+use std::f32::consts::PI;
+
+use common::samples;
+use qrs_detector::prelude::*;
+use qrs_detector::preprocessing::{MainsDetector, MainsFrequency, Preprocessor};
+use qrs_detector::spectrum::Goertzel;
+use qrs_detector::transform::SampleTransform;
+
+/// The record with mains interference, and a DC offset typical of unfiltered ADC data.
+fn with_interference(frequency: f32) -> Vec<f32> {
+    samples()
+        .into_iter()
+        .enumerate()
+        .map(|(i, sample)| 1.5 + sample + 0.05 * (2.0 * PI * frequency * i as f32 / 720.0).sin())
+        .collect()
+}
+
+#[test]
+fn test_mains_frequency_is_detected() {
+    assert_eq!(
+        Some(MainsFrequency::Hz50),
+        MainsFrequency::detect(720.sps(), with_interference(50.0))
+    );
+    assert_eq!(
+        Some(MainsFrequency::Hz60),
+        MainsFrequency::detect(720.sps(), with_interference(60.0))
+    );
+
+    // No interference
+    assert_eq!(None, MainsFrequency::detect(720.sps(), samples()));
+
+    // Not enough samples
+    assert_eq!(
+        None,
+        MainsFrequency::detect(720.sps(), with_interference(50.0).into_iter().take(1000))
+    );
+}
+
+#[test]
+fn test_mains_detector_streaming() {
+    let mut detector = MainsDetector::new(720.sps());
+    let samples = with_interference(60.0);
+
+    for &sample in &samples[..1439] {
+        assert!(!detector.update(sample));
+        assert_eq!(None, detector.result());
+    }
+    assert!(detector.update(samples[1439]));
+    assert_eq!(Some(MainsFrequency::Hz60), detector.result());
+
+    detector.clear();
+    assert!(!detector.is_complete());
+}
+
+#[test]
+fn test_auto_notch_removes_interference() {
+    for (frequency, mains) in [(50.0, MainsFrequency::Hz50), (60.0, MainsFrequency::Hz60)] {
+        let mut preprocessor = Preprocessor::new(720.sps())
+            .with_highpass(0.5)
+            .with_auto_notch();
+        let mut fixed = Preprocessor::new(720.sps())
+            .with_highpass(0.5)
+            .with_notch(mains);
+
+        let mut power = Goertzel::new(720.sps(), frequency);
+        let mut expected = Goertzel::new(720.sps(), frequency);
+        for (i, sample) in with_interference(frequency).into_iter().enumerate() {
+            let output = preprocessor.transform(sample);
+            let reference = fixed.transform(sample);
+
+            // Measure after the detection and the settling of the notch filter
+            if i >= 10 * 720 {
+                power.update(output);
+                expected.update(reference);
+            }
+        }
+
+        assert_eq!(Some(mains), preprocessor.mains_frequency());
+        assert!(power.power() < 0.01 * 0.05 * 0.05 / 2.0);
+        assert!((power.power() - expected.power()).abs() < 1e-7);
+        assert_eq!(fixed.group_delay(), preprocessor.group_delay());
+    }
+}
+
+#[test]
+fn test_auto_notch_without_interference() {
+    let mut preprocessor = Preprocessor::new(720.sps()).with_auto_notch();
+    let samples = samples();
+
+    let outputs = samples
+        .iter()
+        .map(|&sample| preprocessor.transform(sample))
+        .collect::<Vec<_>>();
+
+    // No filter is applied
+    assert_eq!(None, preprocessor.mains_frequency());
+    assert_eq!(samples, outputs);
+}
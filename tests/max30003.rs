@@ -0,0 +1,153 @@
+#![cfg(feature = "sensors")]
+
+mod common;
+
+use common::codes;
+use qrs_detector::config::InputMode;
+use qrs_detector::max30003::{EcgFifoWord, Etag, Max30003Detector, Max30003Error, FIFO_DEPTH};
+use qrs_detector::prelude::*;
+
+fn detector() -> QrsDetector<[f32; 216], [f32; 36]> {
+    QrsDetector::new::<216, 36>(720.sps())
+        .with_config(Config::new().with_input_mode(InputMode::AbsDiff))
+}
+
+/// Encodes an `ECG_FIFO` register value.
+fn word(sample: i32, etag: u32) -> u32 {
+    ((sample as u32 & 0x3_FFFF) << 6) | (etag << 3)
+}
+
+#[test]
+fn test_decode_words() {
+    for sample in [0, 1, -1, 1000, -1000, (1 << 17) - 1, -(1 << 17)] {
+        for (bits, etag) in [
+            (0b000, Etag::Valid),
+            (0b001, Etag::FastMode),
+            (0b010, Etag::LastValid),
+            (0b011, Etag::LastFastMode),
+        ] {
+            assert_eq!(
+                EcgFifoWord { sample, etag },
+                EcgFifoWord::decode(word(sample, bits))
+            );
+        }
+    }
+
+    assert_eq!(
+        EcgFifoWord {
+            sample: 0,
+            etag: Etag::Empty
+        },
+        EcgFifoWord::decode(word(123, 0b110))
+    );
+    assert_eq!(Etag::Overflow, EcgFifoWord::decode(word(0, 0b111)).etag);
+    assert_eq!(
+        Etag::Reserved(0b100),
+        EcgFifoWord::decode(word(0, 0b100)).etag
+    );
+
+    // The PTAG bits and the bits above the register are ignored
+    assert_eq!(
+        EcgFifoWord {
+            sample: -2,
+            etag: Etag::LastValid
+        },
+        EcgFifoWord::decode(0xFF00_0000 | word(-2, 0b010) | 0b111)
+    );
+    assert_eq!(
+        EcgFifoWord::decode(0x12_3450),
+        EcgFifoWord::from_be_bytes([0x12, 0x34, 0x50])
+    );
+
+    assert!(Etag::LastFastMode.is_last() && Etag::LastFastMode.is_fast_mode());
+    assert!(!Etag::Valid.is_last() && Etag::Valid.has_sample());
+    assert!(Etag::Empty.is_last() && !Etag::Empty.has_sample());
+}
+
+#[test]
+fn test_fifo_words_match_direct_processing() {
+    let codes = codes();
+
+    let mut reference = detector();
+    let expected = codes
+        .iter()
+        .filter_map(|&code| reference.update(code as f32))
+        .collect::<Vec<_>>();
+    assert!(expected.len() > 20);
+
+    // Read in bursts of 8 words, the last one tagged, followed by an empty FIFO
+    let mut frontend = Max30003Detector::new(detector());
+    let mut detections = Vec::new();
+    for burst in codes.chunks(8) {
+        for (idx, &code) in burst.iter().enumerate() {
+            let etag = if idx == burst.len() - 1 { 0b010 } else { 0b000 };
+            detections.extend(frontend.push_word(word(code, etag)).unwrap());
+        }
+        assert!(frontend.is_fifo_drained());
+        assert_eq!(Ok(None), frontend.push_word(word(0, 0b110)));
+    }
+
+    assert_eq!(expected, detections);
+    assert_eq!(
+        codes.len() as u32,
+        frontend.detector().timeline().position()
+    );
+}
+
+#[test]
+fn test_overflow_and_invalid_words_are_gaps() {
+    let mut frontend = Max30003Detector::new(detector());
+
+    assert_eq!(Ok(None), frontend.push_word(word(10, 0b000)));
+    assert!(!frontend.is_fifo_drained());
+
+    assert_eq!(
+        Err(Max30003Error::Overflow),
+        frontend.push_word(word(0, 0b111))
+    );
+    assert!(frontend.is_fifo_drained());
+    assert_eq!(1, frontend.overflows());
+    assert_eq!(1 + FIFO_DEPTH, frontend.detector().timeline().position());
+    assert_eq!(FIFO_DEPTH, frontend.detector().timeline().gap_samples());
+
+    assert_eq!(
+        Err(Max30003Error::InvalidTag(0b101)),
+        frontend.push_word(word(0, 0b101))
+    );
+    assert_eq!(2 + FIFO_DEPTH, frontend.detector().timeline().position());
+
+    let mut frontend = Max30003Detector::new(detector()).with_overflow_gap(100);
+    assert_eq!(
+        Err(Max30003Error::Overflow),
+        frontend.push_word(word(0, 0b111))
+    );
+    assert_eq!(100, frontend.detector().timeline().gap_samples());
+}
+
+#[cfg(feature = "quality")]
+#[test]
+fn test_fast_recovery_is_a_quality_hold() {
+    let codes = codes();
+    let fast_mode = 14_400..14_544;
+
+    let mut frontend = Max30003Detector::new(detector().with_history([Detection::default(); 1]));
+    let mut first_after = None;
+    for (idx, &code) in codes.iter().enumerate() {
+        let word = if fast_mode.contains(&idx) {
+            // The channel is clamped during fast recovery
+            word(0, 0b001)
+        } else {
+            word(code, 0b000)
+        };
+        if frontend.push_word(word).unwrap().is_some() && idx >= fast_mode.end {
+            let detection = frontend.detector().recent_detections().last().unwrap();
+            first_after.get_or_insert(detection);
+        }
+    }
+
+    // The step at the end of the recovery is labeled
+    let detection = first_after.unwrap();
+    assert!(detection
+        .reasons
+        .contains(qrs_detector::Reason::QualityHold));
+}
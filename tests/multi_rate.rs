@@ -0,0 +1,118 @@
+use qrs_detector::multi_rate::MultiRateDetector;
+use qrs_detector::prelude::*;
+
+/// A 1 Hz, 1000 sps signal of triangular QRS complexes peaking at 500 ms, with an elevated ST
+/// segment.
+fn ecg(i: u32) -> f32 {
+    let t = (i % 1000) as f32 - 500.0;
+    match t {
+        t if t.abs() < 40.0 => 1.0 - t.abs() / 40.0,
+        t if (40.0..200.0).contains(&t) => 0.1,
+        _ => 0.0,
+    }
+}
+
+#[test]
+fn test_measurements_on_full_rate_signal() {
+    let mut prev = [0.0; 2];
+    let detector = QrsDetector::new::<75, 12>(250.sps()).with_transform(move |sample: f32| {
+        let slope = (sample - prev[0]).abs();
+        prev = [prev[1], sample];
+        slope
+    });
+    let mut multi_rate = MultiRateDetector::new(detector, 4, [0.0; 512]);
+    assert_eq!(1000.sps(), multi_rate.sampling_frequency());
+
+    let mut beats = Vec::new();
+    for i in 0..20_000 {
+        if let Some(beat) = multi_rate.push(ecg(i)) {
+            // Measurements are available 200 ms after the detection
+            assert!(i > beat.peak_index && i - beat.peak_index <= 250);
+            beats.push(beat);
+        }
+    }
+
+    assert!(beats.len() > 10, "{} beats", beats.len());
+    for beat in beats {
+        assert_eq!(500, beat.peak_index % 1000, "{:?}", beat);
+        assert_eq!(1.0, beat.amplitude);
+        assert!((38.0..=41.0).contains(&beat.qrs_width_ms), "{:?}", beat);
+        assert_eq!(0.1, beat.st_level);
+        assert!(!beat.clipped);
+
+        // The complex starts on the baseline, and ends before the step to the ST level
+        let fiducials = beat.fiducials;
+        assert_eq!(beat.peak_index, fiducials.r_index);
+        assert_eq!(Some(beat.peak_index - 40), fiducials.q_index);
+        assert_eq!(Some(beat.peak_index + 39), fiducials.s_index);
+    }
+}
+
+#[test]
+fn test_negative_qrs() {
+    let mut prev = [0.0; 2];
+    let detector = QrsDetector::new::<75, 12>(250.sps()).with_transform(move |sample: f32| {
+        let slope = (sample - prev[0]).abs();
+        prev = [prev[1], sample];
+        slope
+    });
+    let mut multi_rate = MultiRateDetector::new(detector, 4, [0.0; 512]);
+
+    let beats = (0..20_000)
+        .filter_map(|i| multi_rate.push(-ecg(i)))
+        .collect::<Vec<_>>();
+
+    assert!(!beats.is_empty());
+    for beat in beats {
+        assert_eq!(-1.0, beat.amplitude);
+        assert!((38.0..=41.0).contains(&beat.qrs_width_ms), "{:?}", beat);
+        assert_eq!(-0.1, beat.st_level);
+    }
+}
+
+/// A 1 Hz, 1000 sps signal of raised cosine QRS complexes peaking at 500 ms, clipped at `limit`.
+fn clipped_ecg(i: u32, limit: f32) -> f32 {
+    let t = (i % 1000) as f32 - 500.0;
+    let value = if t.abs() < 40.0 {
+        0.5 + 0.5 * (t * core::f32::consts::PI / 40.0).cos()
+    } else {
+        0.0
+    };
+    value.min(limit)
+}
+
+#[test]
+fn test_clipped_peak() {
+    let mut prev = [0.0; 2];
+    let detector = QrsDetector::new::<75, 12>(250.sps()).with_transform(move |sample: f32| {
+        let slope = (sample - prev[0]).abs();
+        prev = [prev[1], sample];
+        slope
+    });
+    let mut multi_rate = MultiRateDetector::new(detector, 4, [0.0; 512]);
+
+    let mut beats = Vec::new();
+    for i in 0..20_000 {
+        // The amplifier clips from the 10th second
+        let limit = if i < 10_000 { f32::MAX } else { 0.7 };
+        if let Some(beat) = multi_rate.push(clipped_ecg(i, limit)) {
+            beats.push(beat);
+        }
+    }
+
+    let (clipped, unclipped): (Vec<_>, Vec<_>) = beats
+        .into_iter()
+        .partition(|beat| beat.peak_index >= 10_000);
+    assert!(clipped.len() > 5 && unclipped.len() > 5);
+    for beat in unclipped {
+        assert!(!beat.clipped, "{:?}", beat);
+        assert_eq!(1.0, beat.amplitude);
+    }
+    for beat in clipped {
+        // The peak is in the middle of the flat top, the amplitude is close to the true one
+        assert!(beat.clipped, "{:?}", beat);
+        assert_eq!(500, beat.peak_index % 1000, "{:?}", beat);
+        assert!((beat.amplitude - 1.0).abs() < 0.1, "{:?}", beat);
+        assert!((38.0..=42.0).contains(&beat.qrs_width_ms), "{:?}", beat);
+    }
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::total_synth;
+use qrs_detector::prelude::*;
+use qrs_detector::synth::SynthParams;
+
+/// The length of the synthetic records, in seconds.
+const SECONDS: usize = 100;
+
+const fn neonate(bpm: f32) -> SynthParams {
+    SynthParams {
+        heart_rate_bpm: bpm,
+        final_heart_rate_bpm: bpm,
+        rr_variability: 0.15,
+        r_amplitude_mv: 0.3,
+        final_r_amplitude_mv: 0.3,
+        t_wave_ratio: 0.8,
+        noise_mv: 0.05,
+        ..SynthParams::REST
+    }
+}
+
+#[test]
+fn test_neonatal_preset_on_neonatal_heart_rates() {
+    for bpm in [100.0, 140.0, 180.0, 230.0] {
+        let metrics = total_synth(Config::neonatal(), neonate(bpm), SECONDS);
+        // The R peak of a detection at the end of the record may be cut off
+        assert!(metrics.false_positives <= 1, "{bpm} bpm: {metrics:?}");
+        assert_eq!(0, metrics.false_negatives, "{bpm} bpm: {metrics:?}");
+    }
+}
+
+#[test]
+fn test_default_refractory_period_misses_beats_at_230_bpm() {
+    let metrics = total_synth(Config::new(), neonate(230.0), SECONDS);
+    assert!(metrics.false_negatives > 10, "{metrics:?}");
+}
+
+#[test]
+fn test_neonatal_preset_on_adult_rhythms() {
+    for params in [SynthParams::REST, SynthParams::ATRIAL_FIBRILLATION] {
+        let metrics = total_synth(Config::neonatal(), params, SECONDS);
+        assert_eq!(0, metrics.false_positives, "{metrics:?}");
+        assert_eq!(0, metrics.false_negatives, "{metrics:?}");
+    }
+}
@@ -0,0 +1,103 @@
+mod common;
+
+#[cfg(feature = "quality")]
+use common::noise;
+use qrs_detector::noise::{MadNoise, NoiseEstimator, RmsNoise};
+use qrs_detector::prelude::*;
+
+#[test]
+fn test_rms_noise_excludes_qrs() {
+    let mut estimator = RmsNoise::new(100.sps());
+    assert_eq!(None, estimator.noise_level());
+
+    for i in 0..100 {
+        // Large values are excluded when marked as QRS
+        let in_qrs = i % 10 == 0;
+        let sample = if in_qrs {
+            100.0
+        } else if i % 2 == 0 {
+            0.5
+        } else {
+            -0.5
+        };
+        estimator.update(sample, in_qrs);
+    }
+
+    assert_eq!(Some(0.5), estimator.noise_level());
+
+    estimator.clear();
+    assert_eq!(None, estimator.noise_level());
+}
+
+#[test]
+fn test_mad_noise_ignores_outliers() {
+    let mut estimator = MadNoise::<9>::new();
+
+    for sample in [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 1000.0] {
+        estimator.update(sample, false);
+    }
+
+    // The median is 5, the median of the absolute deviations is 2
+    let level = estimator.noise_level().unwrap();
+    assert!((level - 2.0 * 1.4826).abs() < 1e-5, "{}", level);
+}
+
+#[test]
+fn test_noise_level_of_reference_record() {
+    let samples = common::samples();
+
+    let mut reference = QrsDetector::new::<216, 36>(720.sps());
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_noise_estimator(RmsNoise::new(720.sps()));
+
+    for window in samples.windows(3) {
+        let slope = (window[0] - window[2]).abs();
+        assert_eq!(reference.update(slope), detector.update(slope));
+    }
+
+    // The noise level is well below the detection threshold
+    let level = detector.noise_level().unwrap();
+    let threshold = detector.thresholds().total().unwrap();
+    assert!(
+        level > 0.0 && level * 2.0 < threshold,
+        "{} {}",
+        level,
+        threshold
+    );
+}
+
+#[cfg(feature = "quality")]
+#[test]
+fn test_noise_gate() {
+    let signal = |i: usize, seed: &mut u32| {
+        if i < 2500 {
+            if i % 250 < 5 {
+                0.5
+            } else {
+                0.0
+            }
+        } else {
+            // Noise without QRS complexes
+            0.6 * noise(seed)
+        }
+    };
+
+    let mut seed = 1;
+    let mut ungated = QrsDetector::new::<75, 12>(250.sps());
+    let ungated_detections = (0..5000)
+        .filter_map(|i| ungated.update(signal(i, &mut seed)))
+        .filter(|&idx| idx > 2750)
+        .count();
+
+    let mut seed = 1;
+    let mut gated =
+        QrsDetector::new::<75, 12>(250.sps()).with_noise_estimator(RmsNoise::new(250.sps()));
+    let gated_detections = (0..5000)
+        .filter_map(|i| gated.update(signal(i, &mut seed)))
+        .filter(|&idx| idx > 2750)
+        .count();
+
+    assert!(ungated_detections > 0);
+    assert_eq!(0, gated_detections);
+    assert!(gated.suppressed_counts().noise > 0);
+}
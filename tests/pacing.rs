@@ -0,0 +1,96 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+
+/// Adds a pacing artifact 80ms before every other beat. Returns the signal, the pulses and the
+/// beats of the original signal.
+fn paced_signal() -> (Vec<f32>, Vec<usize>, Vec<u32>) {
+    let mut signal = preprocessed();
+    let mut reference = QrsDetector::new::<216, 36>(720.sps());
+    let beats = signal
+        .iter()
+        .filter_map(|&sample| reference.update(sample))
+        .collect::<Vec<_>>();
+    let peak = signal.iter().copied().fold(0.0, f32::max);
+
+    let pulses = beats
+        .iter()
+        .skip(4)
+        .step_by(2)
+        .map(|&beat| beat as usize - 58)
+        .collect::<Vec<_>>();
+    for &pulse in &pulses {
+        signal[pulse] += 5.0 * peak;
+        signal[pulse + 1] += 2.0 * peak;
+    }
+    (signal, pulses, beats)
+}
+
+#[test]
+fn test_paced_beats_are_labeled() {
+    let (signal, pulses, beats) = paced_signal();
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_history([Detection::default(); 1]);
+
+    let mut detections = Vec::new();
+    for (i, &sample) in signal.iter().enumerate() {
+        if pulses.contains(&i) {
+            detector.notify_pace(0);
+        }
+        if detector.update(sample).is_some() {
+            detections.push(detector.recent_detections().last().unwrap());
+        }
+    }
+
+    // The artifacts are blanked, every beat is found
+    assert_eq!(beats.len(), detections.len());
+    for (detection, beat) in detections.iter().zip(beats.iter()) {
+        assert!(detection.index.abs_diff(*beat) <= 2, "{detection} {beat}");
+    }
+
+    // Only the beats following a pulse are labeled
+    for detection in &detections {
+        let paced = pulses
+            .iter()
+            .any(|&pulse| (1..100).contains(&(detection.index as i64 - pulse as i64)));
+        assert_eq!(paced, detection.paced, "{detection}");
+    }
+}
+
+#[test]
+fn test_unreported_pulses_disturb_detection() {
+    let (signal, _, beats) = paced_signal();
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+
+    let detections = signal
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect::<Vec<_>>();
+
+    // The artifacts are detected instead of the beats, and raise the thresholds
+    let matching = detections
+        .iter()
+        .filter(|detection| beats.iter().any(|beat| beat.abs_diff(**detection) <= 2))
+        .count();
+    assert!(matching < beats.len() * 3 / 4, "{matching} {}", beats.len());
+}
+
+#[test]
+fn test_late_pace_report() {
+    let signal = preprocessed();
+    let mut reference = QrsDetector::new::<216, 36>(720.sps());
+    let mut detector = QrsDetector::new::<216, 36>(720.sps())
+        .with_config(Config::new().with_pace_blanking(30.0))
+        .with_history([Detection::default(); 1]);
+
+    // Pulses reported after their blanking and capture periods have passed have no effect
+    for &sample in signal.iter() {
+        detector.notify_pace(720);
+        let detection = detector.update(sample);
+        assert_eq!(reference.update(sample), detection);
+        if detection.is_some() {
+            assert!(!detector.recent_detections().last().unwrap().paced);
+        }
+    }
+}
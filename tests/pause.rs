@@ -0,0 +1,38 @@
+use qrs_detector::prelude::*;
+
+/// 1 Hz pulses, with a pause between 15 and 25 seconds. Returns the R threshold values.
+fn r_with_pause(config: Config) -> Vec<f32> {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps()).with_config(config);
+
+    (0..40 * 250)
+        .map(|i| {
+            let pause = (15 * 250..25 * 250).contains(&i);
+            detector.update(if i % 250 < 5 && !pause { 1.0 } else { 0.0 });
+            detector.thresholds().r
+        })
+        .collect()
+}
+
+#[test]
+fn test_r_returns_to_zero_after_pause() {
+    let r = r_with_pause(Config::new());
+
+    // R is active before the pause
+    assert!(r[15 * 250 - 1] < 0.0);
+
+    // Timeout is 3 RR intervals after the last detection
+    assert!(r[17 * 250 - 10] < 0.0);
+    assert!(r[17 * 250 + 10..25 * 250].iter().all(|&r| r == 0.0));
+
+    // RR intervals are collected again after the rhythm resumes
+    assert!(r[25 * 250..30 * 250 + 150].iter().all(|&r| r == 0.0));
+    assert!(r[39 * 250..].iter().any(|&r| r < 0.0));
+}
+
+#[test]
+fn test_r_timeout_is_configurable() {
+    let r = r_with_pause(Config::new().with_r_timeout(5.0));
+
+    assert!(r[19 * 250 - 10] < 0.0);
+    assert!(r[19 * 250 + 10..25 * 250].iter().all(|&r| r == 0.0));
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "preprocessing")]
+
+mod common;
+
+use std::f32::consts::PI;
+
+use qrs_detector::preprocessing::{
+    moving_average, to_fixed_fir, windowed_sinc, Biquad, BiquadCoefficients, Fir, FixedBiquad,
+    FixedFir,
+};
+use qrs_detector::sampling::SamplingFrequency;
+use qrs_detector::transform::SampleTransform;
+
+const FS: SamplingFrequency = SamplingFrequency::from_sps(500.0);
+
+// Evaluated at compile time
+const NOTCH_50: BiquadCoefficients = BiquadCoefficients::notch(FS, 50.0, 30.0);
+const LOWPASS_40: BiquadCoefficients = BiquadCoefficients::lowpass(FS, 40.0, 0.707);
+const HIGHPASS_05: BiquadCoefficients = BiquadCoefficients::highpass(FS, 0.5, 0.707);
+const SINC: [f32; 31] = windowed_sinc(FS, 40.0);
+
+/// Returns the amplitude of the filtered sine wave of `frequency` Hz, after the filter settled.
+fn gain(mut filter: impl SampleTransform, frequency: f32) -> f32 {
+    (0..5000)
+        .map(|i| filter.transform((2.0 * PI * frequency * i as f32 / FS.raw()).sin()))
+        .skip(4000)
+        .fold(0.0, |max, sample| sample.abs().max(max))
+}
+
+#[test]
+fn test_const_coefficients_match_runtime_calculation() {
+    let w0 = 2.0 * PI * 50.0 / 500.0;
+    let alpha = w0.sin() / 60.0;
+    let a0 = 1.0 + alpha;
+
+    let expected = BiquadCoefficients {
+        b0: 1.0 / a0,
+        b1: -2.0 * w0.cos() / a0,
+        b2: 1.0 / a0,
+        a1: -2.0 * w0.cos() / a0,
+        a2: (1.0 - alpha) / a0,
+    };
+
+    for (a, b) in [
+        (NOTCH_50.b0, expected.b0),
+        (NOTCH_50.b1, expected.b1),
+        (NOTCH_50.b2, expected.b2),
+        (NOTCH_50.a1, expected.a1),
+        (NOTCH_50.a2, expected.a2),
+    ] {
+        assert!((a - b).abs() < 1e-6, "{NOTCH_50:?} != {expected:?}");
+    }
+}
+
+#[test]
+fn test_biquad_response() {
+    assert!(gain(Biquad::new(NOTCH_50), 50.0) < 0.01);
+    assert!(gain(Biquad::new(NOTCH_50), 10.0) > 0.99);
+
+    assert!(gain(Biquad::new(LOWPASS_40), 5.0) > 0.99);
+    assert!((gain(Biquad::new(LOWPASS_40), 40.0) - 0.707).abs() < 0.01);
+    assert!(gain(Biquad::new(LOWPASS_40), 150.0) < 0.1);
+
+    assert!(gain(Biquad::new(HIGHPASS_05), 10.0) > 0.99);
+    assert!(gain(Biquad::new(HIGHPASS_05), 0.05) < 0.02);
+}
+
+#[test]
+fn test_fir_response() {
+    assert!((SINC.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    assert!(gain(Fir::new(SINC), 5.0) > 0.98);
+    assert!(gain(Fir::new(SINC), 100.0) < 0.02);
+
+    let mut average = Fir::new(moving_average::<4>());
+    let output = [4.0, 8.0, 0.0, 0.0, 0.0, 0.0].map(|sample| average.transform(sample));
+    assert_eq!([1.0, 3.0, 3.0, 3.0, 2.0, 0.0], output);
+
+    average.clear();
+    assert_eq!(1.0, average.transform(4.0));
+}
+
+/// The shared test vector: the record, scaled to 16 bit ADC counts.
+fn test_vector() -> Vec<i32> {
+    common::samples()
+        .into_iter()
+        .map(|sample| (sample * 32768.0).round() as i32)
+        .collect()
+}
+
+/// Returns the largest difference between the outputs of the float and the integer filters.
+fn max_difference(mut float: impl SampleTransform, mut fixed: impl FnMut(i32) -> i32) -> f32 {
+    test_vector()
+        .into_iter()
+        .map(|sample| (float.transform(sample as f32) - fixed(sample) as f32).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn test_fixed_point_filters_match_float_filters() {
+    for coefficients in [NOTCH_50, LOWPASS_40, HIGHPASS_05] {
+        let mut fixed = FixedBiquad::new(coefficients.to_fixed());
+        let difference = max_difference(Biquad::new(coefficients), |x| fixed.transform(x));
+        // The f32 filter itself is off by a few LSB for the 0.5 Hz high-pass
+        assert!(difference <= 4.0, "{coefficients:?}: {difference}");
+    }
+
+    const FIXED_SINC: [i32; 31] = to_fixed_fir(&SINC);
+    let mut fixed = FixedFir::new(FIXED_SINC);
+    let difference = max_difference(Fir::new(SINC), |x| fixed.transform(x));
+    assert!(difference <= 1.0, "{difference}");
+
+    let mut fixed = FixedFir::new(to_fixed_fir(&moving_average::<4>()));
+    let difference = max_difference(Fir::new(moving_average::<4>()), |x| fixed.transform(x));
+    assert!(difference <= 1.0, "{difference}");
+}
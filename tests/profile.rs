@@ -0,0 +1,103 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::leads::Lead;
+use qrs_detector::prelude::*;
+use qrs_detector::profile::{PatientProfile, Polarity, ProfileError, ENCODED_SIZE};
+
+fn profile() -> PatientProfile {
+    PatientProfile {
+        r_amplitude: 0.75,
+        rr_mean_ms: 950.0,
+        rr_std_ms: 25.0,
+        polarity: Polarity::Negative,
+        preferred_lead: Some(Lead::AVF),
+    }
+}
+
+#[test]
+fn test_encoding() {
+    let bytes = profile().to_bytes();
+    assert_eq!(ENCODED_SIZE, bytes.len());
+    assert_eq!(Ok(profile()), PatientProfile::from_bytes(&bytes));
+
+    assert_eq!(
+        Err(ProfileError::Truncated),
+        PatientProfile::from_bytes(&bytes[..ENCODED_SIZE - 1])
+    );
+
+    let mut future = bytes;
+    future[0] = 2;
+    assert_eq!(
+        Err(ProfileError::UnsupportedVersion(2)),
+        PatientProfile::from_bytes(&future)
+    );
+
+    let mut invalid = bytes;
+    invalid[4..8].copy_from_slice(&f32::NAN.to_le_bytes());
+    assert_eq!(
+        Err(ProfileError::Invalid),
+        PatientProfile::from_bytes(&invalid)
+    );
+}
+
+#[test]
+fn test_warm_start() {
+    let signal = preprocessed();
+
+    let mut cold = QrsDetector::new::<216, 36>(720.sps());
+    let cold_detections = signal
+        .iter()
+        .filter_map(|&sample| cold.update(sample))
+        .collect::<Vec<_>>();
+
+    let mut warm = QrsDetector::new::<216, 36>(720.sps());
+    warm.warm_start(&PatientProfile {
+        r_amplitude: cold.peak_average().unwrap(),
+        ..profile()
+    });
+    let warm_detections = signal
+        .iter()
+        .filter_map(|&sample| warm.update(sample))
+        .collect::<Vec<_>>();
+
+    // The warm detector finds the beats the cold one misses while initializing
+    assert!(warm_detections[0] < 720, "{:?}", warm_detections);
+    assert!(cold_detections[0] > 3 * 720);
+    assert!(warm_detections.len() > cold_detections.len());
+
+    // Both detectors converge to the same beats
+    for (cold, warm) in cold_detections
+        .iter()
+        .rev()
+        .zip(warm_detections.iter().rev())
+        .take(10)
+    {
+        assert!(cold.abs_diff(*warm) <= 7, "{} != {}", cold, warm);
+    }
+}
+
+#[cfg(feature = "hrv")]
+#[test]
+fn test_session_profile() {
+    use qrs_detector::session::SessionAnalyzer;
+
+    let mut analyzer = SessionAnalyzer::new(QrsDetector::new::<216, 36>(720.sps()));
+    assert_eq!(None, analyzer.profile());
+
+    for sample in preprocessed() {
+        analyzer.push(sample);
+    }
+
+    let profile = analyzer.profile().unwrap();
+    let report = analyzer.report();
+    let hr = report.heart_rate.unwrap();
+
+    assert!(profile.is_valid());
+    assert!(
+        (60_000.0 / profile.rr_mean_ms - hr).abs() < 10.0,
+        "{:?}",
+        profile
+    );
+    assert_eq!(Ok(profile), PatientProfile::from_bytes(&profile.to_bytes()));
+}
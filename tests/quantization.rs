@@ -0,0 +1,138 @@
+#![cfg(feature = "preprocessing")]
+
+//! Detection on coarsely quantized signals.
+//!
+//! The reference record spans about 1.1 units, the quantized versions use an ADC input range of
+//! ±1 unit.
+
+mod common;
+
+use common::samples;
+use qrs_detector::prelude::*;
+use qrs_detector::preprocessing::{Dither, Preprocessor};
+use qrs_detector::transform::{Identity, SampleTransform};
+
+/// The allowed difference between detections on coarsely quantized signals, 25ms.
+const TOLERANCE: u32 = 18;
+
+fn lsb(bits: u32) -> f32 {
+    2.0 / (1 << bits) as f32
+}
+
+/// Quantizes `samples` as an ADC with a ±1 input range and `bits` resolution would.
+fn quantize(samples: &[f32], bits: u32) -> Vec<f32> {
+    let lsb = lsb(bits);
+    samples
+        .iter()
+        .map(|sample| (sample / lsb).round() * lsb)
+        .collect()
+}
+
+fn lowpass() -> Preprocessor {
+    Preprocessor::new(720.sps()).with_lowpass(40.0)
+}
+
+fn detect(samples: &[f32], mut transform: impl SampleTransform) -> Vec<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+
+    let filtered = samples
+        .iter()
+        .map(|&sample| transform.transform(sample))
+        .collect::<Vec<_>>();
+    let averaged = filtered
+        .windows(4)
+        .map(|window| window.iter().sum::<f32>() / 4.0)
+        .collect::<Vec<_>>();
+
+    averaged
+        .windows(3)
+        .map(|window| (window[0] - window[2]).abs())
+        .filter_map(|sample| detector.update(sample))
+        .collect()
+}
+
+/// Returns the largest difference between the detections, in samples, if the same number of
+/// QRS complexes were detected.
+fn max_difference(reference: &[u32], detections: &[u32]) -> Option<u32> {
+    if reference.len() != detections.len() {
+        return None;
+    }
+
+    reference
+        .iter()
+        .zip(detections.iter())
+        .map(|(reference, detection)| reference.abs_diff(*detection))
+        .max()
+}
+
+#[test]
+fn test_12_bit() {
+    let reference = detect(&samples(), Identity);
+    assert_eq!(38, reference.len());
+
+    let detections = detect(&quantize(&samples(), 12), Identity);
+    assert_eq!(reference, detections);
+}
+
+#[test]
+fn test_8_bit() {
+    let reference = detect(&samples(), Identity);
+    let detections = detect(&quantize(&samples(), 8), Identity);
+
+    let difference = max_difference(&reference, &detections);
+    assert!(matches!(difference, Some(0..=2)), "{difference:?}");
+}
+
+#[test]
+fn test_minimum_resolution() {
+    let reference = detect(&samples(), Identity);
+
+    // About 18 codes across the QRS complex are enough
+    let detections = detect(&quantize(&samples(), 5), Identity);
+    let difference = max_difference(&reference, &detections);
+    assert!(matches!(difference, Some(0..=TOLERANCE)), "{difference:?}");
+
+    // About 9 codes are not, the quantization steps are detected as QRS complexes
+    let detections = detect(&quantize(&samples(), 4), Identity);
+    assert!(detections.len() > reference.len() * 3 / 2);
+
+    // Unless they are smoothed by a low-pass filter
+    let reference = detect(&samples(), lowpass());
+    let detections = detect(&quantize(&samples(), 4), lowpass());
+    let difference = max_difference(&reference, &detections);
+    assert!(matches!(difference, Some(0..=TOLERANCE)), "{difference:?}");
+}
+
+#[test]
+fn test_noise_shaped_dither() {
+    let reference = detect(&samples(), lowpass());
+    let quantized = quantize(&samples(), 4);
+
+    for seed in 1..=5 {
+        let dither = Dither::new(lsb(4), seed).with_noise_shaping();
+        let detections = detect(&quantized, dither.chain(lowpass()));
+        let difference = max_difference(&reference, &detections);
+        assert!(
+            matches!(difference, Some(0..=TOLERANCE)),
+            "{seed}: {difference:?}"
+        );
+    }
+}
+
+#[test]
+fn test_dither_amplitude_and_determinism() {
+    let mut dither = Dither::new(0.5, 7);
+    let first = (0..1000).map(|_| dither.transform(1.0)).collect::<Vec<_>>();
+    assert!(first.iter().all(|sample| (0.5..=1.5).contains(sample)));
+    assert!(first.iter().any(|sample| *sample > 1.25));
+    assert!(first.iter().any(|sample| *sample < 0.75));
+
+    // The mean of the dither is 0
+    let mean = first.iter().sum::<f32>() / first.len() as f32;
+    assert!((mean - 1.0).abs() < 0.02, "{mean}");
+
+    // Clearing restarts the sequence
+    dither.clear();
+    let second = (0..1000).map(|_| dither.transform(1.0)).collect::<Vec<_>>();
+    assert_eq!(first, second);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+use qrs_detector::{Reason, Reasons};
+
+type Detector = QrsDetector<[f32; 216], [f32; 36], [Detection; 1]>;
+
+/// Runs the detector, calling `event` before every sample. Returns the reasons of every
+/// detection.
+fn detect(event: impl Fn(usize, &mut Detector)) -> Vec<Reasons> {
+    let signal = preprocessed();
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_history([Detection::default(); 1]);
+
+    let mut reasons = Vec::new();
+    for (i, &sample) in signal.iter().enumerate() {
+        event(i, &mut detector);
+        if detector.update(sample).is_some() {
+            reasons.push(detector.recent_detections().last().unwrap().reasons);
+        }
+    }
+    reasons
+}
+
+#[test]
+fn test_clean_signal_has_no_reasons() {
+    let reasons = detect(|_, _| {});
+    assert!(reasons.iter().all(Reasons::is_empty), "{reasons:?}");
+}
+
+#[test]
+fn test_lead_switch() {
+    let reasons = detect(|i, detector| {
+        if i == 20 * 720 {
+            detector.notify_lead_switch();
+        }
+    });
+
+    let first = reasons
+        .iter()
+        .position(|reasons| reasons.contains(Reason::LeadSwitch))
+        .unwrap();
+    let marked = reasons
+        .iter()
+        .filter(|reasons| reasons.contains(Reason::LeadSwitch))
+        .count();
+    assert_eq!(Config::new().mm_depth(), marked);
+    assert!(reasons[first..first + marked]
+        .iter()
+        .all(|reasons| *reasons == Reasons::of(&[Reason::LeadSwitch])));
+}
+
+#[test]
+fn test_gap_and_reset() {
+    let reasons = detect(|i, detector| {
+        if i == 20 * 720 {
+            detector.notify_gap(100);
+        }
+        if i == 40 * 720 {
+            detector.clear();
+        }
+    });
+
+    let marked = reasons
+        .iter()
+        .filter(|reasons| !reasons.is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            &Reasons::of(&[Reason::Gap]),
+            &Reasons::of(&[Reason::Reinitialized])
+        ],
+        marked
+    );
+    assert_eq!("gap", marked[0].to_string());
+    assert_eq!("-", Reasons::empty().to_string());
+}
+
+#[cfg(feature = "quality")]
+#[test]
+fn test_quality_hold() {
+    let reasons = detect(|i, detector| {
+        if i == 20 * 720 {
+            detector.set_saturated(true);
+        }
+        if i == 20 * 720 + 10 {
+            detector.set_saturated(false);
+        }
+    });
+
+    let marked = reasons
+        .iter()
+        .filter(|reasons| !reasons.is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![&Reasons::of(&[Reason::QualityHold])], marked);
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::config::{ConfigUpdate, InputMode, Parameter, Parameters};
+use qrs_detector::prelude::*;
+
+#[test]
+fn test_changed_parameters() {
+    let config = Config::new();
+    assert!(config.changes(&config).is_empty());
+    assert_eq!("-", config.changes(&config).to_string());
+
+    let changed = config.changes(
+        &config
+            .with_rr_depth(4)
+            .with_dc_blocker(0.5)
+            .with_input_mode(InputMode::AbsDiff),
+    );
+    assert_eq!(
+        vec![
+            Parameter::RrDepth,
+            Parameter::DcBlocker,
+            Parameter::InputMode
+        ],
+        changed.iter().collect::<Vec<_>>()
+    );
+    assert_eq!("rr_depth,dc_blocker,input", changed.to_string());
+}
+
+#[test]
+fn test_apply_preserves_state() {
+    let signal = preprocessed();
+    let (before, after) = signal.split_at(signal.len() / 2);
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    for &sample in before {
+        detector.update(sample);
+    }
+    let peak = detector.peak_average().unwrap();
+
+    // Parameters that are applied in place
+    let tuned = Config::new().with_r_floor(0.25).with_refractory_ms(200.0);
+    assert_eq!(
+        ConfigUpdate {
+            changed: Parameters::of(&[Parameter::RFloor, Parameter::Refractory]),
+            reinitialized: Parameters::empty(),
+        },
+        detector.apply_config(tuned)
+    );
+    assert_eq!(&tuned, detector.config());
+    assert_eq!(Some(peak), detector.peak_average());
+
+    // M and R are preset from their previous state
+    let update = detector.apply_config(tuned.with_mm_depth(3).with_rr_depth(4));
+    assert_eq!(
+        Parameters::of(&[Parameter::MmDepth, Parameter::RrDepth]),
+        update.reinitialized
+    );
+    assert!(detector.thresholds().m.is_some());
+    assert!((detector.peak_average().unwrap() - peak).abs() < peak * 1e-3);
+    assert_eq!(Ok(()), detector.verify());
+
+    // The detector keeps detecting without initializing again
+    let detections = after
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect::<Vec<_>>();
+    assert!(detections[0] < before.len() as u32 + 720);
+    assert_eq!(Ok(()), detector.verify());
+}
+
+#[test]
+fn test_apply_during_refractory_period() {
+    let signal = preprocessed();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let detection = signal
+        .iter()
+        .position(|&sample| detector.update(sample).is_some())
+        .unwrap();
+    assert!(detector.state_dump().in_refractory());
+
+    // The ongoing refractory period is shortened as well
+    detector.apply_config(Config::new().with_refractory_ms(100.0));
+    assert_eq!(Ok(()), detector.verify());
+
+    for &sample in &signal[detection + 1..] {
+        detector.update(sample);
+        assert_eq!(Ok(()), detector.verify());
+    }
+}
+
+#[test]
+fn test_apply_input_mode_reinitializes() {
+    let signal = preprocessed();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    for &sample in signal.iter().take(10 * 720) {
+        detector.update(sample);
+    }
+    assert!(detector.peak_average().is_some());
+
+    let config = Config::new()
+        .with_input_mode(InputMode::AbsDiff)
+        .with_r_floor(0.25);
+    let update = detector.apply_config(config);
+    assert_eq!(update.changed, update.reinitialized);
+    assert!(update.reinitialized.contains(Parameter::InputMode));
+    assert!(update.reinitialized.contains(Parameter::RFloor));
+    assert_eq!(None, detector.thresholds().m);
+    assert_eq!(None, detector.peak_average());
+}
+
+#[test]
+fn test_scheduled_config_waits_for_safe_point() {
+    let signal = preprocessed();
+
+    let mut reference = QrsDetector::new::<216, 36>(720.sps());
+    let expected = signal
+        .iter()
+        .filter_map(|&sample| reference.update(sample))
+        .collect::<Vec<_>>();
+
+    let tuned = Config::new().with_r_weight(0.6).with_mm_depth(4);
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut detections = Vec::new();
+    let mut applied_at = None;
+    for (i, &sample) in signal.iter().enumerate() {
+        let detected = detector.update(sample);
+        detections.extend(detected);
+
+        // Scheduled at a detection, i.e. at the start of the refractory period
+        if detections.len() == 10 && detected.is_some() {
+            detector.schedule_config(tuned);
+        }
+        if let Some(update) = detector.take_config_update() {
+            assert_eq!(None, applied_at);
+            assert_eq!(Parameters::of(&[Parameter::MmDepth]), update.reinitialized);
+            assert!(update.changed.contains(Parameter::RWeight));
+            applied_at = Some(i);
+        }
+    }
+
+    let applied_at = applied_at.unwrap();
+    let scheduled_at = detections[9] as usize;
+    assert!(applied_at - scheduled_at > 720 / 5, "{applied_at}");
+    assert_eq!(None, detector.pending_config());
+    assert_eq!(&tuned, detector.config());
+
+    // No beat is lost or doubled while switching
+    assert_eq!(expected.len(), detections.len());
+    for (expected, detection) in expected.iter().zip(detections.iter()) {
+        assert!(
+            expected.abs_diff(*detection) <= 7,
+            "{expected} != {detection}"
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use qrs_detector::sampling::*;
+use qrs_detector::QrsDetector;
+
+/// Feeds 4 seconds of 1 Hz pulses to get the detector past its initialization period.
+fn initialize(detector: &mut QrsDetector<[f32; 75], [f32; 12]>) {
+    for i in 0..1000 {
+        detector.update(if i % 250 < 5 { 1.0 } else { 0.0 });
+    }
+}
+
+#[test]
+fn test_ramp_does_not_block_detection() {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps());
+    initialize(&mut detector);
+
+    let mut detections = 0;
+    let mut timeouts = 0;
+    for i in 0..2500 {
+        if detector.update(0.5 + i as f32 * 0.01).is_some() {
+            detections += 1;
+        }
+        if detector.take_refractory_timeout() {
+            timeouts += 1;
+        }
+    }
+
+    assert!(detections >= 5, "{} detections", detections);
+    assert!(timeouts >= 5, "{} timeouts", timeouts);
+    assert!(!detector.take_refractory_timeout());
+}
+
+#[test]
+fn test_regular_pulses_do_not_trigger_timeout() {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps());
+    initialize(&mut detector);
+
+    for i in 0..2500 {
+        detector.update(if i % 250 < 5 { 1.0 } else { 0.0 });
+        assert!(!detector.take_refractory_timeout());
+    }
+}
@@ -0,0 +1,77 @@
+use qrs_detector::prelude::*;
+
+/// Returns the R threshold values for 1 Hz pulses, starting at `offset` samples.
+fn r_values(detector: &mut QrsDetector<[f32; 75], [f32; 12]>, offset: usize) -> Vec<f32> {
+    (0..12 * 250)
+        .map(|i| {
+            let pulse = i >= offset && (i - offset) % 250 < 5;
+            detector.update(if pulse { 1.0 } else { 0.0 });
+            detector.thresholds().r
+        })
+        .collect()
+}
+
+/// Returns the sample index where R first starts decreasing.
+fn first_decrease(r: &[f32]) -> usize {
+    r.iter().position(|&r| r < 0.0).unwrap()
+}
+
+#[test]
+fn test_first_rr_interval_is_not_measured_from_start() {
+    // The first beat is detected after the initialization period, so the time before it must not
+    // be counted as an RR interval.
+    for offset in [0, 100, 200] {
+        let mut detector = QrsDetector::new::<75, 12>(250.sps());
+        let r = r_values(&mut detector, offset);
+
+        // First detection at 3s, the RR buffer is full at the 6th beat (8s). R starts decreasing
+        // after 2/3 of the average RR interval.
+        assert_eq!(
+            8 * 250 + offset + 168,
+            first_decrease(&r),
+            "offset {}",
+            offset
+        );
+    }
+}
+
+#[test]
+fn test_rr_intervals_after_clear() {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps());
+    r_values(&mut detector, 0);
+
+    detector.clear();
+    let r = r_values(&mut detector, 100);
+
+    assert_eq!(8 * 250 + 100 + 168, first_decrease(&r));
+}
+
+#[test]
+fn test_rr_average() {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps());
+
+    let mut detections = 0;
+    for i in 0..12 * 250 {
+        // 80 bpm
+        if detector
+            .update(if i % 187 < 5 { 1.0 } else { 0.0 })
+            .is_some()
+        {
+            detections += 1;
+        }
+
+        // Not available until 5 RR intervals are collected
+        if detections < 6 {
+            assert_eq!(None, detector.rr_average());
+        } else {
+            let rr = detector.rr_average().unwrap().as_secs_f32();
+            assert!((rr - 0.748).abs() < 0.01, "{}", rr);
+        }
+    }
+
+    // Not available after a long pause
+    for _ in 0..5 * 250 {
+        detector.update(0.0);
+    }
+    assert_eq!(None, detector.rr_average());
+}
@@ -0,0 +1,125 @@
+//! Detection agreement across sampling frequencies.
+//!
+//! The reference record is resampled to common sampling frequencies, so that the sampling
+//! frequency dependent constants of the detector (window lengths, rounding, decrement rates) are
+//! checked against the original 720 sps record.
+
+mod common;
+
+use common::samples;
+use qrs_detector::prelude::*;
+use qrs_detector::BufferSizes;
+
+const REFERENCE_SPS: f32 = 720.0;
+
+/// Detections before this time are affected by the initialization and are not compared.
+const SETTLE_S: f32 = 4.0;
+
+/// The allowed difference between the detection times at different sampling frequencies.
+const TOLERANCE_S: f32 = 0.025;
+
+/// Resamples `samples` from the reference sampling frequency to `sps` using linear
+/// interpolation.
+fn resample(samples: &[f32], sps: f32) -> Vec<f32> {
+    let ratio = REFERENCE_SPS / sps;
+    let len = ((samples.len() - 1) as f32 / ratio) as usize + 1;
+
+    (0..len)
+        .map(|i| {
+            let position = i as f32 * ratio;
+            let idx = position as usize;
+            let fraction = position - idx as f32;
+            match samples.get(idx + 1) {
+                Some(next) => samples[idx] * (1.0 - fraction) + next * fraction,
+                None => samples[idx],
+            }
+        })
+        .collect()
+}
+
+/// The moving average and differentiation of the other tests, scaled to `sps`. The windows are
+/// rounded up, as the differentiated signal is too noisy without averaging.
+fn preprocess(samples: &[f32], sps: f32) -> Vec<f32> {
+    let scale =
+        |samples_at_720: f32| ((samples_at_720 * sps / REFERENCE_SPS).ceil() as usize).max(1);
+    let average = scale(4.0);
+    let lag = scale(2.0);
+
+    let averaged = samples
+        .windows(average)
+        .map(|window| window.iter().sum::<f32>() / average as f32)
+        .collect::<Vec<_>>();
+
+    averaged
+        .windows(lag + 1)
+        .map(|window| (window[0] - window[lag]).abs())
+        .collect()
+}
+
+/// Returns the detection times in seconds.
+fn detect(sps: f32) -> Vec<f32> {
+    let fs = sps.sps();
+    let sizes = BufferSizes::for_fs(fs);
+    let mut detector = QrsDetector::try_new_from(
+        fs,
+        vec![0.0; sizes.samples_300],
+        vec![0.0; sizes.samples_50],
+    )
+    .unwrap();
+
+    let signal = preprocess(&resample(&samples(), sps), sps);
+    signal
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .map(|idx| fs.samples_to_s(idx as usize))
+        .filter(|&t| t >= SETTLE_S)
+        .collect()
+}
+
+fn assert_agrees(sps: f32) {
+    let reference = detect(REFERENCE_SPS);
+    let detections = detect(sps);
+
+    assert_eq!(
+        reference.len(),
+        detections.len(),
+        "{sps} sps: {detections:?}\nreference: {reference:?}"
+    );
+    for (reference, detection) in reference.iter().zip(detections.iter()) {
+        assert!(
+            (reference - detection).abs() <= TOLERANCE_S,
+            "{sps} sps: detection at {detection}s, reference at {reference}s"
+        );
+    }
+}
+
+#[test]
+fn test_200_sps() {
+    assert_agrees(200.0);
+}
+
+#[test]
+fn test_250_sps() {
+    assert_agrees(250.0);
+}
+
+#[test]
+fn test_360_sps() {
+    assert_agrees(360.0);
+}
+
+#[test]
+fn test_500_sps() {
+    assert_agrees(500.0);
+}
+
+#[test]
+fn test_1000_sps() {
+    assert_agrees(1000.0);
+}
+
+#[test]
+fn test_resampling_preserves_reference() {
+    let record = samples();
+    assert_eq!(record, resample(&record, REFERENCE_SPS));
+}
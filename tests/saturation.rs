@@ -0,0 +1,66 @@
+#![cfg(feature = "quality")]
+
+use qrs_detector::quality::SaturationDetector;
+use qrs_detector::sampling::*;
+use qrs_detector::QrsDetector;
+
+const FS: usize = 250;
+const RAIL: f32 = 2.0;
+
+/// 1 Hz triangular pulses. Between 10 and 15 seconds, the gain increases and the amplifier clips.
+fn raw_signal(i: usize) -> f32 {
+    let phase = i % FS;
+    let pulse = if phase < 10 {
+        phase as f32 / 10.0
+    } else if phase < 20 {
+        (20 - phase) as f32 / 10.0
+    } else {
+        0.0
+    };
+
+    if (10 * FS..15 * FS).contains(&i) {
+        (3.0 * pulse).min(RAIL)
+    } else {
+        pulse
+    }
+}
+
+/// Returns the refreshed M threshold values after each beat (starting at 3s), and the detections
+fn run() -> (Vec<f32>, Vec<u32>) {
+    let mut detector = QrsDetector::new::<75, 12>(FS.sps());
+    let mut saturation = SaturationDetector::new(FS.sps(), -RAIL, RAIL);
+    let mut m = Vec::new();
+    let mut detections = Vec::new();
+    let mut prev_m = None;
+
+    for i in 2..25 * FS {
+        detector.set_saturated(saturation.update(raw_signal(i)));
+        if let Some(idx) = detector.update((raw_signal(i) - raw_signal(i - 2)).abs()) {
+            detections.push(idx);
+        }
+        let current_m = detector.thresholds().m;
+        if let (None, Some(current_m)) = (prev_m, current_m) {
+            m.push(current_m);
+        }
+        prev_m = current_m;
+    }
+    (m, detections)
+}
+
+#[test]
+fn test_m_recovers_after_saturation() {
+    let (m, detections) = run();
+
+    // Every beat is detected
+    assert_eq!(22, detections.len());
+
+    // The first refreshed M value after the saturation episode matches the value before it
+    let before = m[6];
+    let after = m[12];
+    assert!(
+        ((after - before) / before).abs() < 0.01,
+        "M before: {}, after: {}",
+        before,
+        after
+    );
+}
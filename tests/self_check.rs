@@ -0,0 +1,47 @@
+mod common;
+
+use qrs_detector::sampling::*;
+use qrs_detector::{Corruption, QrsDetector};
+
+fn load_signal() -> Vec<f32> {
+    let samples = common::samples();
+
+    samples
+        .windows(3)
+        .map(|window| (window[0] - window[2]).abs())
+        .collect()
+}
+
+#[test]
+fn test_verify_accepts_valid_state() {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+
+    for sample in load_signal() {
+        detector.update(sample);
+        assert_eq!(Ok(()), detector.verify());
+    }
+}
+
+#[test]
+fn test_recover_from_non_finite_input() {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let signal = load_signal();
+    let (first, second) = signal.split_at(signal.len() / 2);
+
+    for &sample in first {
+        detector.update(sample);
+    }
+    detector.update(f32::INFINITY);
+
+    assert_eq!(
+        Err(Corruption::NonFiniteThreshold),
+        detector.verify_or_recover()
+    );
+    assert_eq!(Ok(()), detector.verify());
+
+    let detections = second
+        .iter()
+        .filter(|&&sample| detector.update(sample).is_some())
+        .count();
+    assert!(detections > 0);
+}
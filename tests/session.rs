@@ -0,0 +1,78 @@
+#![cfg(feature = "hrv")]
+
+mod common;
+
+use qrs_detector::prelude::*;
+use qrs_detector::session::SessionAnalyzer;
+
+#[test]
+fn test_session_report() {
+    let mut analyzer = SessionAnalyzer::new(QrsDetector::new::<216, 36>(720.sps()));
+
+    let samples = common::samples();
+
+    let mut detections = 0;
+    let mut prev: Option<f32> = None;
+    let mut prev2: Option<f32> = None;
+    for window in samples.windows(4) {
+        // A slight moving average filtering
+        let avg = window.iter().sum::<f32>() / 4.0;
+        if let Some(p) = prev.replace(avg) {
+            if let Some(p2) = prev2.replace(p) {
+                if analyzer.push((p2 - avg).abs()).is_some() {
+                    detections += 1;
+                }
+            }
+        }
+    }
+
+    // The record is a regular 40 bpm rhythm
+    let report = analyzer.report();
+    assert_eq!(samples.len() - 5, report.samples as usize);
+    assert_eq!(38, report.beats);
+    assert_eq!(detections, report.beats);
+    assert!((report.heart_rate.unwrap() - 40.0).abs() < 1.0);
+    assert!((report.last_rr.unwrap().as_secs_f32() - 1.5).abs() < 0.02);
+    assert!(report.hrv.unwrap().sdnn_ms < 20.0);
+    assert_eq!(37, report.events.bradycardia_beats);
+    assert_eq!(0, report.events.pauses);
+    assert_eq!(0, report.events.tachycardia_beats);
+    assert_eq!(
+        analyzer.detector().algorithm_version(),
+        report.algorithm_version
+    );
+
+    analyzer.clear();
+    let report = analyzer.report();
+    assert_eq!(0, report.beats);
+    assert_eq!(None, report.heart_rate);
+}
+
+#[test]
+fn test_memory_size() {
+    use qrs_detector::session::SessionBuilder;
+    use qrs_detector::BufferSizes;
+
+    const LOW: BufferSizes = BufferSizes::for_sps(250);
+    const HIGH: BufferSizes = BufferSizes::for_sps(1000);
+
+    assert_eq!(
+        LOW,
+        BufferSizes {
+            samples_300: 75,
+            samples_50: 12
+        }
+    );
+
+    let low = SessionBuilder::memory_size::<{ LOW.samples_300 }, { LOW.samples_50 }>();
+    let high = SessionBuilder::memory_size::<{ HIGH.samples_300 }, { HIGH.samples_50 }>();
+
+    // The F buffers make up the difference, apart from alignment padding
+    let extra_samples = (HIGH.samples_300 - LOW.samples_300) + (HIGH.samples_50 - LOW.samples_50);
+    let extra_bytes = extra_samples * core::mem::size_of::<f32>();
+    assert!((extra_bytes..extra_bytes + 8).contains(&(high - low)));
+
+    let analyzer =
+        SessionBuilder::new(250.sps()).build::<{ LOW.samples_300 }, { LOW.samples_50 }>();
+    assert_eq!(low, core::mem::size_of_val(&analyzer));
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+use qrs_detector::testing::{Simulator, MAX_STEP_DETECTIONS};
+
+#[test]
+fn test_stepping_matches_sample_by_sample_processing() {
+    let signal = preprocessed();
+    let mut reference = QrsDetector::new::<216, 36>(720.sps());
+    let expected = signal
+        .iter()
+        .filter_map(|&sample| reference.update(sample))
+        .collect::<Vec<_>>();
+
+    // Steps of 250ms, as a test rig would inject them
+    let mut simulator = Simulator::new(QrsDetector::new::<216, 36>(720.sps()));
+    let mut detections = Vec::new();
+    let mut dumps = Vec::new();
+    for chunk in signal.chunks(180) {
+        let report = simulator.step(chunk);
+        assert_eq!(chunk.len() as u32, report.samples);
+        assert_eq!(0, report.dropped_detections);
+        detections.extend_from_slice(report.detections());
+        dumps.push(simulator.dump());
+    }
+    assert_eq!(expected, detections);
+
+    // The dump is deterministic
+    let dump = simulator.dump();
+    assert_eq!(reference.state_dump(), dump);
+    assert_eq!(reference.state_dump().to_string(), dump.to_string());
+    assert_eq!(reference.state_dump().hash(), dump.hash());
+    assert_ne!(dumps[dumps.len() - 2].hash(), dump.hash());
+
+    assert_eq!(*expected.last().unwrap(), dump.last_detection());
+
+    let text = dump.to_string();
+    assert!(
+        text.starts_with(&format!(
+            "position={} elapsed={} epoch=0 last_detection={} m=",
+            signal.len(),
+            signal.len(),
+            dump.last_detection()
+        )),
+        "{text}"
+    );
+    let rr = dump
+        .rr_intervals()
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    assert!(text.contains(&format!(" rr={rr} ")), "{text}");
+}
+
+#[test]
+fn test_virtual_time_and_outputs() {
+    let signal = preprocessed();
+
+    let mut simulator = Simulator::new(QrsDetector::new::<216, 36>(720.sps()));
+    let report = simulator.step_constant(0.0, 720);
+    assert!(report.detections().is_empty());
+    assert_eq!(
+        "position=720 elapsed=720 epoch=0 last_detection=0 m=- f=- r=0 refractory=false mm=- \
+         rr=- suppressed=0/0/0 skipped=0",
+        simulator.dump().to_string()
+    );
+    simulator.skip(360);
+    assert_eq!(1500, simulator.time().as_millis());
+    assert_eq!(1080, simulator.dump().timeline().position());
+
+    // More beats than a report holds
+    let mut simulator = Simulator::new(QrsDetector::new::<216, 36>(720.sps()));
+    let report = simulator.step(&signal);
+    assert_eq!(MAX_STEP_DETECTIONS, report.detections().len());
+    assert!(report.dropped_detections > 0);
+
+    // Scheduled configurations are reported by the step that applies them
+    simulator
+        .detector_mut()
+        .schedule_config(Config::new().with_r_floor(0.25));
+    let report = simulator.step(&signal[..720]);
+    assert!(report.config_update.is_some());
+
+    simulator.detector_mut().clear();
+    assert_eq!(1, simulator.dump().timeline().epoch());
+}
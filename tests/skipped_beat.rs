@@ -0,0 +1,46 @@
+use qrs_detector::{prelude::*, SkippedBeat};
+
+/// 1 Hz pulses, with a small beat at 15 seconds and a missing beat at 25 seconds. Returns the
+/// reported skipped beats.
+fn skipped_beats(config: Config) -> Vec<SkippedBeat> {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps()).with_config(config);
+
+    let mut skipped = Vec::new();
+    for i in 0..40 * 250 {
+        let amplitude = match i / 250 {
+            15 => 0.1,
+            25 => 0.0,
+            _ => 1.0,
+        };
+        detector.update(if i % 250 < 5 { amplitude } else { 0.0 });
+        skipped.extend(detector.take_skipped_beat());
+    }
+
+    assert_eq!(skipped.len() as u32, detector.skipped_beat_count());
+    skipped
+}
+
+#[test]
+fn test_skipped_beats_are_reported() {
+    let skipped = skipped_beats(Config::new().with_skip_diagnostics(true));
+
+    assert_eq!(2, skipped.len());
+
+    // The small beat was close to the threshold
+    assert_eq!(14 * 250, skipped[0].start);
+    assert_eq!(16 * 250, skipped[0].end);
+    assert!((15 * 250..15 * 250 + 5).contains(&skipped[0].index));
+    assert_eq!(0.1, skipped[0].amplitude);
+    assert!(skipped[0].relative_margin() < 0.9);
+
+    // The missing beat left nothing close to the threshold
+    assert_eq!(24 * 250, skipped[1].start);
+    assert_eq!(26 * 250, skipped[1].end);
+    assert_eq!(1.0, skipped[1].relative_margin());
+    assert!(skipped[0].margin() < skipped[1].margin());
+}
+
+#[test]
+fn test_skip_diagnostics_are_disabled_by_default() {
+    assert!(skipped_beats(Config::new()).is_empty());
+}
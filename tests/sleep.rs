@@ -0,0 +1,59 @@
+mod common;
+
+use common::total_synth;
+use qrs_detector::prelude::*;
+use qrs_detector::synth::SynthParams;
+
+/// The length of the synthetic records, in seconds.
+const SECONDS: usize = 200;
+
+const fn low_rate(bpm: f32) -> SynthParams {
+    SynthParams {
+        heart_rate_bpm: bpm,
+        final_heart_rate_bpm: bpm,
+        rr_variability: 0.15,
+        noise_mv: 0.1,
+        ..SynthParams::REST
+    }
+}
+
+#[test]
+fn test_sleep_preset_on_low_heart_rates() {
+    for bpm in [35.0, 45.0, 60.0] {
+        let metrics = total_synth(Config::sleep(), low_rate(bpm), SECONDS);
+        assert_eq!(0, metrics.false_positives, "{bpm} bpm: {metrics:?}");
+        assert_eq!(0, metrics.false_negatives, "{bpm} bpm: {metrics:?}");
+    }
+}
+
+#[test]
+fn test_sleep_preset_reduces_t_wave_oversensing() {
+    for bpm in [35.0, 45.0, 60.0] {
+        // T waves almost as steep as the QRS complexes
+        let params = SynthParams {
+            t_wave_ratio: 2.8,
+            ..low_rate(bpm)
+        };
+
+        let default = total_synth(Config::new(), params, SECONDS);
+        let sleep = total_synth(Config::sleep(), params, SECONDS);
+        assert_eq!(0, sleep.false_negatives, "{bpm} bpm: {sleep:?}");
+        assert!(
+            2 * sleep.false_positives <= default.false_positives,
+            "{bpm} bpm: {sleep:?}, default {default:?}"
+        );
+    }
+}
+
+#[test]
+fn test_sleep_preset_follows_amplitude_drop() {
+    // E.g. the patient turns over
+    let params = SynthParams {
+        final_r_amplitude_mv: 0.5,
+        ramp_s: 1.0,
+        ..low_rate(40.0)
+    };
+
+    let metrics = total_synth(Config::sleep(), params, SECONDS);
+    assert_eq!(0, metrics.false_negatives, "{metrics:?}");
+}
@@ -0,0 +1,117 @@
+//! Long-duration tests, looping a record to check for drift over simulated days of operation.
+//!
+//! The default tests cover a few hours. The multi-day test is ignored by default, run it with
+//! `cargo test --release --test soak -- --ignored`. The duration can be set in days using the
+//! `SOAK_DAYS` environment variable.
+
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+
+const FS: f32 = 720.0;
+
+/// The state of the detector at the end of a loop.
+struct LoopResult {
+    detections: usize,
+    rr_sum: u32,
+    thresholds: Thresholds,
+}
+
+struct Soak {
+    detector: QrsDetector<[f32; 216], [f32; 36]>,
+    record: Vec<f32>,
+    samples: u32,
+    last_detection: Option<u32>,
+}
+
+impl Soak {
+    fn new() -> Self {
+        Self {
+            detector: QrsDetector::new::<216, 36>(FS.sps()),
+            record: preprocessed(),
+            samples: 0,
+            last_detection: None,
+        }
+    }
+
+    /// Simulates `samples` samples of uptime without processing them.
+    fn skip(&mut self, samples: u32) {
+        self.detector.notify_gap(samples);
+        self.samples = self.samples.wrapping_add(samples);
+        self.last_detection = None;
+    }
+
+    /// Processes the record once.
+    fn run_loop(&mut self) -> LoopResult {
+        let mut detections = 0;
+        let mut rr_sum = 0;
+
+        for &sample in &self.record {
+            if let Some(idx) = self.detector.update(sample) {
+                if let Some(prev) = self.last_detection.replace(idx) {
+                    rr_sum += idx.wrapping_sub(prev);
+                }
+                detections += 1;
+            }
+        }
+        self.samples = self.samples.wrapping_add(self.record.len() as u32);
+
+        assert_eq!(Ok(()), self.detector.verify());
+
+        LoopResult {
+            detections,
+            rr_sum,
+            thresholds: self.detector.thresholds(),
+        }
+    }
+
+    /// Loops the record for at least `hours` hours, and checks that every loop after the first
+    /// one (which includes the initialization) gives the same result.
+    fn run(&mut self, hours: f32) {
+        let loops = (hours * 3600.0 * FS / self.record.len() as f32).ceil() as usize;
+
+        let _ = self.run_loop();
+        let reference = self.run_loop();
+
+        for i in 2..loops {
+            let result = self.run_loop();
+
+            assert_eq!(reference.detections, result.detections, "loop {i}");
+            assert_eq!(reference.rr_sum, result.rr_sum, "loop {i}");
+            assert!(
+                reference.thresholds.approx_eq(&result.thresholds, 1e-3),
+                "loop {i}: {:?} != {:?}",
+                reference.thresholds,
+                result.thresholds
+            );
+        }
+    }
+}
+
+#[test]
+fn test_soak_does_not_drift() {
+    Soak::new().run(2.0);
+}
+
+#[test]
+fn test_soak_sample_counter_wraps() {
+    let mut soak = Soak::new();
+    soak.run(0.1);
+
+    // Fast forward, so that the sample counter overflows during the second loop
+    let until_overflow = soak.record.len() as u32 * 3 / 2;
+    soak.skip(u32::MAX - soak.samples - until_overflow);
+    soak.run(0.1);
+}
+
+#[test]
+#[ignore]
+fn test_soak_multi_day() {
+    let days = std::env::var("SOAK_DAYS")
+        .ok()
+        .and_then(|days| days.parse::<f32>().ok())
+        .unwrap_or(3.0);
+
+    Soak::new().run(days * 24.0);
+}
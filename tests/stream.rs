@@ -0,0 +1,68 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+use qrs_detector::stream::SampleSource;
+
+/// Yields a sample on every other poll, like a driver waiting for the ADC.
+struct SlowSource {
+    samples: std::vec::IntoIter<f32>,
+    ready: bool,
+}
+
+impl SampleSource for SlowSource {
+    fn next_sample(&mut self) -> impl Future<Output = Option<f32>> {
+        poll_fn(|cx| {
+            self.ready = !self.ready;
+            if self.ready {
+                Poll::Ready(self.samples.next())
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_next_detection_matches_update() {
+    let samples = preprocessed();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let expected = samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+
+    let mut source = SlowSource {
+        samples: samples.into_iter(),
+        ready: false,
+    };
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let detections = block_on(async {
+        let mut detections = Vec::new();
+        while let Some(index) = detector.next_detection(&mut source).await {
+            detections.push(index);
+        }
+        detections
+    });
+
+    assert_eq!(expected, detections);
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "hrv")]
+
+use core::{cell::Cell, time::Duration};
+
+use qrs_detector::prelude::*;
+use qrs_detector::summary::{PeriodSummary, SummaryAggregator};
+use qrs_detector::time::ClockSource;
+
+#[test]
+fn test_regular_rhythm() {
+    let mut aggregator = SummaryAggregator::new(250.sps(), Duration::from_secs(10));
+
+    let mut summaries = Vec::new();
+    for i in 0..5000 {
+        let detection = (i % 250 == 0).then_some(i);
+        if let Some(summary) = aggregator.update(detection, i < 250) {
+            summaries.push(summary);
+        }
+    }
+
+    assert_eq!(
+        vec![
+            PeriodSummary {
+                beats: 10,
+                min_bpm: 60,
+                avg_bpm: 60,
+                max_bpm: 60,
+                pauses: 0,
+                af_burden_percent: 0,
+                noise_percent: 10,
+            },
+            PeriodSummary {
+                beats: 10,
+                min_bpm: 60,
+                avg_bpm: 60,
+                max_bpm: 60,
+                pauses: 0,
+                af_burden_percent: 0,
+                noise_percent: 0,
+            },
+        ],
+        summaries
+    );
+}
+
+#[test]
+fn test_irregular_rhythm_with_pause() {
+    let mut aggregator = SummaryAggregator::new(250.sps(), Duration::from_secs(20));
+
+    // Alternating 0.6 s and 1.0 s RR intervals, then a 3 s pause
+    let mut beats = vec![0];
+    for rr in [150, 250, 150, 250, 150, 250, 750, 250] {
+        beats.push(beats.last().unwrap() + rr);
+    }
+
+    let mut summary = None;
+    for i in 0..5000 {
+        let detection = beats.contains(&i).then_some(i);
+        if let Some(s) = aggregator.update(detection, false) {
+            summary = Some(s);
+        }
+    }
+
+    let summary = summary.unwrap();
+    assert_eq!(9, summary.beats);
+    assert_eq!(1, summary.pauses);
+    assert_eq!(20, summary.min_bpm);
+    assert_eq!(100, summary.max_bpm);
+    // Every RR interval differs from the previous one, the first has nothing to compare to
+    assert_eq!(100, summary.af_burden_percent);
+}
+
+#[test]
+fn test_af_burden() {
+    let mut aggregator = SummaryAggregator::new(250.sps(), Duration::from_secs(20));
+
+    // A premature beat among regular 1 s RR intervals
+    let mut beats = vec![0];
+    for rr in [250, 250, 250, 250, 150, 250, 250, 250, 250] {
+        beats.push(beats.last().unwrap() + rr);
+    }
+
+    let mut summary = None;
+    for i in 0..5000 {
+        let detection = beats.contains(&i).then_some(i);
+        if let Some(s) = aggregator.update(detection, false) {
+            summary = Some(s);
+        }
+    }
+
+    // The intervals ending and following the premature beat, 2 of the 8 compared intervals
+    assert_eq!(25, summary.unwrap().af_burden_percent);
+}
+
+#[test]
+fn test_p_waves_exclude_irregular_intervals() {
+    let mut aggregator = SummaryAggregator::new(250.sps(), Duration::from_secs(20));
+
+    // Alternating 0.6 s and 1.0 s RR intervals, with a P wave before every other beat
+    let mut beats = vec![0];
+    for rr in [150, 250, 150, 250, 150, 250, 150, 250] {
+        beats.push(beats.last().unwrap() + rr);
+    }
+
+    let mut summary = None;
+    for i in 0..5000 {
+        let detection = beats.contains(&i).then_some(i);
+        if let Some(s) = aggregator.update(detection, false) {
+            summary = Some(s);
+        }
+        if let Some(beat) = beats.iter().position(|&beat| beat == i) {
+            aggregator.notify_p_wave(beat.is_multiple_of(2));
+        }
+    }
+
+    // Of the 7 irregular intervals, 4 end in beats with a P wave
+    assert_eq!(42, summary.unwrap().af_burden_percent);
+}
+
+#[test]
+fn test_summary_is_compact() {
+    assert_eq!(16, core::mem::size_of::<PeriodSummary>());
+}
+
+/// A millisecond timer, advanced by the test.
+struct TestClock(Cell<u64>);
+
+impl ClockSource for &TestClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+
+    fn ticks_per_second(&self) -> f32 {
+        1000.0
+    }
+}
+
+#[test]
+fn test_periods_follow_the_clock() {
+    let clock = TestClock(Cell::new(500));
+    let mut aggregator =
+        SummaryAggregator::new(250.sps(), Duration::from_secs(10)).with_clock(&clock);
+
+    let mut ends = Vec::new();
+    for i in 0..10_000 {
+        // 5 seconds of samples are lost
+        let gap = if i == 5_000 { 5_000 } else { 0 };
+        clock.0.set(clock.0.get() + 4 + gap);
+
+        if let Some(summary) = aggregator.update((i % 250 == 0).then_some(i), false) {
+            ends.push((clock.0.get(), summary.beats));
+        }
+    }
+
+    // The periods stay aligned to the clock, the one with the gap has fewer samples
+    assert_eq!(
+        vec![(10_500, 10), (20_500, 10), (30_500, 5), (40_500, 10)],
+        ends
+    );
+}
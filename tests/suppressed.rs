@@ -0,0 +1,80 @@
+use qrs_detector::prelude::*;
+use qrs_detector::{Suppressed, SuppressedCounts, SuppressionReason};
+
+/// 1 Hz pulses at 250 sps, each followed by a second peak 120 ms later.
+fn double_peak(i: usize) -> f32 {
+    match i % 250 {
+        0..=4 | 30..=34 => 1.0,
+        _ => 0.0,
+    }
+}
+
+#[test]
+fn test_second_peak_is_suppressed() {
+    let mut detector =
+        QrsDetector::new::<75, 12>(250.sps()).with_suppressed_log([Suppressed::default(); 4]);
+
+    let mut detections = Vec::new();
+    for i in 0..5000 {
+        if let Some(idx) = detector.update(double_peak(i)) {
+            detections.push(idx);
+        }
+    }
+
+    // Only the first peak of every pair is detected
+    assert!(detections.len() > 10);
+    assert!(
+        detections.iter().all(|idx| idx % 250 == 0),
+        "{:?}",
+        detections
+    );
+
+    let counts = detector.suppressed_counts();
+    assert!(
+        counts.refractory >= detections.len() as u32 - 1,
+        "{:?}",
+        counts
+    );
+    assert_eq!(0, counts.signal_step);
+
+    let recent = detector.recent_suppressed().collect::<Vec<_>>();
+    assert_eq!(4, recent.len());
+    for suppressed in recent {
+        assert_eq!(30, suppressed.index % 250);
+        assert_eq!(SuppressionReason::Refractory, suppressed.reason);
+        assert!(suppressed.amplitude > suppressed.threshold);
+    }
+
+    detector.clear();
+    assert_eq!(SuppressedCounts::default(), detector.suppressed_counts());
+    assert_eq!(0, detector.recent_suppressed().count());
+}
+
+#[test]
+fn test_single_peaks_are_not_suppressed() {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps());
+
+    for i in 0..5000 {
+        detector.update(if i % 250 < 5 { 1.0 } else { 0.0 });
+    }
+
+    assert_eq!(SuppressedCounts::default(), detector.suppressed_counts());
+}
+
+#[cfg(feature = "quality")]
+#[test]
+fn test_signal_step_is_suppressed() {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps())
+        .with_config(Config::new().with_step_blanking(true))
+        .with_suppressed_log([Suppressed::default(); 4]);
+
+    for i in 0..5000 {
+        let step = if i >= 4100 { 50.0 } else { 0.0 };
+        detector.update(if i % 250 < 5 { 1.0 } else { 0.0 } + step);
+    }
+
+    assert!(detector.suppressed_counts().signal_step >= 1);
+    assert!(detector
+        .recent_suppressed()
+        .any(|s| s.reason == SuppressionReason::SignalStep && s.index == 4100));
+}
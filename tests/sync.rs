@@ -0,0 +1,123 @@
+#![cfg(feature = "sensors")]
+
+mod common;
+
+use qrs_detector::prelude::*;
+use qrs_detector::sync::{Interpolation, StreamAligner};
+
+/// An accelerometer stream whose every axis is a ramp of the sample index.
+fn ramp<const N: usize>(aligner: &mut StreamAligner<3, N>, samples: u32) {
+    for k in 0..samples {
+        let k = k as f32;
+        aligner.push([k, -k, 2.0 * k]);
+    }
+}
+
+#[test]
+fn test_linear_interpolation() {
+    let mut aligner = StreamAligner::<3, 16>::new(500.sps(), 50.sps(), Interpolation::Linear);
+    assert_eq!(None, aligner.sample_at(0));
+
+    ramp(&mut aligner, 100);
+    assert_eq!(100, aligner.len());
+
+    // 10 ECG samples per accelerometer sample
+    assert_eq!(Some([90.0, -90.0, 180.0]), aligner.sample_at(900));
+    assert_eq!(Some([90.5, -90.5, 181.0]), aligner.sample_at(905));
+    let [x, ..] = aligner.sample_at(987).unwrap();
+    assert!((x - 98.7).abs() < 1e-4, "{x}");
+
+    // Later than the last sample: held
+    assert_eq!(Some([99.0, -99.0, 198.0]), aligner.sample_at(995));
+    assert_eq!(Some([99.0, -99.0, 198.0]), aligner.sample_at(5000));
+
+    // Older than the buffer
+    assert_eq!(Some([84.0, -84.0, 168.0]), aligner.sample_at(840));
+    assert_eq!(None, aligner.sample_at(835));
+}
+
+#[test]
+fn test_nearest_sample() {
+    // Incommensurate rates
+    let mut aligner = StreamAligner::<3, 64>::new(360.sps(), 25.sps(), Interpolation::Nearest);
+    ramp(&mut aligner, 50);
+
+    for ecg_index in 0..700 {
+        let expected = (ecg_index as f32 * 25.0 / 360.0).round();
+        assert_eq!(
+            Some([expected, -expected, 2.0 * expected]),
+            aligner.sample_at(ecg_index),
+            "{ecg_index}"
+        );
+    }
+}
+
+#[test]
+fn test_offset() {
+    // The accelerometer started 100ms after the ECG
+    let mut aligner = StreamAligner::<3, 16>::new(500.sps(), 50.sps(), Interpolation::Linear)
+        .with_offset_ms(100.0);
+    ramp(&mut aligner, 10);
+
+    assert_eq!(None, aligner.sample_at(49));
+    assert_eq!(Some([0.0, 0.0, 0.0]), aligner.sample_at(50));
+    assert_eq!(Some([1.5, -1.5, 3.0]), aligner.sample_at(65));
+}
+
+#[test]
+fn test_gap() {
+    let mut aligner = StreamAligner::<1, 16>::new(500.sps(), 50.sps(), Interpolation::Linear);
+    for k in 0..10 {
+        aligner.push([k as f32]);
+    }
+
+    aligner.notify_gap(5);
+    assert_eq!(15, aligner.len());
+    assert_eq!(None, aligner.sample_at(95));
+    assert_eq!(None, aligner.sample_at(200));
+
+    // Values are not interpolated across the gap
+    aligner.push([15.0]);
+    aligner.push([16.0]);
+    assert_eq!(Some([15.5]), aligner.sample_at(155));
+    assert_eq!(None, aligner.sample_at(145));
+
+    aligner.clear();
+    assert!(aligner.is_empty());
+    assert_eq!(None, aligner.sample_at(0));
+}
+
+#[test]
+fn test_motion_at_detections() {
+    let samples = common::samples();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps())
+        .with_config(Config::new().with_input_mode(qrs_detector::config::InputMode::AbsDiff));
+    // Motion during the second half of the record, sampled at 32 sps
+    let mut accelerometer =
+        StreamAligner::<1, 32>::new(720.sps(), 32.sps(), Interpolation::Nearest);
+    let half = samples.len() as u32 / 2;
+
+    let mut beats = Vec::new();
+    for (idx, &sample) in samples.iter().enumerate() {
+        let idx = idx as u32;
+        // The accelerometer sample taken at this ECG sample
+        if (idx * 32) % 720 < 32 {
+            let moving = (idx * 32 / 720) >= half * 32 / 720;
+            accelerometer.push([if moving { 1.0 } else { 0.0 }]);
+        }
+        if let Some(beat) = detector.update(sample) {
+            let [motion] = accelerometer.sample_at(beat).unwrap();
+            beats.push((beat, motion));
+        }
+    }
+
+    assert!(beats.len() > 20);
+    for (beat, motion) in beats {
+        if beat < half - 720 / 32 {
+            assert_eq!(0.0, motion, "{beat}");
+        } else if beat > half + 720 / 32 {
+            assert_eq!(1.0, motion, "{beat}");
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use qrs_detector::prelude::*;
+use qrs_detector::synth::{SynthParams, Synthesizer};
+use qrs_detector::validation::evaluate;
+
+const FS: f32 = 250.0;
+
+/// Returns the samples and the indices of the R peaks.
+fn generate(params: SynthParams, seed: u32, seconds: usize) -> (Vec<f32>, Vec<u32>) {
+    let mut samples = Vec::new();
+    let mut peaks = Vec::new();
+    for (i, sample) in Synthesizer::new(FS.sps(), params, seed)
+        .take(seconds * FS as usize)
+        .enumerate()
+    {
+        samples.push(sample.value);
+        if sample.r_peak {
+            peaks.push(i as u32);
+        }
+    }
+    (samples, peaks)
+}
+
+fn rr_intervals(peaks: &[u32]) -> Vec<f32> {
+    peaks
+        .windows(2)
+        .map(|w| (w[1] - w[0]) as f32 / FS)
+        .collect()
+}
+
+/// Moving average and slope.
+fn detect(samples: &[f32]) -> Vec<u32> {
+    let mut window = [0.0; 4];
+    let mut averages = [0.0; 2];
+    let mut detector = QrsDetector::new::<75, 12>(FS.sps()).with_transform(move |sample| {
+        window = [window[1], window[2], window[3], sample];
+        let average = window.iter().sum::<f32>() / 4.0;
+        let slope = (averages[0] - average).abs();
+        averages = [averages[1], average];
+        slope
+    });
+    samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect()
+}
+
+#[test]
+fn test_generation_is_deterministic() {
+    for params in [
+        SynthParams::REST,
+        SynthParams::EXERCISE,
+        SynthParams::ATRIAL_FIBRILLATION,
+        SynthParams::NOISY,
+    ] {
+        assert_eq!(generate(params, 7, 10), generate(params, 7, 10));
+        assert_ne!(generate(params, 7, 10), generate(params, 8, 10));
+    }
+}
+
+#[test]
+fn test_rest_preset() {
+    let (_, peaks) = generate(SynthParams::REST, 1, 60);
+    assert!((58..=62).contains(&peaks.len()), "{} beats", peaks.len());
+    for rr in rr_intervals(&peaks) {
+        assert!((0.96..=1.04).contains(&rr), "{rr}");
+    }
+}
+
+#[test]
+fn test_exercise_preset_ramps_heart_rate() {
+    let (_, peaks) = generate(SynthParams::EXERCISE, 1, 150);
+    let rr = rr_intervals(&peaks);
+
+    let first = rr[..10].iter().sum::<f32>() / 10.0;
+    let last = rr[rr.len() - 10..].iter().sum::<f32>() / 10.0;
+    assert!((60.0 / first - 90.0).abs() < 5.0, "{}", 60.0 / first);
+    assert!((60.0 / last - 170.0).abs() < 5.0, "{}", 60.0 / last);
+}
+
+#[test]
+fn test_atrial_fibrillation_preset_is_irregular() {
+    let (_, peaks) = generate(SynthParams::ATRIAL_FIBRILLATION, 1, 60);
+    let rr = rr_intervals(&peaks);
+    let irregular = rr
+        .windows(2)
+        .filter(|w| (w[1] - w[0]).abs() > 0.15 * w[0])
+        .count();
+    assert!(irregular > rr.len() / 3, "{irregular} of {}", rr.len());
+}
+
+#[test]
+fn test_detector_on_synthetic_presets() {
+    // The beats of the learning period are not evaluated
+    let learning = 3 * FS as u32;
+    for params in [
+        SynthParams::REST,
+        SynthParams::EXERCISE,
+        SynthParams::ATRIAL_FIBRILLATION,
+        SynthParams::NOISY,
+    ] {
+        let (samples, peaks) = generate(params, 1, 60);
+        let peaks = peaks
+            .into_iter()
+            .filter(|&i| i > learning)
+            .collect::<Vec<_>>();
+        let detections = detect(&samples).into_iter().filter(|&i| i > learning);
+        let metrics = evaluate(FS.sps(), &peaks, detections);
+        assert_eq!(0, metrics.false_positives, "{params:?}: {metrics:?}");
+        assert_eq!(0, metrics.false_negatives, "{params:?}: {metrics:?}");
+    }
+}
@@ -0,0 +1,115 @@
+#![cfg(all(feature = "hrv", feature = "std"))]
+
+use qrs_detector::alarms::{AlarmManager, Priority};
+use qrs_detector::events::Event;
+use qrs_detector::prelude::*;
+use qrs_detector::telemetry::{
+    FrameFull, TelemetryDecoder, TelemetryEncoder, TelemetryError, TelemetryItem, TELEMETRY_VERSION,
+};
+
+#[test]
+fn test_roundtrip() {
+    let mut alarms = AlarmManager::new();
+    alarms.raise(Event::Tachycardia, 1_000);
+
+    let items = [
+        TelemetryItem::Beat {
+            offset_ms: 120,
+            rr_ms: None,
+            confidence: 1.0,
+        },
+        TelemetryItem::Beat {
+            offset_ms: 930,
+            rr_ms: Some(810),
+            confidence: 0.0,
+        },
+        TelemetryItem::HeartRate(Some(74.2)),
+        TelemetryItem::HeartRate(None),
+        TelemetryItem::Quality { flags: 0b101 },
+        TelemetryItem::alarm(&alarms.highest().unwrap()),
+        TelemetryItem::AlgorithmVersion(QrsDetector::new::<75, 12>(250.sps()).algorithm_version()),
+    ];
+
+    let mut encoder = TelemetryEncoder::new();
+    encoder.start(3_600_000);
+    for item in &items {
+        encoder.push(item).unwrap();
+    }
+    let frame = encoder.finish();
+    assert_eq!(TELEMETRY_VERSION, frame.as_bytes()[0]);
+    assert_eq!(1, encoder.sequence());
+
+    let mut decoder = TelemetryDecoder::new();
+    let decoded = decoder.decode(frame.as_bytes()).unwrap();
+    assert_eq!(0, decoded.sequence);
+    assert_eq!(3_600_000, decoded.time_ms);
+    assert_eq!(items.len(), decoded.items.len());
+    assert_eq!(items[..2], decoded.items[..2]);
+    match decoded.items[2] {
+        TelemetryItem::HeartRate(Some(bpm)) => assert!((bpm - 74.2).abs() < 0.05, "{bpm}"),
+        item => panic!("{item:?}"),
+    }
+    assert_eq!(items[3..], decoded.items[3..]);
+    assert_eq!(
+        TelemetryItem::Alarm {
+            event: Event::Tachycardia,
+            priority: Priority::Medium,
+            audible: true,
+        },
+        decoded.items[5]
+    );
+}
+
+#[test]
+fn test_frame_size_limit() {
+    let mut encoder = TelemetryEncoder::new().with_max_size(24);
+    let beat = TelemetryItem::Beat {
+        offset_ms: 0,
+        rr_ms: Some(800),
+        confidence: 0.5,
+    };
+
+    // Header, two beats and the checksum
+    assert!(encoder.is_empty());
+    assert_eq!(Ok(()), encoder.push(&beat));
+    assert_eq!(Ok(()), encoder.push(&beat));
+    assert_eq!(Err(FrameFull), encoder.push(&beat));
+    assert_eq!(Ok(()), encoder.push(&TelemetryItem::Quality { flags: 0 }));
+
+    let frame = encoder.finish();
+    assert_eq!(24, frame.as_bytes().len());
+    assert!(encoder.is_empty());
+}
+
+#[test]
+fn test_decode_errors_and_lost_frames() {
+    let mut encoder = TelemetryEncoder::new();
+    let frames = (0..5)
+        .map(|i| {
+            encoder.start(i * 10_000);
+            encoder.push(&TelemetryItem::HeartRate(Some(60.0))).unwrap();
+            encoder.finish().as_bytes().to_vec()
+        })
+        .collect::<Vec<_>>();
+
+    let mut decoder = TelemetryDecoder::new();
+
+    // Damaged frames
+    let mut corrupted = frames[0].clone();
+    corrupted[4] ^= 0x10;
+    assert_eq!(Err(TelemetryError::Checksum), decoder.decode(&corrupted));
+    assert_eq!(
+        Err(TelemetryError::Truncated),
+        decoder.decode(&frames[0][..frames[0].len() - 1])
+    );
+    assert_eq!(
+        Err(TelemetryError::UnsupportedVersion(0)),
+        decoder.decode(&[0; 16])
+    );
+
+    // Frames 2 and 3 were lost, frame 1 arrived twice
+    for frame in [&frames[0], &frames[1], &frames[1], &frames[4]] {
+        decoder.decode(frame).unwrap();
+    }
+    assert_eq!(2, decoder.lost_frames());
+}
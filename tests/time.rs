@@ -0,0 +1,71 @@
+use qrs_detector::prelude::*;
+use qrs_detector::time::{ClockSource, EpochAnchor, SampleClock};
+
+const EPOCH: u64 = 1_700_000_000_000;
+
+#[test]
+fn test_index_wraparound() {
+    let mut anchor = EpochAnchor::new(1000.sps(), EPOCH);
+    anchor.reset_at(u32::MAX - 999, EPOCH);
+
+    assert_eq!(EPOCH + 500, anchor.to_epoch_ms(u32::MAX - 499));
+    // The index wrapped around
+    assert_eq!(EPOCH + 1000, anchor.to_epoch_ms(0));
+    assert_eq!(EPOCH + 2000, anchor.to_epoch_ms(1000));
+}
+
+#[test]
+fn test_fractional_sampling_frequency() {
+    let anchor = EpochAnchor::new(360.sps(), EPOCH);
+
+    // 1 sample is 2.78 ms
+    assert_eq!(EPOCH + 3, anchor.to_epoch_ms(1));
+    // No rounding error accumulates over a day
+    assert_eq!(EPOCH + 86_400_000, anchor.to_epoch_ms(360 * 86_400));
+}
+
+#[test]
+fn test_detections_across_clear() {
+    let mut detector = QrsDetector::new::<75, 12>(250.sps());
+    let mut anchor = EpochAnchor::new(250.sps(), EPOCH);
+
+    let mut times = Vec::new();
+    for i in 0..10_000 {
+        if i == 5000 {
+            // Acquisition restarted after a 10 s gap. Sample indices continue after a reset.
+            detector.clear();
+            anchor.reset_at(i, EPOCH + 30_000);
+        }
+        if let Some(idx) = detector.update(if i % 250 < 5 { 1.0 } else { 0.0 }) {
+            times.push(anchor.to_epoch_ms(idx));
+        }
+    }
+
+    let (before, after): (Vec<u64>, Vec<u64>) = times.iter().partition(|&&t| t < EPOCH + 30_000);
+    assert!(!before.is_empty() && !after.is_empty());
+    assert!(before.iter().all(|t| (t - EPOCH).is_multiple_of(1000)));
+    assert!(after
+        .iter()
+        .all(|t| (t - EPOCH - 30_000).is_multiple_of(1000)));
+}
+
+#[test]
+fn test_sample_clock() {
+    let mut clock = SampleClock::new(360.sps());
+    for _ in 0..720 {
+        clock.on_sample();
+    }
+
+    assert_eq!(720, clock.now());
+    assert_eq!(2000, clock.ticks_to_ms(clock.now()));
+    assert_eq!(
+        360,
+        clock.duration_to_ticks(core::time::Duration::from_secs(1))
+    );
+
+    // Sample 100 was processed at the current time of the clock
+    let mut anchor = EpochAnchor::new(360.sps(), 0);
+    anchor.reset_at_clock(100, &clock, EPOCH);
+    assert_eq!((100, EPOCH + 2000), anchor.anchor());
+    assert_eq!(EPOCH + 3000, anchor.to_epoch_ms(460));
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+use qrs_detector::timeline::Timeline;
+
+#[test]
+fn test_timeline_epochs() {
+    let mut timeline = Timeline::new();
+    timeline.reset();
+    assert_eq!(0, timeline.epoch());
+
+    for _ in 0..100 {
+        timeline.advance();
+    }
+    timeline.skip(50);
+    assert_eq!(150, timeline.position());
+    assert_eq!(50, timeline.gap_samples());
+
+    timeline.reset();
+    assert_eq!(1, timeline.epoch());
+    assert_eq!(150, timeline.origin());
+    assert_eq!(0, timeline.elapsed());
+
+    for _ in 0..10 {
+        timeline.advance();
+    }
+    assert_eq!(155, timeline.to_absolute(5));
+    assert_eq!(Some(5), timeline.to_relative(155));
+    assert_eq!(None, timeline.to_relative(149));
+
+    timeline.rewind(20, 2);
+    assert_eq!(152, timeline.position());
+
+    // The counters wrap around
+    timeline.restart_at(u32::MAX - 1, 3);
+    assert_eq!(5, timeline.elapsed());
+    assert_eq!(Some(4), timeline.to_relative(2));
+}
+
+#[test]
+fn test_reset_continues_absolute_indices() {
+    let signal = preprocessed();
+    let (before, after) = signal.split_at(signal.len() / 2);
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut detections = before
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect::<Vec<_>>();
+    let reset_at = detections.len();
+    detector.clear();
+    detections.extend(after.iter().filter_map(|&sample| detector.update(sample)));
+    assert_eq!(1, detector.timeline().epoch());
+    assert_eq!(signal.len() as u32, detector.timeline().position());
+    assert_eq!(after.len() as u32, detector.timeline().elapsed());
+    assert!(detections.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // A reset detector behaves like a new one that starts after a gap
+    let mut resumed = QrsDetector::new::<216, 36>(720.sps());
+    resumed.notify_gap(before.len() as u32);
+    let resumed_detections = after
+        .iter()
+        .filter_map(|&sample| resumed.update(sample))
+        .collect::<Vec<_>>();
+    assert_eq!(detections[reset_at..], resumed_detections[..]);
+    assert_eq!(0, resumed.timeline().epoch());
+    assert_eq!(before.len() as u32, resumed.timeline().gap_samples());
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use common::samples;
+use qrs_detector::config::{DifferenceLag, InputMode};
+use qrs_detector::prelude::*;
+use qrs_detector::transform::{
+    DcBlocker, Difference, Rectification, SampleTransform, MAX_DIFFERENCE_LAG,
+};
+
+/// Calculates `|x[n] - x[n-2]|`, outputs 0 until enough samples have been received.
+#[derive(Default)]
+struct Slope {
+    prev: [Option<f32>; 2],
+}
+
+impl SampleTransform for Slope {
+    fn transform(&mut self, sample: f32) -> f32 {
+        let prev2 = self.prev[0];
+        self.prev = [self.prev[1], Some(sample)];
+        prev2.map(|p2| (p2 - sample).abs()).unwrap_or(0.0)
+    }
+
+    fn clear(&mut self) {
+        self.prev = [None; 2];
+    }
+}
+
+#[test]
+fn test_transform_matches_external_preprocessing() {
+    let samples = samples();
+
+    let mut external = QrsDetector::new::<216, 36>(720.sps());
+    let mut expected = Vec::new();
+    // The transform outputs two zeros before the first difference
+    external.update(0.0);
+    external.update(0.0);
+    for window in samples.windows(3) {
+        if let Some(idx) = external.update((window[0] - window[2]).abs()) {
+            expected.push(idx);
+        }
+    }
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps()).with_transform(Slope::default());
+    let detections = samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect::<Vec<_>>();
+
+    assert!(!detections.is_empty());
+    assert_eq!(expected, detections);
+
+    // Clearing the detector resets the transform, too
+    detector.clear();
+    let repeated = samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .map(|idx| idx - samples.len() as u32)
+        .collect::<Vec<_>>();
+
+    assert_eq!(expected, repeated);
+}
+
+#[test]
+fn test_closure_transform() {
+    let samples = samples();
+
+    let mut inverted = QrsDetector::new::<216, 36>(720.sps()).with_transform(|s: f32| -s);
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+
+    for window in samples.windows(3) {
+        let slope = (window[0] - window[2]).abs();
+        assert_eq!(detector.update(slope), inverted.update(-slope));
+    }
+}
+
+#[test]
+fn test_dc_blocker_removes_adc_offset() {
+    // 12 bit ADC codes around the mid-scale offset
+    let samples = samples()
+        .iter()
+        .map(|sample| (sample * 500.0).round())
+        .collect::<Vec<_>>();
+    let offset = 2048.0;
+
+    let mut dc_blocker = DcBlocker::new(720.sps(), 0.5);
+    let mut external = QrsDetector::new::<216, 36>(720.sps()).with_transform(Slope::default());
+    let expected = samples
+        .iter()
+        .filter_map(|&sample| external.update(dc_blocker.transform(sample)))
+        .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+
+    // The DC blocker is applied before the transform
+    let mut detector = QrsDetector::new::<216, 36>(720.sps())
+        .with_transform(Slope::default())
+        .with_config(Config::new().with_dc_blocker(0.5));
+    let detections = samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample + offset))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, detections);
+
+    // The filter does not start with a step
+    let mut dc_blocker = DcBlocker::new(720.sps(), 0.5);
+    assert_eq!(0.0, dc_blocker.transform(offset));
+    dc_blocker.transform(offset + 100.0);
+    dc_blocker.clear();
+    assert_eq!(0.0, dc_blocker.transform(offset + 100.0));
+}
+
+#[test]
+fn test_dc_blocker_settles() {
+    let mut dc_blocker = DcBlocker::new(500.sps(), 0.5);
+    dc_blocker.transform(0.0);
+
+    // A DC step decays to 1/e in about 1 / (2 * pi * 0.5) seconds
+    let step = (0..160).map(|_| dc_blocker.transform(1.0)).last().unwrap();
+    assert!((step - (-1.0f32).exp()).abs() < 0.05, "{step}");
+    let step = (0..1000).map(|_| dc_blocker.transform(1.0)).last().unwrap();
+    assert!(step.abs() < 0.01, "{step}");
+}
+
+#[test]
+fn test_input_mode_matches_external_preprocessing() {
+    let samples = samples();
+
+    let mut external = QrsDetector::new::<216, 36>(720.sps()).with_transform(Slope::default());
+    let expected = samples
+        .iter()
+        .filter_map(|&sample| external.update(sample))
+        .collect::<Vec<_>>();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps())
+        .with_config(Config::new().with_input_mode(InputMode::AbsDiff));
+    assert_eq!(1.0, detector.total_group_delay_samples());
+    let detections = samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect::<Vec<_>>();
+
+    // The reported indices are corrected by the delay of the difference
+    assert_eq!(
+        expected.iter().map(|idx| idx - 1).collect::<Vec<_>>(),
+        detections
+    );
+}
+
+/// The moving average of the integration tests.
+fn average() -> impl SampleTransform {
+    let mut window = [0.0; 4];
+    let mut idx = 0;
+    move |sample: f32| {
+        window[idx % 4] = sample;
+        idx += 1;
+        window.iter().sum::<f32>() / 4.0
+    }
+}
+
+#[test]
+fn test_input_modes_detect_the_same_beats() {
+    let samples = samples();
+
+    let mut abs = QrsDetector::new::<216, 36>(720.sps())
+        .with_transform(average())
+        .with_config(Config::new().with_input_mode(InputMode::AbsDiff));
+    let mut squared = QrsDetector::new::<216, 36>(720.sps())
+        .with_transform(average())
+        .with_config(Config::new().with_input_mode(InputMode::SquaredDiff));
+
+    let mut abs_detections = Vec::new();
+    let mut squared_detections = Vec::new();
+    for &sample in samples.iter() {
+        abs_detections.extend(abs.update(sample));
+        squared_detections.extend(squared.update(sample));
+    }
+
+    // Same as the integration test
+    assert_eq!(38, abs_detections.len());
+    assert_eq!(abs_detections.len(), squared_detections.len());
+    // Squaring changes the threshold crossings of low amplitude beats, by less than 25ms
+    for (abs, squared) in abs_detections.iter().zip(squared_detections.iter()) {
+        assert!(abs.abs_diff(*squared) <= 18, "{abs} != {squared}");
+    }
+}
+
+#[test]
+fn test_difference() {
+    let mut difference = Difference::new(Rectification::Square);
+    let output = [1.0, 2.0, 4.0, 3.0].map(|sample| difference.transform(sample));
+    assert_eq!([0.0, 0.0, 9.0, 1.0], output);
+
+    difference.clear();
+    assert_eq!(0.0, difference.transform(10.0));
+}
+
+#[test]
+fn test_difference_lag() {
+    for lag in 1..=MAX_DIFFERENCE_LAG {
+        let mut difference = Difference::new(Rectification::Abs).with_lag(lag);
+        assert_eq!(lag as f32 / 2.0, difference.group_delay());
+
+        // A ramp rising by 1 per sample
+        let output = (0..8)
+            .map(|sample| difference.transform(sample as f32))
+            .collect::<Vec<_>>();
+        assert!(output[..lag].iter().all(|&sample| sample == 0.0));
+        assert!(output[lag..].iter().all(|&sample| sample == lag as f32));
+    }
+
+    assert_eq!(1, Difference::lag_for(250.sps()));
+    assert_eq!(1, Difference::lag_for(360.sps()));
+    assert_eq!(2, Difference::lag_for(500.sps()));
+    assert_eq!(2, Difference::lag_for(720.sps()));
+    assert_eq!(4, Difference::lag_for(1000.sps()));
+    assert_eq!(4, Difference::lag_for(2000.sps()));
+}
+
+#[test]
+#[should_panic(expected = "The lag must be between 1 and 4 samples")]
+fn test_difference_lag_out_of_range() {
+    let _ = Difference::new(Rectification::Abs).with_lag(5);
+}
+
+#[test]
+fn test_scaled_difference_lag() {
+    let config = Config::new()
+        .with_input_mode(InputMode::AbsDiff)
+        .with_difference_lag(DifferenceLag::Scaled);
+
+    let detector = QrsDetector::new::<300, 50>(1000.sps()).with_config(config);
+    assert_eq!(2.0, detector.total_group_delay_samples());
+
+    let detector = QrsDetector::new::<75, 12>(250.sps()).with_config(config);
+    assert_eq!(0.5, detector.total_group_delay_samples());
+
+    // The scaled lag detects the same beats as the lag of the integration test at 720 sps
+    let samples = samples();
+    let mut fixed = QrsDetector::new::<216, 36>(720.sps())
+        .with_transform(average())
+        .with_config(config.with_difference_lag(DifferenceLag::Samples(2)));
+    let mut scaled = QrsDetector::new::<216, 36>(720.sps())
+        .with_transform(average())
+        .with_config(config);
+    for &sample in samples.iter() {
+        assert_eq!(fixed.update(sample), scaled.update(sample));
+    }
+}
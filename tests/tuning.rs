@@ -0,0 +1,72 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::preprocessed;
+use qrs_detector::config::Config;
+use qrs_detector::prelude::*;
+use qrs_detector::tuning::{Grid, Record, Tuner};
+
+fn detect(samples: &[f32], config: Config) -> Vec<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps()).with_config(config);
+    samples
+        .iter()
+        .filter_map(|&sample| detector.update(sample))
+        .collect()
+}
+
+#[test]
+fn test_grid_contains_every_combination() {
+    let grid = Grid::new(Config::new());
+    assert_eq!(1, grid.size());
+    assert_eq!(vec![Config::new()], grid.configs().collect::<Vec<_>>());
+
+    let grid = Grid::new(Config::new())
+        .with_mm_depths(&[3, 8])
+        .with_r_floors(&[0.1, 0.2, 0.3]);
+    let configs = grid.configs().collect::<Vec<_>>();
+    assert_eq!(6, grid.size());
+    assert_eq!(6, configs.len());
+
+    // The last parameter varies fastest
+    assert_eq!(Config::new().with_mm_depth(3).with_r_floor(0.1), configs[0]);
+    assert_eq!(Config::new().with_mm_depth(3).with_r_floor(0.2), configs[1]);
+    assert_eq!(Config::new().with_mm_depth(8).with_r_floor(0.3), configs[5]);
+}
+
+#[test]
+fn test_tuning_finds_the_best_configuration() {
+    let signal = preprocessed();
+    let reference = detect(&signal, Config::new());
+    assert_eq!(38, reference.len());
+
+    // Without the R delay, the threshold drops right after the beats and T waves are detected
+    let bad = Config::new().with_r_delay(0.0).with_r_floor(1.0);
+    let records = [Record::new(720.sps(), &signal, &reference)];
+    let tuner = Tuner::new(&records);
+    assert!(tuner.evaluate(bad).false_positives > 0);
+
+    let grid = Grid::new(bad)
+        .with_r_delays(&[0.0, 2.0 / 3.0])
+        .with_r_floors(&[1.0, 0.5]);
+    let candidates = tuner.sweep(&grid);
+    assert_eq!(4, candidates.len());
+    for (candidate, config) in candidates.iter().zip(grid.configs()) {
+        assert_eq!(config, candidate.config);
+        assert_eq!(tuner.evaluate(config), candidate.metrics);
+    }
+
+    let best = tuner.tune(&grid);
+    assert_eq!(0, best.errors());
+    assert_eq!(38, best.metrics.true_positives);
+
+    // The results do not depend on the number of threads
+    assert_eq!(
+        candidates,
+        Tuner::new(&records).with_threads(1).sweep(&grid)
+    );
+    assert_eq!(
+        candidates,
+        Tuner::new(&records).with_threads(3).sweep(&grid)
+    );
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "ufmt")]
+
+use core::convert::Infallible;
+
+#[cfg(feature = "hrv")]
+use qrs_detector::summary::PeriodSummary;
+use qrs_detector::{Detection, SuppressedCounts, Thresholds};
+use ufmt::{uDebug, uDisplay, uWrite};
+
+struct Output(String);
+
+impl uWrite for Output {
+    type Error = Infallible;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Infallible> {
+        self.0.push_str(s);
+        Ok(())
+    }
+}
+
+fn udisplay(value: &impl uDisplay) -> String {
+    let mut output = Output(String::new());
+    ufmt::uwrite!(output, "{}", value).unwrap();
+    output.0
+}
+
+fn udebug(value: &impl uDebug) -> String {
+    let mut output = Output(String::new());
+    ufmt::uwrite!(output, "{:?}", value).unwrap();
+    output.0
+}
+
+#[test]
+fn test_udisplay_matches_display() {
+    let values = [
+        0.0,
+        0.75,
+        -0.125,
+        -0.0004,
+        0.0004,
+        0.0625,
+        0.1875,
+        0.9996,
+        123.456,
+        999_999.9,
+        1.5e7,
+        -9.9996e12,
+        f32::MAX,
+        f32::NAN,
+        f32::INFINITY,
+        f32::NEG_INFINITY,
+    ];
+
+    for value in values {
+        let detection = Detection {
+            index: 1234,
+            amplitude: value,
+            threshold: 0.3,
+            paced: value > 1.0,
+            ..Default::default()
+        };
+        assert_eq!(format!("{detection}"), udisplay(&detection));
+
+        let thresholds = Thresholds {
+            m: Some(value),
+            f: None,
+            r: -value,
+        };
+        assert_eq!(format!("{thresholds}"), udisplay(&thresholds));
+    }
+
+    let counts = SuppressedCounts {
+        refractory: 3,
+        signal_step: 1,
+        noise: 0,
+    };
+    assert_eq!(format!("{counts}"), udisplay(&counts));
+}
+
+#[cfg(feature = "hrv")]
+#[test]
+fn test_udisplay_summary_matches_display() {
+    let summary = PeriodSummary {
+        beats: 3600,
+        min_bpm: 52,
+        avg_bpm: 60,
+        max_bpm: 75,
+        pauses: 1,
+        af_burden_percent: 2,
+        noise_percent: 5,
+    };
+    assert_eq!(format!("{summary}"), udisplay(&summary));
+}
+
+#[test]
+fn test_udebug() {
+    let thresholds = Thresholds {
+        m: Some(0.5),
+        f: None,
+        r: 0.0,
+    };
+
+    assert_eq!(
+        "Thresholds { m: Some(0.500), f: None, r: 0.000 }",
+        udebug(&thresholds)
+    );
+}
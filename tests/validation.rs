@@ -0,0 +1,333 @@
+mod common;
+
+use common::samples;
+use qrs_detector::annotation::BeatClass;
+use qrs_detector::config::Config;
+use qrs_detector::prelude::*;
+use qrs_detector::sampling::SamplingFrequency;
+use qrs_detector::validation::{
+    evaluate, evaluate_by_class, BeatCounts, BeatMatcher, LatencyStats, Match, Metrics, NoiseStress,
+};
+
+/// Moving average and slope, see the other tests.
+fn detector() -> impl FnMut(f32) -> Option<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut window = [0.0; 4];
+    let mut averages = [0.0; 2];
+    move |sample| {
+        window = [window[1], window[2], window[3], sample];
+        let average = window.iter().sum::<f32>() / 4.0;
+        let slope = (averages[0] - average).abs();
+        averages = [averages[1], average];
+        detector.update(slope)
+    }
+}
+
+/// Low-pass filtered xorshift noise with an offset, resembling electrode motion artifacts.
+fn noise_record() -> Vec<f32> {
+    let mut seed = 12345u32;
+    let mut filtered = 0.0;
+    (0..10 * 720)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let white = (seed % 100_000) as f32 / 100_000.0 - 0.5;
+            filtered += (white - filtered) * 0.1;
+            0.3 + filtered
+        })
+        .collect()
+}
+
+#[test]
+fn test_beat_matching() {
+    let reference = [100, 500, 900, 1300];
+    let mut matcher = BeatMatcher::new(720.sps(), &reference);
+
+    assert_eq!(Match::TruePositive { reference: 100 }, matcher.push(0));
+    assert_eq!(Match::TruePositive { reference: 500 }, matcher.push(480));
+    // The beat has already been detected
+    assert_eq!(Match::FalsePositive, matcher.push(520));
+    assert_eq!(Match::FalsePositive, matcher.push(1100));
+    assert_eq!(
+        Metrics {
+            true_positives: 2,
+            false_positives: 2,
+            false_negatives: 1,
+        },
+        matcher.metrics()
+    );
+
+    let metrics = matcher.finish();
+    assert_eq!(2, metrics.false_negatives);
+    assert_eq!(0.5, metrics.sensitivity());
+    assert_eq!(0.5, metrics.positive_predictivity());
+
+    assert_eq!(
+        metrics,
+        evaluate(720.sps(), &reference, [0, 480, 520, 1100])
+    );
+    assert_eq!(
+        metrics + metrics,
+        Metrics {
+            true_positives: 4,
+            false_positives: 4,
+            false_negatives: 4,
+        }
+    );
+}
+
+#[test]
+fn test_breakdown_by_beat_class() {
+    let reference = [100, 500, 900, 1300, 1700];
+    let classes = "NV/LE"
+        .chars()
+        .map(|symbol| BeatClass::from_wfdb_symbol(symbol).unwrap());
+    let classes = classes.collect::<Vec<_>>();
+
+    let (metrics, breakdown) =
+        evaluate_by_class(720.sps(), &reference, &classes, [100, 900, 1300, 2500]);
+    assert_eq!(3, metrics.true_positives);
+    assert_eq!(2, metrics.false_negatives);
+
+    assert_eq!(
+        BeatCounts {
+            detected: 2,
+            total: 2,
+        },
+        breakdown.normal
+    );
+    assert_eq!(
+        BeatCounts {
+            detected: 1,
+            total: 1,
+        },
+        breakdown.paced
+    );
+    assert_eq!(breakdown.normal, breakdown.get(BeatClass::Normal));
+    assert_eq!(0.0, breakdown.ventricular.sensitivity());
+    assert_eq!(2, breakdown.ventricular.total);
+    assert_eq!(BeatCounts::default(), breakdown.supraventricular);
+    assert_eq!(BeatCounts::default(), breakdown.unknown);
+
+    // Without classes, there is no breakdown
+    let mut matcher = BeatMatcher::new(720.sps(), &reference);
+    matcher.push(100);
+    assert_eq!(0, matcher.finish_by_class().1.normal.total);
+
+    let mut total = breakdown;
+    total += breakdown;
+    assert_eq!(4, total.ventricular.total);
+}
+
+#[test]
+fn test_rr_agreement() {
+    let reference = [0, 720, 1440, 2160, 2880];
+    let mut matcher = BeatMatcher::new(720.sps(), &reference);
+    for detection in [7, 722, 1450, 2200, 2880] {
+        matcher.push(detection);
+    }
+
+    let differences = [-5.0f64, 8.0, 30.0, -40.0].map(|samples| samples * 1000.0 / 720.0);
+    let bias = differences.iter().sum::<f64>() / 4.0;
+    let sd = (differences
+        .iter()
+        .map(|d| (d - bias) * (d - bias))
+        .sum::<f64>()
+        / 3.0)
+        .sqrt();
+
+    let rr = matcher.rr_agreement();
+    assert_eq!(4, rr.count());
+    assert!((rr.bias_ms() as f64 - bias).abs() < 1e-3);
+    assert!((rr.standard_deviation_ms() as f64 - sd).abs() < 1e-3);
+
+    let (low, high) = rr.limits_of_agreement_ms();
+    assert!((low as f64 - (bias - 1.96 * sd)).abs() < 1e-3);
+    assert!((high as f64 - (bias + 1.96 * sd)).abs() < 1e-3);
+
+    // Intervals interrupted by false or missed detections are not compared
+    let mut matcher = BeatMatcher::new(720.sps(), &reference);
+    for detection in [0, 300, 720, 1440, 2880] {
+        matcher.push(detection);
+    }
+    assert_eq!(1, matcher.rr_agreement().count());
+    assert_eq!(0.0, matcher.rr_agreement().bias_ms());
+    assert_eq!(5, (rr + matcher.rr_agreement()).count());
+}
+
+#[test]
+fn test_latency_stats() {
+    let reference = [0, 720, 1440, 2160, 2880];
+    let mut matcher = BeatMatcher::new(720.sps(), &reference);
+    for detection in [0, 713, 1433, 2153, 2880] {
+        matcher.push(detection);
+    }
+
+    // 7 samples at 720 sps are 9.72 ms
+    let latency = matcher.latency();
+    assert_eq!(5, latency.count());
+    assert!((latency.mean_ms() - -3.0 * 7.0 / 5.0 * 1000.0 / 720.0).abs() < 1e-3);
+    assert_eq!(-10.0, latency.percentile_ms(0.0));
+    assert_eq!(-10.0, latency.percentile_ms(0.6));
+    // The rank of 3.5 of 5 detections rounds up
+    assert_eq!(0.0, latency.percentile_ms(0.7));
+    assert_eq!(0.0, latency.p95_ms());
+
+    // False positives are not included
+    let mut matcher = BeatMatcher::new(720.sps(), &reference);
+    matcher.push(300);
+    assert_eq!(0, matcher.latency().count());
+    assert_eq!(0.0, matcher.latency().mean_ms());
+
+    let mut other = LatencyStats::new(720.sps());
+    other.push(720, 648);
+    let combined = latency.clone() + other;
+    assert_eq!(6, combined.count());
+    assert_eq!(100.0, combined.percentile_ms(1.0));
+}
+
+/// Detects beats in the moving average of the signal, decimated by `factor`, and matches them
+/// to the highest sample around each beat.
+fn measure_latency<const SAMPLES_300: usize, const SAMPLES_50: usize>(
+    factor: usize,
+    config: Config,
+) -> LatencyStats {
+    let fs = SamplingFrequency::from_sps(720.0 / factor as f32);
+    let raw = samples()
+        .windows(4)
+        .map(|window| window.iter().sum::<f32>() / 4.0)
+        .step_by(factor)
+        .collect::<Vec<_>>();
+
+    let mut detector = QrsDetector::new::<SAMPLES_300, SAMPLES_50>(fs).with_config(config);
+    let mut averages = [0.0; 2];
+    let detections = raw
+        .iter()
+        .filter_map(|&sample| {
+            let slope = (averages[0] - sample).abs();
+            averages = [averages[1], sample];
+            detector.update(slope)
+        })
+        .collect::<Vec<_>>();
+
+    let search = fs.ms_to_samples(50.0) as u32;
+    let reference = detections
+        .iter()
+        .map(|&detection| {
+            let start = detection.saturating_sub(search);
+            let end = (detection + search).min(raw.len() as u32 - 1);
+            (start..=end)
+                .max_by(|&a, &b| raw[a as usize].total_cmp(&raw[b as usize]))
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let mut matcher = BeatMatcher::new(fs, &reference);
+    for detection in detections {
+        matcher.push(detection);
+    }
+    matcher.latency().clone()
+}
+
+#[test]
+fn test_latency_compensation_per_sampling_rate() {
+    fn check(latency: LatencyStats, compensated: LatencyStats, sample_ms: f32) {
+        assert_eq!(38, latency.count());
+        // Beats of this record are detected around 10 ms after the R peak
+        assert!(latency.mean_ms() > 5.0, "{}", latency.mean_ms());
+        assert!(latency.percentile_ms(0.05) <= latency.mean_ms());
+        assert!(latency.mean_ms() <= latency.p95_ms());
+
+        // Compensation moves the detections onto the reference peaks, on average
+        assert_eq!(latency.count(), compensated.count());
+        assert!(
+            compensated.mean_ms().abs() <= sample_ms,
+            "{}",
+            compensated.mean_ms()
+        );
+    }
+
+    let latency = measure_latency::<216, 36>(1, Config::default());
+    let config = Config::default().with_latency_compensation(latency.mean_ms());
+    check(
+        latency,
+        measure_latency::<216, 36>(1, config),
+        1000.0 / 720.0,
+    );
+
+    let latency = measure_latency::<108, 18>(2, Config::default());
+    let config = Config::default().with_latency_compensation(latency.mean_ms());
+    check(
+        latency,
+        measure_latency::<108, 18>(2, config),
+        1000.0 / 360.0,
+    );
+
+    let latency = measure_latency::<72, 12>(3, Config::default());
+    let config = Config::default().with_latency_compensation(latency.mean_ms());
+    check(
+        latency,
+        measure_latency::<72, 12>(3, config),
+        1000.0 / 240.0,
+    );
+}
+
+#[test]
+fn test_noise_is_mixed_at_the_requested_snr() {
+    let signal = samples();
+    let reference = signal
+        .iter()
+        .filter_map({
+            let mut detector = detector();
+            move |&sample| detector(sample)
+        })
+        .collect::<Vec<_>>();
+    let noise = noise_record();
+
+    let stress = NoiseStress::new(720.sps(), &signal, &reference, &noise);
+    assert!(stress.signal_power() > 0.0);
+
+    for snr in [24.0, 6.0, -6.0] {
+        let added = stress
+            .noisy_signal(snr)
+            .zip(signal.iter())
+            .map(|(noisy, clean)| noisy - clean)
+            .collect::<Vec<_>>();
+
+        let mean = added.iter().sum::<f32>() / added.len() as f32;
+        let power = added.iter().map(|x| x * x).sum::<f32>() / added.len() as f32;
+        let measured = 10.0 * (stress.signal_power() / power).log10();
+
+        assert!(mean.abs() < 1e-3, "{mean}");
+        assert!((measured - snr).abs() < 0.1, "{measured} != {snr}");
+    }
+}
+
+#[test]
+fn test_noise_stress_curve() {
+    let signal = samples();
+    let reference = signal
+        .iter()
+        .filter_map({
+            let mut detector = detector();
+            move |&sample| detector(sample)
+        })
+        .collect::<Vec<_>>();
+    let noise = noise_record();
+
+    let stress = NoiseStress::new(720.sps(), &signal, &reference, &noise);
+    let curve = stress
+        .curve(&[24.0, 12.0, 6.0, 0.0], detector)
+        .collect::<Vec<_>>();
+
+    // Every beat is detected with low noise
+    assert_eq!(1.0, curve[0].metrics.sensitivity());
+
+    // The detections degrade with the noise
+    let errors = |metrics: Metrics| metrics.false_positives + metrics.false_negatives;
+    for pair in curve.windows(2) {
+        assert!(errors(pair[0].metrics) <= errors(pair[1].metrics));
+    }
+    assert!(errors(curve[3].metrics) > 0);
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "io")]
+
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+use qrs_detector::vectors::{Difference, TestVector, Tolerance, VectorError, VectorWriter};
+
+const VECTOR: &str = "tests/data/vectors/aami3a_10s.csv";
+
+fn record(samples: &[f32]) -> String {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mut writer = VectorWriter::new(Vec::new(), 720.sps()).unwrap();
+    writer
+        .write_metadata("config", detector.config().describe())
+        .unwrap();
+    for &sample in samples {
+        writer.record(&mut detector, sample).unwrap();
+    }
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+/// Regenerates the checked-in vector. Run with
+/// `cargo test --features io --test vectors -- --ignored` after an intentional change of the
+/// algorithm.
+#[test]
+#[ignore]
+fn regenerate_vectors() {
+    let signal = preprocessed();
+    std::fs::write(VECTOR, record(&signal[..7200])).unwrap();
+}
+
+#[test]
+fn test_detector_matches_vector() {
+    let vector = TestVector::parse(include_str!("./data/vectors/aami3a_10s.csv")).unwrap();
+    assert_eq!(720.sps(), vector.fs);
+    assert_eq!(7200, vector.rows.len());
+    assert_eq!(
+        preprocessed()[..7200],
+        *vector.rows.iter().map(|row| row.sample).collect::<Vec<_>>()
+    );
+    assert!(
+        vector
+            .rows
+            .iter()
+            .filter(|row| row.detection.is_some())
+            .count()
+            >= 5
+    );
+
+    let mut detector = QrsDetector::new::<216, 36>(vector.fs);
+    assert_eq!(Ok(()), vector.verify(&mut detector, Tolerance::EXACT));
+}
+
+#[test]
+fn test_round_trip() {
+    let signal = preprocessed();
+    let text = record(&signal[..3000]);
+    let vector = TestVector::parse(&text).unwrap();
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    for row in &vector.rows {
+        assert_eq!(row.detection, detector.update(row.sample));
+        assert_eq!(row.thresholds, detector.thresholds());
+    }
+}
+
+#[test]
+fn test_reports_first_mismatch() {
+    let signal = preprocessed();
+    let mut vector = TestVector::parse(&record(&signal[..7200])).unwrap();
+
+    let (row, expected) = vector
+        .rows
+        .iter()
+        .enumerate()
+        .find_map(|(idx, row)| row.detection.map(|detection| (idx, detection)))
+        .unwrap();
+    vector.rows[row].detection = Some(expected + 2);
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mismatch = vector.verify(&mut detector, Tolerance::EXACT).unwrap_err();
+    assert_eq!(row, mismatch.row);
+    assert_eq!(Difference::Detection, mismatch.difference);
+    assert_eq!(Some(expected), mismatch.actual.detection);
+
+    // Within tolerance
+    let tolerance = Tolerance {
+        detection_samples: 2,
+        ..Tolerance::EXACT
+    };
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    assert_eq!(Ok(()), vector.verify(&mut detector, tolerance));
+
+    // A slightly different threshold, as a double precision reference would produce
+    let mut vector = TestVector::parse(&record(&signal[..7200])).unwrap();
+    let row = vector.rows.len() - 1;
+    let r = vector.rows[row].thresholds.r;
+    vector.rows[row].thresholds.r = r * (1.0 + 1e-6) + 1e-9;
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    let mismatch = vector.verify(&mut detector, Tolerance::EXACT).unwrap_err();
+    assert_eq!(Difference::R, mismatch.difference);
+
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    assert_eq!(Ok(()), vector.verify(&mut detector, Tolerance::REFERENCE));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        Err(VectorError::MissingHeader),
+        TestVector::parse("sample,m,f,r,detection\n")
+    );
+    assert_eq!(
+        Err(VectorError::UnsupportedVersion(2)),
+        TestVector::parse("# qrs_detector test vector 2\n# fs=720\n")
+    );
+    assert_eq!(
+        Err(VectorError::MissingSamplingFrequency),
+        TestVector::parse("# qrs_detector test vector 1\nsample,m,f,r,detection\n")
+    );
+    assert_eq!(
+        Err(VectorError::InvalidLine(4)),
+        TestVector::parse(
+            "# qrs_detector test vector 1\n# fs=720\nsample,m,f,r,detection\n0.5,,,x,\n"
+        )
+    );
+    assert_eq!(
+        Err(VectorError::InvalidLine(3)),
+        TestVector::parse("# qrs_detector test vector 1\n# fs=720\n0.5,,,0,,\n")
+    );
+
+    // Unknown metadata and CRLF line endings are accepted
+    let vector = TestVector::parse(
+        "# qrs_detector test vector 1\r\n# source=matlab\r\n# fs=250\r\nsample,m,f,r,detection\r\n0.5,0.25,0.125,0,\r\n0.75,0.25,0.125,0.5,1\r\n",
+    )
+    .unwrap();
+    assert_eq!(250.sps(), vector.fs);
+    assert_eq!(2, vector.rows.len());
+    assert_eq!(Some(0.25), vector.rows[1].thresholds.m);
+    assert_eq!(Some(1), vector.rows[1].detection);
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "wasm")]
+
+mod common;
+
+use common::preprocessed;
+use qrs_detector::prelude::*;
+use qrs_detector::wasm::WasmDetector;
+
+#[test]
+fn test_wasm_matches_detector() {
+    let samples = preprocessed();
+
+    let mut detector = QrsDetector::new_alloc(720.sps());
+    let reference = |detector: &mut QrsDetector<_, _>| {
+        samples
+            .iter()
+            .filter_map(|&sample| detector.update(sample))
+            .collect::<Vec<_>>()
+    };
+    let expected = reference(&mut detector);
+    detector.clear();
+    let expected_after_clear = reference(&mut detector);
+    assert!(!expected.is_empty());
+
+    let mut wasm = WasmDetector::new(720.0).unwrap();
+    let detections = samples
+        .iter()
+        .filter_map(|&sample| wasm.update(sample))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, detections);
+
+    wasm.clear();
+    let detections = samples
+        .chunks(100)
+        .flat_map(|chunk| wasm.update_chunk(chunk))
+        .collect::<Vec<_>>();
+    assert_eq!(expected_after_clear, detections);
+}
+
+#[test]
+fn test_wasm_rejects_invalid_sampling_frequency() {
+    assert!(WasmDetector::new(f32::NAN).is_err());
+    assert!(WasmDetector::new(0.0).is_err());
+    assert!(WasmDetector::new(10.0).is_err());
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use std::{cell::Cell, time::Instant};
+
+use common::samples;
+use qrs_detector::prelude::*;
+use qrs_detector::wcet::WcetMeter;
+
+#[test]
+fn test_meter_records_worst_case() {
+    let cycles = Cell::new(u32::MAX - 10);
+    let mut meter = WcetMeter::new(|| cycles.get());
+
+    for cost in [5, 30, 12] {
+        // Crosses the wraparound of the counter
+        meter.measure(|| cycles.set(cycles.get().wrapping_add(cost)));
+    }
+
+    assert_eq!(3, meter.measurements());
+    assert_eq!(30, meter.worst_case());
+    assert_eq!(1, meter.worst_case_index());
+
+    meter.clear();
+    assert_eq!(0, meter.measurements());
+    assert_eq!(0, meter.worst_case());
+}
+
+/// Measures the worst case execution time on the host, in nanoseconds.
+///
+/// Run with `cargo test --release --test wcet -- --nocapture` to see the result.
+#[test]
+fn test_worst_case_on_host() {
+    let samples = samples();
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+
+    let start = Instant::now();
+    let mut meter = WcetMeter::new(|| start.elapsed().as_nanos() as u32);
+
+    for window in samples.windows(3) {
+        let slope = (window[0] - window[2]).abs();
+        meter.measure(|| detector.update(slope));
+    }
+
+    assert_eq!(samples.len() as u32 - 2, meter.measurements());
+    println!(
+        "Worst case: {} ns at sample {}",
+        meter.worst_case(),
+        meter.worst_case_index()
+    );
+}
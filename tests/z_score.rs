@@ -0,0 +1,129 @@
+mod common;
+
+use common::preprocessed;
+use qrs_detector::config::{Parameter, Parameters};
+use qrs_detector::prelude::*;
+use qrs_detector::transform::{SampleTransform, ZScore};
+
+fn detect(config: Config, samples: impl Iterator<Item = f32>) -> Vec<u32> {
+    let mut detector = QrsDetector::new::<216, 36>(720.sps()).with_config(config);
+    samples
+        .filter_map(|sample| detector.update(sample))
+        .collect()
+}
+
+#[test]
+fn test_statistics() {
+    let mut z_score = ZScore::new(100.sps(), 16.0);
+    assert_eq!(None, z_score.mean());
+
+    // Blocks of 1 second
+    for i in 0..99 {
+        assert_eq!(0.0, z_score.transform(i as f32));
+    }
+    assert!(!z_score.is_ready());
+    z_score.transform(99.0);
+    assert!(z_score.is_ready());
+    assert_eq!(Some(49.5), z_score.mean());
+    let std = z_score.std().unwrap();
+    assert!((std - 28.866_07).abs() < 1e-3, "{std}");
+    assert!((z_score.transform(49.5 + std) - 1.0).abs() < 1e-6);
+
+    // The window slides after 16 blocks
+    for _ in 0..17 * 100 {
+        z_score.transform(5.0);
+    }
+    assert_eq!(Some(5.0), z_score.mean());
+    assert_eq!(Some(0.0), z_score.std());
+    assert_eq!(0.0, z_score.transform(5.0));
+    assert_eq!(0.0, z_score.transform(500.0));
+
+    z_score.clear();
+    assert!(!z_score.is_ready());
+}
+
+#[test]
+fn test_amplitude_independence() {
+    let samples = preprocessed();
+    let config = Config::new().with_z_score(60.0);
+
+    let reference = detect(Config::new(), samples.iter().copied());
+    let normalized = detect(config, samples.iter().copied());
+
+    // Every beat after the warmup is detected. The normalized threshold may be crossed up to
+    // 30ms apart
+    let warmup = 720 * 60 / 16;
+    let after_warmup = |detections: &[u32]| {
+        detections
+            .iter()
+            .copied()
+            .filter(|&idx| idx > warmup + 3 * 720)
+            .collect::<Vec<_>>()
+    };
+    let expected = after_warmup(&reference);
+    let detected = after_warmup(&normalized);
+    assert_eq!(expected.len(), detected.len());
+    for (expected, detected) in expected.iter().zip(detected.iter()) {
+        assert!(expected.abs_diff(*detected) <= 20, "{expected} {detected}");
+    }
+    assert!(normalized.iter().all(|&idx| idx > warmup));
+
+    // The same thresholds detect the same beats at very different amplitudes
+    for gain in [1e-3, 50.0, 2e4] {
+        let scaled = detect(config, samples.iter().map(|sample| sample * gain));
+        assert_eq!(normalized, scaled, "{gain}");
+    }
+
+    // Without normalization, the thresholds must adapt to the amplitude
+    let tiny = detect(Config::new(), samples.iter().map(|sample| sample * 1e-3));
+    assert_eq!(reference.len(), tiny.len());
+}
+
+#[test]
+fn test_m_is_initialized_from_normalized_samples() {
+    let samples = preprocessed();
+    let mut detector =
+        QrsDetector::new::<216, 36>(720.sps()).with_config(Config::new().with_z_score(16.0));
+
+    // No thresholds while the first block is collected
+    for &sample in &samples[..720] {
+        assert_eq!(None, detector.update(sample));
+        assert_eq!(None, detector.thresholds().m);
+    }
+    for &sample in &samples[720..4 * 720] {
+        detector.update(sample);
+    }
+
+    // M is in units of standard deviations, independent of the input amplitude
+    let m = detector.thresholds().m.unwrap();
+    assert!((2.0..20.0).contains(&m), "{m}");
+}
+
+#[test]
+fn test_config() {
+    let config = Config::new().with_z_score(30.0);
+    assert_eq!(Some(30.0), config.z_score());
+    assert_eq!(None, Config::new().z_score());
+
+    let description = config.describe();
+    assert!(description.to_string().contains(" z_score=30 "));
+    assert_eq!(Ok(config), Config::from_description(&description));
+    assert_ne!(description.hash(), Config::new().describe().hash());
+    assert_eq!("z_score", Parameter::ZScore.name());
+
+    // Changing the normalization resets the detector
+    let mut detector = QrsDetector::new::<216, 36>(720.sps());
+    for sample in preprocessed().into_iter().take(10 * 720) {
+        detector.update(sample);
+    }
+    let update = detector.apply_config(config);
+    assert_eq!(Parameters::of(&[Parameter::ZScore]), update.changed);
+    assert_eq!(update.changed, update.reinitialized);
+    assert_eq!(None, detector.thresholds().m);
+}
+
+#[test]
+#[should_panic(expected = "The z-score window must be at least 1 second")]
+fn test_invalid_window() {
+    let _ = Config::new().with_z_score(0.5);
+}